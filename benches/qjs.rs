@@ -0,0 +1,126 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use qjs::{Context, Eval, NewValue, Runtime};
+
+fn eval(c: &mut Criterion) {
+    let rt = Runtime::new();
+    let ctxt = Context::new(&rt);
+
+    c.bench_function("eval expression", |b| {
+        b.iter(|| {
+            ctxt.eval_script(black_box("1 + 2 * 3"), "<bench>", Eval::GLOBAL)
+                .unwrap()
+        })
+    });
+
+    c.bench_function("eval loop", |b| {
+        b.iter(|| {
+            ctxt.eval_script(
+                black_box("(function () { let s = 0; for (let i = 0; i < 1000; i++) s += i; return s; })()"),
+                "<bench>",
+                Eval::GLOBAL,
+            )
+            .unwrap()
+        })
+    });
+
+    let func = ctxt
+        .eval_script(
+            "(function (a, b) { return a + b; })",
+            "<bench>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+    c.bench_function("call function", |b| {
+        b.iter(|| func.call(None, (black_box(1), black_box(2))).unwrap())
+    });
+}
+
+fn conversions(c: &mut Criterion) {
+    let rt = Runtime::new();
+    let ctxt = Context::new(&rt);
+    let s = "hello world".repeat(10);
+
+    c.bench_function("i32 to value", |b| {
+        b.iter(|| ctxt.bind(black_box(42).new_value(&ctxt)))
+    });
+
+    c.bench_function("value to i32", |b| {
+        let v = ctxt.bind(42.new_value(&ctxt));
+
+        b.iter(|| black_box(&v).to_int32())
+    });
+
+    c.bench_function("str to value", |b| {
+        b.iter(|| ctxt.bind(black_box(s.as_str()).new_value(&ctxt)))
+    });
+
+    c.bench_function("value to string", |b| {
+        let v = ctxt.bind(s.as_str().new_value(&ctxt));
+
+        b.iter(|| black_box(&v).to_string())
+    });
+}
+
+fn property_access(c: &mut Criterion) {
+    let rt = Runtime::new();
+    let ctxt = Context::new(&rt);
+    let obj = ctxt
+        .eval_script(
+            "({ name: 'qjs', items: [1, 2, 3] })",
+            "<bench>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+    let atom = ctxt.new_atom("name");
+
+    c.bench_function("get property by name", |b| {
+        b.iter(|| obj.get_property(black_box("name")).unwrap())
+    });
+
+    c.bench_function("get property by atom", |b| {
+        b.iter(|| obj.get_property(black_box(&atom)).unwrap())
+    });
+
+    c.bench_function("set property", |b| {
+        b.iter(|| obj.set_property(black_box("count"), black_box(1)).unwrap())
+    });
+
+    let keys = ["name", "items", "count"];
+
+    c.bench_function("get properties one by one", |b| {
+        b.iter(|| {
+            keys.iter()
+                .map(|&key| obj.get_property(black_box(key)))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    c.bench_function("get many properties", |b| {
+        b.iter(|| obj.get_many(black_box(&keys)).unwrap())
+    });
+
+    let props = [("a", 1), ("b", 2), ("c", 3)];
+
+    c.bench_function("set properties one by one", |b| {
+        b.iter(|| {
+            for &(key, value) in black_box(&props) {
+                obj.set_property(key, value).unwrap();
+            }
+        })
+    });
+
+    c.bench_function("set many properties", |b| {
+        b.iter(|| obj.set_many(black_box(&props)).unwrap())
+    });
+
+    let items = obj.get_property("items").unwrap();
+
+    c.bench_function("get element", |b| {
+        b.iter(|| items.get_property(black_box(1u32)).unwrap())
+    });
+}
+
+criterion_group!(benches, eval, conversions, property_access);
+criterion_main!(benches);
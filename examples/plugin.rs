@@ -0,0 +1,42 @@
+//! A plugin host which runs the plugin scripts in a deterministic context.
+//!
+//! It uses neither threads nor the system clock, so it also runs on the `wasm32` targets.
+//!
+//! ```sh
+//! $ cargo run --example plugin
+//! $ cargo build --example plugin --target wasm32-wasi --no-default-features --features bignum
+//! $ wasmtime target/wasm32-wasi/debug/examples/plugin.wasm
+//! ```
+use std::time::{Duration, UNIX_EPOCH};
+
+use failure::Error;
+
+use qjs::{Context, Determinism, Eval, ManualClock, Runtime};
+
+const PLUGIN: &str = r#"
+({
+    name: 'greeter',
+    greet(name) {
+        return `Hello, ${name}! (${new Date().toISOString()})`;
+    },
+})
+"#;
+
+fn main() -> Result<(), Error> {
+    let rt = Runtime::new();
+    let ctxt = Context::new(&rt);
+    let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_500_000_000));
+
+    ctxt.init_determinism(Determinism::new().with_clock(clock))?;
+
+    let plugin = ctxt.eval_script(PLUGIN, "plugin.js", Eval::GLOBAL)?;
+    let name = plugin
+        .get_property("name")
+        .map(|name| name.to_string())
+        .unwrap_or_default();
+    let greeting = plugin.invoke("greet", "world")?;
+
+    println!("{}: {}", name, greeting);
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+/*
+ * The C API of the `qjs` crate, link with the `qjs_capi` static or dynamic library.
+ */
+#ifndef QJS_H
+#define QJS_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JSRuntime JSRuntime;
+typedef struct JSContext JSContext;
+typedef struct qjs_result qjs_result;
+
+/* the arguments are converted to strings, which are valid during the call */
+typedef void qjs_callback(void *user_data, int argc, const char **argv, qjs_result *result);
+
+/* create a new runtime, returns NULL if failed */
+JSRuntime *qjs_runtime_new(void);
+/* free the runtime, all its contexts should be freed before */
+void qjs_runtime_free(JSRuntime *rt);
+
+/* create a new context of the runtime, returns NULL if failed */
+JSContext *qjs_context_new(JSRuntime *rt);
+void qjs_context_free(JSContext *ctx);
+
+/* evaluate a script and run the pending jobs,
+   returns 0 and the result value, or -1 and the error message,
+   the returned string should be freed with `qjs_string_free` */
+int qjs_eval(JSContext *ctx, const char *source, const char *filename, char **result);
+void qjs_string_free(char *s);
+
+/* register a global function which calls the callback with the user data,
+   returns 0 if succeeded, or -1 if failed */
+int qjs_register_callback(JSContext *ctx, const char *name, qjs_callback *callback, void *user_data);
+
+/* set the string value returned by the callback */
+void qjs_result_set_string(qjs_result *result, const char *s);
+/* throw an `Error` with the message after the callback returned */
+void qjs_result_set_error(qjs_result *result, const char *msg);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* QJS_H */
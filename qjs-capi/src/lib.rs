@@ -0,0 +1,397 @@
+//! A stable C API over the `qjs` crate, for the non-Rust hosts to embed it with FFI.
+//!
+//! The handles are the QuickJS `JSRuntime` and `JSContext`, see `include/qjs.h` for the declarations.
+#![allow(non_camel_case_types)]
+
+#[macro_use]
+extern crate log;
+
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::null_mut;
+use std::slice;
+
+use failure::{err_msg, Error};
+use foreign_types::{ForeignType, ForeignTypeRef};
+
+use qjs::{ffi, Context, ContextRef, Eval, NewValue, Prop, Runtime, RuntimeRef, Value};
+
+/// The result of a callback, which is set by `qjs_result_set_string` or `qjs_result_set_error`.
+#[derive(Debug, Default)]
+pub struct qjs_result {
+    value: Option<CString>,
+    error: Option<CString>,
+}
+
+/// The native callback registered by `qjs_register_callback`.
+///
+/// The arguments are converted to strings, which are valid during the call.
+pub type qjs_callback = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        argc: c_int,
+        argv: *const *const c_char,
+        result: *mut qjs_result,
+    ),
+>;
+
+struct Callback {
+    func: unsafe extern "C" fn(*mut c_void, c_int, *const *const c_char, *mut qjs_result),
+    user_data: *mut c_void,
+}
+
+/// Create a new runtime, returns `NULL` if failed.
+#[no_mangle]
+pub extern "C" fn qjs_runtime_new() -> *mut ffi::JSRuntime {
+    panic::catch_unwind(|| into_ptr(Runtime::new())).unwrap_or_else(|_| null_mut())
+}
+
+/// Free the runtime, all its contexts should be freed before.
+///
+/// # Safety
+///
+/// The `rt` must be `NULL` or a runtime created by `qjs_runtime_new`, which is not used after freed.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_runtime_free(rt: *mut ffi::JSRuntime) {
+    if !rt.is_null() {
+        drop(Runtime::from_ptr(rt))
+    }
+}
+
+/// Create a new context of the runtime, returns `NULL` if failed.
+///
+/// # Safety
+///
+/// The `rt` must be `NULL` or a runtime created by `qjs_runtime_new`.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_context_new(rt: *mut ffi::JSRuntime) -> *mut ffi::JSContext {
+    if rt.is_null() {
+        return null_mut();
+    }
+
+    panic::catch_unwind(|| into_ptr(Context::new(RuntimeRef::from_ptr(rt))))
+        .unwrap_or_else(|_| null_mut())
+}
+
+/// Free the context.
+///
+/// # Safety
+///
+/// The `ctx` must be `NULL` or a context created by `qjs_context_new`, which is not used after freed.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_context_free(ctx: *mut ffi::JSContext) {
+    if !ctx.is_null() {
+        drop(Context::from_ptr(ctx))
+    }
+}
+
+/// Evaluate a script, and run the pending jobs.
+///
+/// Returns 0 and the string of the result value if succeeded,
+/// or -1 and the error message if failed.
+/// The returned string should be freed with `qjs_string_free`.
+///
+/// # Safety
+///
+/// The `ctx` must be a context created by `qjs_context_new`,
+/// the `source` and the optional `filename` must be NUL-terminated UTF-8 strings,
+/// and the `result` must be `NULL` or a valid pointer to store the returned string.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_eval(
+    ctx: *mut ffi::JSContext,
+    source: *const c_char,
+    filename: *const c_char,
+    result: *mut *mut c_char,
+) -> c_int {
+    let res = panic::catch_unwind(AssertUnwindSafe(|| eval(ctx, source, filename)))
+        .unwrap_or_else(|_| Err(err_msg("panicked")));
+    let (ret, s) = match res {
+        Ok(s) => (0, s),
+        Err(err) => (-1, err.to_string()),
+    };
+
+    if let Some(result) = result.as_mut() {
+        *result = to_c_string(s).into_raw();
+    }
+
+    ret
+}
+
+unsafe fn eval(
+    ctx: *mut ffi::JSContext,
+    source: *const c_char,
+    filename: *const c_char,
+) -> Result<String, Error> {
+    if ctx.is_null() || source.is_null() {
+        return Err(err_msg("invalid arguments"));
+    }
+
+    let ctxt = ContextRef::from_ptr(ctx);
+    let source = CStr::from_ptr(source).to_str()?;
+    let filename = if filename.is_null() {
+        "<eval>"
+    } else {
+        CStr::from_ptr(filename).to_str()?
+    };
+
+    trace!("eval `{}`", filename);
+
+    let value = ctxt.eval_script(source, filename, Eval::GLOBAL)?;
+
+    while ctxt.runtime().execute_pending_job()?.is_some() {}
+
+    Ok(value.to_string())
+}
+
+/// Free the string returned by `qjs_eval`.
+///
+/// # Safety
+///
+/// The `s` must be `NULL` or a string returned by `qjs_eval`, which is not used after freed.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s))
+    }
+}
+
+/// Register a global function which calls the native callback with the `user_data`.
+///
+/// Returns 0 if succeeded, or -1 if failed.
+///
+/// # Safety
+///
+/// The `ctx` must be a context created by `qjs_context_new`, the `name` must be a NUL-terminated UTF-8 string,
+/// and the `user_data` must be valid whenever the callback is called.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_register_callback(
+    ctx: *mut ffi::JSContext,
+    name: *const c_char,
+    callback: qjs_callback,
+    user_data: *mut c_void,
+) -> c_int {
+    let func = match callback {
+        Some(func) if !ctx.is_null() && !name.is_null() => func,
+        _ => return -1,
+    };
+
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        register_callback(
+            ContextRef::from_ptr(ctx),
+            CStr::from_ptr(name).to_str()?,
+            Callback { func, user_data },
+        )
+    }))
+    .map_or(-1, |res| match res {
+        Ok(()) => 0,
+        Err(err) => {
+            warn!("register callback failed, {}", err);
+
+            -1
+        }
+    })
+}
+
+fn register_callback(ctxt: &ContextRef, name: &str, callback: Callback) -> Result<(), Error> {
+    let func = ctxt.new_c_function_data(callback_stub, 0, 0, ctxt.new_userdata(callback))?;
+
+    ctxt.global_object()
+        .define_property_value(name, func, Prop::WRITABLE | Prop::CONFIGURABLE)?;
+
+    Ok(())
+}
+
+unsafe extern "C" fn callback_stub(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    _magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    // the conversions of the arguments may panic too, which must not unwind into C.
+    panic::catch_unwind(AssertUnwindSafe(|| call_callback(ctxt, argc, argv, data))).unwrap_or_else(
+        |_| {
+            ctxt.throw_internal_error("callback panicked")
+                .into_inner()
+                .raw()
+        },
+    )
+}
+
+unsafe fn call_callback(
+    ctxt: &ContextRef,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let callback = ctxt.get_userdata_unchecked::<Callback>(&Value::from(*data));
+    let callback = callback.as_ref();
+    let args = if argc > 0 {
+        slice::from_raw_parts(argv, argc as usize)
+            .iter()
+            .map(|&arg| ctxt.to_cstring(&Value::from(arg)).unwrap_or_default())
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    let argv = args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+    let mut result = qjs_result::default();
+
+    (callback.func)(
+        callback.user_data,
+        argv.len() as c_int,
+        argv.as_ptr(),
+        &mut result,
+    );
+
+    match result {
+        qjs_result {
+            error: Some(err), ..
+        } => ctxt
+            .throw_error(err.to_string_lossy(), None)
+            .into_inner()
+            .raw(),
+        qjs_result {
+            value: Some(value), ..
+        } => value.to_string_lossy().as_ref().new_value(ctxt),
+        _ => ffi::UNDEFINED,
+    }
+}
+
+/// Set the string value of the callback result.
+///
+/// # Safety
+///
+/// The `result` must be the one passed to the callback, and the `s` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_result_set_string(result: *mut qjs_result, s: *const c_char) {
+    if let Some(result) = result.as_mut() {
+        result.value = if s.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(s).to_owned())
+        };
+    }
+}
+
+/// Throw an `Error` with the message from the callback.
+///
+/// # Safety
+///
+/// The `result` must be the one passed to the callback, and the `msg` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn qjs_result_set_error(result: *mut qjs_result, msg: *const c_char) {
+    if let Some(result) = result.as_mut() {
+        result.error = Some(if msg.is_null() {
+            CString::default()
+        } else {
+            CStr::from_ptr(msg).to_owned()
+        });
+    }
+}
+
+/// Take the ownership of the raw pointer, which will be freed by the `*_free` functions.
+fn into_ptr<T: ForeignType>(v: T) -> *mut T::CType {
+    let ptr = v.as_ptr();
+
+    mem::forget(v);
+
+    ptr
+}
+
+fn to_c_string(s: String) -> CString {
+    CString::new(s).unwrap_or_else(|err| {
+        let pos = err.nul_position();
+        let mut bytes = err.into_vec();
+
+        bytes.truncate(pos);
+
+        CString::new(bytes).unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    unsafe extern "C" fn concat(
+        user_data: *mut c_void,
+        argc: c_int,
+        argv: *const *const c_char,
+        result: *mut qjs_result,
+    ) {
+        let calls = &mut *(user_data as *mut usize);
+
+        *calls += 1;
+
+        if argc == 0 {
+            qjs_result_set_error(result, b"no arguments\0".as_ptr() as *const _);
+        } else {
+            let s = slice::from_raw_parts(argv, argc as usize)
+                .iter()
+                .map(|&arg| CStr::from_ptr(arg).to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("-");
+            let s = CString::new(s).unwrap();
+
+            qjs_result_set_string(result, s.as_ptr());
+        }
+    }
+
+    unsafe fn eval(ctx: *mut ffi::JSContext, source: &str) -> (c_int, String) {
+        let source = CString::new(source).unwrap();
+        let mut result = ptr::null_mut();
+        let ret = qjs_eval(ctx, source.as_ptr(), ptr::null(), &mut result);
+        let s = CStr::from_ptr(result).to_string_lossy().into_owned();
+
+        qjs_string_free(result);
+
+        (ret, s)
+    }
+
+    #[test]
+    fn capi() {
+        unsafe {
+            let rt = qjs_runtime_new();
+            let ctx = qjs_context_new(rt);
+            let mut calls = 0usize;
+
+            assert_eq!(eval(ctx, "1 + 2"), (0, "3".to_owned()));
+            assert_eq!(eval(ctx, "throw new Error('boom')").0, -1);
+            assert!(eval(ctx, "throw new Error('boom')").1.contains("boom"));
+
+            assert_eq!(
+                qjs_register_callback(
+                    ctx,
+                    b"concat\0".as_ptr() as *const _,
+                    Some(concat),
+                    &mut calls as *mut usize as *mut _
+                ),
+                0
+            );
+            assert_eq!(
+                eval(ctx, "concat('a', 1, true)"),
+                (0, "a-1-true".to_owned())
+            );
+            assert_eq!(
+                eval(ctx, "try { concat() } catch (e) { e.message }"),
+                (0, "no arguments".to_owned())
+            );
+            assert_eq!(calls, 2);
+            assert_eq!(
+                qjs_register_callback(ctx, b"f\0".as_ptr() as *const _, None, null_mut()),
+                -1
+            );
+
+            qjs_context_free(ctx);
+            qjs_runtime_free(rt);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! A minimal line editor of the terminal for the interactive mode.
+
+use std::io::{self, Read, Write};
+use std::mem;
+
+use qjs::Repl;
+
+/// The key of the editor, decoded from the terminal input.
+enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Interrupt,
+    Eof,
+    Unknown,
+}
+
+/// Switch the terminal to the raw mode until dropped.
+struct RawMode(libc::termios);
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        unsafe {
+            let mut termios = mem::zeroed();
+
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let saved = termios;
+
+            termios.c_iflag &=
+                !(libc::BRKINT | libc::ICRNL | libc::INPCK | libc::ISTRIP | libc::IXON);
+            termios.c_lflag &= !(libc::ECHO | libc::ICANON | libc::IEXTEN | libc::ISIG);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &termios) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(RawMode(saved))
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.0);
+        }
+    }
+}
+
+pub fn is_terminal() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO) != 0 && libc::isatty(libc::STDOUT_FILENO) != 0 }
+}
+
+/// The line editor with the history and the tab completion of the REPL.
+#[derive(Default)]
+pub struct Editor {
+    history: Vec<String>,
+}
+
+impl Editor {
+    /// Read a line with the prompt, returns `None` at the end of input.
+    pub fn read_line(&mut self, repl: &mut Repl) -> io::Result<Option<String>> {
+        let _raw = RawMode::enable()?;
+        let prompt = repl.prompt();
+        let mut stdin = io::stdin();
+        let mut stdout = io::stdout();
+        let mut line = Vec::<char>::new();
+        let mut pos = 0;
+        let mut history = self.history.len();
+
+        refresh(&mut stdout, prompt, &line, pos)?;
+
+        loop {
+            match read_key(&mut stdin)? {
+                Key::Char(c) => {
+                    line.insert(pos, c);
+                    pos += 1;
+                }
+                Key::Enter => {
+                    write!(stdout, "\r\n")?;
+
+                    let line = line.into_iter().collect::<String>();
+
+                    if !line.trim().is_empty() {
+                        self.history.push(line.clone());
+                    }
+
+                    return Ok(Some(line));
+                }
+                Key::Tab => {
+                    let s = line.iter().collect::<String>();
+                    let cursor = line[..pos].iter().map(|c| c.len_utf8()).sum();
+                    let (start, candidates) = repl.complete(&s, cursor);
+                    let prefix = common_prefix(&candidates);
+                    let typed = s[start..cursor].chars().count();
+
+                    if prefix.chars().count() > typed {
+                        for c in prefix.chars().skip(typed) {
+                            line.insert(pos, c);
+                            pos += 1;
+                        }
+                    } else if candidates.len() > 1 {
+                        write!(stdout, "\r\n{}\r\n", candidates.join("  "))?;
+                    }
+                }
+                Key::Backspace if pos > 0 => {
+                    pos -= 1;
+                    line.remove(pos);
+                }
+                Key::Delete if pos < line.len() => {
+                    line.remove(pos);
+                }
+                Key::Left if pos > 0 => pos -= 1,
+                Key::Right if pos < line.len() => pos += 1,
+                Key::Home => pos = 0,
+                Key::End => pos = line.len(),
+                Key::Up if history > 0 => {
+                    history -= 1;
+                    line = self.history[history].chars().collect();
+                    pos = line.len();
+                }
+                Key::Down if history < self.history.len() => {
+                    history += 1;
+                    line = self
+                        .history
+                        .get(history)
+                        .map(|s| s.chars().collect())
+                        .unwrap_or_default();
+                    pos = line.len();
+                }
+                Key::Interrupt => {
+                    write!(stdout, "^C\r\n")?;
+
+                    repl.reset();
+                    line.clear();
+                    pos = 0;
+                }
+                Key::Eof if line.is_empty() => {
+                    write!(stdout, "\r\n")?;
+
+                    return Ok(None);
+                }
+                _ => {}
+            }
+
+            refresh(&mut stdout, repl.prompt(), &line, pos)?;
+        }
+    }
+}
+
+fn refresh<W: Write>(w: &mut W, prompt: &str, line: &[char], pos: usize) -> io::Result<()> {
+    let line = line.iter().collect::<String>();
+
+    write!(w, "\r\x1b[K{}{}\r", prompt, line)?;
+
+    let col = prompt.chars().count() + line.chars().take(pos).count();
+
+    if col > 0 {
+        write!(w, "\x1b[{}C", col)?;
+    }
+
+    w.flush()
+}
+
+fn read_byte<R: Read>(r: &mut R) -> io::Result<Option<u8>> {
+    let mut buf = [0];
+
+    Ok(if r.read(&mut buf)? == 0 {
+        None
+    } else {
+        Some(buf[0])
+    })
+}
+
+fn read_key<R: Read>(r: &mut R) -> io::Result<Key> {
+    let b = match read_byte(r)? {
+        Some(b) => b,
+        None => return Ok(Key::Eof),
+    };
+
+    Ok(match b {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x05 => Key::End,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Eof,
+        0x1b => match (read_byte(r)?, read_byte(r)?) {
+            (Some(b'['), Some(b'A')) => Key::Up,
+            (Some(b'['), Some(b'B')) => Key::Down,
+            (Some(b'['), Some(b'C')) => Key::Right,
+            (Some(b'['), Some(b'D')) => Key::Left,
+            (Some(b'['), Some(b'H')) | (Some(b'O'), Some(b'H')) => Key::Home,
+            (Some(b'['), Some(b'F')) | (Some(b'O'), Some(b'F')) => Key::End,
+            (Some(b'['), Some(b'3')) => {
+                read_byte(r)?;
+
+                Key::Delete
+            }
+            _ => Key::Unknown,
+        },
+        b if b < 0x20 => Key::Unknown,
+        b => {
+            let len = match b {
+                0xf0..=0xff => 4,
+                0xe0..=0xef => 3,
+                0xc0..=0xdf => 2,
+                _ => 1,
+            };
+            let mut buf = vec![b];
+
+            for _ in 1..len {
+                buf.extend(read_byte(r)?);
+            }
+
+            String::from_utf8(buf)
+                .ok()
+                .and_then(|s| s.chars().next())
+                .map_or(Key::Unknown, Key::Char)
+        }
+    })
+}
+
+fn common_prefix(candidates: &[String]) -> String {
+    let mut iter = candidates.iter();
+    let mut prefix = match iter.next() {
+        Some(first) => first.clone(),
+        None => return String::new(),
+    };
+
+    for s in iter {
+        let len = prefix
+            .chars()
+            .zip(s.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+
+        prefix.truncate(len);
+    }
+
+    prefix
+}
@@ -0,0 +1,282 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate failure;
+
+mod editor;
+
+use std::ffi::CStr;
+use std::io::{self, Read};
+use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
+use std::process;
+use std::ptr::null_mut;
+use std::time::Duration;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+use structopt::StructOpt;
+
+use qjs::{
+    ffi, Context, ContextRef, ErrorKind, Eval, Local, Outcome, Repl, Runtime, StdLib, Value,
+};
+
+use crate::editor::Editor;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "qjs-cli", about = "Run the scripts and modules with QuickJS")]
+pub struct Opt {
+    /// Evaluate EXPR
+    #[structopt(name = "EXPR", short = "e", long = "eval")]
+    expr: Option<String>,
+
+    /// Go to interactive mode (default if no script and stdin is a terminal)
+    #[structopt(short, long)]
+    interactive: bool,
+
+    /// Load as ES6 module (default if .mjs file extension)
+    #[structopt(short, long)]
+    module: bool,
+
+    /// Evaluate the script before the main script
+    #[structopt(
+        short = "I",
+        long = "include",
+        number_of_values = 1,
+        parse(from_os_str)
+    )]
+    includes: Vec<PathBuf>,
+
+    /// Limit the memory usage of the runtime, in bytes with an optional K, M or G suffix
+    #[structopt(long = "memory-limit", parse(try_from_str = parse_size))]
+    memory_limit: Option<usize>,
+
+    /// Limit the execution time of each evaluation, in milliseconds
+    #[structopt(long = "timeout", parse(try_from_str = parse_timeout))]
+    timeout: Option<Duration>,
+
+    /// Make 'std' and 'os' invisible to non module code
+    #[structopt(long = "nostd")]
+    no_std: bool,
+
+    /// Initialize the Web platform APIs
+    #[cfg(feature = "web")]
+    #[structopt(long)]
+    web: bool,
+
+    /// The script file, or `-` to read from stdin
+    #[structopt(name = "FILE")]
+    file: Option<String>,
+
+    /// Script arguments
+    args: Vec<String>,
+}
+
+fn parse_size(s: &str) -> Result<usize, Error> {
+    let s = s.trim();
+    let (n, unit) = match s.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => (&s[..idx], c.to_ascii_uppercase()),
+        _ => (s, 'B'),
+    };
+    let n = n.parse::<usize>()?;
+    let scale = match unit {
+        'B' => 1,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        _ => bail!("unknown size unit `{}`", unit),
+    };
+
+    n.checked_mul(scale)
+        .ok_or_else(|| format_err!("size `{}` overflow", s))
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, Error> {
+    Ok(Duration::from_millis(s.trim().parse()?))
+}
+
+unsafe extern "C" fn module_loader(
+    ctx: *mut ffi::JSContext,
+    module_name: *const c_char,
+    _opaque: *mut c_void,
+) -> *mut ffi::JSModuleDef {
+    let ctxt = ContextRef::from_ptr(ctx);
+    let module_name = CStr::from_ptr(module_name).to_string_lossy();
+
+    debug!("load module: {}", module_name);
+
+    match qjs::load_file(module_name.as_ref())
+        .and_then(|buf| ctxt.eval_script(buf, &module_name, Eval::MODULE | Eval::COMPILE_ONLY))
+    {
+        Ok(module) => {
+            let _ = ctxt.set_import_meta(&module, true, false);
+
+            module.into_inner().as_ptr().as_ptr()
+        }
+        Err(err) => {
+            ctxt.throw_reference_error(format!("could not load module '{}', {}", module_name, err));
+
+            null_mut()
+        }
+    }
+}
+
+fn eval_buf<'a>(
+    ctxt: &'a ContextRef,
+    buf: &str,
+    filename: &str,
+    flags: Eval,
+) -> Result<Local<'a, Value>, Error> {
+    if flags.contains(Eval::MODULE) {
+        let module = ctxt.eval_script(buf, filename, flags | Eval::COMPILE_ONLY)?;
+
+        let _ = ctxt.set_import_meta(&module, true, true);
+
+        ctxt.eval_function(module)
+    } else {
+        ctxt.eval_script(buf, filename, flags)
+    }
+}
+
+fn eval_flags(opt: &Opt, filename: &str, buf: &str) -> Eval {
+    if opt.module || filename.ends_with(".mjs") || qjs::detect_module(buf) {
+        Eval::MODULE
+    } else {
+        Eval::GLOBAL
+    }
+}
+
+fn report(err: &Error) {
+    eprintln!("{}", err);
+
+    if let Some(stack) = err.downcast_ref::<ErrorKind>().and_then(|err| err.stack()) {
+        eprintln!("{}", stack)
+    }
+}
+
+fn run(opt: &Opt, ctxt: &ContextRef) -> Result<(), Error> {
+    let args = opt.file.iter().chain(opt.args.iter()).cloned();
+
+    StdLib::new()
+        .with_std()
+        .with_os()
+        .with_helpers(args)
+        .init(ctxt)?;
+
+    #[cfg(feature = "web")]
+    {
+        if opt.web {
+            ctxt.init_web_platform()?;
+        }
+    }
+
+    if !opt.no_std {
+        eval_buf(
+            ctxt,
+            "import * as std from 'std';\nimport * as os from 'os';\nglobalThis.std = std;\nglobalThis.os = os;\n",
+            "<input>",
+            Eval::MODULE,
+        )?;
+    }
+
+    for path in &opt.includes {
+        let filename = path.to_string_lossy();
+
+        debug!("include file: {}", filename);
+
+        let buf = qjs::load_file(path)?;
+
+        eval_buf(ctxt, &buf, &filename, eval_flags(opt, &filename, &buf))?;
+    }
+
+    let mut interactive = opt.interactive;
+
+    if let Some(ref expr) = opt.expr {
+        debug!("eval expr: {}", expr);
+
+        let res = eval_buf(ctxt, expr, "<cmdline>", Eval::GLOBAL)?;
+
+        if !res.is_undefined() {
+            println!("{}", res);
+        }
+    } else if opt.file.is_none() && (interactive || editor::is_terminal()) {
+        interactive = true;
+    } else {
+        let (filename, buf) = match opt.file.as_ref().map(String::as_str) {
+            None | Some("-") => {
+                let mut buf = String::new();
+
+                io::stdin().read_to_string(&mut buf)?;
+
+                ("<stdin>".to_owned(), buf)
+            }
+            Some(filename) => (filename.to_owned(), qjs::load_file(filename)?),
+        };
+
+        debug!("eval file: {}", filename);
+
+        eval_buf(ctxt, &buf, &filename, eval_flags(opt, &filename, &buf))?;
+    }
+
+    if interactive {
+        interact(ctxt)?;
+    }
+
+    ctxt.std_loop();
+
+    Ok(())
+}
+
+fn interact(ctxt: &ContextRef) -> Result<(), Error> {
+    if !editor::is_terminal() {
+        let stdin = io::stdin();
+
+        return Ok(Repl::new(ctxt).run(stdin.lock(), io::stdout())?);
+    }
+
+    let mut repl = Repl::new(ctxt).with_colors(true);
+
+    let mut editor = Editor::default();
+
+    while let Some(line) = editor.read_line(&mut repl)? {
+        match repl.feed(&line) {
+            Outcome::Incomplete => {}
+            Outcome::Value(value) => {
+                if !value.is_empty() {
+                    println!("{}", value)
+                }
+            }
+            Outcome::Exception(err) => eprintln!("Uncaught {}", err),
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    pretty_env_logger::init();
+
+    let opt = Opt::from_clap(
+        &Opt::clap()
+            .version(qjs::LONG_VERSION.as_str())
+            .get_matches(),
+    );
+    debug!("opts: {:?}", opt);
+
+    let rt = Runtime::new();
+
+    rt.set_memory_limit(opt.memory_limit);
+    rt.set_time_limit(opt.timeout);
+    rt.set_module_loader::<()>(None, Some(module_loader), None);
+
+    let ctxt = Context::new(&rt);
+    let res = run(&opt, &ctxt);
+
+    rt.std_free_handlers();
+
+    if let Err(err) = res {
+        report(&err);
+
+        process::exit(1);
+    }
+}
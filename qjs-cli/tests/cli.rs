@@ -0,0 +1,101 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn qjs(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_qjs-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    if let Some(input) = stdin {
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+    } else {
+        child.stdin.take();
+    }
+
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn eval_expr() {
+    let output = qjs(&["-e", "1 + 2"], None);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "3\n");
+}
+
+#[test]
+fn run_stdin() {
+    let output = qjs(&["-"], Some("print('hello', scriptArgs.length)"));
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "hello 1\n");
+}
+
+#[test]
+fn run_module_with_include() {
+    let dir = tempfile::tempdir().unwrap();
+    let lib = dir.path().join("lib.mjs");
+    let prelude = dir.path().join("prelude.js");
+    let main = dir.path().join("main.mjs");
+
+    fs::write(&lib, "export const name = 'lib';").unwrap();
+    fs::write(&prelude, "globalThis.greeting = 'hello';").unwrap();
+    fs::write(
+        &main,
+        "import { name } from './lib.mjs';\nimport * as std from 'std';\nstd.out.puts(`${greeting} ${name} ${scriptArgs[1]}\\n`);",
+    )
+    .unwrap();
+
+    let output = qjs(
+        &[
+            "--include",
+            prelude.to_str().unwrap(),
+            main.to_str().unwrap(),
+            "world",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stdout(&output), "hello lib world\n");
+}
+
+#[test]
+fn limits() {
+    let output = qjs(&["--timeout", "50", "-e", "for (;;) {}"], None);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("interrupted"));
+
+    let output = qjs(
+        &["--memory-limit", "4M", "-e", "new Array(1 << 24).fill(0)"],
+        None,
+    );
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn interactive() {
+    let output = qjs(
+        &["-i"],
+        Some("function add(a, b) {\n return a + b;\n}\nadd(1,\n 2)\n"),
+    );
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "> ... ... undefined\n> ... 3\n> ");
+}
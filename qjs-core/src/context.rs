@@ -0,0 +1,313 @@
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::String;
+use core::ffi::c_int;
+use core::slice;
+
+use crate::{
+    ffi,
+    value::{take_exception, to_string},
+    Error, Platform, Runtime, Value,
+};
+
+/// The context of the scripts, which owns the global object and the platform hooks.
+pub struct Context<'a> {
+    ctx: *mut ffi::JSContext,
+    rt: &'a Runtime,
+}
+
+impl Drop for Context<'_> {
+    fn drop(&mut self) {
+        trace!("free context @ {:p}", self.ctx);
+
+        unsafe {
+            let platform = ffi::JS_GetContextOpaque(self.ctx) as *mut Platform;
+
+            ffi::JS_FreeContext(self.ctx);
+
+            drop(Box::from_raw(platform));
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Create a context with the platform hooks.
+    pub fn new(rt: &'a Runtime, platform: Platform) -> Result<Self, Error> {
+        let ctx = unsafe { ffi::JS_NewContext(rt.as_ptr()) };
+
+        if ctx.is_null() {
+            return Err(Error::OutOfMemory);
+        }
+
+        trace!("new context @ {:p}", ctx);
+
+        let hooks = (
+            platform.clock.is_some(),
+            platform.random.is_some(),
+            platform.print.is_some(),
+        );
+
+        unsafe { ffi::JS_SetContextOpaque(ctx, Box::into_raw(Box::new(platform)) as *mut _) };
+
+        let ctxt = Context { ctx, rt };
+
+        ctxt.install_hooks(hooks)?;
+
+        Ok(ctxt)
+    }
+
+    /// Returns the runtime of the context.
+    pub fn runtime(&self) -> &'a Runtime {
+        self.rt
+    }
+
+    /// Evaluate a script in the global scope.
+    pub fn eval(&self, source: &str) -> Result<Value<'_>, Error> {
+        let input = CString::new(source).map_err(|_| Error::Nul)?;
+        let value = unsafe {
+            ffi::JS_Eval(
+                self.ctx,
+                input.as_ptr(),
+                source.len(),
+                b"<eval>\0".as_ptr() as *const _,
+                ffi::JS_EVAL_TYPE_GLOBAL as c_int,
+            )
+        };
+
+        self.check(value)
+    }
+
+    fn check(&self, value: ffi::JSValue) -> Result<Value<'_>, Error> {
+        if value.tag as i32 == ffi::JS_TAG_EXCEPTION {
+            Err(take_exception(self.ctx))
+        } else {
+            Ok(Value::new(self.ctx, value))
+        }
+    }
+
+    fn install_hooks(&self, (clock, random, print): (bool, bool, bool)) -> Result<(), Error> {
+        let global = Value::new(self.ctx, unsafe { ffi::JS_GetGlobalObject(self.ctx) });
+
+        if clock {
+            self.set_function(
+                &self.get_property(&global, b"Date\0")?,
+                b"now\0",
+                date_now,
+                0,
+            )?;
+        }
+
+        if random {
+            self.set_function(
+                &self.get_property(&global, b"Math\0")?,
+                b"random\0",
+                math_random,
+                0,
+            )?;
+        }
+
+        if print {
+            self.set_function(&global, b"print\0", print_args, 1)?;
+            self.set_property(&global, b"console\0", unsafe {
+                ffi::JS_NewObject(self.ctx)
+            })?;
+            self.set_function(
+                &self.get_property(&global, b"console\0")?,
+                b"log\0",
+                print_args,
+                1,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the property of the object, the name must be nul terminated.
+    fn get_property(&self, obj: &Value, name: &[u8]) -> Result<Value<'_>, Error> {
+        self.check(unsafe {
+            ffi::JS_GetPropertyStr(self.ctx, obj.raw(), name.as_ptr() as *const _)
+        })
+    }
+
+    /// Set the property of the object, the name must be nul terminated and the value is consumed.
+    fn set_property(&self, obj: &Value, name: &[u8], value: ffi::JSValue) -> Result<(), Error> {
+        if value.tag as i32 == ffi::JS_TAG_EXCEPTION
+            || unsafe {
+                ffi::JS_SetPropertyStr(self.ctx, obj.raw(), name.as_ptr() as *const _, value)
+            } < 0
+        {
+            Err(take_exception(self.ctx))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set the native function as the property of the object.
+    fn set_function(
+        &self,
+        obj: &Value,
+        name: &[u8],
+        func: CFunction,
+        length: c_int,
+    ) -> Result<(), Error> {
+        let func = unsafe {
+            ffi::JS_NewCFunction2(
+                self.ctx,
+                Some(func),
+                name.as_ptr() as *const _,
+                length,
+                ffi::JSCFunctionEnum::JS_CFUNC_generic,
+                0,
+            )
+        };
+
+        self.set_property(obj, name, func)
+    }
+}
+
+type CFunction = unsafe extern "C" fn(
+    *mut ffi::JSContext,
+    ffi::JSValue,
+    c_int,
+    *mut ffi::JSValue,
+) -> ffi::JSValue;
+
+unsafe fn platform<'a>(ctx: *mut ffi::JSContext) -> &'a Platform {
+    &*(ffi::JS_GetContextOpaque(ctx) as *const Platform)
+}
+
+fn new_float64(n: f64) -> ffi::JSValue {
+    ffi::JSValue {
+        u: ffi::JSValueUnion { float64: n },
+        tag: ffi::JS_TAG_FLOAT64 as i64,
+    }
+}
+
+unsafe extern "C" fn date_now(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    _argc: c_int,
+    _argv: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    match platform(ctx).clock {
+        Some(ref clock) => new_float64(clock.now()),
+        None => ffi::UNDEFINED,
+    }
+}
+
+unsafe extern "C" fn math_random(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    _argc: c_int,
+    _argv: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    match platform(ctx).random {
+        Some(ref random) => new_float64(random.borrow_mut().random()),
+        None => ffi::UNDEFINED,
+    }
+}
+
+unsafe extern "C" fn print_args(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let args = if argc > 0 {
+        slice::from_raw_parts(argv, argc as usize)
+    } else {
+        &[]
+    };
+    let mut msg = String::new();
+
+    for (i, &arg) in args.iter().enumerate() {
+        if i > 0 {
+            msg.push(' ');
+        }
+
+        match to_string(ctx, arg) {
+            Some(s) => msg.push_str(&s),
+            None => return ffi::EXCEPTION,
+        }
+    }
+
+    if let Some(ref print) = platform(ctx).print {
+        print.print(&msg);
+    }
+
+    ffi::UNDEFINED
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn platform_hooks() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new().unwrap();
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let mut seed = 0.0;
+        let platform = Platform::new()
+            .with_clock(|| 60_000.0)
+            .with_random(move || {
+                seed += 0.25;
+                seed
+            })
+            .with_print({
+                let printed = printed.clone();
+
+                move |msg: &str| printed.borrow_mut().push(String::from(msg))
+            });
+        let ctxt = Context::new(&rt, platform).unwrap();
+
+        assert_eq!(ctxt.eval("Date.now()").unwrap().to_f64().unwrap(), 60_000.0);
+        assert_eq!(
+            ctxt.eval("[Math.random(), Math.random()].join()")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "0.25,0.5"
+        );
+
+        ctxt.eval("print('hello', 1, true); console.log({})")
+            .unwrap();
+
+        assert_eq!(*printed.borrow(), ["hello 1 true", "[object Object]"]);
+    }
+
+    #[test]
+    fn builtin_hooks() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new().unwrap();
+        let ctxt = Context::new(&rt, Platform::new()).unwrap();
+
+        assert!(ctxt.eval("Date.now()").unwrap().to_f64().unwrap() > 0.0);
+        assert!(ctxt.eval("typeof print").unwrap().to_str().unwrap() == "undefined");
+    }
+
+    #[test]
+    fn exception() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new().unwrap();
+        let ctxt = Context::new(&rt, Platform::new()).unwrap();
+
+        assert_eq!(
+            ctxt.eval("throw new TypeError('boom')").unwrap_err(),
+            Error::Exception("TypeError: boom".into())
+        );
+        assert_eq!(
+            ctxt.eval("foo(").unwrap_err().to_string(),
+            "SyntaxError: unexpected token in expression: ''"
+        );
+        assert_eq!(ctxt.eval("'a\0b'").unwrap_err(), Error::Nul);
+    }
+}
@@ -0,0 +1,23 @@
+use alloc::string::String;
+use core::fmt;
+
+/// The error of the core layer, which doesn't depend on `std`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Fail to allocate the runtime or context.
+    OutOfMemory,
+    /// The source contains a nul byte.
+    Nul,
+    /// The script raised an exception, with the message of the thrown value.
+    Exception(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfMemory => write!(f, "out of memory"),
+            Error::Nul => write!(f, "nul byte found in the source"),
+            Error::Exception(msg) => write!(f, "{}", msg),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! A minimal `no_std` API of the QuickJS Javascript Engine, which only requires `alloc`.
+//!
+//! It is a separate crate over `qjs-sys`, not the core of the `qjs` crate,
+//! which keeps its own `std` based API; only the evaluation and the basic values are covered.
+//!
+//! The embedder supplies the platform hooks for the clock, the random number generator
+//! and the printing, so the scripts could run on the embedded Linux or RTOS targets
+//! where QuickJS itself already runs.
+//!
+//! # Examples
+//!
+//! ```
+//! use qjs_core::{Context, Platform, Runtime};
+//!
+//! let rt = Runtime::new().unwrap();
+//! let platform = Platform::new()
+//!     .with_clock(|| 1_000.0)
+//!     .with_random(|| 0.5)
+//!     .with_print(|msg: &str| assert_eq!(msg, "hello world"));
+//! let ctxt = Context::new(&rt, platform).unwrap();
+//!
+//! ctxt.eval("print('hello', 'world')").unwrap();
+//!
+//! assert_eq!(ctxt.eval("Date.now() + Math.random()").unwrap().to_f64().unwrap(), 1_000.5);
+//! ```
+#![no_std]
+
+extern crate alloc;
+#[macro_use]
+extern crate log;
+
+pub use qjs_sys as ffi;
+
+mod context;
+mod error;
+mod platform;
+mod runtime;
+mod value;
+
+pub use context::Context;
+pub use error::Error;
+pub use platform::{Clock, Platform, Print, Random};
+pub use runtime::Runtime;
+pub use value::Value;
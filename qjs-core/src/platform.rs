@@ -0,0 +1,72 @@
+use alloc::boxed::Box;
+use core::cell::RefCell;
+
+/// The clock of `Date.now()` supplied by the embedder.
+pub trait Clock {
+    /// The milliseconds elapsed since the UNIX epoch.
+    fn now(&self) -> f64;
+}
+
+impl<F: Fn() -> f64> Clock for F {
+    fn now(&self) -> f64 {
+        self()
+    }
+}
+
+/// The random number generator of `Math.random()` supplied by the embedder.
+pub trait Random {
+    /// Returns a number in `[0, 1)`.
+    fn random(&mut self) -> f64;
+}
+
+impl<F: FnMut() -> f64> Random for F {
+    fn random(&mut self) -> f64 {
+        self()
+    }
+}
+
+/// The output of `print()` and `console.log()` supplied by the embedder.
+pub trait Print {
+    /// Print a message, the arguments are joined with a space.
+    fn print(&self, msg: &str);
+}
+
+impl<F: Fn(&str)> Print for F {
+    fn print(&self, msg: &str) {
+        self(msg)
+    }
+}
+
+/// The platform hooks of a context, the builtin implementations of QuickJS are used for the missing ones.
+///
+/// `print()` and `console.log()` are only defined when the printing supplied.
+#[derive(Default)]
+pub struct Platform {
+    pub(crate) clock: Option<Box<dyn Clock>>,
+    pub(crate) random: Option<RefCell<Box<dyn Random>>>,
+    pub(crate) print: Option<Box<dyn Print>>,
+}
+
+impl Platform {
+    pub fn new() -> Self {
+        Platform::default()
+    }
+
+    /// Set the clock of `Date.now()`.
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Set the random number generator of `Math.random()`.
+    pub fn with_random<R: Random + 'static>(mut self, random: R) -> Self {
+        self.random = Some(RefCell::new(Box::new(random)));
+        self
+    }
+
+    /// Set the output of `print()` and `console.log()`.
+    pub fn with_print<P: Print + 'static>(mut self, print: P) -> Self {
+        self.print = Some(Box::new(print));
+        self
+    }
+}
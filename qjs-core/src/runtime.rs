@@ -0,0 +1,86 @@
+use core::ptr;
+
+use crate::{ffi, value::take_exception, Error};
+
+/// The runtime of the contexts, which owns the heap and the pending jobs.
+pub struct Runtime(*mut ffi::JSRuntime);
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        trace!("free runtime @ {:p}", self.0);
+
+        unsafe { ffi::JS_FreeRuntime(self.0) }
+    }
+}
+
+impl Runtime {
+    pub fn new() -> Result<Self, Error> {
+        let rt = unsafe { ffi::JS_NewRuntime() };
+
+        if rt.is_null() {
+            Err(Error::OutOfMemory)
+        } else {
+            trace!("new runtime @ {:p}", rt);
+
+            Ok(Runtime(rt))
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::JSRuntime {
+        self.0
+    }
+
+    /// Set the memory limit of the runtime in bytes.
+    pub fn set_memory_limit(&self, limit: usize) -> &Self {
+        unsafe { ffi::JS_SetMemoryLimit(self.0, limit) };
+        self
+    }
+
+    /// Returns `true` if there are the pending jobs, e.g. the promise reactions.
+    pub fn is_job_pending(&self) -> bool {
+        unsafe { ffi::JS_IsJobPending(self.0) != 0 }
+    }
+
+    /// Execute the pending jobs, returns the number of the jobs executed.
+    pub fn run_pending_jobs(&self) -> Result<usize, Error> {
+        let mut executed = 0;
+
+        loop {
+            let mut ctx = ptr::null_mut();
+
+            match unsafe { ffi::JS_ExecutePendingJob(self.0, &mut ctx) } {
+                0 => return Ok(executed),
+                n if n < 0 => return Err(take_exception(ctx)),
+                _ => executed += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Platform};
+
+    use super::*;
+
+    #[test]
+    fn pending_jobs() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new().unwrap();
+        let ctxt = Context::new(&rt, Platform::new()).unwrap();
+
+        ctxt.eval("var n = 0; Promise.resolve().then(() => { n++ }).then(() => { n++ })")
+            .unwrap();
+
+        assert!(rt.is_job_pending());
+        assert_eq!(rt.run_pending_jobs().unwrap(), 2);
+        assert!(!rt.is_job_pending());
+        assert_eq!(ctxt.eval("n").unwrap().to_i32().unwrap(), 2);
+
+        ctxt.eval("Promise.resolve().then(() => { throw new Error('boom') })")
+            .unwrap();
+
+        assert_eq!(rt.run_pending_jobs().unwrap(), 1);
+    }
+}
@@ -0,0 +1,189 @@
+use alloc::string::String;
+use core::fmt;
+use core::marker::PhantomData;
+use core::slice;
+
+use crate::{ffi, Context, Error};
+
+/// The value owned by a context, which is freed when dropped.
+pub struct Value<'a> {
+    ctx: *mut ffi::JSContext,
+    value: ffi::JSValue,
+    phantom: PhantomData<&'a Context<'a>>,
+}
+
+impl Drop for Value<'_> {
+    fn drop(&mut self) {
+        unsafe { free_value(self.ctx, self.value) }
+    }
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Value").field(&self.tag()).finish()
+    }
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = unsafe { to_string(self.ctx, self.value) }.ok_or(fmt::Error)?;
+
+        f.write_str(&s)
+    }
+}
+
+impl<'a> Value<'a> {
+    pub(crate) fn new(ctx: *mut ffi::JSContext, value: ffi::JSValue) -> Self {
+        Value {
+            ctx,
+            value,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn raw(&self) -> ffi::JSValue {
+        self.value
+    }
+
+    fn tag(&self) -> i32 {
+        self.value.tag as i32
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.tag() == ffi::JS_TAG_UNDEFINED
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.tag() == ffi::JS_TAG_NULL
+    }
+
+    pub fn is_bool(&self) -> bool {
+        self.tag() == ffi::JS_TAG_BOOL
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.tag() == ffi::JS_TAG_INT || self.tag() == ffi::JS_TAG_FLOAT64
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.tag() == ffi::JS_TAG_STRING
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.tag() == ffi::JS_TAG_OBJECT
+    }
+
+    /// Convert the value to a boolean.
+    pub fn to_bool(&self) -> bool {
+        unsafe { ffi::JS_ToBool(self.ctx, self.value) != 0 }
+    }
+
+    /// Convert the value to an `i32`.
+    pub fn to_i32(&self) -> Result<i32, Error> {
+        let mut n = 0;
+
+        if unsafe { ffi::JS_ToInt32(self.ctx, &mut n, self.value) } < 0 {
+            Err(take_exception(self.ctx))
+        } else {
+            Ok(n)
+        }
+    }
+
+    /// Convert the value to a `f64`.
+    pub fn to_f64(&self) -> Result<f64, Error> {
+        let mut n = 0.0;
+
+        if unsafe { ffi::JS_ToFloat64(self.ctx, &mut n, self.value) } < 0 {
+            Err(take_exception(self.ctx))
+        } else {
+            Ok(n)
+        }
+    }
+
+    /// Convert the value to a string, the exception is returned if `toString()` throws.
+    pub fn to_str(&self) -> Result<String, Error> {
+        unsafe { to_string(self.ctx, self.value) }.ok_or_else(|| take_exception(self.ctx))
+    }
+}
+
+/// Free the value if its reference count reaches zero.
+pub(crate) unsafe fn free_value(ctx: *mut ffi::JSContext, value: ffi::JSValue) {
+    if (value.tag as u32) >= (ffi::JS_TAG_FIRST as u32) {
+        let ref_cnt = value.u.ptr as *mut ffi::JSRefCountHeader;
+
+        (*ref_cnt).ref_count -= 1;
+
+        if (*ref_cnt).ref_count <= 0 {
+            ffi::__JS_FreeValue(ctx, value)
+        }
+    }
+}
+
+/// Convert the value to a string, returns `None` if the conversion raised an exception.
+pub(crate) unsafe fn to_string(ctx: *mut ffi::JSContext, value: ffi::JSValue) -> Option<String> {
+    let mut len = 0;
+    let p = ffi::JS_ToCStringLen2(ctx, &mut len, value, 0);
+
+    if p.is_null() {
+        None
+    } else {
+        let s = String::from_utf8_lossy(slice::from_raw_parts(p as *const u8, len)).into_owned();
+
+        ffi::JS_FreeCString(ctx, p);
+
+        Some(s)
+    }
+}
+
+/// Take the pending exception of the context as an error.
+pub(crate) fn take_exception(ctx: *mut ffi::JSContext) -> Error {
+    unsafe {
+        let exc = ffi::JS_GetException(ctx);
+        let msg = to_string(ctx, exc).unwrap_or_else(|| String::from("unknown exception"));
+
+        free_value(ctx, exc);
+
+        Error::Exception(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::{Context, Platform, Runtime};
+
+    #[test]
+    fn value() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new().unwrap();
+        let ctxt = Context::new(&rt, Platform::new()).unwrap();
+
+        assert!(ctxt.eval("undefined").unwrap().is_undefined());
+        assert!(ctxt.eval("null").unwrap().is_null());
+        assert!(ctxt.eval("true").unwrap().to_bool());
+        assert!(ctxt.eval("({})").unwrap().is_object());
+
+        let n = ctxt.eval("1 + 2.5").unwrap();
+
+        assert!(n.is_number());
+        assert_eq!(n.to_f64().unwrap(), 3.5);
+        assert_eq!(n.to_i32().unwrap(), 3);
+
+        let s = ctxt.eval("'hello ' + 'world'").unwrap();
+
+        assert!(s.is_string());
+        assert_eq!(s.to_str().unwrap(), "hello world");
+        assert_eq!(s.to_string(), "hello world");
+
+        assert_eq!(
+            ctxt.eval("({ toString() { throw new Error('boom') } })")
+                .unwrap()
+                .to_str()
+                .unwrap_err()
+                .to_string(),
+            "Error: boom"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Python bindings of `qjs`, built as the `qjs_py` extension module with `maturin`.
+//!
+//! The `dict`, `list`, `tuple`, `str`, `int`, `float`, `bool` and `None` are converted to
+//! the Javascript objects, arrays, strings, numbers, booleans and `null`, and vice versa.
+//!
+//! ```python
+//! import qjs_py
+//!
+//! ctxt = qjs_py.Context()
+//! ctxt.set("config", {"name": "qjs", "items": [1, 2.5, True, None]})
+//! assert ctxt.eval("config.items.length") == 4
+//! assert ctxt.call("JSON.stringify", [1, 2]) == "[1,2]"
+//! ```
+use failure::Error;
+use pyo3::exceptions::{RuntimeError, TypeError, ValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use pyo3::wrap_pyfunction;
+
+use qjs::{ContextRef, Eval, Local, PropertyNames as Names, Runtime, Value};
+
+/// The maximum depth of the nested objects, to break the cycles.
+const MAX_DEPTH: usize = 64;
+
+fn js_error(err: Error) -> PyErr {
+    PyErr::new::<RuntimeError, _>(err.to_string())
+}
+
+/// A Javascript context with its own runtime.
+#[pyclass(unsendable)]
+pub struct Context {
+    // the context should be dropped before the runtime
+    ctxt: qjs::Context,
+    _rt: Runtime,
+}
+
+#[pymethods]
+impl Context {
+    #[new]
+    fn new() -> Self {
+        let rt = Runtime::new();
+        let ctxt = qjs::Context::new(&rt);
+
+        Context { ctxt, _rt: rt }
+    }
+
+    /// Evaluate a script and returns the converted result.
+    #[args(filename = "\"<eval>\"")]
+    fn eval(&self, py: Python, source: &str, filename: &str) -> PyResult<PyObject> {
+        let value = self
+            .ctxt
+            .eval_script(source, filename, Eval::GLOBAL)
+            .map_err(js_error)?;
+
+        to_py(py, &self.ctxt, &value, 0)
+    }
+
+    /// Get a global variable.
+    fn get(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        match self.ctxt.global_object().get_property(name) {
+            Some(value) => to_py(py, &self.ctxt, &value, 0),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Set a global variable.
+    fn set(&self, name: &str, value: &PyAny) -> PyResult<()> {
+        let value = to_js(&self.ctxt, value, 0)?;
+
+        self.ctxt
+            .global_object()
+            .set_property(name, value)
+            .map_err(js_error)?;
+
+        Ok(())
+    }
+
+    /// Call a function by its expression, e.g. `JSON.stringify`, with the converted arguments.
+    #[args(args = "*")]
+    fn call(&self, py: Python, func: &str, args: &PyTuple) -> PyResult<PyObject> {
+        let f = self
+            .ctxt
+            .eval_script(func, "<call>", Eval::GLOBAL)
+            .map_err(js_error)?;
+
+        if !f.is_function() {
+            return Err(PyErr::new::<TypeError, _>(format!(
+                "`{}` is not a function",
+                func
+            )));
+        }
+
+        let args = args
+            .iter()
+            .map(|arg| to_js(&self.ctxt, arg, 0))
+            .collect::<PyResult<Vec<_>>>()?;
+        let res = self
+            .ctxt
+            .call(&f, None, args.as_slice())
+            .map_err(js_error)?;
+
+        to_py(py, &self.ctxt, &res, 0)
+    }
+}
+
+/// Evaluate a script in a new context and returns the converted result.
+#[pyfunction]
+fn eval(py: Python, source: &str) -> PyResult<PyObject> {
+    Context::new().eval(py, source, "<eval>")
+}
+
+#[pymodule]
+fn qjs_py(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("__version__", qjs::VERSION)?;
+    m.add_class::<Context>()?;
+    m.add_wrapped(wrap_pyfunction!(eval))?;
+
+    Ok(())
+}
+
+/// Convert a Javascript value to the Python object.
+fn to_py(py: Python, ctxt: &ContextRef, value: &Local<Value>, depth: usize) -> PyResult<PyObject> {
+    if depth > MAX_DEPTH {
+        return Err(PyErr::new::<ValueError, _>("too deep or circular value"));
+    }
+
+    if value.is_null() || value.is_undefined() {
+        Ok(py.None())
+    } else if let Some(b) = value.as_bool() {
+        Ok(b.to_object(py))
+    } else if let Some(n) = value.as_int() {
+        Ok(n.to_object(py))
+    } else if let Some(n) = value.as_float() {
+        Ok(n.to_object(py))
+    } else if value.is_string() {
+        Ok(value.to_string().to_object(py))
+    } else if value.is_function() {
+        Err(PyErr::new::<TypeError, _>("function can't be converted"))
+    } else if value.is_array().map_err(js_error)? {
+        let len = value
+            .get_property("length")
+            .and_then(|len| len.to_int32())
+            .unwrap_or_default()
+            .max(0) as u32;
+        let items = (0..len)
+            .map(|i| match value.get_property(i) {
+                Some(item) => to_py(py, ctxt, &item, depth + 1),
+                None => Ok(py.None()),
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(PyList::new(py, items).to_object(py))
+    } else if value.is_object() {
+        let dict = PyDict::new(py);
+        let names = ctxt
+            .get_own_property_names(value, Names::STRING | Names::ENUM_ONLY)
+            .map_err(js_error)?
+            .unwrap_or_default();
+
+        for name in names {
+            let key = name.to_string();
+
+            if let Some(item) = value.get_property(name) {
+                dict.set_item(key, to_py(py, ctxt, &item, depth + 1)?)?;
+            }
+        }
+
+        Ok(dict.to_object(py))
+    } else if let Some(n) = value.to_float64() {
+        Ok(n.to_object(py))
+    } else {
+        Ok(value.to_string().to_object(py))
+    }
+}
+
+/// Convert a Python object to the Javascript value.
+fn to_js<'a>(ctxt: &'a ContextRef, obj: &PyAny, depth: usize) -> PyResult<Local<'a, Value>> {
+    if depth > MAX_DEPTH {
+        return Err(PyErr::new::<ValueError, _>("too deep or circular value"));
+    }
+
+    let value = if obj.is_none() {
+        ctxt.null()
+    } else if let Ok(b) = obj.downcast::<PyBool>() {
+        ctxt.bind(ctxt.new_value(b.is_true()))
+    } else if obj.downcast::<PyLong>().is_ok() {
+        let n: i64 = obj.extract()?;
+
+        if n >= i64::from(i32::min_value()) && n <= i64::from(i32::max_value()) {
+            ctxt.bind(ctxt.new_value(n as i32))
+        } else {
+            ctxt.bind(ctxt.new_value(n as f64))
+        }
+    } else if let Ok(f) = obj.downcast::<PyFloat>() {
+        ctxt.bind(ctxt.new_value(f.value()))
+    } else if let Ok(s) = obj.downcast::<PyString>() {
+        ctxt.bind(ctxt.new_value(s.to_string()?.as_ref()))
+    } else if obj.downcast::<PyList>().is_ok() || obj.downcast::<PyTuple>().is_ok() {
+        let arr = ctxt.bind(ctxt.new_array());
+
+        for (i, item) in obj.iter()?.enumerate() {
+            arr.set_property(i as u32, to_js(ctxt, item?, depth + 1)?)
+                .map_err(js_error)?;
+        }
+
+        arr
+    } else if let Ok(dict) = obj.downcast::<PyDict>() {
+        let o = ctxt.bind(ctxt.new_object());
+
+        for (key, item) in dict.iter() {
+            let key = key.str()?.to_string()?;
+
+            o.set_property(key.as_ref(), to_js(ctxt, item, depth + 1)?)
+                .map_err(js_error)?;
+        }
+
+        o
+    } else {
+        return Err(PyErr::new::<TypeError, _>(format!(
+            "`{}` can't be converted",
+            obj.get_type().name()
+        )));
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+
+    use super::*;
+
+    /// Convert a Python expression to Javascript and back, then compare it with `expected`.
+    fn round_trip(py: Python, ctxt: &Context, expr: &str, expected: &str) -> PyResult<bool> {
+        let value = py.eval(expr, None, None)?;
+
+        ctxt.set("value", value)?;
+
+        let res = ctxt.get(py, "value")?;
+        let locals = vec![("res", res)].into_py_dict(py);
+
+        py.eval(&format!("res == {}", expected), None, Some(locals))?
+            .extract()
+    }
+
+    #[test]
+    fn conversion() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ctxt = Context::new();
+
+        for &(expr, expected) in &[
+            ("None", "None"),
+            ("True", "True"),
+            ("False", "False"),
+            ("123", "123"),
+            ("-123", "-123"),
+            ("2**40", "2**40"),
+            ("1.5", "1.5"),
+            ("'hello'", "'hello'"),
+            ("''", "''"),
+            ("[]", "[]"),
+            ("[1, 2.5, True, None, 'a']", "[1, 2.5, True, None, 'a']"),
+            ("(1, 2)", "[1, 2]"),
+            ("{}", "{}"),
+            (
+                "{'a': 1, 'b': [2, {'c': None}]}",
+                "{'a': 1, 'b': [2, {'c': None}]}",
+            ),
+            ("{1: 'a'}", "{'1': 'a'}"),
+        ] {
+            assert!(
+                round_trip(py, &ctxt, expr, expected).unwrap(),
+                "round trip `{}`",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn context() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ctxt = Context::new();
+
+        ctxt.set("config", vec![("items", vec![1, 2, 3])].into_py_dict(py))
+            .unwrap();
+
+        let len: i32 = ctxt
+            .eval(py, "config.items.length", "<eval>")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(len, 3);
+
+        let s: String = ctxt
+            .call(py, "JSON.stringify", PyTuple::new(py, &[1, 2]))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(s, "[1,2]");
+
+        assert!(ctxt.get(py, "missing").unwrap().is_none());
+        assert!(ctxt.eval(py, "(function() {})", "<eval>").is_err());
+        assert!(ctxt
+            .eval(py, "let o = {}; o.self = o; o", "<eval>")
+            .is_err());
+        assert!(ctxt.call(py, "Math.PI", PyTuple::empty(py)).is_err());
+        assert!(ctxt.eval(py, "throw new Error('boom')", "<eval>").is_err());
+    }
+
+    #[test]
+    fn unsupported() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ctxt = Context::new();
+
+        assert!(ctxt
+            .set("value", py.eval("object()", None, None).unwrap())
+            .is_err());
+        assert!(ctxt
+            .set("value", py.eval("1j", None, None).unwrap())
+            .is_err());
+    }
+}
@@ -4,11 +4,22 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use failure::{Error, ResultExt};
+use failure::{bail, Error, ResultExt};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+/// The bundled QuickJS source.
+///
+/// Only this release is supported, the bindings and the patches depend on its internal layout,
+/// so a fork like `quickjs-ng` can't be built in its place.
 const QUICKJS_SRC: &str = "quickjs-2019-09-18.tar.xz";
+const QUICKJS_VERSION: &str = "2019-09-18";
+
+/// The environment variable to link the system library found by `pkg-config`.
+const QUICKJS_SYS_USE_PKG_CONFIG: &str = "QUICKJS_SYS_USE_PKG_CONFIG";
+
+/// The `cfg` of the library without the patches of the bundled QuickJS.
+const UNPATCHED_CFG: &str = "qjs_sys_unpatched";
 
 lazy_static! {
     static ref OUT_DIR: PathBuf = env::var_os("OUT_DIR").expect("OUT_DIR").into();
@@ -96,6 +107,57 @@ fn patch_quickjs(quickjs: &Path) -> Result<(), Error> {
     if cfg!(feature = "dump_read_object") {
         content = content.replace("//#define DUMP_READ_OBJECT\n", "#define DUMP_READ_OBJECT\n");
     }
+    if !content.contains("JS_EVAL_FLAG_PARSE_ONLY") {
+        content = patch_syntax_check(&content);
+    }
+    if cfg!(feature = "debugger") && !content.contains("debugger_hook") {
+        content = patch_debugger(&content);
+    }
+    if !content.contains("JS_GetFastArray") {
+        content = patch_fast_array(&content);
+    }
+    if !content.contains("JS_GetEngineStats") {
+        content = patch_engine_stats(&content);
+    }
+    if !content.contains("find_column_num") {
+        content = patch_column_numbers(&content)?;
+    }
+    if !content.contains("JS_SetBacktraceFilter") {
+        content = patch_backtrace_filter(&content)?;
+    }
+    if !content.contains("JS_SetPrepareStackTrace") {
+        content = patch_prepare_stack_trace(&content)?;
+    }
+    if !content.contains("JS_StrictEq") {
+        content = patch_equality(&content);
+    }
+    if !content.contains("JS_ResetModules") {
+        content = patch_context_reset(&content)?;
+    }
+    if !content.contains("shallower frame") {
+        content = patch_stack_check(&content)?;
+    }
+    if !content.contains("JS_SetHostPromiseRejectionTracker") {
+        content = patch_promise_rejection_tracker(&content)?;
+    }
+    if !content.contains("JS_ForEachLiveObject") {
+        content = patch_live_objects(&content);
+    }
+    if !content.contains("JS_NewStringUTF16") {
+        content = patch_utf16(&content);
+    }
+    if !content.contains("JS_SetEngineConfig") {
+        content = patch_engine_config(&content)?;
+    }
+    if !content.contains("JS_SetRuntimeOpaque") {
+        content = patch_runtime_opaque(&content)?;
+    }
+    if !content.contains("JS_GetValueRuntime") {
+        content = patch_value_runtime(&content)?;
+    }
+    if !content.contains("JS_GetCFunctionPtr") {
+        content = patch_api_audit(&content);
+    }
 
     fs::rename(quickjs, quickjs.with_extension("bak"))?;
     fs::write(quickjs, content.as_bytes())?;
@@ -103,6 +165,1263 @@ fn patch_quickjs(quickjs: &Path) -> Result<(), Error> {
     Ok(())
 }
 
+/// Add the statement-level hook to the interpreter loop, see `src/debugger.c`.
+fn patch_debugger(content: &str) -> String {
+    content
+        .replace(
+            "struct JSRuntime {\n",
+            r#"#define JS_DEBUGGER_EVENT_STEP      0
+#define JS_DEBUGGER_EVENT_EXCEPTION 1
+#define JS_DEBUGGER_EVENT_ENTER     2
+#define JS_DEBUGGER_EVENT_EXIT      3
+
+typedef int JSDebuggerHook(JSContext *ctx, int event, JSAtom filename,
+                           int line_num, int depth, JSValueConst exception,
+                           void *opaque);
+
+typedef struct JSDebuggerFrame {
+    JSAtom function_name;
+    JSAtom filename;
+    int function_line;
+    int line_num;
+} JSDebuggerFrame;
+
+struct JSRuntime {
+"#,
+        )
+        .replace(
+            "    JSInterruptHandler *interrupt_handler;\n",
+            r#"    JSInterruptHandler *interrupt_handler;
+    JSDebuggerHook *debugger_hook;
+    void *debugger_opaque;
+    BOOL debugger_in_hook;
+"#,
+        )
+        .replace(
+            "typedef struct JSStackFrame {\n",
+            r#"typedef struct JSStackFrame {
+    int debug_line; /* the last source line reported to the debugger hook */
+"#,
+        )
+        .replace(
+            "JSValue JS_Throw(JSContext *ctx, JSValue obj)\n{\n",
+            r#"static int js_debugger_hook(JSContext *ctx, int event, struct JSStackFrame *sf,
+                            JSValueConst exception);
+
+JSValue JS_Throw(JSContext *ctx, JSValue obj)
+{
+    if (unlikely(ctx->rt->debugger_hook != NULL) && !ctx->rt->debugger_in_hook)
+        js_debugger_hook(ctx, JS_DEBUGGER_EVENT_EXCEPTION, ctx->current_stack_frame, obj);
+"#,
+        )
+        .replace(
+            "static JSValue JS_CallInternal(JSContext *ctx, JSValueConst func_obj,\n                               JSValueConst this_obj, JSValueConst new_target,\n                               int argc, JSValue *argv, int flags)\n{\n",
+            r#"#include "debugger.c"
+
+static JSValue JS_CallInternal(JSContext *ctx, JSValueConst func_obj,
+                               JSValueConst this_obj, JSValueConst new_target,
+                               int argc, JSValue *argv, int flags)
+{
+"#,
+        )
+        .replace(
+            "#define SWITCH(pc)      goto *dispatch_table[opcode = *pc++];\n",
+            r#"#define SWITCH(pc)                                                       \
+    if (unlikely(ctx->rt->debugger_hook != NULL) && js_debugger_check(ctx, b, sf, pc) < 0) \
+        goto exception;                                                  \
+    goto *dispatch_table[opcode = *pc++];
+"#,
+        )
+        .replace(
+            "    } else {\n    done:\n        if (unlikely(!list_empty(&sf->var_ref_list))) {\n",
+            r#"    } else {
+    done:
+        if (unlikely(ctx->rt->debugger_hook != NULL) && sf->debug_line >= 0 &&
+            !ctx->rt->debugger_in_hook)
+            js_debugger_hook(ctx, JS_DEBUGGER_EVENT_EXIT, sf, JS_UNDEFINED);
+        if (unlikely(!list_empty(&sf->var_ref_list))) {
+"#,
+        )
+        .replace(
+            "    sf->cur_func = (JSValue)func_obj;\n    init_list_head(&sf->var_ref_list);\n",
+            "    sf->cur_func = (JSValue)func_obj;\n    sf->debug_line = -1;\n    init_list_head(&sf->var_ref_list);\n",
+        )
+        .replace(
+            "    sf->cur_func = JS_DupValue(ctx, func_obj);\n",
+            "    sf->cur_func = JS_DupValue(ctx, func_obj);\n    sf->debug_line = -1;\n",
+        )
+}
+
+/// Add the parse only mode of evaluation, and the position of the syntax errors.
+fn patch_syntax_check(content: &str) -> String {
+    content
+        .replace(
+            "typedef struct JSParseState {\n",
+            "#define JS_EVAL_FLAG_PARSE_ONLY (1 << 6) /* only check the syntax */\n\ntypedef struct JSParseState {\n",
+        )
+        .replace(
+            "    const uint8_t *buf_end;\n\n    /* current function code */\n",
+            "    const uint8_t *buf_end;\n    const uint8_t *buf_start;\n\n    /* current function code */\n",
+        )
+        .replace(
+            "    s->buf_end = s->buf_ptr + input_len;\n",
+            "    s->buf_end = s->buf_ptr + input_len;\n    s->buf_start = s->buf_ptr;\n",
+        )
+        .replace(
+            "    build_backtrace(ctx, ctx->current_exception, s->filename, s->line_num, NULL);\n    return -1;\n",
+            r#"    build_backtrace(ctx, ctx->current_exception, s->filename, s->line_num, NULL);
+    {
+        /* the position of the current token */
+        const uint8_t *p, *ptr = s->token.ptr ? s->token.ptr : s->buf_ptr;
+        int column_num = 1;
+
+        for(p = ptr; p > s->buf_start && p[-1] != '\n'; p--) {
+            if ((p[-1] & 0xc0) != 0x80)
+                column_num++;
+        }
+        JS_DefinePropertyValueStr(ctx, ctx->current_exception, "lineNumber",
+                                  JS_NewInt32(ctx, s->token.ptr ? s->token.line_num : s->line_num),
+                                  JS_PROP_WRITABLE | JS_PROP_CONFIGURABLE);
+        JS_DefinePropertyValueStr(ctx, ctx->current_exception, "columnNumber",
+                                  JS_NewInt32(ctx, column_num),
+                                  JS_PROP_WRITABLE | JS_PROP_CONFIGURABLE);
+    }
+    return -1;
+"#,
+        )
+        .replace(
+            "    /* create the function object and all the enclosed functions */\n    fun_obj = js_create_function(ctx, fd);\n",
+            r#"    if (flags & JS_EVAL_FLAG_PARSE_ONLY) {
+        free_token(s, &s->token);
+        js_free_function_def(ctx, fd);
+        if (m)
+            js_free_module_def(ctx, m);
+        return JS_UNDEFINED;
+    }
+
+    /* create the function object and all the enclosed functions */
+    fun_obj = js_create_function(ctx, fd);
+"#,
+        )
+}
+
+/// Expose the backing store of the fast arrays and typed arrays, see `JS_GetTypedArrayElements`.
+fn patch_fast_array(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+/* Access the values of a fast array, which are borrowed from the array */
+BOOL JS_GetFastArray(JSContext *ctx, JSValueConst obj,
+                     JSValue **arrpp, uint32_t *countp)
+{
+    return js_get_fast_array(ctx, obj, arrpp, countp);
+}
+
+#define JS_TYPED_ARRAY_UINT8C    0
+#define JS_TYPED_ARRAY_INT8      1
+#define JS_TYPED_ARRAY_UINT8     2
+#define JS_TYPED_ARRAY_INT16     3
+#define JS_TYPED_ARRAY_UINT16    4
+#define JS_TYPED_ARRAY_INT32     5
+#define JS_TYPED_ARRAY_UINT32    6
+#define JS_TYPED_ARRAY_BIG_INT64 7
+#define JS_TYPED_ARRAY_BIG_UINT64 8
+#define JS_TYPED_ARRAY_FLOAT32   9
+#define JS_TYPED_ARRAY_FLOAT64   10
+
+/* Access the elements of a typed array, returns the element type or -1 */
+int JS_GetTypedArrayElements(JSContext *ctx, JSValueConst obj,
+                             void **pdata, uint32_t *pcount)
+{
+    JSObject *p;
+
+    if (JS_VALUE_GET_TAG(obj) != JS_TAG_OBJECT)
+        return -1;
+    p = JS_VALUE_GET_OBJ(obj);
+    if (p->class_id < JS_CLASS_UINT8C_ARRAY ||
+        p->class_id > JS_CLASS_FLOAT64_ARRAY ||
+        typed_array_is_detached(ctx, p))
+        return -1;
+    *pdata = p->u.array.u.ptr;
+    *pcount = p->u.array.count;
+    switch(p->class_id) {
+    case JS_CLASS_UINT8C_ARRAY: return JS_TYPED_ARRAY_UINT8C;
+    case JS_CLASS_INT8_ARRAY: return JS_TYPED_ARRAY_INT8;
+    case JS_CLASS_UINT8_ARRAY: return JS_TYPED_ARRAY_UINT8;
+    case JS_CLASS_INT16_ARRAY: return JS_TYPED_ARRAY_INT16;
+    case JS_CLASS_UINT16_ARRAY: return JS_TYPED_ARRAY_UINT16;
+    case JS_CLASS_INT32_ARRAY: return JS_TYPED_ARRAY_INT32;
+    case JS_CLASS_UINT32_ARRAY: return JS_TYPED_ARRAY_UINT32;
+#ifdef CONFIG_BIGNUM
+    case JS_CLASS_BIG_INT64_ARRAY: return JS_TYPED_ARRAY_BIG_INT64;
+    case JS_CLASS_BIG_UINT64_ARRAY: return JS_TYPED_ARRAY_BIG_UINT64;
+#endif
+    case JS_CLASS_FLOAT32_ARRAY: return JS_TYPED_ARRAY_FLOAT32;
+    case JS_CLASS_FLOAT64_ARRAY: return JS_TYPED_ARRAY_FLOAT64;
+    default: return -1;
+    }
+}
+"#,
+    );
+
+    content
+}
+
+/// Add the accessors of the atom table and shapes, which are not covered by `JS_ComputeMemoryUsage`.
+fn patch_engine_stats(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+typedef struct JSEngineStats {
+    int64_t atom_count;
+    int64_t atom_size;
+    int64_t atom_hash_size;
+    int64_t shape_hash_count;
+    int64_t shape_hash_size;
+} JSEngineStats;
+
+/* Get the statistics of the atom table and the shape hash table */
+void JS_GetEngineStats(JSRuntime *rt, JSEngineStats *s)
+{
+    s->atom_count = rt->atom_count;
+    s->atom_size = rt->atom_size;
+    s->atom_hash_size = rt->atom_hash_size;
+    s->shape_hash_count = rt->shape_hash_count;
+    s->shape_hash_size = rt->shape_hash_size;
+}
+
+/* Returns the shape of an object or NULL, the shape is shared by the objects with the same layout */
+const void *JS_GetObjectShape(JSValueConst obj, int *prop_count, BOOL *is_hashed)
+{
+    JSObject *p;
+
+    if (JS_VALUE_GET_TAG(obj) != JS_TAG_OBJECT)
+        return NULL;
+    p = JS_VALUE_GET_OBJ(obj);
+    *prop_count = p->shape->prop_count;
+    *is_hashed = p->shape->is_hashed;
+    return p->shape;
+}
+"#,
+    );
+
+    content
+}
+
+/// Track the column numbers of the opcodes, and append them to the locations of the backtrace.
+///
+/// The column is encoded with the line in the operand of `OP_line_num`,
+/// and saved in a `pc2col` table beside the `pc2line` table, which is not serialized to the bytecode.
+fn patch_column_numbers(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "#define PC2LINE_DIFF_PC_MAX ((255 - PC2LINE_OP_FIRST) / PC2LINE_RANGE)\n",
+            r#"#define PC2LINE_DIFF_PC_MAX ((255 - PC2LINE_OP_FIRST) / PC2LINE_RANGE)
+
+/* the operand of OP_line_num: a line number, or a line and column number with the flag */
+#define JS_POS_COLUMN_FLAG  (1 << 30)
+#define JS_POS_COLUMN_SHIFT 20
+#define JS_POS_LINE_MAX     ((1 << JS_POS_COLUMN_SHIFT) - 1)
+#define JS_POS_COLUMN_MAX   ((1 << 10) - 1)
+
+static inline int js_pos_encode(int line_num, int column_num)
+{
+    if (line_num < 0 || line_num > JS_POS_LINE_MAX ||
+        column_num <= 0 || column_num > JS_POS_COLUMN_MAX)
+        return line_num;
+    return JS_POS_COLUMN_FLAG | (column_num << JS_POS_COLUMN_SHIFT) | line_num;
+}
+
+static inline int js_pos_line(int pos)
+{
+    return (pos >= 0 && (pos & JS_POS_COLUMN_FLAG)) ? (pos & JS_POS_LINE_MAX) : pos;
+}
+
+static inline int js_pos_column(int pos)
+{
+    return (pos >= 0 && (pos & JS_POS_COLUMN_FLAG)) ?
+        ((pos >> JS_POS_COLUMN_SHIFT) & JS_POS_COLUMN_MAX) : 0;
+}
+"#,
+        ),
+        (
+            "        int pc2line_len;\n        uint8_t *pc2line_buf;\n",
+            "        int pc2line_len;\n        uint8_t *pc2line_buf;\n        int pc2col_len;\n        uint8_t *pc2col_buf;\n",
+        ),
+        (
+            "/* in order to avoid executing arbitrary code during the stack trace\n",
+            r#"/* returns the column number of the pc, or 0 if unknown */
+static int find_column_num(JSFunctionBytecode *b, uint32_t pc_value)
+{
+    const uint8_t *p_end, *p;
+    uint32_t pc, val;
+    int column_num, ret;
+
+    if (!b->has_debug || !b->debug.pc2col_buf)
+        return 0;
+
+    p = b->debug.pc2col_buf;
+    p_end = p + b->debug.pc2col_len;
+    pc = 0;
+    column_num = 0;
+    while (p < p_end) {
+        ret = get_leb128(&val, p, p_end);
+        if (ret < 0)
+            return 0;
+        pc += val;
+        p += ret;
+        ret = get_leb128(&val, p, p_end);
+        if (ret < 0)
+            return 0;
+        p += ret;
+        if (pc_value < pc)
+            return column_num;
+        column_num = val;
+    }
+    return column_num;
+}
+
+/* in order to avoid executing arbitrary code during the stack trace
+"#,
+        ),
+        (
+            "            int line_num1;\n",
+            "            int line_num1, column_num1 = 0;\n",
+        ),
+        (
+            "                        line_num1 = find_line_num(ctx, b,\n                                                  cur_pc - b->byte_code_buf - 1);\n",
+            "                        line_num1 = find_line_num(ctx, b,\n                                                  cur_pc - b->byte_code_buf - 1);\n                        column_num1 = find_column_num(b, cur_pc - b->byte_code_buf - 1);\n",
+        ),
+        (
+            "                    line_num1 = find_line_num(ctx, b,\n                                              sf->cur_pc - b->byte_code_buf - 1);\n",
+            "                    line_num1 = find_line_num(ctx, b,\n                                              sf->cur_pc - b->byte_code_buf - 1);\n                    column_num1 = find_column_num(b, sf->cur_pc - b->byte_code_buf - 1);\n",
+        ),
+        (
+            "                if (line_num1 != -1)\n                    dbuf_printf(&dbuf, \":%d\", line_num1);\n",
+            "                if (line_num1 != -1) {\n                    dbuf_printf(&dbuf, \":%d\", line_num1);\n                    if (column_num1 > 0)\n                        dbuf_printf(&dbuf, \":%d\", column_num1);\n                }\n",
+        ),
+        (
+            "    DynBuf pc2line;\n",
+            "    DynBuf pc2line;\n    DynBuf pc2col; /* the column numbers of the pc */\n",
+        ),
+        (
+            "    int last_line_num;  /* line number of last token */\n",
+            r#"    int last_line_num;  /* line number of last token */
+    int last_column_num; /* column number of last token, or 0 if unknown */
+    const uint8_t *last_token_ptr;
+    const uint8_t *column_ptr; /* the last position with a computed column */
+    int column_num;
+"#,
+        ),
+        (
+            "static __exception int next_token(JSParseState *s)\n{\n",
+            r#"/* returns the column number of a position, counting the UTF-8 characters */
+static int js_parse_column_num(JSParseState *s, const uint8_t *ptr)
+{
+    const uint8_t *p;
+    int column_num;
+
+    if (!ptr)
+        return 0;
+    if (!s->column_ptr || ptr < s->column_ptr) {
+        column_num = 1;
+        for(p = ptr; p > s->buf_start && p[-1] != '\n'; p--) {
+            if ((p[-1] & 0xc0) != 0x80)
+                column_num++;
+        }
+    } else {
+        column_num = s->column_num;
+        for(p = s->column_ptr; p < ptr; p++) {
+            if (*p == '\n')
+                column_num = 1;
+            else if ((*p & 0xc0) != 0x80)
+                column_num++;
+        }
+    }
+    s->column_ptr = ptr;
+    s->column_num = column_num;
+    return column_num;
+}
+
+static void js_parse_update_last_pos(JSParseState *s)
+{
+    s->last_line_num = s->token.line_num;
+    s->last_column_num = js_parse_column_num(s, s->token.ptr);
+    s->last_token_ptr = s->token.ptr;
+}
+
+static __exception int next_token(JSParseState *s)
+{
+"#,
+        ),
+        (
+            "    s->last_line_num = s->token.line_num;\n redo:\n",
+            "    js_parse_update_last_pos(s);\n redo:\n",
+        ),
+        (
+            "        s->last_line_num = s->token.line_num;\n        if (js_parse_template_part(s, s->buf_ptr))\n",
+            "        js_parse_update_last_pos(s);\n        if (js_parse_template_part(s, s->buf_ptr))\n",
+        ),
+        (
+            "    BOOL got_lf;\n    const uint8_t *ptr;\n} JSParsePos;\n",
+            "    BOOL got_lf;\n    const uint8_t *ptr;\n    const uint8_t *last_token_ptr;\n} JSParsePos;\n",
+        ),
+        (
+            "    sp->ptr = s->token.ptr;\n",
+            "    sp->ptr = s->token.ptr;\n    sp->last_token_ptr = s->last_token_ptr;\n",
+        ),
+        (
+            "    s->buf_ptr = sp->ptr;\n",
+            "    s->buf_ptr = sp->ptr;\n    s->token.ptr = sp->last_token_ptr;\n",
+        ),
+        (
+            r#"    if (unlikely(fd->last_opcode_line_num != s->last_line_num)) {
+        dbuf_putc(bc, OP_line_num);
+        dbuf_put_u32(bc, s->last_line_num);
+        fd->last_opcode_line_num = s->last_line_num;
+    }
+"#,
+            r#"    pos = js_pos_encode(s->last_line_num, s->last_column_num);
+    if (unlikely(fd->last_opcode_line_num != pos)) {
+        dbuf_putc(bc, OP_line_num);
+        dbuf_put_u32(bc, pos);
+        fd->last_opcode_line_num = pos;
+    }
+"#,
+        ),
+        (
+            "    JSFunctionDef *fd = s->cur_func;\n    DynBuf *bc = &fd->byte_code;\n\n    /* Use the line number of the last token used",
+            "    JSFunctionDef *fd = s->cur_func;\n    DynBuf *bc = &fd->byte_code;\n    int pos;\n\n    /* Use the line number of the last token used",
+        ),
+        (
+            "    js_dbuf_init(ctx, &fd->pc2line);\n",
+            "    js_dbuf_init(ctx, &fd->pc2line);\n    js_dbuf_init(ctx, &fd->pc2col);\n",
+        ),
+        (
+            "    dbuf_free(&fd->pc2line);\n\n    js_free(ctx, fd->source);\n",
+            "    dbuf_free(&fd->pc2line);\n    dbuf_free(&fd->pc2col);\n\n    js_free(ctx, fd->source);\n",
+        ),
+        (
+            "line1 = get_u32(tab + pos + 1) - line_num + 1;",
+            "line1 = js_pos_line(get_u32(tab + pos + 1)) - line_num + 1;",
+        ),
+        (
+            "        uint32_t last_pc = 0;\n        int i;\n\n        js_dbuf_init(s->ctx, &s->pc2line);\n",
+            "        uint32_t last_pc = 0, last_column_pc = 0;\n        int i, last_column_num = 0;\n\n        js_dbuf_init(s->ctx, &s->pc2line);\n        js_dbuf_init(s->ctx, &s->pc2col);\n",
+        ),
+        (
+            "            int line_num = s->line_number_slots[i].line_num;\n            int diff_pc, diff_line;\n\n            if (line_num < 0)\n                continue;\n",
+            r#"            int line_num = js_pos_line(s->line_number_slots[i].line_num);
+            int column_num = js_pos_column(s->line_number_slots[i].line_num);
+            int diff_pc, diff_line;
+
+            if (line_num < 0)
+                continue;
+
+            if (column_num != last_column_num && pc >= last_column_pc) {
+                dbuf_put_leb128(&s->pc2col, pc - last_column_pc);
+                dbuf_put_leb128(&s->pc2col, column_num);
+                last_column_pc = pc;
+                last_column_num = column_num;
+            }
+"#,
+        ),
+        (
+            "        dbuf_free(&fd->pc2line);    // probably useless\n",
+            "        dbuf_free(&fd->pc2line);    // probably useless\n        dbuf_free(&fd->pc2col);\n",
+        ),
+        (
+            "        b->debug.pc2line_len = fd->pc2line.size;\n",
+            r#"        b->debug.pc2line_len = fd->pc2line.size;
+        if (fd->pc2col.size) {
+            b->debug.pc2col_buf = js_realloc(ctx, fd->pc2col.buf, fd->pc2col.size);
+            if (!b->debug.pc2col_buf)
+                b->debug.pc2col_buf = fd->pc2col.buf;
+            b->debug.pc2col_len = fd->pc2col.size;
+        } else {
+            dbuf_free(&fd->pc2col);
+        }
+"#,
+        ),
+        (
+            "        js_free_rt(rt, b->debug.pc2line_buf);\n",
+            "        js_free_rt(rt, b->debug.pc2line_buf);\n        js_free_rt(rt, b->debug.pc2col_buf);\n",
+        ),
+        // the default class constructor is parsed from a static string outside of the source
+        (
+            "    const uint8_t *saved_buf_end;\n",
+            "    const uint8_t *saved_buf_end, *saved_buf_start;\n",
+        ),
+        (
+            "    saved_buf_end = s->buf_end;\n",
+            "    saved_buf_end = s->buf_end;\n    saved_buf_start = s->buf_start;\n    s->buf_start = (uint8_t *)str;\n    s->column_ptr = NULL;\n",
+        ),
+        (
+            "    s->buf_end = saved_buf_end;\n",
+            "    s->buf_end = saved_buf_end;\n    s->buf_start = saved_buf_start;\n    s->column_ptr = NULL;\n",
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch column numbers, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    Ok(content)
+}
+
+/// Limit the frames of the backtrace, and hide the frames rejected by the filter.
+fn patch_backtrace_filter(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "struct JSRuntime {\n",
+            r#"/* returns FALSE to hide the frame from the backtrace, filename is JS_ATOM_NULL for the native functions */
+typedef BOOL JSBacktraceFilter(JSContext *ctx, const char *func_name,
+                               JSAtom filename, int line_num, int column_num,
+                               void *opaque);
+
+struct JSRuntime {
+"#,
+        ),
+        (
+            "    JSInterruptHandler *interrupt_handler;\n",
+            "    JSInterruptHandler *interrupt_handler;\n    int backtrace_limit; /* the maximum frames of the backtrace, or -1 if unlimited */\n    JSBacktraceFilter *backtrace_filter;\n    void *backtrace_filter_opaque;\n",
+        ),
+        (
+            "    rt->malloc_gc_threshold = 256 * 1024;\n",
+            "    rt->malloc_gc_threshold = 256 * 1024;\n    rt->backtrace_limit = -1;\n",
+        ),
+        (
+            "/* if filename != NULL, an additional level is added with the filename\n",
+            r#"static BOOL js_backtrace_filter_frame(JSContext *ctx, JSStackFrame *sf,
+                                      const uint8_t *cur_pc)
+{
+    JSRuntime *rt = ctx->rt;
+    const char *func_name_str;
+    JSAtom filename = JS_ATOM_NULL;
+    int line_num = -1, column_num = 0;
+    JSObject *p;
+    BOOL ret;
+
+    p = JS_VALUE_GET_OBJ(sf->cur_func);
+    if (js_class_has_bytecode(p->class_id)) {
+        JSFunctionBytecode *b = p->u.func.function_bytecode;
+
+        if (b->has_debug) {
+            filename = b->debug.filename;
+            if (sf != ctx->current_stack_frame)
+                cur_pc = sf->cur_pc;
+            if (!cur_pc) {
+                line_num = b->debug.line_num;
+            } else {
+                line_num = find_line_num(ctx, b, cur_pc - b->byte_code_buf - 1);
+                column_num = find_column_num(b, cur_pc - b->byte_code_buf - 1);
+            }
+        }
+    }
+    func_name_str = get_func_name(ctx, sf->cur_func);
+    ret = rt->backtrace_filter(ctx, func_name_str ? func_name_str : "", filename,
+                               line_num, column_num, rt->backtrace_filter_opaque);
+    JS_FreeCString(ctx, func_name_str);
+    return ret;
+}
+
+/* if filename != NULL, an additional level is added with the filename
+"#,
+        ),
+        (
+            "    const char *str1;\n    JSObject *p;\n\n    ctx->exception_needs_backtrace = FALSE;\n",
+            "    const char *str1;\n    JSObject *p;\n    int frame_count = 0;\n\n    ctx->exception_needs_backtrace = FALSE;\n",
+        ),
+        (
+            "    for(sf = ctx->current_stack_frame; sf != NULL; sf = sf->prev_frame) {\n        func_name_str = get_func_name(ctx, sf->cur_func);\n",
+            r#"    for(sf = ctx->current_stack_frame; sf != NULL; sf = sf->prev_frame) {
+        if (ctx->rt->backtrace_limit >= 0 &&
+            frame_count >= ctx->rt->backtrace_limit)
+            break;
+        if (ctx->rt->backtrace_filter &&
+            !js_backtrace_filter_frame(ctx, sf, cur_pc))
+            continue;
+        frame_count++;
+        func_name_str = get_func_name(ctx, sf->cur_func);
+"#,
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch backtrace filter, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Set the maximum frames of the backtrace, or -1 if unlimited */
+void JS_SetBacktraceLimit(JSRuntime *rt, int limit)
+{
+    rt->backtrace_limit = limit;
+}
+
+int JS_GetBacktraceLimit(JSRuntime *rt)
+{
+    return rt->backtrace_limit;
+}
+
+/* Set the filter of the backtrace frames, or NULL to show all the frames */
+void JS_SetBacktraceFilter(JSRuntime *rt, JSBacktraceFilter *filter, void *opaque)
+{
+    rt->backtrace_filter = filter;
+    rt->backtrace_filter_opaque = opaque;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Call the hook with the `stack` property of the errors before it's defined.
+fn patch_prepare_stack_trace(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "struct JSRuntime {\n",
+            r#"/* returns the new stack of the error, the stack is freed by the hook */
+typedef JSValue JSPrepareStackTrace(JSContext *ctx, JSValueConst error_obj,
+                                    JSValue stack, void *opaque);
+
+struct JSRuntime {
+"#,
+        ),
+        (
+            "    JSInterruptHandler *interrupt_handler;\n",
+            "    JSInterruptHandler *interrupt_handler;\n    JSPrepareStackTrace *prepare_stack_trace;\n    void *prepare_stack_trace_opaque;\n    BOOL in_prepare_stack_trace;\n",
+        ),
+        (
+            "    dbuf_free(&dbuf);\n    JS_DefinePropertyValue(ctx, error_obj, JS_ATOM_stack, str,\n",
+            r#"    dbuf_free(&dbuf);
+    if (ctx->rt->prepare_stack_trace && !ctx->rt->in_prepare_stack_trace) {
+        /* the errors created by the hook keep the original stack */
+        ctx->rt->in_prepare_stack_trace = TRUE;
+        str = ctx->rt->prepare_stack_trace(ctx, error_obj, str,
+                                           ctx->rt->prepare_stack_trace_opaque);
+        ctx->rt->in_prepare_stack_trace = FALSE;
+    }
+    JS_DefinePropertyValue(ctx, error_obj, JS_ATOM_stack, str,
+"#,
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch prepare stack trace, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Set the hook to rewrite the stack of the errors, or NULL to remove it */
+void JS_SetPrepareStackTrace(JSRuntime *rt, JSPrepareStackTrace *hook, void *opaque)
+{
+    rt->prepare_stack_trace = hook;
+    rt->prepare_stack_trace_opaque = opaque;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Export the equality operators of the interpreter.
+fn patch_equality(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+/* Returns the result of `op1 === op2` */
+int JS_StrictEq(JSContext *ctx, JSValueConst op1, JSValueConst op2)
+{
+    return js_strict_eq(ctx, JS_DupValue(ctx, op1), JS_DupValue(ctx, op2));
+}
+
+/* Returns the result of `Object.is(op1, op2)` */
+int JS_SameValue(JSContext *ctx, JSValueConst op1, JSValueConst op2)
+{
+    return js_same_value(ctx, op1, op2);
+}
+
+/* Returns the result of `op1 == op2`, or -1 if an exception is thrown by the conversions */
+int JS_LooseEq(JSContext *ctx, JSValueConst op1, JSValueConst op2)
+{
+    JSValue sp[2];
+
+    sp[0] = JS_DupValue(ctx, op1);
+    sp[1] = JS_DupValue(ctx, op2);
+    if (js_eq_slow(ctx, sp + 2, FALSE))
+        return -1;
+    return JS_VALUE_GET_BOOL(sp[0]);
+}
+"#,
+    );
+
+    content
+}
+
+/// Allow to scrub the global objects and forget the loaded modules without freeing the context.
+///
+/// The forgotten modules are retired instead of freed, because the functions may still refer to them.
+fn patch_context_reset(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "    struct list_head loaded_modules; /* list of JSModuleDef.link */\n",
+            "    struct list_head loaded_modules; /* list of JSModuleDef.link */\n    struct list_head retired_modules; /* list of JSModuleDef.link */\n",
+        ),
+        (
+            "    init_list_head(&ctx->loaded_modules);\n",
+            "    init_list_head(&ctx->loaded_modules);\n    init_list_head(&ctx->retired_modules);\n",
+        ),
+        (
+            "static void js_free_modules(JSContext *ctx, JSFreeModuleEnum flag)\n{\n    struct list_head *el, *el1;\n",
+            r#"static void js_free_modules(JSContext *ctx, JSFreeModuleEnum flag)
+{
+    struct list_head *el, *el1;
+    if (flag == JS_FREE_MODULE_ALL) {
+        list_for_each_safe(el, el1, &ctx->retired_modules) {
+            list_del(el);
+            list_add_tail(el, &ctx->loaded_modules);
+        }
+    }
+"#,
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch context reset, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Forget the loaded modules, the unreferenced ones are freed and the others are retired */
+void JS_ResetModules(JSContext *ctx)
+{
+    struct list_head *el, *el1;
+    JSModuleDef *m;
+    BOOL changed;
+    int i;
+
+    list_for_each_safe(el, el1, &ctx->loaded_modules) {
+        list_del(el);
+        list_add_tail(el, &ctx->retired_modules);
+    }
+
+    /* keep the modules referenced by a handle, and the modules required by them */
+    list_for_each(el, &ctx->retired_modules) {
+        m = list_entry(el, JSModuleDef, link);
+        m->eval_mark = m->header.ref_count > 1;
+    }
+    do {
+        changed = FALSE;
+        list_for_each(el, &ctx->retired_modules) {
+            m = list_entry(el, JSModuleDef, link);
+            if (!m->eval_mark)
+                continue;
+            for(i = 0; i < m->req_module_entries_count; i++) {
+                JSModuleDef *m1 = m->req_module_entries[i].module;
+                if (m1 && !m1->eval_mark) {
+                    m1->eval_mark = TRUE;
+                    changed = TRUE;
+                }
+            }
+        }
+    } while (changed);
+
+    list_for_each_safe(el, el1, &ctx->retired_modules) {
+        m = list_entry(el, JSModuleDef, link);
+        if (m->eval_mark)
+            m->eval_mark = FALSE;
+        else
+            js_free_module_def(ctx, m);
+    }
+}
+
+/* Returns the object of the global let, const and class definitions */
+JSValue JS_GetGlobalVarObject(JSContext *ctx)
+{
+    return JS_DupValue(ctx, ctx->global_var_obj);
+}
+
+/* Delete an own property even if it is not configurable, returns FALSE if the property does not exist */
+int JS_ScrubProperty(JSContext *ctx, JSValueConst obj, JSAtom prop)
+{
+    JSObject *p;
+    JSShapeProperty *prs;
+    JSProperty *pr;
+
+    if (JS_VALUE_GET_TAG(obj) != JS_TAG_OBJECT) {
+        JS_ThrowTypeErrorNotAnObject(ctx);
+        return -1;
+    }
+    p = JS_VALUE_GET_OBJ(obj);
+    prs = find_own_property(&pr, p, prop);
+    if (!prs)
+        return FALSE;
+    if (js_update_property_flags(ctx, p, &prs, prs->flags | JS_PROP_CONFIGURABLE))
+        return -1;
+    return delete_property(ctx, p, prop);
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Add the `HostPromiseRejectionTracker` hook, which is called when a promise is rejected without any handler,
+/// and when a handler is added to such a rejected promise later.
+fn patch_promise_rejection_tracker(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "struct JSRuntime {\n",
+            r#"typedef void JSHostPromiseRejectionTracker(JSContext *ctx, JSValueConst promise,
+                                           JSValueConst reason, BOOL is_handled,
+                                           void *opaque);
+
+struct JSRuntime {
+"#,
+        ),
+        (
+            "    JSInterruptHandler *interrupt_handler;\n",
+            "    JSInterruptHandler *interrupt_handler;\n    JSHostPromiseRejectionTracker *host_promise_rejection_tracker;\n    void *host_promise_rejection_tracker_opaque;\n",
+        ),
+        (
+            "    /* Note: could call HostPromiseRejectTracker */\n",
+            r#"    if (is_reject && !s->is_handled && ctx->rt->host_promise_rejection_tracker) {
+        ctx->rt->host_promise_rejection_tracker(ctx, promise, value, FALSE,
+                                                ctx->rt->host_promise_rejection_tracker_opaque);
+    }
+"#,
+        ),
+        (
+            "    s->is_handled = TRUE;\n",
+            r#"    if (s->promise_state == JS_PROMISE_REJECTED && !s->is_handled &&
+        ctx->rt->host_promise_rejection_tracker) {
+        ctx->rt->host_promise_rejection_tracker(ctx, promise, s->promise_result, TRUE,
+                                                ctx->rt->host_promise_rejection_tracker_opaque);
+    }
+    s->is_handled = TRUE;
+"#,
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!(
+                "patch promise rejection tracker, unexpected `{}`",
+                from.trim()
+            );
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Set the tracker of the unhandled promise rejections, or NULL to remove it */
+void JS_SetHostPromiseRejectionTracker(JSRuntime *rt, JSHostPromiseRejectionTracker *cb, void *opaque)
+{
+    rt->host_promise_rejection_tracker = cb;
+    rt->host_promise_rejection_tracker_opaque = opaque;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Enumerate the live contexts and objects of the runtime, to report the leaks before freeing it.
+fn patch_live_objects(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+/* Returns the number of the contexts which are not freed */
+int JS_CountLiveContexts(JSRuntime *rt)
+{
+    struct list_head *el;
+    int count = 0;
+
+    list_for_each(el, &rt->context_list) {
+        count++;
+    }
+    return count;
+}
+
+typedef void JSLiveObjectFunc(void *opaque, const char *class_name, int ref_count);
+
+/* Call the function with the class name and the reference count of the live objects */
+void JS_ForEachLiveObject(JSRuntime *rt, JSLiveObjectFunc *func, void *opaque)
+{
+    struct list_head *el;
+    char buf[ATOM_GET_STR_BUF_SIZE];
+
+    list_for_each(el, &rt->obj_list) {
+        JSObject *p = list_entry(el, JSObject, link);
+        func(opaque, JS_AtomGetStrRT(rt, buf, sizeof(buf), rt->class_array[p->class_id].class_name),
+             p->header.ref_count);
+    }
+}
+"#,
+    );
+
+    content
+}
+
+fn patch_utf16(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+/* Returns a new string of the UTF-16 code units, which may contain the lone surrogates */
+JSValue JS_NewStringUTF16(JSContext *ctx, const uint16_t *buf, size_t len)
+{
+    JSString *str;
+    size_t i;
+
+    if (len > JS_STRING_LEN_MAX)
+        return JS_ThrowRangeError(ctx, "invalid string length");
+    for (i = 0; i < len && buf[i] < 0x100; i++)
+        continue;
+    if (i < len)
+        return js_new_string16(ctx, buf, len);
+    if (len == 0)
+        return JS_AtomToString(ctx, JS_ATOM_empty_string);
+    str = js_alloc_string(ctx, len, 0);
+    if (!str)
+        return JS_EXCEPTION;
+    for (i = 0; i < len; i++)
+        str->u.str8[i] = buf[i];
+    str->u.str8[len] = '\0';
+    return JS_MKPTR(JS_TAG_STRING, str);
+}
+
+/* Copies at most `size` UTF-16 code units of a string to `buf`,
+   returns the length of the string, or -1 if it is not a string */
+int JS_GetStringUTF16(JSValueConst val, uint16_t *buf, uint32_t size)
+{
+    JSString *p;
+    uint32_t i, n;
+
+    if (JS_VALUE_GET_TAG(val) != JS_TAG_STRING)
+        return -1;
+    p = JS_VALUE_GET_STRING(val);
+    n = min_uint32(p->len, size);
+    if (p->is_wide_char) {
+        memcpy(buf, p->u.str16, n * 2);
+    } else {
+        for (i = 0; i < n; i++)
+            buf[i] = p->u.str8[i];
+    }
+    return p->len;
+}
+"#,
+    );
+
+    content
+}
+
+/// Switch the language extensions of the runtime, instead of the `CONFIG_BIGNUM` of the build.
+///
+/// The flags are checked by the parser and the operators, the builtin objects of the extensions
+/// are removed from the new contexts by the binding.
+fn patch_engine_config(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "struct JSRuntime {\n",
+            r#"#define JS_CONFIG_BIGNUM_EXT            (1 << 0) /* BigFloat literals and "use bigint" */
+#define JS_CONFIG_OPERATOR_OVERLOADING  (1 << 1) /* Symbol.operatorXXX methods */
+#define JS_CONFIG_MATH_MODE             (1 << 2) /* "use math" */
+#define JS_CONFIG_ANNEX_B               (1 << 3) /* html comments and __proto__ in object literals */
+#define JS_CONFIG_DEFAULT               ((1 << 4) - 1)
+
+struct JSRuntime {
+"#,
+        ),
+        (
+            "    void *interrupt_opaque;\n",
+            "    void *interrupt_opaque;\n    int engine_config; /* the JS_CONFIG_x flags */\n",
+        ),
+        (
+            "    rt->malloc_gc_threshold = 256 * 1024;\n",
+            "    rt->malloc_gc_threshold = 256 * 1024;\n    rt->engine_config = JS_CONFIG_DEFAULT;\n",
+        ),
+        (
+            "    bool_result = FALSE;\n    swap_op = FALSE;\n",
+            r#"    if (!(ctx->rt->engine_config & JS_CONFIG_OPERATOR_OVERLOADING)) {
+        *pret = JS_UNDEFINED;
+        return 0;
+    }
+    bool_result = FALSE;
+    swap_op = FALSE;
+"#,
+        ),
+        (
+            "    if (JS_IsObject(op1)) {\n        switch(op) {\n        case OP_plus:\n",
+            "    if (JS_IsObject(op1) &&\n        (ctx->rt->engine_config & JS_CONFIG_OPERATOR_OVERLOADING)) {\n        switch(op) {\n        case OP_plus:\n",
+        ),
+        (
+            "    if (JS_IsObject(op1)) {\n        method = JS_GetProperty(ctx, op1, JS_ATOM_Symbol_operatorNot);\n",
+            "    if (JS_IsObject(op1) &&\n        (ctx->rt->engine_config & JS_CONFIG_OPERATOR_OVERLOADING)) {\n        method = JS_GetProperty(ctx, op1, JS_ATOM_Symbol_operatorNot);\n",
+        ),
+        (
+            "                } else if (*p == 'l') {\n",
+            "                } else if (*p == 'l' &&\n                           (s->ctx->rt->engine_config & JS_CONFIG_BIGNUM_EXT)) {\n",
+        ),
+        (
+            "        else if (!strcmp(str, \"use bigint\")) {\n",
+            "        else if (!strcmp(str, \"use bigint\") &&\n                 (s->ctx->rt->engine_config & JS_CONFIG_BIGNUM_EXT)) {\n",
+        ),
+        (
+            "        else if (!strcmp(str, \"use math\")) {\n",
+            "        else if (!strcmp(str, \"use math\") &&\n                 (s->ctx->rt->engine_config & JS_CONFIG_MATH_MODE)) {\n",
+        ),
+        (
+            "            } else if (name == JS_ATOM___proto__) {\n",
+            "            } else if (name == JS_ATOM___proto__ &&\n                       (s->ctx->rt->engine_config & JS_CONFIG_ANNEX_B)) {\n",
+        ),
+        (
+            "    s->allow_html_comments = !s->is_module;\n",
+            "    s->allow_html_comments = !s->is_module &&\n        (ctx->rt->engine_config & JS_CONFIG_ANNEX_B);\n",
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch engine config, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Set the JS_CONFIG_x flags of the language extensions */
+void JS_SetEngineConfig(JSRuntime *rt, int flags)
+{
+    rt->engine_config = flags & JS_CONFIG_DEFAULT;
+}
+
+int JS_GetEngineConfig(JSRuntime *rt)
+{
+    return rt->engine_config;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+fn patch_libunicode(libunicode: &Path) -> Result<(), Error> {
+    let content = fs::read_to_string(libunicode)?;
+
+    // drop the normalization and the Unicode property tables, which are most of the `libunicode` size
+    if cfg!(feature = "lite-unicode") && content.contains("#define CONFIG_ALL_UNICODE\n") {
+        fs::write(
+            libunicode,
+            content
+                .replace("#define CONFIG_ALL_UNICODE\n", "")
+                .as_bytes(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Check the stack overflow from the frames which are shallower than the one created the context,
+/// e.g. the pending jobs are executed by the host, otherwise the stack size underflows.
+fn patch_stack_check(content: &str) -> Result<String, Error> {
+    let from = r#"static inline BOOL js_check_stack_overflow(JSContext *ctx, size_t alloca_size)
+{
+    size_t size;
+    size = ctx->stack_top - js_get_stack_pointer();
+    return unlikely((size + alloca_size) > ctx->stack_size);
+}"#;
+    let to = r#"static inline BOOL js_check_stack_overflow(JSContext *ctx, size_t alloca_size)
+{
+    const uint8_t *sp = js_get_stack_pointer();
+    size_t size;
+    /* the context is used from a shallower frame than the one created it */
+    if (unlikely(sp > ctx->stack_top))
+        ctx->stack_top = sp;
+    size = ctx->stack_top - sp;
+    return unlikely((size + alloca_size) > ctx->stack_size);
+}"#;
+
+    if content.matches(from).count() != 1 {
+        bail!(
+            "patch stack check, unexpected `{}`",
+            from.lines().next().unwrap_or_default()
+        );
+    }
+
+    Ok(content.replacen(from, to, 1))
+}
+
+/// Attach an opaque pointer to the runtime, as the context does.
+fn patch_runtime_opaque(content: &str) -> Result<String, Error> {
+    let from = "    const char *rt_info;\n";
+
+    if content.matches(from).count() != 1 {
+        bail!("patch runtime opaque, unexpected `{}`", from.trim());
+    }
+
+    let mut content = content.replacen(
+        from,
+        "    const char *rt_info;\n    void *user_opaque;\n",
+        1,
+    );
+
+    content.push_str(
+        r#"
+void *JS_GetRuntimeOpaque(JSRuntime *rt)
+{
+    return rt->user_opaque;
+}
+
+void JS_SetRuntimeOpaque(JSRuntime *rt, void *opaque)
+{
+    rt->user_opaque = opaque;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Record the owning runtime in the shapes, which are shared by the objects of the same layout,
+/// so the host could check the objects passed from another runtime.
+fn patch_value_runtime(content: &str) -> Result<String, Error> {
+    let patches: &[(&str, &str)] = &[
+        (
+            "    JSObject *proto;\n    JSShapeProperty prop[0]; /* prop_size elements */\n",
+            "    JSObject *proto;\n    JSRuntime *rt; /* the owning runtime */\n    JSShapeProperty prop[0]; /* prop_size elements */\n",
+        ),
+        (
+            "    sh->proto = proto;\n    memset(sh->prop_hash_end - hash_size",
+            "    sh->proto = proto;\n    sh->rt = ctx->rt;\n    memset(sh->prop_hash_end - hash_size",
+        ),
+    ];
+
+    let mut content = content.to_owned();
+
+    for (from, to) in patches {
+        if content.matches(from).count() != 1 {
+            bail!("patch value runtime, unexpected `{}`", from.trim());
+        }
+
+        content = content.replacen(from, to, 1);
+    }
+
+    content.push_str(
+        r#"
+/* Returns the owning runtime of an object, or NULL for the other values */
+JSRuntime *JS_GetValueRuntime(JSValueConst v)
+{
+    if (JS_VALUE_GET_TAG(v) != JS_TAG_OBJECT)
+        return NULL;
+    return JS_VALUE_GET_OBJ(v)->shape->rt;
+}
+"#,
+    );
+
+    Ok(content)
+}
+
+/// Expose the native implementations of the C functions and the namespaces of the script modules,
+/// so the host could audit the functions reachable by the scripts.
+fn patch_api_audit(content: &str) -> String {
+    let mut content = content.to_owned();
+
+    content.push_str(
+        r#"
+/* Returns the native implementation of a C function, or NULL for the other values */
+void *JS_GetCFunctionPtr(JSValueConst v)
+{
+    JSObject *p;
+
+    if (JS_VALUE_GET_TAG(v) != JS_TAG_OBJECT)
+        return NULL;
+    p = JS_VALUE_GET_OBJ(v);
+    switch(p->class_id) {
+    case JS_CLASS_C_FUNCTION:
+        return (void *)p->u.cfunc.c_function.generic;
+    case JS_CLASS_C_FUNCTION_DATA:
+        return (void *)p->u.c_function_data_record->func;
+    default:
+        return NULL;
+    }
+}
+
+/* Returns an array of the [name, namespace] pairs of the evaluated script modules */
+JSValue JS_GetScriptModules(JSContext *ctx)
+{
+    struct list_head *el;
+    JSModuleDef *m;
+    JSValue arr, pair, ns;
+    uint32_t idx = 0;
+
+    arr = JS_NewArray(ctx);
+    if (JS_IsException(arr))
+        return arr;
+    list_for_each(el, &ctx->loaded_modules) {
+        m = list_entry(el, JSModuleDef, link);
+        if (m->init_func || !m->evaluated || m->eval_has_exception)
+            continue;
+        ns = js_get_module_ns(ctx, m);
+        if (JS_IsException(ns))
+            goto fail;
+        pair = JS_NewArray(ctx);
+        if (JS_IsException(pair)) {
+            JS_FreeValue(ctx, ns);
+            goto fail;
+        }
+        if (JS_SetPropertyUint32(ctx, pair, 0, JS_AtomToString(ctx, m->module_name)) < 0 ||
+            JS_SetPropertyUint32(ctx, pair, 1, ns) < 0 ||
+            JS_SetPropertyUint32(ctx, arr, idx++, pair) < 0)
+            goto fail;
+    }
+    return arr;
+ fail:
+    JS_FreeValue(ctx, arr);
+    return JS_EXCEPTION;
+}
+"#,
+    );
+
+    content
+}
+
 fn patch_quickjs_libc(quickjs_libc: &Path) -> Result<(), Error> {
     let mut content = fs::read_to_string(quickjs_libc)?;
 
@@ -119,7 +1438,8 @@ fn patch_quickjs_libc(quickjs_libc: &Path) -> Result<(), Error> {
     Ok(())
 }
 
-fn build_libquickjs() -> Result<(), Error> {
+/// Extract and patch the bundled source files.
+fn prepare_source_files() -> Result<(), Error> {
     if !QUICKJS_DIR.join("quickjs.h").is_file() {
         unpack_source_files(
             &CARGO_MANIFEST_DIR.join(QUICKJS_SRC).canonicalize()?,
@@ -133,7 +1453,28 @@ fn build_libquickjs() -> Result<(), Error> {
 
     patch_makefile(&QUICKJS_DIR.join("Makefile"))?;
     patch_quickjs(&QUICKJS_DIR.join("quickjs.c"))?;
+
+    if cfg!(feature = "debugger") {
+        let debugger_c = QUICKJS_DIR.join("debugger.c");
+        let content = fs::read(CARGO_MANIFEST_DIR.join("src/debugger.c"))?;
+
+        if fs::read(&debugger_c).ok().as_ref() != Some(&content) {
+            fs::write(&debugger_c, &content)?;
+
+            // touch `quickjs.c` which includes the hook, so `make` will rebuild it
+            let quickjs_c = QUICKJS_DIR.join("quickjs.c");
+
+            fs::write(&quickjs_c, fs::read(&quickjs_c)?)?;
+        }
+    }
     patch_quickjs_libc(&QUICKJS_DIR.join("quickjs-libc.c"))?;
+    patch_libunicode(&QUICKJS_DIR.join("libunicode.h"))?;
+
+    Ok(())
+}
+
+fn build_libquickjs() -> Result<(), Error> {
+    prepare_source_files()?;
 
     let repl_c = if cfg!(feature = "bignum") {
         "repl-bn.c"
@@ -179,6 +1520,9 @@ fn build_libquickjs() -> Result<(), Error> {
     );
     println!("cargo:rustc-link-lib=static={}", quickjs);
     println!("cargo:rerun-if-changed={}", QUICKJS_SRC);
+    if cfg!(feature = "debugger") {
+        println!("cargo:rerun-if-changed=src/debugger.c");
+    }
 
     if cfg!(feature = "repl") {
         cc::Build::new()
@@ -195,23 +1539,144 @@ fn build_libquickjs() -> Result<(), Error> {
     Ok(())
 }
 
+/// Build the bundled source for the `wasm32` targets with `cc` instead of `make`.
+///
+/// The interpreter is built without the computed goto, the stack check and the `Atomics`,
+/// and the `std` and `os` modules are not available for `wasm32-wasi`.
+fn build_libquickjs_wasm(target_os: &str) -> Result<(), Error> {
+    for (enabled, feature) in &[
+        (cfg!(feature = "repl"), "repl"),
+        (cfg!(feature = "qjscalc"), "qjscalc"),
+        (cfg!(feature = "debugger"), "debugger"),
+    ] {
+        if *enabled {
+            bail!(
+                "the `{}` feature is not supported by the `wasm32` targets",
+                feature
+            );
+        }
+    }
+
+    prepare_source_files()?;
+
+    let version = fs::read_to_string(QUICKJS_DIR.join("VERSION"))?;
+    let mut build = cc::Build::new();
+
+    build
+        .include(QUICKJS_DIR.as_path())
+        .define("_GNU_SOURCE", None)
+        .define("EMSCRIPTEN", None)
+        .define("CONFIG_VERSION", format!("\"{}\"", version.trim()).as_str())
+        .warnings(false);
+
+    for src in &["quickjs.c", "libregexp.c", "libunicode.c", "cutils.c"] {
+        build.file(QUICKJS_DIR.join(src));
+    }
+
+    if cfg!(feature = "bignum") {
+        build
+            .define("CONFIG_BIGNUM", None)
+            .file(QUICKJS_DIR.join("libbf.c"));
+    }
+
+    if target_os == "wasi" {
+        build.file(CARGO_MANIFEST_DIR.join("src/wasi.c"));
+
+        println!("cargo:rerun-if-changed=src/wasi.c");
+    } else {
+        build.file(QUICKJS_DIR.join("quickjs-libc.c"));
+    }
+
+    if cfg!(feature = "debug") {
+        build.opt_level(0).debug(true);
+    }
+
+    build.compile("quickjs");
+
+    println!("cargo:rerun-if-changed={}", QUICKJS_SRC);
+
+    Ok(())
+}
+
+/// Link the system `libquickjs` found by `pkg-config` instead of building the bundled source.
+fn use_system_library() -> bool {
+    println!("cargo:rerun-if-env-changed={}", QUICKJS_SYS_USE_PKG_CONFIG);
+
+    cfg!(feature = "system")
+        || env::var_os(QUICKJS_SYS_USE_PKG_CONFIG).map_or(false, |v| !v.is_empty() && v != "0")
+}
+
+/// Link the system `libquickjs`, the patches of the bundled QuickJS are not applied.
+fn link_system_libquickjs() -> Result<(), Error> {
+    for (enabled, feature) in &[
+        (cfg!(feature = "repl"), "repl"),
+        (cfg!(feature = "qjscalc"), "qjscalc"),
+        (cfg!(feature = "debugger"), "debugger"),
+    ] {
+        if *enabled {
+            bail!(
+                "the `{}` feature is not supported by the system library",
+                feature
+            );
+        }
+    }
+
+    let lib = pkg_config::Config::new()
+        .probe("quickjs")
+        .context("probe system library `quickjs`")?;
+
+    // the bundled bindings are only valid for the bundled version, otherwise they should be generated
+    if !cfg!(feature = "gen") && lib.version.replace('.', "-") != QUICKJS_VERSION {
+        bail!(
+            "the system library `quickjs` {} doesn't match the bundled bindings of {}, enable the `gen` feature",
+            lib.version,
+            QUICKJS_VERSION
+        );
+    }
+
+    fs::write(OUT_DIR.join("VERSION"), &lib.version)?;
+
+    Ok(())
+}
+
+/// The source directory of QuickJS, which contains the headers.
+#[cfg(feature = "gen")]
+fn quickjs_dir() -> Result<PathBuf, Error> {
+    if use_system_library() {
+        let lib = pkg_config::Config::new()
+            .cargo_metadata(false)
+            .probe("quickjs")?;
+
+        lib.include_paths
+            .iter()
+            .flat_map(|dir| vec![dir.clone(), dir.join("quickjs")])
+            .find(|dir| dir.join("quickjs-libc.h").is_file())
+            .ok_or_else(|| failure::err_msg("`quickjs-libc.h` not found in the include paths"))
+    } else {
+        Ok(QUICKJS_DIR.clone())
+    }
+}
+
 #[cfg(feature = "gen")]
 fn gen_binding_files() -> Result<(), Error> {
     use failure::err_msg;
 
     let raw_file = OUT_DIR.join("raw.rs");
+    let quickjs_dir = quickjs_dir()?;
 
     println!("generating binding files to {:?}", raw_file);
 
     bindgen::builder()
-        .header(QUICKJS_DIR.join("quickjs-libc.h").to_string_lossy())
-        .clang_arg(format!("-I{}", QUICKJS_DIR.to_string_lossy()))
+        .header(quickjs_dir.join("quickjs-libc.h").to_string_lossy())
+        .clang_arg(format!("-I{}", quickjs_dir.to_string_lossy()))
         .whitelist_var("JS_.*")
         .whitelist_type("JS.*")
         .whitelist_function("(__)?(JS|JS|js)_.*")
         .opaque_type("FILE")
         .blacklist_type("__.*")
         .default_enum_style(bindgen::EnumVariation::ModuleConsts)
+        .use_core()
+        .ctypes_prefix("::core::ffi")
         .generate()
         .map_err(|_| err_msg("generate binding file"))?
         .write_to_file(raw_file)
@@ -229,7 +1694,19 @@ fn main() -> Result<(), Error> {
     match &env::var("CARGO") {
         Ok(path) if path.ends_with("rls") => {}
         _ => {
-            build_libquickjs().context("build quickjs library")?;
+            println!("cargo:rustc-check-cfg=cfg({})", UNPATCHED_CFG);
+
+            if use_system_library() {
+                link_system_libquickjs().context("link system quickjs library")?;
+
+                println!("cargo:rustc-cfg={}", UNPATCHED_CFG);
+            } else if env::var("CARGO_CFG_TARGET_ARCH").ok().as_deref() == Some("wasm32") {
+                let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+
+                build_libquickjs_wasm(&target_os).context("build quickjs library for wasm32")?;
+            } else {
+                build_libquickjs().context("build quickjs library")?;
+            }
             gen_binding_files().context("generate binding files")?;
         }
     };
@@ -0,0 +1,331 @@
+/*
+ * The statement-level debugger hook, included into `quickjs.c` when the `debugger` feature enabled.
+ */
+
+/* the source line of the PC, or the line of the function if the PC has no line info */
+static int js_debugger_line(JSContext *ctx, JSFunctionBytecode *b, const uint8_t *pc)
+{
+    int line_num = -1;
+
+    if (pc)
+        line_num = find_line_num(ctx, b, pc - b->byte_code_buf - 1);
+    return line_num < 0 ? b->debug.line_num : line_num;
+}
+
+/* the hook is called before the first instruction of each new source line of a frame,
+   the first line of a frame is reported as `JS_DEBUGGER_EVENT_ENTER`,
+   and the return of the frame as `JS_DEBUGGER_EVENT_EXIT` which can't interrupt the execution */
+static int js_debugger_hook(JSContext *ctx, int event, JSStackFrame *sf,
+                            JSValueConst exception)
+{
+    JSRuntime *rt = ctx->rt;
+    JSStackFrame *f;
+    JSObject *p;
+    JSFunctionBytecode *b;
+    JSAtom filename = JS_ATOM_NULL;
+    JSValue saved_exception;
+    int line_num = -1, depth = 0, ret, can_abort;
+
+    /* the location of the nearest bytecode frame */
+    for(f = sf; f != NULL; f = f->prev_frame) {
+        if (JS_VALUE_GET_TAG(f->cur_func) != JS_TAG_OBJECT)
+            continue;
+        p = JS_VALUE_GET_OBJ(f->cur_func);
+        if (!js_class_has_bytecode(p->class_id))
+            continue;
+        b = p->u.func.function_bytecode;
+        if (b->has_debug) {
+            filename = b->debug.filename;
+            line_num = js_debugger_line(ctx, b, f->cur_pc);
+        }
+        break;
+    }
+    for(f = sf; f != NULL; f = f->prev_frame)
+        depth++;
+
+    /* the pending exception is kept while the hook evaluating scripts */
+    saved_exception = ctx->current_exception;
+    ctx->current_exception = JS_NULL;
+
+    rt->debugger_in_hook = TRUE;
+    ret = rt->debugger_hook(ctx, event, filename, line_num, depth, exception,
+                            rt->debugger_opaque);
+    can_abort = event != JS_DEBUGGER_EVENT_EXCEPTION && event != JS_DEBUGGER_EVENT_EXIT;
+
+    if (ret < 0 && can_abort &&
+        !JS_IsNull(ctx->current_exception)) {
+        JS_FreeValue(ctx, saved_exception);
+    } else {
+        JS_FreeValue(ctx, ctx->current_exception);
+        ctx->current_exception = saved_exception;
+        if (ret < 0 && can_abort)
+            JS_ThrowInternalError(ctx, "interrupted");
+    }
+    rt->debugger_in_hook = FALSE;
+    return ret;
+}
+
+static int js_debugger_check(JSContext *ctx, JSFunctionBytecode *b,
+                             JSStackFrame *sf, const uint8_t *pc)
+{
+    int line_num, event;
+
+    if (ctx->rt->debugger_in_hook || !b->has_debug)
+        return 0;
+    /* same as the PC saved before the calls */
+    sf->cur_pc = pc + 1;
+    line_num = js_debugger_line(ctx, b, sf->cur_pc);
+    if (line_num == sf->debug_line)
+        return 0;
+    event = sf->debug_line < 0 ? JS_DEBUGGER_EVENT_ENTER : JS_DEBUGGER_EVENT_STEP;
+    sf->debug_line = line_num;
+    return js_debugger_hook(ctx, event, sf, JS_UNDEFINED);
+}
+
+void JS_SetDebuggerHook(JSRuntime *rt, JSDebuggerHook *hook, void *opaque)
+{
+    rt->debugger_hook = hook;
+    rt->debugger_opaque = opaque;
+}
+
+static JSStackFrame *js_debugger_frame(JSContext *ctx, int level,
+                                       JSFunctionBytecode **pb, JSObject **pp)
+{
+    JSStackFrame *sf;
+    JSObject *p;
+
+    for(sf = ctx->current_stack_frame; sf != NULL && level > 0; sf = sf->prev_frame)
+        level--;
+    if (!sf || JS_VALUE_GET_TAG(sf->cur_func) != JS_TAG_OBJECT)
+        return NULL;
+    p = JS_VALUE_GET_OBJ(sf->cur_func);
+    if (!js_class_has_bytecode(p->class_id))
+        return NULL;
+    *pp = p;
+    *pb = p->u.func.function_bytecode;
+    return sf;
+}
+
+static int js_debugger_define(JSContext *ctx, JSValueConst obj, JSAtom name,
+                              JSValueConst val)
+{
+    if (name == JS_ATOM_NULL)
+        return 0;
+    if (JS_VALUE_GET_TAG(val) == JS_TAG_UNINITIALIZED)
+        val = JS_UNDEFINED;
+    return JS_DefinePropertyValue(ctx, obj, name, JS_DupValue(ctx, val),
+                                  JS_PROP_C_W_E);
+}
+
+/* returns the arguments and local variables, or the closure variables of the stack frame */
+JSValue JS_GetFrameScope(JSContext *ctx, int level, int closure)
+{
+    JSStackFrame *sf;
+    JSFunctionBytecode *b;
+    JSObject *p;
+    JSValue obj;
+    int i;
+
+    sf = js_debugger_frame(ctx, level, &b, &p);
+    if (!sf)
+        return JS_UNDEFINED;
+    obj = JS_NewObjectProto(ctx, JS_NULL);
+    if (JS_IsException(obj))
+        return obj;
+    if (closure) {
+        for(i = 0; i < b->closure_var_count; i++) {
+            JSVarRef *var_ref = p->u.func.var_refs ? p->u.func.var_refs[i] : NULL;
+            if (var_ref &&
+                js_debugger_define(ctx, obj, b->closure_var[i].var_name, *var_ref->pvalue) < 0)
+                goto fail;
+        }
+    } else if (b->vardefs) {
+        for(i = 0; i < b->arg_count; i++) {
+            if (js_debugger_define(ctx, obj, b->vardefs[i].var_name, sf->arg_buf[i]) < 0)
+                goto fail;
+        }
+        for(i = 0; i < b->var_count; i++) {
+            if (js_debugger_define(ctx, obj, b->vardefs[b->arg_count + i].var_name,
+                                   sf->var_buf[i]) < 0)
+                goto fail;
+        }
+    }
+    return obj;
+ fail:
+    JS_FreeValue(ctx, obj);
+    return JS_EXCEPTION;
+}
+
+/* returns the `{ functionName, fileName, lineNumber }` of the stack frames, the innermost first */
+JSValue JS_GetStackFrames(JSContext *ctx)
+{
+    JSStackFrame *sf;
+    JSValue arr, frame;
+    JSObject *p;
+    JSFunctionBytecode *b;
+    const char *func_name;
+    uint32_t n = 0;
+    int line_num;
+
+    arr = JS_NewArray(ctx);
+    if (JS_IsException(arr))
+        return arr;
+    for(sf = ctx->current_stack_frame; sf != NULL; sf = sf->prev_frame) {
+        frame = JS_NewObject(ctx);
+        if (JS_IsException(frame))
+            goto fail;
+        func_name = get_func_name(ctx, sf->cur_func);
+        JS_SetPropertyStr(ctx, frame, "functionName",
+                          JS_NewString(ctx, func_name ? func_name : ""));
+        JS_FreeCString(ctx, func_name);
+        if (JS_VALUE_GET_TAG(sf->cur_func) == JS_TAG_OBJECT) {
+            p = JS_VALUE_GET_OBJ(sf->cur_func);
+            if (js_class_has_bytecode(p->class_id)) {
+                b = p->u.func.function_bytecode;
+                if (b->has_debug) {
+                    line_num = js_debugger_line(ctx, b, sf->cur_pc);
+                    JS_SetPropertyStr(ctx, frame, "fileName",
+                                      JS_AtomToString(ctx, b->debug.filename));
+                    JS_SetPropertyStr(ctx, frame, "lineNumber",
+                                      JS_NewInt32(ctx, line_num));
+                }
+            }
+        }
+        if (JS_SetPropertyUint32(ctx, arr, n++, frame) < 0)
+            goto fail;
+    }
+    return arr;
+ fail:
+    JS_FreeValue(ctx, arr);
+    return JS_EXCEPTION;
+}
+
+/* returns -1 if no frame at the level, 0 for the native function, 1 for the bytecode function,
+   the atoms of the frame should be freed by the caller */
+int JS_GetFrameInfo(JSContext *ctx, int level, JSDebuggerFrame *frame)
+{
+    JSStackFrame *sf;
+    JSFunctionBytecode *b;
+    JSObject *p;
+    const char *func_name;
+
+    for(sf = ctx->current_stack_frame; sf != NULL && level > 0; sf = sf->prev_frame)
+        level--;
+    if (!sf)
+        return -1;
+    frame->function_name = JS_ATOM_NULL;
+    frame->filename = JS_ATOM_NULL;
+    frame->function_line = -1;
+    frame->line_num = -1;
+    if (JS_VALUE_GET_TAG(sf->cur_func) == JS_TAG_OBJECT) {
+        p = JS_VALUE_GET_OBJ(sf->cur_func);
+        if (js_class_has_bytecode(p->class_id)) {
+            b = p->u.func.function_bytecode;
+            frame->function_name = JS_DupAtom(ctx, b->func_name);
+            if (b->has_debug) {
+                frame->filename = JS_DupAtom(ctx, b->debug.filename);
+                frame->function_line = b->debug.line_num;
+                frame->line_num = js_debugger_line(ctx, b, sf->cur_pc);
+            }
+            return 1;
+        }
+    }
+    func_name = get_func_name(ctx, sf->cur_func);
+    if (func_name) {
+        frame->function_name = JS_NewAtom(ctx, func_name);
+        JS_FreeCString(ctx, func_name);
+    }
+    return 0;
+}
+
+/* appends the source lines which have the bytecode, in the order of the bytecode */
+static int js_debugger_lines(JSContext *ctx, JSFunctionBytecode *b, JSValueConst arr)
+{
+    const uint8_t *p_end, *p;
+    int line_num, v, ret;
+    uint32_t val, n = 0;
+    unsigned int op;
+
+    line_num = b->debug.line_num;
+    if (JS_SetPropertyUint32(ctx, arr, n++, JS_NewInt32(ctx, line_num)) < 0)
+        return -1;
+    if (!b->debug.pc2line_buf)
+        return 0;
+    p = b->debug.pc2line_buf;
+    p_end = p + b->debug.pc2line_len;
+    while (p < p_end) {
+        op = *p++;
+        if (op == 0) {
+            ret = get_leb128(&val, p, p_end);
+            if (ret < 0)
+                break;
+            p += ret;
+            ret = get_sleb128(&v, p, p_end);
+            if (ret < 0)
+                break;
+            p += ret;
+            line_num += v;
+        } else {
+            op -= PC2LINE_OP_FIRST;
+            line_num += (op % PC2LINE_RANGE) + PC2LINE_BASE;
+        }
+        if (JS_SetPropertyUint32(ctx, arr, n++, JS_NewInt32(ctx, line_num)) < 0)
+            return -1;
+    }
+    return 0;
+}
+
+static int js_debugger_functions(JSContext *ctx, JSFunctionBytecode *b,
+                                 JSValueConst arr, uint32_t *pn)
+{
+    JSValue func, lines;
+    int i;
+
+    if (!b->has_debug)
+        return 0;
+    func = JS_NewObject(ctx);
+    if (JS_IsException(func))
+        return -1;
+    lines = JS_NewArray(ctx);
+    if (JS_IsException(lines) ||
+        JS_DefinePropertyValueStr(ctx, func, "lines", lines, JS_PROP_C_W_E) < 0 ||
+        js_debugger_lines(ctx, b, lines) < 0 ||
+        JS_DefinePropertyValueStr(ctx, func, "name",
+                                  JS_AtomToString(ctx, b->func_name == JS_ATOM_NULL ?
+                                                  JS_ATOM_empty_string : b->func_name),
+                                  JS_PROP_C_W_E) < 0 ||
+        JS_DefinePropertyValueStr(ctx, func, "lineNumber", JS_NewInt32(ctx, b->debug.line_num),
+                                  JS_PROP_C_W_E) < 0 ||
+        JS_SetPropertyUint32(ctx, arr, (*pn)++, JS_DupValue(ctx, func)) < 0) {
+        JS_FreeValue(ctx, func);
+        return -1;
+    }
+    JS_FreeValue(ctx, func);
+    for(i = 0; i < b->cpool_count; i++) {
+        if (JS_VALUE_GET_TAG(b->cpool[i]) == JS_TAG_FUNCTION_BYTECODE &&
+            js_debugger_functions(ctx, JS_VALUE_GET_PTR(b->cpool[i]), arr, pn) < 0)
+            return -1;
+    }
+    return 0;
+}
+
+/* returns the `{ name, lineNumber, lines }` of the function of the stack frame
+   and the functions defined in it, `lines` are the source lines which have the bytecode */
+JSValue JS_GetFrameFunctions(JSContext *ctx, int level)
+{
+    JSFunctionBytecode *b;
+    JSObject *p;
+    JSValue arr;
+    uint32_t n = 0;
+
+    if (!js_debugger_frame(ctx, level, &b, &p))
+        return JS_UNDEFINED;
+    arr = JS_NewArray(ctx);
+    if (JS_IsException(arr))
+        return arr;
+    if (js_debugger_functions(ctx, b, arr, &n) < 0) {
+        JS_FreeValue(ctx, arr);
+        return JS_EXCEPTION;
+    }
+    return arr;
+}
@@ -2,9 +2,12 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 #![allow(clippy::unreadable_literal)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 #[macro_use]
 extern crate cfg_if;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate lazy_static;
 
@@ -16,13 +19,14 @@ cfg_if! {
     }
 }
 
+#[cfg(feature = "std")]
 lazy_static! {
     pub static ref VERSION: &'static str =
         include_str!(concat!(env!("OUT_DIR"), "/VERSION")).trim();
 }
 
 cfg_if! {
-    if #[cfg(feature = "repl")] {
+    if #[cfg(all(feature = "std", feature = "repl"))] {
         extern "C" {
             #[no_mangle]
             pub static qjsc_repl: [u8; 0];
@@ -33,14 +37,14 @@ cfg_if! {
 
         lazy_static! {
             pub static ref REPL: &'static [u8] = unsafe {
-                std::slice::from_raw_parts(qjsc_repl.as_ptr(), qjsc_repl_size as usize)
+                core::slice::from_raw_parts(qjsc_repl.as_ptr(), qjsc_repl_size as usize)
             };
         }
     }
 }
 
 cfg_if! {
-    if #[cfg(feature = "qjscalc")] {
+    if #[cfg(all(feature = "std", feature = "qjscalc"))] {
         extern "C" {
             #[no_mangle]
             pub static qjsc_qjscalc: [u8; 0];
@@ -51,7 +55,7 @@ cfg_if! {
 
         lazy_static! {
             pub static ref QJSCALC: &'static [u8] = unsafe {
-                std::slice::from_raw_parts(qjsc_qjscalc.as_ptr(), qjsc_qjscalc_size as usize)
+                core::slice::from_raw_parts(qjsc_qjscalc.as_ptr(), qjsc_qjscalc_size as usize)
             };
         }
     }
@@ -78,7 +82,7 @@ pub const fn mkptr<T>(tag: i32, val: *mut T) -> JSValue {
 
 pub const NAN: JSValue = JSValue {
     u: JSValueUnion {
-        float64: std::f64::NAN,
+        float64: core::f64::NAN,
     },
     tag: JS_TAG_FLOAT64 as i64,
 };
@@ -90,6 +94,534 @@ pub const TRUE: JSValue = mkval(JS_TAG_BOOL, TRUE_VALUE);
 pub const EXCEPTION: JSValue = mkval(JS_TAG_EXCEPTION, 0);
 pub const UNINITIALIZED: JSValue = mkval(JS_TAG_UNINITIALIZED, 0);
 
+/// The library is the bundled QuickJS with the patches, otherwise the patched functions are the shims.
+pub const PATCHED: bool = !cfg!(qjs_sys_unpatched);
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// Only check the syntax of the source, the unpatched library compiles the source without running it.
+        pub const JS_EVAL_FLAG_PARSE_ONLY: u32 = JS_EVAL_FLAG_COMPILE_ONLY;
+    } else {
+        /// Only check the syntax of the source, the error has the `lineNumber` and `columnNumber` properties.
+        pub const JS_EVAL_FLAG_PARSE_ONLY: u32 = 1 << 6;
+    }
+}
+
+cfg_if! {
+    if #[cfg(feature = "debugger")] {
+        pub const JS_DEBUGGER_EVENT_STEP: ::core::ffi::c_int = 0;
+        pub const JS_DEBUGGER_EVENT_EXCEPTION: ::core::ffi::c_int = 1;
+        pub const JS_DEBUGGER_EVENT_ENTER: ::core::ffi::c_int = 2;
+        pub const JS_DEBUGGER_EVENT_EXIT: ::core::ffi::c_int = 3;
+
+        pub type JSDebuggerHook = ::core::option::Option<
+            unsafe extern "C" fn(
+                ctx: *mut JSContext,
+                event: ::core::ffi::c_int,
+                filename: JSAtom,
+                line_num: ::core::ffi::c_int,
+                depth: ::core::ffi::c_int,
+                exception: JSValue,
+                opaque: *mut ::core::ffi::c_void,
+            ) -> ::core::ffi::c_int,
+        >;
+
+        #[repr(C)]
+        #[derive(Debug, Default, Copy, Clone)]
+        pub struct JSDebuggerFrame {
+            pub function_name: JSAtom,
+            pub filename: JSAtom,
+            pub function_line: ::core::ffi::c_int,
+            pub line_num: ::core::ffi::c_int,
+        }
+
+        extern "C" {
+            pub fn JS_SetDebuggerHook(
+                rt: *mut JSRuntime,
+                hook: JSDebuggerHook,
+                opaque: *mut ::core::ffi::c_void,
+            );
+
+            pub fn JS_GetFrameScope(
+                ctx: *mut JSContext,
+                level: ::core::ffi::c_int,
+                closure: ::core::ffi::c_int,
+            ) -> JSValue;
+
+            pub fn JS_GetStackFrames(ctx: *mut JSContext) -> JSValue;
+
+            pub fn JS_GetFrameInfo(
+                ctx: *mut JSContext,
+                level: ::core::ffi::c_int,
+                frame: *mut JSDebuggerFrame,
+            ) -> ::core::ffi::c_int;
+
+            pub fn JS_GetFrameFunctions(ctx: *mut JSContext, level: ::core::ffi::c_int) -> JSValue;
+        }
+    }
+}
+
+pub const JS_TYPED_ARRAY_UINT8C: ::core::ffi::c_int = 0;
+pub const JS_TYPED_ARRAY_INT8: ::core::ffi::c_int = 1;
+pub const JS_TYPED_ARRAY_UINT8: ::core::ffi::c_int = 2;
+pub const JS_TYPED_ARRAY_INT16: ::core::ffi::c_int = 3;
+pub const JS_TYPED_ARRAY_UINT16: ::core::ffi::c_int = 4;
+pub const JS_TYPED_ARRAY_INT32: ::core::ffi::c_int = 5;
+pub const JS_TYPED_ARRAY_UINT32: ::core::ffi::c_int = 6;
+pub const JS_TYPED_ARRAY_BIG_INT64: ::core::ffi::c_int = 7;
+pub const JS_TYPED_ARRAY_BIG_UINT64: ::core::ffi::c_int = 8;
+pub const JS_TYPED_ARRAY_FLOAT32: ::core::ffi::c_int = 9;
+pub const JS_TYPED_ARRAY_FLOAT64: ::core::ffi::c_int = 10;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library has no access to the fast arrays.
+        pub unsafe extern "C" fn JS_GetFastArray(
+            _ctx: *mut JSContext,
+            _obj: JSValue,
+            _arrpp: *mut *mut JSValue,
+            _countp: *mut u32,
+        ) -> ::core::ffi::c_int {
+            FALSE_VALUE
+        }
+
+        /// The unpatched library has no access to the typed arrays.
+        pub unsafe extern "C" fn JS_GetTypedArrayElements(
+            _ctx: *mut JSContext,
+            _obj: JSValue,
+            _pdata: *mut *mut ::core::ffi::c_void,
+            _pcount: *mut u32,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+    } else {
+        extern "C" {
+            /// Access the values of a fast array, which are borrowed from the array.
+            pub fn JS_GetFastArray(
+                ctx: *mut JSContext,
+                obj: JSValue,
+                arrpp: *mut *mut JSValue,
+                countp: *mut u32,
+            ) -> ::core::ffi::c_int;
+
+            /// Access the elements of a typed array, returns the `JS_TYPED_ARRAY_*` element type or -1.
+            pub fn JS_GetTypedArrayElements(
+                ctx: *mut JSContext,
+                obj: JSValue,
+                pdata: *mut *mut ::core::ffi::c_void,
+                pcount: *mut u32,
+            ) -> ::core::ffi::c_int;
+        }
+    }
+}
+
+/// The statistics of the atom table and the shape hash table.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct JSEngineStats {
+    pub atom_count: i64,
+    pub atom_size: i64,
+    pub atom_hash_size: i64,
+    pub shape_hash_count: i64,
+    pub shape_hash_size: i64,
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library has no access to the atom table and shapes.
+        pub unsafe extern "C" fn JS_GetEngineStats(_rt: *mut JSRuntime, _s: *mut JSEngineStats) {}
+
+        /// The unpatched library has no access to the shapes.
+        pub unsafe extern "C" fn JS_GetObjectShape(
+            _obj: JSValue,
+            _prop_count: *mut ::core::ffi::c_int,
+            _is_hashed: *mut ::core::ffi::c_int,
+        ) -> *const ::core::ffi::c_void {
+            ::core::ptr::null()
+        }
+    } else {
+        extern "C" {
+            /// Get the statistics of the atom table and the shape hash table.
+            pub fn JS_GetEngineStats(rt: *mut JSRuntime, s: *mut JSEngineStats);
+
+            /// Returns the shape of an object or NULL, the shape is shared by the objects with the same layout.
+            pub fn JS_GetObjectShape(
+                obj: JSValue,
+                prop_count: *mut ::core::ffi::c_int,
+                is_hashed: *mut ::core::ffi::c_int,
+            ) -> *const ::core::ffi::c_void;
+        }
+    }
+}
+
+/// Returns `FALSE` to hide the frame from the backtrace, the `filename` is `JS_ATOM_NULL` for the native functions.
+pub type JSBacktraceFilter = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut JSContext,
+        func_name: *const ::core::ffi::c_char,
+        filename: JSAtom,
+        line_num: ::core::ffi::c_int,
+        column_num: ::core::ffi::c_int,
+        opaque: *mut ::core::ffi::c_void,
+    ) -> ::core::ffi::c_int,
+>;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library always captures the whole backtrace, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_SetBacktraceLimit(_rt: *mut JSRuntime, _limit: ::core::ffi::c_int) {}
+
+        /// The unpatched library always captures the whole backtrace.
+        pub unsafe extern "C" fn JS_GetBacktraceLimit(_rt: *mut JSRuntime) -> ::core::ffi::c_int {
+            -1
+        }
+
+        /// The unpatched library has no filter of the backtrace frames, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_SetBacktraceFilter(
+            _rt: *mut JSRuntime,
+            _filter: JSBacktraceFilter,
+            _opaque: *mut ::core::ffi::c_void,
+        ) {
+        }
+    } else {
+        extern "C" {
+            /// Set the maximum frames of the backtrace, or -1 if unlimited.
+            pub fn JS_SetBacktraceLimit(rt: *mut JSRuntime, limit: ::core::ffi::c_int);
+
+            /// Get the maximum frames of the backtrace, or -1 if unlimited.
+            pub fn JS_GetBacktraceLimit(rt: *mut JSRuntime) -> ::core::ffi::c_int;
+
+            /// Set the filter of the backtrace frames, or `None` to show all the frames.
+            pub fn JS_SetBacktraceFilter(
+                rt: *mut JSRuntime,
+                filter: JSBacktraceFilter,
+                opaque: *mut ::core::ffi::c_void,
+            );
+        }
+    }
+}
+
+/// Returns the new stack of the error, the `stack` is freed by the hook.
+pub type JSPrepareStackTrace = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut JSContext,
+        error_obj: JSValue,
+        stack: JSValue,
+        opaque: *mut ::core::ffi::c_void,
+    ) -> JSValue,
+>;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library has no hook of the error stack.
+        pub unsafe extern "C" fn JS_SetPrepareStackTrace(
+            _rt: *mut JSRuntime,
+            _hook: JSPrepareStackTrace,
+            _opaque: *mut ::core::ffi::c_void,
+        ) {
+        }
+    } else {
+        extern "C" {
+            /// Set the hook to rewrite the stack of the errors, or `None` to remove it.
+            pub fn JS_SetPrepareStackTrace(
+                rt: *mut JSRuntime,
+                hook: JSPrepareStackTrace,
+                opaque: *mut ::core::ffi::c_void,
+            );
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library can't compare the values, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_StrictEq(
+            _ctx: *mut JSContext,
+            _op1: JSValue,
+            _op2: JSValue,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+
+        /// The unpatched library can't compare the values, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_SameValue(
+            _ctx: *mut JSContext,
+            _op1: JSValue,
+            _op2: JSValue,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+
+        /// The unpatched library can't compare the values, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_LooseEq(
+            _ctx: *mut JSContext,
+            _op1: JSValue,
+            _op2: JSValue,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+    } else {
+        extern "C" {
+            /// Returns the result of `op1 === op2`.
+            pub fn JS_StrictEq(ctx: *mut JSContext, op1: JSValue, op2: JSValue) -> ::core::ffi::c_int;
+
+            /// Returns the result of `Object.is(op1, op2)`.
+            pub fn JS_SameValue(ctx: *mut JSContext, op1: JSValue, op2: JSValue) -> ::core::ffi::c_int;
+
+            /// Returns the result of `op1 == op2`, or -1 if an exception is thrown by the conversions.
+            pub fn JS_LooseEq(ctx: *mut JSContext, op1: JSValue, op2: JSValue) -> ::core::ffi::c_int;
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library keeps the loaded modules.
+        pub unsafe extern "C" fn JS_ResetModules(_ctx: *mut JSContext) {}
+
+        /// The unpatched library has no access to the global variables.
+        pub unsafe extern "C" fn JS_GetGlobalVarObject(_ctx: *mut JSContext) -> JSValue {
+            NULL
+        }
+
+        /// The unpatched library can't delete the non-configurable properties, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_ScrubProperty(
+            _ctx: *mut JSContext,
+            _obj: JSValue,
+            _prop: JSAtom,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+    } else {
+        extern "C" {
+            /// Forget the loaded modules, the unreferenced ones are freed and the others are freed with the context.
+            pub fn JS_ResetModules(ctx: *mut JSContext);
+
+            /// Returns the object of the global `let`, `const` and `class` definitions.
+            pub fn JS_GetGlobalVarObject(ctx: *mut JSContext) -> JSValue;
+
+            /// Delete an own property even if it is not configurable, returns `FALSE` if the property does not exist.
+            pub fn JS_ScrubProperty(ctx: *mut JSContext, obj: JSValue, prop: JSAtom) -> ::core::ffi::c_int;
+        }
+    }
+}
+
+/// Called when a promise is rejected without any handler, or a handler is added to such a rejected promise later.
+pub type JSHostPromiseRejectionTracker = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut JSContext,
+        promise: JSValue,
+        reason: JSValue,
+        is_handled: ::core::ffi::c_int,
+        opaque: *mut ::core::ffi::c_void,
+    ),
+>;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library doesn't track the promise rejections.
+        pub unsafe extern "C" fn JS_SetHostPromiseRejectionTracker(
+            _rt: *mut JSRuntime,
+            _cb: JSHostPromiseRejectionTracker,
+            _opaque: *mut ::core::ffi::c_void,
+        ) {
+        }
+    } else {
+        extern "C" {
+            /// Set the tracker of the unhandled promise rejections, or `None` to remove it.
+            pub fn JS_SetHostPromiseRejectionTracker(
+                rt: *mut JSRuntime,
+                cb: JSHostPromiseRejectionTracker,
+                opaque: *mut ::core::ffi::c_void,
+            );
+        }
+    }
+}
+
+/// Called with the class name and the reference count of a live object.
+pub type JSLiveObjectFunc = ::core::option::Option<
+    unsafe extern "C" fn(
+        opaque: *mut ::core::ffi::c_void,
+        class_name: *const ::core::ffi::c_char,
+        ref_count: ::core::ffi::c_int,
+    ),
+>;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library doesn't enumerate the contexts.
+        pub unsafe extern "C" fn JS_CountLiveContexts(_rt: *mut JSRuntime) -> ::core::ffi::c_int {
+            0
+        }
+
+        /// The unpatched library doesn't enumerate the objects.
+        pub unsafe extern "C" fn JS_ForEachLiveObject(
+            _rt: *mut JSRuntime,
+            _func: JSLiveObjectFunc,
+            _opaque: *mut ::core::ffi::c_void,
+        ) {
+        }
+    } else {
+        extern "C" {
+            /// Returns the number of the contexts which are not freed.
+            pub fn JS_CountLiveContexts(rt: *mut JSRuntime) -> ::core::ffi::c_int;
+
+            /// Call the function with the class name and the reference count of the live objects.
+            pub fn JS_ForEachLiveObject(
+                rt: *mut JSRuntime,
+                func: JSLiveObjectFunc,
+                opaque: *mut ::core::ffi::c_void,
+            );
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library creates the string from UTF-8, the lone surrogates are replaced.
+        pub unsafe extern "C" fn JS_NewStringUTF16(
+            ctx: *mut JSContext,
+            buf: *const u16,
+            len: usize,
+        ) -> JSValue {
+            let s = alloc::string::String::from_utf16_lossy(::core::slice::from_raw_parts(buf, len));
+
+            JS_NewStringLen(ctx, s.as_ptr() as *const _, s.len())
+        }
+
+        /// The unpatched library has no access to the code units of the strings.
+        pub unsafe extern "C" fn JS_GetStringUTF16(
+            _val: JSValue,
+            _buf: *mut u16,
+            _size: u32,
+        ) -> ::core::ffi::c_int {
+            -1
+        }
+    } else {
+        extern "C" {
+            /// Returns a new string of the UTF-16 code units, which may contain the lone surrogates.
+            pub fn JS_NewStringUTF16(ctx: *mut JSContext, buf: *const u16, len: usize) -> JSValue;
+
+            /// Copies at most `size` UTF-16 code units of a string to `buf`,
+            /// returns the length of the string, or -1 if it is not a string.
+            pub fn JS_GetStringUTF16(val: JSValue, buf: *mut u16, size: u32) -> ::core::ffi::c_int;
+        }
+    }
+}
+
+/// BigFloat literals and the `"use bigint"` directive.
+pub const JS_CONFIG_BIGNUM_EXT: ::core::ffi::c_int = 1 << 0;
+/// The `Symbol.operatorXXX` methods of the objects.
+pub const JS_CONFIG_OPERATOR_OVERLOADING: ::core::ffi::c_int = 1 << 1;
+/// The `"use math"` directive.
+pub const JS_CONFIG_MATH_MODE: ::core::ffi::c_int = 1 << 2;
+/// The html comments and `__proto__` in the object literals.
+pub const JS_CONFIG_ANNEX_B: ::core::ffi::c_int = 1 << 3;
+/// All the language extensions.
+pub const JS_CONFIG_DEFAULT: ::core::ffi::c_int = (1 << 4) - 1;
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library always enables the language extensions of the build, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_SetEngineConfig(_rt: *mut JSRuntime, _flags: ::core::ffi::c_int) {}
+
+        /// The unpatched library always enables the language extensions of the build.
+        pub unsafe extern "C" fn JS_GetEngineConfig(_rt: *mut JSRuntime) -> ::core::ffi::c_int {
+            JS_CONFIG_DEFAULT
+        }
+    } else {
+        extern "C" {
+            /// Set the `JS_CONFIG_*` flags of the language extensions.
+            pub fn JS_SetEngineConfig(rt: *mut JSRuntime, flags: ::core::ffi::c_int);
+
+            /// Get the `JS_CONFIG_*` flags of the language extensions.
+            pub fn JS_GetEngineConfig(rt: *mut JSRuntime) -> ::core::ffi::c_int;
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(all(qjs_sys_unpatched, feature = "std"))] {
+        lazy_static! {
+            static ref RUNTIME_OPAQUES: std::sync::Mutex<std::collections::HashMap<usize, usize>> =
+                Default::default();
+        }
+
+        /// The unpatched library has no opaque of the runtime, it is kept in a global map.
+        pub unsafe extern "C" fn JS_GetRuntimeOpaque(rt: *mut JSRuntime) -> *mut ::core::ffi::c_void {
+            RUNTIME_OPAQUES
+                .lock()
+                .unwrap()
+                .get(&(rt as usize))
+                .map_or(::core::ptr::null_mut(), |&opaque| opaque as *mut _)
+        }
+
+        /// The unpatched library has no opaque of the runtime, it is kept in a global map
+        /// until reset to NULL, which should be done before freeing the runtime.
+        pub unsafe extern "C" fn JS_SetRuntimeOpaque(rt: *mut JSRuntime, opaque: *mut ::core::ffi::c_void) {
+            let mut opaques = RUNTIME_OPAQUES.lock().unwrap();
+
+            if opaque.is_null() {
+                opaques.remove(&(rt as usize));
+            } else {
+                opaques.insert(rt as usize, opaque as usize);
+            }
+        }
+    } else if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library has no opaque of the runtime, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_GetRuntimeOpaque(_rt: *mut JSRuntime) -> *mut ::core::ffi::c_void {
+            ::core::ptr::null_mut()
+        }
+
+        /// The unpatched library has no opaque of the runtime, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_SetRuntimeOpaque(_rt: *mut JSRuntime, _opaque: *mut ::core::ffi::c_void) {}
+    } else {
+        extern "C" {
+            /// Get the opaque pointer of the runtime.
+            pub fn JS_GetRuntimeOpaque(rt: *mut JSRuntime) -> *mut ::core::ffi::c_void;
+
+            /// Set the opaque pointer of the runtime.
+            pub fn JS_SetRuntimeOpaque(rt: *mut JSRuntime, opaque: *mut ::core::ffi::c_void);
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library doesn't record the owning runtime, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_GetValueRuntime(_v: JSValue) -> *mut JSRuntime {
+            ::core::ptr::null_mut()
+        }
+    } else {
+        extern "C" {
+            /// Returns the owning runtime of an object, or `NULL` for the other values.
+            pub fn JS_GetValueRuntime(v: JSValue) -> *mut JSRuntime;
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(qjs_sys_unpatched)] {
+        /// The unpatched library doesn't expose the native implementations, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_GetCFunctionPtr(_v: JSValue) -> *mut ::core::ffi::c_void {
+            ::core::ptr::null_mut()
+        }
+
+        /// The unpatched library doesn't enumerate the modules, the caller should check `PATCHED`.
+        pub unsafe extern "C" fn JS_GetScriptModules(_ctx: *mut JSContext) -> JSValue {
+            UNDEFINED
+        }
+    } else {
+        extern "C" {
+            /// Returns the native implementation of a C function, or `NULL` for the other values.
+            pub fn JS_GetCFunctionPtr(v: JSValue) -> *mut ::core::ffi::c_void;
+
+            /// Returns an array of the `[name, namespace]` pairs of the evaluated script modules.
+            pub fn JS_GetScriptModules(ctx: *mut JSContext) -> JSValue;
+        }
+    }
+}
+
 impl Default for JSValue {
     fn default() -> JSValue {
         UNDEFINED
@@ -97,22 +97,22 @@ pub mod _bindgen_ty_1 {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSRefCountHeader {
-    pub ref_count: ::std::os::raw::c_int,
+    pub ref_count: ::core::ffi::c_int,
 }
 #[test]
 fn bindgen_test_layout_JSRefCountHeader() {
     assert_eq!(
-        ::std::mem::size_of::<JSRefCountHeader>(),
+        ::core::mem::size_of::<JSRefCountHeader>(),
         4usize,
         concat!("Size of: ", stringify!(JSRefCountHeader))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSRefCountHeader>(),
+        ::core::mem::align_of::<JSRefCountHeader>(),
         4usize,
         concat!("Alignment of ", stringify!(JSRefCountHeader))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSRefCountHeader>())).ref_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSRefCountHeader>())).ref_count as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -127,23 +127,23 @@ fn bindgen_test_layout_JSRefCountHeader() {
 pub union JSValueUnion {
     pub int32: i32,
     pub float64: f64,
-    pub ptr: *mut ::std::os::raw::c_void,
+    pub ptr: *mut ::core::ffi::c_void,
     _bindgen_union_align: u64,
 }
 #[test]
 fn bindgen_test_layout_JSValueUnion() {
     assert_eq!(
-        ::std::mem::size_of::<JSValueUnion>(),
+        ::core::mem::size_of::<JSValueUnion>(),
         8usize,
         concat!("Size of: ", stringify!(JSValueUnion))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSValueUnion>(),
+        ::core::mem::align_of::<JSValueUnion>(),
         8usize,
         concat!("Alignment of ", stringify!(JSValueUnion))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSValueUnion>())).int32 as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSValueUnion>())).int32 as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -153,7 +153,7 @@ fn bindgen_test_layout_JSValueUnion() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSValueUnion>())).float64 as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSValueUnion>())).float64 as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -163,7 +163,7 @@ fn bindgen_test_layout_JSValueUnion() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSValueUnion>())).ptr as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSValueUnion>())).ptr as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -182,17 +182,17 @@ pub struct JSValue {
 #[test]
 fn bindgen_test_layout_JSValue() {
     assert_eq!(
-        ::std::mem::size_of::<JSValue>(),
+        ::core::mem::size_of::<JSValue>(),
         16usize,
         concat!("Size of: ", stringify!(JSValue))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSValue>(),
+        ::core::mem::align_of::<JSValue>(),
         8usize,
         concat!("Alignment of ", stringify!(JSValue))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSValue>())).u as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSValue>())).u as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -202,7 +202,7 @@ fn bindgen_test_layout_JSValue() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSValue>())).tag as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSValue>())).tag as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -212,30 +212,30 @@ fn bindgen_test_layout_JSValue() {
         )
     );
 }
-pub type JSCFunction = ::std::option::Option<
+pub type JSCFunction = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
         this_val: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue,
 >;
-pub type JSCFunctionMagic = ::std::option::Option<
+pub type JSCFunctionMagic = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
         this_val: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
-        magic: ::std::os::raw::c_int,
+        magic: ::core::ffi::c_int,
     ) -> JSValue,
 >;
-pub type JSCFunctionData = ::std::option::Option<
+pub type JSCFunctionData = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
         this_val: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
-        magic: ::std::os::raw::c_int,
+        magic: ::core::ffi::c_int,
         func_data: *mut JSValue,
     ) -> JSValue,
 >;
@@ -244,22 +244,22 @@ pub struct JSMallocState {
     pub malloc_count: usize,
     pub malloc_size: usize,
     pub malloc_limit: usize,
-    pub opaque: *mut ::std::os::raw::c_void,
+    pub opaque: *mut ::core::ffi::c_void,
 }
 #[test]
 fn bindgen_test_layout_JSMallocState() {
     assert_eq!(
-        ::std::mem::size_of::<JSMallocState>(),
+        ::core::mem::size_of::<JSMallocState>(),
         32usize,
         concat!("Size of: ", stringify!(JSMallocState))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSMallocState>(),
+        ::core::mem::align_of::<JSMallocState>(),
         8usize,
         concat!("Alignment of ", stringify!(JSMallocState))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocState>())).malloc_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocState>())).malloc_count as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -269,7 +269,7 @@ fn bindgen_test_layout_JSMallocState() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocState>())).malloc_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocState>())).malloc_size as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -279,7 +279,7 @@ fn bindgen_test_layout_JSMallocState() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocState>())).malloc_limit as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocState>())).malloc_limit as *const _ as usize },
         16usize,
         concat!(
             "Offset of field: ",
@@ -289,7 +289,7 @@ fn bindgen_test_layout_JSMallocState() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocState>())).opaque as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocState>())).opaque as *const _ as usize },
         24usize,
         concat!(
             "Offset of field: ",
@@ -302,36 +302,36 @@ fn bindgen_test_layout_JSMallocState() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSMallocFunctions {
-    pub js_malloc: ::std::option::Option<
-        unsafe extern "C" fn(s: *mut JSMallocState, size: usize) -> *mut ::std::os::raw::c_void,
+    pub js_malloc: ::core::option::Option<
+        unsafe extern "C" fn(s: *mut JSMallocState, size: usize) -> *mut ::core::ffi::c_void,
     >,
-    pub js_free: ::std::option::Option<
-        unsafe extern "C" fn(s: *mut JSMallocState, ptr: *mut ::std::os::raw::c_void),
+    pub js_free: ::core::option::Option<
+        unsafe extern "C" fn(s: *mut JSMallocState, ptr: *mut ::core::ffi::c_void),
     >,
-    pub js_realloc: ::std::option::Option<
+    pub js_realloc: ::core::option::Option<
         unsafe extern "C" fn(
             s: *mut JSMallocState,
-            ptr: *mut ::std::os::raw::c_void,
+            ptr: *mut ::core::ffi::c_void,
             size: usize,
-        ) -> *mut ::std::os::raw::c_void,
+        ) -> *mut ::core::ffi::c_void,
     >,
     pub js_malloc_usable_size:
-        ::std::option::Option<unsafe extern "C" fn(ptr: *const ::std::os::raw::c_void) -> usize>,
+        ::core::option::Option<unsafe extern "C" fn(ptr: *const ::core::ffi::c_void) -> usize>,
 }
 #[test]
 fn bindgen_test_layout_JSMallocFunctions() {
     assert_eq!(
-        ::std::mem::size_of::<JSMallocFunctions>(),
+        ::core::mem::size_of::<JSMallocFunctions>(),
         32usize,
         concat!("Size of: ", stringify!(JSMallocFunctions))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSMallocFunctions>(),
+        ::core::mem::align_of::<JSMallocFunctions>(),
         8usize,
         concat!("Alignment of ", stringify!(JSMallocFunctions))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocFunctions>())).js_malloc as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocFunctions>())).js_malloc as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -341,7 +341,7 @@ fn bindgen_test_layout_JSMallocFunctions() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocFunctions>())).js_free as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocFunctions>())).js_free as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -351,7 +351,7 @@ fn bindgen_test_layout_JSMallocFunctions() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMallocFunctions>())).js_realloc as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMallocFunctions>())).js_realloc as *const _ as usize },
         16usize,
         concat!(
             "Offset of field: ",
@@ -362,7 +362,8 @@ fn bindgen_test_layout_JSMallocFunctions() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMallocFunctions>())).js_malloc_usable_size as *const _ as usize
+            &(*(::core::ptr::null::<JSMallocFunctions>())).js_malloc_usable_size as *const _
+                as usize
         },
         24usize,
         concat!(
@@ -377,7 +378,7 @@ extern "C" {
     pub fn JS_NewRuntime() -> *mut JSRuntime;
 }
 extern "C" {
-    pub fn JS_SetRuntimeInfo(rt: *mut JSRuntime, info: *const ::std::os::raw::c_char);
+    pub fn JS_SetRuntimeInfo(rt: *mut JSRuntime, info: *const ::core::ffi::c_char);
 }
 extern "C" {
     pub fn JS_SetMemoryLimit(rt: *mut JSRuntime, limit: usize);
@@ -388,14 +389,14 @@ extern "C" {
 extern "C" {
     pub fn JS_NewRuntime2(
         mf: *const JSMallocFunctions,
-        opaque: *mut ::std::os::raw::c_void,
+        opaque: *mut ::core::ffi::c_void,
     ) -> *mut JSRuntime;
 }
 extern "C" {
     pub fn JS_FreeRuntime(rt: *mut JSRuntime);
 }
 pub type JS_MarkFunc =
-    ::std::option::Option<unsafe extern "C" fn(rt: *mut JSRuntime, val: JSValue)>;
+    ::core::option::Option<unsafe extern "C" fn(rt: *mut JSRuntime, val: JSValue)>;
 extern "C" {
     pub fn JS_MarkValue(rt: *mut JSRuntime, val: JSValue, mark_func: JS_MarkFunc);
 }
@@ -403,10 +404,10 @@ extern "C" {
     pub fn JS_RunGC(rt: *mut JSRuntime);
 }
 extern "C" {
-    pub fn JS_IsLiveObject(rt: *mut JSRuntime, obj: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsLiveObject(rt: *mut JSRuntime, obj: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_IsInGCSweep(rt: *mut JSRuntime) -> ::std::os::raw::c_int;
+    pub fn JS_IsInGCSweep(rt: *mut JSRuntime) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_NewContext(rt: *mut JSRuntime) -> *mut JSContext;
@@ -415,10 +416,10 @@ extern "C" {
     pub fn JS_FreeContext(s: *mut JSContext);
 }
 extern "C" {
-    pub fn JS_GetContextOpaque(ctx: *mut JSContext) -> *mut ::std::os::raw::c_void;
+    pub fn JS_GetContextOpaque(ctx: *mut JSContext) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn JS_SetContextOpaque(ctx: *mut JSContext, opaque: *mut ::std::os::raw::c_void);
+    pub fn JS_SetContextOpaque(ctx: *mut JSContext, opaque: *mut ::core::ffi::c_void);
 }
 extern "C" {
     pub fn JS_GetRuntime(ctx: *mut JSContext) -> *mut JSRuntime;
@@ -472,71 +473,68 @@ extern "C" {
     pub fn js_string_codePointRange(
         ctx: *mut JSContext,
         this_val: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue;
 }
 extern "C" {
-    pub fn js_malloc_rt(rt: *mut JSRuntime, size: usize) -> *mut ::std::os::raw::c_void;
+    pub fn js_malloc_rt(rt: *mut JSRuntime, size: usize) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_free_rt(rt: *mut JSRuntime, ptr: *mut ::std::os::raw::c_void);
+    pub fn js_free_rt(rt: *mut JSRuntime, ptr: *mut ::core::ffi::c_void);
 }
 extern "C" {
     pub fn js_realloc_rt(
         rt: *mut JSRuntime,
-        ptr: *mut ::std::os::raw::c_void,
+        ptr: *mut ::core::ffi::c_void,
         size: usize,
-    ) -> *mut ::std::os::raw::c_void;
+    ) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_malloc_usable_size_rt(
-        rt: *mut JSRuntime,
-        ptr: *const ::std::os::raw::c_void,
-    ) -> usize;
+    pub fn js_malloc_usable_size_rt(rt: *mut JSRuntime, ptr: *const ::core::ffi::c_void) -> usize;
 }
 extern "C" {
-    pub fn js_mallocz_rt(rt: *mut JSRuntime, size: usize) -> *mut ::std::os::raw::c_void;
+    pub fn js_mallocz_rt(rt: *mut JSRuntime, size: usize) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_malloc(ctx: *mut JSContext, size: usize) -> *mut ::std::os::raw::c_void;
+    pub fn js_malloc(ctx: *mut JSContext, size: usize) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_free(ctx: *mut JSContext, ptr: *mut ::std::os::raw::c_void);
+    pub fn js_free(ctx: *mut JSContext, ptr: *mut ::core::ffi::c_void);
 }
 extern "C" {
     pub fn js_realloc(
         ctx: *mut JSContext,
-        ptr: *mut ::std::os::raw::c_void,
+        ptr: *mut ::core::ffi::c_void,
         size: usize,
-    ) -> *mut ::std::os::raw::c_void;
+    ) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_malloc_usable_size(ctx: *mut JSContext, ptr: *const ::std::os::raw::c_void) -> usize;
+    pub fn js_malloc_usable_size(ctx: *mut JSContext, ptr: *const ::core::ffi::c_void) -> usize;
 }
 extern "C" {
     pub fn js_realloc2(
         ctx: *mut JSContext,
-        ptr: *mut ::std::os::raw::c_void,
+        ptr: *mut ::core::ffi::c_void,
         size: usize,
         pslack: *mut usize,
-    ) -> *mut ::std::os::raw::c_void;
+    ) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
-    pub fn js_mallocz(ctx: *mut JSContext, size: usize) -> *mut ::std::os::raw::c_void;
+    pub fn js_mallocz(ctx: *mut JSContext, size: usize) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
     pub fn js_strdup(
         ctx: *mut JSContext,
-        str: *const ::std::os::raw::c_char,
-    ) -> *mut ::std::os::raw::c_char;
+        str: *const ::core::ffi::c_char,
+    ) -> *mut ::core::ffi::c_char;
 }
 extern "C" {
     pub fn js_strndup(
         ctx: *mut JSContext,
-        s: *const ::std::os::raw::c_char,
+        s: *const ::core::ffi::c_char,
         n: usize,
-    ) -> *mut ::std::os::raw::c_char;
+    ) -> *mut ::core::ffi::c_char;
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -571,17 +569,17 @@ pub struct JSMemoryUsage {
 #[test]
 fn bindgen_test_layout_JSMemoryUsage() {
     assert_eq!(
-        ::std::mem::size_of::<JSMemoryUsage>(),
+        ::core::mem::size_of::<JSMemoryUsage>(),
         208usize,
         concat!("Size of: ", stringify!(JSMemoryUsage))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSMemoryUsage>(),
+        ::core::mem::align_of::<JSMemoryUsage>(),
         8usize,
         concat!("Alignment of ", stringify!(JSMemoryUsage))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).malloc_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).malloc_size as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -591,7 +589,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).malloc_limit as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).malloc_limit as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -601,7 +599,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).memory_used_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).memory_used_size as *const _ as usize },
         16usize,
         concat!(
             "Offset of field: ",
@@ -611,7 +609,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).malloc_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).malloc_count as *const _ as usize },
         24usize,
         concat!(
             "Offset of field: ",
@@ -621,7 +619,9 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).memory_used_count as *const _ as usize },
+        unsafe {
+            &(*(::core::ptr::null::<JSMemoryUsage>())).memory_used_count as *const _ as usize
+        },
         32usize,
         concat!(
             "Offset of field: ",
@@ -631,7 +631,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).atom_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).atom_count as *const _ as usize },
         40usize,
         concat!(
             "Offset of field: ",
@@ -641,7 +641,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).atom_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).atom_size as *const _ as usize },
         48usize,
         concat!(
             "Offset of field: ",
@@ -651,7 +651,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).str_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).str_count as *const _ as usize },
         56usize,
         concat!(
             "Offset of field: ",
@@ -661,7 +661,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).str_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).str_size as *const _ as usize },
         64usize,
         concat!(
             "Offset of field: ",
@@ -671,7 +671,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).obj_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).obj_count as *const _ as usize },
         72usize,
         concat!(
             "Offset of field: ",
@@ -681,7 +681,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).obj_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).obj_size as *const _ as usize },
         80usize,
         concat!(
             "Offset of field: ",
@@ -691,7 +691,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).prop_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).prop_count as *const _ as usize },
         88usize,
         concat!(
             "Offset of field: ",
@@ -701,7 +701,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).prop_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).prop_size as *const _ as usize },
         96usize,
         concat!(
             "Offset of field: ",
@@ -711,7 +711,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).shape_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).shape_count as *const _ as usize },
         104usize,
         concat!(
             "Offset of field: ",
@@ -721,7 +721,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).shape_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).shape_size as *const _ as usize },
         112usize,
         concat!(
             "Offset of field: ",
@@ -731,7 +731,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).js_func_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).js_func_count as *const _ as usize },
         120usize,
         concat!(
             "Offset of field: ",
@@ -741,7 +741,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).js_func_size as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).js_func_size as *const _ as usize },
         128usize,
         concat!(
             "Offset of field: ",
@@ -751,7 +751,9 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).js_func_code_size as *const _ as usize },
+        unsafe {
+            &(*(::core::ptr::null::<JSMemoryUsage>())).js_func_code_size as *const _ as usize
+        },
         136usize,
         concat!(
             "Offset of field: ",
@@ -762,7 +764,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMemoryUsage>())).js_func_pc2line_count as *const _ as usize
+            &(*(::core::ptr::null::<JSMemoryUsage>())).js_func_pc2line_count as *const _ as usize
         },
         144usize,
         concat!(
@@ -774,7 +776,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMemoryUsage>())).js_func_pc2line_size as *const _ as usize
+            &(*(::core::ptr::null::<JSMemoryUsage>())).js_func_pc2line_size as *const _ as usize
         },
         152usize,
         concat!(
@@ -785,7 +787,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).c_func_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).c_func_count as *const _ as usize },
         160usize,
         concat!(
             "Offset of field: ",
@@ -795,7 +797,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).array_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).array_count as *const _ as usize },
         168usize,
         concat!(
             "Offset of field: ",
@@ -805,7 +807,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSMemoryUsage>())).fast_array_count as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSMemoryUsage>())).fast_array_count as *const _ as usize },
         176usize,
         concat!(
             "Offset of field: ",
@@ -816,7 +818,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMemoryUsage>())).fast_array_elements as *const _ as usize
+            &(*(::core::ptr::null::<JSMemoryUsage>())).fast_array_elements as *const _ as usize
         },
         184usize,
         concat!(
@@ -828,7 +830,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMemoryUsage>())).binary_object_count as *const _ as usize
+            &(*(::core::ptr::null::<JSMemoryUsage>())).binary_object_count as *const _ as usize
         },
         192usize,
         concat!(
@@ -840,7 +842,7 @@ fn bindgen_test_layout_JSMemoryUsage() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSMemoryUsage>())).binary_object_size as *const _ as usize
+            &(*(::core::ptr::null::<JSMemoryUsage>())).binary_object_size as *const _ as usize
         },
         200usize,
         concat!(
@@ -860,12 +862,12 @@ extern "C" {
 extern "C" {
     pub fn JS_NewAtomLen(
         ctx: *mut JSContext,
-        str: *const ::std::os::raw::c_char,
+        str: *const ::core::ffi::c_char,
         len: usize,
     ) -> JSAtom;
 }
 extern "C" {
-    pub fn JS_NewAtom(ctx: *mut JSContext, str: *const ::std::os::raw::c_char) -> JSAtom;
+    pub fn JS_NewAtom(ctx: *mut JSContext, str: *const ::core::ffi::c_char) -> JSAtom;
 }
 extern "C" {
     pub fn JS_NewAtomUInt32(ctx: *mut JSContext, n: u32) -> JSAtom;
@@ -886,7 +888,7 @@ extern "C" {
     pub fn JS_AtomToString(ctx: *mut JSContext, atom: JSAtom) -> JSValue;
 }
 extern "C" {
-    pub fn JS_AtomToCString(ctx: *mut JSContext, atom: JSAtom) -> *const ::std::os::raw::c_char;
+    pub fn JS_AtomToCString(ctx: *mut JSContext, atom: JSAtom) -> *const ::core::ffi::c_char;
 }
 extern "C" {
     pub fn JS_ValueToAtom(ctx: *mut JSContext, val: JSValue) -> JSAtom;
@@ -894,23 +896,23 @@ extern "C" {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSPropertyEnum {
-    pub is_enumerable: ::std::os::raw::c_int,
+    pub is_enumerable: ::core::ffi::c_int,
     pub atom: JSAtom,
 }
 #[test]
 fn bindgen_test_layout_JSPropertyEnum() {
     assert_eq!(
-        ::std::mem::size_of::<JSPropertyEnum>(),
+        ::core::mem::size_of::<JSPropertyEnum>(),
         8usize,
         concat!("Size of: ", stringify!(JSPropertyEnum))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSPropertyEnum>(),
+        ::core::mem::align_of::<JSPropertyEnum>(),
         4usize,
         concat!("Alignment of ", stringify!(JSPropertyEnum))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyEnum>())).is_enumerable as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyEnum>())).is_enumerable as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -920,7 +922,7 @@ fn bindgen_test_layout_JSPropertyEnum() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyEnum>())).atom as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyEnum>())).atom as *const _ as usize },
         4usize,
         concat!(
             "Offset of field: ",
@@ -933,7 +935,7 @@ fn bindgen_test_layout_JSPropertyEnum() {
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct JSPropertyDescriptor {
-    pub flags: ::std::os::raw::c_int,
+    pub flags: ::core::ffi::c_int,
     pub value: JSValue,
     pub getter: JSValue,
     pub setter: JSValue,
@@ -941,17 +943,17 @@ pub struct JSPropertyDescriptor {
 #[test]
 fn bindgen_test_layout_JSPropertyDescriptor() {
     assert_eq!(
-        ::std::mem::size_of::<JSPropertyDescriptor>(),
+        ::core::mem::size_of::<JSPropertyDescriptor>(),
         56usize,
         concat!("Size of: ", stringify!(JSPropertyDescriptor))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSPropertyDescriptor>(),
+        ::core::mem::align_of::<JSPropertyDescriptor>(),
         8usize,
         concat!("Alignment of ", stringify!(JSPropertyDescriptor))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyDescriptor>())).flags as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyDescriptor>())).flags as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -961,7 +963,7 @@ fn bindgen_test_layout_JSPropertyDescriptor() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyDescriptor>())).value as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyDescriptor>())).value as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -971,7 +973,7 @@ fn bindgen_test_layout_JSPropertyDescriptor() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyDescriptor>())).getter as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyDescriptor>())).getter as *const _ as usize },
         24usize,
         concat!(
             "Offset of field: ",
@@ -981,7 +983,7 @@ fn bindgen_test_layout_JSPropertyDescriptor() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSPropertyDescriptor>())).setter as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSPropertyDescriptor>())).setter as *const _ as usize },
         40usize,
         concat!(
             "Offset of field: ",
@@ -994,30 +996,26 @@ fn bindgen_test_layout_JSPropertyDescriptor() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSClassExoticMethods {
-    pub get_own_property: ::std::option::Option<
+    pub get_own_property: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             desc: *mut JSPropertyDescriptor,
             obj: JSValue,
             prop: JSAtom,
-        ) -> ::std::os::raw::c_int,
+        ) -> ::core::ffi::c_int,
     >,
-    pub get_own_property_names: ::std::option::Option<
+    pub get_own_property_names: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             ptab: *mut *mut JSPropertyEnum,
             plen: *mut u32,
             obj: JSValue,
-        ) -> ::std::os::raw::c_int,
+        ) -> ::core::ffi::c_int,
     >,
-    pub delete_property: ::std::option::Option<
-        unsafe extern "C" fn(
-            ctx: *mut JSContext,
-            obj: JSValue,
-            prop: JSAtom,
-        ) -> ::std::os::raw::c_int,
+    pub delete_property: ::core::option::Option<
+        unsafe extern "C" fn(ctx: *mut JSContext, obj: JSValue, prop: JSAtom) -> ::core::ffi::c_int,
     >,
-    pub define_own_property: ::std::option::Option<
+    pub define_own_property: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             this_obj: JSValue,
@@ -1025,17 +1023,13 @@ pub struct JSClassExoticMethods {
             val: JSValue,
             getter: JSValue,
             setter: JSValue,
-            flags: ::std::os::raw::c_int,
-        ) -> ::std::os::raw::c_int,
+            flags: ::core::ffi::c_int,
+        ) -> ::core::ffi::c_int,
     >,
-    pub has_property: ::std::option::Option<
-        unsafe extern "C" fn(
-            ctx: *mut JSContext,
-            obj: JSValue,
-            atom: JSAtom,
-        ) -> ::std::os::raw::c_int,
+    pub has_property: ::core::option::Option<
+        unsafe extern "C" fn(ctx: *mut JSContext, obj: JSValue, atom: JSAtom) -> ::core::ffi::c_int,
     >,
-    pub get_property: ::std::option::Option<
+    pub get_property: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             obj: JSValue,
@@ -1043,32 +1037,32 @@ pub struct JSClassExoticMethods {
             receiver: JSValue,
         ) -> JSValue,
     >,
-    pub set_property: ::std::option::Option<
+    pub set_property: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             obj: JSValue,
             atom: JSAtom,
             value: JSValue,
             receiver: JSValue,
-            flags: ::std::os::raw::c_int,
-        ) -> ::std::os::raw::c_int,
+            flags: ::core::ffi::c_int,
+        ) -> ::core::ffi::c_int,
     >,
 }
 #[test]
 fn bindgen_test_layout_JSClassExoticMethods() {
     assert_eq!(
-        ::std::mem::size_of::<JSClassExoticMethods>(),
+        ::core::mem::size_of::<JSClassExoticMethods>(),
         56usize,
         concat!("Size of: ", stringify!(JSClassExoticMethods))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSClassExoticMethods>(),
+        ::core::mem::align_of::<JSClassExoticMethods>(),
         8usize,
         concat!("Alignment of ", stringify!(JSClassExoticMethods))
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).get_own_property as *const _ as usize
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).get_own_property as *const _ as usize
         },
         0usize,
         concat!(
@@ -1080,7 +1074,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).get_own_property_names as *const _
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).get_own_property_names as *const _
                 as usize
         },
         8usize,
@@ -1093,7 +1087,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).delete_property as *const _ as usize
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).delete_property as *const _ as usize
         },
         16usize,
         concat!(
@@ -1105,7 +1099,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).define_own_property as *const _
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).define_own_property as *const _
                 as usize
         },
         24usize,
@@ -1118,7 +1112,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).has_property as *const _ as usize
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).has_property as *const _ as usize
         },
         32usize,
         concat!(
@@ -1130,7 +1124,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).get_property as *const _ as usize
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).get_property as *const _ as usize
         },
         40usize,
         concat!(
@@ -1142,7 +1136,7 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSClassExoticMethods>())).set_property as *const _ as usize
+            &(*(::core::ptr::null::<JSClassExoticMethods>())).set_property as *const _ as usize
         },
         48usize,
         concat!(
@@ -1154,23 +1148,23 @@ fn bindgen_test_layout_JSClassExoticMethods() {
     );
 }
 pub type JSClassFinalizer =
-    ::std::option::Option<unsafe extern "C" fn(rt: *mut JSRuntime, val: JSValue)>;
-pub type JSClassGCMark = ::std::option::Option<
+    ::core::option::Option<unsafe extern "C" fn(rt: *mut JSRuntime, val: JSValue)>;
+pub type JSClassGCMark = ::core::option::Option<
     unsafe extern "C" fn(rt: *mut JSRuntime, val: JSValue, mark_func: JS_MarkFunc),
 >;
-pub type JSClassCall = ::std::option::Option<
+pub type JSClassCall = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
         func_obj: JSValue,
         this_val: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue,
 >;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSClassDef {
-    pub class_name: *const ::std::os::raw::c_char,
+    pub class_name: *const ::core::ffi::c_char,
     pub finalizer: JSClassFinalizer,
     pub gc_mark: JSClassGCMark,
     pub call: JSClassCall,
@@ -1179,17 +1173,17 @@ pub struct JSClassDef {
 #[test]
 fn bindgen_test_layout_JSClassDef() {
     assert_eq!(
-        ::std::mem::size_of::<JSClassDef>(),
+        ::core::mem::size_of::<JSClassDef>(),
         40usize,
         concat!("Size of: ", stringify!(JSClassDef))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSClassDef>(),
+        ::core::mem::align_of::<JSClassDef>(),
         8usize,
         concat!("Alignment of ", stringify!(JSClassDef))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSClassDef>())).class_name as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSClassDef>())).class_name as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1199,7 +1193,7 @@ fn bindgen_test_layout_JSClassDef() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSClassDef>())).finalizer as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSClassDef>())).finalizer as *const _ as usize },
         8usize,
         concat!(
             "Offset of field: ",
@@ -1209,7 +1203,7 @@ fn bindgen_test_layout_JSClassDef() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSClassDef>())).gc_mark as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSClassDef>())).gc_mark as *const _ as usize },
         16usize,
         concat!(
             "Offset of field: ",
@@ -1219,7 +1213,7 @@ fn bindgen_test_layout_JSClassDef() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSClassDef>())).call as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSClassDef>())).call as *const _ as usize },
         24usize,
         concat!(
             "Offset of field: ",
@@ -1229,7 +1223,7 @@ fn bindgen_test_layout_JSClassDef() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSClassDef>())).exotic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSClassDef>())).exotic as *const _ as usize },
         32usize,
         concat!(
             "Offset of field: ",
@@ -1247,10 +1241,10 @@ extern "C" {
         rt: *mut JSRuntime,
         class_id: JSClassID,
         class_def: *const JSClassDef,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_IsRegisteredClass(rt: *mut JSRuntime, class_id: JSClassID) -> ::std::os::raw::c_int;
+    pub fn JS_IsRegisteredClass(rt: *mut JSRuntime, class_id: JSClassID) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_NewInt64(ctx: *mut JSContext, v: i64) -> JSValue;
@@ -1262,7 +1256,7 @@ extern "C" {
     pub fn JS_NewBigUint64(ctx: *mut JSContext, v: u64) -> JSValue;
 }
 extern "C" {
-    pub fn JS_IsNumber(v: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsNumber(v: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_Throw(ctx: *mut JSContext, obj: JSValue) -> JSValue;
@@ -1271,10 +1265,10 @@ extern "C" {
     pub fn JS_GetException(ctx: *mut JSContext) -> JSValue;
 }
 extern "C" {
-    pub fn JS_IsError(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsError(ctx: *mut JSContext, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_EnableIsErrorProperty(ctx: *mut JSContext, enable: ::std::os::raw::c_int);
+    pub fn JS_EnableIsErrorProperty(ctx: *mut JSContext, enable: ::core::ffi::c_int);
 }
 extern "C" {
     pub fn JS_ResetUncatchableError(ctx: *mut JSContext);
@@ -1285,35 +1279,28 @@ extern "C" {
 extern "C" {
     pub fn JS_ThrowSyntaxError(
         ctx: *mut JSContext,
-        fmt: *const ::std::os::raw::c_char,
+        fmt: *const ::core::ffi::c_char,
         ...
     ) -> JSValue;
 }
 extern "C" {
-    pub fn JS_ThrowTypeError(
-        ctx: *mut JSContext,
-        fmt: *const ::std::os::raw::c_char,
-        ...
-    ) -> JSValue;
+    pub fn JS_ThrowTypeError(ctx: *mut JSContext, fmt: *const ::core::ffi::c_char, ...) -> JSValue;
 }
 extern "C" {
     pub fn JS_ThrowReferenceError(
         ctx: *mut JSContext,
-        fmt: *const ::std::os::raw::c_char,
+        fmt: *const ::core::ffi::c_char,
         ...
     ) -> JSValue;
 }
 extern "C" {
-    pub fn JS_ThrowRangeError(
-        ctx: *mut JSContext,
-        fmt: *const ::std::os::raw::c_char,
-        ...
-    ) -> JSValue;
+    pub fn JS_ThrowRangeError(ctx: *mut JSContext, fmt: *const ::core::ffi::c_char, ...)
+        -> JSValue;
 }
 extern "C" {
     pub fn JS_ThrowInternalError(
         ctx: *mut JSContext,
-        fmt: *const ::std::os::raw::c_char,
+        fmt: *const ::core::ffi::c_char,
         ...
     ) -> JSValue;
 }
@@ -1327,40 +1314,35 @@ extern "C" {
     pub fn __JS_FreeValueRT(rt: *mut JSRuntime, v: JSValue);
 }
 extern "C" {
-    pub fn JS_ToBool(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_ToBool(ctx: *mut JSContext, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_ToInt32(ctx: *mut JSContext, pres: *mut i32, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_ToInt32(ctx: *mut JSContext, pres: *mut i32, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_ToInt64(ctx: *mut JSContext, pres: *mut i64, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_ToInt64(ctx: *mut JSContext, pres: *mut i64, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_ToIndex(ctx: *mut JSContext, plen: *mut u64, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_ToIndex(ctx: *mut JSContext, plen: *mut u64, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_ToFloat64(ctx: *mut JSContext, pres: *mut f64, val: JSValue)
-        -> ::std::os::raw::c_int;
+    pub fn JS_ToFloat64(ctx: *mut JSContext, pres: *mut f64, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_ToBigInt64(
-        ctx: *mut JSContext,
-        pres: *mut i64,
-        val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    pub fn JS_ToBigInt64(ctx: *mut JSContext, pres: *mut i64, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_NewStringLen(
         ctx: *mut JSContext,
-        str1: *const ::std::os::raw::c_char,
+        str1: *const ::core::ffi::c_char,
         len1: usize,
     ) -> JSValue;
 }
 extern "C" {
-    pub fn JS_NewString(ctx: *mut JSContext, str: *const ::std::os::raw::c_char) -> JSValue;
+    pub fn JS_NewString(ctx: *mut JSContext, str: *const ::core::ffi::c_char) -> JSValue;
 }
 extern "C" {
-    pub fn JS_NewAtomString(ctx: *mut JSContext, str: *const ::std::os::raw::c_char) -> JSValue;
+    pub fn JS_NewAtomString(ctx: *mut JSContext, str: *const ::core::ffi::c_char) -> JSValue;
 }
 extern "C" {
     pub fn JS_ToString(ctx: *mut JSContext, val: JSValue) -> JSValue;
@@ -1373,11 +1355,11 @@ extern "C" {
         ctx: *mut JSContext,
         plen: *mut usize,
         val1: JSValue,
-        cesu8: ::std::os::raw::c_int,
-    ) -> *const ::std::os::raw::c_char;
+        cesu8: ::core::ffi::c_int,
+    ) -> *const ::core::ffi::c_char;
 }
 extern "C" {
-    pub fn JS_FreeCString(ctx: *mut JSContext, ptr: *const ::std::os::raw::c_char);
+    pub fn JS_FreeCString(ctx: *mut JSContext, ptr: *const ::core::ffi::c_char);
 }
 extern "C" {
     pub fn JS_NewObjectProtoClass(
@@ -1387,7 +1369,7 @@ extern "C" {
     ) -> JSValue;
 }
 extern "C" {
-    pub fn JS_NewObjectClass(ctx: *mut JSContext, class_id: ::std::os::raw::c_int) -> JSValue;
+    pub fn JS_NewObjectClass(ctx: *mut JSContext, class_id: ::core::ffi::c_int) -> JSValue;
 }
 extern "C" {
     pub fn JS_NewObjectProto(ctx: *mut JSContext, proto: JSValue) -> JSValue;
@@ -1396,16 +1378,16 @@ extern "C" {
     pub fn JS_NewObject(ctx: *mut JSContext) -> JSValue;
 }
 extern "C" {
-    pub fn JS_IsFunction(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsFunction(ctx: *mut JSContext, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_IsConstructor(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsConstructor(ctx: *mut JSContext, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_NewArray(ctx: *mut JSContext) -> JSValue;
 }
 extern "C" {
-    pub fn JS_IsArray(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsArray(ctx: *mut JSContext, val: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_GetPropertyInternal(
@@ -1413,14 +1395,14 @@ extern "C" {
         obj: JSValue,
         prop: JSAtom,
         receiver: JSValue,
-        throw_ref_error: ::std::os::raw::c_int,
+        throw_ref_error: ::core::ffi::c_int,
     ) -> JSValue;
 }
 extern "C" {
     pub fn JS_GetPropertyStr(
         ctx: *mut JSContext,
         this_obj: JSValue,
-        prop: *const ::std::os::raw::c_char,
+        prop: *const ::core::ffi::c_char,
     ) -> JSValue;
 }
 extern "C" {
@@ -1432,8 +1414,8 @@ extern "C" {
         this_obj: JSValue,
         prop: JSAtom,
         val: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetPropertyUint32(
@@ -1441,7 +1423,7 @@ extern "C" {
         this_obj: JSValue,
         idx: u32,
         val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetPropertyInt64(
@@ -1449,43 +1431,43 @@ extern "C" {
         this_obj: JSValue,
         idx: i64,
         val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetPropertyStr(
         ctx: *mut JSContext,
         this_obj: JSValue,
-        prop: *const ::std::os::raw::c_char,
+        prop: *const ::core::ffi::c_char,
         val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_HasProperty(
         ctx: *mut JSContext,
         this_obj: JSValue,
         prop: JSAtom,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_IsExtensible(ctx: *mut JSContext, obj: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_IsExtensible(ctx: *mut JSContext, obj: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_PreventExtensions(ctx: *mut JSContext, obj: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_PreventExtensions(ctx: *mut JSContext, obj: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DeleteProperty(
         ctx: *mut JSContext,
         obj: JSValue,
         prop: JSAtom,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetPrototype(
         ctx: *mut JSContext,
         obj: JSValue,
         proto_val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_GetPrototype(ctx: *mut JSContext, val: JSValue) -> JSValue;
@@ -1496,8 +1478,8 @@ extern "C" {
         ptab: *mut *mut JSPropertyEnum,
         plen: *mut u32,
         obj: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_GetOwnProperty(
@@ -1505,14 +1487,14 @@ extern "C" {
         desc: *mut JSPropertyDescriptor,
         obj: JSValue,
         prop: JSAtom,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_ParseJSON(
         ctx: *mut JSContext,
-        buf: *const ::std::os::raw::c_char,
+        buf: *const ::core::ffi::c_char,
         buf_len: usize,
-        filename: *const ::std::os::raw::c_char,
+        filename: *const ::core::ffi::c_char,
     ) -> JSValue;
 }
 extern "C" {
@@ -1520,7 +1502,7 @@ extern "C" {
         ctx: *mut JSContext,
         func_obj: JSValue,
         this_obj: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue;
 }
@@ -1529,7 +1511,7 @@ extern "C" {
         ctx: *mut JSContext,
         this_val: JSValue,
         atom: JSAtom,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue;
 }
@@ -1537,7 +1519,7 @@ extern "C" {
     pub fn JS_CallConstructor(
         ctx: *mut JSContext,
         func_obj: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue;
 }
@@ -1546,23 +1528,23 @@ extern "C" {
         ctx: *mut JSContext,
         func_obj: JSValue,
         new_target: JSValue,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue;
 }
 extern "C" {
     pub fn JS_DetectModule(
-        input: *const ::std::os::raw::c_char,
+        input: *const ::core::ffi::c_char,
         input_len: usize,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_Eval(
         ctx: *mut JSContext,
-        input: *const ::std::os::raw::c_char,
+        input: *const ::core::ffi::c_char,
         input_len: usize,
-        filename: *const ::std::os::raw::c_char,
-        eval_flags: ::std::os::raw::c_int,
+        filename: *const ::core::ffi::c_char,
+        eval_flags: ::core::ffi::c_int,
     ) -> JSValue;
 }
 extern "C" {
@@ -1572,11 +1554,7 @@ extern "C" {
     pub fn JS_GetGlobalObject(ctx: *mut JSContext) -> JSValue;
 }
 extern "C" {
-    pub fn JS_IsInstanceOf(
-        ctx: *mut JSContext,
-        val: JSValue,
-        obj: JSValue,
-    ) -> ::std::os::raw::c_int;
+    pub fn JS_IsInstanceOf(ctx: *mut JSContext, val: JSValue, obj: JSValue) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DefineProperty(
@@ -1586,8 +1564,8 @@ extern "C" {
         val: JSValue,
         getter: JSValue,
         setter: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DefinePropertyValue(
@@ -1595,8 +1573,8 @@ extern "C" {
         this_obj: JSValue,
         prop: JSAtom,
         val: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DefinePropertyValueUint32(
@@ -1604,17 +1582,17 @@ extern "C" {
         this_obj: JSValue,
         idx: u32,
         val: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DefinePropertyValueStr(
         ctx: *mut JSContext,
         this_obj: JSValue,
-        prop: *const ::std::os::raw::c_char,
+        prop: *const ::core::ffi::c_char,
         val: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_DefinePropertyGetSet(
@@ -1623,27 +1601,27 @@ extern "C" {
         prop: JSAtom,
         getter: JSValue,
         setter: JSValue,
-        flags: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_SetOpaque(obj: JSValue, opaque: *mut ::std::os::raw::c_void);
+    pub fn JS_SetOpaque(obj: JSValue, opaque: *mut ::core::ffi::c_void);
 }
 extern "C" {
-    pub fn JS_GetOpaque(obj: JSValue, class_id: JSClassID) -> *mut ::std::os::raw::c_void;
+    pub fn JS_GetOpaque(obj: JSValue, class_id: JSClassID) -> *mut ::core::ffi::c_void;
 }
 extern "C" {
     pub fn JS_GetOpaque2(
         ctx: *mut JSContext,
         obj: JSValue,
         class_id: JSClassID,
-    ) -> *mut ::std::os::raw::c_void;
+    ) -> *mut ::core::ffi::c_void;
 }
-pub type JSFreeArrayBufferDataFunc = ::std::option::Option<
+pub type JSFreeArrayBufferDataFunc = ::core::option::Option<
     unsafe extern "C" fn(
         rt: *mut JSRuntime,
-        opaque: *mut ::std::os::raw::c_void,
-        ptr: *mut ::std::os::raw::c_void,
+        opaque: *mut ::core::ffi::c_void,
+        ptr: *mut ::core::ffi::c_void,
     ),
 >;
 extern "C" {
@@ -1652,8 +1630,8 @@ extern "C" {
         buf: *mut u8,
         len: usize,
         free_func: JSFreeArrayBufferDataFunc,
-        opaque: *mut ::std::os::raw::c_void,
-        is_shared: ::std::os::raw::c_int,
+        opaque: *mut ::core::ffi::c_void,
+        is_shared: ::core::ffi::c_int,
     ) -> JSValue;
 }
 extern "C" {
@@ -1668,40 +1646,40 @@ extern "C" {
 extern "C" {
     pub fn JS_NewPromiseCapability(ctx: *mut JSContext, resolving_funcs: *mut JSValue) -> JSValue;
 }
-pub type JSInterruptHandler = ::std::option::Option<
+pub type JSInterruptHandler = ::core::option::Option<
     unsafe extern "C" fn(
         rt: *mut JSRuntime,
-        opaque: *mut ::std::os::raw::c_void,
-    ) -> ::std::os::raw::c_int,
+        opaque: *mut ::core::ffi::c_void,
+    ) -> ::core::ffi::c_int,
 >;
 extern "C" {
     pub fn JS_SetInterruptHandler(
         rt: *mut JSRuntime,
         cb: JSInterruptHandler,
-        opaque: *mut ::std::os::raw::c_void,
+        opaque: *mut ::core::ffi::c_void,
     );
 }
 extern "C" {
-    pub fn JS_SetCanBlock(rt: *mut JSRuntime, can_block: ::std::os::raw::c_int);
+    pub fn JS_SetCanBlock(rt: *mut JSRuntime, can_block: ::core::ffi::c_int);
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSModuleDef {
     _unused: [u8; 0],
 }
-pub type JSModuleNormalizeFunc = ::std::option::Option<
+pub type JSModuleNormalizeFunc = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
-        module_base_name: *const ::std::os::raw::c_char,
-        module_name: *const ::std::os::raw::c_char,
-        opaque: *mut ::std::os::raw::c_void,
-    ) -> *mut ::std::os::raw::c_char,
+        module_base_name: *const ::core::ffi::c_char,
+        module_name: *const ::core::ffi::c_char,
+        opaque: *mut ::core::ffi::c_void,
+    ) -> *mut ::core::ffi::c_char,
 >;
-pub type JSModuleLoaderFunc = ::std::option::Option<
+pub type JSModuleLoaderFunc = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
-        module_name: *const ::std::os::raw::c_char,
-        opaque: *mut ::std::os::raw::c_void,
+        module_name: *const ::core::ffi::c_char,
+        opaque: *mut ::core::ffi::c_void,
     ) -> *mut JSModuleDef,
 >;
 extern "C" {
@@ -1709,7 +1687,7 @@ extern "C" {
         rt: *mut JSRuntime,
         module_normalize: JSModuleNormalizeFunc,
         module_loader: JSModuleLoaderFunc,
-        opaque: *mut ::std::os::raw::c_void,
+        opaque: *mut ::core::ffi::c_void,
     );
 }
 extern "C" {
@@ -1718,10 +1696,10 @@ extern "C" {
 extern "C" {
     pub fn JS_GetModuleName(ctx: *mut JSContext, m: *mut JSModuleDef) -> JSAtom;
 }
-pub type JSJobFunc = ::std::option::Option<
+pub type JSJobFunc = ::core::option::Option<
     unsafe extern "C" fn(
         ctx: *mut JSContext,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
     ) -> JSValue,
 >;
@@ -1729,25 +1707,25 @@ extern "C" {
     pub fn JS_EnqueueJob(
         ctx: *mut JSContext,
         job_func: JSJobFunc,
-        argc: ::std::os::raw::c_int,
+        argc: ::core::ffi::c_int,
         argv: *mut JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
-    pub fn JS_IsJobPending(rt: *mut JSRuntime) -> ::std::os::raw::c_int;
+    pub fn JS_IsJobPending(rt: *mut JSRuntime) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_ExecutePendingJob(
         rt: *mut JSRuntime,
         pctx: *mut *mut JSContext,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_WriteObject(
         ctx: *mut JSContext,
         psize: *mut usize,
         obj: JSValue,
-        flags: ::std::os::raw::c_int,
+        flags: ::core::ffi::c_int,
     ) -> *mut u8;
 }
 extern "C" {
@@ -1755,11 +1733,11 @@ extern "C" {
         ctx: *mut JSContext,
         buf: *const u8,
         buf_len: usize,
-        flags: ::std::os::raw::c_int,
+        flags: ::core::ffi::c_int,
     ) -> JSValue;
 }
 extern "C" {
-    pub fn JS_ResolveModule(ctx: *mut JSContext, obj: JSValue) -> ::std::os::raw::c_int;
+    pub fn JS_ResolveModule(ctx: *mut JSContext, obj: JSValue) -> ::core::ffi::c_int;
 }
 pub mod JSCFunctionEnum {
     pub type Type = u32;
@@ -1781,57 +1759,57 @@ pub mod JSCFunctionEnum {
 #[derive(Copy, Clone)]
 pub union JSCFunctionType {
     pub generic: JSCFunction,
-    pub generic_magic: ::std::option::Option<
+    pub generic_magic: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             this_val: JSValue,
-            argc: ::std::os::raw::c_int,
+            argc: ::core::ffi::c_int,
             argv: *mut JSValue,
-            magic: ::std::os::raw::c_int,
+            magic: ::core::ffi::c_int,
         ) -> JSValue,
     >,
     pub constructor: JSCFunction,
-    pub constructor_magic: ::std::option::Option<
+    pub constructor_magic: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             new_target: JSValue,
-            argc: ::std::os::raw::c_int,
+            argc: ::core::ffi::c_int,
             argv: *mut JSValue,
-            magic: ::std::os::raw::c_int,
+            magic: ::core::ffi::c_int,
         ) -> JSValue,
     >,
     pub constructor_or_func: JSCFunction,
-    pub f_f: ::std::option::Option<unsafe extern "C" fn(arg1: f64) -> f64>,
-    pub f_f_f: ::std::option::Option<unsafe extern "C" fn(arg1: f64, arg2: f64) -> f64>,
-    pub getter: ::std::option::Option<
+    pub f_f: ::core::option::Option<unsafe extern "C" fn(arg1: f64) -> f64>,
+    pub f_f_f: ::core::option::Option<unsafe extern "C" fn(arg1: f64, arg2: f64) -> f64>,
+    pub getter: ::core::option::Option<
         unsafe extern "C" fn(ctx: *mut JSContext, this_val: JSValue) -> JSValue,
     >,
-    pub setter: ::std::option::Option<
+    pub setter: ::core::option::Option<
         unsafe extern "C" fn(ctx: *mut JSContext, this_val: JSValue, val: JSValue) -> JSValue,
     >,
-    pub getter_magic: ::std::option::Option<
+    pub getter_magic: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             this_val: JSValue,
-            magic: ::std::os::raw::c_int,
+            magic: ::core::ffi::c_int,
         ) -> JSValue,
     >,
-    pub setter_magic: ::std::option::Option<
+    pub setter_magic: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             this_val: JSValue,
             val: JSValue,
-            magic: ::std::os::raw::c_int,
+            magic: ::core::ffi::c_int,
         ) -> JSValue,
     >,
-    pub iterator_next: ::std::option::Option<
+    pub iterator_next: ::core::option::Option<
         unsafe extern "C" fn(
             ctx: *mut JSContext,
             this_val: JSValue,
-            argc: ::std::os::raw::c_int,
+            argc: ::core::ffi::c_int,
             argv: *mut JSValue,
-            pdone: *mut ::std::os::raw::c_int,
-            magic: ::std::os::raw::c_int,
+            pdone: *mut ::core::ffi::c_int,
+            magic: ::core::ffi::c_int,
         ) -> JSValue,
     >,
     _bindgen_union_align: u64,
@@ -1839,17 +1817,17 @@ pub union JSCFunctionType {
 #[test]
 fn bindgen_test_layout_JSCFunctionType() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionType>(),
+        ::core::mem::size_of::<JSCFunctionType>(),
         8usize,
         concat!("Size of: ", stringify!(JSCFunctionType))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionType>(),
+        ::core::mem::align_of::<JSCFunctionType>(),
         8usize,
         concat!("Alignment of ", stringify!(JSCFunctionType))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).generic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).generic as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1859,7 +1837,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).generic_magic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).generic_magic as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1869,7 +1847,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).constructor as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).constructor as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1880,7 +1858,7 @@ fn bindgen_test_layout_JSCFunctionType() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionType>())).constructor_magic as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionType>())).constructor_magic as *const _ as usize
         },
         0usize,
         concat!(
@@ -1892,7 +1870,7 @@ fn bindgen_test_layout_JSCFunctionType() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionType>())).constructor_or_func as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionType>())).constructor_or_func as *const _ as usize
         },
         0usize,
         concat!(
@@ -1903,7 +1881,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).f_f as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).f_f as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1913,7 +1891,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).f_f_f as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).f_f_f as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1923,7 +1901,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).getter as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).getter as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1933,7 +1911,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).setter as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).setter as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1943,7 +1921,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).getter_magic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).getter_magic as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1953,7 +1931,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).setter_magic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).setter_magic as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1963,7 +1941,7 @@ fn bindgen_test_layout_JSCFunctionType() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionType>())).iterator_next as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionType>())).iterator_next as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -1977,26 +1955,26 @@ extern "C" {
     pub fn JS_NewCFunction2(
         ctx: *mut JSContext,
         func: JSCFunction,
-        name: *const ::std::os::raw::c_char,
-        length: ::std::os::raw::c_int,
+        name: *const ::core::ffi::c_char,
+        length: ::core::ffi::c_int,
         cproto: JSCFunctionEnum::Type,
-        magic: ::std::os::raw::c_int,
+        magic: ::core::ffi::c_int,
     ) -> JSValue;
 }
 extern "C" {
     pub fn JS_NewCFunctionData(
         ctx: *mut JSContext,
         func: JSCFunctionData,
-        length: ::std::os::raw::c_int,
-        magic: ::std::os::raw::c_int,
-        data_len: ::std::os::raw::c_int,
+        length: ::core::ffi::c_int,
+        magic: ::core::ffi::c_int,
+        data_len: ::core::ffi::c_int,
         data: *mut JSValue,
     ) -> JSValue;
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct JSCFunctionListEntry {
-    pub name: *const ::std::os::raw::c_char,
+    pub name: *const ::core::ffi::c_char,
     pub prop_flags: u8,
     pub def_type: u8,
     pub magic: i16,
@@ -2009,7 +1987,7 @@ pub union JSCFunctionListEntry__bindgen_ty_1 {
     pub getset: JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2,
     pub alias: JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3,
     pub prop_list: JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4,
-    pub str: *const ::std::os::raw::c_char,
+    pub str: *const ::core::ffi::c_char,
     pub i32: i32,
     pub i64: i64,
     pub f64: f64,
@@ -2025,7 +2003,7 @@ pub struct JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1 {
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>(),
+        ::core::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>(),
         16usize,
         concat!(
             "Size of: ",
@@ -2033,7 +2011,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1() {
         )
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>(),
+        ::core::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>(),
         8usize,
         concat!(
             "Alignment of ",
@@ -2042,7 +2020,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).length
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).length
                 as *const _ as usize
         },
         0usize,
@@ -2055,7 +2033,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).cproto
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).cproto
                 as *const _ as usize
         },
         1usize,
@@ -2068,7 +2046,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).cfunc
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1>())).cfunc
                 as *const _ as usize
         },
         8usize,
@@ -2089,7 +2067,7 @@ pub struct JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2 {
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>(),
+        ::core::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>(),
         16usize,
         concat!(
             "Size of: ",
@@ -2097,7 +2075,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2() {
         )
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>(),
+        ::core::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>(),
         8usize,
         concat!(
             "Alignment of ",
@@ -2106,7 +2084,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>())).get
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>())).get
                 as *const _ as usize
         },
         0usize,
@@ -2119,7 +2097,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>())).set
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2>())).set
                 as *const _ as usize
         },
         8usize,
@@ -2134,13 +2112,13 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_2() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3 {
-    pub name: *const ::std::os::raw::c_char,
-    pub base: ::std::os::raw::c_int,
+    pub name: *const ::core::ffi::c_char,
+    pub base: ::core::ffi::c_int,
 }
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>(),
+        ::core::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>(),
         16usize,
         concat!(
             "Size of: ",
@@ -2148,7 +2126,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3() {
         )
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>(),
+        ::core::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>(),
         8usize,
         concat!(
             "Alignment of ",
@@ -2157,7 +2135,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>())).name
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>())).name
                 as *const _ as usize
         },
         0usize,
@@ -2170,7 +2148,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>())).base
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3>())).base
                 as *const _ as usize
         },
         8usize,
@@ -2186,12 +2164,12 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_3() {
 #[derive(Debug, Copy, Clone)]
 pub struct JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4 {
     pub tab: *const JSCFunctionListEntry,
-    pub len: ::std::os::raw::c_int,
+    pub len: ::core::ffi::c_int,
 }
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>(),
+        ::core::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>(),
         16usize,
         concat!(
             "Size of: ",
@@ -2199,7 +2177,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4() {
         )
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>(),
+        ::core::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>(),
         8usize,
         concat!(
             "Alignment of ",
@@ -2208,7 +2186,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>())).tab
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>())).tab
                 as *const _ as usize
         },
         0usize,
@@ -2221,7 +2199,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>())).len
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4>())).len
                 as *const _ as usize
         },
         8usize,
@@ -2236,12 +2214,12 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_4() {
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1>(),
+        ::core::mem::size_of::<JSCFunctionListEntry__bindgen_ty_1>(),
         16usize,
         concat!("Size of: ", stringify!(JSCFunctionListEntry__bindgen_ty_1))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1>(),
+        ::core::mem::align_of::<JSCFunctionListEntry__bindgen_ty_1>(),
         8usize,
         concat!(
             "Alignment of ",
@@ -2250,7 +2228,8 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).func as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).func as *const _
+                as usize
         },
         0usize,
         concat!(
@@ -2262,7 +2241,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).getset as *const _
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).getset as *const _
                 as usize
         },
         0usize,
@@ -2275,7 +2254,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).alias as *const _
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).alias as *const _
                 as usize
         },
         0usize,
@@ -2288,7 +2267,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).prop_list as *const _
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).prop_list as *const _
                 as usize
         },
         0usize,
@@ -2301,7 +2280,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).str as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).str as *const _ as usize
         },
         0usize,
         concat!(
@@ -2313,7 +2292,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).i32 as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).i32 as *const _ as usize
         },
         0usize,
         concat!(
@@ -2325,7 +2304,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).i64 as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).i64 as *const _ as usize
         },
         0usize,
         concat!(
@@ -2337,7 +2316,7 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
     );
     assert_eq!(
         unsafe {
-            &(*(::std::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).f64 as *const _ as usize
+            &(*(::core::ptr::null::<JSCFunctionListEntry__bindgen_ty_1>())).f64 as *const _ as usize
         },
         0usize,
         concat!(
@@ -2351,17 +2330,17 @@ fn bindgen_test_layout_JSCFunctionListEntry__bindgen_ty_1() {
 #[test]
 fn bindgen_test_layout_JSCFunctionListEntry() {
     assert_eq!(
-        ::std::mem::size_of::<JSCFunctionListEntry>(),
+        ::core::mem::size_of::<JSCFunctionListEntry>(),
         32usize,
         concat!("Size of: ", stringify!(JSCFunctionListEntry))
     );
     assert_eq!(
-        ::std::mem::align_of::<JSCFunctionListEntry>(),
+        ::core::mem::align_of::<JSCFunctionListEntry>(),
         8usize,
         concat!("Alignment of ", stringify!(JSCFunctionListEntry))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionListEntry>())).name as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionListEntry>())).name as *const _ as usize },
         0usize,
         concat!(
             "Offset of field: ",
@@ -2371,7 +2350,9 @@ fn bindgen_test_layout_JSCFunctionListEntry() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionListEntry>())).prop_flags as *const _ as usize },
+        unsafe {
+            &(*(::core::ptr::null::<JSCFunctionListEntry>())).prop_flags as *const _ as usize
+        },
         8usize,
         concat!(
             "Offset of field: ",
@@ -2381,7 +2362,7 @@ fn bindgen_test_layout_JSCFunctionListEntry() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionListEntry>())).def_type as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionListEntry>())).def_type as *const _ as usize },
         9usize,
         concat!(
             "Offset of field: ",
@@ -2391,7 +2372,7 @@ fn bindgen_test_layout_JSCFunctionListEntry() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionListEntry>())).magic as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionListEntry>())).magic as *const _ as usize },
         10usize,
         concat!(
             "Offset of field: ",
@@ -2401,7 +2382,7 @@ fn bindgen_test_layout_JSCFunctionListEntry() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<JSCFunctionListEntry>())).u as *const _ as usize },
+        unsafe { &(*(::core::ptr::null::<JSCFunctionListEntry>())).u as *const _ as usize },
         16usize,
         concat!(
             "Offset of field: ",
@@ -2416,16 +2397,16 @@ extern "C" {
         ctx: *mut JSContext,
         obj: JSValue,
         tab: *const JSCFunctionListEntry,
-        len: ::std::os::raw::c_int,
+        len: ::core::ffi::c_int,
     );
 }
-pub type JSModuleInitFunc = ::std::option::Option<
-    unsafe extern "C" fn(ctx: *mut JSContext, m: *mut JSModuleDef) -> ::std::os::raw::c_int,
+pub type JSModuleInitFunc = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut JSContext, m: *mut JSModuleDef) -> ::core::ffi::c_int,
 >;
 extern "C" {
     pub fn JS_NewCModule(
         ctx: *mut JSContext,
-        name_str: *const ::std::os::raw::c_char,
+        name_str: *const ::core::ffi::c_char,
         func: JSModuleInitFunc,
     ) -> *mut JSModuleDef;
 }
@@ -2433,50 +2414,50 @@ extern "C" {
     pub fn JS_AddModuleExport(
         ctx: *mut JSContext,
         m: *mut JSModuleDef,
-        name_str: *const ::std::os::raw::c_char,
-    ) -> ::std::os::raw::c_int;
+        name_str: *const ::core::ffi::c_char,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_AddModuleExportList(
         ctx: *mut JSContext,
         m: *mut JSModuleDef,
         tab: *const JSCFunctionListEntry,
-        len: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        len: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetModuleExport(
         ctx: *mut JSContext,
         m: *mut JSModuleDef,
-        export_name: *const ::std::os::raw::c_char,
+        export_name: *const ::core::ffi::c_char,
         val: JSValue,
-    ) -> ::std::os::raw::c_int;
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn JS_SetModuleExportList(
         ctx: *mut JSContext,
         m: *mut JSModuleDef,
         tab: *const JSCFunctionListEntry,
-        len: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        len: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn js_init_module_std(
         ctx: *mut JSContext,
-        module_name: *const ::std::os::raw::c_char,
+        module_name: *const ::core::ffi::c_char,
     ) -> *mut JSModuleDef;
 }
 extern "C" {
     pub fn js_init_module_os(
         ctx: *mut JSContext,
-        module_name: *const ::std::os::raw::c_char,
+        module_name: *const ::core::ffi::c_char,
     ) -> *mut JSModuleDef;
 }
 extern "C" {
     pub fn js_std_add_helpers(
         ctx: *mut JSContext,
-        argc: ::std::os::raw::c_int,
-        argv: *mut *mut ::std::os::raw::c_char,
+        argc: ::core::ffi::c_int,
+        argv: *mut *mut ::core::ffi::c_char,
     );
 }
 extern "C" {
@@ -2492,22 +2473,22 @@ extern "C" {
     pub fn js_load_file(
         ctx: *mut JSContext,
         pbuf_len: *mut usize,
-        filename: *const ::std::os::raw::c_char,
+        filename: *const ::core::ffi::c_char,
     ) -> *mut u8;
 }
 extern "C" {
     pub fn js_module_set_import_meta(
         ctx: *mut JSContext,
         func_val: JSValue,
-        use_realpath: ::std::os::raw::c_int,
-        is_main: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_int;
+        use_realpath: ::core::ffi::c_int,
+        is_main: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
 }
 extern "C" {
     pub fn js_module_loader(
         ctx: *mut JSContext,
-        module_name: *const ::std::os::raw::c_char,
-        opaque: *mut ::std::os::raw::c_void,
+        module_name: *const ::core::ffi::c_char,
+        opaque: *mut ::core::ffi::c_void,
     ) -> *mut JSModuleDef;
 }
 extern "C" {
@@ -2515,6 +2496,6 @@ extern "C" {
         ctx: *mut JSContext,
         buf: *const u8,
         buf_len: usize,
-        flags: ::std::os::raw::c_int,
+        flags: ::core::ffi::c_int,
     );
 }
@@ -0,0 +1,279 @@
+use std::os::raw::c_int;
+use std::slice;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, DefinePropertyGetSet, Local, NewValue, Prop, Value};
+
+/// The setter of an accessor property, receives the `this` object and the assigned value.
+pub type Setter = fn(&ContextRef, &Local<Value>, &Local<Value>) -> Result<(), Error>;
+
+type GetterHandler = Box<dyn Fn(&ContextRef, &Local<Value>) -> ffi::JSValue>;
+
+type SetterHandler = Box<dyn Fn(&ContextRef, &Local<Value>, &Local<Value>) -> Result<(), Error>>;
+
+impl Local<'_, Value> {
+    /// Defines an accessor property backed by the Rust closures,
+    /// the getter is called with the `this` object each time the property is read.
+    ///
+    /// The property is configurable and enumerable, like the accessors of an object literal,
+    /// assigning to an accessor without setter is ignored, or throws a `TypeError` in the strict mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// use qjs::{Context, ContextRef, Eval, Local, Runtime, Setter, Value};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let hits = Rc::new(Cell::new(0));
+    /// let stats = ctxt.bind(ctxt.new_object());
+    ///
+    /// stats
+    ///     .define_accessor(
+    ///         "hits",
+    ///         {
+    ///             let hits = hits.clone();
+    ///             move |_ctxt, _this| Ok(hits.get())
+    ///         },
+    ///         Some({
+    ///             let hits = hits.clone();
+    ///             move |_ctxt: &ContextRef, _this: &Local<Value>, value: &Local<Value>| {
+    ///                 hits.set(value.to_int32().unwrap_or_default());
+    ///                 Ok(())
+    ///             }
+    ///         }),
+    ///     )
+    ///     .unwrap();
+    /// stats
+    ///     .define_accessor("version", |_ctxt, _this| Ok("1.0"), None::<Setter>)
+    ///     .unwrap();
+    /// ctxt.global_object().set_property("stats", stats).unwrap();
+    ///
+    /// hits.set(41);
+    ///
+    /// assert_eq!(ctxt.eval("++stats.hits", Eval::GLOBAL).unwrap(), Some(42));
+    /// assert_eq!(hits.get(), 42);
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("stats.version = '2.0'; stats.version", Eval::GLOBAL).unwrap(),
+    ///     Some("1.0".to_owned())
+    /// );
+    /// ```
+    pub fn define_accessor<P, G, T, S>(
+        &self,
+        prop: P,
+        getter: G,
+        setter: Option<S>,
+    ) -> Result<bool, Error>
+    where
+        P: DefinePropertyGetSet,
+        G: Fn(&ContextRef, &Local<Value>) -> Result<T, Error> + 'static,
+        T: NewValue,
+        S: Fn(&ContextRef, &Local<Value>, &Local<Value>) -> Result<(), Error> + 'static,
+    {
+        self.ctxt.define_accessor(self, prop, getter, setter)
+    }
+}
+
+impl ContextRef {
+    /// Defines an accessor property backed by the Rust closures on an object.
+    pub fn define_accessor<P, G, T, S>(
+        &self,
+        this: &Value,
+        prop: P,
+        getter: G,
+        setter: Option<S>,
+    ) -> Result<bool, Error>
+    where
+        P: DefinePropertyGetSet,
+        G: Fn(&ContextRef, &Local<Value>) -> Result<T, Error> + 'static,
+        T: NewValue,
+        S: Fn(&ContextRef, &Local<Value>, &Local<Value>) -> Result<(), Error> + 'static,
+    {
+        let getter: GetterHandler = Box::new(move |ctxt, this| {
+            getter(ctxt, this)
+                .map(|v| ctxt.bind(v.new_value(ctxt)))
+                .new_value(ctxt)
+        });
+        let getter = self
+            .new_c_function_data(getter_stub, 0, 0, self.new_userdata(getter))?
+            .into_inner_untracked();
+        let setter = match setter {
+            Some(setter) => {
+                let setter: SetterHandler = Box::new(setter);
+
+                Some(
+                    self.new_c_function_data(setter_stub, 1, 0, self.new_userdata(setter))?
+                        .into_inner_untracked(),
+                )
+            }
+            None => None,
+        };
+
+        // the getter and setter are freed by `JS_DefinePropertyGetSet`
+        self.define_property_get_set(
+            this,
+            prop,
+            Some(&getter),
+            setter.as_ref(),
+            Prop::CONFIGURABLE | Prop::ENUMERABLE,
+        )
+    }
+}
+
+unsafe extern "C" fn getter_stub(
+    ctx: *mut ffi::JSContext,
+    this_val: ffi::JSValue,
+    _argc: c_int,
+    _argv: *mut ffi::JSValue,
+    _magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let getter = ctxt.clone_value(&Value::from(*data));
+        let getter = match getter.downcast_ref::<GetterHandler>() {
+            Ok(getter) => getter,
+            Err(err) => return Err::<Value, _>(err).new_value(ctxt),
+        };
+
+        getter(ctxt, &ctxt.clone_value(&Value::from(this_val)))
+    })
+}
+
+unsafe extern "C" fn setter_stub(
+    ctx: *mut ffi::JSContext,
+    this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    _magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let setter = ctxt.clone_value(&Value::from(*data));
+        let setter = match setter.downcast_ref::<SetterHandler>() {
+            Ok(setter) => setter,
+            Err(err) => return Err::<Value, _>(err).new_value(ctxt),
+        };
+        let args = slice::from_raw_parts(argv, argc as usize);
+        let value = args.first().map_or(ffi::UNDEFINED, |v| *v);
+
+        setter(
+            ctxt,
+            &ctxt.clone_value(&Value::from(this_val)),
+            &ctxt.clone_value(&Value::from(value)),
+        )
+        .map(|_| ctxt.undefined())
+        .new_value(ctxt)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use failure::err_msg;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn accessor() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let names = Rc::new(RefCell::new(vec!["foo".to_owned()]));
+        let proto = ctxt.bind(ctxt.new_object());
+
+        proto
+            .define_accessor(
+                "names",
+                {
+                    let names = names.clone();
+                    move |_ctxt, _this| Ok(names.borrow().join(","))
+                },
+                Some({
+                    let names = names.clone();
+                    move |_ctxt: &ContextRef, _this: &Local<Value>, value: &Local<Value>| {
+                        if !value.is_string() {
+                            return Err(err_msg("expected a string"));
+                        }
+
+                        names.borrow_mut().push(value.to_string());
+
+                        Ok(())
+                    }
+                }),
+            )
+            .unwrap();
+        proto
+            .define_accessor(
+                "tag",
+                |_ctxt, this| {
+                    this.get_property("id")
+                        .and_then(|id| id.to_int32())
+                        .map(|id| format!("#{}", id))
+                        .ok_or_else(|| err_msg("missing id"))
+                },
+                None::<Setter>,
+            )
+            .unwrap();
+        ctxt.global_object().set_property("proto", proto).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "var obj = Object.create(proto); obj.names = 'bar'; obj.names",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("foo,bar".to_owned())
+        );
+        assert_eq!(*names.borrow(), vec!["foo", "bar"]);
+
+        // the getter is called with the receiver
+        assert_eq!(
+            ctxt.eval::<_, String>("obj.id = 7; obj.tag", Eval::GLOBAL)
+                .unwrap(),
+            Some("#7".to_owned())
+        );
+
+        // the errors are thrown to the scripts
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[proto.tag, obj.names = 1].map(String).join()",
+                Eval::GLOBAL
+            )
+            .unwrap_err()
+            .to_string(),
+            "Error: missing id"
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { obj.names = 1 } catch (err) { err.message }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("expected a string".to_owned())
+        );
+
+        // the property without setter is read-only
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "'use strict'; try { obj.tag = 1 } catch (err) { err.name }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("TypeError".to_owned())
+        );
+    }
+}
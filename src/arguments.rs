@@ -0,0 +1,243 @@
+use std::any;
+
+use failure::Error;
+
+use crate::{ContextRef, ErrorKind, ExtractValue, Local, Value};
+
+/// A view over the `this` value and arguments of a native function,
+/// extracts them to the Rust types with the errors which tell the position of the bad argument.
+///
+/// The errors are `TypeError`s, which are thrown to the scripts when returned from the native function.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Arguments, Context, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let join = ctxt
+///     .new_c_function(
+///         |ctxt, this, args| {
+///             let args = Arguments::new(ctxt, this, args).with_name("join");
+///             let sep = args.get::<String>(0)?;
+///             let limit = args.opt::<i32>(1)?.unwrap_or(i32::max_value());
+///             let items = args.rest_from::<String>(2)?;
+///
+///             Ok(items.into_iter().take(limit as usize).collect::<Vec<_>>().join(&sep))
+///         },
+///         Some("join"),
+///         1,
+///     )
+///     .unwrap();
+///
+/// ctxt.global_object().set_property("join", join).unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("join('-', undefined, 'a', 'b', 'c')", Eval::GLOBAL).unwrap(),
+///     Some("a-b-c".to_owned())
+/// );
+/// assert_eq!(
+///     ctxt.eval::<_, String>("try { join() } catch (err) { err.message }", Eval::GLOBAL).unwrap(),
+///     Some("join: missing argument 0, expected alloc::string::String".to_owned())
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Arguments<'a> {
+    ctxt: &'a ContextRef,
+    this: Option<&'a Value>,
+    args: &'a [Value],
+    name: Option<&'a str>,
+}
+
+impl<'a> Arguments<'a> {
+    /// Create a view over the `this` value and arguments of a native function.
+    pub fn new(ctxt: &'a ContextRef, this: Option<&'a Value>, args: &'a [Value]) -> Self {
+        Arguments {
+            ctxt,
+            this,
+            args,
+            name: None,
+        }
+    }
+
+    /// Set the function name which prefixes the error messages.
+    pub fn with_name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Returns the number of arguments.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns `true` if there are no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Returns the argument at the position.
+    pub fn value(&self, idx: usize) -> Option<Local<'a, Value>> {
+        self.args.get(idx).map(|v| self.ctxt.clone_value(v))
+    }
+
+    /// Extract the required argument at the position.
+    pub fn get<T: ExtractValue>(&self, idx: usize) -> Result<T, Error> {
+        match self.value(idx) {
+            Some(v) => self.extract(&v, || format!("argument {}", idx)),
+            None => Err(self.type_error(format!(
+                "missing argument {}, expected {}",
+                idx,
+                any::type_name::<T>()
+            ))),
+        }
+    }
+
+    /// Extract the optional argument at the position, `None` if it is missing or `undefined`.
+    pub fn opt<T: ExtractValue>(&self, idx: usize) -> Result<Option<T>, Error> {
+        match self.value(idx) {
+            Some(ref v) if !v.is_undefined() => {
+                self.extract(v, || format!("argument {}", idx)).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Extract the rest arguments from the position, like `...rest` of a Javascript function.
+    pub fn rest_from<T: ExtractValue>(&self, idx: usize) -> Result<Vec<T>, Error> {
+        (idx..self.args.len()).map(|idx| self.get(idx)).collect()
+    }
+
+    /// Extract the `this` value.
+    pub fn this<T: ExtractValue>(&self) -> Result<T, Error> {
+        let this = self
+            .this
+            .map_or_else(|| self.ctxt.undefined(), |v| self.ctxt.clone_value(v));
+
+        self.extract(&this, || "`this`".to_owned())
+    }
+
+    fn extract<T: ExtractValue, F: FnOnce() -> String>(
+        &self,
+        v: &Local<Value>,
+        what: F,
+    ) -> Result<T, Error> {
+        v.extract()
+            .map_err(|err| match err.downcast::<ErrorKind>() {
+                Ok(ErrorKind::Conversion { expected, found }) => {
+                    self.type_error(format!("{} expected {}, found {}", what(), expected, found))
+                }
+                Ok(err) => err.into(),
+                Err(err) => err,
+            })
+    }
+
+    fn type_error(&self, msg: String) -> Error {
+        let msg = match self.name {
+            Some(name) => format!("{}: {}", name, msg),
+            None => msg,
+        };
+
+        ErrorKind::TypeError(msg, None).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn arguments() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let repeat = ctxt
+            .new_c_function(
+                |ctxt, this, args| {
+                    let args = Arguments::new(ctxt, this, args).with_name("repeat");
+                    let s = args.this::<String>()?;
+                    let count = args.opt::<i32>(0)?.unwrap_or(1);
+                    let sep = args.opt::<String>(1)?.unwrap_or_default();
+
+                    Ok(vec![s; count as usize].join(&sep))
+                },
+                Some("repeat"),
+                2,
+            )
+            .unwrap();
+        let sum = ctxt
+            .new_c_function(
+                |ctxt, this, args| {
+                    let args = Arguments::new(ctxt, this, args);
+                    let init = args.get::<f64>(0)?;
+                    let rest = args.rest_from::<Vec<f64>>(1)?;
+
+                    assert_eq!(args.len(), rest.len() + 1);
+
+                    Ok(init + rest.iter().flatten().sum::<f64>())
+                },
+                Some("sum"),
+                1,
+            )
+            .unwrap();
+
+        let neg = ctxt
+            .new_c_function(
+                |ctxt, this, args| {
+                    Arguments::new(ctxt, this, args)
+                        .this::<Vec<f64>>()
+                        .map(|v| v.iter().map(|n| -n).sum::<f64>())
+                },
+                Some("neg"),
+                0,
+            )
+            .unwrap();
+
+        ctxt.global_object().set_property("repeat", repeat).unwrap();
+        ctxt.global_object().set_property("sum", sum).unwrap();
+        ctxt.global_object().set_property("neg", neg).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[repeat.call('ab'), repeat.call('ab', 3, '-'), sum(1), sum(1, [2, 3], [4]), neg.call([1, 2])].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("ab,ab-ab-ab,1,10,-3".to_owned())
+        );
+
+        let cases = [
+            ("repeat.call('ab', 2, undefined, 1)", "abab"),
+            (
+                "neg.call('x')",
+                "TypeError: `this` expected alloc::vec::Vec<f64>, found string",
+            ),
+            ("sum()", "TypeError: missing argument 0, expected f64"),
+            (
+                "sum(1, [2], 3)",
+                "TypeError: argument 2 expected alloc::vec::Vec<f64>, found number",
+            ),
+        ];
+
+        for &(script, msg) in &cases {
+            assert_eq!(
+                ctxt.eval::<_, String>(
+                    format!(
+                        "try {{ String({}) }} catch (err) {{ `${{err.name}}: ${{err.message}}` }}",
+                        script
+                    )
+                    .as_str(),
+                    Eval::GLOBAL
+                )
+                .unwrap(),
+                Some(msg.to_owned()),
+                "{}",
+                script
+            );
+        }
+    }
+}
@@ -163,6 +163,20 @@ impl ContextRef {
         }))
     }
 
+    /// Returns the `ArrayBuffer` if the value is an `ArrayBuffer`.
+    pub fn get_array_buffer(&self, val: &Value) -> Option<ArrayBuffer> {
+        let mut size = 0;
+
+        if unsafe { ffi::JS_GetArrayBuffer(self.as_ptr(), &mut size, val.raw()) }.is_null() {
+            // clear the `TypeError` thrown by `JS_GetArrayBuffer`
+            self.get_exception();
+
+            None
+        } else {
+            Some(ArrayBuffer(self.clone_value(val)))
+        }
+    }
+
     /// Creates a new `ArrayBuffer` which copy the given bytes.
     pub fn new_array_buffer_copy(&self, buf: &mut [u8]) -> ArrayBuffer {
         ArrayBuffer(self.bind(unsafe {
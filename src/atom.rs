@@ -30,6 +30,12 @@ impl NewAtom for u32 {
     }
 }
 
+impl NewAtom for &Atom<'_> {
+    fn new_atom(self, context: &ContextRef) -> ffi::JSAtom {
+        context.clone_atom(**self).into_inner()
+    }
+}
+
 impl Unbindable for ffi::JSAtom {
     fn unbind(ctxt: &ContextRef, atom: ffi::JSAtom) {
         ctxt.free_atom(atom)
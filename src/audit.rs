@@ -0,0 +1,484 @@
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{self, Write};
+
+use failure::{format_err, Error};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, module::ModuleExports, Context, ContextRef, Eval, Local, Value};
+
+/// The kind of a function reachable by the scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ApiKind {
+    /// the native function provided by the engine, e.g. `Math.max`
+    Intrinsic,
+    /// the native function provided by the host
+    Host,
+    /// the function defined by the scripts, e.g. the wrappers of the host functions
+    Script,
+}
+
+impl ApiKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiKind::Intrinsic => "intrinsic",
+            ApiKind::Host => "host",
+            ApiKind::Script => "script",
+        }
+    }
+}
+
+/// A function reachable from the global object or the exports of a module.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ApiEntry {
+    /// the module which exports the function, or `None` for the global object
+    pub module: Option<String>,
+    /// the property path from the global object or the module namespace,
+    /// the accessors are prefixed with `get ` or `set `, and the symbols are written as `[Symbol.iterator]`.
+    pub path: String,
+    /// the kind of the function
+    pub kind: ApiKind,
+}
+
+impl fmt::Display for ApiEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.module {
+            Some(ref module) => write!(f, "import({:?}).{}", module, self.path),
+            None => f.write_str(&self.path),
+        }
+    }
+}
+
+/// The manifest of the functions reachable by the scripts in a context.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApiManifest {
+    /// the functions sorted by the module and path
+    pub entries: Vec<ApiEntry>,
+}
+
+impl ApiManifest {
+    /// Returns the functions which are not intrinsics.
+    pub fn host_functions(&self) -> impl Iterator<Item = &ApiEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind != ApiKind::Intrinsic)
+    }
+
+    /// Verify the non-intrinsic functions are exactly the allowed ones, which are written as `ApiEntry` displayed.
+    pub fn verify<I, S>(&self, allowed: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let exposed = self
+            .host_functions()
+            .map(ApiEntry::to_string)
+            .collect::<HashSet<_>>();
+        let allowed = allowed
+            .into_iter()
+            .map(|name| name.as_ref().to_owned())
+            .collect::<HashSet<_>>();
+        let mut unexpected = exposed.difference(&allowed).cloned().collect::<Vec<_>>();
+        let mut missing = allowed.difference(&exposed).cloned().collect::<Vec<_>>();
+
+        if unexpected.is_empty() && missing.is_empty() {
+            return Ok(());
+        }
+
+        unexpected.sort();
+        missing.sort();
+
+        Err(format_err!(
+            "API surface mismatch, unexpected: [{}], missing: [{}]",
+            unexpected.join(", "),
+            missing.join(", ")
+        ))
+    }
+
+    /// Returns the manifest in JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                json.push(',');
+            }
+
+            json.push_str("{\"module\":");
+
+            match entry.module {
+                Some(ref module) => write_json_string(&mut json, module),
+                None => json.push_str("null"),
+            }
+
+            json.push_str(",\"path\":");
+            write_json_string(&mut json, &entry.path);
+            json.push_str(",\"kind\":");
+            write_json_string(&mut json, entry.kind.as_str());
+            json.push('}');
+        }
+
+        json.push(']');
+        json
+    }
+}
+
+fn write_json_string(json: &mut String, s: &str) {
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+}
+
+/// The native implementations of the host functions created by `ContextRef::new_c_function*` in the runtime.
+#[derive(Default)]
+struct HostFunctions(RefCell<HashSet<usize>>);
+
+impl ContextRef {
+    /// Mark the native implementation as a host function, which is reported as `ApiKind::Host`.
+    pub(crate) fn register_host_function(&self, func: usize) {
+        self.runtime()
+            .state::<HostFunctions>()
+            .0
+            .borrow_mut()
+            .insert(func);
+    }
+
+    /// Returns whether a C function is created by the host, or `None` if it isn't a C function.
+    fn is_host_function(&self, func: &Value) -> Option<bool> {
+        let ptr = unsafe { ffi::JS_GetCFunctionPtr(func.raw()) };
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(
+                self.runtime()
+                    .state::<HostFunctions>()
+                    .0
+                    .borrow()
+                    .contains(&(ptr as usize)),
+            )
+        }
+    }
+
+    /// Returns the exports of the evaluated script modules.
+    fn script_module_exports(&self) -> Result<Vec<(String, ModuleExports)>, Error> {
+        let modules = self
+            .bind(unsafe { ffi::JS_GetScriptModules(self.as_ptr()) })
+            .ok()?;
+
+        if !modules.is_array()? {
+            return Ok(vec![]);
+        }
+
+        let len = modules
+            .get_property("length")
+            .and_then(|len| len.to_int32())
+            .unwrap_or_default();
+        let mut exports = vec![];
+
+        for idx in 0..len as u32 {
+            let pair = self
+                .get_property(&modules, idx)
+                .ok_or_else(|| format_err!("missing module #{}", idx))?;
+            let name = self.get_property(&pair, 0).map(|name| name.to_string());
+            let ns = self.get_property(&pair, 1);
+
+            if let (Some(name), Some(ns)) = (name, ns) {
+                let mut values = vec![];
+
+                for key in ns.get_own_property_names()?.unwrap_or_default() {
+                    if let Some(value) = self.get_property(&ns, &key) {
+                        values.push((key.to_string(), value));
+                    }
+                }
+
+                exports.push((name, values));
+            }
+        }
+
+        Ok(exports)
+    }
+}
+
+/// The functions reachable from the roots, each function is visited once by its shortest path.
+fn reachable_functions<'a>(
+    ctxt: &'a ContextRef,
+    roots: Vec<(Option<String>, Local<'a, Value>)>,
+) -> Result<Vec<(String, Local<'a, Value>)>, Error> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut functions = vec![];
+
+    for (path, value) in roots {
+        if value.is_object() && visited.insert(value.as_ptr::<()>()) {
+            if let Some(ref path) = path {
+                if value.is_function() {
+                    functions.push((path.clone(), value.clone()));
+                }
+            }
+
+            queue.push_back((path, value));
+        }
+    }
+
+    while let Some((path, obj)) = queue.pop_front() {
+        for key in obj.get_own_property_names()?.unwrap_or_default() {
+            let name = if key.to_value().is_symbol() {
+                format!("[{}]", key)
+            } else {
+                key.to_string()
+            };
+            let path = match path {
+                Some(ref path) if name.starts_with('[') => format!("{}{}", path, name),
+                Some(ref path) => format!("{}.{}", path, name),
+                None => name,
+            };
+            let desc = match ctxt.get_own_property_descriptor(&obj, &key)? {
+                Some(desc) => desc,
+                None => continue,
+            };
+            let values = desc
+                .value
+                .map(|value| (path.clone(), value))
+                .into_iter()
+                .chain(desc.getter.map(|getter| (format!("get {}", path), getter)))
+                .chain(desc.setter.map(|setter| (format!("set {}", path), setter)));
+
+            for (path, value) in values {
+                // the accessors are never invoked
+                if !value.is_object() || !visited.insert(value.as_ptr::<()>()) {
+                    continue;
+                }
+
+                if value.is_function() {
+                    functions.push((path.clone(), value.clone()));
+                }
+
+                queue.push_back((Some(path), value));
+            }
+        }
+    }
+
+    Ok(functions)
+}
+
+impl ContextRef {
+    /// Walk the global object, the exports of the native modules and the evaluated script modules,
+    /// and report every reachable function as a machine-readable manifest.
+    ///
+    /// The accessors are reported without invoking them. The C functions are classified by their
+    /// native implementations, the ones created by `ContextRef::new_c_function*` are reported as host functions,
+    /// even if they replace an intrinsic with the same path. The other native functions, e.g. the bound functions,
+    /// or all of them if the linked library is not patched, are reported as intrinsics
+    /// if they also exist in a fresh context of the same runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Runtime, Value};
+    ///
+    /// fn add(ctxt: &qjs::ContextRef, _this: Option<&Value>, args: &[Value]) -> i32 {
+    ///     ctxt.clone_value(&args[0]).to_int32().unwrap_or_default() + ctxt.clone_value(&args[1]).to_int32().unwrap_or_default()
+    /// }
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.global_object()
+    ///     .set_property("add", ctxt.new_c_function(add, Some("add"), 2).unwrap())
+    ///     .unwrap();
+    ///
+    /// let manifest = ctxt.audit_api().unwrap();
+    ///
+    /// assert!(manifest.verify(&["add"]).is_ok());
+    /// assert!(manifest.to_json().contains(r#"{"module":null,"path":"add","kind":"host"}"#));
+    /// ```
+    pub fn audit_api(&self) -> Result<ApiManifest, Error> {
+        let baseline = Context::new(self.runtime());
+        let to_string =
+            baseline.eval_script("Function.prototype.toString", "<audit>", Eval::GLOBAL)?;
+        let is_native = |func: &Local<Value>| {
+            to_string
+                .call(Some(func), ())
+                .map(|s| s.to_string().ends_with("{\n    [native code]\n}"))
+                .unwrap_or_default()
+        };
+        let intrinsics = reachable_functions(&baseline, vec![(None, baseline.global_object())])?
+            .into_iter()
+            .filter(|(_, func)| is_native(func))
+            .map(|(path, _)| path)
+            .collect::<HashSet<_>>();
+        let kind = |path: Option<&str>, func: &Local<Value>| {
+            if !is_native(func) {
+                return ApiKind::Script;
+            }
+
+            match self.is_host_function(func) {
+                Some(true) => ApiKind::Host,
+                Some(false) => ApiKind::Intrinsic,
+                // the intrinsics are only reachable from the global object
+                None if path.map_or(false, |path| intrinsics.contains(path)) => ApiKind::Intrinsic,
+                None => ApiKind::Host,
+            }
+        };
+
+        let mut entries = reachable_functions(self, vec![(None, self.global_object())])?
+            .into_iter()
+            .map(|(path, func)| ApiEntry {
+                module: None,
+                kind: kind(Some(&path), &func),
+                path,
+            })
+            .collect::<Vec<_>>();
+
+        for (module, exports) in self
+            .native_module_exports()
+            .into_iter()
+            .chain(self.script_module_exports()?)
+        {
+            let roots = exports
+                .into_iter()
+                .map(|(name, value)| (Some(name), value))
+                .collect();
+
+            entries.extend(
+                reachable_functions(self, roots)?
+                    .into_iter()
+                    .map(|(path, func)| ApiEntry {
+                        module: Some(module.clone()),
+                        kind: kind(None, &func),
+                        path,
+                    }),
+            );
+        }
+
+        entries.sort();
+
+        Ok(ApiManifest { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HostProcess, Intrinsics, Runtime, Sandbox};
+
+    use super::*;
+
+    fn stringify(_ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> &'static str {
+        "{}"
+    }
+
+    #[test]
+    fn audit_api() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Sandbox::new()
+            .with_intrinsics(Intrinsics::BASE_OBJECTS | Intrinsics::EVAL | Intrinsics::JSON)
+            .build(&rt)
+            .unwrap();
+
+        HostProcess::new()
+            .with_args(vec!["app"])
+            .with_env(vec!["HOME"])
+            .init(&ctxt)
+            .unwrap();
+
+        ctxt.eval_script(
+            "globalThis.util = { get version() { return 1 }, greet: () => 'hi' };",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        // the host function replacing an intrinsic is still reported as a host function
+        ctxt.get_property(&ctxt.global_object(), "JSON")
+            .unwrap()
+            .set_property(
+                "stringify",
+                ctxt.new_c_function(stringify, Some("stringify"), 1)
+                    .unwrap(),
+            )
+            .unwrap();
+        ctxt.eval_script(
+            "export function f() {} export const stringify = JSON.stringify, parse = JSON.parse;",
+            "lib",
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        let manifest = ctxt.audit_api().unwrap();
+        let find = |module: Option<&str>, path: &str| {
+            manifest
+                .entries
+                .iter()
+                .find(|entry| entry.module.as_deref() == module && entry.path == path)
+                .map(|entry| entry.kind)
+        };
+
+        assert_eq!(find(None, "JSON.parse"), Some(ApiKind::Intrinsic));
+        assert_eq!(find(None, "JSON.stringify"), Some(ApiKind::Host));
+        assert_eq!(find(Some("lib"), "f"), Some(ApiKind::Script));
+        assert_eq!(find(Some("lib"), "stringify"), Some(ApiKind::Host));
+        assert_eq!(find(Some("lib"), "parse"), Some(ApiKind::Intrinsic));
+        assert_eq!(find(None, "Array.prototype.map"), Some(ApiKind::Intrinsic));
+        assert_eq!(
+            find(None, "get Array[Symbol.species]"),
+            Some(ApiKind::Intrinsic)
+        );
+        assert_eq!(find(None, "Date.now"), None);
+        assert_eq!(find(None, "util.greet"), Some(ApiKind::Script));
+        assert_eq!(find(None, "get util.version"), Some(ApiKind::Script));
+        assert_eq!(find(Some("host:process"), "getenv"), Some(ApiKind::Host));
+        assert_eq!(find(Some("host:process"), "spawn"), Some(ApiKind::Script));
+
+        assert_eq!(
+            manifest
+                .host_functions()
+                .map(ApiEntry::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "JSON.stringify",
+                "get util.version",
+                "util.greet",
+                r#"import("host:process").cwd"#,
+                r#"import("host:process").env"#,
+                r#"import("host:process").exitCode"#,
+                r#"import("host:process").getenv"#,
+                r#"import("host:process").setExitCode"#,
+                r#"import("host:process").spawn"#,
+                r#"import("lib").f"#,
+                r#"import("lib").stringify"#,
+            ]
+        );
+        assert!(manifest
+            .verify(manifest.host_functions().map(ApiEntry::to_string))
+            .is_ok());
+        assert_eq!(
+            manifest
+                .verify(&["util.greet", "util.other"])
+                .unwrap_err()
+                .to_string(),
+            "API surface mismatch, unexpected: [JSON.stringify, get util.version, import(\"host:process\").cwd, \
+             import(\"host:process\").env, import(\"host:process\").exitCode, \
+             import(\"host:process\").getenv, import(\"host:process\").setExitCode, \
+             import(\"host:process\").spawn, import(\"lib\").f, import(\"lib\").stringify], \
+             missing: [util.other]"
+        );
+        assert!(manifest.to_json().starts_with(r#"[{"module":null,"path":"#));
+    }
+}
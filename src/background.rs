@@ -0,0 +1,431 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ContextRef, ExceptionOrigin, NewValue, RuntimeRef, Value, NULL};
+
+/// A notification of a background operation, which is executed on the runtime thread
+/// with the values kept for the operation, the first one is the callback of the operation.
+type Notification = Box<dyn FnOnce(&ContextRef, &[Value]) -> Result<(), Error> + Send>;
+
+enum Message {
+    Notify(u32, Notification),
+    Done(u32, Option<Notification>),
+}
+
+/// A background operation in progress, the values are kept alive until it is done.
+struct Pending {
+    ctx: usize,
+    values: Vec<Value>,
+    cancelled: Arc<AtomicBool>,
+    /// the error of a notification, which fails the operation when it is done
+    failed: Option<Error>,
+}
+
+impl Pending {
+    fn free(self) {
+        let ctxt = unsafe { ContextRef::from_ptr(self.ctx as *mut _) };
+
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        for value in self.values {
+            ctxt.free_value(value);
+        }
+    }
+}
+
+/// The background operations of a runtime.
+struct Operations {
+    sender: mpsc::Sender<Message>,
+    receiver: mpsc::Receiver<Message>,
+    pending: RefCell<HashMap<u32, Pending>>,
+    next_id: Cell<u32>,
+}
+
+unsafe impl Send for Operations {}
+
+impl Default for Operations {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Operations {
+            sender,
+            receiver,
+            pending: RefCell::new(HashMap::new()),
+            next_id: Cell::new(0),
+        }
+    }
+}
+
+impl Operations {
+    /// Free the cancelled operations, their notifications are dropped when received.
+    fn sweep(&self) {
+        let cancelled = {
+            let mut pending = self.pending.borrow_mut();
+            let ids = pending
+                .iter()
+                .filter(|(_, op)| op.cancelled.load(Ordering::SeqCst))
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>();
+
+            ids.into_iter()
+                .flat_map(|id| pending.remove(&id))
+                .collect::<Vec<_>>()
+        };
+
+        for op in cancelled {
+            op.free();
+        }
+    }
+
+    fn dispatch(&self, msg: Message) -> Result<(), Error> {
+        let (id, notification, done) = match msg {
+            Message::Notify(id, notification) => (id, Some(notification), false),
+            Message::Done(id, notification) => (id, notification, true),
+        };
+
+        // the operation is taken out while the notification executed, which may start the other operations
+        let mut op = match self.pending.borrow_mut().remove(&id) {
+            Some(op) => op,
+            None => return Ok(()),
+        };
+        let ctxt = unsafe { ContextRef::from_ptr(op.ctx as *mut _) };
+
+        let res = match (notification, op.failed.take()) {
+            (Some(notification), None) => notification(ctxt, &op.values),
+            (None, None) if done => ctxt
+                .call(&op.values[0], None, "the operation was aborted")
+                .map(|_| ()),
+            (_, Some(err)) if done => ctxt.call(&op.values[0], None, err.to_string()).map(|_| ()),
+            (_, failed) => {
+                op.failed = failed;
+
+                Ok(())
+            }
+        };
+
+        if done || op.cancelled.load(Ordering::SeqCst) {
+            op.free();
+
+            match res {
+                Err(ref err) if ctxt.handle_exception(ExceptionOrigin::Job, err) => Ok(()),
+                res => res,
+            }
+        } else {
+            if let Err(err) = res {
+                op.failed = Some(err);
+            }
+
+            self.pending.borrow_mut().insert(id, op);
+
+            Ok(())
+        }
+    }
+}
+
+/// The background operations of a context are cancelled when the context freed.
+#[derive(Default)]
+struct ContextOperations {
+    ctx: Cell<usize>,
+    rt: Cell<usize>,
+}
+
+impl Drop for ContextOperations {
+    fn drop(&mut self) {
+        if self.rt.get() == 0 {
+            return;
+        }
+
+        let rt = unsafe { RuntimeRef::from_ptr(self.rt.get() as *mut _) };
+        let ops = rt.state::<Operations>();
+        let freed = {
+            let mut pending = ops.pending.borrow_mut();
+            let ids = pending
+                .iter()
+                .filter(|(_, op)| op.ctx == self.ctx.get())
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>();
+
+            ids.into_iter()
+                .flat_map(|id| pending.remove(&id))
+                .collect::<Vec<_>>()
+        };
+
+        for op in freed {
+            op.free();
+        }
+    }
+}
+
+/// The handle of a background operation, which notifies the runtime thread.
+///
+/// The operation is aborted if the handle dropped before it completes.
+pub(crate) struct Operation {
+    id: u32,
+    sender: mpsc::Sender<Message>,
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    done: bool,
+}
+
+impl Operation {
+    /// Returns `true` if the operation was cancelled by the runtime, or its deadline exceeded.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self
+                .deadline
+                .map_or(false, |deadline| deadline <= Instant::now())
+    }
+
+    /// Execute the notification on the runtime thread, with the values kept for the operation.
+    ///
+    /// The operation fails with the error of the notification, the following notifications are skipped.
+    pub fn notify<F>(&self, notification: F)
+    where
+        F: FnOnce(&ContextRef, &[Value]) -> Result<(), Error> + Send + 'static,
+    {
+        let _ = self
+            .sender
+            .send(Message::Notify(self.id, Box::new(notification)));
+    }
+
+    /// Complete the operation, the callback is called with `(null, value)` or `(err)` on the runtime thread.
+    pub fn complete<T: NewValue + Send + 'static>(mut self, res: Result<T, Error>) {
+        let notification: Notification = match res {
+            Ok(value) => {
+                Box::new(move |ctxt, values| ctxt.call(&values[0], None, (NULL, value)).map(|_| ()))
+            }
+            Err(err) => {
+                let msg = err.to_string();
+
+                Box::new(move |ctxt, values| ctxt.call(&values[0], None, msg).map(|_| ()))
+            }
+        };
+
+        self.done = true;
+
+        let _ = self.sender.send(Message::Done(self.id, Some(notification)));
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.sender.send(Message::Done(self.id, None));
+        }
+    }
+}
+
+impl RuntimeRef {
+    /// Returns the number of the background operations in progress, e.g. the child processes or the HTTP requests.
+    pub fn pending_operations(&self) -> usize {
+        self.state::<Operations>()
+            .pending
+            .borrow()
+            .values()
+            .filter(|op| !op.cancelled.load(Ordering::SeqCst))
+            .count()
+    }
+
+    /// Execute the notifications of the background operations, returns the number of the notifications executed.
+    ///
+    /// It waits up to the timeout for the first notification, or until one arrives if `None`,
+    /// the callbacks of the completed operations are called, and their promises are settled by the pending jobs.
+    pub fn poll_operations(&self, timeout: Option<Duration>) -> Result<usize, Error> {
+        let ops = self.state::<Operations>();
+        let mut executed = 0;
+
+        ops.sweep();
+
+        while !ops.pending.borrow().is_empty() {
+            let msg = match (executed, timeout) {
+                (0, Some(timeout)) => ops.receiver.recv_timeout(timeout).ok(),
+                (0, None) => ops.receiver.recv().ok(),
+                _ => ops.receiver.try_recv().ok(),
+            };
+
+            match msg {
+                Some(msg) => {
+                    executed += 1;
+
+                    ops.dispatch(msg)?;
+                }
+                None => break,
+            }
+        }
+
+        Ok(executed)
+    }
+
+    /// Cancel the background operations, their callbacks will never be called.
+    ///
+    /// The operations are cancelled when the runtime is interrupted, e.g. the time limit exceeded.
+    pub fn cancel_operations(&self) {
+        for op in self.state::<Operations>().pending.borrow().values() {
+            op.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+impl ContextRef {
+    /// Start a background operation, which keeps the values until it is done, the first one is the callback.
+    ///
+    /// The deadline of the operation is the time limit of the runtime from now.
+    pub(crate) fn start_operation(&self, values: &[&Value]) -> Operation {
+        let rt = self.runtime();
+        let ops = rt.state::<Operations>();
+        let state = self.state::<ContextOperations>();
+
+        state.ctx.set(self.as_ptr() as usize);
+        state.rt.set(rt.as_ptr() as usize);
+
+        let id = ops.next_id.get();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        ops.next_id.set(id.wrapping_add(1));
+        ops.pending.borrow_mut().insert(
+            id,
+            Pending {
+                ctx: self.as_ptr() as usize,
+                values: values
+                    .iter()
+                    .map(|&value| self.clone_value(value).into_inner_untracked())
+                    .collect(),
+                cancelled: cancelled.clone(),
+                failed: None,
+            },
+        );
+
+        trace!("{:?} start operation #{}", self, id);
+
+        Operation {
+            id,
+            sender: ops.sender.clone(),
+            cancelled,
+            deadline: rt.time_limit().map(|limit| Instant::now() + limit),
+            done: false,
+        }
+    }
+
+    /// Run the operation on a background thread, the callback is called when it completes.
+    pub(crate) fn spawn_operation<F, T>(&self, values: &[&Value], f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&Operation) -> Result<T, Error> + Send + 'static,
+        T: NewValue + Send + 'static,
+    {
+        let op = self.start_operation(values);
+
+        thread::Builder::new()
+            .name("qjs-operation".into())
+            .spawn(move || {
+                let res = f(&op);
+
+                op.complete(res)
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, EventLoop, Runtime, UNDEFINED};
+
+    use super::*;
+
+    #[test]
+    fn background_operations() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let sleep = ctxt
+            .new_c_function(
+                |ctxt, _this, args| {
+                    let ms = ctxt.to_int32(&args[0]).unwrap_or_default() as u64;
+
+                    ctxt.spawn_operation(&[&args[1]], move |op| {
+                        let started = Instant::now();
+
+                        while started.elapsed() < Duration::from_millis(ms) {
+                            if op.is_cancelled() {
+                                return Err(format_err!("cancelled"));
+                            }
+
+                            op.notify(|_ctxt, _values| Ok(()));
+
+                            thread::sleep(Duration::from_millis(5));
+                        }
+
+                        Ok(ms as i32)
+                    })
+                    .map(|_| UNDEFINED)
+                },
+                Some("sleep"),
+                2,
+            )
+            .unwrap();
+
+        ctxt.global_object().set_property("sleep", sleep).unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var log = [];
+
+sleep(50, (err, ms) => log.push(`slow ${ms}`));
+sleep(0, (err, ms) => log.push(`fast ${ms}`));
+log.push('script');
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        assert_eq!(rt.pending_operations(), 2);
+
+        // the script is not blocked by the operations
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(rt.pending_operations(), 0);
+        assert_eq!(
+            ctxt.eval::<_, String>("log.join()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "script,fast 0,slow 50"
+        );
+
+        // the operations are cancelled by the time limit
+        rt.set_time_limit(Some(Duration::from_millis(20)));
+
+        ctxt.eval::<_, ()>("sleep(10000, (err) => log.push(err))", Eval::GLOBAL)
+            .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("log.pop()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "cancelled"
+        );
+
+        // the callbacks of the operations cancelled by the runtime are never called
+        rt.set_time_limit(None);
+
+        ctxt.eval::<_, ()>("sleep(10000, (err) => log.push(err))", Eval::GLOBAL)
+            .unwrap();
+        rt.cancel_operations();
+
+        assert_eq!(rt.pending_operations(), 0);
+        assert_eq!(rt.poll_operations(Some(Duration::default())).unwrap(), 0);
+        assert_eq!(
+            ctxt.eval::<_, i32>("log.length", Eval::GLOBAL).unwrap(),
+            Some(3)
+        );
+    }
+}
@@ -0,0 +1,625 @@
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr::null_mut;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ErrorKind, Local, RuntimeRef, Unsupported, Value};
+
+/// The position in the source of a stack frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourcePosition {
+    /// the filename of the source
+    pub file: String,
+    /// the 1-based line number, or `None` if the script has only one line or was stripped
+    pub line: Option<u32>,
+    /// the 1-based column number in characters, or `None` if unknown
+    pub column: Option<u32>,
+}
+
+impl fmt::Display for SourcePosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SourcePosition {
+    fn parse(s: &str) -> Self {
+        let mut parts = s.rsplitn(3, ':').collect::<Vec<_>>();
+        let mut numbers = vec![];
+
+        // the trailing `:line` or `:line:column`, the filename may contain ':'
+        while parts.len() > 1 && numbers.len() < 2 {
+            match parts[0].parse::<u32>() {
+                Ok(n) => {
+                    numbers.insert(0, n);
+                    parts.remove(0);
+                }
+                Err(_) => break,
+            }
+        }
+
+        let file = parts.into_iter().rev().collect::<Vec<_>>().join(":");
+
+        SourcePosition {
+            file,
+            line: numbers.first().cloned(),
+            column: numbers.get(1).cloned(),
+        }
+    }
+}
+
+/// A frame of the Javascript backtrace.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BacktraceFrame {
+    /// the function name, or `None` for the frame of a syntax error
+    pub function: Option<String>,
+    /// the position in the source, or `None` for the native functions
+    pub position: Option<SourcePosition>,
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.function, &self.position) {
+            (Some(function), Some(position)) => write!(f, "{} ({})", function, position),
+            (Some(function), None) => write!(f, "{} (native)", function),
+            (None, Some(position)) => write!(f, "{}", position),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl BacktraceFrame {
+    /// Parse a line of the `stack` property, the Rust frames are skipped.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim().strip_prefix("at ")?;
+
+        if line.starts_with("[rust]") {
+            return None;
+        }
+
+        match line.find(" (") {
+            Some(off) if line.ends_with(')') => {
+                let function = line[..off].to_owned();
+                let location = &line[off + 2..line.len() - 1];
+
+                Some(BacktraceFrame {
+                    function: Some(function),
+                    position: if location == "native" {
+                        None
+                    } else {
+                        Some(SourcePosition::parse(location))
+                    },
+                })
+            }
+            _ => Some(BacktraceFrame {
+                function: None,
+                position: Some(SourcePosition::parse(line)),
+            }),
+        }
+    }
+
+    /// Returns `true` if the frame is a native function.
+    pub fn is_native(&self) -> bool {
+        self.function.is_some() && self.position.is_none()
+    }
+}
+
+/// Parse the `stack` property of an `Error` to the frames.
+pub fn parse_backtrace(stack: &str) -> Vec<BacktraceFrame> {
+    stack.lines().flat_map(BacktraceFrame::parse).collect()
+}
+
+/// Format the frames to the `stack` property of an `Error`.
+pub fn format_backtrace(frames: &[BacktraceFrame]) -> String {
+    frames
+        .iter()
+        .map(|frame| format!("    at {}\n", frame))
+        .collect()
+}
+
+impl ErrorKind {
+    /// Returns the Javascript frames of the error.
+    ///
+    /// The column is the position of the last token parsed when the failing operation was compiled,
+    /// e.g. the `.` of a property access or the `)` of a call.
+    ///
+    /// The column numbers are only tracked for the scripts compiled from the source,
+    /// the functions loaded from the bytecode have only the line numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, Runtime, SourcePosition};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let err = ctxt
+    ///     .eval_script("function f(o) {\n  return o.x.y;\n}\nf({})", "app.js", Eval::GLOBAL)
+    ///     .unwrap_err()
+    ///     .downcast::<ErrorKind>()
+    ///     .unwrap();
+    /// let frames = err.frames();
+    ///
+    /// assert_eq!(frames[0].function.as_deref(), Some("f"));
+    /// assert_eq!(
+    ///     err.position(),
+    ///     Some(SourcePosition {
+    ///         file: "app.js".to_owned(),
+    ///         line: Some(2),
+    ///         column: Some(13),
+    ///     })
+    /// );
+    /// ```
+    pub fn frames(&self) -> Vec<BacktraceFrame> {
+        self.stack().map(parse_backtrace).unwrap_or_default()
+    }
+
+    /// Returns the source position of the innermost Javascript frame.
+    pub fn position(&self) -> Option<SourcePosition> {
+        self.frames()
+            .into_iter()
+            .flat_map(|frame| frame.position)
+            .next()
+    }
+}
+
+type FrameFilter = Box<dyn Fn(&BacktraceFrame) -> bool>;
+
+type PrepareStackTrace = Box<dyn Fn(&Local<Value>, Vec<BacktraceFrame>) -> Vec<BacktraceFrame>>;
+
+#[derive(Default)]
+struct Filters(RefCell<Option<FrameFilter>>);
+
+unsafe impl Send for Filters {}
+
+#[derive(Default)]
+struct StackTraceHook(RefCell<Option<PrepareStackTrace>>);
+
+unsafe impl Send for StackTraceHook {}
+
+impl RuntimeRef {
+    /// Set the maximum frames captured in the backtrace of the errors, or `None` if unlimited.
+    ///
+    /// The frames hidden by the filter are not counted.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched, which always captures the whole backtrace.
+    pub fn set_stack_trace_limit(&self, limit: Option<usize>) -> Result<&Self, Error> {
+        if limit.is_some() {
+            Unsupported::check("set_stack_trace_limit")?;
+        }
+
+        trace!("{:?} set stack trace limit to {:?}", self, limit);
+
+        unsafe {
+            ffi::JS_SetBacktraceLimit(
+                self.as_ptr(),
+                limit.map_or(-1, |n| n.min(c_int::MAX as usize) as c_int),
+            )
+        }
+
+        Ok(self)
+    }
+
+    /// Get the maximum frames captured in the backtrace of the errors.
+    pub fn stack_trace_limit(&self) -> Option<usize> {
+        let limit = unsafe { ffi::JS_GetBacktraceLimit(self.as_ptr()) };
+
+        if limit < 0 {
+            None
+        } else {
+            Some(limit as usize)
+        }
+    }
+
+    /// Set a filter of the frames in the backtrace of the errors, the frames are hidden if it returns `false`.
+    ///
+    /// The filter is called while the error is created, and should not call into the Javascript.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched, which has no filter of the frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// rt.set_stack_frame_filter(|frame| !frame.is_native()).unwrap();
+    ///
+    /// let err = ctxt
+    ///     .eval_script("[1].map(function cb() { throw new Error('oops') })", "<eval>", Eval::GLOBAL)
+    ///     .unwrap_err()
+    ///     .downcast::<ErrorKind>()
+    ///     .unwrap();
+    /// let frames = err.frames();
+    ///
+    /// assert_eq!(frames.len(), 2);
+    /// assert_eq!(frames[0].function.as_deref(), Some("cb"));
+    /// assert_eq!(frames[1].function.as_deref(), Some("<eval>"));
+    /// ```
+    pub fn set_stack_frame_filter<F>(&self, filter: F) -> Result<(), Error>
+    where
+        F: Fn(&BacktraceFrame) -> bool + 'static,
+    {
+        Unsupported::check("set_stack_frame_filter")?;
+
+        *self.state::<Filters>().0.borrow_mut() = Some(Box::new(filter));
+
+        unsafe { ffi::JS_SetBacktraceFilter(self.as_ptr(), Some(filter_frame), null_mut()) }
+
+        Ok(())
+    }
+
+    /// Remove the filter of the frames in the backtrace.
+    pub fn clear_stack_frame_filter(&self) {
+        unsafe { ffi::JS_SetBacktraceFilter(self.as_ptr(), None, null_mut()) }
+
+        self.state::<Filters>().0.borrow_mut().take();
+    }
+
+    /// Set a hook to rewrite the frames when the `stack` property of an error is created,
+    /// e.g. rewrite the file names to the virtual paths, apply the source maps, or redact the internals.
+    ///
+    /// The hook receives the error and the frames after the filter and the source maps,
+    /// and returns the frames of the `stack`.
+    /// The errors created in the hook keep their original stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// rt.set_prepare_stack_trace(|_err, mut frames| {
+    ///     for pos in frames.iter_mut().flat_map(|frame| frame.position.as_mut()) {
+    ///         pos.file = pos.file.replace("/srv/app/", "app://");
+    ///     }
+    ///     frames
+    /// });
+    ///
+    /// let err = ctxt
+    ///     .eval_script("\nthrow new Error('oops')", "/srv/app/main.js", Eval::GLOBAL)
+    ///     .unwrap_err()
+    ///     .downcast::<ErrorKind>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(err.stack(), Some("    at <eval> (app://main.js:2:23)\n"));
+    /// ```
+    pub fn set_prepare_stack_trace<F>(&self, hook: F)
+    where
+        F: Fn(&Local<Value>, Vec<BacktraceFrame>) -> Vec<BacktraceFrame> + 'static,
+    {
+        *self.state::<StackTraceHook>().0.borrow_mut() = Some(Box::new(hook));
+
+        self.update_stack_trace_hook();
+    }
+
+    /// Remove the hook to rewrite the frames of the errors.
+    pub fn clear_prepare_stack_trace(&self) {
+        self.state::<StackTraceHook>().0.borrow_mut().take();
+
+        self.update_stack_trace_hook();
+    }
+
+    pub(crate) fn update_stack_trace_hook(&self) {
+        let hooked = self.state::<StackTraceHook>().0.borrow().is_some() || self.has_source_maps();
+
+        unsafe {
+            ffi::JS_SetPrepareStackTrace(
+                self.as_ptr(),
+                if hooked {
+                    Some(prepare_stack_trace)
+                } else {
+                    None
+                },
+                null_mut(),
+            )
+        }
+    }
+}
+
+unsafe extern "C" fn prepare_stack_trace(
+    ctx: *mut ffi::JSContext,
+    error_obj: ffi::JSValue,
+    stack: ffi::JSValue,
+    _opaque: *mut c_void,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+    let rt = ctxt.runtime();
+
+    let prepared = rt.catch_unwind(None, || {
+        let hook = rt.state::<StackTraceHook>().0.try_borrow().ok()?;
+        let frames = parse_backtrace(&ctxt.clone_value(&Value::from(stack)).to_string());
+        let frames = rt.map_frames(frames);
+        let frames = match hook.as_ref() {
+            Some(hook) => hook(&ctxt.clone_value(&Value::from(error_obj)), frames),
+            None => frames,
+        };
+
+        Some(format_backtrace(&frames))
+    });
+
+    match prepared {
+        Some(s) => {
+            ctxt.free_value(Value::from(stack));
+            ctxt.new_value(s.as_str()).raw()
+        }
+        None => stack,
+    }
+}
+
+unsafe extern "C" fn filter_frame(
+    ctx: *mut ffi::JSContext,
+    func_name: *const c_char,
+    filename: ffi::JSAtom,
+    line_num: c_int,
+    column_num: c_int,
+    _opaque: *mut c_void,
+) -> c_int {
+    let ctxt = ContextRef::from_ptr(ctx);
+    let rt = ctxt.runtime();
+
+    rt.catch_unwind(ffi::TRUE_VALUE, || {
+        let function = CStr::from_ptr(func_name).to_string_lossy();
+        let frame = BacktraceFrame {
+            function: Some(if function.is_empty() {
+                "<anonymous>".to_owned()
+            } else {
+                function.into_owned()
+            }),
+            position: if filename == 0 {
+                None
+            } else {
+                Some(SourcePosition {
+                    file: ctxt.clone_atom(filename).to_string(),
+                    line: if line_num < 0 {
+                        None
+                    } else {
+                        Some(line_num as u32)
+                    },
+                    column: if column_num > 0 {
+                        Some(column_num as u32)
+                    } else {
+                        None
+                    },
+                })
+            },
+        };
+
+        match rt.state::<Filters>().0.try_borrow() {
+            Ok(filter) => filter.as_ref().map_or(true, |f| f(&frame)) as c_int,
+            Err(_) => ffi::TRUE_VALUE,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn backtrace() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let err = ctxt
+            .eval_script(
+                "function inner() {\n    null.foo;\n}\nfunction outer() {\n  [1].map(inner);\n}\nouter();",
+                "test.js",
+                Eval::GLOBAL,
+            )
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        let pos = |line, column| {
+            Some(SourcePosition {
+                file: "test.js".to_owned(),
+                line: Some(line),
+                column: Some(column),
+            })
+        };
+
+        assert_eq!(
+            err.frames(),
+            vec![
+                BacktraceFrame {
+                    function: Some("inner".to_owned()),
+                    position: pos(2, 9),
+                },
+                BacktraceFrame {
+                    function: Some("map".to_owned()),
+                    position: None,
+                },
+                BacktraceFrame {
+                    function: Some("outer".to_owned()),
+                    position: pos(5, 16),
+                },
+                BacktraceFrame {
+                    function: Some("<eval>".to_owned()),
+                    position: pos(7, 7),
+                },
+            ]
+        );
+        assert!(err.frames()[1].is_native());
+        assert_eq!(err.position(), pos(2, 9));
+        assert_eq!(err.frames()[0].to_string(), "inner (test.js:2:9)");
+
+        // the default constructors are parsed outside of the source
+        let err = ctxt
+            .eval_script(
+                "class A {}\nclass B extends A {}\nnew B().foo.bar;",
+                "class.js",
+                Eval::GLOBAL,
+            )
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.frames()[0].to_string(), "<eval> (class.js:3:12)");
+
+        assert_eq!(
+            parse_backtrace("    at <repl>:1\n    at [rust] qjs::eval\n    at f (C:\\app.js:3)\n"),
+            vec![
+                BacktraceFrame {
+                    function: None,
+                    position: Some(SourcePosition {
+                        file: "<repl>".to_owned(),
+                        line: Some(1),
+                        column: None,
+                    }),
+                },
+                BacktraceFrame {
+                    function: Some("f".to_owned()),
+                    position: Some(SourcePosition {
+                        file: "C:\\app.js".to_owned(),
+                        line: Some(3),
+                        column: None,
+                    }),
+                },
+            ]
+        );
+        assert_eq!(
+            BacktraceFrame::parse("    at <eval> (<evalScript>)"),
+            Some(BacktraceFrame {
+                function: Some("<eval>".to_owned()),
+                position: Some(SourcePosition {
+                    file: "<evalScript>".to_owned(),
+                    line: None,
+                    column: None,
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn prepare_stack_trace() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        rt.set_prepare_stack_trace(|err, frames| {
+            assert!(err.is_error());
+
+            frames
+                .into_iter()
+                .filter(|frame| frame.function.as_deref() != Some("__internal"))
+                .map(|mut frame| {
+                    if let Some(pos) = frame.position.as_mut() {
+                        pos.file = format!("vfs://{}", pos.file);
+                    }
+                    frame
+                })
+                .collect()
+        });
+
+        let err = ctxt
+            .eval_script(
+                "function __internal(f) { return f() }\nfunction fail() {\n  throw new Error('oops')\n}\n__internal(fail)",
+                "main.js",
+                Eval::GLOBAL,
+            )
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(
+            err.stack(),
+            Some("    at fail (vfs://main.js:3:25)\n    at <eval> (vfs://main.js:5:16)\n")
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>("try { null.x } catch (e) { e.stack }", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "    at <eval> (vfs://<evalScript>)\n"
+        );
+
+        rt.clear_prepare_stack_trace();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("try { null.x } catch (e) { e.stack }", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "    at <eval> (<evalScript>)\n"
+        );
+    }
+
+    #[test]
+    fn stack_trace_filter() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let script = "function glue(f) { return f() }\nfunction recurse(n) {\n  if (n == 0) throw new Error('bottom');\n  return glue(() => recurse(n - 1))\n}\nrecurse(10)";
+        let frames = || {
+            ctxt.eval_script(script, "test.js", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .frames()
+        };
+
+        assert_eq!(rt.stack_trace_limit(), None);
+        assert_eq!(frames().len(), 32);
+
+        rt.set_stack_trace_limit(Some(3)).unwrap();
+
+        assert_eq!(rt.stack_trace_limit(), Some(3));
+        assert_eq!(
+            frames()
+                .iter()
+                .map(|frame| frame.function.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["recurse", "<anonymous>", "glue"]
+        );
+
+        rt.set_stack_frame_filter(|frame| {
+            frame.function.as_deref() != Some("glue")
+                && frame.function.as_deref() != Some("<anonymous>")
+        })
+        .unwrap();
+
+        let filtered = frames();
+
+        assert_eq!(filtered.len(), 3);
+        assert!(filtered
+            .iter()
+            .all(|frame| frame.function.as_deref() == Some("recurse")));
+        assert_eq!(
+            filtered[0].position.as_ref().and_then(|pos| pos.line),
+            Some(3)
+        );
+
+        rt.set_stack_trace_limit(None).unwrap();
+
+        assert_eq!(frames().len(), 12);
+
+        rt.clear_stack_frame_filter();
+
+        assert_eq!(frames().len(), 32);
+    }
+}
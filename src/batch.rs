@@ -0,0 +1,267 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::os::raw::c_int;
+use std::slice;
+
+use failure::{bail, Error};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Eval, Local, NewValue, Value};
+
+/// The default number of the records buffered by a batch.
+pub const DEFAULT_BATCH_CAPACITY: usize = 1024;
+
+type Handler = Box<dyn FnMut(&ContextRef, &[f64])>;
+
+/// A batched host function, which buffers the numeric calls from the scripts to reduce the FFI crossings.
+///
+/// The scripts call `send(...)` of the batch object to append a record of `arity` numbers
+/// to a `Float64Array` backed by an `ArrayBuffer`, the records are handed over to the handler
+/// with a single host call when the buffer is full, or the scripts call `flush()`.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// use qjs::{Batch, Context, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let sum = Rc::new(RefCell::new(0.0));
+/// let total = sum.clone();
+///
+/// let batch = ctxt
+///     .new_batch(Batch::new(2, move |_ctxt, record| {
+///         *total.borrow_mut() += record[0] * record[1];
+///     }))
+///     .unwrap();
+///
+/// ctxt.global_object().set_property("metrics", batch).unwrap();
+///
+/// ctxt.eval_script(
+///     "for (let i = 0; i < 10000; i++) metrics.send(i, 2); metrics.flush()",
+///     "<eval>",
+///     Eval::GLOBAL,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(*sum.borrow(), 99990000.0);
+/// ```
+pub struct Batch {
+    arity: usize,
+    capacity: usize,
+    handler: Handler,
+}
+
+impl fmt::Debug for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batch")
+            .field("arity", &self.arity)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl Batch {
+    /// Create a batch of the records with `arity` numbers, which are handed over to the handler one by one.
+    pub fn new<F>(arity: usize, handler: F) -> Self
+    where
+        F: FnMut(&ContextRef, &[f64]) + 'static,
+    {
+        Batch {
+            arity: arity.max(1),
+            capacity: DEFAULT_BATCH_CAPACITY,
+            handler: Box::new(handler),
+        }
+    }
+
+    /// The number of the records buffered before flushing them to the handler.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Generate the script side of the batch, which takes the host `flush` function.
+    fn wrapper(&self) -> String {
+        let params = (0..self.arity)
+            .map(|i| format!("a{}", i))
+            .collect::<Vec<_>>();
+        let stores = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| format!("slots[base + {}] = {};", i, param))
+            .collect::<Vec<_>>();
+
+        format!(
+            r#"(function (flush) {{
+    const arity = {arity};
+    const capacity = {capacity};
+    const buffer = new ArrayBuffer(arity * capacity * 8);
+    const slots = new Float64Array(buffer);
+    let len = 0;
+
+    function flushBatch() {{
+        if (len > 0) {{
+            const n = len;
+
+            len = 0;
+            flush(buffer, n);
+        }}
+    }}
+
+    return Object.freeze({{
+        send({params}) {{
+            if (len === capacity) {{
+                flushBatch();
+            }}
+
+            const base = len * arity;
+
+            {stores}
+            len++;
+        }},
+        flush: flushBatch,
+        get pending() {{
+            return len;
+        }},
+    }});
+}})"#,
+            arity = self.arity,
+            capacity = self.capacity,
+            params = params.join(", "),
+            stores = stores.join("\n            "),
+        )
+    }
+}
+
+struct Flusher {
+    arity: usize,
+    records: Vec<f64>,
+    handler: Handler,
+}
+
+impl ContextRef {
+    /// Create a batch object with the `send(...)` and `flush()` methods and the `pending` getter.
+    pub fn new_batch(&self, batch: Batch) -> Result<Local<Value>, Error> {
+        let wrapper = self.eval_script(batch.wrapper(), "<batch>", Eval::GLOBAL)?;
+        let flusher = Flusher {
+            arity: batch.arity,
+            records: Vec::with_capacity(batch.arity * batch.capacity),
+            handler: batch.handler,
+        };
+        let flush = self.new_c_function_data(flush_stub, 2, 0, self.new_userdata(flusher))?;
+
+        self.call(&wrapper, None, flush)
+    }
+}
+
+unsafe extern "C" fn flush_stub(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    _magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let args = slice::from_raw_parts(argv, argc as usize);
+
+        flush(ctxt, &Value::from(*data), args)
+            .map(|_| ctxt.undefined())
+            .new_value(ctxt)
+    })
+}
+
+fn flush(ctxt: &ContextRef, data: &Value, args: &[ffi::JSValue]) -> Result<(), Error> {
+    let mut flusher = data.downcast_mut::<Flusher>()?;
+    let Flusher {
+        arity,
+        records,
+        handler,
+    } = &mut *flusher;
+
+    let (buf, len) = match args {
+        [buf, len] => (Value::from(*buf), Value::from(*len)),
+        _ => bail!("expected the buffer and length of batch"),
+    };
+    let buf = match ctxt.get_array_buffer(&buf) {
+        Some(buf) => buf,
+        None => bail!("expected the buffer of batch"),
+    };
+    let len = len.as_int().unwrap_or_default().max(0) as usize * *arity;
+
+    records.clear();
+    records.extend(
+        buf.as_ref()
+            .chunks_exact(8)
+            .take(len)
+            .map(|b| f64::from_ne_bytes(b.try_into().unwrap())),
+    );
+
+    trace!("flush {} records of batch", records.len() / *arity);
+
+    for record in records.chunks_exact(*arity) {
+        handler(ctxt, record)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn batch() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let records = Rc::new(RefCell::new(vec![]));
+        let received = records.clone();
+
+        let batch = ctxt
+            .new_batch(
+                Batch::new(3, move |_ctxt, record| {
+                    received.borrow_mut().push(record.to_vec())
+                })
+                .with_capacity(4),
+            )
+            .unwrap();
+
+        ctxt.global_object().set_property("batch", batch).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, i32>(
+                "for (let i = 0; i < 10; i++) batch.send(i, -i, i / 2); batch.pending",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(2)
+        );
+        assert_eq!(records.borrow().len(), 8);
+
+        ctxt.eval_script(
+            "batch.send(10); batch.flush(); batch.flush()",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let records = records.borrow();
+
+        assert_eq!(records.len(), 11);
+        assert_eq!(records[3], vec![3.0, -3.0, 1.5]);
+        assert_eq!(records[10][0], 10.0);
+        assert!(records[10][1].is_nan());
+    }
+}
@@ -0,0 +1,350 @@
+use std::alloc::{self, Layout};
+use std::cell::Cell;
+use std::fmt;
+use std::os::raw::c_void;
+use std::ptr::{null_mut, NonNull};
+use std::time::{Duration, Instant};
+
+use failure::Error;
+
+use crate::{ffi, Context, ContextRef, Eval, MallocFunctions, Runtime};
+
+/// The size of the header which keeps the allocated size before the block.
+const HEADER_SIZE: usize = 16;
+
+/// The default number of the measured iterations.
+const DEFAULT_ITERATIONS: usize = 100;
+
+/// The default number of the warm-up iterations.
+const DEFAULT_WARM_UP: usize = 10;
+
+/// The allocations made by the runtime.
+#[derive(Debug, Default)]
+struct Allocations {
+    count: Cell<usize>,
+    bytes: Cell<usize>,
+}
+
+impl Allocations {
+    fn record(&self, size: usize) {
+        self.count.set(self.count.get() + 1);
+        self.bytes.set(self.bytes.get() + size);
+    }
+
+    fn snapshot(&self) -> (usize, usize) {
+        (self.count.get(), self.bytes.get())
+    }
+}
+
+fn layout(size: usize) -> Layout {
+    Layout::from_size_align(size + HEADER_SIZE, HEADER_SIZE).expect("layout")
+}
+
+unsafe extern "C" fn bench_malloc(s: *mut ffi::JSMallocState, size: usize) -> *mut c_void {
+    let s = s.as_mut().expect("state");
+
+    if s.malloc_size + size > s.malloc_limit {
+        return null_mut();
+    }
+
+    let block = alloc::alloc(layout(size));
+
+    if block.is_null() {
+        return null_mut();
+    }
+
+    *(block as *mut usize) = size;
+
+    s.malloc_count += 1;
+    s.malloc_size += size;
+
+    if let Some(allocations) = (s.opaque as *const Allocations).as_ref() {
+        allocations.record(size);
+    }
+
+    block.add(HEADER_SIZE) as *mut _
+}
+
+unsafe extern "C" fn bench_free(s: *mut ffi::JSMallocState, ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let s = s.as_mut().expect("state");
+    let size = bench_malloc_usable_size(ptr);
+
+    s.malloc_count -= 1;
+    s.malloc_size -= size;
+
+    alloc::dealloc((ptr as *mut u8).sub(HEADER_SIZE), layout(size));
+}
+
+unsafe extern "C" fn bench_realloc(
+    s: *mut ffi::JSMallocState,
+    ptr: *mut c_void,
+    size: usize,
+) -> *mut c_void {
+    if ptr.is_null() {
+        return if size == 0 {
+            null_mut()
+        } else {
+            bench_malloc(s, size)
+        };
+    }
+
+    if size == 0 {
+        bench_free(s, ptr);
+
+        return null_mut();
+    }
+
+    let st = s.as_mut().expect("state");
+    let old_size = bench_malloc_usable_size(ptr);
+
+    if st.malloc_size + size - old_size > st.malloc_limit {
+        return null_mut();
+    }
+
+    let block = alloc::realloc(
+        (ptr as *mut u8).sub(HEADER_SIZE),
+        layout(old_size),
+        size + HEADER_SIZE,
+    );
+
+    if block.is_null() {
+        return null_mut();
+    }
+
+    *(block as *mut usize) = size;
+
+    st.malloc_size = st.malloc_size + size - old_size;
+
+    if let Some(allocations) = (st.opaque as *const Allocations).as_ref() {
+        allocations.record(size);
+    }
+
+    block.add(HEADER_SIZE) as *mut _
+}
+
+unsafe extern "C" fn bench_malloc_usable_size(ptr: *const c_void) -> usize {
+    if ptr.is_null() {
+        0
+    } else {
+        *((ptr as *const u8).sub(HEADER_SIZE) as *const usize)
+    }
+}
+
+/// The report of a benchmark.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchReport {
+    /// the number of the measured iterations.
+    pub iterations: usize,
+    /// the total wall time of the measured iterations.
+    pub total: Duration,
+    /// the fastest iteration.
+    pub min: Duration,
+    /// the slowest iteration.
+    pub max: Duration,
+    /// the number of the allocations made by the measured iterations, including the reallocations.
+    pub allocations: usize,
+    /// the bytes allocated by the measured iterations.
+    pub allocated_bytes: usize,
+}
+
+impl BenchReport {
+    /// The mean wall time of the iterations.
+    pub fn mean(&self) -> Duration {
+        if self.iterations == 0 {
+            Duration::default()
+        } else {
+            self.total / self.iterations as u32
+        }
+    }
+
+    /// The mean number of the allocations of the iterations.
+    pub fn allocations_per_iteration(&self) -> usize {
+        self.allocations
+            .checked_div(self.iterations)
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} iterations in {:?}, mean {:?} (min {:?}, max {:?}), {} allocations ({} bytes) per iteration",
+            self.iterations,
+            self.total,
+            self.mean(),
+            self.min,
+            self.max,
+            self.allocations_per_iteration(),
+            self.allocated_bytes.checked_div(self.iterations).unwrap_or_default(),
+        )
+    }
+}
+
+type Setup = Box<dyn Fn(&ContextRef) -> Result<(), Error>>;
+
+/// A benchmark which runs a script repeatedly with warm-up.
+///
+/// Each iteration evaluates the script in a fresh context of the same runtime,
+/// the creation of the context and the setup are not measured.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::Bench;
+///
+/// let report = Bench::new()
+///     .with_iterations(10)
+///     .with_warm_up(2)
+///     .run("[1, 2, 3].map(x => x * 2)")
+///     .unwrap();
+///
+/// assert_eq!(report.iterations, 10);
+/// assert!(report.min <= report.mean() && report.mean() <= report.max);
+/// assert!(report.allocations > 0);
+/// ```
+pub struct Bench {
+    iterations: usize,
+    warm_up: usize,
+    flags: Eval,
+    setup: Option<Setup>,
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Bench {
+            iterations: DEFAULT_ITERATIONS,
+            warm_up: DEFAULT_WARM_UP,
+            flags: Eval::GLOBAL,
+            setup: None,
+        }
+    }
+}
+
+impl Bench {
+    pub fn new() -> Self {
+        Bench::default()
+    }
+
+    /// Set the number of the measured iterations.
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the number of the iterations to run before measuring.
+    pub fn with_warm_up(mut self, warm_up: usize) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Set the eval flags of the script.
+    pub fn with_flags(mut self, flags: Eval) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Prepare each context before evaluating the script.
+    pub fn with_setup<F>(mut self, setup: F) -> Self
+    where
+        F: Fn(&ContextRef) -> Result<(), Error> + 'static,
+    {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Run the benchmark of the script.
+    pub fn run(&self, source: &str) -> Result<BenchReport, Error> {
+        let allocations = Box::new(Allocations::default());
+        let rt = Runtime::with_malloc_funcs(
+            &MallocFunctions {
+                js_malloc: Some(bench_malloc),
+                js_free: Some(bench_free),
+                js_realloc: Some(bench_realloc),
+                js_malloc_usable_size: Some(bench_malloc_usable_size),
+            },
+            Some(NonNull::from(&*allocations)),
+        );
+        let mut report = BenchReport {
+            min: Duration::from_secs(u64::MAX),
+            ..Default::default()
+        };
+
+        for i in 0..self.warm_up + self.iterations {
+            let ctxt = Context::new(&rt);
+
+            if let Some(ref setup) = self.setup {
+                setup(&ctxt)?;
+            }
+
+            let (count, bytes) = allocations.snapshot();
+            let started = Instant::now();
+
+            ctxt.eval_script(source, "<bench>", self.flags)?;
+
+            while rt.execute_pending_job()?.is_some() {}
+
+            let elapsed = started.elapsed();
+
+            if i >= self.warm_up {
+                let (new_count, new_bytes) = allocations.snapshot();
+
+                report.iterations += 1;
+                report.total += elapsed;
+                report.min = report.min.min(elapsed);
+                report.max = report.max.max(elapsed);
+                report.allocations += new_count - count;
+                report.allocated_bytes += new_bytes - bytes;
+            }
+        }
+
+        if report.iterations == 0 {
+            report.min = Duration::default();
+        }
+
+        debug!("bench: {}", report);
+
+        Ok(report)
+    }
+}
+
+/// Run the benchmark of the script with the default settings.
+pub fn bench(source: &str, iterations: usize) -> Result<BenchReport, Error> {
+    Bench::new().with_iterations(iterations).run(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench() {
+        let _ = pretty_env_logger::try_init();
+
+        let report = Bench::new()
+            .with_iterations(5)
+            .with_warm_up(1)
+            .with_setup(|ctxt| {
+                ctxt.eval_script("var data = [];", "<setup>", Eval::GLOBAL)?;
+
+                Ok(())
+            })
+            .run("let n = 1000; for (let i = 0; i < n; i++) data.push({ i }); data.length")
+            .unwrap();
+
+        assert_eq!(report.iterations, 5);
+        assert!(report.total >= report.max);
+        assert!(report.min <= report.max);
+        assert!(report.allocations_per_iteration() >= 1000, "{}", report);
+        assert!(report.allocated_bytes > report.allocations);
+
+        let small = super::bench("1 + 2", 3).unwrap();
+
+        assert!(small.allocations < report.allocations);
+        assert!(super::bench("throw new Error('boom')", 1).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+use std::fmt;
+use std::str::FromStr;
+
+use failure::Error;
+
+use crate::{ffi, ContextRef, ErrorKind, ExtractValue, Local, NewValue, Value};
+
+/// `BigFloat` is an arbitrary precision binary floating point number of the `bignum` extension,
+/// which is kept as its decimal digits in Rust.
+///
+/// It's converted to a Javascript `BigFloat` with the precision of the current floating point environment,
+/// 53 bits by default, or the precision set with `with_precision`.
+/// It could be extracted from a `BigFloat`, a `BigInt` or a number,
+/// the digits are the shortest ones to round-trip at the precision of the current environment.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{BigFloat, Context, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let half = "0.5".parse::<BigFloat>().unwrap();
+///
+/// ctxt.global_object().set_property("half", half).unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("typeof half", Eval::GLOBAL).unwrap(),
+///     Some("bigfloat".to_owned())
+/// );
+///
+/// let pi = ctxt
+///     .eval::<_, BigFloat>("BigFloatEnv.setPrec(() => BigFloat.PI, 128)", Eval::GLOBAL)
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(pi.to_f64(), std::f64::consts::PI);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BigFloat {
+    digits: String,
+    precision: Option<u32>,
+}
+
+/// `BigDecimal` is a decimal number for the financial calculations in the scripts.
+///
+/// QuickJS has no decimal type in this version, so it's converted to a Javascript `BigFloat`
+/// with the precision to keep all of its significant digits, instead of the precision of the environment.
+/// The scripts could run the calculations in a `BigFloatEnv` with the precision they need,
+/// and round the results with `toFixed` before they are extracted.
+/// A `BigDecimal` is extracted from a `BigFloat`, a `BigInt`, a number or a decimal string,
+/// the digits beyond the precision of the current environment are only kept by the strings.
+///
+/// Unlike `BigFloat`, the `NaN` and infinite values are not decimal numbers.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{BigDecimal, Context, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let price = "19.99".parse::<BigDecimal>().unwrap();
+///
+/// ctxt.global_object().set_property("price", price).unwrap();
+///
+/// let total = ctxt
+///     .eval::<_, BigDecimal>("(price * 3l * 1.08l).toFixed(2)", Eval::GLOBAL)
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(total.to_string(), "64.77");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BigDecimal {
+    digits: String,
+}
+
+impl BigFloat {
+    /// Set the precision in bits to create the Javascript `BigFloat`.
+    pub fn with_precision(mut self, precision: u32) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Returns the decimal digits.
+    pub fn as_str(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns the nearest `f64` of the value.
+    pub fn to_f64(&self) -> f64 {
+        self.digits.parse().unwrap_or(std::f64::NAN)
+    }
+}
+
+impl BigDecimal {
+    /// Returns the decimal digits.
+    pub fn as_str(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns the nearest `f64` of the value.
+    pub fn to_f64(&self) -> f64 {
+        self.digits.parse().unwrap_or(std::f64::NAN)
+    }
+
+    /// Returns the precision in bits to keep all the significant digits.
+    fn precision(&self) -> u32 {
+        let mantissa = self.digits.split(['e', 'E']).next();
+        let digits = mantissa
+            .unwrap_or_default()
+            .trim_start_matches(['+', '-', '0', '.'])
+            .chars()
+            .filter(char::is_ascii_digit)
+            .count() as f64;
+
+        // log2(10) bits per digit, with the guard bits of the rounding
+        ((digits * std::f64::consts::LOG2_10).ceil() as u32 + 2).max(53)
+    }
+}
+
+impl fmt::Display for BigFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.digits)
+    }
+}
+
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.digits)
+    }
+}
+
+impl FromStr for BigFloat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if is_decimal(s) || ["NaN", "Infinity", "+Infinity", "-Infinity"].contains(&s) {
+            Ok(BigFloat {
+                digits: s.trim_start_matches('+').to_owned(),
+                precision: None,
+            })
+        } else {
+            Err(format_err!("invalid BigFloat `{}`", s))
+        }
+    }
+}
+
+impl FromStr for BigDecimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if is_decimal(s) {
+            Ok(BigDecimal {
+                digits: s.trim_start_matches('+').to_owned(),
+            })
+        } else {
+            Err(format_err!("invalid BigDecimal `{}`", s))
+        }
+    }
+}
+
+impl From<f64> for BigFloat {
+    fn from(n: f64) -> Self {
+        let digits = if n.is_nan() {
+            "NaN".to_owned()
+        } else if n.is_infinite() {
+            if n > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+        } else {
+            n.to_string()
+        };
+
+        BigFloat {
+            digits,
+            precision: None,
+        }
+    }
+}
+
+impl From<i64> for BigDecimal {
+    fn from(n: i64) -> Self {
+        BigDecimal {
+            digits: n.to_string(),
+        }
+    }
+}
+
+impl From<BigDecimal> for BigFloat {
+    fn from(n: BigDecimal) -> Self {
+        let precision = n.precision();
+
+        BigFloat {
+            digits: n.digits,
+            precision: Some(precision),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Decimal> for BigDecimal {
+    fn from(n: rust_decimal::Decimal) -> Self {
+        BigDecimal {
+            digits: n.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl std::convert::TryFrom<BigDecimal> for rust_decimal::Decimal {
+    type Error = Error;
+
+    fn try_from(n: BigDecimal) -> Result<Self, Self::Error> {
+        if n.digits.contains(['e', 'E']) {
+            rust_decimal::Decimal::from_scientific(&n.digits)
+        } else {
+            n.digits.parse()
+        }
+        .map_err(|err| format_err!("invalid Decimal `{}`, {}", n.digits, err))
+    }
+}
+
+impl NewValue for BigFloat {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.new_big_float(&self.digits, self.precision)
+            .new_value(ctxt)
+    }
+}
+
+impl NewValue for BigDecimal {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        BigFloat::from(self).new_value(ctxt)
+    }
+}
+
+impl ExtractValue for BigFloat {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        big_float_digits(v).map(|digits| BigFloat {
+            digits,
+            precision: None,
+        })
+    }
+}
+
+impl ExtractValue for BigDecimal {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        let digits = if v.is_string() {
+            v.to_cstring()
+                .and_then(|s| s.into_string().ok())
+                .map(|s| s.trim().trim_start_matches('+').to_owned())
+        } else {
+            big_float_digits(v)
+        };
+
+        digits
+            .filter(|digits| is_decimal(digits))
+            .map(|digits| BigDecimal { digits })
+    }
+}
+
+impl ContextRef {
+    /// Create a Javascript `BigFloat` from the decimal digits,
+    /// with the precision in bits, or the precision of the current floating point environment.
+    fn new_big_float(&self, digits: &str, precision: Option<u32>) -> Result<Local<Value>, Error> {
+        let global = self.global_object();
+        let ctor = global
+            .get_property("BigFloat")
+            .filter(|ctor| ctor.is_function())
+            .ok_or_else(|| ErrorKind::TypeError("BigFloat is not supported".to_owned(), None))?;
+
+        match precision {
+            Some(precision) => {
+                let env = global
+                    .get_property("BigFloatEnv")
+                    .filter(|ctor| ctor.is_constructor())
+                    .ok_or_else(|| {
+                        ErrorKind::TypeError("BigFloatEnv is not supported".to_owned(), None)
+                    })?;
+                let env = self.call_constructor(&env, precision as i32)?;
+
+                self.invoke(&ctor, "parseFloat", (digits, 10, env))
+            }
+            None => self.invoke(&ctor, "parseFloat", (digits, 10)),
+        }
+    }
+}
+
+/// Returns the decimal digits of a `BigFloat`, a `BigInt` or a number.
+fn big_float_digits(v: &Local<Value>) -> Option<String> {
+    if v.is_number() || v.is_big_float() || v.tag() == ffi::JS_TAG_BIG_INT {
+        v.to_cstring()?.into_string().ok()
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the string is a finite decimal number, like `-1.5e-3`.
+fn is_decimal(s: &str) -> bool {
+    let s = s.trim_start_matches(['+', '-']);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+        None => (s, None),
+    };
+    let mut parts = mantissa.splitn(2, '.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+
+    !(integer.is_empty() && fraction.is_empty())
+        && is_digits(integer)
+        && is_digits(fraction)
+        && exponent.map_or(true, |exp| {
+            let exp = exp.trim_start_matches(['+', '-']);
+
+            !exp.is_empty() && is_digits(exp)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn big_float() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        for &s in &["1", "-1.5", "+.5", "1e10", "2.5E-3"] {
+            assert!(s.parse::<BigDecimal>().is_ok(), "{}", s);
+        }
+        for &s in &["", ".", "1.2.3", "1e", "0x10", "Infinity", "NaN", "1_000"] {
+            assert!(s.parse::<BigDecimal>().is_err(), "{}", s);
+        }
+        assert_eq!(BigFloat::from(-1.0 / 0.0).as_str(), "-Infinity");
+        assert!("NaN".parse::<BigFloat>().unwrap().to_f64().is_nan());
+
+        // the digits of a decimal are kept beyond the precision of `f64`
+        let amount = "12345678901234567.89".parse::<BigDecimal>().unwrap();
+
+        ctxt.global_object().set_property("amount", amount).unwrap();
+        ctxt.global_object()
+            .set_property("rate", BigFloat::from(0.25))
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[typeof amount, typeof rate, amount.toFixed(2), rate * 4l].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("bigfloat,bigfloat,12345678901234567.89,1".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, BigDecimal>(
+                "BigFloatEnv.setPrec(() => (amount * 2l).toFixed(2), 128)",
+                Eval::GLOBAL
+            )
+            .unwrap()
+            .map(|n| n.to_string()),
+            Some("24691357802469135.78".to_owned())
+        );
+
+        // extract from the numbers and the BigInts
+        assert_eq!(
+            ctxt.eval::<_, BigFloat>("1.5", Eval::GLOBAL)
+                .unwrap()
+                .map(|n| n.to_f64()),
+            Some(1.5)
+        );
+        assert_eq!(
+            ctxt.eval::<_, BigDecimal>("2n ** 70n", Eval::GLOBAL)
+                .unwrap()
+                .map(|n| n.to_string()),
+            Some("1180591620717411303424".to_owned())
+        );
+        assert!(ctxt
+            .eval::<_, BigDecimal>("1 / 0", Eval::GLOBAL)
+            .unwrap()
+            .is_none());
+        assert!(ctxt
+            .eval::<_, BigFloat>("'1.5'", Eval::GLOBAL)
+            .unwrap()
+            .is_none());
+        assert!(ctxt
+            .eval::<_, BigDecimal>("'1.5x'", Eval::GLOBAL)
+            .unwrap()
+            .is_none());
+
+        #[cfg(feature = "decimal")]
+        {
+            use std::convert::TryFrom;
+
+            use rust_decimal::Decimal;
+
+            let n = BigDecimal::from(Decimal::new(-12345, 3));
+
+            assert_eq!(n.as_str(), "-12.345");
+            assert_eq!(
+                Decimal::try_from("1.5e3".parse::<BigDecimal>().unwrap()).unwrap(),
+                Decimal::new(1500, 0)
+            );
+        }
+    }
+}
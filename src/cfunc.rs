@@ -1,6 +1,9 @@
+use std::any::{type_name, TypeId};
+use std::cell::{Ref, RefMut};
 use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ops::Deref;
 use std::os::raw::c_int;
-use std::panic;
 use std::ptr;
 use std::slice;
 
@@ -9,7 +12,7 @@ use foreign_types::ForeignTypeRef;
 
 use crate::{
     ffi::{self, JSCFunctionEnum::*},
-    Args, ContextRef, ExtractValue, Local, NewValue, Prop, Value,
+    Args, ContextRef, ErrorKind, ExtractValue, Local, NewValue, Prop, Value,
 };
 
 /// `CFunction` is a shortcut to easily add functions, setters and getters properties to a given object.
@@ -61,15 +64,21 @@ pub enum CFunc {
     IteratorNext = JS_CFUNC_iterator_next,
 }
 
+/// The host function with its name.
+struct Native<T> {
+    func: CFunction<T>,
+    name: String,
+}
+
 impl ContextRef {
     /// Create a new C function.
-    pub fn new_c_function<T: NewValue>(
+    pub fn new_c_function<T: NewValue + 'static>(
         &self,
         func: CFunction<T>,
         name: Option<&str>,
         length: usize,
     ) -> Result<Local<Value>, Error> {
-        unsafe extern "C" fn stub<T: NewValue>(
+        unsafe extern "C" fn stub<T: NewValue + 'static>(
             ctx: *mut ffi::JSContext,
             this_val: ffi::JSValue,
             argc: c_int,
@@ -77,14 +86,16 @@ impl ContextRef {
             magic: c_int,
             data: *mut ffi::JSValue,
         ) -> ffi::JSValue {
-            panic::catch_unwind(|| {
-                let ctxt = ContextRef::from_ptr(ctx);
+            let ctxt = ContextRef::from_ptr(ctx);
+
+            ctxt.catch_unwind(|| {
                 let this = Value::from(this_val);
                 let this = this.check_undefined();
                 let args = slice::from_raw_parts(argv, argc as usize);
                 let data = ptr::NonNull::new_unchecked(data);
-                let func = ctxt.get_userdata_unchecked::<CFunction<T>>(data.cast().as_ref());
-                let func = *func.as_ref();
+                let native = ctxt.get_userdata_unchecked::<Native<T>>(data.cast().as_ref());
+                let native = native.as_ref();
+                let func = native.func;
 
                 trace!(
                     "call C function @ {:p} with {} args, this = {:?}, magic = {}",
@@ -94,17 +105,24 @@ impl ContextRef {
                     magic
                 );
 
-                func(ctxt, this, &*(args as *const _ as *const _)).new_value(ctxt)
+                let args = &*(args as *const _ as *const [Value]);
+
+                ctxt.call_host(&native.name, args, || {
+                    func(ctxt, this, args).new_value(ctxt)
+                })
             })
-            .unwrap_or_default()
         }
 
         trace!("new C function @ {:p}", &func);
 
-        let func = self.new_c_function_data(stub::<T>, length, 0, self.new_userdata(func))?;
+        let native = Native {
+            func,
+            name: name.unwrap_or("<anonymous>").to_owned(),
+        };
+        let func = self.new_c_function_data(stub::<T>, length, 0, self.new_userdata(native))?;
 
         if let Some(name) = name {
-            func.define_property_value("name", name, Prop::CONFIGURABLE)?;
+            func.set_function_name(name)?;
         }
 
         Ok(func)
@@ -120,11 +138,14 @@ impl ContextRef {
         magic: i32,
     ) -> Result<Local<Value>, Error> {
         let name = name.map(CString::new).transpose()?;
+
+        self.register_host_function(func as usize);
         self.bind(unsafe {
             ffi::JS_NewCFunction2(
                 self.as_ptr(),
                 Some(*(&func as *const _ as *const _)),
-                name.map_or_else(ptr::null_mut, |s| s.as_ptr() as *mut _),
+                name.as_ref()
+                    .map_or_else(ptr::null_mut, |s| s.as_ptr() as *mut _),
                 length as i32,
                 cproto as u32,
                 magic,
@@ -143,11 +164,14 @@ impl ContextRef {
         magic: i32,
     ) -> Result<Local<Value>, Error> {
         let name = name.map(CString::new).transpose()?;
+
+        self.register_host_function(func as usize);
         self.bind(unsafe {
             ffi::JS_NewCFunction2(
                 self.as_ptr(),
                 Some(func),
-                name.map_or_else(ptr::null_mut, |s| s.as_ptr() as *mut _),
+                name.as_ref()
+                    .map_or_else(ptr::null_mut, |s| s.as_ptr() as *mut _),
                 length as i32,
                 cproto as u32,
                 magic,
@@ -166,6 +190,9 @@ impl ContextRef {
     ) -> Result<Local<Value>, Error> {
         let data = data.into_values(self);
         let data = data.as_ref();
+
+        self.register_host_function(func as usize);
+
         let func_obj = unsafe {
             ffi::JS_NewCFunctionData(
                 self.as_ptr(),
@@ -183,11 +210,188 @@ impl ContextRef {
     }
 }
 
+impl ContextRef {
+    /// Create a named function from a typed function like `fn(T0, T1) -> Ret`,
+    /// the `length` of the function is the number of its parameters, excluding `This`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// fn add(a: i32, b: i32) -> i32 {
+    ///     a + b
+    /// }
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let func = ctxt.new_function("add", add as fn(i32, i32) -> i32).unwrap();
+    ///
+    /// func.set_function_source("function add(a, b) { [native code] }").unwrap();
+    /// ctxt.global_object().set_property("add", func).unwrap();
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("`${add.name}/${add.length} = ${add(1, 2)}`", Eval::GLOBAL).unwrap(),
+    ///     Some("add/2 = 3".to_owned())
+    /// );
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("String(add)", Eval::GLOBAL).unwrap(),
+    ///     Some("function add(a, b) { [native code] }".to_owned())
+    /// );
+    /// ```
+    pub fn new_function<F: NewValue>(&self, name: &str, func: F) -> Result<Local<Value>, Error> {
+        let func = self.bind(func.new_value(self)).ok()?;
+
+        func.set_function_name(name)?;
+
+        Ok(func)
+    }
+}
+
+impl Local<'_, Value> {
+    /// Set the `name` property of a function.
+    pub fn set_function_name(&self, name: &str) -> Result<(), Error> {
+        self.define_property_value("name", name, Prop::CONFIGURABLE)
+            .map(|_| ())
+    }
+
+    /// Set the `length` property of a function, the number of its expected arguments.
+    pub fn set_function_length(&self, length: usize) -> Result<(), Error> {
+        self.define_property_value("length", length as i32, Prop::CONFIGURABLE)
+            .map(|_| ())
+    }
+
+    /// Override the `toString` of a function to return the source,
+    /// instead of the default `function name() { [native code] }` of the native functions.
+    pub fn set_function_source(&self, source: &str) -> Result<(), Error> {
+        unsafe extern "C" fn source_stub(
+            ctx: *mut ffi::JSContext,
+            _this_val: ffi::JSValue,
+            _argc: c_int,
+            _argv: *mut ffi::JSValue,
+            _magic: c_int,
+            data: *mut ffi::JSValue,
+        ) -> ffi::JSValue {
+            let ctxt = ContextRef::from_ptr(ctx);
+
+            ctxt.clone_value(&Value::from(*data)).new_value(ctxt)
+        }
+
+        let to_string = self.ctxt.new_c_function_data(
+            source_stub,
+            0,
+            0,
+            self.ctxt.bind(source.new_value(self.ctxt)),
+        )?;
+
+        to_string.set_function_name("toString")?;
+
+        self.define_property_value("toString", to_string, Prop::CONFIGURABLE | Prop::WRITABLE)
+            .map(|_| ())
+    }
+}
+
+/// The `this` value of a native function, received as the first parameter like `fn(This<T>, ...) -> Ret`.
+///
+/// `This<Value>` accepts any object, `This<T>` only accepts the userdata objects which hold a value of type `T`,
+/// calling the function with an incompatible `this` throws a `TypeError`.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, Runtime, This};
+///
+/// struct Counter {
+///     count: i32,
+/// }
+///
+/// fn incr(this: This<Counter>, step: i32) -> i32 {
+///     let mut counter = this.borrow_mut();
+///
+///     counter.count += step;
+///     counter.count
+/// }
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let counter = ctxt.new_userdata(Counter { count: 0 });
+///
+/// counter.set_property("incr", incr as fn(This<Counter>, i32) -> i32).unwrap();
+/// ctxt.global_object().set_property("counter", counter).unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, i32>("counter.incr(1); counter.incr(2)", Eval::GLOBAL).unwrap(),
+///     Some(3)
+/// );
+/// assert_eq!(
+///     ctxt.eval::<_, String>("try { counter.incr.call({}, 1) } catch (err) { err.name }", Eval::GLOBAL)
+///         .unwrap(),
+///     Some("TypeError".to_owned())
+/// );
+/// ```
+pub struct This<'a, T: 'static = Value> {
+    this: Local<'a, Value>,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T: 'static> This<'a, T> {
+    fn new(ctxt: &'a ContextRef, this: ffi::JSValue) -> Result<Self, ErrorKind> {
+        let this = ctxt.clone_value(&Value::from(this));
+
+        if !this.is_object() {
+            return Err(ErrorKind::TypeError("`this` is not an object".into(), None));
+        }
+
+        if TypeId::of::<T>() != TypeId::of::<Value>() && !this.is_userdata::<T>() {
+            return Err(ErrorKind::TypeError(
+                format!("`this` is not an instance of `{}`", type_name::<T>()),
+                None,
+            ));
+        }
+
+        Ok(This {
+            this,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Immutably borrows the Rust value holds by the `this` object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently mutably borrowed or has been taken.
+    pub fn borrow(&self) -> Ref<T> {
+        self.this.downcast_ref::<T>().unwrap()
+    }
+
+    /// Mutably borrows the Rust value holds by the `this` object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed or has been taken.
+    pub fn borrow_mut(&self) -> RefMut<T> {
+        self.this.downcast_mut::<T>().unwrap()
+    }
+
+    /// Consumes the wrapper, returning the `this` object.
+    pub fn into_inner(self) -> Local<'a, Value> {
+        self.this
+    }
+}
+
+impl<'a, T: 'static> Deref for This<'a, T> {
+    type Target = Local<'a, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.this
+    }
+}
+
 macro_rules! new_func_value {
     () => {
-        impl<Ret: NewValue> NewValue for fn() -> Ret {
+        impl<Ret: NewValue + 'static> NewValue for fn() -> Ret {
             fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
-                unsafe extern "C" fn stub<Ret: NewValue>(
+                unsafe extern "C" fn stub<Ret: NewValue + 'static>(
                     ctx: *mut ffi::JSContext,
                     _this_val: ffi::JSValue,
                     _argc: c_int,
@@ -195,20 +399,20 @@ macro_rules! new_func_value {
                     _magic: c_int,
                     data: *mut ffi::JSValue,
                 ) -> ffi::JSValue {
-                    panic::catch_unwind(|| {
-                        let ctxt = ContextRef::from_ptr(ctx);
+                    let ctxt = ContextRef::from_ptr(ctx);
+
+                    ctxt.catch_unwind(|| {
                         let data = ptr::NonNull::new_unchecked(data);
                         let func = ctxt.get_userdata_unchecked::<fn() -> Ret>(data.cast().as_ref());
                         let func = *func.as_ref();
 
                         func().new_value(ctxt).into()
                     })
-                    .unwrap_or_default()
                 }
 
                 ctxt.new_c_function_data(stub::<Ret>, 0, 0, ctxt.new_userdata(self))
                     .unwrap()
-                    .into_inner()
+                    .into_inner_untracked()
                     .into()
 
             }
@@ -216,9 +420,9 @@ macro_rules! new_func_value {
     };
 
     ($($Arg:ident)+) => {
-        impl<Ret: NewValue, $($Arg : ExtractValue),*> NewValue for fn($( $Arg ),*) -> Ret {
+        impl<Ret: NewValue + 'static, $($Arg : ExtractValue + 'static),*> NewValue for fn($( $Arg ),*) -> Ret {
             fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
-                unsafe extern "C" fn stub<Ret: NewValue, $($Arg : ExtractValue),*>(
+                unsafe extern "C" fn stub<Ret: NewValue + 'static, $($Arg : ExtractValue + 'static),*>(
                     ctx: *mut ffi::JSContext,
                     _this_val: ffi::JSValue,
                     argc: c_int,
@@ -226,8 +430,9 @@ macro_rules! new_func_value {
                     _magic: c_int,
                     data: *mut ffi::JSValue,
                 ) -> ffi::JSValue {
-                    panic::catch_unwind(|| {
-                        let ctxt = ContextRef::from_ptr(ctx);
+                    let ctxt = ContextRef::from_ptr(ctx);
+
+                    ctxt.catch_unwind(|| {
                         let data = ptr::NonNull::new_unchecked(data);
                         let func = ctxt.get_userdata_unchecked::<fn($( $Arg ),*) -> Ret>(data.cast().as_ref());
                         let func = *func.as_ref();
@@ -235,18 +440,17 @@ macro_rules! new_func_value {
                         let mut iter = args.iter();
 
                         func($({
-                            let value = ctxt.bind(*iter.next().unwrap());
+                            let value = ctxt.clone_value(&Value::from(*iter.next().unwrap()));
                             <$Arg as ExtractValue>::extract_value(&value).unwrap()
                         }),*)
                             .new_value(&ctxt)
                             .into()
                     })
-                    .unwrap_or_default()
                 }
 
-                ctxt.new_c_function_data(stub::<Ret, $($Arg),*>, 0, 0, ctxt.new_userdata(self))
+                ctxt.new_c_function_data(stub::<Ret, $($Arg),*>, <[&str]>::len(&[$(stringify!($Arg)),*]), 0, ctxt.new_userdata(self))
                     .unwrap()
-                    .into_inner()
+                    .into_inner_untracked()
                     .into()
             }
         }
@@ -268,9 +472,66 @@ new_func_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 }
 new_func_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 }
 new_func_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 }
 
+macro_rules! new_method_value {
+    ($($Arg:ident)*) => {
+        impl<Ret: NewValue + 'static, S: 'static, $($Arg : ExtractValue + 'static),*> NewValue for fn(This<S>, $( $Arg ),*) -> Ret {
+            fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+                #[allow(unused_variables, unused_mut)]
+                unsafe extern "C" fn stub<Ret: NewValue + 'static, S: 'static, $($Arg : ExtractValue + 'static),*>(
+                    ctx: *mut ffi::JSContext,
+                    this_val: ffi::JSValue,
+                    argc: c_int,
+                    argv: *mut ffi::JSValue,
+                    _magic: c_int,
+                    data: *mut ffi::JSValue,
+                ) -> ffi::JSValue {
+                    let ctxt = ContextRef::from_ptr(ctx);
+
+                    ctxt.catch_unwind(|| {
+                        let this = match This::<S>::new(ctxt, this_val) {
+                            Ok(this) => this,
+                            Err(err) => return err.new_value(ctxt),
+                        };
+                        let data = ptr::NonNull::new_unchecked(data);
+                        let func = ctxt.get_userdata_unchecked::<fn(This<S>, $( $Arg ),*) -> Ret>(data.cast().as_ref());
+                        let func = *func.as_ref();
+                        let args = slice::from_raw_parts(argv, argc as usize);
+                        let mut iter = args.iter();
+
+                        func(this, $({
+                            let value = ctxt.clone_value(&Value::from(*iter.next().unwrap()));
+                            <$Arg as ExtractValue>::extract_value(&value).unwrap()
+                        }),*)
+                            .new_value(&ctxt)
+                    })
+                }
+
+                ctxt.new_c_function_data(stub::<Ret, S, $($Arg),*>, <[&str]>::len(&[$(stringify!($Arg)),*]), 0, ctxt.new_userdata(self))
+                    .unwrap()
+                    .into_inner_untracked()
+                    .into()
+            }
+        }
+    }
+}
+
+new_method_value! {}
+new_method_value! { T0 }
+new_method_value! { T0 T1 }
+new_method_value! { T0 T1 T2 }
+new_method_value! { T0 T1 T2 T3 }
+new_method_value! { T0 T1 T2 T3 T4 }
+new_method_value! { T0 T1 T2 T3 T4 T5 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 T7 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 }
+new_method_value! { T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 }
+
 #[cfg(test)]
 mod tests {
-    use crate::{Context, Eval, ExtractValue, Runtime};
+    use crate::{Context, Eval, ExtractValue, Runtime, This};
 
     #[test]
     fn cfunc() {
@@ -313,6 +574,130 @@ mod tests {
         // assert_eq!(String::extract_value(&res).unwrap(), "hello world");
     }
 
+    #[test]
+    fn this_binding() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        fn sum(this: This) -> f64 {
+            let len = this.get_property("length").unwrap().to_int32().unwrap();
+
+            (0..len as u32)
+                .map(|idx| this.get_property(idx).unwrap().to_float64().unwrap())
+                .sum()
+        }
+
+        let proto = ctxt
+            .eval_script("Array.prototype", "<evalScript>", Eval::GLOBAL)
+            .unwrap();
+
+        proto.set_property("sum", sum as fn(This) -> f64).unwrap();
+
+        assert_eq!(
+            ctxt.eval("[1, 2, 3].sum()", Eval::GLOBAL).unwrap(),
+            Some(6.0)
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { [].sum.call(1) } catch (err) { err.message }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("`this` is not an object".to_owned())
+        );
+
+        struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        fn scale(this: This<Point>, factor: f64) -> f64 {
+            let mut pt = this.borrow_mut();
+
+            pt.x *= factor;
+            pt.y *= factor;
+            pt.x.hypot(pt.y)
+        }
+
+        let pt = ctxt.new_userdata(Point { x: 3.0, y: 4.0 });
+
+        pt.set_property("scale", scale as fn(This<Point>, f64) -> f64)
+            .unwrap();
+        ctxt.global_object().set_property("pt", pt).unwrap();
+
+        assert_eq!(ctxt.eval("pt.scale(2)", Eval::GLOBAL).unwrap(), Some(10.0));
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { pt.scale.call([], 2) } catch (err) { err.name }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("TypeError".to_owned())
+        );
+    }
+
+    #[test]
+    fn function_name() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        fn scale(this: This, factor: f64) -> f64 {
+            this.get_property("value").unwrap().to_float64().unwrap() * factor
+        }
+
+        let global = ctxt.global_object();
+
+        global
+            .set_property("hello", hello as fn(String) -> String)
+            .unwrap();
+        global
+            .set_property(
+                "scale",
+                ctxt.new_function("scale", scale as fn(This, f64) -> f64)
+                    .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[hello.name, hello.length, scale.name, scale.length, scale.call({ value: 2 }, 3)].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(",1,scale,1,6".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>("String(scale)", Eval::GLOBAL)
+                .unwrap(),
+            Some("function scale() {\n    [native code]\n}".to_owned())
+        );
+
+        let hello = global.get_property("hello").unwrap();
+
+        hello.set_function_name("greet").unwrap();
+        hello.set_function_length(2).unwrap();
+        hello
+            .set_function_source("function greet(name, greeting) { [native code] }")
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[hello.name, hello.length, hello.toString(), Function.prototype.toString.call(hello)].join('|')",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(
+                "greet|2|function greet(name, greeting) { [native code] }|\
+                 function greet() {\n    [native code]\n}"
+                    .to_owned()
+            )
+        );
+    }
+
     pub fn hello(name: String) -> String {
         format!("hello {}", name)
     }
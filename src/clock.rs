@@ -0,0 +1,289 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+
+use crate::{ContextRef, Eval, Value};
+
+const CLOCK_WRAPPER: &str = r#"(function (Date, now, dateNow) {
+    const timeOrigin = dateNow() - now();
+
+    class Performance {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        get timeOrigin() {
+            return timeOrigin;
+        }
+
+        now() {
+            return now();
+        }
+
+        toJSON() {
+            return { timeOrigin };
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Performance';
+        }
+    }
+
+    const performance = Object.create(Performance.prototype);
+
+    if (typeof Date !== 'function') {
+        return { performance };
+    }
+
+    Object.defineProperty(Date, 'now', { value: dateNow, writable: true, configurable: true });
+
+    return {
+        performance,
+        Date: new Proxy(Date, {
+            apply(target) {
+                return new target(dateNow()).toString();
+            },
+            construct(target, args, newTarget) {
+                return Reflect.construct(target, args.length === 0 ? [dateNow()] : args, newTarget);
+            },
+        }),
+    };
+})"#;
+
+/// The clock of `performance.now()` and `Date.now()` in the scripts.
+pub trait Clock: Send {
+    /// The monotonic time elapsed since the clock created.
+    fn elapsed(&self) -> Duration;
+
+    /// The current system time.
+    fn now(&self) -> SystemTime;
+}
+
+impl Clock for Box<dyn Clock> {
+    fn elapsed(&self) -> Duration {
+        self.as_ref().elapsed()
+    }
+
+    fn now(&self) -> SystemTime {
+        self.as_ref().now()
+    }
+}
+
+/// The clock based on the monotonic and system clocks of the host.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl SystemClock {
+    /// Create a clock starts from now.
+    pub fn new() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// The virtual clock which only advances when asked, for the deterministic evaluation.
+///
+/// The clones of `ManualClock` share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock(Arc<Mutex<(Duration, SystemTime)>>);
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new(UNIX_EPOCH)
+    }
+}
+
+impl ManualClock {
+    /// Create a clock starts from the system time.
+    pub fn new(now: SystemTime) -> Self {
+        ManualClock(Arc::new(Mutex::new((Duration::default(), now))))
+    }
+
+    /// Advance the clock.
+    pub fn advance(&self, duration: Duration) {
+        let mut time = self.0.lock().unwrap();
+
+        time.0 += duration;
+        time.1 += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn elapsed(&self) -> Duration {
+        self.0.lock().unwrap().0
+    }
+
+    fn now(&self) -> SystemTime {
+        self.0.lock().unwrap().1
+    }
+}
+
+#[derive(Default)]
+struct ContextClock(RefCell<Option<Box<dyn Clock>>>);
+
+impl ContextRef {
+    /// Set the clock of `performance.now()` and `Date.now()`, the `SystemClock` is used by default.
+    pub fn set_clock<C: Clock + 'static>(&self, clock: C) -> &Self {
+        *self.state::<ContextClock>().0.borrow_mut() = Some(Box::new(clock));
+        self
+    }
+
+    fn with_clock<F: FnOnce(&dyn Clock) -> T, T>(&self, f: F) -> T {
+        let state = self.state::<ContextClock>();
+        let mut clock = state.0.borrow_mut();
+
+        f(clock
+            .get_or_insert_with(|| Box::new(SystemClock::new()))
+            .as_ref())
+    }
+
+    /// Install the `performance` object and hook `Date` with the clock of the context.
+    ///
+    /// The `performance.now()` returns the monotonic time in milliseconds,
+    /// the `Date.now()` and `new Date()` without arguments read the current time from the clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use qjs::{Context, Eval, ManualClock, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(60));
+    ///
+    /// ctxt.set_clock(clock.clone());
+    /// ctxt.init_performance().unwrap();
+    ///
+    /// clock.advance(Duration::from_millis(5));
+    ///
+    /// assert_eq!(ctxt.eval::<_, f64>("performance.now()", Eval::GLOBAL).unwrap(), Some(5.0));
+    /// assert_eq!(ctxt.eval::<_, f64>("Date.now()", Eval::GLOBAL).unwrap(), Some(60005.0));
+    /// ```
+    pub fn init_performance(&self) -> Result<(), Error> {
+        let global = self.global_object();
+        let wrapper = self.eval_script(CLOCK_WRAPPER, "<clock>", Eval::GLOBAL)?;
+        let exports = wrapper.call(
+            None,
+            (
+                global
+                    .get_property("Date")
+                    .unwrap_or_else(|| self.undefined()),
+                self.new_c_function(performance_now, Some("now"), 0)?,
+                self.new_c_function(date_now, Some("now"), 0)?,
+            ),
+        )?;
+
+        if let Some(performance) = exports.get_property("performance") {
+            global.set_property("performance", performance)?;
+        }
+
+        if let Some(date) = exports
+            .get_property("Date")
+            .and_then(|v| v.check_undefined())
+        {
+            global.set_property("Date", date)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn performance_now(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> f64 {
+    ctxt.with_clock(|clock| clock.elapsed().as_secs_f64() * 1000.0)
+}
+
+fn date_now(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> f64 {
+    match ctxt
+        .with_clock(|clock| clock.now())
+        .duration_since(UNIX_EPOCH)
+    {
+        Ok(d) => d.as_millis() as f64,
+        Err(err) => -(err.duration().as_millis() as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn manual_clock() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+
+        ctxt.set_clock(clock.clone());
+        ctxt.init_performance().unwrap();
+
+        clock.advance(Duration::from_micros(1500));
+
+        for (script, expected) in &[
+            ("String(performance.now())", "1.5"),
+            ("String(performance.timeOrigin)", "1000000"),
+            ("String(Date.now())", "1000001"),
+            ("String(new Date().getTime())", "1000001"),
+            ("String(new Date(5).getTime())", "5"),
+            ("String(new Date(2019, 0, 1).getFullYear())", "2019"),
+            ("String(Date().includes('1970'))", "true"),
+            ("String(new Date() instanceof Date)", "true"),
+            ("String(Date.UTC(1970, 0, 2))", "86400000"),
+            ("String(Object.keys(globalThis).includes('Date'))", "false"),
+            ("JSON.stringify(performance)", r#"{"timeOrigin":1000000}"#),
+            (
+                "try { new performance.constructor() } catch (e) { e.name }",
+                "TypeError",
+            ),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(*script, Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                *expected,
+                "{}",
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn system_clock() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_performance().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "var t = performance.now(); t >= 0 && performance.now() >= t && Math.abs(Date.now() - new Date().getTime()) < 1000",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+    }
+}
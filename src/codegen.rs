@@ -0,0 +1,211 @@
+use std::cell::Cell;
+use std::os::raw::c_int;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, CFunc, ContextRef, ErrorKind, Eval, Local, NewValue, Prop, Value};
+
+pub(crate) const CODE_GENERATION_DISALLOWED: &str =
+    "code generation from strings disallowed for this context";
+
+/// The function constructors which can generate code from strings.
+const FUNCTION_CONSTRUCTORS: &[(&str, &str)] = &[
+    ("AsyncFunction", "(async function(){})"),
+    ("GeneratorFunction", "(function*(){})"),
+    ("AsyncGeneratorFunction", "(async function*(){})"),
+];
+
+#[derive(Debug, Default)]
+struct CodeGeneration {
+    disabled: Cell<bool>,
+    module_loading: Cell<usize>,
+}
+
+/// Allow the module loader to be called until dropped.
+pub(crate) struct ModuleLoading<'a>(&'a ContextRef);
+
+impl Drop for ModuleLoading<'_> {
+    fn drop(&mut self) {
+        let state = self.0.state::<CodeGeneration>();
+
+        state.module_loading.set(state.module_loading.get() - 1);
+    }
+}
+
+unsafe extern "C" fn code_generation_disallowed(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    _argc: c_int,
+    _argv: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ErrorKind::EvalError(CODE_GENERATION_DISALLOWED.to_owned(), None).new_value(ctxt)
+}
+
+impl ContextRef {
+    /// Disable the code generation from strings in the context.
+    ///
+    /// The `eval` function, the `Function` constructor (and its async and generator variants)
+    /// and the dynamic `import()` will throw an `EvalError` instead.
+    /// The host could still evaluate scripts and load modules in the context.
+    ///
+    /// The code generation can't be enabled again once disabled.
+    pub fn disable_code_generation(&self) -> Result<(), Error> {
+        if self.is_code_generation_disabled() {
+            return Ok(());
+        }
+
+        let global = self.global_object();
+
+        global.set_property(
+            "eval",
+            self.new_c_function2(
+                code_generation_disallowed,
+                Some("eval"),
+                1,
+                CFunc::Generic,
+                0,
+            )?,
+        )?;
+
+        if let Some(ctor) = global.get_property("Function") {
+            global.set_property("Function", self.disallow_constructor(&ctor, "Function")?)?;
+        }
+
+        for (name, source) in FUNCTION_CONSTRUCTORS {
+            // the context may be created without the evaluator or those syntaxes
+            if let Some(ctor) = self
+                .eval_script(*source, "<codegen>", Eval::GLOBAL)
+                .ok()
+                .and_then(|func| self.get_property(&func, "constructor"))
+            {
+                self.disallow_constructor(&ctor, name)?;
+            }
+        }
+
+        self.state::<CodeGeneration>().disabled.set(true);
+
+        Ok(())
+    }
+
+    /// Returns `true` if the code generation from strings was disabled in the context.
+    pub fn is_code_generation_disabled(&self) -> bool {
+        self.state::<CodeGeneration>().disabled.get()
+    }
+
+    /// Replace the `constructor` of the function prototype with a function throws `EvalError`.
+    fn disallow_constructor(&self, ctor: &Value, name: &str) -> Result<Local<Value>, Error> {
+        let func = self.new_c_function2(
+            code_generation_disallowed,
+            Some(name),
+            1,
+            CFunc::ConstructorOrFunc,
+            0,
+        )?;
+
+        if let Some(proto) = self.get_property(ctor, "prototype") {
+            proto.define_property_value(
+                "constructor",
+                &func,
+                Prop::CONFIGURABLE | Prop::WRITABLE,
+            )?;
+            func.define_property_value("prototype", proto, Prop::empty())?;
+        }
+
+        Ok(func)
+    }
+
+    pub(crate) fn is_module_loading_allowed(&self) -> bool {
+        let state = self.state::<CodeGeneration>();
+
+        !state.disabled.get() || state.module_loading.get() > 0
+    }
+
+    pub(crate) fn start_module_loading(&self) -> ModuleLoading {
+        let state = self.state::<CodeGeneration>();
+
+        state.module_loading.set(state.module_loading.get() + 1);
+
+        ModuleLoading(self)
+    }
+}
+
+/// Returns `true` if the flags evaluates the module code.
+pub(crate) fn is_module(flags: Eval) -> bool {
+    flags.bits() & ffi::JS_EVAL_TYPE_MASK == ffi::JS_EVAL_TYPE_MODULE
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn disable_code_generation() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        rt.set_module_loader::<()>(None, Some(ffi::js_module_loader), None);
+
+        ctxt.eval::<_, ()>(
+            "var f = function() { return 1; }; var g = async function() {}",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+        ctxt.disable_code_generation().unwrap();
+
+        assert!(ctxt.is_code_generation_disabled());
+
+        for script in &[
+            "eval('1+2')",
+            "(0, eval)('1+2')",
+            "new Function('return 1')",
+            "Function('return 1')",
+            "f.constructor('return 1')",
+            "new g.constructor('return 1')",
+            "(function*(){}).constructor('yield 1')",
+            "(async function*(){}).constructor('yield 1')",
+        ] {
+            match ctxt
+                .eval::<_, ()>(*script, Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+            {
+                ErrorKind::EvalError(msg, _) => assert_eq!(msg, CODE_GENERATION_DISALLOWED),
+                err => panic!("unexpected error: {:?}, {}", err, script),
+            }
+        }
+
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "f instanceof Function && typeof g === 'function' && f() === 1",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+
+        ctxt.eval::<_, ()>(
+            "var err; import('./hello.js').catch(e => err = e.message)",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+
+        assert_eq!(
+            ctxt.eval::<_, String>("err", Eval::GLOBAL).unwrap(),
+            Some(CODE_GENERATION_DISALLOWED.to_owned())
+        );
+
+        assert_eq!(ctxt.eval::<_, i32>("1+2", Eval::GLOBAL).unwrap(), Some(3));
+    }
+}
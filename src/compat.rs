@@ -0,0 +1,146 @@
+//! The `rquickjs` style trait and method names, to ease the incremental migration from `rquickjs`.
+//!
+//! The traits are implemented for all the types which implement the conversion traits of this crate,
+//! `IntoJs` for `NewValue` and `FromJs` for `ExtractValue`, so the code could be migrated piece by piece.
+//!
+//! # Examples
+//!
+//! ```
+//! use qjs::compat::{CtxExt, IntoJs, ObjectExt};
+//! use qjs::{Context, Runtime};
+//!
+//! let rt = Runtime::new();
+//! let ctxt = Context::new(&rt);
+//! let globals = ctxt.globals();
+//!
+//! globals.set("answer", 42).unwrap();
+//! globals.set("greeting", "hello".into_js(&ctxt).unwrap()).unwrap();
+//!
+//! assert_eq!(globals.get::<_, i32>("answer").unwrap(), 42);
+//! assert_eq!(ctxt.eval_as::<String, _>("greeting + ' world'").unwrap(), "hello world");
+//! ```
+use failure::Error;
+
+use crate::{
+    ContextRef, Eval, ExtractValue, GetProperty, HasProperty, Local, NewValue, SetProperty, Value,
+};
+
+/// Convert a Rust value to the Javascript value, like `rquickjs::IntoJs`.
+pub trait IntoJs {
+    /// Convert the value to a Javascript value of the context.
+    fn into_js(self, ctxt: &ContextRef) -> Result<Local<Value>, Error>;
+}
+
+impl<T: NewValue> IntoJs for T {
+    fn into_js(self, ctxt: &ContextRef) -> Result<Local<Value>, Error> {
+        ctxt.bind(ctxt.new_value(self)).ok()
+    }
+}
+
+/// Convert a Javascript value to the Rust value, like `rquickjs::FromJs`.
+pub trait FromJs: Sized {
+    /// Convert the Javascript value of the context to a Rust value.
+    fn from_js(ctxt: &ContextRef, value: Local<Value>) -> Result<Self, Error>;
+}
+
+impl<T: ExtractValue> FromJs for T {
+    fn from_js(_ctxt: &ContextRef, value: Local<Value>) -> Result<Self, Error> {
+        value.extract()
+    }
+}
+
+/// The `rquickjs::Ctx` style methods of the context.
+pub trait CtxExt {
+    /// Get the global object, like `Ctx::globals`.
+    fn globals(&self) -> Local<Value>;
+
+    /// Evaluate a script and convert the result, like `Ctx::eval`.
+    fn eval_as<V: FromJs, S: Into<Vec<u8>>>(&self, source: S) -> Result<V, Error>;
+}
+
+impl CtxExt for ContextRef {
+    fn globals(&self) -> Local<Value> {
+        self.global_object()
+    }
+
+    fn eval_as<V: FromJs, S: Into<Vec<u8>>>(&self, source: S) -> Result<V, Error> {
+        let value = self.eval_script(source, "<eval>", Eval::GLOBAL)?;
+
+        V::from_js(self, value)
+    }
+}
+
+/// The `rquickjs::Object` style methods of the object.
+pub trait ObjectExt {
+    /// Get and convert a property of the object, like `Object::get`.
+    fn get<K: GetProperty, V: FromJs>(&self, key: K) -> Result<V, Error>;
+
+    /// Set a property of the object, like `Object::set`.
+    fn set<K: SetProperty, V: IntoJs>(&self, key: K, value: V) -> Result<(), Error>;
+
+    /// Check if the object has a property, like `Object::contains_key`.
+    fn contains_key<K: HasProperty>(&self, key: K) -> Result<bool, Error>;
+}
+
+impl ObjectExt for Local<'_, Value> {
+    fn get<K: GetProperty, V: FromJs>(&self, key: K) -> Result<V, Error> {
+        let value = match self.get_property(key) {
+            Some(value) => value.ok()?,
+            None => self.ctxt.undefined(),
+        };
+
+        V::from_js(self.ctxt, value)
+    }
+
+    fn set<K: SetProperty, V: IntoJs>(&self, key: K, value: V) -> Result<(), Error> {
+        let value = value.into_js(self.ctxt)?;
+
+        self.set_property(key, value).map(|_| ())
+    }
+
+    fn contains_key<K: HasProperty>(&self, key: K) -> Result<bool, Error> {
+        self.has_property(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn compat() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let obj = ctxt.bind(ctxt.new_object());
+
+        obj.set("n", 1).unwrap();
+        obj.set("null", ctxt.null()).unwrap();
+        obj.set(0u32, "zero").unwrap();
+        obj.set("pi", 1.5.into_js(&ctxt).unwrap()).unwrap();
+
+        assert_eq!(obj.get::<_, i32>("n").unwrap(), 1);
+        assert_eq!(obj.get::<_, String>(0u32).unwrap(), "zero");
+        assert_eq!(obj.get::<_, f64>("pi").unwrap(), 1.5);
+        assert!(obj.contains_key("n").unwrap());
+        assert!(!obj.contains_key("m").unwrap());
+        assert_eq!(
+            obj.get::<_, ()>("null")
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap(),
+            ErrorKind::Conversion {
+                expected: "()",
+                found: "null"
+            }
+        );
+
+        ctxt.globals().set("obj", obj).unwrap();
+
+        assert_eq!(ctxt.eval_as::<i32, _>("obj.n + 1").unwrap(), 2);
+        assert!(ctxt.eval_as::<i32, _>("throw new Error('boom')").is_err());
+    }
+}
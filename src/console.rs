@@ -0,0 +1,665 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Instant;
+
+use failure::Error;
+
+use crate::{prop::Names, ContextRef, Eval, Local, Value, UNDEFINED};
+
+/// Wrap the native `trace` function with the stack captured from the Javascript frames.
+const TRACE_WRAPPER: &str = r#"(function (native) {
+    return function trace(...args) {
+        try { throw new Error() } catch (e) { return native(e.stack, ...args) }
+    }
+})"#;
+
+/// The max depth of the nested objects to format.
+const MAX_FORMAT_DEPTH: usize = 2;
+
+/// The level of a console message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// `console.trace`
+    Trace,
+    /// `console.debug`
+    Debug,
+    /// `console.log` and `console.table`
+    Log,
+    /// `console.info`
+    Info,
+    /// `console.warn`
+    Warn,
+    /// `console.error` and `console.assert`
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Log => "log",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        })
+    }
+}
+
+/// The destination of the console messages.
+pub trait ConsoleSink: Send {
+    /// Write a formatted console message.
+    fn write(&self, level: Level, msg: &str);
+}
+
+impl<F: Fn(Level, &str) + Send> ConsoleSink for F {
+    fn write(&self, level: Level, msg: &str) {
+        self(level, msg)
+    }
+}
+
+/// Write the console messages to the standard error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StderrSink;
+
+impl ConsoleSink for StderrSink {
+    fn write(&self, _level: Level, msg: &str) {
+        let _ = writeln!(io::stderr(), "{}", msg);
+    }
+}
+
+/// Write the console messages to the `log` crate with the `qjs::console` target.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogSink;
+
+impl ConsoleSink for LogSink {
+    fn write(&self, level: Level, msg: &str) {
+        let level = match level {
+            Level::Trace => log::Level::Trace,
+            Level::Debug => log::Level::Debug,
+            Level::Log | Level::Info => log::Level::Info,
+            Level::Warn => log::Level::Warn,
+            Level::Error => log::Level::Error,
+        };
+
+        log!(target: "qjs::console", level, "{}", msg);
+    }
+}
+
+/// Write the console messages as the `tracing` events with the `qjs::console` target.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingSink;
+
+#[cfg(feature = "tracing")]
+impl ConsoleSink for TracingSink {
+    fn write(&self, level: Level, msg: &str) {
+        match level {
+            Level::Trace => tracing::trace!(target: "qjs::console", "{}", msg),
+            Level::Debug => tracing::debug!(target: "qjs::console", "{}", msg),
+            Level::Log | Level::Info => tracing::info!(target: "qjs::console", "{}", msg),
+            Level::Warn => tracing::warn!(target: "qjs::console", "{}", msg),
+            Level::Error => tracing::error!(target: "qjs::console", "{}", msg),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Console {
+    sink: RefCell<Option<Box<dyn ConsoleSink>>>,
+    timers: RefCell<HashMap<String, Instant>>,
+}
+
+impl ContextRef {
+    /// Install the global `console` object which writes the messages to the sink.
+    ///
+    /// The `console` supports `log`, `info`, `warn`, `error`, `debug`, `trace`,
+    /// `assert`, `time`, `timeEnd` and `table` methods,
+    /// the sink will be replaced if the `console` has been installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use qjs::{ConsoleLevel, Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let output = Arc::new(Mutex::new(vec![]));
+    /// let sink = output.clone();
+    ///
+    /// ctxt.init_console(move |level: ConsoleLevel, msg: &str| {
+    ///     sink.lock().unwrap().push(format!("{}: {}", level, msg))
+    /// })
+    /// .unwrap();
+    ///
+    /// ctxt.eval::<_, ()>("console.warn('hello %s', 'world', { n: 1 })", Eval::GLOBAL)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(*output.lock().unwrap(), vec!["warn: hello world { n: 1 }"]);
+    /// ```
+    pub fn init_console<S: ConsoleSink + 'static>(&self, sink: S) -> Result<(), Error> {
+        *self.state::<Console>().sink.borrow_mut() = Some(Box::new(sink));
+
+        let console = self.bind(self.new_object());
+
+        console.set_property("log", self.new_c_function(log, Some("log"), 0)?)?;
+        console.set_property("info", self.new_c_function(info, Some("info"), 0)?)?;
+        console.set_property("warn", self.new_c_function(warn, Some("warn"), 0)?)?;
+        console.set_property("error", self.new_c_function(error, Some("error"), 0)?)?;
+        console.set_property("debug", self.new_c_function(debug, Some("debug"), 0)?)?;
+        console.set_property(
+            "trace",
+            self.eval_script(TRACE_WRAPPER, "<console>", Eval::GLOBAL)?
+                .call(None, self.new_c_function(trace, Some("trace"), 0)?)?,
+        )?;
+        console.set_property("assert", self.new_c_function(assert, Some("assert"), 0)?)?;
+        console.set_property("time", self.new_c_function(time, Some("time"), 0)?)?;
+        console.set_property(
+            "timeEnd",
+            self.new_c_function(time_end, Some("timeEnd"), 0)?,
+        )?;
+        console.set_property("table", self.new_c_function(table, Some("table"), 1)?)?;
+
+        self.global_object().set_property("console", console)?;
+
+        Ok(())
+    }
+
+    fn console_write(&self, level: Level, msg: &str) {
+        if let Some(ref sink) = *self.state::<Console>().sink.borrow() {
+            sink.write(level, msg)
+        }
+    }
+
+    /// Format the arguments like `console.log`, supports the `%s`, `%d`, `%i`, `%f`, `%o`, `%O` and `%%` specifiers.
+    fn console_format(&self, args: &[Value]) -> String {
+        let mut parts = vec![];
+        let mut args = args.iter();
+
+        if let Some(first) = args.next() {
+            if first.is_string() {
+                let fmt = self.clone_value(first).to_string();
+                let mut s = String::new();
+                let mut chars = fmt.chars().peekable();
+
+                while let Some(c) = chars.next() {
+                    if c != '%' {
+                        s.push(c);
+                        continue;
+                    }
+
+                    match chars.peek().cloned() {
+                        Some('%') => {
+                            chars.next();
+                            s.push('%');
+                        }
+                        Some(spec @ 's') | Some(spec @ 'd') | Some(spec @ 'i')
+                        | Some(spec @ 'f') | Some(spec @ 'o') | Some(spec @ 'O') => {
+                            if let Some(arg) = args.next() {
+                                chars.next();
+
+                                let arg = self.clone_value(arg);
+
+                                match spec {
+                                    's' => s.push_str(&self.format_value(&arg, false)),
+                                    'd' | 'i' => match arg.to_float64() {
+                                        Some(n) if n.is_finite() => {
+                                            s.push_str(&(n.trunc() as i64).to_string())
+                                        }
+                                        _ => s.push_str("NaN"),
+                                    },
+                                    'f' => s.push_str(&arg.to_str().to_string()),
+                                    _ => s.push_str(&self.format_value(&arg, true)),
+                                }
+                            } else {
+                                s.push('%');
+                            }
+                        }
+                        _ => s.push('%'),
+                    }
+                }
+
+                parts.push(s);
+            } else {
+                parts.push(self.format_value(&self.clone_value(first), false));
+            }
+        }
+
+        parts.extend(args.map(|arg| self.format_value(&self.clone_value(arg), false)));
+        parts.join(" ")
+    }
+
+    pub(crate) fn format_value(&self, value: &Local<Value>, quoted: bool) -> String {
+        let mut s = String::new();
+
+        self.write_value(&mut s, value, quoted, 0, &mut HashSet::new());
+
+        s
+    }
+
+    fn write_value(
+        &self,
+        s: &mut String,
+        value: &Local<Value>,
+        quoted: bool,
+        depth: usize,
+        visited: &mut HashSet<usize>,
+    ) {
+        if value.is_string() {
+            if quoted {
+                s.push_str(&format!("'{}'", value))
+            } else {
+                s.push_str(&value.to_string())
+            }
+        } else if value.is_symbol() {
+            s.push_str(
+                &value
+                    .invoke("toString", ())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| "Symbol()".to_owned()),
+            )
+        } else if !value.is_object() {
+            s.push_str(&value.to_string())
+        } else if value.is_function() {
+            match value.get_property("name").map(|name| name.to_string()) {
+                Some(ref name) if !name.is_empty() => s.push_str(&format!("[Function: {}]", name)),
+                _ => s.push_str("[Function (anonymous)]"),
+            }
+        } else if value.is_error() {
+            s.push_str(&value.to_string());
+
+            if let Some(stack) = value.get_property("stack").filter(|s| s.is_string()) {
+                let stack = stack.to_string();
+
+                if !stack.is_empty() {
+                    s.push('\n');
+                    s.push_str(stack.trim_end());
+                }
+            }
+        } else {
+            let ptr = value.as_object().unwrap().as_ptr() as usize;
+            let is_array = self.is_array(value).unwrap_or_default();
+
+            if visited.contains(&ptr) {
+                s.push_str("[Circular]");
+            } else if depth > MAX_FORMAT_DEPTH {
+                s.push_str(if is_array { "[Array]" } else { "[Object]" });
+            } else {
+                visited.insert(ptr);
+
+                let names = self
+                    .get_own_property_names(value, Names::STRING | Names::ENUM_ONLY)
+                    .ok()
+                    .and_then(|names| names)
+                    .unwrap_or_default();
+
+                if names.is_empty() {
+                    s.push_str(if is_array { "[]" } else { "{}" });
+                } else {
+                    s.push_str(if is_array { "[ " } else { "{ " });
+
+                    for (i, name) in names.iter().enumerate() {
+                        if i > 0 {
+                            s.push_str(", ");
+                        }
+                        if !is_array {
+                            s.push_str(&format!("{}: ", name));
+                        }
+
+                        match self.get_property(value, name) {
+                            Some(v) => self.write_value(s, &v, true, depth + 1, visited),
+                            None => s.push_str("undefined"),
+                        }
+                    }
+
+                    s.push_str(if is_array { " ]" } else { " }" });
+                }
+
+                visited.remove(&ptr);
+            }
+        }
+    }
+
+    /// Format the tabular data like `console.table`.
+    fn format_table(&self, data: &Local<Value>, columns: Option<&Local<Value>>) -> String {
+        let keys = |obj: &Value| {
+            self.get_own_property_names(obj, Names::STRING | Names::ENUM_ONLY)
+                .ok()
+                .and_then(|names| names)
+                .unwrap_or_default()
+        };
+
+        let mut header = columns.map_or_else(Vec::new, |columns| {
+            keys(columns)
+                .iter()
+                .flat_map(|idx| self.get_property(columns, idx))
+                .map(|name| name.to_string())
+                .collect()
+        });
+        let fixed_columns = columns.is_some();
+        let mut has_values = false;
+        let mut rows = vec![];
+
+        for idx in keys(data) {
+            let row = match self.get_property(data, &idx) {
+                Some(row) => row,
+                None => continue,
+            };
+            let mut cells = HashMap::new();
+
+            if row.is_object() && !row.is_function() {
+                for name in keys(&row) {
+                    let name = name.to_string();
+
+                    if !header.contains(&name) {
+                        if fixed_columns {
+                            continue;
+                        }
+
+                        header.push(name.clone());
+                    }
+
+                    if let Some(v) = self.get_property(&row, name.as_str()) {
+                        cells.insert(name, self.format_value(&v, true));
+                    }
+                }
+
+                rows.push((idx.to_string(), cells, None));
+            } else {
+                has_values = true;
+
+                rows.push((idx.to_string(), cells, Some(self.format_value(&row, true))));
+            }
+        }
+
+        let mut titles = vec!["(index)".to_owned()];
+        titles.extend(header.iter().cloned());
+        if has_values {
+            titles.push("Values".to_owned());
+        }
+
+        let lines = rows
+            .into_iter()
+            .map(|(idx, mut cells, value)| {
+                let mut line = vec![idx];
+                line.extend(
+                    header
+                        .iter()
+                        .map(|name| cells.remove(name).unwrap_or_default()),
+                );
+                if has_values {
+                    line.push(value.unwrap_or_default());
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+
+        let widths = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                lines
+                    .iter()
+                    .map(|line| line[i].chars().count())
+                    .chain(Some(title.chars().count()))
+                    .max()
+                    .unwrap_or_default()
+                    + 2
+            })
+            .collect::<Vec<_>>();
+
+        let border = |left: &str, mid: &str, right: &str| {
+            let cols = widths.iter().map(|w| "─".repeat(*w)).collect::<Vec<_>>();
+
+            format!("{}{}{}", left, cols.join(mid), right)
+        };
+        let row = |cells: &[String]| {
+            let cols = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let pad = width - cell.chars().count();
+
+                    format!(
+                        "{}{}{}",
+                        " ".repeat(pad / 2),
+                        cell,
+                        " ".repeat(pad - pad / 2)
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            format!("│{}│", cols.join("│"))
+        };
+
+        let mut table = vec![border("┌", "┬", "┐"), row(&titles), border("├", "┼", "┤")];
+        table.extend(lines.iter().map(|line| row(line)));
+        table.push(border("└", "┴", "┘"));
+        table.join("\n")
+    }
+}
+
+fn log(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    ctxt.console_write(Level::Log, &ctxt.console_format(args));
+    UNDEFINED
+}
+
+fn info(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    ctxt.console_write(Level::Info, &ctxt.console_format(args));
+    UNDEFINED
+}
+
+fn warn(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    ctxt.console_write(Level::Warn, &ctxt.console_format(args));
+    UNDEFINED
+}
+
+fn error(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    ctxt.console_write(Level::Error, &ctxt.console_format(args));
+    UNDEFINED
+}
+
+fn debug(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    ctxt.console_write(Level::Debug, &ctxt.console_format(args));
+    UNDEFINED
+}
+
+fn trace(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let mut msg = format!(
+        "Trace: {}",
+        ctxt.console_format(args.get(1..).unwrap_or_default())
+    );
+
+    // skip the frame of the wrapper function
+    if let Some(stack) = args.first().filter(|stack| stack.is_string()) {
+        let stack = ctxt.clone_value(stack).to_string();
+
+        for frame in stack.lines().skip(1) {
+            msg.push('\n');
+            msg.push_str(frame);
+        }
+    }
+
+    ctxt.console_write(Level::Trace, msg.trim_end());
+    UNDEFINED
+}
+
+fn assert(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let ok = args
+        .first()
+        .and_then(|cond| ctxt.to_bool(cond))
+        .unwrap_or_default();
+
+    if !ok {
+        let msg = ctxt.console_format(args.get(1..).unwrap_or_default());
+
+        if msg.is_empty() {
+            ctxt.console_write(Level::Error, "Assertion failed");
+        } else {
+            ctxt.console_write(Level::Error, &format!("Assertion failed: {}", msg));
+        }
+    }
+
+    UNDEFINED
+}
+
+fn timer_label(ctxt: &ContextRef, args: &[Value]) -> String {
+    args.first()
+        .filter(|label| !label.is_undefined())
+        .map_or_else(
+            || "default".to_owned(),
+            |label| ctxt.clone_value(label).to_string(),
+        )
+}
+
+fn time(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let label = timer_label(ctxt, args);
+    let console = ctxt.state::<Console>();
+    let mut timers = console.timers.borrow_mut();
+
+    if timers.contains_key(&label) {
+        drop(timers);
+
+        ctxt.console_write(Level::Warn, &format!("Timer '{}' already exists", label));
+    } else {
+        timers.insert(label, Instant::now());
+    }
+
+    UNDEFINED
+}
+
+fn time_end(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let label = timer_label(ctxt, args);
+    let started = ctxt.state::<Console>().timers.borrow_mut().remove(&label);
+
+    match started {
+        Some(started) => {
+            let elapsed = started.elapsed();
+
+            ctxt.console_write(
+                Level::Log,
+                &format!("{}: {:.3}ms", label, elapsed.as_secs_f64() * 1000.0),
+            )
+        }
+        None => ctxt.console_write(Level::Warn, &format!("Timer '{}' does not exist", label)),
+    }
+
+    UNDEFINED
+}
+
+fn table(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    match args.first() {
+        Some(data) if data.is_object() => {
+            let data = ctxt.clone_value(data);
+            let columns = args
+                .get(1)
+                .filter(|columns| columns.is_object())
+                .map(|columns| ctxt.clone_value(columns));
+
+            ctxt.console_write(Level::Log, &ctxt.format_table(&data, columns.as_ref()))
+        }
+        _ => ctxt.console_write(Level::Log, &ctxt.console_format(args)),
+    }
+
+    UNDEFINED
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn console() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let output = Arc::new(Mutex::new(vec![]));
+        let sink = output.clone();
+
+        ctxt.init_console(move |level, msg: &str| {
+            sink.lock().unwrap().push((level, msg.to_owned()))
+        })
+        .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+console.log('hello', 'world', 42, true, null, undefined);
+console.info('%s is %d years, %% %o', 'Bob', 42.5, 'x', 'extra');
+console.debug([1, 'a', [2, [3, [4]]]], { a: { b: { c: { d: 1 } } } }, function foo() {});
+console.error(Symbol('s'), {}, []);
+var o = { name: 'o' }; o.self = o;
+console.warn(o);
+console.assert(true, 'not shown');
+console.assert(1 > 2, 'math is %s', 'broken');
+console.timeEnd('t');
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                (Level::Log, "hello world 42 true null undefined".to_owned()),
+                (Level::Info, "Bob is 42 years, % 'x' extra".to_owned()),
+                (
+                    Level::Debug,
+                    "[ 1, 'a', [ 2, [ 3, [Array] ] ] ] { a: { b: { c: [Object] } } } [Function: foo]"
+                        .to_owned()
+                ),
+                (Level::Error, "Symbol(s) {} []".to_owned()),
+                (Level::Warn, "{ name: 'o', self: [Circular] }".to_owned()),
+                (Level::Error, "Assertion failed: math is broken".to_owned()),
+                (Level::Warn, "Timer 't' does not exist".to_owned()),
+            ]
+        );
+
+        ctxt.eval::<_, ()>(
+            r#"
+console.time('t');
+console.timeEnd('t');
+function f() { console.trace('here') }
+f();
+console.table([{ a: 1, b: 'Y' }, { a: 'Z', c: 2 }, 3]);
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let output = output.lock().unwrap();
+
+        assert_eq!(output[0].0, Level::Log);
+        assert!(output[0].1.starts_with("t: "), "{}", output[0].1);
+        assert!(output[0].1.ends_with("ms"), "{}", output[0].1);
+        assert_eq!(output[1].0, Level::Trace);
+        assert!(output[1].1.starts_with("Trace: here\n"), "{}", output[1].1);
+        assert!(
+            output[1].1.contains("at f (<evalScript>)"),
+            "{}",
+            output[1].1
+        );
+        assert_eq!(
+            output[2],
+            (
+                Level::Log,
+                r#"┌─────────┬─────┬─────┬───┬────────┐
+│ (index) │  a  │  b  │ c │ Values │
+├─────────┼─────┼─────┼───┼────────┤
+│    0    │  1  │ 'Y' │   │        │
+│    1    │ 'Z' │     │ 2 │        │
+│    2    │     │     │   │   3    │
+└─────────┴─────┴─────┴───┴────────┘"#
+                    .to_owned()
+            )
+        );
+    }
+}
@@ -1,8 +1,15 @@
+use std::any::Any;
+use std::cell::Cell;
+#[cfg(feature = "leak-detection")]
+use std::panic::Location;
 use std::ptr::{null_mut, NonNull};
 
+use failure::Error;
 use foreign_types::{ForeignType, ForeignTypeRef};
 
-use crate::{ffi, Local, RuntimeRef, Value};
+#[cfg(feature = "leak-detection")]
+use crate::leak::{self, HandleKind};
+use crate::{ffi, state, Local, RuntimeRef, Value};
 
 foreign_type! {
     /// `Context` represents a Javascript context (or Realm).
@@ -13,21 +20,105 @@ foreign_type! {
     pub type Context : Send {
         type CType = ffi::JSContext;
 
-        fn drop = ffi::JS_FreeContext;
+        fn drop = free_context;
     }
 }
 
+unsafe fn free_context(ctx: *mut ffi::JSContext) {
+    // the states may hold the values which must be freed before the context.
+    state::CONTEXT.free(ctx);
+
+    #[cfg(feature = "leak-detection")]
+    leak::untrack(
+        RuntimeRef::from_ptr(ffi::JS_GetRuntime(ctx)),
+        HandleKind::Context,
+        ctx as usize,
+    );
+
+    ffi::JS_FreeContext(ctx);
+}
+
 impl_foreign_type!(Context, ContextRef);
 
 pub struct Builder(Context);
 
 impl Context {
+    /// Create a new context with the intrinsic objects and the registered preludes.
+    ///
+    /// A prelude which failed to load is only logged, use `Context::try_new` to get the error.
+    #[cfg_attr(feature = "leak-detection", track_caller)]
     pub fn new(runtime: &RuntimeRef) -> Context {
-        unsafe { Context::from_ptr(ffi::JS_NewContext(runtime.as_ptr())) }
+        let ctxt = Context::new_with_intrinsics(runtime);
+
+        if let Err(err) = ctxt.load_preludes() {
+            warn!("failed to load preludes, {}", err);
+        }
+
+        ctxt.record_initial_baseline();
+
+        ctxt
     }
 
+    /// Create a new context with the intrinsic objects and the registered preludes,
+    /// returns the error if a prelude failed to load.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    ///
+    /// rt.register_prelude("sdk", "throw new Error('boom')").unwrap();
+    ///
+    /// assert!(Context::try_new(&rt).unwrap_err().to_string().contains("boom"));
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn try_new(runtime: &RuntimeRef) -> Result<Context, Error> {
+        let ctxt = Context::new_with_intrinsics(runtime);
+
+        ctxt.load_preludes()?;
+        ctxt.record_initial_baseline();
+
+        Ok(ctxt)
+    }
+
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    fn new_with_intrinsics(runtime: &RuntimeRef) -> Context {
+        let ctxt = unsafe { Context::from_ptr(ffi::JS_NewContext(runtime.as_ptr())) };
+
+        #[cfg(feature = "leak-detection")]
+        ctxt.track_leak(Location::caller());
+
+        ctxt.remove_disabled_builtins();
+
+        ctxt
+    }
+
+    fn record_initial_baseline(&self) {
+        if let Err(err) = self.record_baseline() {
+            warn!("failed to record baseline, {}", err);
+        }
+    }
+
+    #[cfg_attr(feature = "leak-detection", track_caller)]
     pub fn builder(runtime: &RuntimeRef) -> Builder {
-        Builder(unsafe { Context::from_ptr(ffi::JS_NewContextRaw(runtime.as_ptr())) })
+        let ctxt = unsafe { Context::from_ptr(ffi::JS_NewContextRaw(runtime.as_ptr())) };
+
+        #[cfg(feature = "leak-detection")]
+        ctxt.track_leak(Location::caller());
+
+        Builder(ctxt)
+    }
+
+    #[cfg(feature = "leak-detection")]
+    fn track_leak(&self, location: &'static Location<'static>) {
+        leak::track(
+            self.runtime(),
+            HandleKind::Context,
+            self.as_ptr() as usize,
+            location,
+        );
     }
 }
 
@@ -88,6 +179,12 @@ impl Builder {
     }
 
     pub fn build(self) -> Context {
+        self.0.remove_disabled_builtins();
+
+        if let Err(err) = self.0.record_baseline() {
+            debug!("failed to record baseline, {}", err);
+        }
+
         self.0
     }
 }
@@ -98,32 +195,169 @@ impl ContextRef {
     }
 
     pub fn userdata<T>(&self) -> Option<NonNull<T>> {
-        NonNull::new(unsafe { ffi::JS_GetContextOpaque(self.as_ptr()) } as *mut _)
+        unsafe { state::CONTEXT.get(self.as_ptr()) }
+            .and_then(|states| NonNull::new(states.userdata.get() as *mut _))
     }
 
     pub fn set_userdata<T>(&self, userdata: Option<NonNull<T>>) -> &Self {
         trace!("{:?} set userdata to {:?}", self, userdata);
 
-        unsafe {
-            ffi::JS_SetContextOpaque(
-                self.as_ptr(),
-                userdata.map_or_else(null_mut, |p| p.as_ptr() as *mut _),
-            );
-        }
+        unsafe { state::CONTEXT.get_or_attach(self.as_ptr()) }
+            .userdata
+            .set(userdata.map_or_else(null_mut, |p| p.as_ptr() as *mut _));
         self
     }
 
     /// Set the maximum system stack size.
+    ///
+    /// The stack size is measured from the stack pointer when the context was created,
+    /// exceeding it throws a `StackOverflow` error.
     pub fn set_max_stack_size(&self, stack_size: usize) -> &Self {
         trace!("{:?} set stack size to {:?}", self, stack_size);
 
         unsafe {
             ffi::JS_SetMaxStackSize(self.as_ptr(), stack_size);
         }
+        self.state::<StackSize>().0.set(Some(stack_size));
         self
     }
 
+    /// Get the maximum system stack size.
+    pub fn max_stack_size(&self) -> usize {
+        self.state::<StackSize>()
+            .0
+            .get()
+            .unwrap_or(ffi::JS_DEFAULT_STACK_SIZE as usize)
+    }
+
+    /// Call the function with a temporary maximum system stack size,
+    /// which limits the recursion depth of the evaluation in it.
+    ///
+    /// The previous stack size is restored when `f` returns or panics.
+    pub fn with_max_stack_size<F: FnOnce(&Self) -> T, T>(&self, stack_size: usize, f: F) -> T {
+        let _saved = SavedStackSize(self, self.max_stack_size());
+
+        self.set_max_stack_size(stack_size);
+
+        f(self)
+    }
+
     pub fn global_object(&self) -> Local<Value> {
         self.bind(unsafe { ffi::JS_GetGlobalObject(self.as_ptr()) })
     }
+
+    /// Get the Rust state of type `T` attached to the `Context`.
+    pub(crate) fn state<T: Any + Send + Default>(&self) -> &T {
+        unsafe { state::CONTEXT.get_or_attach(self.as_ptr()) }.get_or_default()
+    }
+}
+
+#[derive(Default)]
+struct StackSize(Cell<Option<usize>>);
+
+/// The previous maximum stack size, restore it when dropped, even if the function panics.
+struct SavedStackSize<'a>(&'a ContextRef, usize);
+
+impl Drop for SavedStackSize<'_> {
+    fn drop(&mut self) {
+        self.0.set_max_stack_size(self.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use crate::{ErrorKind, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn stack_overflow() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.eval::<_, ()>("function f(n) { return f(n+1) + 1; }", Eval::GLOBAL)
+            .unwrap();
+
+        let err = ctxt
+            .with_max_stack_size(64 * 1024, |ctxt| ctxt.eval::<_, i32>("f(0)", Eval::GLOBAL))
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        match err {
+            ErrorKind::StackOverflow(Some(ref stack)) => assert!(stack.contains("at f ")),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(err.message(), "stack overflow");
+        assert_eq!(ctxt.max_stack_size(), ffi::JS_DEFAULT_STACK_SIZE as usize);
+
+        // the recursion is still limited after the context was used from a shallower frame
+        ctxt.eval::<_, ()>("Promise.resolve().then(function () { f(0) })", Eval::GLOBAL)
+            .unwrap();
+
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+    }
+
+    #[test]
+    fn restore_stack_size() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            ctxt.with_max_stack_size(64 * 1024, |ctxt| {
+                assert_eq!(ctxt.max_stack_size(), 64 * 1024);
+
+                panic!("boom")
+            })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(ctxt.max_stack_size(), ffi::JS_DEFAULT_STACK_SIZE as usize);
+    }
+
+    #[test]
+    fn shallow_frame() {
+        let _ = pretty_env_logger::try_init();
+
+        // the context is created in a deeper frame than the one executing the pending jobs
+        fn new_context(rt: &Runtime, depth: usize) -> Context {
+            let frame = [depth as u8; 4096];
+
+            if depth == 0 {
+                Context::new(rt)
+            } else {
+                let ctxt = new_context(rt, depth - 1);
+
+                std::hint::black_box(&frame);
+
+                ctxt
+            }
+        }
+
+        let rt = Runtime::new();
+        let ctxt = new_context(&rt, 16);
+
+        ctxt.eval::<_, ()>(
+            "var called = false; Promise.resolve().then(function () { called = true })",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+
+        assert_eq!(
+            ctxt.eval::<_, bool>("called", Eval::GLOBAL).unwrap(),
+            Some(true)
+        );
+    }
 }
@@ -0,0 +1,385 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use failure::Error;
+
+use crate::{ContextRef, ExtractValue, Local, Value};
+
+/// The limits applied when converting the Javascript values to the Rust types,
+/// so a script returning a pathological structure can't exhaust the host memory.
+///
+/// The elements and string bytes are counted for the whole conversion, not for each collection or string.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConversionLimits {
+    /// the maximum depth of the nested arrays and objects
+    pub max_depth: Option<usize>,
+    /// the maximum number of the array elements and object properties
+    pub max_elements: Option<usize>,
+    /// the maximum bytes of the strings in UTF-8, including the object keys
+    pub max_string_bytes: Option<usize>,
+}
+
+impl ConversionLimits {
+    /// Set the maximum depth of the nested arrays and objects.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Set the maximum number of the array elements and object properties.
+    pub fn with_max_elements(mut self, elements: usize) -> Self {
+        self.max_elements = Some(elements);
+        self
+    }
+
+    /// Set the maximum bytes of the strings in UTF-8.
+    pub fn with_max_string_bytes(mut self, bytes: usize) -> Self {
+        self.max_string_bytes = Some(bytes);
+        self
+    }
+}
+
+/// The limit exceeded by a conversion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConversionLimit {
+    Depth,
+    Elements,
+    StringBytes,
+}
+
+impl fmt::Display for ConversionLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ConversionLimit::Depth => "depth",
+            ConversionLimit::Elements => "elements",
+            ConversionLimit::StringBytes => "string bytes",
+        })
+    }
+}
+
+/// The conversion was aborted because it exceeded the `ConversionLimits`.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+#[fail(display = "conversion exceeds the {} limit of {}", limit, max)]
+pub struct ConversionLimitExceeded {
+    /// the exceeded limit
+    pub limit: ConversionLimit,
+    /// the value of the limit
+    pub max: usize,
+}
+
+/// The budget of the outermost conversion in progress.
+#[derive(Debug, Default)]
+pub(crate) struct Budget {
+    limits: Cell<ConversionLimits>,
+    active: Cell<bool>,
+    depth: Cell<usize>,
+    elements: Cell<usize>,
+    string_bytes: Cell<usize>,
+    failure: RefCell<Option<Error>>,
+}
+
+/// Leave the nested array or object when dropped.
+pub(crate) struct Nested<'a>(&'a Budget);
+
+/// Resume the suspended conversion when dropped.
+pub(crate) struct Suspended<'a> {
+    budget: &'a Budget,
+    active: bool,
+    depth: usize,
+    elements: usize,
+    string_bytes: usize,
+    failure: Option<Error>,
+}
+
+impl Drop for Suspended<'_> {
+    fn drop(&mut self) {
+        self.budget.active.set(self.active);
+        self.budget.depth.set(self.depth);
+        self.budget.elements.set(self.elements);
+        self.budget.string_bytes.set(self.string_bytes);
+        self.budget.failure.replace(self.failure.take());
+    }
+}
+
+impl Drop for Nested<'_> {
+    fn drop(&mut self) {
+        self.0.depth.set(self.0.depth.get() - 1);
+    }
+}
+
+impl Budget {
+    fn exceed(
+        &self,
+        limit: ConversionLimit,
+        max: Option<usize>,
+        used: usize,
+    ) -> Result<(), ConversionLimitExceeded> {
+        match max {
+            Some(max) if used > max => {
+                let err = ConversionLimitExceeded { limit, max };
+
+                self.fail(err);
+
+                Err(err)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record the reason why the conversion failed, which is returned by the outermost conversion.
+    pub fn fail<E: Into<Error>>(&self, err: E) {
+        let mut failure = self.failure.borrow_mut();
+
+        if failure.is_none() {
+            *failure = Some(err.into());
+        }
+    }
+
+    /// Suspend the conversion in progress, e.g. when a getter calls into a native function,
+    /// so the nested conversions have their own budget.
+    pub fn suspend(&self) -> Suspended {
+        Suspended {
+            budget: self,
+            active: self.active.replace(false),
+            depth: self.depth.get(),
+            elements: self.elements.get(),
+            string_bytes: self.string_bytes.get(),
+            failure: self.failure.replace(None),
+        }
+    }
+
+    /// Enter a nested array or object.
+    pub fn enter(&self) -> Result<Nested, ConversionLimitExceeded> {
+        let depth = self.depth.get() + 1;
+
+        self.depth.set(depth);
+
+        let nested = Nested(self);
+
+        self.exceed(ConversionLimit::Depth, self.limits.get().max_depth, depth)?;
+
+        Ok(nested)
+    }
+
+    /// Charge the array elements or object properties.
+    pub fn charge_elements(&self, n: usize) -> Result<(), ConversionLimitExceeded> {
+        let used = self.elements.get().saturating_add(n);
+
+        self.elements.set(used);
+        self.exceed(
+            ConversionLimit::Elements,
+            self.limits.get().max_elements,
+            used,
+        )
+    }
+
+    /// Charge the string bytes in UTF-8.
+    pub fn charge_string_bytes(&self, n: usize) -> Result<(), ConversionLimitExceeded> {
+        let used = self.string_bytes.get().saturating_add(n);
+
+        self.string_bytes.set(used);
+        self.exceed(
+            ConversionLimit::StringBytes,
+            self.limits.get().max_string_bytes,
+            used,
+        )
+    }
+
+    /// Check the string before converting it, the UTF-8 bytes are never less than its length in UTF-16.
+    pub fn check_string(&self, s: &Local<Value>) -> Result<(), ConversionLimitExceeded> {
+        let max = self.limits.get().max_string_bytes;
+
+        if max.is_none() || !s.is_string() {
+            return Ok(());
+        }
+
+        let len = s
+            .get_property("length")
+            .and_then(|len| len.to_index())
+            .unwrap_or_default() as usize;
+
+        self.exceed(
+            ConversionLimit::StringBytes,
+            max,
+            self.string_bytes.get().saturating_add(len),
+        )
+    }
+}
+
+impl ContextRef {
+    /// Set the limits applied when converting the values with `ExtractValue` or `to_msgpack`.
+    ///
+    /// The `Local::extract`, `eval` and `TypedFunction::call` return `ConversionLimitExceeded` if the limits exceeded,
+    /// the other conversions fail as the value can't be converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ConversionLimitExceeded, ConversionLimits, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.set_conversion_limits(ConversionLimits::default().with_max_elements(1000));
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, Vec<i32>>("[1, 2, 3]", Eval::GLOBAL).unwrap(),
+    ///     Some(vec![1, 2, 3])
+    /// );
+    ///
+    /// let err = ctxt
+    ///     .eval::<_, Vec<i32>>("new Array(1 << 30)", Eval::GLOBAL)
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "conversion exceeds the elements limit of 1000"
+    /// );
+    /// assert!(err.downcast_ref::<ConversionLimitExceeded>().is_some());
+    /// ```
+    pub fn set_conversion_limits(&self, limits: ConversionLimits) {
+        self.state::<Budget>().limits.set(limits)
+    }
+
+    /// Returns the limits applied when converting the values.
+    pub fn conversion_limits(&self) -> ConversionLimits {
+        self.state::<Budget>().limits.get()
+    }
+
+    /// Run the conversion with the budget, which is reset for the outermost conversion.
+    pub(crate) fn convert<T, F: FnOnce(&Budget) -> T>(&self, f: F) -> T {
+        let budget = self.state::<Budget>();
+
+        if budget.active.get() {
+            return f(budget);
+        }
+
+        budget.active.set(true);
+        budget.depth.set(0);
+        budget.elements.set(0);
+        budget.string_bytes.set(0);
+        budget.failure.replace(None);
+
+        let res = f(budget);
+
+        budget.active.set(false);
+
+        res
+    }
+
+    /// Extract the value with the budget, returns the error if the limits exceeded or the conversion was rejected.
+    pub(crate) fn extract_within_limits<V: ExtractValue>(
+        &self,
+        v: &Local<Value>,
+    ) -> Result<Option<V>, Error> {
+        let budget = self.state::<Budget>();
+        let outermost = !budget.active.get();
+
+        match self.convert(|_| V::extract_value(v)) {
+            None if outermost => budget.failure.take().map_or(Ok(None), Err),
+            res => Ok(res),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn conversion_limits() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(ctxt.conversion_limits(), ConversionLimits::default());
+
+        ctxt.set_conversion_limits(
+            ConversionLimits::default()
+                .with_max_depth(2)
+                .with_max_elements(8)
+                .with_max_string_bytes(16),
+        );
+
+        let extract =
+            |script: &str| -> Result<HashMap<String, Vec<String>>, ConversionLimitExceeded> {
+                let value = ctxt.eval_script(script, "<eval>", Eval::GLOBAL).unwrap();
+
+                value
+                    .extract()
+                    .map_err(|err| err.downcast::<ConversionLimitExceeded>().unwrap())
+            };
+
+        assert_eq!(
+            extract("({ a: ['x', 'y'], b: [] })").unwrap(),
+            vec![
+                ("a".to_owned(), vec!["x".to_owned(), "y".to_owned()]),
+                ("b".to_owned(), vec![])
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert_eq!(
+            extract("({ a: ['a', 'b', 'c', 'd'], b: ['e', 'f', 'g'] })").unwrap_err(),
+            ConversionLimitExceeded {
+                limit: ConversionLimit::Elements,
+                max: 8
+            }
+        );
+        assert_eq!(
+            extract("({ a: ['0123456789', '0123456789'] })").unwrap_err(),
+            ConversionLimitExceeded {
+                limit: ConversionLimit::StringBytes,
+                max: 16
+            }
+        );
+        // the oversized string is rejected before converting
+        assert_eq!(
+            ctxt.eval::<_, String>("'x'.repeat(1 << 24)", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ConversionLimitExceeded>()
+                .unwrap(),
+            ConversionLimitExceeded {
+                limit: ConversionLimit::StringBytes,
+                max: 16
+            }
+        );
+        assert_eq!(
+            ctxt.eval::<_, Vec<Vec<Vec<i32>>>>("[[[1]]]", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ConversionLimitExceeded>()
+                .unwrap(),
+            ConversionLimitExceeded {
+                limit: ConversionLimit::Depth,
+                max: 2
+            }
+        );
+        #[cfg(feature = "rmp")]
+        assert_eq!(
+            ctxt.eval_script("[[[1]]]", "<eval>", Eval::GLOBAL)
+                .unwrap()
+                .to_msgpack()
+                .unwrap_err()
+                .downcast::<ConversionLimitExceeded>()
+                .unwrap(),
+            ConversionLimitExceeded {
+                limit: ConversionLimit::Depth,
+                max: 2
+            }
+        );
+
+        // the budget is reset for each conversion
+        for _ in 0..3 {
+            assert_eq!(
+                ctxt.eval::<_, Vec<i32>>("[1, 2, 3, 4, 5, 6]", Eval::GLOBAL)
+                    .unwrap(),
+                Some(vec![1, 2, 3, 4, 5, 6])
+            );
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::cell::RefCell;
+use std::os::raw::c_int;
+use std::slice;
+use std::sync::Arc;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, CFunc, ContextRef, Local, NewValue, Prop, Value};
+
+/// How a native function handles the `new` operator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NewTarget {
+    /// the function must be called with `new`, calling it as a plain function throws a `TypeError`
+    Required,
+    /// the function could be called with or without `new`, like `Date` or `Error`
+    Optional,
+    /// the function can't be called with `new`, like the methods of the builtin objects
+    Forbidden,
+}
+
+type Handler = Arc<
+    dyn for<'a> Fn(&'a ContextRef, Option<&Value>, &[Value]) -> Result<Local<'a, Value>, Error>
+        + Send
+        + Sync,
+>;
+
+/// The native constructors of the context, indexed by the magic of the C function.
+#[derive(Default)]
+struct Constructors(RefCell<Vec<(String, Handler)>>);
+
+impl ContextRef {
+    /// Create a native function which checks how it is called, the errors of the handler are thrown to the scripts.
+    ///
+    /// For `NewTarget::Required` and `NewTarget::Optional`, the function receives the `new.target` as `this`,
+    /// or `None` if it is called as a plain function, and a `prototype` object is created for the instances.
+    /// For `NewTarget::Forbidden`, the function receives the `this` value like `new_c_function` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, NewTarget, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let ctor = ctxt
+    ///     .new_constructor("Point", 2, NewTarget::Required, |ctxt, new_target, args| {
+    ///         let obj = ctxt.new_object_from_target(new_target.unwrap())?;
+    ///
+    ///         obj.set_property("x", &args[0])?;
+    ///         obj.set_property("y", &args[1])?;
+    ///
+    ///         Ok(obj)
+    ///     })
+    ///     .unwrap();
+    ///
+    /// ctxt.global_object().set_property("Point", ctor).unwrap();
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval("var p = new Point(1, 2); p instanceof Point && p.x + p.y", Eval::GLOBAL).unwrap(),
+    ///     Some(3)
+    /// );
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("try { Point(1, 2) } catch (err) { err.message }", Eval::GLOBAL)
+    ///         .unwrap(),
+    ///     Some("must be called with new".to_owned())
+    /// );
+    /// ```
+    pub fn new_constructor<F>(
+        &self,
+        name: &str,
+        length: usize,
+        new_target: NewTarget,
+        handler: F,
+    ) -> Result<Local<Value>, Error>
+    where
+        F: for<'a> Fn(&'a ContextRef, Option<&Value>, &[Value]) -> Result<Local<'a, Value>, Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        unsafe extern "C" fn stub(
+            ctx: *mut ffi::JSContext,
+            this_val: ffi::JSValue,
+            argc: c_int,
+            argv: *mut ffi::JSValue,
+            magic: c_int,
+        ) -> ffi::JSValue {
+            let ctxt = ContextRef::from_ptr(ctx);
+
+            ctxt.catch_unwind(|| {
+                let (name, handler) =
+                    ctxt.state::<Constructors>().0.borrow()[magic as usize].clone();
+                let this = Value::from(this_val);
+                let this = this.check_undefined();
+                let args = slice::from_raw_parts(argv, argc as usize);
+                let args = &*(args as *const _ as *const [Value]);
+
+                trace!(
+                    "call constructor `{}` with {} args, this = {:?}",
+                    name,
+                    args.len(),
+                    this
+                );
+
+                ctxt.call_host(&name, args, || handler(ctxt, this, args).new_value(ctxt))
+            })
+        }
+
+        let cproto = match new_target {
+            NewTarget::Required => CFunc::ConstructorMagic,
+            NewTarget::Optional => CFunc::ConstructorOrFuncMagic,
+            NewTarget::Forbidden => CFunc::GenericMagic,
+        };
+        let magic = {
+            let mut ctors = self.state::<Constructors>().0.borrow_mut();
+
+            ctors.push((name.to_owned(), Arc::new(handler) as Handler));
+            ctors.len() - 1
+        };
+
+        let func = self.new_c_function_magic(stub, Some(name), length, cproto, magic as i32)?;
+
+        if new_target != NewTarget::Forbidden {
+            let proto = self.bind(self.new_object());
+
+            proto.define_property_value(
+                "constructor",
+                &func,
+                Prop::CONFIGURABLE | Prop::WRITABLE,
+            )?;
+            func.define_property_value("prototype", proto, Prop::WRITABLE)?;
+        }
+
+        Ok(func)
+    }
+
+    /// Create an object for the `new.target` of a native constructor,
+    /// its prototype is the `prototype` property of the `new.target`, so the subclasses are supported.
+    pub fn new_object_from_target(&self, new_target: &Value) -> Result<Local<Value>, Error> {
+        match self.get_property(new_target, "prototype") {
+            Some(proto) if proto.is_object() => Ok(self.bind(self.new_object_proto(&proto))),
+            _ => Ok(self.bind(self.new_object())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn new_target() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let global = ctxt.global_object();
+
+        global
+            .set_property(
+                "Point",
+                ctxt.new_constructor("Point", 1, NewTarget::Required, |ctxt, new_target, args| {
+                    let obj = ctxt.new_object_from_target(new_target.unwrap())?;
+
+                    obj.set_property("x", &args[0])?;
+
+                    Ok(obj)
+                })
+                .unwrap(),
+            )
+            .unwrap();
+        global
+            .set_property(
+                "Celsius",
+                ctxt.new_constructor(
+                    "Celsius",
+                    1,
+                    NewTarget::Optional,
+                    |ctxt, new_target, args| {
+                        let s = format!(
+                            "{}{}",
+                            ctxt.to_float64(&args[0]).unwrap_or_default(),
+                            if new_target.is_some() { " (new)" } else { "" }
+                        );
+
+                        Ok(ctxt.bind(s.new_value(ctxt)))
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        global
+            .set_property(
+                "reset",
+                ctxt.new_constructor("reset", 0, NewTarget::Forbidden, |ctxt, this, _args| {
+                    Ok(ctxt.bind(this.is_some().new_value(ctxt)))
+                })
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+class Point3D extends Point {}
+
+const p = new Point3D(1);
+
+[Point.name, Point.length, p instanceof Point3D, p instanceof Point, p.x].join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("Point,1,true,true,1".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { Point(1) } catch (err) { `${err.name}: ${err.message}` }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("TypeError: must be called with new".to_owned())
+        );
+
+        // the optional `new` is visible to the constructor
+        assert_eq!(
+            ctxt.eval::<_, String>("Celsius(1) + ', ' + new Celsius(2)", Eval::GLOBAL)
+                .unwrap(),
+            Some("1, 2 (new)".to_owned())
+        );
+
+        // the plain functions reject construction
+        let hello = ctxt
+            .new_c_function(|_ctxt, _this, _args| "hello", Some("hello"), 0)
+            .unwrap();
+
+        global.set_property("hello", hello).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+[() => new reset(), () => new hello(), () => reset.call({})]
+    .map(f => { try { return f() } catch (err) { return err.message } })
+    .join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("not a constructor,not a constructor,true".to_owned())
+        );
+    }
+}
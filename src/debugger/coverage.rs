@@ -0,0 +1,410 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::os::raw::c_int;
+
+use foreign_types::ForeignTypeRef;
+
+use super::json::Json;
+use crate::{ffi, ContextRef, RuntimeRef};
+
+/// The coverage of a function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionCoverage {
+    /// the name of the function, empty for the anonymous function
+    pub name: String,
+    /// the line where the function is defined
+    pub line: u32,
+    /// the times the function was called
+    pub hits: usize,
+}
+
+/// The coverage of a source file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileCoverage {
+    functions: Vec<FunctionCoverage>,
+    lines: BTreeMap<u32, usize>,
+}
+
+impl FileCoverage {
+    /// The functions defined in the file, in the order of definition.
+    pub fn functions(&self) -> &[FunctionCoverage] {
+        &self.functions
+    }
+
+    /// The executable lines of the file and the times they were executed.
+    pub fn lines(&self) -> &BTreeMap<u32, usize> {
+        &self.lines
+    }
+
+    /// The times the line was executed, `None` if the line is not executable.
+    pub fn line_hits(&self, line: u32) -> Option<usize> {
+        self.lines.get(&line).cloned()
+    }
+
+    fn function_mut(&mut self, name: &str, line: u32) -> Option<&mut FunctionCoverage> {
+        self.functions
+            .iter_mut()
+            .find(|func| func.name == name && func.line == line)
+    }
+}
+
+/// The coverage of the evaluated scripts, grouped by the source file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+    files: BTreeMap<String, FileCoverage>,
+}
+
+impl Coverage {
+    /// The source files and their coverage, ordered by the filename.
+    pub fn files(&self) -> impl Iterator<Item = (&str, &FileCoverage)> {
+        self.files.iter().map(|(file, cov)| (file.as_str(), cov))
+    }
+
+    /// The coverage of the source file.
+    pub fn file(&self, filename: &str) -> Option<&FileCoverage> {
+        self.files.get(filename)
+    }
+
+    /// Write the coverage in the [lcov](http://ltp.sourceforge.net/coverage/lcov/geninfo.1.php) tracefile format.
+    pub fn write_lcov<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for (file, cov) in &self.files {
+            writeln!(w, "SF:{}", file)?;
+
+            for func in &cov.functions {
+                writeln!(w, "FN:{},{}", func.line, lcov_name(func))?;
+            }
+            for func in &cov.functions {
+                writeln!(w, "FNDA:{},{}", func.hits, lcov_name(func))?;
+            }
+
+            writeln!(w, "FNF:{}", cov.functions.len())?;
+            writeln!(
+                w,
+                "FNH:{}",
+                cov.functions.iter().filter(|func| func.hits > 0).count()
+            )?;
+
+            for (line, hits) in &cov.lines {
+                writeln!(w, "DA:{},{}", line, hits)?;
+            }
+
+            writeln!(w, "LF:{}", cov.lines.len())?;
+            writeln!(
+                w,
+                "LH:{}",
+                cov.lines.values().filter(|&&hits| hits > 0).count()
+            )?;
+            writeln!(w, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the coverage as a JSON object keyed by the filename,
+    /// each file has the `functions` with `name`, `line` and `hits`, and the `lines` with `line` and `hits`.
+    pub fn write_json<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let files = Json::object(self.files.iter().map(|(file, cov)| {
+            (
+                file.as_str(),
+                Json::object(vec![
+                    (
+                        "functions",
+                        cov.functions
+                            .iter()
+                            .map(|func| {
+                                Json::object(vec![
+                                    ("name", Json::from(func.name.as_str())),
+                                    ("line", func.line.into()),
+                                    ("hits", func.hits.into()),
+                                ])
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                    (
+                        "lines",
+                        cov.lines
+                            .iter()
+                            .map(|(&line, &hits)| {
+                                Json::object(vec![
+                                    ("line", Json::from(line)),
+                                    ("hits", hits.into()),
+                                ])
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                ]),
+            )
+        }));
+
+        write!(w, "{}", files)
+    }
+
+    fn file_mut(&mut self, filename: String) -> &mut FileCoverage {
+        self.files.entry(filename).or_default()
+    }
+}
+
+/// The function name in the lcov tracefile, which is unique in the file.
+fn lcov_name(func: &FunctionCoverage) -> String {
+    if func.name.is_empty() {
+        format!("(anonymous):{}", func.line)
+    } else {
+        format!("{}:{}", func.name, func.line)
+    }
+}
+
+#[derive(Default)]
+struct Collecting(RefCell<Option<Coverage>>);
+
+impl RuntimeRef {
+    /// Start collecting the coverage of the scripts.
+    ///
+    /// The functions and lines of a script are recorded once its top-level code starts,
+    /// so the functions never called are reported as uncovered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// rt.start_coverage();
+    ///
+    /// ctxt.eval_script("function used() {}\nfunction unused() {}\nused();", "test.js", Eval::GLOBAL)
+    ///     .unwrap();
+    ///
+    /// let coverage = rt.stop_coverage().unwrap();
+    /// let file = coverage.file("test.js").unwrap();
+    ///
+    /// assert_eq!(file.functions()[1].name, "used");
+    /// assert_eq!(file.functions()[1].hits, 1);
+    /// assert_eq!(file.functions()[2].name, "unused");
+    /// assert_eq!(file.functions()[2].hits, 0);
+    ///
+    /// let mut lcov = vec![];
+    ///
+    /// coverage.write_lcov(&mut lcov).unwrap();
+    /// ```
+    pub fn start_coverage(&self) {
+        *self.state::<Collecting>().0.borrow_mut() = Some(Coverage::default());
+
+        self.update_debugger_hook();
+    }
+
+    /// Returns the coverage collected so far, and continue collecting from scratch.
+    pub fn take_coverage(&self) -> Option<Coverage> {
+        self.state::<Collecting>()
+            .0
+            .borrow_mut()
+            .as_mut()
+            .map(|coverage| Coverage {
+                files: coverage
+                    .files
+                    .iter_mut()
+                    .map(|(file, cov)| {
+                        let taken = cov.clone();
+
+                        cov.functions.iter_mut().for_each(|func| func.hits = 0);
+                        cov.lines.values_mut().for_each(|hits| *hits = 0);
+
+                        (file.clone(), taken)
+                    })
+                    .collect(),
+            })
+    }
+
+    /// Stop collecting and returns the collected coverage.
+    pub fn stop_coverage(&self) -> Option<Coverage> {
+        let coverage = self.state::<Collecting>().0.borrow_mut().take();
+
+        self.update_debugger_hook();
+
+        coverage
+    }
+}
+
+pub(crate) fn is_collecting(rt: &RuntimeRef) -> bool {
+    rt.state::<Collecting>().0.borrow().is_some()
+}
+
+pub(crate) fn hit(ctxt: &ContextRef, event: c_int, filename: ffi::JSAtom, line: c_int) {
+    let collecting = ctxt.runtime().state::<Collecting>();
+    let mut coverage = collecting.0.borrow_mut();
+    let coverage = match coverage.as_mut() {
+        Some(coverage) if filename != 0 && line >= 0 => coverage,
+        _ => return,
+    };
+    let file = coverage.file_mut(ctxt.clone_atom(filename).to_string());
+
+    if event == ffi::JS_DEBUGGER_EVENT_ENTER {
+        enter(ctxt, file);
+    }
+
+    *file.lines.entry(line as u32).or_default() += 1;
+}
+
+/// Count the call of the innermost function, and record the functions defined in it at the first call.
+fn enter(ctxt: &ContextRef, file: &mut FileCoverage) {
+    let mut info = ffi::JSDebuggerFrame::default();
+
+    if unsafe { ffi::JS_GetFrameInfo(ctxt.as_ptr(), 0, &mut info) } <= 0 {
+        return;
+    }
+
+    let _ = ctxt.bind_atom(info.filename);
+    let name = ctxt.bind_atom(info.function_name);
+    let name = if *name == 0 {
+        String::new()
+    } else {
+        name.to_string()
+    };
+    let line = info.function_line.max(0) as u32;
+
+    if file.function_mut(&name, line).is_none() {
+        record(ctxt, file);
+    }
+
+    if let Some(func) = file.function_mut(&name, line) {
+        func.hits += 1;
+    }
+}
+
+fn record(ctxt: &ContextRef, file: &mut FileCoverage) {
+    let funcs = match ctxt
+        .bind(unsafe { ffi::JS_GetFrameFunctions(ctxt.as_ptr(), 0) })
+        .ok()
+    {
+        Ok(funcs) => funcs,
+        Err(err) => {
+            warn!("fail to get functions of frame, {}", err);
+            return;
+        }
+    };
+    let len = funcs
+        .get_property("length")
+        .and_then(|v| v.to_index())
+        .unwrap_or_default();
+
+    for func in (0..len as u32).flat_map(|i| funcs.get_property(i)) {
+        let name = func
+            .get_property("name")
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let line = func
+            .get_property("lineNumber")
+            .and_then(|v| v.as_int())
+            .unwrap_or_default()
+            .max(0) as u32;
+
+        if file.function_mut(&name, line).is_none() {
+            file.functions.push(FunctionCoverage {
+                name,
+                line,
+                hits: 0,
+            });
+        }
+
+        if let Some(lines) = func.get_property("lines") {
+            let len = lines
+                .get_property("length")
+                .and_then(|v| v.to_index())
+                .unwrap_or_default();
+
+            for line in (0..len as u32)
+                .flat_map(|i| lines.get_property(i))
+                .flat_map(|v| v.as_int())
+            {
+                file.lines.entry(line.max(0) as u32).or_default();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert!(rt.stop_coverage().is_none());
+
+        rt.start_coverage();
+
+        ctxt.eval_script(
+            "function add(a, b) {\n return a + b;\n}\nfunction unused(n) {\n return n;\n}\n[1, 2].map(v => add(v, 1));\nif (add(0, 0)) {\n unused(1);\n}",
+            "test.js",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let coverage = rt.take_coverage().unwrap();
+        let file = coverage.file("test.js").unwrap();
+
+        assert_eq!(
+            file.functions()
+                .iter()
+                .map(|func| (func.name.as_str(), func.line, func.hits))
+                .collect::<Vec<_>>(),
+            vec![
+                ("<eval>", 1, 1),
+                ("add", 1, 3),
+                ("unused", 4, 0),
+                ("", 7, 2)
+            ]
+        );
+        assert_eq!(file.line_hits(2), Some(3));
+        assert_eq!(file.line_hits(5), Some(0));
+        assert_eq!(file.line_hits(9), Some(0));
+        assert_eq!(file.line_hits(3), None);
+
+        let mut lcov = vec![];
+
+        coverage.write_lcov(&mut lcov).unwrap();
+
+        let lcov = String::from_utf8(lcov).unwrap();
+
+        assert!(lcov.starts_with("SF:test.js\n"));
+        assert!(lcov.contains("FN:4,unused:4\n"));
+        assert!(lcov.contains("FNDA:0,unused:4\n"));
+        assert!(lcov.contains("FNF:4\nFNH:3\n"));
+        assert!(lcov.contains("DA:2,3\n"));
+        assert!(lcov.contains("DA:5,0\n"));
+        assert!(lcov.ends_with("end_of_record\n"));
+
+        let mut json = vec![];
+
+        coverage.write_json(&mut json).unwrap();
+
+        let json = Json::parse(&String::from_utf8(json).unwrap()).unwrap();
+        let file = json.get("test.js").unwrap();
+
+        assert_eq!(
+            file.get("functions")
+                .and_then(Json::as_array)
+                .map(|funcs| funcs.len()),
+            Some(4)
+        );
+
+        // the counts are reset after taken
+        ctxt.eval_script("add(1, 2)", "other.js", Eval::GLOBAL)
+            .unwrap();
+
+        let coverage = rt.stop_coverage().unwrap();
+        let file = coverage.file("test.js").unwrap();
+
+        assert_eq!(file.functions()[1].hits, 1);
+        assert_eq!(file.functions()[0].hits, 0);
+        assert_eq!(file.line_hits(2), Some(1));
+        assert!(coverage.file("other.js").is_some());
+    }
+}
@@ -0,0 +1,861 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use failure::Error;
+
+use super::json::Json;
+use super::{DebugHandler, Debugger, PauseReason, Paused, Resume};
+use crate::{Local, Value, UNDEFINED};
+
+/// The scripts are running in a single thread.
+const THREAD_ID: i64 = 1;
+
+/// The Debug Adapter Protocol server of the debugger.
+///
+/// The adapter serves the requests from the client on its own thread,
+/// and forwards the requests about the paused execution to the `AdapterHandler`,
+/// which should be attached to the runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::TcpListener;
+/// use std::thread;
+///
+/// use qjs::{Context, DebugAdapter, Debugger, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let debugger = Debugger::new();
+/// let (adapter, handler) = DebugAdapter::new(&debugger);
+/// let ready = adapter.ready();
+///
+/// rt.attach_debugger(&debugger, handler);
+///
+/// thread::spawn(move || {
+///     let (stream, _) = TcpListener::bind("127.0.0.1:4711").unwrap().accept().unwrap();
+///
+///     adapter.serve(stream.try_clone().unwrap(), stream).unwrap();
+/// });
+///
+/// // wait until the client finished the configuration, e.g. set the breakpoints
+/// ready.wait();
+///
+/// ctxt.eval_file("app.js", Eval::GLOBAL).unwrap();
+/// ```
+pub struct DebugAdapter {
+    debugger: Debugger,
+    commands: Sender<Json>,
+    messages: Sender<Json>,
+    outgoing: Receiver<Json>,
+    paused: Arc<AtomicBool>,
+    ready: Ready,
+}
+
+/// The `DebugHandler` forwards the requests of the `DebugAdapter` to the paused execution.
+pub struct AdapterHandler {
+    commands: Receiver<Json>,
+    messages: Sender<Json>,
+    paused: Arc<AtomicBool>,
+}
+
+/// Signaled when the client finished the configuration or disconnected.
+#[derive(Clone, Default)]
+pub struct Ready(Arc<(Mutex<bool>, Condvar)>);
+
+impl Ready {
+    /// Block until the client is ready.
+    pub fn wait(&self) {
+        let (ref lock, ref cond) = *self.0;
+        let mut ready = lock.lock().unwrap();
+
+        while !*ready {
+            ready = cond.wait(ready).unwrap();
+        }
+    }
+
+    /// Block until the client is ready or the timeout elapsed, returns `true` if the client is ready.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (ref lock, ref cond) = *self.0;
+        let ready = lock.lock().unwrap();
+
+        *cond
+            .wait_timeout_while(ready, timeout, |ready| !*ready)
+            .unwrap()
+            .0
+    }
+
+    fn set(&self) {
+        let (ref lock, ref cond) = *self.0;
+
+        *lock.lock().unwrap() = true;
+        cond.notify_all();
+    }
+}
+
+impl DebugAdapter {
+    /// Create an adapter of the debugger, and the handler which should be attached to the runtime.
+    pub fn new(debugger: &Debugger) -> (DebugAdapter, AdapterHandler) {
+        let (commands, commands_rx) = mpsc::channel();
+        let (messages, outgoing) = mpsc::channel();
+        let paused = Arc::new(AtomicBool::new(false));
+
+        (
+            DebugAdapter {
+                debugger: debugger.clone(),
+                commands,
+                messages: messages.clone(),
+                outgoing,
+                paused: paused.clone(),
+                ready: Ready::default(),
+            },
+            AdapterHandler {
+                commands: commands_rx,
+                messages,
+                paused,
+            },
+        )
+    }
+
+    /// The signal of the client finished the configuration.
+    pub fn ready(&self) -> Ready {
+        self.ready.clone()
+    }
+
+    /// Serve the client until it disconnected.
+    pub fn serve<R, W>(self, input: R, mut output: W) -> Result<(), Error>
+    where
+        R: Read,
+        W: Write + Send + 'static,
+    {
+        let DebugAdapter {
+            debugger,
+            commands,
+            messages,
+            outgoing,
+            paused,
+            ready,
+        } = self;
+
+        thread::spawn(move || {
+            for (seq, message) in outgoing.into_iter().enumerate() {
+                if let Err(err) = write_message(&mut output, seq as i64 + 1, message) {
+                    warn!("fail to write DAP message, {}", err);
+                    break;
+                }
+            }
+        });
+
+        let mut input = BufReader::new(input);
+
+        while let Some(request) = read_message(&mut input)? {
+            let command = request
+                .get("command")
+                .and_then(Json::as_str)
+                .unwrap_or_default()
+                .to_owned();
+            let args = request.get("arguments").cloned().unwrap_or(Json::Null);
+
+            trace!("DAP request `{}`: {}", command, args);
+
+            let res = match command.as_str() {
+                "initialize" => {
+                    messages.send(response(&request, Ok(capabilities())))?;
+                    messages.send(event("initialized", Json::Null))?;
+                    continue;
+                }
+                "launch" | "attach" => Ok(Json::Null),
+                "setBreakpoints" => {
+                    let source = args.get("source");
+                    let file = source
+                        .and_then(|s| s.get("path"))
+                        .or_else(|| source.and_then(|s| s.get("name")))
+                        .and_then(Json::as_str)
+                        .unwrap_or_default();
+                    let lines = args
+                        .get("breakpoints")
+                        .and_then(Json::as_array)
+                        .unwrap_or_default()
+                        .iter()
+                        .flat_map(|bp| bp.get("line").and_then(Json::as_i64))
+                        .map(|line| line as u32)
+                        .collect::<Vec<_>>();
+
+                    debugger.set_breakpoints(file, lines.iter().cloned());
+
+                    Ok(Json::object(vec![(
+                        "breakpoints",
+                        lines
+                            .into_iter()
+                            .map(|line| {
+                                Json::object(vec![
+                                    ("verified", Json::from(true)),
+                                    ("line", line.into()),
+                                ])
+                            })
+                            .collect::<Vec<_>>(),
+                    )]))
+                }
+                "setExceptionBreakpoints" => {
+                    debugger.set_pause_on_exceptions(
+                        args.get("filters")
+                            .and_then(Json::as_array)
+                            .unwrap_or_default()
+                            .iter()
+                            .any(|filter| filter.as_str() == Some("all")),
+                    );
+
+                    Ok(Json::Null)
+                }
+                "configurationDone" => {
+                    messages.send(response(&request, Ok(Json::Null)))?;
+                    ready.set();
+                    continue;
+                }
+                "threads" => Ok(Json::object(vec![(
+                    "threads",
+                    vec![Json::object(vec![
+                        ("id", Json::from(THREAD_ID)),
+                        ("name", "main".into()),
+                    ])],
+                )])),
+                "pause" => {
+                    debugger.pause();
+
+                    Ok(Json::Null)
+                }
+                "disconnect" => {
+                    debugger.clear_breakpoints();
+                    debugger.set_pause_on_exceptions(false);
+                    ready.set();
+
+                    if paused.load(Ordering::SeqCst) {
+                        commands.send(request)?;
+                    } else {
+                        messages.send(response(&request, Ok(Json::Null)))?;
+                    }
+
+                    break;
+                }
+                "stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next"
+                | "stepIn" | "stepOut" => {
+                    if paused.load(Ordering::SeqCst) {
+                        commands.send(request)?;
+                        continue;
+                    }
+
+                    Err("the execution is not paused".to_owned())
+                }
+                _ => Err(format!("unsupported request `{}`", command)),
+            };
+
+            messages.send(response(&request, res))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AdapterHandler {
+    fn drop(&mut self) {
+        let _ = self.messages.send(event("terminated", Json::Null));
+    }
+}
+
+impl DebugHandler for AdapterHandler {
+    fn paused(&mut self, paused: &Paused) -> Resume {
+        let mut refs = vec![];
+        let mut body = vec![
+            ("reason", Json::from(stopped_reason(paused.reason()))),
+            ("threadId", THREAD_ID.into()),
+            ("allThreadsStopped", true.into()),
+        ];
+
+        if let Some(exception) = paused.exception() {
+            body.push((
+                "text",
+                describe(&paused.context().clone_value(exception)).into(),
+            ));
+        }
+
+        self.paused.store(true, Ordering::SeqCst);
+
+        if self
+            .messages
+            .send(event("stopped", Json::object(body)))
+            .is_err()
+        {
+            self.paused.store(false, Ordering::SeqCst);
+
+            return Resume::Continue;
+        }
+
+        loop {
+            let request = match self.commands.recv() {
+                Ok(request) => request,
+                Err(_) => {
+                    self.paused.store(false, Ordering::SeqCst);
+
+                    return Resume::Continue;
+                }
+            };
+            let command = request
+                .get("command")
+                .and_then(Json::as_str)
+                .unwrap_or_default();
+            let args = request.get("arguments").cloned().unwrap_or(Json::Null);
+
+            let resume = match command {
+                "continue" => Some(Resume::Continue),
+                "next" => Some(Resume::StepOver),
+                "stepIn" => Some(Resume::StepIn),
+                "stepOut" => Some(Resume::StepOut),
+                "disconnect" => Some(
+                    if args.get("terminateDebuggee").and_then(Json::as_bool) == Some(true) {
+                        Resume::Terminate
+                    } else {
+                        Resume::Continue
+                    },
+                ),
+                _ => None,
+            };
+
+            if let Some(resume) = resume {
+                let body = if command == "continue" {
+                    Json::object(vec![("allThreadsContinued", true)])
+                } else {
+                    Json::Null
+                };
+
+                self.paused.store(false, Ordering::SeqCst);
+                let _ = self.messages.send(response(&request, Ok(body)));
+
+                return resume;
+            }
+
+            let res = match command {
+                "stackTrace" => stack_trace(paused),
+                "scopes" => scopes(paused, &args, &mut refs),
+                "variables" => variables(paused, &args, &mut refs),
+                "evaluate" => evaluate(paused, &args, &mut refs),
+                _ => Err(format!("unsupported request `{}`", command)),
+            };
+
+            let _ = self.messages.send(response(&request, res));
+        }
+    }
+}
+
+fn stopped_reason(reason: PauseReason) -> &'static str {
+    match reason {
+        PauseReason::Breakpoint => "breakpoint",
+        PauseReason::Step => "step",
+        PauseReason::Exception => "exception",
+        PauseReason::Pause => "pause",
+    }
+}
+
+fn capabilities() -> Json {
+    Json::object(vec![
+        ("supportsConfigurationDoneRequest", Json::from(true)),
+        ("supportsTerminateRequest", false.into()),
+        (
+            "exceptionBreakpointFilters",
+            vec![Json::object(vec![
+                ("filter", Json::from("all")),
+                ("label", "All Exceptions".into()),
+                ("default", false.into()),
+            ])]
+            .into(),
+        ),
+    ])
+}
+
+fn stack_trace(paused: &Paused) -> Result<Json, String> {
+    let frames = paused.stack_frames().map_err(|err| err.to_string())?;
+    let total = frames.len();
+
+    Ok(Json::object(vec![
+        (
+            "stackFrames",
+            Json::from(
+                frames
+                    .into_iter()
+                    .enumerate()
+                    .map(|(level, frame)| {
+                        let mut members = vec![
+                            ("id", Json::from(level)),
+                            (
+                                "name",
+                                if frame.function.is_empty() {
+                                    "<anonymous>".into()
+                                } else {
+                                    frame.function.into()
+                                },
+                            ),
+                            ("line", frame.line.unwrap_or_default().into()),
+                            ("column", 1u32.into()),
+                        ];
+
+                        match frame.file {
+                            Some(file) => members.push((
+                                "source",
+                                Json::object(vec![
+                                    (
+                                        "name",
+                                        Path::new(&file)
+                                            .file_name()
+                                            .map(|name| name.to_string_lossy().to_string())
+                                            .unwrap_or_else(|| file.clone()),
+                                    ),
+                                    ("path", file),
+                                ]),
+                            )),
+                            None => members.push(("presentationHint", "subtle".into())),
+                        }
+
+                        Json::object(members)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        ("totalFrames", total.into()),
+    ]))
+}
+
+fn scopes<'a>(
+    paused: &Paused<'a>,
+    args: &Json,
+    refs: &mut Vec<Local<'a, Value>>,
+) -> Result<Json, String> {
+    let level = args.get("frameId").and_then(Json::as_i64).unwrap_or(0) as usize;
+    let mut scopes = vec![];
+
+    for &(name, closure) in &[("Locals", false), ("Closure", true)] {
+        let scope = if closure {
+            paused.closure(level)
+        } else {
+            paused.locals(level)
+        }
+        .map_err(|err| err.to_string())?;
+
+        if let Some(scope) = scope {
+            scopes.push(Json::object(vec![
+                ("name", Json::from(name)),
+                ("variablesReference", reference(refs, scope).into()),
+                ("expensive", false.into()),
+            ]));
+        }
+    }
+
+    scopes.push(Json::object(vec![
+        ("name", Json::from("Global")),
+        (
+            "variablesReference",
+            reference(refs, paused.context().global_object()).into(),
+        ),
+        ("expensive", true.into()),
+    ]));
+
+    Ok(Json::object(vec![("scopes", scopes)]))
+}
+
+fn variables<'a>(
+    paused: &Paused<'a>,
+    args: &Json,
+    refs: &mut Vec<Local<'a, Value>>,
+) -> Result<Json, String> {
+    let ctxt = paused.context();
+    let obj = args
+        .get("variablesReference")
+        .and_then(Json::as_i64)
+        .and_then(|id| refs.get(id as usize - 1))
+        .cloned()
+        .ok_or_else(|| "invalid variables reference".to_owned())?;
+    let names = obj
+        .get_own_property_names()
+        .map_err(|err| err.to_string())?
+        .unwrap_or_default();
+    let mut variables = vec![];
+
+    for name in names {
+        let value = ctxt
+            .get_property(&obj, &name)
+            .unwrap_or_else(|| ctxt.bind(UNDEFINED));
+
+        variables.push(Json::object(vec![
+            ("name", Json::from(name.to_string())),
+            ("value", describe(&value).into()),
+            ("variablesReference", reference(refs, value).into()),
+        ]));
+    }
+
+    Ok(Json::object(vec![("variables", variables)]))
+}
+
+fn evaluate<'a>(
+    paused: &Paused<'a>,
+    args: &Json,
+    refs: &mut Vec<Local<'a, Value>>,
+) -> Result<Json, String> {
+    let expr = args
+        .get("expression")
+        .and_then(Json::as_str)
+        .unwrap_or_default();
+    let value = paused.evaluate(expr).map_err(|err| err.to_string())?;
+
+    Ok(Json::object(vec![
+        ("result", Json::from(describe(&value))),
+        ("variablesReference", reference(refs, value).into()),
+    ]))
+}
+
+/// The variables reference of the objects, `0` for the primitive values.
+fn reference<'a>(refs: &mut Vec<Local<'a, Value>>, value: Local<'a, Value>) -> usize {
+    if value.is_object() {
+        refs.push(value);
+        refs.len()
+    } else {
+        0
+    }
+}
+
+/// The short description of the value shown by the client.
+fn describe(value: &Local<Value>) -> String {
+    if value.is_string() {
+        format!("{:?}", value.to_string())
+    } else if value.is_symbol() {
+        "Symbol()".to_owned()
+    } else if value.is_function() {
+        format!(
+            "function {}()",
+            value
+                .get_property("name")
+                .map(|name| name.to_string())
+                .unwrap_or_default()
+        )
+    } else if value.is_error() {
+        value.to_string()
+    } else if value.is_array().unwrap_or(false) {
+        format!(
+            "Array({})",
+            value
+                .get_property("length")
+                .and_then(|len| len.to_index())
+                .unwrap_or_default()
+        )
+    } else if value.is_object() {
+        value
+            .get_property("constructor")
+            .and_then(|ctor| value.context().get_property(&ctor, "name"))
+            .filter(|name| name.is_string())
+            .map_or_else(|| "Object".to_owned(), |name| name.to_string())
+    } else {
+        value.to_string()
+    }
+}
+
+fn response(request: &Json, res: Result<Json, String>) -> Json {
+    let mut members = vec![
+        ("type", Json::from("response")),
+        (
+            "request_seq",
+            request.get("seq").cloned().unwrap_or(Json::Null),
+        ),
+        (
+            "command",
+            request.get("command").cloned().unwrap_or(Json::Null),
+        ),
+        ("success", res.is_ok().into()),
+    ];
+
+    match res {
+        Ok(Json::Null) => {}
+        Ok(body) => members.push(("body", body)),
+        Err(message) => members.push(("message", message.into())),
+    }
+
+    Json::object(members)
+}
+
+fn event(name: &str, body: Json) -> Json {
+    let mut members = vec![("type", Json::from("event")), ("event", name.into())];
+
+    if body != Json::Null {
+        members.push(("body", body));
+    }
+
+    Json::object(members)
+}
+
+/// Read a message with the `Content-Length` header, returns `None` at the end of the input.
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Json>, Error> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+        } else if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = Some(len.trim().parse::<usize>()?);
+        }
+    }
+
+    let mut buf = vec![0; content_length.unwrap_or_default()];
+
+    input.read_exact(&mut buf)?;
+
+    Json::parse(&String::from_utf8(buf)?).map(Some)
+}
+
+fn write_message<W: Write>(output: &mut W, seq: i64, message: Json) -> Result<(), Error> {
+    let message = match message {
+        Json::Object(mut members) => {
+            members.insert(0, ("seq".to_owned(), seq.into()));
+            Json::Object(members)
+        }
+        message => message,
+    }
+    .to_string();
+
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        message.len(),
+        message
+    )?;
+    output.flush()?;
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::collections::VecDeque;
+    use std::os::unix::net::UnixStream;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    struct Client {
+        input: BufReader<UnixStream>,
+        output: UnixStream,
+        seq: i64,
+        events: VecDeque<Json>,
+    }
+
+    impl Client {
+        fn request(&mut self, command: &str, args: Json) -> Json {
+            self.seq += 1;
+
+            write_message(
+                &mut self.output,
+                self.seq,
+                Json::object(vec![
+                    ("type", Json::from("request")),
+                    ("command", command.into()),
+                    ("arguments", args),
+                ]),
+            )
+            .unwrap();
+
+            loop {
+                let message = self.recv();
+
+                if message.get("type").and_then(Json::as_str) == Some("response") {
+                    assert_eq!(message.get("command").and_then(Json::as_str), Some(command));
+
+                    return message;
+                }
+
+                self.events.push_back(message);
+            }
+        }
+
+        fn recv(&mut self) -> Json {
+            read_message(&mut self.input).unwrap().unwrap()
+        }
+
+        fn wait_event(&mut self, name: &str) -> Json {
+            loop {
+                let message = self.events.pop_front().unwrap_or_else(|| self.recv());
+
+                if message.get("event").and_then(Json::as_str) == Some(name) {
+                    return message.get("body").cloned().unwrap_or(Json::Null);
+                }
+            }
+        }
+    }
+
+    fn body<'a>(res: &'a Json, path: &[&str]) -> &'a Json {
+        path.iter()
+            .fold(res.get("body").unwrap(), |json, key| match json {
+                Json::Array(items) => &items[key.parse::<usize>().unwrap()],
+                _ => json.get(key).unwrap(),
+            })
+    }
+
+    #[test]
+    fn debug_adapter() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (adapter, handler) = DebugAdapter::new(&debugger);
+        let ready = adapter.ready();
+        let (client, server) = UnixStream::pair().unwrap();
+
+        rt.attach_debugger(&debugger, handler);
+
+        thread::spawn(move || {
+            adapter.serve(server.try_clone().unwrap(), server).unwrap();
+        });
+
+        let client = thread::spawn(move || {
+            let mut client = Client {
+                input: BufReader::new(client.try_clone().unwrap()),
+                output: client,
+                seq: 0,
+                events: VecDeque::new(),
+            };
+            let mut results = vec![];
+
+            let res = client.request("initialize", Json::object(vec![("adapterID", "qjs")]));
+
+            assert_eq!(
+                body(&res, &["supportsConfigurationDoneRequest"]),
+                &Json::Bool(true)
+            );
+            client.wait_event("initialized");
+
+            let res = client.request(
+                "setBreakpoints",
+                Json::object(vec![
+                    (
+                        "source",
+                        Json::object(vec![("path", "/src/app.js"), ("name", "app.js")]),
+                    ),
+                    (
+                        "breakpoints",
+                        vec![Json::object(vec![("line", 3u32)])].into(),
+                    ),
+                ]),
+            );
+
+            assert_eq!(
+                body(&res, &["breakpoints", "0", "verified"]),
+                &Json::Bool(true)
+            );
+
+            let res = client.request("stackTrace", Json::object(vec![("threadId", THREAD_ID)]));
+
+            assert_eq!(res.get("success"), Some(&Json::Bool(false)));
+
+            client.request("configurationDone", Json::Null);
+
+            let stopped = client.wait_event("stopped");
+
+            assert_eq!(
+                stopped.get("reason").and_then(Json::as_str),
+                Some("breakpoint")
+            );
+
+            let res = client.request("stackTrace", Json::object(vec![("threadId", THREAD_ID)]));
+
+            results.push(format!(
+                "{}@{}:{}",
+                body(&res, &["stackFrames", "0", "name"]).as_str().unwrap(),
+                body(&res, &["stackFrames", "0", "source", "name"])
+                    .as_str()
+                    .unwrap(),
+                body(&res, &["stackFrames", "0", "line"]).as_i64().unwrap(),
+            ));
+
+            let res = client.request("scopes", Json::object(vec![("frameId", 0u32)]));
+            let locals = body(&res, &["scopes", "0", "variablesReference"]).clone();
+
+            assert_eq!(
+                body(&res, &["scopes", "0", "name"]).as_str(),
+                Some("Locals")
+            );
+
+            let res = client.request(
+                "variables",
+                Json::object(vec![("variablesReference", locals)]),
+            );
+
+            if let Some(Json::Array(vars)) = res.get("body").and_then(|b| b.get("variables")) {
+                for var in vars {
+                    results.push(format!(
+                        "{}={}",
+                        var.get("name").and_then(Json::as_str).unwrap(),
+                        var.get("value").and_then(Json::as_str).unwrap()
+                    ));
+                }
+            }
+
+            let res = client.request("evaluate", Json::object(vec![("expression", "[1, 2, 3]")]));
+
+            results.push(body(&res, &["result"]).as_str().unwrap().to_owned());
+
+            client.request("next", Json::object(vec![("threadId", THREAD_ID)]));
+            client.wait_event("stopped");
+
+            let res = client.request("stackTrace", Json::object(vec![("threadId", THREAD_ID)]));
+
+            results.push(format!(
+                "line {}",
+                body(&res, &["stackFrames", "0", "line"]).as_i64().unwrap()
+            ));
+
+            client.request("continue", Json::object(vec![("threadId", THREAD_ID)]));
+            client.wait_event("terminated");
+            client.request("disconnect", Json::Null);
+
+            results
+        });
+
+        assert!(ready.wait_timeout(Duration::from_secs(10)));
+
+        assert_eq!(
+            ctxt.eval_script(
+                "function greet(name) {\n    var msg = 'hello ' + name;\n    var obj = { msg: msg };\n    return obj.msg;\n}\ngreet('world');",
+                "/src/app.js",
+                Eval::GLOBAL,
+            )
+            .unwrap()
+            .to_string(),
+            "hello world"
+        );
+
+        rt.detach_debugger();
+
+        assert_eq!(
+            client.join().unwrap(),
+            vec![
+                "greet@app.js:3",
+                "name=\"world\"",
+                "msg=\"hello world\"",
+                "obj=undefined",
+                "Array(3)",
+                "line 4",
+            ]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::os::raw::c_int;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, RuntimeRef};
+
+/// The event reported to the execution hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecEvent {
+    /// entered a function, before running its first line
+    Enter,
+    /// returned or thrown from a function
+    Exit,
+    /// stepped the source lines as many as the interval of the hook
+    Tick,
+}
+
+/// The location of the execution reported to the execution hook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Execution {
+    /// the event of the execution
+    pub event: ExecEvent,
+    /// the name of the current function, empty for the anonymous function
+    pub function: String,
+    /// the source file of the current function
+    pub file: String,
+    /// the current source line
+    pub line: u32,
+    /// the depth of the call stack
+    pub depth: usize,
+}
+
+type Handler = Box<dyn FnMut(&ContextRef, &Execution) + Send>;
+
+/// A low-frequency hook of the script execution, for the custom tracers, samplers or loggers.
+///
+/// The hook is called when entering and exiting the script functions,
+/// and/or every `n` source lines stepped by the scripts.
+///
+/// The handler should not set or clear the execution hook of the runtime,
+/// and the scripts evaluated by the handler are not reported.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use qjs::{Context, Eval, ExecEvent, ExecHook, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let calls = Arc::new(Mutex::new(vec![]));
+/// let trace = calls.clone();
+///
+/// rt.set_exec_hook(ExecHook::new(move |_ctxt, exec| {
+///     if exec.event == ExecEvent::Enter {
+///         trace.lock().unwrap().push(format!("{}:{}", exec.function, exec.line));
+///     }
+/// }));
+///
+/// ctxt.eval_script("function hello() {\n  return 'hello';\n}\nhello();", "hello.js", Eval::GLOBAL)
+///     .unwrap();
+///
+/// rt.clear_exec_hook();
+///
+/// assert!(calls.lock().unwrap().contains(&"hello:2".to_owned()));
+/// ```
+pub struct ExecHook {
+    calls: bool,
+    every: Option<usize>,
+    steps: usize,
+    handler: Handler,
+}
+
+impl fmt::Debug for ExecHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExecHook")
+            .field("calls", &self.calls)
+            .field("every", &self.every)
+            .finish()
+    }
+}
+
+impl ExecHook {
+    /// Create a hook which reports the entry and exit of functions to the handler.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: FnMut(&ContextRef, &Execution) + Send + 'static,
+    {
+        ExecHook {
+            calls: true,
+            every: None,
+            steps: 0,
+            handler: Box::new(handler),
+        }
+    }
+
+    /// Report the entry and exit of functions or not.
+    pub fn with_calls(mut self, calls: bool) -> Self {
+        self.calls = calls;
+        self
+    }
+
+    /// Report the `ExecEvent::Tick` event every `n` source lines stepped by the scripts.
+    pub fn with_every(mut self, n: usize) -> Self {
+        self.every = if n == 0 { None } else { Some(n) };
+        self
+    }
+
+    fn event(&mut self, event: c_int) -> Option<ExecEvent> {
+        let stepped = event == ffi::JS_DEBUGGER_EVENT_STEP || event == ffi::JS_DEBUGGER_EVENT_ENTER;
+
+        if stepped {
+            if let Some(n) = self.every {
+                self.steps += 1;
+
+                if self.steps >= n {
+                    self.steps = 0;
+
+                    return Some(ExecEvent::Tick);
+                }
+            }
+        }
+
+        match event {
+            ffi::JS_DEBUGGER_EVENT_ENTER if self.calls => Some(ExecEvent::Enter),
+            ffi::JS_DEBUGGER_EVENT_EXIT if self.calls => Some(ExecEvent::Exit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Hooked(RefCell<Option<ExecHook>>);
+
+impl RuntimeRef {
+    /// Set the execution hook of the runtime, replaces the previous one.
+    pub fn set_exec_hook(&self, hook: ExecHook) {
+        *self.state::<Hooked>().0.borrow_mut() = Some(hook);
+
+        self.update_debugger_hook();
+    }
+
+    /// Clear the execution hook of the runtime, returns the previous one.
+    pub fn clear_exec_hook(&self) -> Option<ExecHook> {
+        let hook = self.state::<Hooked>().0.borrow_mut().take();
+
+        self.update_debugger_hook();
+
+        hook
+    }
+}
+
+pub(crate) fn is_hooked(rt: &RuntimeRef) -> bool {
+    rt.state::<Hooked>().0.borrow().is_some()
+}
+
+pub(crate) fn dispatch(
+    ctxt: &ContextRef,
+    event: c_int,
+    filename: ffi::JSAtom,
+    line_num: c_int,
+    depth: c_int,
+) {
+    let hooked = ctxt.runtime().state::<Hooked>();
+    let mut hook = match hooked.0.try_borrow_mut() {
+        Ok(hook) => hook,
+        Err(_) => return,
+    };
+    let hook = match hook.as_mut() {
+        Some(hook) => hook,
+        None => return,
+    };
+    let event = match hook.event(event) {
+        Some(event) => event,
+        None => return,
+    };
+    let atom_to_string = |atom| {
+        let atom = ctxt.bind_atom(atom);
+
+        if *atom == 0 {
+            String::new()
+        } else {
+            atom.to_string()
+        }
+    };
+    let mut frame = ffi::JSDebuggerFrame::default();
+    let function = if unsafe { ffi::JS_GetFrameInfo(ctxt.as_ptr(), 0, &mut frame) } < 0 {
+        String::new()
+    } else {
+        let _filename = ctxt.bind_atom(frame.filename);
+
+        atom_to_string(frame.function_name)
+    };
+    let execution = Execution {
+        event,
+        function,
+        file: if filename == 0 {
+            String::new()
+        } else {
+            ctxt.clone_atom(filename).to_string()
+        },
+        line: line_num.max(0) as u32,
+        depth: depth.max(0) as usize,
+    };
+
+    trace!("execution hook: {:?}", execution);
+
+    (hook.handler)(ctxt, &execution)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn exec_hook() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let script = "function add(a, b) {\n  return a + b;\n}\nfunction outer(n) {\n  var x = add(n, 1);\n  return add(x, 2);\n}\nouter(1);";
+        let events = Arc::new(Mutex::new(vec![]));
+        let trace = events.clone();
+
+        rt.set_exec_hook(ExecHook::new(move |_ctxt, exec| {
+            trace.lock().unwrap().push((
+                exec.event,
+                exec.function.clone(),
+                exec.file.clone(),
+                exec.depth,
+            ))
+        }));
+
+        ctxt.eval_script(script, "test.js", Eval::GLOBAL).unwrap();
+
+        assert!(rt.clear_exec_hook().is_some());
+
+        let calls = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, function, ..)| function == "add" || function == "outer")
+            .map(|(event, function, file, _)| {
+                assert_eq!(file, "test.js");
+
+                (*event, function.clone())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            calls,
+            vec![
+                (ExecEvent::Enter, "outer".to_owned()),
+                (ExecEvent::Enter, "add".to_owned()),
+                (ExecEvent::Exit, "add".to_owned()),
+                (ExecEvent::Enter, "add".to_owned()),
+                (ExecEvent::Exit, "add".to_owned()),
+                (ExecEvent::Exit, "outer".to_owned()),
+            ]
+        );
+
+        let depths = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, function, ..)| function == "add")
+            .map(|(.., depth)| *depth)
+            .collect::<Vec<_>>();
+
+        assert!(depths.iter().all(|&depth| depth == depths[0]));
+
+        let ticks = Arc::new(Mutex::new(0));
+        let counter = ticks.clone();
+
+        rt.set_exec_hook(
+            ExecHook::new(move |_ctxt, exec| {
+                assert_eq!(exec.event, ExecEvent::Tick);
+
+                *counter.lock().unwrap() += 1;
+            })
+            .with_calls(false)
+            .with_every(10),
+        );
+
+        ctxt.eval_script(
+            "for (var i = 0; i < 100; i++) {\n  i += 0;\n}",
+            "loop.js",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        rt.clear_exec_hook();
+
+        assert!(*ticks.lock().unwrap() >= 10, "{}", ticks.lock().unwrap());
+
+        events.lock().unwrap().clear();
+
+        ctxt.eval_script(script, "test.js", Eval::GLOBAL).unwrap();
+
+        assert!(events.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+use std::fmt::{self, Write};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use failure::Error;
+
+/// The maximum nesting depth of the arrays and objects, to avoid overflowing the stack on a malicious message.
+const MAX_DEPTH: usize = 128;
+
+/// The minimal JSON value of the Debug Adapter Protocol messages.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a JSON text.
+    pub fn parse(s: &str) -> Result<Json, Error> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+            depth: 0,
+        };
+        let value = parser.value()?;
+
+        parser.skip_whitespace();
+
+        if parser.chars.peek().is_some() {
+            bail!("unexpected trailing characters")
+        }
+
+        Ok(value)
+    }
+
+    /// Create an object of the members.
+    pub fn object<I, K, V>(members: I) -> Json
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Json>,
+    {
+        Json::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+
+    /// Get the member of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Number(n) if n.fract() == 0.0 => Some(*n as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(n: i64) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Self {
+        Json::Number(f64::from(n))
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(items: Vec<Json>) -> Self {
+        Json::Array(items)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Json::Null, Into::into)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => f.write_str("null"),
+            Json::String(s) => write_str(f, s),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(members) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => bail!("expected `{}`, found `{}`", expected, c),
+            None => bail!("expected `{}`, found end of input", expected),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, Error> {
+        for c in keyword.chars() {
+            self.expect(c)?;
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') | Some('{') => {
+                if self.depth >= MAX_DEPTH {
+                    bail!("exceeded the maximum nesting depth of {}", MAX_DEPTH)
+                }
+
+                self.depth += 1;
+
+                let value = if self.chars.peek() == Some(&'[') {
+                    self.array()
+                } else {
+                    self.object()
+                };
+
+                self.depth -= 1;
+
+                value
+            }
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let mut s = String::new();
+
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_ascii_digit() || "+-.eE".contains(c) {
+                        s.push(c);
+                        self.chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                Ok(Json::Number(s.parse()?))
+            }
+            Some(c) => bail!("unexpected character `{}`", c),
+            None => bail!("unexpected end of input"),
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, Error> {
+        self.chars.next();
+
+        let mut items = vec![];
+
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => bail!("expected `,` or `]` in array"),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, Error> {
+        self.chars.next();
+
+        let mut members = vec![];
+
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+
+            members.push((key, self.value()?));
+
+            self.skip_whitespace();
+
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => bail!("expected `,` or `}}` in object"),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let hi = self.hex4()?;
+                        let c = if (0xD800..0xDC00).contains(&hi) {
+                            self.expect('\\')?;
+                            self.expect('u')?;
+
+                            let lo = self.hex4()?;
+
+                            0x10000 + ((hi - 0xD800) << 10) + (lo.wrapping_sub(0xDC00) & 0x3FF)
+                        } else {
+                            hi
+                        };
+
+                        s.push(std::char::from_u32(c).unwrap_or('\u{FFFD}'));
+                    }
+                    _ => bail!("invalid escape sequence"),
+                },
+                Some(c) => s.push(c),
+                None => bail!("unterminated string"),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let mut n = 0;
+
+        for _ in 0..4 {
+            n = n * 16
+                + self
+                    .chars
+                    .next()
+                    .and_then(|c| c.to_digit(16))
+                    .ok_or_else(|| format_err!("invalid unicode escape"))?;
+        }
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let s = r#" {"seq": 1, "type":"request", "arguments": {"lines": [1, 2.5, -3e2], "ok": true, "none": null, "s": "a\"b\\né😀"}} "#;
+        let json = Json::parse(s).unwrap();
+
+        assert_eq!(json.get("seq").and_then(Json::as_i64), Some(1));
+        assert_eq!(json.get("type").and_then(Json::as_str), Some("request"));
+
+        let args = json.get("arguments").unwrap();
+
+        assert_eq!(
+            args.get("lines").and_then(Json::as_array).unwrap(),
+            &[Json::Number(1.0), Json::Number(2.5), Json::Number(-300.0)][..]
+        );
+        assert_eq!(args.get("ok").and_then(Json::as_bool), Some(true));
+        assert_eq!(args.get("none"), Some(&Json::Null));
+        assert_eq!(args.get("s").and_then(Json::as_str), Some("a\"b\\né😀"));
+
+        assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+        assert_eq!(
+            Json::object(vec![
+                ("a", Json::from("x\ny")),
+                ("b", vec![Json::from(1u32)].into())
+            ])
+            .to_string(),
+            r#"{"a":"x\ny","b":[1]}"#
+        );
+
+        assert!(Json::parse("{").is_err());
+        assert!(Json::parse("[1,]").is_err());
+        assert!(Json::parse("1 2").is_err());
+
+        let nested = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+
+        assert!(Json::parse(&nested).is_ok());
+        assert_eq!(
+            Json::parse(&("[".repeat(100_000) + &"]".repeat(100_000)))
+                .unwrap_err()
+                .to_string(),
+            "exceeded the maximum nesting depth of 128"
+        );
+
+        let object = |depth| r#"{"a":"#.repeat(depth) + "1" + &"}".repeat(depth);
+
+        assert!(Json::parse(&object(MAX_DEPTH)).is_ok());
+        assert!(Json::parse(&object(MAX_DEPTH + 1)).is_err());
+    }
+}
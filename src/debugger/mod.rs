@@ -0,0 +1,758 @@
+//! The statement-level debugger of the scripts.
+//!
+//! The interpreter reports each new source line of the running frames to the debugger,
+//! which pauses the execution at the breakpoints, steps through the source,
+//! and inspects the stack frames and scopes while paused.
+//!
+//! The `DebugAdapter` exposes the debugger over the [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/),
+//! so the embedded scripts could be debugged from VS Code or other DAP clients.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::os::raw::{c_int, c_void};
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Eval, Local, RuntimeRef, SourcePosition, Value};
+
+#[cfg(feature = "coverage")]
+pub(crate) mod coverage;
+mod dap;
+#[cfg(feature = "hooks")]
+pub(crate) mod hook;
+mod json;
+#[cfg(feature = "profiler")]
+pub(crate) mod profiler;
+
+#[cfg(feature = "coverage")]
+pub use self::coverage::{Coverage, FileCoverage, FunctionCoverage};
+pub use self::dap::{AdapterHandler, DebugAdapter, Ready};
+#[cfg(feature = "hooks")]
+pub use self::hook::{ExecEvent, ExecHook, Execution};
+#[cfg(feature = "profiler")]
+pub use self::profiler::{Profile, ProfileNode};
+
+/// The reason why the execution paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseReason {
+    /// hit a breakpoint
+    Breakpoint,
+    /// finished a step
+    Step,
+    /// an exception is thrown
+    Exception,
+    /// the pause was requested by `Debugger::pause`
+    Pause,
+}
+
+/// How to resume the paused execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    /// Continue until the next breakpoint.
+    Continue,
+    /// Pause at the next source line, entering the called functions.
+    StepIn,
+    /// Pause at the next source line of the current function or its callers.
+    StepOver,
+    /// Pause after returned from the current function.
+    StepOut,
+    /// Terminate the execution with an `InternalError: interrupted`.
+    Terminate,
+}
+
+/// A stack frame of the paused execution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StackFrame {
+    /// the name of the function, empty for the anonymous function
+    pub function: String,
+    /// the source file of the function, `None` for the native function
+    pub file: Option<String>,
+    /// the current line of the frame
+    pub line: Option<u32>,
+}
+
+/// The paused execution.
+pub struct Paused<'a> {
+    ctxt: &'a ContextRef,
+    reason: PauseReason,
+    file: String,
+    line: u32,
+    depth: usize,
+    exception: Option<Value>,
+}
+
+impl<'a> Paused<'a> {
+    /// The context of the paused execution.
+    pub fn context(&self) -> &'a ContextRef {
+        self.ctxt
+    }
+
+    /// The reason why the execution paused.
+    pub fn reason(&self) -> PauseReason {
+        self.reason
+    }
+
+    /// The source file of the innermost script frame.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// The current line of the innermost script frame.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The position of the innermost script frame in the original source,
+    /// or `None` if the file has no source map, see `RuntimeRef::register_source_map`.
+    pub fn original_position(&self) -> Option<SourcePosition> {
+        self.ctxt.runtime().map_position(&SourcePosition {
+            file: self.file.clone(),
+            line: Some(self.line),
+            column: None,
+        })
+    }
+
+    /// The depth of the call stack.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The thrown exception when paused on exception.
+    pub fn exception(&self) -> Option<&Value> {
+        self.exception.as_ref()
+    }
+
+    /// The stack frames of the paused execution, the innermost first.
+    pub fn stack_frames(&self) -> Result<Vec<StackFrame>, Error> {
+        let frames = self
+            .ctxt
+            .bind(unsafe { ffi::JS_GetStackFrames(self.ctxt.as_ptr()) })
+            .ok()?;
+        let len = frames
+            .get_property("length")
+            .and_then(|v| v.to_index())
+            .unwrap_or_default();
+
+        Ok((0..len as u32)
+            .flat_map(|i| frames.get_property(i))
+            .map(|frame| StackFrame {
+                function: frame
+                    .get_property("functionName")
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                file: frame
+                    .get_property("fileName")
+                    .filter(|v| !v.is_undefined())
+                    .map(|v| v.to_string()),
+                line: frame
+                    .get_property("lineNumber")
+                    .and_then(|v| v.as_int())
+                    .map(|n| n as u32),
+            })
+            .collect())
+    }
+
+    /// The arguments and local variables of the stack frame at `level`, `0` is the innermost.
+    ///
+    /// Returns `None` if the frame is not a script function.
+    pub fn locals(&self, level: usize) -> Result<Option<Local<'a, Value>>, Error> {
+        self.scope(level, false)
+    }
+
+    /// The closure variables captured by the function of the stack frame at `level`.
+    pub fn closure(&self, level: usize) -> Result<Option<Local<'a, Value>>, Error> {
+        self.scope(level, true)
+    }
+
+    fn scope(&self, level: usize, closure: bool) -> Result<Option<Local<'a, Value>>, Error> {
+        let scope = self
+            .ctxt
+            .bind(unsafe {
+                ffi::JS_GetFrameScope(self.ctxt.as_ptr(), level as c_int, closure as c_int)
+            })
+            .ok()?;
+
+        Ok(if scope.is_undefined() {
+            None
+        } else {
+            Some(scope)
+        })
+    }
+
+    /// Evaluate an expression in the global scope while paused.
+    ///
+    /// The evaluation doesn't trigger the breakpoints.
+    pub fn evaluate(&self, expr: &str) -> Result<Local<'a, Value>, Error> {
+        self.ctxt.eval_script(expr, "<debugger>", Eval::GLOBAL)
+    }
+}
+
+/// The handler called when the execution paused.
+///
+/// The handler is called on the thread of the runtime,
+/// the execution keeps paused until the handler returns.
+pub trait DebugHandler: Send {
+    /// Inspect the paused execution and decide how to resume it.
+    fn paused(&mut self, paused: &Paused) -> Resume;
+}
+
+impl<F> DebugHandler for F
+where
+    F: FnMut(&Paused) -> Resume + Send,
+{
+    fn paused(&mut self, paused: &Paused) -> Resume {
+        self(paused)
+    }
+}
+
+/// The breakpoints and pause requests, which could be shared with the other threads.
+#[derive(Clone, Debug, Default)]
+pub struct Debugger(Arc<Shared>);
+
+#[derive(Debug, Default)]
+struct Shared {
+    breakpoints: Mutex<HashMap<String, BTreeSet<u32>>>,
+    pause_on_exceptions: AtomicBool,
+    pause_requested: AtomicBool,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a breakpoint at the line of the source file.
+    ///
+    /// Returns `false` if the breakpoint already exists.
+    pub fn set_breakpoint(&self, file: &str, line: u32) -> bool {
+        self.0
+            .breakpoints
+            .lock()
+            .unwrap()
+            .entry(file.to_owned())
+            .or_default()
+            .insert(line)
+    }
+
+    /// Remove the breakpoint at the line of the source file.
+    pub fn remove_breakpoint(&self, file: &str, line: u32) -> bool {
+        self.0
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get_mut(file)
+            .map_or(false, |lines| lines.remove(&line))
+    }
+
+    /// Replace all the breakpoints of the source file.
+    pub fn set_breakpoints<I: IntoIterator<Item = u32>>(&self, file: &str, lines: I) {
+        let lines = lines.into_iter().collect::<BTreeSet<_>>();
+        let mut breakpoints = self.0.breakpoints.lock().unwrap();
+
+        if lines.is_empty() {
+            breakpoints.remove(file);
+        } else {
+            breakpoints.insert(file.to_owned(), lines);
+        }
+    }
+
+    /// Remove all the breakpoints.
+    pub fn clear_breakpoints(&self) {
+        self.0.breakpoints.lock().unwrap().clear()
+    }
+
+    /// Returns `true` if there is a breakpoint at the line of the source file.
+    pub fn has_breakpoint(&self, file: &str, line: u32) -> bool {
+        self.0
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get(file)
+            .map_or(false, |lines| lines.contains(&line))
+    }
+
+    /// Pause the execution when an exception is thrown.
+    pub fn set_pause_on_exceptions(&self, enabled: bool) {
+        self.0.pause_on_exceptions.store(enabled, Ordering::SeqCst)
+    }
+
+    /// Request to pause the execution at the next source line.
+    pub fn pause(&self) {
+        self.0.pause_requested.store(true, Ordering::SeqCst)
+    }
+}
+
+struct Session {
+    debugger: Debugger,
+    handler: Box<dyn DebugHandler>,
+    step: Option<(Resume, usize)>,
+}
+
+impl Session {
+    fn should_pause(&self, file: &str, line: u32, depth: usize) -> Option<PauseReason> {
+        if self
+            .debugger
+            .0
+            .pause_requested
+            .swap(false, Ordering::SeqCst)
+        {
+            return Some(PauseReason::Pause);
+        }
+
+        match self.step {
+            Some((Resume::StepIn, _)) => return Some(PauseReason::Step),
+            Some((Resume::StepOver, d)) if depth <= d => return Some(PauseReason::Step),
+            Some((Resume::StepOut, d)) if depth < d => return Some(PauseReason::Step),
+            _ => {}
+        }
+
+        if self.debugger.has_breakpoint(file, line) {
+            Some(PauseReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct Sessions(RefCell<Option<Session>>);
+
+unsafe impl Send for Sessions {}
+
+impl RuntimeRef {
+    /// Attach the debugger to the runtime, the handler is called when the execution paused.
+    ///
+    /// The previous attached debugger will be replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Debugger, Eval, Resume, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let debugger = Debugger::new();
+    ///
+    /// debugger.set_breakpoint("test.js", 2);
+    ///
+    /// rt.attach_debugger(&debugger, |paused: &qjs::Paused| {
+    ///     let locals = paused.locals(0).unwrap().unwrap();
+    ///
+    ///     assert_eq!(paused.line(), 2);
+    ///     assert_eq!(locals.get_property("x").unwrap().as_int(), Some(42));
+    ///
+    ///     Resume::Continue
+    /// });
+    ///
+    /// ctxt.eval_script("(function () { var x = 42;\n return x; })()", "test.js", Eval::GLOBAL)
+    ///     .unwrap();
+    /// ```
+    pub fn attach_debugger<H: DebugHandler + 'static>(&self, debugger: &Debugger, handler: H) {
+        *self.state::<Sessions>().0.borrow_mut() = Some(Session {
+            debugger: debugger.clone(),
+            handler: Box::new(handler),
+            step: None,
+        });
+
+        self.update_debugger_hook();
+    }
+
+    /// Detach the debugger from the runtime.
+    ///
+    /// The debugger should not be detached from the `DebugHandler`.
+    pub fn detach_debugger(&self) {
+        self.state::<Sessions>().0.borrow_mut().take();
+
+        self.update_debugger_hook();
+    }
+
+    pub(crate) fn update_debugger_hook(&self) {
+        let hooked = self.state::<Sessions>().0.borrow().is_some();
+        #[cfg(feature = "profiler")]
+        let hooked = hooked || profiler::is_profiling(self);
+        #[cfg(feature = "coverage")]
+        let hooked = hooked || coverage::is_collecting(self);
+        #[cfg(feature = "hooks")]
+        let hooked = hooked || hook::is_hooked(self);
+
+        unsafe {
+            ffi::JS_SetDebuggerHook(
+                self.as_ptr(),
+                if hooked { Some(debugger_hook) } else { None },
+                null_mut(),
+            )
+        }
+    }
+}
+
+unsafe extern "C" fn debugger_hook(
+    ctx: *mut ffi::JSContext,
+    event: c_int,
+    filename: ffi::JSAtom,
+    line_num: c_int,
+    depth: c_int,
+    exception: ffi::JSValue,
+    _opaque: *mut c_void,
+) -> c_int {
+    let ctxt = ContextRef::from_ptr(ctx);
+    let rt = ctxt.runtime();
+
+    rt.catch_unwind(0, || {
+        let stepped = event == ffi::JS_DEBUGGER_EVENT_STEP || event == ffi::JS_DEBUGGER_EVENT_ENTER;
+
+        #[cfg(feature = "profiler")]
+        {
+            if stepped {
+                profiler::sample(ctxt);
+            }
+        }
+        #[cfg(feature = "coverage")]
+        {
+            if stepped {
+                coverage::hit(ctxt, event, filename, line_num);
+            }
+        }
+        #[cfg(feature = "hooks")]
+        {
+            hook::dispatch(ctxt, event, filename, line_num, depth);
+        }
+
+        if !stepped && event != ffi::JS_DEBUGGER_EVENT_EXCEPTION {
+            return 0;
+        }
+
+        let sessions = rt.state::<Sessions>();
+        let mut session = match sessions.0.try_borrow_mut() {
+            Ok(session) => session,
+            Err(_) => return 0,
+        };
+        let session = match session.as_mut() {
+            Some(session) => session,
+            None => return 0,
+        };
+        let file = if filename == 0 {
+            String::new()
+        } else {
+            ctxt.clone_atom(filename).to_string()
+        };
+        let line = line_num.max(0) as u32;
+        let depth = depth.max(0) as usize;
+
+        let reason = if event == ffi::JS_DEBUGGER_EVENT_EXCEPTION {
+            if session
+                .debugger
+                .0
+                .pause_on_exceptions
+                .load(Ordering::SeqCst)
+            {
+                Some(PauseReason::Exception)
+            } else {
+                None
+            }
+        } else {
+            session.should_pause(&file, line, depth)
+        };
+
+        let reason = match reason {
+            Some(reason) => reason,
+            None => return 0,
+        };
+
+        trace!("debugger paused at {}:{}, {:?}", file, line, reason);
+
+        let paused = Paused {
+            ctxt,
+            reason,
+            file,
+            line,
+            depth,
+            exception: if event == ffi::JS_DEBUGGER_EVENT_EXCEPTION {
+                Some(Value::from(exception))
+            } else {
+                None
+            },
+        };
+        let resume = session.handler.paused(&paused);
+
+        trace!("debugger resumed, {:?}", resume);
+
+        session.step = match resume {
+            Resume::Continue | Resume::Terminate => None,
+            step => Some((step, depth)),
+        };
+
+        if resume == Resume::Terminate {
+            -1
+        } else {
+            0
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use crate::{Context, ErrorKind, Runtime};
+
+    use super::*;
+
+    const SCRIPT: &str = r#"function add(a, b) {
+    var sum = a + b;
+    return sum;
+}
+function outer(n) {
+    var x = add(n, 1);
+    var y = add(x, 2);
+    return y;
+}
+outer(1);
+"#;
+
+    #[test]
+    fn breakpoints() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (tx, rx) = mpsc::channel();
+
+        assert!(debugger.set_breakpoint("test.js", 2));
+        assert!(!debugger.set_breakpoint("test.js", 2));
+
+        rt.attach_debugger(&debugger, move |paused: &Paused| {
+            let locals = paused.locals(0).unwrap().unwrap();
+
+            tx.send((
+                paused.reason(),
+                paused.line(),
+                paused.depth(),
+                locals.get_property("a").unwrap().as_int(),
+                locals.get_property("sum").is_none(),
+                paused.evaluate("typeof outer").unwrap().to_string(),
+            ))
+            .unwrap();
+
+            Resume::Continue
+        });
+
+        assert_eq!(
+            ctxt.eval_script(SCRIPT, "test.js", Eval::GLOBAL)
+                .unwrap()
+                .as_int(),
+            Some(4)
+        );
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    PauseReason::Breakpoint,
+                    2,
+                    3,
+                    Some(1),
+                    true,
+                    "function".to_owned()
+                ),
+                (
+                    PauseReason::Breakpoint,
+                    2,
+                    3,
+                    Some(2),
+                    true,
+                    "function".to_owned()
+                ),
+            ]
+        );
+
+        assert!(debugger.remove_breakpoint("test.js", 2));
+
+        ctxt.eval_script(SCRIPT, "test.js", Eval::GLOBAL).unwrap();
+
+        assert_eq!(rx.try_iter().count(), 0);
+
+        rt.detach_debugger();
+    }
+
+    #[test]
+    fn stepping() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (tx, rx) = mpsc::channel();
+        let mut steps = vec![
+            Resume::StepOver,
+            Resume::StepIn,
+            Resume::StepOver,
+            Resume::StepOut,
+            Resume::StepOver,
+            Resume::Continue,
+        ]
+        .into_iter();
+
+        debugger.set_breakpoint("test.js", 6);
+
+        rt.attach_debugger(&debugger, move |paused: &Paused| {
+            let frames = paused.stack_frames().unwrap();
+
+            tx.send((paused.line(), frames[0].function.clone()))
+                .unwrap();
+
+            steps.next().unwrap_or(Resume::Continue)
+        });
+
+        ctxt.eval_script(SCRIPT, "test.js", Eval::GLOBAL).unwrap();
+
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                (6, "outer".to_owned()),
+                (7, "outer".to_owned()),
+                (2, "add".to_owned()),
+                (3, "add".to_owned()),
+                (8, "outer".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inspect() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (tx, rx) = mpsc::channel();
+
+        debugger.set_breakpoint("closure.js", 4);
+
+        rt.attach_debugger(&debugger, move |paused: &Paused| {
+            let closure = paused.closure(0).unwrap().unwrap();
+            let frames = paused.stack_frames().unwrap();
+
+            tx.send((
+                closure.get_property("counter").unwrap().as_int(),
+                frames
+                    .iter()
+                    .map(|frame| format!("{}@{}", frame.function, frame.line.unwrap_or(0)))
+                    .collect::<Vec<_>>()
+                    .join(","),
+                frames[0].file.clone(),
+                paused.locals(frames.len()).unwrap().is_none(),
+            ))
+            .unwrap();
+
+            Resume::Continue
+        });
+
+        ctxt.eval_script(
+            "var counter = 0;\nvar inc = (function () { let counter = 10;\n return function inc() {\n counter++;\n return counter; } })();\n[1, 2].map(() => inc());",
+            "closure.js",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    Some(10),
+                    "inc@4,@6,map@0,<eval>@6".to_owned(),
+                    Some("closure.js".to_owned()),
+                    true
+                ),
+                (
+                    Some(11),
+                    "inc@4,@6,map@0,<eval>@6".to_owned(),
+                    Some("closure.js".to_owned()),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn exceptions() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (tx, rx) = mpsc::channel();
+
+        rt.attach_debugger(&debugger, move |paused: &Paused| {
+            tx.send((
+                paused.reason(),
+                paused.line(),
+                paused
+                    .exception()
+                    .map(|e| paused.context().clone_value(e).to_string()),
+            ))
+            .unwrap();
+
+            Resume::Continue
+        });
+
+        let script = "try { throw new Error('boom') } catch (e) {}\n\nnull.x;";
+
+        assert!(ctxt.eval_script(script, "throw.js", Eval::GLOBAL).is_err());
+        assert_eq!(rx.try_iter().count(), 0);
+
+        debugger.set_pause_on_exceptions(true);
+
+        assert!(ctxt.eval_script(script, "throw.js", Eval::GLOBAL).is_err());
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                (PauseReason::Exception, 1, Some("Error: boom".to_owned())),
+                (
+                    PauseReason::Exception,
+                    3,
+                    Some("TypeError: value has no property".to_owned())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn pause_and_terminate() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let debugger = Debugger::new();
+        let (tx, rx) = mpsc::channel();
+
+        rt.attach_debugger(&debugger, move |paused: &Paused| {
+            tx.send((paused.reason(), paused.line())).unwrap();
+
+            Resume::Terminate
+        });
+
+        debugger.pause();
+
+        match ctxt
+            .eval_script(
+                "var i = 0;\nwhile (true) {\n i++;\n}",
+                "loop.js",
+                Eval::GLOBAL,
+            )
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+        {
+            Ok(ErrorKind::Interrupted(_)) => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![(PauseReason::Pause, 1)]
+        );
+    }
+}
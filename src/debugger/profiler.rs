@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use foreign_types::ForeignTypeRef;
+
+use super::json::Json;
+use crate::{ffi, ContextRef, RuntimeRef};
+
+/// A node of the call tree recorded by the profiler.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileNode {
+    /// the name of the function, empty for the anonymous function
+    pub function: String,
+    /// the source file of the function, `None` for the native function
+    pub file: Option<String>,
+    /// the line where the function is defined
+    pub line: Option<u32>,
+    /// the caller of the function, `None` for the root node
+    pub parent: Option<usize>,
+    /// the functions called by the function
+    pub children: Vec<usize>,
+    /// the samples of the function on the top of stack
+    pub hits: usize,
+    /// the time of the function on the top of stack
+    pub self_time: Duration,
+}
+
+impl ProfileNode {
+    fn new(parent: Option<usize>, frame: Frame) -> Self {
+        ProfileNode {
+            function: frame.function,
+            file: frame.file,
+            line: frame.line,
+            parent,
+            children: vec![],
+            hits: 0,
+            self_time: Duration::default(),
+        }
+    }
+
+    fn is_frame(&self, frame: &Frame) -> bool {
+        self.function == frame.function && self.file == frame.file && self.line == frame.line
+    }
+
+    /// The label of the function in the flame graph.
+    fn label(&self) -> String {
+        let function = if self.function.is_empty() {
+            "(anonymous)"
+        } else {
+            &self.function
+        };
+
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => format!("{} ({}:{})", function, file, line),
+            (Some(file), None) => format!("{} ({})", function, file),
+            _ => format!("{} (native)", function),
+        }
+    }
+}
+
+/// The call tree and samples recorded by the profiler.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    nodes: Vec<ProfileNode>,
+    samples: Vec<(usize, Duration)>,
+    duration: Duration,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            nodes: vec![ProfileNode::new(
+                None,
+                Frame {
+                    function: "(root)".to_owned(),
+                    file: None,
+                    line: None,
+                },
+            )],
+            samples: vec![],
+            duration: Duration::default(),
+        }
+    }
+}
+
+impl Profile {
+    /// The nodes of the call tree, the first one is the root node.
+    pub fn nodes(&self) -> &[ProfileNode] {
+        &self.nodes
+    }
+
+    /// The wall-clock time of profiling.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The sampled node and the elapsed time since the previous sample.
+    pub fn samples(&self) -> &[(usize, Duration)] {
+        &self.samples
+    }
+
+    /// Write the profile in the collapsed stack format of [FlameGraph](https://github.com/brendangregg/FlameGraph),
+    /// each line is the stack of functions and its self time in microseconds.
+    pub fn write_collapsed<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for (id, node) in self.nodes.iter().enumerate().skip(1) {
+            if node.hits == 0 {
+                continue;
+            }
+
+            let mut labels = vec![];
+            let mut next = Some(id);
+
+            while let Some(id) = next.filter(|&id| id > 0) {
+                labels.push(self.nodes[id].label());
+                next = self.nodes[id].parent;
+            }
+
+            labels.reverse();
+
+            writeln!(w, "{} {}", labels.join(";"), node.self_time.as_micros())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the profile as a Chrome `.cpuprofile`, which could be loaded by the Chrome DevTools or VS Code.
+    pub fn write_cpuprofile<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut scripts = HashMap::new();
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                let script_id = node.file.as_ref().map_or(0, |file| {
+                    let next = scripts.len() + 1;
+
+                    *scripts.entry(file.clone()).or_insert(next)
+                });
+
+                Json::object(vec![
+                    ("id", Json::from(id + 1)),
+                    (
+                        "callFrame",
+                        Json::object(vec![
+                            ("functionName", Json::from(node.function.as_str())),
+                            ("scriptId", script_id.to_string().into()),
+                            ("url", node.file.clone().unwrap_or_default().into()),
+                            (
+                                "lineNumber",
+                                node.line.map_or(-1, |line| i64::from(line) - 1).into(),
+                            ),
+                            ("columnNumber", Json::from(-1i64)),
+                        ]),
+                    ),
+                    ("hitCount", node.hits.into()),
+                    (
+                        "children",
+                        node.children
+                            .iter()
+                            .map(|id| Json::from(id + 1))
+                            .collect::<Vec<_>>()
+                            .into(),
+                    ),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let profile = Json::object(vec![
+            ("nodes", Json::from(nodes)),
+            ("startTime", 0u32.into()),
+            ("endTime", (self.duration.as_micros() as usize).into()),
+            (
+                "samples",
+                self.samples
+                    .iter()
+                    .map(|(id, _)| Json::from(id + 1))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            (
+                "timeDeltas",
+                self.samples
+                    .iter()
+                    .map(|(_, delta)| Json::from(delta.as_micros() as usize))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        ]);
+
+        write!(w, "{}", profile)
+    }
+
+    /// Record a sample of the stack, the outermost frame first.
+    fn record(&mut self, stack: Vec<Frame>, elapsed: Duration) {
+        let mut id = 0;
+
+        for frame in stack {
+            id = match self.nodes[id]
+                .children
+                .iter()
+                .find(|&&child| self.nodes[child].is_frame(&frame))
+            {
+                Some(&child) => child,
+                None => {
+                    let child = self.nodes.len();
+
+                    self.nodes.push(ProfileNode::new(Some(id), frame));
+                    self.nodes[id].children.push(child);
+
+                    child
+                }
+            };
+        }
+
+        let node = &mut self.nodes[id];
+
+        node.hits += 1;
+        node.self_time += elapsed;
+
+        self.samples.push((id, elapsed));
+    }
+}
+
+struct Frame {
+    function: String,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+struct Profiler {
+    interval: Duration,
+    started: Instant,
+    last: Option<Instant>,
+    profile: Profile,
+}
+
+#[derive(Default)]
+struct Profiling(RefCell<Option<Profiler>>);
+
+impl RuntimeRef {
+    /// Start the sampling profiler, which samples the stack of scripts every `interval`.
+    ///
+    /// The stack is sampled when the scripts step to a new source line,
+    /// and the time elapsed since the previous sample is attributed to the sampled stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// rt.start_profiling(Duration::from_millis(1));
+    ///
+    /// ctxt.eval_script("function fib(n) {\n return n < 2 ? n : fib(n - 1) + fib(n - 2);\n}\nfib(20);", "fib.js", Eval::GLOBAL)
+    ///     .unwrap();
+    ///
+    /// let profile = rt.stop_profiling().unwrap();
+    /// let mut flamegraph = vec![];
+    ///
+    /// profile.write_collapsed(&mut flamegraph).unwrap();
+    /// ```
+    pub fn start_profiling(&self, interval: Duration) {
+        *self.state::<Profiling>().0.borrow_mut() = Some(Profiler {
+            interval,
+            started: Instant::now(),
+            last: None,
+            profile: Profile::default(),
+        });
+
+        self.update_debugger_hook();
+    }
+
+    /// Stop the profiler and returns the recorded profile.
+    pub fn stop_profiling(&self) -> Option<Profile> {
+        let profiler = self.state::<Profiling>().0.borrow_mut().take();
+
+        self.update_debugger_hook();
+
+        profiler.map(|profiler| Profile {
+            duration: profiler.started.elapsed(),
+            ..profiler.profile
+        })
+    }
+}
+
+pub(crate) fn is_profiling(rt: &RuntimeRef) -> bool {
+    rt.state::<Profiling>().0.borrow().is_some()
+}
+
+pub(crate) fn start_evaluation(rt: &RuntimeRef) {
+    if let Some(profiler) = rt.state::<Profiling>().0.borrow_mut().as_mut() {
+        profiler.last = Some(Instant::now());
+    }
+}
+
+/// The time between the evaluations is not attributed to any stack.
+pub(crate) fn end_evaluation(rt: &RuntimeRef) {
+    if let Some(profiler) = rt.state::<Profiling>().0.borrow_mut().as_mut() {
+        profiler.last = None;
+    }
+}
+
+pub(crate) fn sample(ctxt: &ContextRef) {
+    let profiling = ctxt.runtime().state::<Profiling>();
+    let mut profiler = profiling.0.borrow_mut();
+    let profiler = match profiler.as_mut() {
+        Some(profiler) => profiler,
+        None => return,
+    };
+    let now = Instant::now();
+
+    match profiler.last {
+        Some(last) if now.duration_since(last) >= profiler.interval => {
+            profiler
+                .profile
+                .record(stack(ctxt), now.duration_since(last));
+            profiler.last = Some(now);
+        }
+        Some(_) => {}
+        None => profiler.last = Some(now),
+    }
+}
+
+/// The frames of current stack, the outermost first.
+fn stack(ctxt: &ContextRef) -> Vec<Frame> {
+    let atom_to_string = |atom| {
+        let atom = ctxt.bind_atom(atom);
+
+        if *atom == 0 {
+            None
+        } else {
+            Some(atom.to_string())
+        }
+    };
+    let mut frames = vec![];
+
+    for level in 0.. {
+        let mut info = ffi::JSDebuggerFrame::default();
+        let res = unsafe { ffi::JS_GetFrameInfo(ctxt.as_ptr(), level, &mut info) };
+
+        if res < 0 {
+            break;
+        }
+
+        frames.push(Frame {
+            function: atom_to_string(info.function_name).unwrap_or_default(),
+            file: atom_to_string(info.filename),
+            line: if info.function_line < 0 {
+                None
+            } else {
+                Some(info.function_line as u32)
+            },
+        });
+    }
+
+    frames.reverse();
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn profiler() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert!(rt.stop_profiling().is_none());
+
+        rt.start_profiling(Duration::default());
+
+        ctxt.eval_script(
+            "function add(a, b) {\n return a + b;\n}\nfunction outer(n) {\n var x = add(n, 1);\n return [x].map(v => add(v, 2));\n}\nouter(1);",
+            "test.js",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let profile = rt.stop_profiling().unwrap();
+        let mut collapsed = vec![];
+
+        profile.write_collapsed(&mut collapsed).unwrap();
+
+        let mut stacks = String::from_utf8(collapsed)
+            .unwrap()
+            .lines()
+            .map(|line| line.rsplitn(2, ' ').nth(1).unwrap().to_owned())
+            .collect::<Vec<_>>();
+
+        stacks.sort();
+        stacks.dedup();
+
+        assert_eq!(
+            stacks,
+            vec![
+                "<eval> (test.js:1)",
+                "<eval> (test.js:1);outer (test.js:4)",
+                "<eval> (test.js:1);outer (test.js:4);add (test.js:1)",
+                "<eval> (test.js:1);outer (test.js:4);map (native);(anonymous) (test.js:6)",
+                "<eval> (test.js:1);outer (test.js:4);map (native);(anonymous) (test.js:6);add (test.js:1)",
+            ]
+        );
+        assert_eq!(
+            profile.samples().len(),
+            profile.nodes().iter().map(|node| node.hits).sum::<usize>()
+        );
+
+        let mut cpuprofile = vec![];
+
+        profile.write_cpuprofile(&mut cpuprofile).unwrap();
+
+        let cpuprofile = Json::parse(&String::from_utf8(cpuprofile).unwrap()).unwrap();
+        let nodes = cpuprofile.get("nodes").and_then(Json::as_array).unwrap();
+
+        assert_eq!(nodes.len(), profile.nodes().len());
+        assert_eq!(
+            nodes[0]
+                .get("callFrame")
+                .and_then(|frame| frame.get("functionName"))
+                .and_then(Json::as_str),
+            Some("(root)")
+        );
+        assert_eq!(
+            cpuprofile
+                .get("samples")
+                .and_then(Json::as_array)
+                .map(|samples| samples.len()),
+            Some(profile.samples().len())
+        );
+    }
+}
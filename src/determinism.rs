@@ -0,0 +1,316 @@
+use std::cell::RefCell;
+use std::fmt;
+
+use failure::Error;
+
+use crate::{Clock, ContextRef, Eval, ManualClock, Prop, Value};
+
+/// Evaluate the local time of `Date` in the UTC time zone, regardless of the host time zone.
+const UTC_DATE_WRAPPER: &str = r#"(function (Date) {
+    const proto = Date.prototype;
+    const days = ['Sun', 'Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat'];
+    const months = ['Jan', 'Feb', 'Mar', 'Apr', 'May', 'Jun', 'Jul', 'Aug', 'Sep', 'Oct', 'Nov', 'Dec'];
+    const pad = (n, len = 2) => String(n).padStart(len, '0');
+    const define = (name, value) =>
+        Object.defineProperty(proto, name, { value, writable: true, configurable: true });
+
+    for (const name of ['FullYear', 'Month', 'Date', 'Day', 'Hours', 'Minutes', 'Seconds', 'Milliseconds']) {
+        define('get' + name, proto['getUTC' + name]);
+
+        if (name !== 'Day') {
+            define('set' + name, proto['setUTC' + name]);
+        }
+    }
+
+    define('getTimezoneOffset', function getTimezoneOffset() {
+        return isNaN(this.getTime()) ? NaN : 0;
+    });
+    define('toDateString', function toDateString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        return `${days[this.getUTCDay()]} ${months[this.getUTCMonth()]} ${pad(this.getUTCDate())} ${pad(this.getUTCFullYear(), 4)}`;
+    });
+    define('toTimeString', function toTimeString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        return `${pad(this.getUTCHours())}:${pad(this.getUTCMinutes())}:${pad(this.getUTCSeconds())} GMT+0000`;
+    });
+    define('toString', function toString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        return `${this.toDateString()} ${this.toTimeString()}`;
+    });
+    define('toLocaleString', function toLocaleString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        return `${this.toLocaleDateString()}, ${this.toLocaleTimeString()}`;
+    });
+    define('toLocaleDateString', function toLocaleDateString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        return `${this.getUTCMonth() + 1}/${this.getUTCDate()}/${this.getUTCFullYear()}`;
+    });
+    define('toLocaleTimeString', function toLocaleTimeString() {
+        if (isNaN(this.getTime())) return 'Invalid Date';
+        const hours = this.getUTCHours();
+        return `${hours % 12 || 12}:${pad(this.getUTCMinutes())}:${pad(this.getUTCSeconds())} ${hours < 12 ? 'AM' : 'PM'}`;
+    });
+
+    return new Proxy(Date, {
+        construct(target, args, newTarget) {
+            if (args.length > 1) {
+                args = [Date.UTC(...args)];
+            }
+            return Reflect.construct(target, args, newTarget);
+        },
+    });
+})"#;
+
+/// The seed of the random number generator by default.
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// The random number generator of `Math.random()`, returns a number in `[0, 1)`.
+pub type Random = Box<dyn FnMut() -> f64 + Send>;
+
+/// The deterministic execution mode of a context, for the reproducible evaluations.
+///
+/// - `Math.random()` is seeded or generated by the host,
+/// - `Date.now()`, `new Date()` and `performance.now()` read from a frozen or virtual clock,
+/// - `Date` is evaluated in the UTC time zone with the fixed formats, instead of the host time zone and locale.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Determinism, Eval, Runtime};
+///
+/// let rt = Runtime::new();
+/// let run = || {
+///     let ctxt = Context::new(&rt);
+///
+///     ctxt.init_determinism(Determinism::new().with_seed(42)).unwrap();
+///     ctxt.eval::<_, String>(
+///         "[Math.random(), Date.now(), new Date(2019, 0, 1).toString()].join()",
+///         Eval::GLOBAL,
+///     )
+///     .unwrap()
+///     .unwrap()
+/// };
+///
+/// let s = run();
+///
+/// assert_eq!(s, run());
+/// assert!(s.ends_with(",0,Tue Jan 01 2019 00:00:00 GMT+0000"), "{}", s);
+/// ```
+pub struct Determinism {
+    random: Random,
+    clock: Box<dyn Clock>,
+    utc: bool,
+}
+
+impl Default for Determinism {
+    fn default() -> Self {
+        Determinism {
+            random: xorshift(DEFAULT_SEED),
+            clock: Box::new(ManualClock::default()),
+            utc: true,
+        }
+    }
+}
+
+impl fmt::Debug for Determinism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Determinism")
+            .field("utc", &self.utc)
+            .finish()
+    }
+}
+
+impl Determinism {
+    /// Create the deterministic mode with a fixed seed and a clock frozen at the Unix epoch.
+    pub fn new() -> Self {
+        Determinism::default()
+    }
+
+    /// Seed the builtin random number generator of `Math.random()`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = xorshift(seed);
+        self
+    }
+
+    /// Generate the numbers of `Math.random()` with the host random number generator.
+    ///
+    /// The numbers should be in the range `[0, 1)`.
+    pub fn with_random<F: FnMut() -> f64 + Send + 'static>(mut self, random: F) -> Self {
+        self.random = Box::new(random);
+        self
+    }
+
+    /// Read the time from the clock, which could be a `ManualClock` advanced by the host.
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Keep the host time zone and the locale-dependent formats of `Date`.
+    pub fn with_host_locale(mut self) -> Self {
+        self.utc = false;
+        self
+    }
+}
+
+/// The xorshift64* generator of the 53 bits random numbers.
+fn xorshift(seed: u64) -> Random {
+    let mut state = if seed == 0 { DEFAULT_SEED } else { seed };
+
+    Box::new(move || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+
+        let n = state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+
+        (n >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+#[derive(Default)]
+struct ContextRandom(RefCell<Option<Random>>);
+
+impl ContextRef {
+    /// Switch the context to the deterministic execution mode.
+    ///
+    /// It should be called before evaluating any script,
+    /// the `performance` object is installed with the clock of the `Determinism`.
+    pub fn init_determinism(&self, determinism: Determinism) -> Result<(), Error> {
+        debug!("init determinism: {:?}", determinism);
+
+        let Determinism { random, clock, utc } = determinism;
+
+        *self.state::<ContextRandom>().0.borrow_mut() = Some(random);
+
+        let global = self.global_object();
+
+        if let Some(math) = global.get_property("Math") {
+            math.define_property_value(
+                "random",
+                self.new_c_function(math_random, Some("random"), 0)?,
+                Prop::WRITABLE | Prop::CONFIGURABLE,
+            )?;
+        }
+
+        self.set_clock(clock);
+        self.init_performance()?;
+
+        if utc {
+            if let Some(date) = global
+                .get_property("Date")
+                .filter(|date| date.is_function())
+            {
+                let wrapper = self.eval_script(UTC_DATE_WRAPPER, "<determinism>", Eval::GLOBAL)?;
+
+                global.define_property_value(
+                    "Date",
+                    wrapper.call(None, date)?,
+                    Prop::WRITABLE | Prop::CONFIGURABLE,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn math_random(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> f64 {
+    ctxt.state::<ContextRandom>()
+        .0
+        .borrow_mut()
+        .as_mut()
+        .map_or(0.0, |random| random())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn determinism() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let eval = |determinism: Determinism, script: &str| {
+            let ctxt = Context::new(&rt);
+
+            ctxt.init_determinism(determinism).unwrap();
+            ctxt.eval::<_, String>(script, Eval::GLOBAL)
+                .unwrap()
+                .unwrap()
+        };
+        let randoms = "[Math.random(), Math.random(), Math.random()].join()";
+
+        assert_eq!(
+            eval(Determinism::new().with_seed(1), randoms),
+            eval(Determinism::new().with_seed(1), randoms)
+        );
+        assert_ne!(
+            eval(Determinism::new().with_seed(1), randoms),
+            eval(Determinism::new().with_seed(2), randoms)
+        );
+        assert_eq!(
+            eval(
+                Determinism::new().with_seed(3),
+                "Array.from({ length: 1000 }, Math.random).every(n => n >= 0 && n < 1)"
+            ),
+            "true"
+        );
+
+        let mut n = 0.0;
+
+        assert_eq!(
+            eval(
+                Determinism::new().with_random(move || {
+                    n += 0.25;
+                    n
+                }),
+                randoms
+            ),
+            "0.25,0.5,0.75"
+        );
+
+        let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(86400));
+
+        clock.advance(Duration::from_millis(1500));
+
+        assert_eq!(
+            eval(
+                Determinism::new().with_clock(clock),
+                "[Date.now(), performance.now(), new Date().getTime()].join()"
+            ),
+            "86401500,1500,86401500"
+        );
+
+        for (script, expected) in &[
+            ("String(Date.now())", "0"),
+            (
+                "new Date(2019, 11, 31, 23, 59).getTime() === Date.UTC(2019, 11, 31, 23, 59)",
+                "true",
+            ),
+            ("new Date(0).getTimezoneOffset()", "0"),
+            ("new Date(0).getHours()", "0"),
+            (
+                "var d = new Date(0); d.setHours(5); d.toISOString()",
+                "1970-01-01T05:00:00.000Z",
+            ),
+            (
+                "new Date(Date.UTC(2020, 0, 3)).toString()",
+                "Fri Jan 03 2020 00:00:00 GMT+0000",
+            ),
+            (
+                "new Date(Date.UTC(2020, 1, 3, 13, 4, 5)).toLocaleString()",
+                "2/3/2020, 1:04:05 PM",
+            ),
+            ("new Date(NaN).toString()", "Invalid Date"),
+            ("new Date() instanceof Date", "true"),
+        ] {
+            assert_eq!(eval(Determinism::new(), script), *expected, "{}", script);
+        }
+    }
+}
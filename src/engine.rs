@@ -0,0 +1,170 @@
+use std::os::raw::c_int;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, value::ToBool, ContextRef, Local, RuntimeRef, Value};
+
+/// The engine-level statistics of a `Runtime`, to verify the object patterns are monomorphic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EngineStats {
+    /// the number of the live objects
+    pub objects: usize,
+    /// the number of the properties of the live objects
+    pub properties: usize,
+    /// the number of the live shapes, including the unshared shapes of the dictionary mode objects
+    pub shapes: usize,
+    /// the memory used by the shapes
+    pub shape_size: usize,
+    /// the number of the shapes shared by the objects with the same layout
+    pub hashed_shapes: usize,
+    /// the number of the buckets of the shape hash table
+    pub shape_hash_size: usize,
+    /// the number of the atoms in the atom table
+    pub atoms: usize,
+    /// the allocated entries of the atom table
+    pub atom_table_size: usize,
+    /// the number of the buckets of the atom hash table
+    pub atom_hash_size: usize,
+    /// the number of the arrays
+    pub arrays: usize,
+    /// the number of the fast arrays, which store the elements without the properties
+    pub fast_arrays: usize,
+    /// the hits of the inline caches, QuickJS looks up the properties in the shapes without inline caches
+    pub inline_cache_hits: Option<u64>,
+}
+
+/// The shape (hidden class) of an object, which is shared by the objects with the same layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shape {
+    /// the identity of the shape
+    pub id: usize,
+    /// the number of the properties in the shape
+    pub properties: usize,
+    /// `true` if the shape is shared in the shape hash table, otherwise the object is in the dictionary mode
+    pub hashed: bool,
+}
+
+impl RuntimeRef {
+    /// Returns the engine-level statistics of the runtime.
+    ///
+    /// The objects and shapes are counted with `RuntimeRef::memory_usage`, which walks through the whole heap.
+    pub fn engine_stats(&self) -> EngineStats {
+        let usage = self.memory_usage();
+        let mut stats = ffi::JSEngineStats::default();
+
+        unsafe { ffi::JS_GetEngineStats(self.as_ptr(), &mut stats) };
+
+        EngineStats {
+            objects: usage.obj_count as usize,
+            properties: usage.prop_count as usize,
+            shapes: usage.shape_count as usize,
+            shape_size: usage.shape_size as usize,
+            hashed_shapes: stats.shape_hash_count as usize,
+            shape_hash_size: stats.shape_hash_size as usize,
+            atoms: usage.atom_count as usize,
+            atom_table_size: stats.atom_size as usize,
+            atom_hash_size: stats.atom_hash_size as usize,
+            arrays: usage.array_count as usize,
+            fast_arrays: usage.fast_array_count as usize,
+            inline_cache_hits: None,
+        }
+    }
+}
+
+impl ContextRef {
+    /// Returns the shape of an object, or `None` if the value is not an object.
+    pub fn shape_of(&self, value: &Value) -> Option<Shape> {
+        let mut properties: c_int = 0;
+        let mut hashed: c_int = 0;
+        let shape = unsafe { ffi::JS_GetObjectShape(value.raw(), &mut properties, &mut hashed) };
+
+        if shape.is_null() {
+            None
+        } else {
+            Some(Shape {
+                id: shape as usize,
+                properties: properties as usize,
+                hashed: hashed.to_bool(),
+            })
+        }
+    }
+}
+
+impl Local<'_, Value> {
+    /// Returns the shape of the object, or `None` if the value is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let a = ctxt.eval_script("({ x: 1, y: 2 })", "<eval>", Eval::GLOBAL).unwrap();
+    /// let b = ctxt.eval_script("({ x: 3, y: 4 })", "<eval>", Eval::GLOBAL).unwrap();
+    /// let c = ctxt.eval_script("({ y: 5, x: 6 })", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert_eq!(a.shape(), b.shape());
+    /// assert_ne!(a.shape(), c.shape());
+    /// ```
+    pub fn shape(&self) -> Option<Shape> {
+        self.ctxt.shape_of(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    #[test]
+    fn engine_stats() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let before = rt.engine_stats();
+
+        assert!(before.objects > 0);
+        assert!(before.shapes > 0);
+        assert!(before.hashed_shapes > 0);
+        assert!(before.atoms > 0);
+        assert!(before.atom_table_size >= before.atoms);
+        assert_eq!(before.inline_cache_hits, None);
+
+        let points = ctxt
+            .eval_script(
+                "var points = Array.from({ length: 100 }, (_, i) => ({ x: i, y: -i })); points",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let after = rt.engine_stats();
+
+        assert!(after.objects >= before.objects + 100);
+        assert!(after.shapes < before.shapes + 10);
+
+        let first = points.get_property(0u32).unwrap().shape().unwrap();
+        let last = points.get_property(99u32).unwrap().shape().unwrap();
+
+        assert_eq!(first, last);
+        assert_eq!(first.properties, 2);
+        assert!(first.hashed);
+
+        let deleted = ctxt
+            .eval_script(
+                "var p = { x: 1, y: 2 }; delete p.x; p",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap()
+            .shape()
+            .unwrap();
+
+        assert_ne!(deleted, first);
+        assert!(!deleted.hashed);
+
+        assert!(ctxt.bind(ctxt.new_value(1)).shape().is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    ffi, value::ToBool, Atom, ContextRef, Local, PropertyNames as Names, Unsupported, Value,
+};
+
+/// The builtin classes which are compared with their primitive values.
+const BOXED_CLASSES: &[&str] = &["Boolean", "Number", "String", "Date", "RegExp"];
+
+/// The options of `Value::deep_eq`.
+#[derive(Clone, Debug)]
+pub struct EqualOptions {
+    strict: bool,
+    prototypes: bool,
+}
+
+impl Default for EqualOptions {
+    fn default() -> Self {
+        EqualOptions {
+            strict: true,
+            prototypes: false,
+        }
+    }
+}
+
+impl EqualOptions {
+    pub fn new() -> Self {
+        EqualOptions::default()
+    }
+
+    /// Compare the primitive values with `Object.is` if `true`, otherwise with `==`.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Compare the prototypes of the objects.
+    pub fn with_prototypes(mut self, prototypes: bool) -> Self {
+        self.prototypes = prototypes;
+        self
+    }
+}
+
+impl Local<'_, Value> {
+    /// Returns the result of `this === other`.
+    pub fn strict_eq(&self, other: &Value) -> Result<bool, Error> {
+        self.ctxt.strict_eq(self, other)
+    }
+
+    /// Returns the result of `this == other`, the objects may be converted to the primitive values.
+    pub fn loose_eq(&self, other: &Value) -> Result<bool, Error> {
+        self.ctxt.loose_eq(self, other)
+    }
+
+    /// Returns the result of `Object.is(this, other)`.
+    pub fn same_value(&self, other: &Value) -> Result<bool, Error> {
+        self.ctxt.same_value(self, other)
+    }
+
+    /// Compare the values structurally, the own enumerable properties of the objects are compared recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, EqualOptions, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let a = ctxt.eval_script("var a = { n: 1, s: [2, '3'] }; a.self = a; a", "<eval>", Eval::GLOBAL).unwrap();
+    /// let b = ctxt.eval_script("var b = { n: 1, s: [2, '3'] }; b.self = b; b", "<eval>", Eval::GLOBAL).unwrap();
+    /// let c = ctxt.eval_script("({ n: '1', s: [2, 3], self: b })", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert!(!a.strict_eq(&b).unwrap());
+    /// assert!(a.deep_eq(&b, &EqualOptions::new()).unwrap());
+    /// assert!(!a.deep_eq(&c, &EqualOptions::new()).unwrap());
+    /// assert!(a.deep_eq(&c, &EqualOptions::new().with_strict(false)).unwrap());
+    /// ```
+    pub fn deep_eq(&self, other: &Value, options: &EqualOptions) -> Result<bool, Error> {
+        self.ctxt.deep_eq(self, other, options)
+    }
+}
+
+impl ContextRef {
+    /// Returns the result of `a === b`.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched.
+    pub fn strict_eq(&self, a: &Value, b: &Value) -> Result<bool, Error> {
+        Unsupported::check("strict_eq")?;
+
+        Ok(unsafe { ffi::JS_StrictEq(self.as_ptr(), a.raw(), b.raw()) }.to_bool())
+    }
+
+    /// Returns the result of `a == b`, the objects may be converted to the primitive values.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched.
+    pub fn loose_eq(&self, a: &Value, b: &Value) -> Result<bool, Error> {
+        Unsupported::check("loose_eq")?;
+
+        self.check_bool(unsafe { ffi::JS_LooseEq(self.as_ptr(), a.raw(), b.raw()) })
+    }
+
+    /// Returns the result of `Object.is(a, b)`.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched.
+    pub fn same_value(&self, a: &Value, b: &Value) -> Result<bool, Error> {
+        Unsupported::check("same_value")?;
+
+        Ok(unsafe { ffi::JS_SameValue(self.as_ptr(), a.raw(), b.raw()) }.to_bool())
+    }
+
+    /// Compare the values structurally, the own enumerable properties of the objects are compared recursively.
+    pub fn deep_eq(&self, a: &Value, b: &Value, options: &EqualOptions) -> Result<bool, Error> {
+        Comparator {
+            ctxt: self,
+            options,
+            seen: vec![],
+        }
+        .equals(&self.clone_value(a), &self.clone_value(b))
+    }
+}
+
+struct Comparator<'a> {
+    ctxt: &'a ContextRef,
+    options: &'a EqualOptions,
+    seen: Vec<(usize, usize)>,
+}
+
+impl<'a> Comparator<'a> {
+    fn equals(&mut self, a: &Local<Value>, b: &Local<Value>) -> Result<bool, Error> {
+        if !a.is_object() || !b.is_object() {
+            return if self.options.strict {
+                self.ctxt.same_value(a, b)
+            } else {
+                self.ctxt.loose_eq(a, b)
+            };
+        }
+
+        if self.ctxt.strict_eq(a, b)? {
+            return Ok(true);
+        }
+
+        let pair = (
+            a.as_object().unwrap().as_ptr() as usize,
+            b.as_object().unwrap().as_ptr() as usize,
+        );
+
+        // the objects being compared are assumed to be equal for the circular references
+        if self.seen.contains(&pair) {
+            return Ok(true);
+        }
+
+        if a.is_array()? != b.is_array()? || a.is_function() != b.is_function() {
+            return Ok(false);
+        }
+
+        if a.is_function() {
+            return Ok(false);
+        }
+
+        if self.options.prototypes {
+            let proto_a = unsafe { ffi::JS_GetPrototype(self.ctxt.as_ptr(), a.raw()) };
+            let proto_b = unsafe { ffi::JS_GetPrototype(self.ctxt.as_ptr(), b.raw()) };
+
+            if !self
+                .ctxt
+                .strict_eq(&Value::from(proto_a), &Value::from(proto_b))?
+            {
+                return Ok(false);
+            }
+        }
+
+        let tag = self.builtin_tag(a)?;
+
+        if tag != self.builtin_tag(b)? {
+            return Ok(false);
+        }
+
+        if BOXED_CLASSES.contains(&tag.as_str()) {
+            let (a, b) = if tag == "RegExp" {
+                (a.to_str(), b.to_str())
+            } else {
+                (a.invoke("valueOf", ())?, b.invoke("valueOf", ())?)
+            };
+
+            if !self.ctxt.same_value(&a, &b)? {
+                return Ok(false);
+            }
+        }
+
+        let keys_a = self.own_keys(a)?;
+        let keys_b = self.own_keys(b)?;
+
+        if keys_a.len() != keys_b.len()
+            || keys_a
+                .iter()
+                .any(|key| !keys_b.iter().any(|k| **k == **key))
+        {
+            return Ok(false);
+        }
+
+        self.seen.push(pair);
+
+        let mut res = Ok(true);
+
+        for key in &keys_a {
+            let ctxt = self.ctxt;
+            let v_a = ctxt
+                .get_property(a, key)
+                .unwrap_or_else(|| ctxt.undefined());
+            let v_b = ctxt
+                .get_property(b, key)
+                .unwrap_or_else(|| ctxt.undefined());
+
+            res = self.equals(&v_a, &v_b);
+
+            if res.as_ref().map_or(true, |eq| !eq) {
+                break;
+            }
+        }
+
+        self.seen.pop();
+
+        res
+    }
+
+    /// The own enumerable property names and symbols of the object.
+    fn own_keys(&self, value: &Local<Value>) -> Result<Vec<Atom<'a>>, Error> {
+        Ok(self
+            .ctxt
+            .get_own_property_names(value, Names::STRING | Names::SYMBOL | Names::ENUM_ONLY)?
+            .unwrap_or_default())
+    }
+
+    /// The `[[Class]]` tag of the object from `Object.prototype.toString`.
+    fn builtin_tag(&self, value: &Local<Value>) -> Result<String, Error> {
+        let ctxt = self.ctxt;
+        let to_string = ctxt
+            .get_property(&ctxt.global_object(), "Object")
+            .and_then(|object| ctxt.get_property(&object, "prototype"))
+            .and_then(|proto| ctxt.get_property(&proto, "toString"))
+            .ok_or_else(|| failure::err_msg("missing `Object.prototype.toString`"))?;
+
+        let tag = ctxt.call(&to_string, Some(value), ())?.to_string();
+
+        Ok(tag
+            .trim_start_matches("[object ")
+            .trim_end_matches(']')
+            .to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn equality() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let eval = |s: &str| ctxt.eval_script(s, "<eval>", Eval::GLOBAL).unwrap();
+
+        let one = eval("1");
+        let one_str = eval("'1'");
+        let nan = eval("NaN");
+        let zero = eval("0");
+        let neg_zero = eval("-0");
+
+        assert!(one.strict_eq(&eval("1.0")).unwrap());
+        assert!(!one.strict_eq(&one_str).unwrap());
+        assert!(one.loose_eq(&one_str).unwrap());
+        assert!(eval("null").loose_eq(&eval("undefined")).unwrap());
+        assert!(eval("({ valueOf() { return 1 } })").loose_eq(&one).unwrap());
+        assert!(eval("({ valueOf() { throw new Error('boom') } })")
+            .loose_eq(&one)
+            .is_err());
+
+        assert!(!nan.strict_eq(&nan).unwrap());
+        assert!(nan.same_value(&nan).unwrap());
+        assert!(zero.strict_eq(&neg_zero).unwrap());
+        assert!(!zero.same_value(&neg_zero).unwrap());
+
+        let options = EqualOptions::new();
+        let deep_eq =
+            |a: &str, b: &str, options: &EqualOptions| eval(a).deep_eq(&eval(b), options).unwrap();
+
+        assert!(deep_eq(
+            "[1, [2, { a: 3 }]]",
+            "[1, [2, { a: 3 }]]",
+            &options
+        ));
+        assert!(!deep_eq("[1, 2]", "({ 0: 1, 1: 2 })", &options));
+        assert!(!deep_eq("({ a: 1 })", "({ a: 1, b: 2 })", &options));
+        assert!(!deep_eq("({ a: 1 })", "({ b: 1 })", &options));
+        assert!(deep_eq("({ a: NaN })", "({ a: NaN })", &options));
+        assert!(!deep_eq("({ a: 1 })", "({ a: '1' })", &options));
+        assert!(deep_eq(
+            "({ a: 1 })",
+            "({ a: '1' })",
+            &EqualOptions::new().with_strict(false)
+        ));
+        assert!(deep_eq("new Date(0)", "new Date(0)", &options));
+        assert!(!deep_eq("new Date(0)", "new Date(1)", &options));
+        assert!(!deep_eq("/a/g", "/a/i", &options));
+        assert!(deep_eq("class A {}; new A()", "({})", &options));
+        assert!(!deep_eq(
+            "class B {}; new B()",
+            "({})",
+            &EqualOptions::new().with_prototypes(true)
+        ));
+        assert!(deep_eq(
+            "var x = [1]; x.push(x); x",
+            "var y = [1]; y.push([1, y]); y",
+            &options
+        ));
+    }
+}
@@ -1,6 +1,10 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::RefCell;
 use std::convert::TryFrom;
 use std::ffi::CString;
+use std::fmt;
 use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
 
 use failure::{err_msg, Error};
 use foreign_types::ForeignTypeRef;
@@ -8,15 +12,27 @@ use foreign_types::ForeignTypeRef;
 use crate::{
     ffi,
     value::{ToBool, ERR},
-    ContextRef, Local, NewValue, Prop, Value,
+    ContextRef, HostError, Local, NewValue, Prop, RuntimeMismatch, Value,
 };
 
+const STACK_OVERFLOW: &str = "stack overflow";
+const INTERRUPTED: &str = "interrupted";
+const OUT_OF_MEMORY: &str = "out of memory";
+const CONVERSION_FAILED: &str = "conversion failed";
+
 /// Javascript error.
+///
+/// The Javascript stack of the exception is kept in the error,
+/// and the Rust backtrace where the exception crossed into Rust could be got from `failure::Error::backtrace`.
 #[derive(Debug, Clone, Fail, PartialEq)]
 pub enum ErrorKind {
     #[fail(display = "Throw: {}", _0)]
     Throw(String),
 
+    /// a thrown object which is not an `Error`, with the original value.
+    #[fail(display = "Throw: {}", _0)]
+    Exception(Exception),
+
     #[fail(display = "Error: {}", _0)]
     Error(String, Option<String>),
 
@@ -31,6 +47,27 @@ pub enum ErrorKind {
     #[fail(display = "InternalError: {}", _0)]
     InternalError(String, Option<String>),
 
+    /// an error that occurs when the system stack size exceeded, with the partial backtrace.
+    #[fail(display = "InternalError: stack overflow")]
+    StackOverflow(Option<String>),
+
+    /// an error that occurs when the execution was interrupted by the interrupt handler, with the partial backtrace.
+    #[fail(display = "InternalError: interrupted")]
+    Interrupted(Option<String>),
+
+    /// an error that occurs when the memory limit exceeded.
+    #[fail(display = "InternalError: out of memory")]
+    OutOfMemory,
+
+    /// an error that occurs when a value can't be converted to the expected Rust type.
+    #[fail(display = "ConversionError: expected {}, found {}", expected, found)]
+    Conversion {
+        /// the expected Rust type
+        expected: &'static str,
+        /// the type of Javascript value
+        found: &'static str,
+    },
+
     /// an error that occurs when a numeric variable or parameter is outside of its valid range.
     #[fail(display = "RangeError: {}", _0)]
     RangeError(String, Option<String>),
@@ -52,11 +89,149 @@ pub enum ErrorKind {
     URIError(String, Option<String>),
 }
 
+/// The function requires the patches of the bundled QuickJS, which are not applied to the linked library.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+#[fail(
+    display = "`{}` is not supported by the unpatched QuickJS library",
+    function
+)]
+pub struct Unsupported {
+    /// the name of the unsupported function
+    pub function: &'static str,
+}
+
+impl Unsupported {
+    /// Returns an error unless the linked library is patched.
+    pub(crate) fn check(function: &'static str) -> Result<(), Unsupported> {
+        if ffi::PATCHED {
+            Ok(())
+        } else {
+            Err(Unsupported { function })
+        }
+    }
+}
+
+/// The thrown value owned by `ErrorKind::Exception`.
+///
+/// The value is retained by the context which threw it until the last clone dropped or the context freed,
+/// so the error could be sent to other threads, but the value is only accessible in the context.
+#[derive(Clone)]
+pub struct Exception(Arc<Retained>);
+
+struct Retained {
+    ctx: usize,
+    desc: String,
+    value: Mutex<Option<RawValue>>,
+}
+
+struct RawValue(Value);
+
+unsafe impl Send for RawValue {}
+
+impl fmt::Debug for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Exception").field(&self.0.desc).finish()
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0.desc)
+    }
+}
+
+impl PartialEq for Exception {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Exception {
+    fn retain(value: Local<Value>) -> Exception {
+        let ctxt = value.context();
+        let retained = Arc::new(Retained {
+            ctx: ctxt.as_ptr() as usize,
+            desc: value.to_string(),
+            value: Mutex::new(Some(RawValue(value.into_inner_untracked()))),
+        });
+
+        ctxt.state::<Exceptions>().retain(ctxt, retained.clone());
+
+        Exception(retained)
+    }
+
+    /// The string representation of the thrown value.
+    pub fn message(&self) -> &str {
+        self.0.desc.as_str()
+    }
+
+    /// Returns the thrown value, or `None` if the context is not the one which threw it, or it has been freed.
+    pub fn value<'a>(&self, ctxt: &'a ContextRef) -> Option<Local<'a, Value>> {
+        if self.0.ctx != ctxt.as_ptr() as usize {
+            return None;
+        }
+
+        self.0
+            .value
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|RawValue(value)| ctxt.clone_value(value))
+    }
+}
+
+/// The thrown values retained by `Exception`, which are freed when no longer referenced.
+#[derive(Default)]
+struct Exceptions(RefCell<Vec<Arc<Retained>>>);
+
+impl Exceptions {
+    fn retain(&self, ctxt: &ContextRef, retained: Arc<Retained>) {
+        let mut exceptions = self.0.borrow_mut();
+
+        // only the state holds the unreferenced values, which can't be cloned by others
+        exceptions.retain(|retained| {
+            if Arc::strong_count(retained) > 1 {
+                true
+            } else {
+                Exceptions::free(ctxt, retained);
+
+                false
+            }
+        });
+        exceptions.push(retained);
+    }
+
+    fn free(ctxt: &ContextRef, retained: &Retained) {
+        if let Some(RawValue(value)) = retained.value.lock().unwrap().take() {
+            ctxt.free_value(value)
+        }
+    }
+}
+
+impl Drop for Exceptions {
+    fn drop(&mut self) {
+        for retained in self.0.get_mut().drain(..) {
+            let ctxt = unsafe { ContextRef::from_ptr(retained.ctx as *mut _) };
+
+            Exceptions::free(ctxt, &retained);
+        }
+    }
+}
+
 impl ErrorKind {
+    /// Returns the thrown value retained by `ErrorKind::Exception` in the context.
+    pub fn exception<'a>(&self, ctxt: &'a ContextRef) -> Option<Local<'a, Value>> {
+        match self {
+            ErrorKind::Exception(exc) => exc.value(ctxt),
+            _ => None,
+        }
+    }
+
     pub fn message(&self) -> &str {
         use ErrorKind::*;
 
         match self {
+            Exception(exc) => exc.message(),
             Throw(msg)
             | Error(msg, _)
             | Custom(_, msg, _)
@@ -67,6 +242,10 @@ impl ErrorKind {
             | SyntaxError(msg, _)
             | TypeError(msg, _)
             | URIError(msg, _) => msg.as_str(),
+            StackOverflow(_) => STACK_OVERFLOW,
+            Interrupted(_) => INTERRUPTED,
+            OutOfMemory => OUT_OF_MEMORY,
+            Conversion { .. } => CONVERSION_FAILED,
         }
     }
 
@@ -74,7 +253,7 @@ impl ErrorKind {
         use ErrorKind::*;
 
         match self {
-            Throw(_) => None,
+            Throw(_) | Exception(_) | OutOfMemory | Conversion { .. } => None,
             Error(_, ref stack)
             | Custom(_, _, ref stack)
             | EvalError(_, ref stack)
@@ -83,7 +262,9 @@ impl ErrorKind {
             | ReferenceError(_, ref stack)
             | SyntaxError(_, ref stack)
             | TypeError(_, ref stack)
-            | URIError(_, ref stack) => stack.as_ref().map(|s| s.as_str()),
+            | URIError(_, ref stack)
+            | StackOverflow(ref stack)
+            | Interrupted(ref stack) => stack.as_ref().map(|s| s.as_str()),
         }
     }
 }
@@ -109,6 +290,9 @@ impl TryFrom<Local<'_, Value>> for ErrorKind {
 
             match name.as_str() {
                 "EvalError" => EvalError(msg, stack),
+                "InternalError" if msg == STACK_OVERFLOW => StackOverflow(stack),
+                "InternalError" if msg == INTERRUPTED => Interrupted(stack),
+                "InternalError" if msg == OUT_OF_MEMORY => OutOfMemory,
                 "InternalError" => InternalError(msg, stack),
                 "RangeError" => RangeError(msg, stack),
                 "ReferenceError" => ReferenceError(msg, stack),
@@ -118,24 +302,36 @@ impl TryFrom<Local<'_, Value>> for ErrorKind {
                 "Error" => Error(msg, stack),
                 _ => Custom(name, msg, stack),
             }
+        } else if value.is_object() {
+            Exception(self::Exception::retain(value))
         } else {
             Throw(value.to_string())
         })
     }
 }
 
-impl NewValue for Result<Local<'_, Value>, Error> {
+impl<T: NewValue> NewValue for Result<T, Error> {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
         match self {
-            Ok(v) => v,
+            Ok(v) => return v.new_value(ctxt),
             Err(err) => match err.downcast::<ErrorKind>() {
-                Ok(err) => ctxt.throw(err),
-                Err(err) => ctxt.throw(err.to_string()),
+                Ok(err) => return err.new_value(ctxt),
+                Err(err) => match err.downcast::<HostError>() {
+                    Ok(err) => ctxt.throw_host_error(err),
+                    Err(err) => ctxt.throw_rust_error(&err),
+                },
             },
         }
-        .into_inner()
+        .into_inner_untracked()
         .raw()
     }
+
+    fn check_value(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        match self {
+            Ok(v) => v.check_value(ctxt),
+            Err(_) => Ok(()),
+        }
+    }
 }
 
 impl NewValue for ErrorKind {
@@ -144,17 +340,27 @@ impl NewValue for ErrorKind {
 
         match self {
             Throw(msg) => ctxt.throw(msg),
+            Exception(exc) => match exc.value(ctxt) {
+                Some(value) => ctxt.throw(value),
+                None => ctxt.throw(exc.message()),
+            },
             Error(msg, stack) => ctxt.throw_error(msg, stack),
             Custom(name, msg, stack) => ctxt.throw_custom_error(&name, msg, stack),
             EvalError(msg, stack) => ctxt.throw_custom_error("EvalError", msg, stack),
             InternalError(msg, _) => ctxt.throw_internal_error(msg),
+            StackOverflow(_) => ctxt.throw_internal_error(STACK_OVERFLOW),
+            Interrupted(_) => ctxt.throw_internal_error(INTERRUPTED),
+            OutOfMemory => ctxt.throw_out_of_memory(),
+            Conversion { expected, found } => {
+                ctxt.throw_type_error(format!("expected {}, found {}", expected, found))
+            }
             RangeError(msg, _) => ctxt.throw_range_error(msg),
             ReferenceError(msg, _) => ctxt.throw_reference_error(msg),
             SyntaxError(msg, _) => ctxt.throw_syntax_error(msg),
             TypeError(msg, _) => ctxt.throw_type_error(msg),
             URIError(msg, stack) => ctxt.throw_custom_error("URIError", msg, stack),
         }
-        .into_inner()
+        .into_inner_untracked()
         .raw()
     }
 }
@@ -162,6 +368,8 @@ impl NewValue for ErrorKind {
 impl<'a> Local<'a, Value> {
     pub fn ok(self) -> Result<Local<'a, Value>, Error> {
         if self.is_exception() {
+            self.ctxt.account_exception();
+
             let err = self.ctxt.take_exception()?;
 
             trace!("-> Err({:?})", err);
@@ -219,6 +427,46 @@ impl ContextRef {
         self.throw(err)
     }
 
+    /// Throw a Rust error as a Javascript `Error`.
+    ///
+    /// The Rust backtrace will be captured if `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` enabled,
+    /// and the Rust frames will be prepended to the Javascript stack of the `Error`.
+    pub fn throw_rust_error(&self, err: &Error) -> Local<Value> {
+        let backtrace = Backtrace::capture();
+        let err_obj = self.new_error();
+
+        err_obj
+            .define_property_value(
+                "message",
+                err.to_string(),
+                Prop::WRITABLE | Prop::CONFIGURABLE,
+            )
+            .expect("message");
+
+        if backtrace.status() == BacktraceStatus::Captured {
+            let js_stack = err_obj
+                .get_property("stack")
+                .and_then(|stack| stack.to_cstring())
+                .map(|stack| stack.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let rust_stack = backtrace
+                .to_string()
+                .lines()
+                .map(|line| format!("    at [rust] {}\n", line.trim()))
+                .collect::<String>();
+
+            err_obj
+                .define_property_value(
+                    "stack",
+                    rust_stack + &js_stack,
+                    Prop::WRITABLE | Prop::CONFIGURABLE,
+                )
+                .expect("stack");
+        }
+
+        self.throw(err_obj)
+    }
+
     pub fn throw_out_of_memory(&self) -> Local<Value> {
         self.bind(unsafe { ffi::JS_ThrowOutOfMemory(self.as_ptr()) })
     }
@@ -229,7 +477,10 @@ impl ContextRef {
         msg: T,
         stack: Option<String>,
     ) -> Local<Value> {
-        if let Some(ctor) = self.global_object().get_property(name) {
+        if let Some(ctor) = self
+            .error_class(name)
+            .or_else(|| self.get_property(&self.global_object(), name))
+        {
             match ctor.call_constructor(msg.to_string()) {
                 Ok(err) => {
                     if let Some(stack) = stack {
@@ -346,7 +597,7 @@ impl ContextRef {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Context, Eval, Runtime};
+    use crate::{Context, Eval, NewValue, Runtime, Value};
 
     use super::ErrorKind::{self, *};
 
@@ -383,7 +634,7 @@ mod tests {
                 .unwrap_err()
                 .downcast::<ErrorKind>()
                 .unwrap(),
-            InternalError("out of memory".into(), None)
+            OutOfMemory
         );
 
         assert_eq!(
@@ -526,4 +777,105 @@ class CustomError extends Error {
             Throw("123".into())
         );
     }
+
+    #[test]
+    fn throw_rust_error() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let f = ctxt
+            .new_c_function(
+                |ctxt, _this, _args| {
+                    ctxt.throw_rust_error(&format_err!("Whoops!"))
+                        .into_inner()
+                        .raw()
+                },
+                Some("f"),
+                0,
+            )
+            .unwrap();
+
+        ctxt.global_object().set_property("f", f).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "try { f() } catch (e) { e instanceof Error && e.message == 'Whoops!' }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+
+        let err = ctxt
+            .eval::<_, ()>("function g() { f() }; g()", Eval::GLOBAL)
+            .unwrap_err();
+
+        match err.downcast_ref::<ErrorKind>() {
+            Some(Error(msg, Some(stack))) => {
+                assert_eq!(msg, "Whoops!");
+                assert!(stack.contains("at g (<evalScript>)"), "{}", stack);
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn exception() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let ctxt2 = Context::new(&rt);
+
+        let err = ctxt
+            .eval::<_, ()>("throw { code: 42 }", Eval::GLOBAL)
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.to_string(), "Throw: [object Object]");
+        assert_eq!(err.message(), "[object Object]");
+        assert!(err.exception(&ctxt2).is_none());
+
+        let exc = err.exception(&ctxt).unwrap();
+
+        assert!(exc.is_object());
+        assert_eq!(ctxt.get_property(&exc, "code").unwrap(), 42);
+
+        // the error could be sent to another thread, and rethrown with the original value
+        let err = std::thread::spawn(move || err).join().unwrap();
+
+        assert!(Value::from(err.clone().new_value(&ctxt)).is_exception());
+
+        let thrown = ctxt.get_exception().unwrap();
+
+        assert_eq!(ctxt.get_property(&thrown, "code").unwrap(), 42);
+
+        // the primitive values are thrown as is
+        assert_eq!(
+            ctxt.eval::<_, ()>("throw 'oops'", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap(),
+            Throw("oops".into())
+        );
+
+        let null = ctxt
+            .eval_script("null", "<evalScript>", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(
+            null.extract::<()>()
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap(),
+            Conversion {
+                expected: "()",
+                found: "null"
+            }
+        );
+        assert_eq!(null.extract::<String>().unwrap(), "null");
+    }
 }
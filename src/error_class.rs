@@ -0,0 +1,319 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use failure::{err_msg, Error, Fail};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Eval, Local, NewValue, Prop, Value};
+
+/// The factory of the `Error` subclasses, the fields are assigned to the new instance.
+const ERROR_CLASS_FACTORY: &str = r#"
+(function (Base, name) {
+    const cls = class extends Base {
+        constructor(message, fields) {
+            super(message);
+            if (fields !== undefined) Object.assign(this, fields);
+        }
+    };
+    Object.defineProperty(cls, 'name', { value: name, configurable: true });
+    Object.defineProperty(cls.prototype, 'name', { value: name, writable: true, configurable: true });
+    return cls;
+})
+"#;
+
+type FieldValue = Box<dyn FnOnce(&ContextRef) -> ffi::JSValue + Send + Sync>;
+
+/// An error thrown by the host functions as an instance of the registered `Error` subclass.
+///
+/// The host function returns it as an error, and the script catches an instance of the subclass
+/// with the structured fields as its properties.
+pub struct HostError {
+    /// the name of the registered `Error` subclass
+    pub name: String,
+    /// the error message
+    pub message: String,
+    fields: Vec<(String, FieldValue)>,
+}
+
+impl HostError {
+    pub fn new<N: Into<String>, M: Into<String>>(name: N, message: M) -> Self {
+        HostError {
+            name: name.into(),
+            message: message.into(),
+            fields: vec![],
+        }
+    }
+
+    /// Add a field, which is assigned to the error object as a property.
+    pub fn with_field<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: NewValue + Send + Sync + 'static,
+    {
+        self.fields
+            .push((key.into(), Box::new(move |ctxt| value.new_value(ctxt))));
+        self
+    }
+
+    /// The names of the fields.
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(key, _)| key.as_str())
+    }
+}
+
+impl fmt::Debug for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HostError")
+            .field("name", &self.name)
+            .field("message", &self.message)
+            .field("fields", &self.field_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+impl Fail for HostError {}
+
+impl NewValue for HostError {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.throw_host_error(self).into_inner_untracked().raw()
+    }
+}
+
+/// The `Error` subclasses registered in the context.
+#[derive(Default)]
+struct ErrorClasses(RefCell<Option<(usize, HashMap<String, Value>)>>);
+
+unsafe impl Send for ErrorClasses {}
+
+impl Drop for ErrorClasses {
+    fn drop(&mut self) {
+        if let Some((ctx, classes)) = self.0.get_mut().take() {
+            let ctxt = unsafe { ContextRef::from_ptr(ctx as *mut _) };
+
+            for (_, class) in classes {
+                ctxt.free_value(class);
+            }
+        }
+    }
+}
+
+impl ContextRef {
+    /// Register a subclass of `Error` as a global class, which could be thrown by the host functions.
+    ///
+    /// The parent is a registered class or a builtin error class, e.g. `TypeError`, the default is `Error`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use failure::Error;
+    /// use qjs::{Context, ContextRef, Eval, HostError, Local, Runtime, Value};
+    ///
+    /// fn open(_ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Result<Local<'static, Value>, Error> {
+    ///     Err(HostError::new("PermissionError", "access denied").with_field("path", "/etc/passwd").into())
+    /// }
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.register_error_class("HostError", None).unwrap();
+    /// ctxt.register_error_class("PermissionError", Some("HostError")).unwrap();
+    /// ctxt.global_object().set_property("open", ctxt.new_c_function(open, Some("open"), 1).unwrap()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>(
+    ///         "try { open() } catch (e) { if (e instanceof PermissionError && e instanceof HostError) `${e.name} ${e.path}` }",
+    ///         Eval::GLOBAL,
+    ///     ).unwrap().unwrap(),
+    ///     "PermissionError /etc/passwd"
+    /// );
+    /// ```
+    pub fn register_error_class(
+        &self,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Result<Local<Value>, Error> {
+        let parent = parent.unwrap_or("Error");
+        let base = self
+            .error_class(parent)
+            .or_else(|| {
+                self.get_property(&self.global_object(), parent)
+                    .filter(|ctor| ctor.is_constructor())
+            })
+            .ok_or_else(|| err_msg(format!("error class `{}` not found", parent)))?;
+        let factory = self.eval_script(
+            ERROR_CLASS_FACTORY,
+            "<error_class>",
+            Eval::GLOBAL | Eval::STRICT,
+        )?;
+        let class = self.call(&factory, None, (&base, name))?;
+
+        self.global_object().define_property_value(
+            name,
+            &class,
+            Prop::WRITABLE | Prop::CONFIGURABLE,
+        )?;
+
+        debug!(
+            "{:?} register error class `{}` extends `{}`",
+            self, name, parent
+        );
+
+        let classes = self.state::<ErrorClasses>();
+        let mut classes = classes.0.borrow_mut();
+        let (_, classes) = classes.get_or_insert_with(|| (self.as_ptr() as usize, HashMap::new()));
+
+        if let Some(class) = classes.insert(
+            name.to_owned(),
+            self.clone_value(&class).into_inner_untracked(),
+        ) {
+            self.free_value(class);
+        }
+
+        Ok(class)
+    }
+
+    /// Returns the registered `Error` subclass.
+    pub fn error_class(&self, name: &str) -> Option<Local<Value>> {
+        self.state::<ErrorClasses>()
+            .0
+            .borrow()
+            .as_ref()
+            .and_then(|(_, classes)| classes.get(name))
+            .map(|class| self.clone_value(class))
+    }
+
+    /// Create an instance of the registered `Error` subclass.
+    pub fn new_host_error(&self, err: HostError) -> Result<Local<Value>, Error> {
+        let class = self
+            .error_class(&err.name)
+            .ok_or_else(|| err_msg(format!("error class `{}` not registered", err.name)))?;
+        let fields = self.bind(self.new_object());
+
+        for (key, value) in err.fields {
+            fields.set_property(key.as_str(), self.bind(value(self)))?;
+        }
+
+        self.call_constructor(&class, (err.message.as_str(), fields))
+    }
+
+    /// Throw an instance of the registered `Error` subclass.
+    pub fn throw_host_error(&self, err: HostError) -> Local<Value> {
+        match self.new_host_error(err) {
+            Ok(err) => self.throw(err),
+            Err(err) => self.throw_rust_error(&err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{Context, ErrorKind, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn error_class() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let host_error = ctxt.register_error_class("HostError", None).unwrap();
+
+        ctxt.register_error_class("PermissionError", Some("HostError"))
+            .unwrap();
+        ctxt.register_error_class("QuotaError", Some("RangeError"))
+            .unwrap();
+
+        assert!(ctxt
+            .register_error_class("Broken", Some("Missing"))
+            .is_err());
+
+        let err = ctxt
+            .new_host_error(
+                HostError::new("PermissionError", "denied")
+                    .with_field("path", "/tmp")
+                    .with_field("code", 13),
+            )
+            .unwrap();
+
+        assert!(err.is_error());
+        assert!(err.instance_of(&host_error).unwrap());
+        assert_eq!(err.get_property("path").unwrap().to_string(), "/tmp");
+        assert_eq!(err.get_property("code").unwrap().as_int(), Some(13));
+        match ErrorKind::try_from(err).unwrap() {
+            ErrorKind::Custom(name, msg, _) => {
+                assert_eq!(name, "PermissionError");
+                assert_eq!(msg, "denied");
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        assert!(ctxt
+            .new_host_error(HostError::new("Unknown", "oops"))
+            .is_err());
+
+        // the scripts see the classes, and the errors are thrown from Rust
+        let check = ctxt
+            .eval_script(
+                r#"
+(f) => {
+    try {
+        f();
+    } catch (e) {
+        return [
+            e instanceof PermissionError,
+            e instanceof HostError,
+            e instanceof Error,
+            e.name,
+            e.message,
+            e.code,
+            String(e),
+        ].join();
+    }
+}
+"#,
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+        fn denied(
+            _ctxt: &ContextRef,
+            _this: Option<&Value>,
+            _args: &[Value],
+        ) -> Result<Local<'static, Value>, Error> {
+            Err(HostError::new("PermissionError", "denied")
+                .with_field("code", 13)
+                .into())
+        }
+
+        let f = ctxt.new_c_function(denied, Some("denied"), 0).unwrap();
+
+        assert_eq!(
+            check.call(None, [&f]).unwrap().to_string(),
+            "true,true,true,PermissionError,denied,13,PermissionError: denied"
+        );
+
+        let quota = ctxt
+            .eval_script("new QuotaError('full')", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert!(quota
+            .instance_of(&ctxt.global_object().get_property("RangeError").unwrap())
+            .unwrap());
+        assert_eq!(
+            quota.get_property("name").unwrap().to_string(),
+            "QuotaError"
+        );
+    }
+}
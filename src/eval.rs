@@ -6,7 +6,10 @@ use std::path::Path;
 use failure::{Error, ResultExt};
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, Context, ContextRef, ExtractValue, Local, ReadObj, Runtime, Value};
+use crate::{
+    codegen, ffi, Context, ContextRef, ExceptionOrigin, ExtractValue, Local, NewValue, ReadObj,
+    Runtime, Value,
+};
 
 bitflags! {
     /// Flags for `eval` method.
@@ -115,12 +118,15 @@ pub fn eval<T: Source, V: ExtractValue>(source: T) -> Result<Option<V>, Error> {
     let rt = Runtime::new();
     let ctxt = Context::new(&rt);
 
-    rt.set_module_loader::<()>(None, Some(ffi::js_module_loader), None);
+    #[cfg(all(feature = "stdlib", not(target_os = "wasi")))]
+    {
+        rt.set_module_loader::<()>(None, Some(ffi::js_module_loader), None);
 
-    ctxt.std_add_helpers::<_, String>(None)?;
+        ctxt.std_add_helpers::<_, String>(None)?;
 
-    ctxt.init_module_std()?;
-    ctxt.init_module_os()?;
+        ctxt.init_module_std()?;
+        ctxt.init_module_os()?;
+    }
 
     if cfg!(feature = "qjscalc") {
         ctxt.eval_binary(&*ffi::QJSCALC, false)?;
@@ -134,6 +140,7 @@ pub fn eval<T: Source, V: ExtractValue>(source: T) -> Result<Option<V>, Error> {
         }
     });
 
+    #[cfg(all(feature = "stdlib", not(target_os = "wasi")))]
     rt.std_free_handlers();
 
     res
@@ -155,13 +162,13 @@ impl ContextRef {
         source: T,
         flags: T::Flags,
     ) -> Result<Option<V>, Error> {
-        source.eval(self, flags).map(|v| {
-            if v.is_undefined() {
-                None
-            } else {
-                V::extract_value(&v)
-            }
-        })
+        let v = source.eval(self, flags)?;
+
+        if v.is_undefined() {
+            Ok(None)
+        } else {
+            self.extract_within_limits(&v)
+        }
     }
 
     /// Evaluate a script or module source.
@@ -171,6 +178,20 @@ impl ContextRef {
         filename: &str,
         flags: Eval,
     ) -> Result<Local<Value>, Error> {
+        if self.is_code_generation_disabled()
+            && codegen::is_module(flags)
+            && !flags.contains(Eval::COMPILE_ONLY)
+        {
+            // resolve the imported modules before running the module,
+            // the module loader is disallowed during the execution.
+            let module = self.eval_script(input, filename, flags | Eval::COMPILE_ONLY)?;
+
+            return self.eval_function(module);
+        }
+
+        instrument!("qjs::eval", DEBUG, "eval_script", filename, ?flags);
+
+        let input = self.transform_source(input.into(), filename)?;
         let input = CString::new(input).context("input")?;
 
         trace!(
@@ -182,17 +203,32 @@ impl ContextRef {
 
         let input = input.to_bytes_with_nul();
         let filename = CString::new(filename).context("filename")?;
+        let _evaluation = self.start_evaluation();
+        let _loading = if flags.contains(Eval::COMPILE_ONLY) {
+            Some(self.start_module_loading())
+        } else {
+            None
+        };
+
+        let res = self
+            .bind(unsafe {
+                ffi::JS_Eval(
+                    self.as_ptr(),
+                    input.as_ptr() as *const _,
+                    input.len() - 1,
+                    filename.as_ptr() as *const _,
+                    flags.bits as i32,
+                )
+            })
+            .ok();
+
+        if let Err(ref err) = res {
+            if codegen::is_module(flags) && !flags.contains(Eval::COMPILE_ONLY) {
+                self.handle_exception(ExceptionOrigin::Module, err);
+            }
+        }
 
-        self.bind(unsafe {
-            ffi::JS_Eval(
-                self.as_ptr(),
-                input.as_ptr() as *const _,
-                input.len() - 1,
-                filename.as_ptr() as *const _,
-                flags.bits as i32,
-            )
-        })
-        .ok()
+        res.and_then(|value| self.check_rejections().map(|_| value))
     }
 
     /// Evaluate a script or module source in file.
@@ -204,6 +240,14 @@ impl ContextRef {
 
     /// Evaluate a script or module source in bytecode.
     pub fn eval_binary(&self, buf: &[u8], load_only: bool) -> Result<(Local<Value>), Error> {
+        instrument!(
+            "qjs::eval",
+            DEBUG,
+            "eval_binary",
+            len = buf.len(),
+            load_only
+        );
+
         trace!(
             "eval {} bytes function{}",
             buf.len(),
@@ -248,6 +292,74 @@ impl ContextRef {
         })
         .ok()
     }
+
+    /// Evaluate a script with the temporary bindings, which are only visible to this evaluation.
+    ///
+    /// The script is evaluated with a direct `eval` in a wrapper function,
+    /// the bindings are its parameters and the `var` declarations are local to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// assert_eq!(ctxt.eval_with::<i32, _>("var z = x * y; z", &[("x", 6), ("y", 7)]).unwrap(), Some(42));
+    /// assert_eq!(ctxt.eval_with::<String, i32>("typeof x + typeof z", &[]).unwrap().unwrap(), "undefinedundefined");
+    /// ```
+    pub fn eval_with<V: ExtractValue, B: NewValue + Clone>(
+        &self,
+        source: &str,
+        bindings: &[(&str, B)],
+    ) -> Result<Option<V>, Error> {
+        for (name, _) in bindings {
+            if !is_binding_name(name) {
+                bail!("invalid binding name `{}`", name)
+            }
+        }
+
+        let params = bindings
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let wrapper = self.eval_script(
+            format!(
+                "(function ({}) {{ return eval(arguments[{}]); }})",
+                params,
+                bindings.len()
+            ),
+            "<eval_with>",
+            Eval::GLOBAL,
+        )?;
+        let mut args = bindings
+            .iter()
+            .map(|(_, value)| self.new_value(value.clone()).raw())
+            .collect::<Vec<_>>();
+
+        args.push(self.new_value(source).raw());
+
+        self.call(&wrapper, None, args).map(|v| {
+            if v.is_undefined() {
+                None
+            } else {
+                V::extract_value(&v)
+            }
+        })
+    }
+}
+
+/// The binding name should be an identifier which could be declared as a parameter.
+fn is_binding_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .map_or(false, |c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        && !["arguments", "eval"].contains(&name)
 }
 
 #[cfg(test)]
@@ -339,4 +451,56 @@ mod tests {
         assert_eq!(obj.get_property("age").unwrap().to_int32().unwrap(), 30);
         assert_eq!(obj.get_property("city").unwrap().to_string(), "New York");
     }
+
+    #[test]
+    fn eval_with() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let obj = ctxt
+            .eval_script("({ n: 1 })", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval_with::<i32, _>(
+                "o.n += 1; var local = o.n; let l = local; l",
+                &[("o", &obj)]
+            )
+            .unwrap(),
+            Some(2)
+        );
+        assert_eq!(obj.get_property("n").unwrap().as_int(), Some(2));
+
+        // the bindings and declarations are not leaked to the global scope
+        for name in &["o", "local", "l"] {
+            assert_eq!(
+                ctxt.eval::<_, String>(format!("typeof {}", name).as_str(), Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                "undefined"
+            );
+        }
+
+        // the bindings shadow the globals
+        ctxt.eval::<_, ()>("var x = 'global'", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval_with::<String, _>("x", &[("x", "local")])
+                .unwrap()
+                .unwrap(),
+            "local"
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>("x", Eval::GLOBAL).unwrap().unwrap(),
+            "global"
+        );
+
+        assert!(ctxt.eval_with::<(), _>("1", &[("a b", 1)]).is_err());
+        assert!(ctxt.eval_with::<(), _>("1", &[("eval", 1)]).is_err());
+        assert!(ctxt
+            .eval_with::<(), _>("syntax error", &[("x", 1)])
+            .is_err());
+    }
 }
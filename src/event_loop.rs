@@ -0,0 +1,813 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::os::raw::c_int;
+use std::slice;
+use std::thread;
+use std::time::Duration;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    ffi, Capabilities, Clock, ContextRef, ErrorKind, NewValue, RuntimeRef, SystemClock, Value,
+    UNDEFINED,
+};
+
+/// The number of the slots in the timer wheel, each slot covers one millisecond.
+pub const TIMER_WHEEL_SLOTS: usize = 512;
+
+/// The default maximum number of the timers fired in a tick.
+pub const DEFAULT_MAX_TIMERS_PER_TICK: usize = 1000;
+
+/// The nesting level of the timers, above which the timeout is clamped.
+const MAX_TIMER_NESTING: u32 = 5;
+
+/// The minimum timeout of the deeply nested timers.
+const MIN_NESTED_TIMEOUT: u64 = 4;
+
+/// A timer registered by `setTimeout` or `setInterval`.
+struct Timer {
+    ctx: usize,
+    deadline: u64,
+    interval: Option<u64>,
+    nesting: u32,
+    callback: Value,
+    args: Vec<Value>,
+}
+
+impl Timer {
+    fn free(self) {
+        let ctxt = unsafe { ContextRef::from_ptr(self.ctx as *mut _) };
+
+        ctxt.free_value(self.callback);
+
+        for arg in self.args {
+            ctxt.free_value(arg);
+        }
+    }
+}
+
+/// The hashed timer wheel, the timers are hashed into the slots by their deadlines in milliseconds,
+/// so inserting and cancelling a timer cost O(1), and a tick only visits the elapsed slots.
+struct TimerWheel {
+    slots: Vec<Vec<u32>>,
+    timers: HashMap<u32, Timer>,
+    /// the last tick processed
+    current: u64,
+    /// the timers due but not fired yet, in the order of the deadline and registration
+    ready: VecDeque<u32>,
+}
+
+impl Default for TimerWheel {
+    fn default() -> Self {
+        TimerWheel {
+            slots: vec![vec![]; TIMER_WHEEL_SLOTS],
+            timers: HashMap::new(),
+            current: 0,
+            ready: VecDeque::new(),
+        }
+    }
+}
+
+impl TimerWheel {
+    fn insert(&mut self, id: u32, timer: Timer) {
+        if timer.deadline <= self.current {
+            // the timer is already due
+            self.ready.push_back(id);
+        } else {
+            self.slots[timer.deadline as usize % TIMER_WHEEL_SLOTS].push(id);
+        }
+
+        self.timers.insert(id, timer);
+    }
+
+    /// Advance the wheel to `now`, and move the due timers to the ready queue.
+    fn advance(&mut self, now: u64) {
+        if now <= self.current {
+            return;
+        }
+
+        let ticks = (now - self.current).min(TIMER_WHEEL_SLOTS as u64);
+        let mut due = vec![];
+
+        for tick in self.current + 1..=self.current + ticks {
+            let timers = &self.timers;
+
+            self.slots[tick as usize % TIMER_WHEEL_SLOTS].retain(|id| match timers.get(id) {
+                // the cancelled timers are dropped lazily
+                None => false,
+                Some(timer) if timer.deadline <= now => {
+                    due.push((timer.deadline, *id));
+                    false
+                }
+                Some(_) => true,
+            });
+        }
+
+        due.sort_unstable();
+
+        self.ready.extend(due.into_iter().map(|(_, id)| id));
+        self.current = now;
+    }
+
+    /// Returns the earliest deadline of the timers.
+    fn next_deadline(&self) -> Option<u64> {
+        if self.ready.iter().any(|id| self.timers.contains_key(id)) {
+            return Some(self.current);
+        }
+
+        let rotation = self.current + TIMER_WHEEL_SLOTS as u64;
+
+        (self.current + 1..=rotation)
+            .find_map(|tick| {
+                self.slots[tick as usize % TIMER_WHEEL_SLOTS]
+                    .iter()
+                    .flat_map(|id| self.timers.get(id))
+                    .map(|timer| timer.deadline)
+                    .filter(|&deadline| deadline <= rotation)
+                    .min()
+            })
+            .or_else(|| self.timers.values().map(|timer| timer.deadline).min())
+    }
+}
+
+/// The hook called by the event loop, the loop stops with the error returned by the hook.
+type Hook<F> = RefCell<Option<Box<F>>>;
+
+/// The state of the event loop in a runtime.
+struct LoopState {
+    wheel: RefCell<TimerWheel>,
+    clock: RefCell<Box<dyn Clock>>,
+    max_timers_per_tick: Cell<usize>,
+    next_id: Cell<u32>,
+    /// the nesting level of the timer being fired
+    nesting: Cell<u32>,
+    on_idle: Hook<dyn FnMut(Option<Duration>) -> Result<(), Error>>,
+    before_job: Hook<dyn FnMut() -> Result<(), Error>>,
+    after_tick: Hook<dyn FnMut(usize) -> Result<(), Error>>,
+}
+
+unsafe impl Send for LoopState {}
+
+impl Default for LoopState {
+    fn default() -> Self {
+        LoopState {
+            wheel: RefCell::new(TimerWheel::default()),
+            clock: RefCell::new(Box::new(SystemClock::new())),
+            max_timers_per_tick: Cell::new(DEFAULT_MAX_TIMERS_PER_TICK),
+            next_id: Cell::new(0),
+            nesting: Cell::new(0),
+            on_idle: RefCell::new(None),
+            before_job: RefCell::new(None),
+            after_tick: RefCell::new(None),
+        }
+    }
+}
+
+impl LoopState {
+    fn now(&self) -> u64 {
+        self.clock.borrow().elapsed().as_millis() as u64
+    }
+
+    /// Returns `false` if there is no `on_idle` hook.
+    fn idle(&self, timeout: Option<Duration>) -> Result<bool, Error> {
+        match self.on_idle.borrow_mut().as_mut() {
+            Some(hook) => hook(timeout).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    fn before_job(&self) -> Result<(), Error> {
+        self.before_job
+            .borrow_mut()
+            .as_mut()
+            .map_or(Ok(()), |hook| hook())
+    }
+
+    fn after_tick(&self, fired: usize) -> Result<(), Error> {
+        self.after_tick
+            .borrow_mut()
+            .as_mut()
+            .map_or(Ok(()), |hook| hook(fired))
+    }
+}
+
+/// The timers of a context are cancelled when the context freed.
+#[derive(Default)]
+struct ContextTimers {
+    ctx: Cell<usize>,
+    rt: Cell<usize>,
+}
+
+impl Drop for ContextTimers {
+    fn drop(&mut self) {
+        if self.rt.get() == 0 {
+            return;
+        }
+
+        let rt = unsafe { RuntimeRef::from_ptr(self.rt.get() as *mut _) };
+        let state = rt.state::<LoopState>();
+        let mut wheel = state.wheel.borrow_mut();
+        let ids = wheel
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.ctx == self.ctx.get())
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            if let Some(timer) = wheel.timers.remove(&id) {
+                timer.free();
+            }
+        }
+    }
+}
+
+/// The event loop of a runtime, which runs the microtasks and the timers in the order of the HTML spec.
+///
+/// The `setTimeout`, `setInterval`, `clearTimeout`, `clearInterval` and `queueMicrotask` functions
+/// are installed into the contexts. The timers are kept in a hashed timer wheel,
+/// and each tick fires the due timers in the order of their deadlines and registration,
+/// up to a limit, the microtasks (the pending jobs) are drained before the tick and after each timer.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, EventLoop, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let event_loop = EventLoop::new(&rt);
+///
+/// event_loop.install(&ctxt).unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     r#"
+/// var log = [];
+///
+/// setTimeout(() => log.push('timeout'), 0);
+/// Promise.resolve().then(() => log.push('promise'));
+/// queueMicrotask(() => log.push('microtask'));
+/// log.push('script');
+/// "#,
+///     Eval::GLOBAL,
+/// )
+/// .unwrap();
+///
+/// event_loop.run().unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("log.join()", Eval::GLOBAL).unwrap().unwrap(),
+///     "script,promise,microtask,timeout"
+/// );
+/// ```
+pub struct EventLoop<'a> {
+    rt: &'a RuntimeRef,
+}
+
+impl<'a> EventLoop<'a> {
+    pub fn new(rt: &'a RuntimeRef) -> Self {
+        EventLoop { rt }
+    }
+
+    /// Set the clock of the timers, the `SystemClock` is used by default.
+    pub fn with_clock<C: Clock + 'static>(self, clock: C) -> Self {
+        let state = self.state();
+
+        *state.clock.borrow_mut() = Box::new(clock);
+        state.wheel.borrow_mut().current = state.now();
+        self
+    }
+
+    /// Set the maximum number of the timers fired in a tick, the remaining due timers are fired in the next tick.
+    pub fn with_max_timers_per_tick(self, n: usize) -> Self {
+        self.state().max_timers_per_tick.set(n.max(1));
+        self
+    }
+
+    /// Set the hook called when the loop is idle, with the duration until the next timer is due,
+    /// or `None` if there are no pending timers and jobs.
+    ///
+    /// The hook is called instead of sleeping, so it may block on the channels of the embedder up to the duration,
+    /// and the loop keeps running if some timers or jobs were added by the hook.
+    pub fn on_idle<F>(self, hook: F) -> Self
+    where
+        F: FnMut(Option<Duration>) -> Result<(), Error> + 'static,
+    {
+        *self.state().on_idle.borrow_mut() = Some(Box::new(hook));
+        self
+    }
+
+    /// Set the hook called before each pending job or timer callback executed.
+    pub fn before_job<F>(self, hook: F) -> Self
+    where
+        F: FnMut() -> Result<(), Error> + 'static,
+    {
+        *self.state().before_job.borrow_mut() = Some(Box::new(hook));
+        self
+    }
+
+    /// Set the hook called after each tick, with the number of the timers fired in the tick.
+    pub fn after_tick<F>(self, hook: F) -> Self
+    where
+        F: FnMut(usize) -> Result<(), Error> + 'static,
+    {
+        *self.state().after_tick.borrow_mut() = Some(Box::new(hook));
+        self
+    }
+
+    fn state(&self) -> &LoopState {
+        self.rt.state::<LoopState>()
+    }
+
+    /// Install the timer functions and `queueMicrotask` into the global object of the context.
+    pub fn install(&self, ctxt: &ContextRef) -> Result<(), Error> {
+        let timers = ctxt.state::<ContextTimers>();
+
+        timers.ctx.set(ctxt.as_ptr() as usize);
+        timers.rt.set(self.rt.as_ptr() as usize);
+
+        let global = ctxt.global_object();
+
+        for &(name, func, length) in &[
+            (
+                "setTimeout",
+                set_timeout as fn(&ContextRef, Option<&Value>, &[Value]) -> Value,
+                2,
+            ),
+            ("setInterval", set_interval, 2),
+            ("clearTimeout", clear_timer, 1),
+            ("clearInterval", clear_timer, 1),
+        ] {
+            global.set_property(
+                name,
+                ctxt.new_c_function_with_capabilities(
+                    func,
+                    Some(name),
+                    length,
+                    Capabilities::TIME,
+                )?,
+            )?;
+        }
+
+        global.set_property(
+            "queueMicrotask",
+            ctxt.new_c_function(queue_microtask, Some("queueMicrotask"), 1)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the number of the pending timers.
+    pub fn pending_timers(&self) -> usize {
+        self.state().wheel.borrow().timers.len()
+    }
+
+    /// Returns `true` if there are the pending timers, jobs or background operations.
+    pub fn is_pending(&self) -> bool {
+        self.pending_timers() > 0 || self.rt.is_job_pending() || self.rt.pending_operations() > 0
+    }
+
+    /// Returns the duration until the next timer is due.
+    pub fn next_timeout(&self) -> Option<Duration> {
+        let state = self.state();
+        let now = state.now();
+
+        state
+            .wheel
+            .borrow()
+            .next_deadline()
+            .map(|deadline| Duration::from_millis(deadline.saturating_sub(now)))
+    }
+
+    /// Execute the pending jobs, the microtask queue is drained.
+    fn run_microtasks(&self) -> Result<(), Error> {
+        let state = self.state();
+
+        while self.rt.is_job_pending() {
+            state.before_job()?;
+
+            self.rt.execute_pending_job()?;
+        }
+
+        Ok(())
+    }
+
+    /// Run a tick of the event loop, returns the number of the timers fired.
+    ///
+    /// The microtasks are drained, and the notifications of the background operations are executed,
+    /// then the due timers are fired up to the limit, and the microtasks are drained after each timer,
+    /// the `after_tick` hook is called at last.
+    pub fn tick(&self) -> Result<usize, Error> {
+        self.run_microtasks()?;
+
+        if self.rt.poll_operations(Some(Duration::default()))? > 0 {
+            self.run_microtasks()?;
+        }
+
+        let state = self.state();
+
+        state.wheel.borrow_mut().advance(state.now());
+
+        let mut fired = 0;
+
+        while fired < state.max_timers_per_tick.get() {
+            let id = match state.wheel.borrow_mut().ready.pop_front() {
+                Some(id) => id,
+                None => break,
+            };
+
+            if self.fire(id)? {
+                fired += 1;
+
+                self.run_microtasks()?;
+            }
+        }
+
+        state.after_tick(fired)?;
+
+        Ok(fired)
+    }
+
+    /// Fire a timer, the interval timer is rescheduled before its callback called.
+    fn fire(&self, id: u32) -> Result<bool, Error> {
+        let state = self.state();
+        let now = state.now();
+
+        let (ctx, nesting, callback, args) = {
+            let mut wheel = state.wheel.borrow_mut();
+            let timer = match wheel.timers.remove(&id) {
+                Some(timer) => timer,
+                None => return Ok(false),
+            };
+
+            match timer.interval {
+                Some(interval) => {
+                    let ctxt = unsafe { ContextRef::from_ptr(timer.ctx as *mut _) };
+                    let fire = (
+                        timer.ctx,
+                        timer.nesting,
+                        ctxt.clone_value(&timer.callback).into_inner_untracked(),
+                        timer
+                            .args
+                            .iter()
+                            .map(|arg| ctxt.clone_value(arg).into_inner_untracked())
+                            .collect(),
+                    );
+
+                    wheel.insert(
+                        id,
+                        Timer {
+                            deadline: now + interval,
+                            ..timer
+                        },
+                    );
+
+                    fire
+                }
+                None => (timer.ctx, timer.nesting, timer.callback, timer.args),
+            }
+        };
+
+        let ctxt = unsafe { ContextRef::from_ptr(ctx as *mut _) };
+        let callback = ctxt.bind(callback);
+        let args = args
+            .into_iter()
+            .map(|arg| ctxt.bind(arg))
+            .collect::<Vec<_>>();
+
+        trace!("{:?} fire timer #{}", ctxt, id);
+
+        state.before_job()?;
+
+        let prev = state.nesting.replace(nesting);
+        let res = callback.call(None, args.iter().collect::<Vec<_>>().as_slice());
+
+        state.nesting.set(prev);
+
+        res.map(|_| true)
+    }
+
+    /// Run the event loop until there are no pending timers, jobs and background operations.
+    ///
+    /// The thread sleeps until the next timer is due, or the `on_idle` hook is called instead,
+    /// so the clock must advance by itself. It waits for the background operations instead if any is in progress.
+    pub fn run(&self) -> Result<(), Error> {
+        let state = self.state();
+
+        loop {
+            self.tick()?;
+
+            if !self.is_pending() {
+                if state.idle(None)? && self.is_pending() {
+                    continue;
+                }
+
+                break Ok(());
+            }
+
+            if self.rt.is_job_pending() {
+                continue;
+            }
+
+            // wake up when a background operation notifies, or the next timer is due
+            if self.rt.pending_operations() > 0 {
+                self.rt.poll_operations(self.next_timeout())?;
+
+                continue;
+            }
+
+            if let Some(timeout) = self
+                .next_timeout()
+                .filter(|timeout| *timeout > Duration::default())
+            {
+                if !state.idle(Some(timeout))? {
+                    thread::sleep(timeout);
+                }
+            }
+        }
+    }
+}
+
+fn add_timer(ctxt: &ContextRef, args: &[Value], repeat: bool) -> Value {
+    let callback = ctxt.clone_value(&args[0]);
+
+    if !callback.is_function() {
+        return ErrorKind::TypeError("callback is not a function".into(), None)
+            .new_value(ctxt)
+            .into();
+    }
+
+    let rt = ctxt.runtime();
+    let state = rt.state::<LoopState>();
+    let nesting = state.nesting.get();
+    let mut timeout = ctxt
+        .clone_value(&args[1])
+        .to_float64()
+        .filter(|timeout| timeout.is_finite() && *timeout > 0.0)
+        .map_or(0, |timeout| timeout.min(f64::from(i32::max_value())) as u64);
+
+    // the deeply nested timers are clamped as the HTML spec
+    if nesting > MAX_TIMER_NESTING && timeout < MIN_NESTED_TIMEOUT {
+        timeout = MIN_NESTED_TIMEOUT;
+    }
+
+    let id = match state.next_id.get() {
+        id if id < i32::max_value() as u32 => id + 1,
+        _ => 1,
+    };
+
+    state.next_id.set(id);
+
+    let timer = Timer {
+        ctx: ctxt.as_ptr() as usize,
+        deadline: state.now() + timeout,
+        interval: if repeat { Some(timeout.max(1)) } else { None },
+        nesting: nesting + 1,
+        callback: callback.into_inner_untracked(),
+        args: args[2..]
+            .iter()
+            .map(|arg| ctxt.clone_value(arg).into_inner_untracked())
+            .collect(),
+    };
+
+    trace!("{:?} add timer #{} after {}ms", ctxt, id, timeout);
+
+    state.wheel.borrow_mut().insert(id, timer);
+
+    ctxt.new_value(id as i32)
+}
+
+fn set_timeout(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    add_timer(ctxt, args, false)
+}
+
+fn set_interval(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    add_timer(ctxt, args, true)
+}
+
+fn clear_timer(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    if let Some(id) = ctxt.clone_value(&args[0]).to_int32() {
+        let rt = ctxt.runtime();
+        let state = rt.state::<LoopState>();
+        let mut wheel = state.wheel.borrow_mut();
+        let owned = wheel
+            .timers
+            .get(&(id as u32))
+            .map_or(false, |timer| timer.ctx == ctxt.as_ptr() as usize);
+
+        if owned {
+            if let Some(timer) = wheel.timers.remove(&(id as u32)) {
+                drop(wheel);
+
+                timer.free();
+            }
+        }
+    }
+
+    UNDEFINED
+}
+
+fn queue_microtask(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    if !ctxt.is_function(&args[0]) {
+        return ErrorKind::TypeError("callback is not a function".into(), None)
+            .new_value(ctxt)
+            .into();
+    }
+
+    let args = args[..1].iter().collect::<Vec<_>>();
+
+    match ctxt.enqueue_job(Some(microtask_job), args.as_slice()) {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+unsafe extern "C" fn microtask_job(
+    ctx: *mut ffi::JSContext,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let args = slice::from_raw_parts(argv as *const Value, argc as usize);
+
+        ctxt.call(&args[0], None, ()).new_value(ctxt)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use failure::err_msg;
+
+    use crate::{Context, Eval, ManualClock, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn event_loop() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let clock = ManualClock::default();
+        let event_loop = EventLoop::new(&rt)
+            .with_clock(clock.clone())
+            .with_max_timers_per_tick(2);
+
+        event_loop.install(&ctxt).unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var log = [];
+
+setTimeout((name) => log.push(name), 10, 'b');
+setTimeout(() => {
+    log.push('a');
+    Promise.resolve().then(() => log.push('a.then'));
+}, 5);
+setTimeout(() => log.push('c'), 10);
+var cancelled = setTimeout(() => log.push('cancelled'), 10);
+var ticks = 0;
+var interval = setInterval(() => { if (++ticks === 3) clearInterval(interval); }, 1000);
+clearTimeout(cancelled);
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let log = || {
+            ctxt.eval::<_, String>("log.join()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(event_loop.pending_timers(), 4);
+        assert_eq!(event_loop.next_timeout(), Some(Duration::from_millis(5)));
+        assert_eq!(event_loop.tick().unwrap(), 0);
+
+        clock.advance(Duration::from_millis(10));
+
+        // the timers fired in the order of deadline and registration, the microtasks run after each timer
+        assert_eq!(event_loop.tick().unwrap(), 2);
+        assert_eq!(log(), "a,a.then,b");
+        assert_eq!(event_loop.tick().unwrap(), 1);
+        assert_eq!(log(), "a,a.then,b,c");
+        assert_eq!(event_loop.next_timeout(), Some(Duration::from_millis(990)));
+
+        // the time jumps over the whole wheel
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(1));
+
+            assert_eq!(event_loop.tick().unwrap(), 1);
+        }
+
+        assert_eq!(ctxt.eval::<_, i32>("ticks", Eval::GLOBAL).unwrap(), Some(3));
+        assert_eq!(event_loop.pending_timers(), 0);
+        assert_eq!(event_loop.next_timeout(), None);
+
+        // many timers
+        ctxt.eval::<_, ()>(
+            "var fired = 0; for (let i = 0; i < 20000; i++) setTimeout(() => fired++, i % 3000);",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let event_loop = event_loop.with_max_timers_per_tick(usize::max_value());
+
+        clock.advance(Duration::from_secs(3));
+
+        assert_eq!(event_loop.tick().unwrap(), 20000);
+
+        // the nested timers are clamped
+        ctxt.eval::<_, ()>(
+            "var depth = 0; (function nest() { if (++depth < 10) setTimeout(nest, 0); })();",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        while event_loop.tick().unwrap() > 0 {}
+
+        assert_eq!(ctxt.eval::<_, i32>("depth", Eval::GLOBAL).unwrap(), Some(7));
+        assert_eq!(event_loop.next_timeout(), Some(Duration::from_millis(4)));
+
+        // the timers are cancelled when the context freed
+        drop(ctxt);
+
+        assert_eq!(event_loop.pending_timers(), 0);
+    }
+
+    #[test]
+    fn event_loop_hooks() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let clock = ManualClock::default();
+        let log = Rc::new(RefCell::new(vec![]));
+        let jobs = Rc::new(Cell::new(0));
+        let event_loop = EventLoop::new(&rt)
+            .with_clock(clock.clone())
+            .on_idle({
+                let log = log.clone();
+
+                move |timeout| {
+                    log.borrow_mut().push(format!("idle {:?}", timeout));
+
+                    // block on the embedder's channels
+                    if let Some(timeout) = timeout {
+                        clock.advance(timeout);
+                    }
+
+                    Ok(())
+                }
+            })
+            .before_job({
+                let jobs = jobs.clone();
+
+                move || {
+                    jobs.set(jobs.get() + 1);
+
+                    if jobs.get() > 3 {
+                        Err(err_msg("cancelled"))
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .after_tick({
+                let log = log.clone();
+
+                move |fired| {
+                    log.borrow_mut().push(format!("tick {}", fired));
+
+                    Ok(())
+                }
+            });
+
+        event_loop.install(&ctxt).unwrap();
+
+        ctxt.eval::<_, ()>(
+            "setTimeout(() => Promise.resolve().then(() => {}), 10); queueMicrotask(() => {});",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        event_loop.run().unwrap();
+
+        assert_eq!(jobs.get(), 3);
+        assert_eq!(
+            *log.borrow(),
+            vec!["tick 0", "idle Some(10ms)", "tick 1", "idle None"]
+        );
+
+        // the loop stops with the error of hook
+        ctxt.eval::<_, ()>("setTimeout(() => {}, 0);", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(event_loop.run().unwrap_err().to_string(), "cancelled");
+    }
+}
@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use failure::Error;
+
+use crate::{ContextRef, ErrorKind};
+
+/// Where an unhandled exception escaped from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExceptionOrigin {
+    /// a pending job, e.g. a promise reaction or a job enqueued by the host
+    Job,
+    /// the top-level evaluation of a module
+    Module,
+}
+
+type Handler = Rc<dyn Fn(&ContextRef, ExceptionOrigin, &ErrorKind)>;
+
+#[derive(Default)]
+struct ExceptionHandler(RefCell<Option<Handler>>);
+
+unsafe impl Send for ExceptionHandler {}
+
+impl ContextRef {
+    /// Set a callback which is invoked for the exceptions escaped from the jobs or the top-level module evaluation.
+    ///
+    /// The exceptions of the jobs are handled by the callback, `RuntimeRef::execute_pending_job` returns `Ok` for them,
+    /// the module evaluation still returns the exception to the caller after the callback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let errors = Rc::new(RefCell::new(vec![]));
+    /// let errs = errors.clone();
+    ///
+    /// ctxt.set_exception_handler(move |_ctxt, origin, err| {
+    ///     errs.borrow_mut().push(format!("{:?}: {}", origin, err))
+    /// });
+    ///
+    /// let _ = ctxt.eval_script("throw new TypeError('oops')", "main.mjs", Eval::MODULE);
+    ///
+    /// assert_eq!(*errors.borrow(), vec!["Module: TypeError: oops"]);
+    /// ```
+    pub fn set_exception_handler<F>(&self, handler: F)
+    where
+        F: Fn(&ContextRef, ExceptionOrigin, &ErrorKind) + 'static,
+    {
+        *self.state::<ExceptionHandler>().0.borrow_mut() = Some(Rc::new(handler));
+    }
+
+    /// Remove the callback of the unhandled exceptions.
+    pub fn clear_exception_handler(&self) {
+        self.state::<ExceptionHandler>().0.borrow_mut().take();
+    }
+
+    /// Invoke the exception handler, returns `true` if the exception was handled.
+    pub(crate) fn handle_exception(&self, origin: ExceptionOrigin, err: &Error) -> bool {
+        let handler = self.state::<ExceptionHandler>().0.borrow().clone();
+
+        match handler {
+            Some(handler) => {
+                let err = err
+                    .downcast_ref::<ErrorKind>()
+                    .cloned()
+                    .unwrap_or_else(|| ErrorKind::Throw(err.to_string()));
+
+                debug!("{:?} unhandled exception from {:?}, {}", self, origin, err);
+
+                handler(self, origin, &err);
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::os::raw::c_int;
+    use std::ptr;
+    use std::rc::Rc;
+
+    use crate::{ffi, Context, Eval, Runtime};
+
+    use super::*;
+
+    unsafe extern "C" fn call_job(
+        ctx: *mut ffi::JSContext,
+        _argc: c_int,
+        argv: *mut ffi::JSValue,
+    ) -> ffi::JSValue {
+        ffi::JS_Call(ctx, *argv, ffi::UNDEFINED, 0, ptr::null_mut())
+    }
+
+    #[test]
+    fn exception_handler() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let errors = Rc::new(RefCell::new(vec![]));
+
+        let enqueue = |source: &str| {
+            let func = ctxt.eval_script(source, "<eval>", Eval::GLOBAL).unwrap();
+
+            ctxt.enqueue_job(Some(call_job), &[func][..]).unwrap();
+        };
+
+        enqueue("() => { throw new Error('unseen') }");
+
+        assert!(rt.execute_pending_job().is_err());
+
+        let errs = errors.clone();
+
+        ctxt.set_exception_handler(move |_, origin, err| {
+            errs.borrow_mut().push((origin, err.message().to_owned()))
+        });
+
+        enqueue("() => { throw new Error('first') }");
+        enqueue("() => { throw 'second' }");
+
+        assert!(rt.execute_pending_job().unwrap().is_some());
+        assert!(rt.execute_pending_job().unwrap().is_some());
+        assert!(rt.execute_pending_job().unwrap().is_none());
+
+        assert!(ctxt
+            .eval_script("throw new RangeError('module')", "mod.mjs", Eval::MODULE)
+            .is_err());
+        assert!(ctxt
+            .eval_script("throw new RangeError('script')", "<eval>", Eval::GLOBAL)
+            .is_err());
+
+        assert_eq!(
+            *errors.borrow(),
+            vec![
+                (ExceptionOrigin::Job, "first".to_owned()),
+                (ExceptionOrigin::Job, "second".to_owned()),
+                (ExceptionOrigin::Module, "module".to_owned()),
+            ]
+        );
+
+        ctxt.clear_exception_handler();
+
+        enqueue("() => { throw new Error('again') }");
+
+        assert!(rt.execute_pending_job().is_err());
+        assert_eq!(errors.borrow().len(), 3);
+    }
+}
@@ -0,0 +1,578 @@
+use std::ops::Deref;
+
+use failure::Error;
+
+use crate::{
+    Atom, Context, ContextRef, ErrorKind, Eval, ExtractValue, Local, NewValue, RuntimeRef, Value,
+    NULL,
+};
+
+/// The keywords of the statements and declarations, which are not allowed in the expressions.
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "debugger",
+    "default", "delete", "do", "else", "export", "finally", "for", "function", "if", "import",
+    "let", "return", "switch", "throw", "try", "var", "while", "with", "yield",
+];
+
+/// The punctuators, the longer ones first.
+const PUNCTUATORS: &[&str] = &[
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "**", "<<", ">>", "{", "}", "(", ")", "[", "]", ";", ",", "<", ">", "+", "-", "*", "/", "%",
+    "&", "|", "^", "!", "~", "?", ":", "=", ".",
+];
+
+/// The assignment and update operators, which are not allowed in the expressions.
+const ASSIGNMENTS: &[&str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "**=", "<<=", ">>=", ">>>=", "&=", "|=", "^=", "&&=", "||=",
+    "??=", "++", "--",
+];
+
+/// A context which only evaluates the expressions, for the user-configurable formulas.
+///
+/// The expressions are validated when compiled, the statements, declarations, assignments
+/// and function definitions are rejected, and the code generation from strings is disabled.
+/// The host functions and variables could be exposed with the global object.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{ExpressionContext, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = ExpressionContext::new(&rt).unwrap();
+///
+/// ctxt.global_object().set_property("price", 30).unwrap();
+/// ctxt.global_object().set_property("qty", 3).unwrap();
+///
+/// assert_eq!(ctxt.eval_expr::<i32>("price * qty > 50 ? price * qty - 10 : price * qty").unwrap(), Some(80));
+///
+/// assert!(ctxt.compile("price = 0").is_err());
+/// assert!(ctxt.compile("var x = 1").is_err());
+/// assert!(ctxt.compile("(() => { while (true) {} })()").is_err());
+/// assert!(ctxt.eval_expr::<i32>("eval('price = 0')").is_err());
+/// ```
+pub struct ExpressionContext(Context);
+
+impl Deref for ExpressionContext {
+    type Target = ContextRef;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ExpressionContext {
+    pub fn new(rt: &RuntimeRef) -> Result<Self, Error> {
+        let ctxt = Context::new(rt);
+
+        ctxt.disable_code_generation()?;
+
+        Ok(ExpressionContext(ctxt))
+    }
+
+    /// Validate and compile an expression.
+    pub fn compile(&self, expr: &str) -> Result<CompiledExpr, Error> {
+        validate_expr(expr)?;
+
+        let func = self.eval_script(
+            format!("(function () {{ return (\n{}\n); }})", expr),
+            "<expr>",
+            Eval::GLOBAL | Eval::STRICT,
+        )?;
+        // `with` is not allowed in the strict mode, the expression was validated without assignments
+        let scoped = self.eval_script(
+            format!(
+                "(function (__scope__) {{ with (__scope__) {{ return (\n{}\n); }} }})",
+                expr
+            ),
+            "<expr>",
+            Eval::GLOBAL,
+        )?;
+
+        Ok(CompiledExpr { func, scoped })
+    }
+
+    /// Validate and evaluate an expression.
+    pub fn eval_expr<V: ExtractValue>(&self, expr: &str) -> Result<Option<V>, Error> {
+        self.compile(expr)?.eval()
+    }
+}
+
+/// A compiled expression, which could be evaluated many times.
+pub struct CompiledExpr<'a> {
+    func: Local<'a, Value>,
+    scoped: Local<'a, Value>,
+}
+
+impl CompiledExpr<'_> {
+    /// Evaluate the expression with the current global variables.
+    pub fn eval<V: ExtractValue>(&self) -> Result<Option<V>, Error> {
+        self.func.call(None, ()).map(|v| {
+            if v.is_undefined() {
+                None
+            } else {
+                V::extract_value(&v)
+            }
+        })
+    }
+
+    /// Evaluate the expression against each input, the fields of the input are the variables of the expression.
+    ///
+    /// A single scope object is reused for all the inputs, the field names are interned as atoms,
+    /// and the fields missing in the next input are removed from the scope.
+    /// The global variables are visible unless shadowed by the fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{ExpressionContext, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = ExpressionContext::new(&rt).unwrap();
+    ///
+    /// ctxt.global_object().set_property("limit", 100).unwrap();
+    ///
+    /// let expr = ctxt.compile("price * qty > limit").unwrap();
+    /// let rows = (1..=3).map(|qty| vec![("price", 40), ("qty", qty)]);
+    ///
+    /// let res: Vec<bool> = expr.eval_batch(rows).into_iter().collect::<Result<_, _>>().unwrap();
+    ///
+    /// assert_eq!(res, vec![false, false, true]);
+    /// ```
+    pub fn eval_batch<R, I, T, K, V>(&self, inputs: I) -> Vec<Result<R, Error>>
+    where
+        I: IntoIterator<Item = T>,
+        R: ExtractValue,
+        T: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: NewValue,
+    {
+        let ctxt = self.scoped.ctxt;
+        let scope = ctxt.bind(ctxt.new_object_proto(&NULL));
+        let mut fields: Vec<Atom> = vec![];
+
+        inputs
+            .into_iter()
+            .map(|input| {
+                let mut names = Vec::with_capacity(fields.len());
+
+                for (key, value) in input {
+                    let atom = ctxt.intern_atom(key.as_ref());
+
+                    scope.set_property(ctxt.clone_atom(*atom), value)?;
+                    names.push(atom);
+                }
+
+                for field in fields.drain(..) {
+                    if !names.iter().any(|name| **name == *field) {
+                        scope.delete_property(&field)?;
+                    }
+                }
+
+                fields = names;
+
+                self.scoped.call(None, [&scope])?.extract()
+            })
+            .collect()
+    }
+}
+
+/// Check the tokens of the expression, the syntax errors are left to the parser.
+fn validate_expr(expr: &str) -> Result<(), Error> {
+    let reject = |pos: usize, what: &str| -> Result<(), Error> {
+        let before = &expr[..pos];
+        let line = before
+            .replace("\r\n", "\n")
+            .matches(is_line_terminator)
+            .count()
+            + 1;
+        let column = before
+            .rsplit(is_line_terminator)
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+
+        Err(ErrorKind::SyntaxError(
+            format!(
+                "{} is not allowed in expression at {}:{}",
+                what, line, column
+            ),
+            None,
+        )
+        .into())
+    };
+
+    let bytes = expr.as_bytes();
+    let mut pos = 0;
+    let mut last = "";
+    // the brace depths where the template literals continue
+    let mut templates = vec![];
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+
+        match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                pos += 1;
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                pos = expr[pos..]
+                    .find(is_line_terminator)
+                    .map_or(bytes.len(), |n| pos + n);
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = expr[pos + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| pos + n + 4);
+                continue;
+            }
+            b'\'' | b'"' => {
+                pos = skip_string(bytes, pos + 1, c);
+                last = "string";
+            }
+            b'`' => {
+                pos = skip_template(bytes, pos + 1);
+
+                if bytes[..pos].ends_with(b"${") {
+                    templates.push(depth);
+                    depth += 1;
+                    last = "(";
+                } else {
+                    last = "string";
+                }
+            }
+            b'}' if depth > 0 && templates.last() == Some(&(depth - 1)) => {
+                templates.pop();
+                depth -= 1;
+                pos = skip_template(bytes, pos + 1);
+
+                if bytes[..pos].ends_with(b"${") {
+                    templates.push(depth);
+                    depth += 1;
+                    last = "(";
+                } else {
+                    last = "string";
+                }
+            }
+            b'/' if !is_operand(last) => {
+                pos = skip_regexp(bytes, pos + 1);
+                last = "string";
+            }
+            b'0'..=b'9' => {
+                pos += 1;
+
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric()
+                        || bytes[pos] == b'.'
+                        || bytes[pos] == b'_'
+                        || ((bytes[pos] == b'+' || bytes[pos] == b'-')
+                            && (bytes[pos - 1] == b'e' || bytes[pos - 1] == b'E')
+                            && !expr[start..pos].starts_with("0x")))
+                {
+                    pos += 1;
+                }
+
+                last = "number";
+            }
+            _ if c == b'$'
+                || c == b'_'
+                || c == b'\\'
+                || expr[pos..]
+                    .chars()
+                    .next()
+                    .map_or(false, char::is_alphabetic) =>
+            {
+                let end = expr[pos..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '$' || c == '_' || c == '\\'))
+                    .map_or(bytes.len(), |n| pos + n);
+                let word = &expr[pos..end];
+
+                // the keywords may be used as the property names
+                let is_property = last == "."
+                    || last == "?."
+                    || ((last == "{" || last == ",") && expr[end..].trim_start().starts_with(':'));
+
+                if !is_property && STATEMENT_KEYWORDS.contains(&word) {
+                    return reject(pos, &format!("`{}`", word));
+                }
+
+                pos = end;
+                last = "identifier";
+            }
+            _ => match PUNCTUATORS.iter().find(|p| expr[pos..].starts_with(*p)) {
+                Some(p) => {
+                    if ASSIGNMENTS.contains(p) {
+                        return reject(pos, &format!("assignment `{}`", p));
+                    }
+
+                    match *p {
+                        ";" => return reject(pos, "statement"),
+                        "=>" => return reject(pos, "function"),
+                        "{" if last == ")" => return reject(pos, "function body"),
+                        // the expression should not close the wrapper
+                        ")" | "]" if parens == 0 => {
+                            return reject(pos, &format!("unbalanced `{}`", p))
+                        }
+                        "(" | "[" => parens += 1,
+                        ")" | "]" => parens -= 1,
+                        "{" => depth += 1,
+                        "}" => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+
+                    pos += p.len();
+                    last = p;
+                }
+                None => {
+                    let c = expr[pos..].chars().next();
+
+                    pos += c.map_or(1, char::len_utf8);
+
+                    // the whitespaces and line terminators beyond ASCII keep the previous token
+                    if !c.map_or(false, is_whitespace) {
+                        last = "";
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// The line terminators of ECMAScript, which end the single line comments.
+fn is_line_terminator(c: char) -> bool {
+    c == '\n' || c == '\r' || c == '\u{2028}' || c == '\u{2029}'
+}
+
+/// The whitespaces and line terminators of ECMAScript.
+fn is_whitespace(c: char) -> bool {
+    c.is_whitespace() || c == '\u{feff}'
+}
+
+/// The previous token is an operand, so the `/` is a division instead of a regular expression.
+fn is_operand(last: &str) -> bool {
+    ["identifier", "number", "string", ")", "]", "}"].contains(&last)
+}
+
+fn skip_string(bytes: &[u8], mut pos: usize, quote: u8) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            c if c == quote => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+/// Skip the template literal until the end quote or the placeholder.
+fn skip_template(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'`' => return pos + 1,
+            b'$' if bytes.get(pos + 1) == Some(&b'{') => return pos + 2,
+            _ => pos += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+fn skip_regexp(bytes: &[u8], mut pos: usize) -> usize {
+    let mut class = false;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'[' => {
+                class = true;
+                pos += 1;
+            }
+            b']' => {
+                class = false;
+                pos += 1;
+            }
+            b'/' if !class => {
+                pos += 1;
+
+                while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+                    pos += 1;
+                }
+
+                return pos;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Runtime;
+
+    use super::*;
+
+    #[test]
+    fn expression() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = ExpressionContext::new(&rt).unwrap();
+
+        ctxt.eval_script(
+            "var order = { items: [{ price: 10, qty: 2 }, { price: 5, qty: 4 }], coupon: 'SAVE' };",
+            "<host>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        for (expr, expected) in vec![
+            ("1 + 2 * 3", "7"),
+            ("order.items.map(i => 1)", "invalid"),
+            ("order.items.reduce", "function"),
+            ("order.items.length >= 2 && order.coupon === 'SAVE'", "true"),
+            ("`${order.items[0].price}${'}'}:${ { a: 1 }.a }`", "10}:1"),
+            ("/a=b;/.test('a=b;') ? 'yes' : 'no'", "yes"),
+            ("order.items[1].price / 5 / 1", "1"),
+            ("'x = 1; var y'.length", "12"),
+            ("({ if: 1, delete: 2 }).if + order.items.length", "3"),
+            ("typeof order['items'] // a = 1;", "object"),
+            ("1e-3 * 1000 + 0x1E", "31"),
+        ] {
+            let res = ctxt.compile(expr).and_then(|expr| expr.eval::<String>());
+
+            match expected {
+                "invalid" => assert!(res.is_err(), "{}", expr),
+                "function" => assert!(res.unwrap().unwrap().starts_with("function"), "{}", expr),
+                _ => assert_eq!(res.unwrap().unwrap(), expected, "{}", expr),
+            }
+        }
+
+        for (expr, err) in vec![
+            (
+                "a = 1",
+                "assignment `=` is not allowed in expression at 1:3",
+            ),
+            ("1;\n  x++", "statement is not allowed in expression at 1:2"),
+            (
+                "[1,\n  i += 2]",
+                "assignment `+=` is not allowed in expression at 2:5",
+            ),
+            (
+                "function () {}",
+                "`function` is not allowed in expression at 1:1",
+            ),
+            ("(x) => x", "function is not allowed in expression at 1:5"),
+            (
+                "({ f() { g() } })",
+                "function body is not allowed in expression at 1:8",
+            ),
+            (
+                "delete order.coupon",
+                "`delete` is not allowed in expression at 1:1",
+            ),
+            (
+                "`${ x = 1 }`",
+                "assignment `=` is not allowed in expression at 1:7",
+            ),
+        ] {
+            assert_eq!(
+                ctxt.compile(expr)
+                    .err()
+                    .unwrap()
+                    .downcast::<ErrorKind>()
+                    .unwrap(),
+                ErrorKind::SyntaxError(err.into(), None),
+                "{}",
+                expr
+            );
+        }
+
+        // all the line terminators end the single line comments
+        for &(expr, at) in &[
+            ("1 //\u{2028}, order = 0", "2:9"),
+            ("1 //\u{2029}); var y = function () {}; (y()", "2:1"),
+            ("1 //\r\n, order = 0", "2:9"),
+        ] {
+            let err = ctxt
+                .compile(expr)
+                .err()
+                .unwrap()
+                .downcast::<ErrorKind>()
+                .unwrap();
+
+            assert!(err.message().ends_with(at), "{}: {}", expr, err);
+        }
+
+        // the whitespaces beyond ASCII don't turn the division into a regular expression
+        assert!(ctxt.compile("order\u{a0}/ 1, order = 2 / 1").is_err());
+        assert_eq!(
+            ctxt.eval_expr::<i32>("order.items.length\u{2028}/ 2")
+                .unwrap(),
+            Some(1)
+        );
+
+        // the syntax errors are reported by the parser
+        assert!(ctxt.compile("1 +").is_err());
+        assert!(ctxt.compile("1) + (2").is_err());
+
+        // the code generation is disabled
+        assert!(ctxt.eval_expr::<()>("Function('return 1')()").is_err());
+    }
+
+    #[test]
+    fn eval_batch() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = ExpressionContext::new(&rt).unwrap();
+
+        ctxt.global_object().set_property("discount", 5).unwrap();
+
+        let expr = ctxt
+            .compile("typeof coupon === 'undefined' ? price * qty : price * qty - discount")
+            .unwrap();
+
+        let rows = vec![
+            vec![("price", 10), ("qty", 2)],
+            vec![("price", 10), ("qty", 2), ("coupon", 1)],
+            // the field of the previous input is removed
+            vec![("price", 3), ("qty", 3)],
+            // the field shadows the global variable
+            vec![("price", 10), ("qty", 1), ("coupon", 1), ("discount", 1)],
+        ];
+
+        let res = expr
+            .eval_batch::<i32, _, _, _, _>(rows)
+            .into_iter()
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(res, vec![20, 15, 9, 9]);
+
+        // the errors are reported per input
+        let expr = ctxt.compile("name.toUpperCase()").unwrap();
+        let res = expr.eval_batch::<String, _, _, _, _>(vec![
+            vec![("name", "foo")],
+            vec![],
+            vec![("name", "bar")],
+        ]);
+
+        assert_eq!(res[0].as_ref().unwrap(), "FOO");
+        assert!(res[1].is_err());
+        assert_eq!(res[2].as_ref().unwrap(), "BAR");
+    }
+}
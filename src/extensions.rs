@@ -0,0 +1,298 @@
+use std::cell::Cell;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, RuntimeRef, Unsupported};
+
+bitflags! {
+    /// The language extensions of the QuickJS engine, which could be switched per runtime.
+    ///
+    /// The extensions are only available if the engine was built with them, e.g. the `bignum` feature.
+    pub struct Extensions: i32 {
+        /// `BigFloat` and `BigFloatEnv`, the BigFloat literals like `1.5l`, and the `"use bigint"` directive.
+        const BIGNUM = ffi::JS_CONFIG_BIGNUM_EXT;
+        /// The operators of the objects are overloaded by the `Symbol.operatorXXX` methods.
+        const OPERATOR_OVERLOADING = ffi::JS_CONFIG_OPERATOR_OVERLOADING;
+        /// The `"use math"` directive.
+        const MATH_MODE = ffi::JS_CONFIG_MATH_MODE;
+        /// The legacy features of the web browsers in the Annex B of the specification,
+        /// e.g. the html comments, `__proto__`, `escape` and `String.prototype.substr`.
+        const ANNEX_B = ffi::JS_CONFIG_ANNEX_B;
+    }
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Extensions::all()
+    }
+}
+
+/// How to tell the module sources from the scripts, e.g. when checking the syntax.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModuleDetection {
+    /// the sources with the `.mjs` extension, or the `import` and `export` statements, are modules
+    Heuristic,
+    /// only the sources with the `.mjs` extension are modules
+    Extension,
+    /// all the sources are modules
+    Always,
+    /// all the sources are scripts
+    Never,
+}
+
+impl Default for ModuleDetection {
+    fn default() -> Self {
+        ModuleDetection::Heuristic
+    }
+}
+
+#[derive(Default)]
+struct Detection(Cell<ModuleDetection>);
+
+/// The builtin properties of the Annex B, as the paths from the global object.
+const ANNEX_B_BUILTINS: &[(&[&str], &[&str])] = &[
+    (&[], &["escape", "unescape"]),
+    (
+        &["Object", "prototype"],
+        &[
+            "__proto__",
+            "__defineGetter__",
+            "__defineSetter__",
+            "__lookupGetter__",
+            "__lookupSetter__",
+        ],
+    ),
+    (
+        &["String", "prototype"],
+        &[
+            "substr",
+            "trimLeft",
+            "trimRight",
+            "anchor",
+            "big",
+            "blink",
+            "bold",
+            "fixed",
+            "fontcolor",
+            "fontsize",
+            "italics",
+            "link",
+            "small",
+            "strike",
+            "sub",
+            "sup",
+        ],
+    ),
+    (
+        &["Date", "prototype"],
+        &["getYear", "setYear", "toGMTString"],
+    ),
+    (&["RegExp", "prototype"], &["compile"]),
+];
+
+/// The builtin properties of the BigNum extensions, as the paths from the global object.
+const BIGNUM_BUILTINS: &[(&[&str], &[&str])] = &[(&[], &["BigFloat", "BigFloatEnv"])];
+
+impl RuntimeRef {
+    /// Returns the enabled language extensions.
+    pub fn extensions(&self) -> Extensions {
+        Extensions::from_bits_truncate(unsafe { ffi::JS_GetEngineConfig(self.as_ptr()) })
+    }
+
+    /// Enable the language extensions and disable the others.
+    ///
+    /// The parser and the operators follow the extensions immediately,
+    /// the builtin objects of the disabled extensions are removed from the contexts created afterwards.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched,
+    /// which always enables the extensions of the build.
+    pub fn set_extensions(&self, extensions: Extensions) -> Result<&Self, Error> {
+        if extensions != self.extensions() {
+            Unsupported::check("set_extensions")?;
+        }
+
+        trace!("{:?} set extensions to {:?}", self, extensions);
+
+        unsafe { ffi::JS_SetEngineConfig(self.as_ptr(), extensions.bits()) };
+
+        Ok(self)
+    }
+
+    /// Returns how to tell the module sources from the scripts.
+    pub fn module_detection(&self) -> ModuleDetection {
+        self.state::<Detection>().0.get()
+    }
+
+    /// Set how to tell the module sources from the scripts.
+    pub fn set_module_detection(&self, detection: ModuleDetection) -> &Self {
+        self.state::<Detection>().0.set(detection);
+        self
+    }
+
+    /// Returns `true` if the source should be evaluated as a module.
+    pub fn is_module(&self, source: &str, filename: &str) -> bool {
+        match self.module_detection() {
+            ModuleDetection::Heuristic => {
+                filename.ends_with(".mjs") || crate::detect_module(source)
+            }
+            ModuleDetection::Extension => filename.ends_with(".mjs"),
+            ModuleDetection::Always => true,
+            ModuleDetection::Never => false,
+        }
+    }
+}
+
+impl ContextRef {
+    /// Remove the builtin objects of the disabled extensions from a new context.
+    pub(crate) fn remove_disabled_builtins(&self) {
+        let extensions = self.runtime().extensions();
+
+        if extensions.contains(Extensions::all()) {
+            return;
+        }
+
+        let mut builtins = vec![];
+
+        if !extensions.contains(Extensions::BIGNUM) {
+            builtins.extend_from_slice(BIGNUM_BUILTINS);
+        }
+        if !extensions.contains(Extensions::ANNEX_B) {
+            builtins.extend_from_slice(ANNEX_B_BUILTINS);
+        }
+        if let Err(err) = self.remove_builtins(&builtins) {
+            warn!(
+                "failed to remove the builtins of the disabled extensions, {}",
+                err
+            );
+        }
+    }
+
+    fn remove_builtins(&self, builtins: &[(&[&str], &[&str])]) -> Result<(), Error> {
+        for &(path, names) in builtins {
+            let obj = path.iter().try_fold(self.global_object(), |obj, &name| {
+                self.get_property(&obj, name)
+            });
+
+            if let Some(obj) = obj.filter(|obj| obj.is_object()) {
+                for &name in names {
+                    obj.delete_property(name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::builder()
+            .with_extensions(Extensions::all() - Extensions::ANNEX_B - Extensions::BIGNUM)
+            .with_module_detection(ModuleDetection::Extension)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            rt.extensions(),
+            Extensions::OPERATOR_OVERLOADING | Extensions::MATH_MODE
+        );
+
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[typeof escape, typeof ''.substr, typeof BigFloat, typeof BigInt, '__proto__' in {}].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("undefined,undefined,undefined,function,false".to_owned())
+        );
+
+        // `__proto__` is an ordinary property of the object literals
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "var o = { __proto__: Array.prototype }; [Array.isArray(o), o instanceof Array, Object.keys(o)].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("false,false,__proto__".to_owned())
+        );
+        assert!(ctxt.eval::<_, i32>("1 <!-- 2", Eval::GLOBAL).is_err());
+
+        rt.set_extensions(Extensions::all()).unwrap();
+
+        assert_eq!(ctxt.eval("1 <!-- 2", Eval::GLOBAL).unwrap(), Some(1));
+
+        // the contexts created afterwards have the builtins
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(
+            ctxt.eval::<_, String>("escape('a b')", Eval::GLOBAL)
+                .unwrap(),
+            Some("a%20b".to_owned())
+        );
+
+        // the modules are detected by the extension
+        assert_eq!(rt.module_detection(), ModuleDetection::Extension);
+        assert!(!rt.is_module("import * as std from 'std'", "main.js"));
+        assert!(rt.is_module("1 + 2", "main.mjs"));
+
+        rt.set_module_detection(ModuleDetection::default());
+
+        assert!(rt.is_module("import * as std from 'std'", "main.js"));
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn bignum_extensions() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let script = r#"
+class Vec2 {
+    constructor(x) { this.x = x }
+    static [Symbol.operatorAdd](a, b) { return new Vec2(a.x + b.x) }
+}
+
+const sum = new Vec2(1) + new Vec2(2);
+
+[typeof sum, typeof 1.5l, (function () { "use math"; return typeof 1 })()].join()
+"#;
+
+        assert_eq!(
+            ctxt.eval::<_, String>(script, Eval::GLOBAL).unwrap(),
+            Some("object,bigfloat,bigint".to_owned())
+        );
+
+        rt.set_extensions(Extensions::ANNEX_B).unwrap();
+
+        let ctxt = Context::new(&rt);
+
+        assert!(ctxt.eval::<_, String>(script, Eval::GLOBAL).is_err());
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+class Vec2 {
+    constructor(x) { this.x = x }
+    static [Symbol.operatorAdd](a, b) { return new Vec2(a.x + b.x) }
+}
+
+[typeof (new Vec2(1) + new Vec2(2)), (function () { "use math"; return typeof 1 })()].join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("string,number".to_owned())
+        );
+    }
+}
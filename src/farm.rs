@@ -0,0 +1,553 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::task::{Context as TaskContext, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use failure::{err_msg, Error};
+
+use crate::{Args, Context, ContextRef, Eval, ExtractValue, Runtime};
+
+type Init = Arc<dyn Fn(&ContextRef) -> Result<(), Error> + Send + Sync>;
+type Task = Box<dyn FnOnce(&ContextRef) + Send>;
+
+/// The limits of a job executed by the `RuntimeFarm`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// the wall-clock time limit of the job
+    pub time_limit: Option<Duration>,
+    /// the memory limit of the runtime when executing the job
+    pub memory_limit: Option<usize>,
+}
+
+impl Limits {
+    /// Set the wall-clock time limit of the job.
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Set the memory limit of the runtime when executing the job.
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+}
+
+struct Job {
+    limits: Limits,
+    task: Task,
+}
+
+#[derive(Default)]
+struct Pending {
+    queued: usize,
+    stopped: bool,
+}
+
+struct Shared {
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    pending: Mutex<Pending>,
+    cond: Condvar,
+    next: AtomicUsize,
+}
+
+impl Shared {
+    fn push(&self, job: Job) {
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.queues.len();
+
+        self.queues[idx].lock().unwrap().push_back(job);
+        self.pending.lock().unwrap().queued += 1;
+        self.cond.notify_one();
+    }
+
+    /// Wait for a job, returns `None` if the farm stopped and all the jobs finished.
+    fn pop(&self, idx: usize) -> Option<Job> {
+        {
+            let mut pending = self.pending.lock().unwrap();
+
+            while pending.queued == 0 && !pending.stopped {
+                pending = self.cond.wait(pending).unwrap();
+            }
+
+            if pending.queued == 0 {
+                return None;
+            }
+
+            pending.queued -= 1;
+        }
+
+        // take a job from the front of its own queue, or steal from the back of the others.
+        loop {
+            if let Some(job) = self.queues[idx].lock().unwrap().pop_front() {
+                return Some(job);
+            }
+
+            for offset in 1..self.queues.len() {
+                let victim = (idx + offset) % self.queues.len();
+
+                if let Some(job) = self.queues[victim].lock().unwrap().pop_back() {
+                    trace!("worker #{} stole a job from worker #{}", idx, victim);
+
+                    return Some(job);
+                }
+            }
+
+            thread::yield_now();
+        }
+    }
+
+    fn run(&self, idx: usize, ctxt: &ContextRef, defaults: Limits) {
+        let rt = ctxt.runtime();
+
+        while let Some(Job { limits, task }) = self.pop(idx) {
+            rt.set_time_limit(limits.time_limit.or(defaults.time_limit));
+            rt.set_memory_limit(limits.memory_limit.or(defaults.memory_limit));
+
+            task(ctxt);
+
+            rt.set_memory_limit(None);
+            rt.run_gc();
+        }
+
+        debug!("worker #{} stopped", idx);
+    }
+}
+
+struct Slot<T> {
+    result: Option<Result<T, Error>>,
+    waker: Option<Waker>,
+}
+
+struct Completion<T> {
+    slot: Mutex<Slot<T>>,
+    cond: Condvar,
+}
+
+impl<T> Completion<T> {
+    fn complete(&self, result: Result<T, Error>) {
+        let mut slot = self.slot.lock().unwrap();
+
+        slot.result = Some(result);
+
+        if let Some(waker) = slot.waker.take() {
+            waker.wake()
+        }
+
+        self.cond.notify_all();
+    }
+}
+
+/// The handle of a job submitted to the `RuntimeFarm`.
+///
+/// The result could be waited in blocking mode with `JobHandle::wait`, or awaited as a `Future`.
+pub struct JobHandle<T>(Arc<Completion<T>>);
+
+impl<T> fmt::Debug for JobHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl<T> JobHandle<T> {
+    /// Returns `true` if the job has finished.
+    pub fn is_finished(&self) -> bool {
+        self.0.slot.lock().unwrap().result.is_some()
+    }
+
+    /// Block the current thread until the job finished.
+    pub fn wait(self) -> Result<T, Error> {
+        let mut slot = self.0.slot.lock().unwrap();
+
+        loop {
+            if let Some(result) = slot.result.take() {
+                return result;
+            }
+
+            slot = self.0.cond.wait(slot).unwrap();
+        }
+    }
+}
+
+impl<T> Future for JobHandle<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output> {
+        let mut slot = self.0.slot.lock().unwrap();
+
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The builder of `RuntimeFarm`.
+pub struct Builder {
+    workers: usize,
+    limits: Limits,
+    init: Option<Init>,
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("workers", &self.workers)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+impl Builder {
+    /// Set the number of the worker threads, each owns a runtime.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Set the default limits of the jobs.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the function to initialize the context of each worker, e.g. load the scripts.
+    pub fn with_init<F>(mut self, init: F) -> Self
+    where
+        F: Fn(&ContextRef) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.init = Some(Arc::new(init));
+        self
+    }
+
+    /// Spawn the worker threads, returns the first error of initializing the workers.
+    pub fn build(self) -> Result<RuntimeFarm, Error> {
+        let shared = Arc::new(Shared {
+            queues: (0..self.workers).map(|_| Default::default()).collect(),
+            pending: Default::default(),
+            cond: Condvar::new(),
+            next: AtomicUsize::new(0),
+        });
+        let (tx, rx) = mpsc::channel();
+        let mut farm = RuntimeFarm {
+            shared: shared.clone(),
+            threads: Vec::with_capacity(self.workers),
+        };
+
+        for idx in 0..self.workers {
+            let shared = shared.clone();
+            let init = self.init.clone();
+            let limits = self.limits;
+            let tx = tx.clone();
+
+            let thread = thread::Builder::new()
+                .name(format!("qjs-farm-{}", idx))
+                .spawn(move || {
+                    let rt = Runtime::new();
+                    let ctxt = Context::new(&rt);
+                    let res = init.map_or(Ok(()), |init| {
+                        panic::catch_unwind(AssertUnwindSafe(|| init(&ctxt))).unwrap_or_else(|_| {
+                            Err(format_err!("worker #{} panicked when initializing", idx))
+                        })
+                    });
+                    let ok = res.is_ok();
+
+                    let _ = tx.send(res);
+
+                    if ok {
+                        shared.run(idx, &ctxt, limits)
+                    }
+                })?;
+
+            farm.threads.push(thread);
+        }
+
+        // the workers hold the only senders, so a dead worker could not block the receiver.
+        drop(tx);
+
+        for _ in 0..self.workers {
+            rx.recv()??;
+        }
+
+        Ok(farm)
+    }
+}
+
+/// `RuntimeFarm` owns a runtime on each worker thread, and executes the submitted jobs in parallel.
+///
+/// The jobs are distributed to the workers in round-robin, and the idle workers steal the jobs from the others.
+/// The pending jobs are finished before the farm dropped.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::RuntimeFarm;
+///
+/// let farm = RuntimeFarm::builder()
+///     .with_workers(4)
+///     .with_init(|ctxt| {
+///         ctxt.eval_script("function score(x) { return x * 2 }", "<init>", qjs::Eval::GLOBAL)?;
+///         Ok(())
+///     })
+///     .build()
+///     .unwrap();
+///
+/// let jobs = (0..100).map(|i| farm.call::<_, i32>("score", i)).collect::<Vec<_>>();
+/// let total = jobs.into_iter().map(|job| job.wait().unwrap()).sum::<i32>();
+///
+/// assert_eq!(total, 9900);
+/// ```
+pub struct RuntimeFarm {
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl fmt::Debug for RuntimeFarm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RuntimeFarm")
+            .field("workers", &self.threads.len())
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl Drop for RuntimeFarm {
+    fn drop(&mut self) {
+        self.shared.pending.lock().unwrap().stopped = true;
+        self.shared.cond.notify_all();
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl RuntimeFarm {
+    /// Create a farm with `workers` threads.
+    pub fn new(workers: usize) -> Result<Self, Error> {
+        Self::builder().with_workers(workers).build()
+    }
+
+    /// Create a builder of `RuntimeFarm`, which spawns a worker for each CPU by default.
+    pub fn builder() -> Builder {
+        Builder {
+            workers: thread::available_parallelism().map_or(1, |n| n.get()),
+            limits: Limits::default(),
+            init: None,
+        }
+    }
+
+    /// Returns the number of the worker threads.
+    pub fn workers(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Returns the number of the jobs waiting for a worker.
+    pub fn pending(&self) -> usize {
+        self.shared.pending.lock().unwrap().queued
+    }
+
+    /// Submit a job with the default limits.
+    pub fn submit<F, T>(&self, f: F) -> JobHandle<T>
+    where
+        F: FnOnce(&ContextRef) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.submit_with_limits(Limits::default(), f)
+    }
+
+    /// Submit a job with the limits, which override the default limits.
+    pub fn submit_with_limits<F, T>(&self, limits: Limits, f: F) -> JobHandle<T>
+    where
+        F: FnOnce(&ContextRef) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let completion = Arc::new(Completion {
+            slot: Mutex::new(Slot {
+                result: None,
+                waker: None,
+            }),
+            cond: Condvar::new(),
+        });
+        let handle = JobHandle(completion.clone());
+
+        self.shared.push(Job {
+            limits,
+            task: Box::new(move |ctxt| {
+                let res = panic::catch_unwind(AssertUnwindSafe(|| f(ctxt)))
+                    .unwrap_or_else(|_| Err(err_msg("job panicked")));
+
+                completion.complete(res)
+            }),
+        });
+
+        handle
+    }
+
+    /// Evaluate a script in one of the workers.
+    pub fn eval<S, T>(&self, source: S) -> JobHandle<Option<T>>
+    where
+        S: Into<String>,
+        T: ExtractValue + Send + 'static,
+    {
+        self.eval_with_limits(source, Limits::default())
+    }
+
+    /// Evaluate a script in one of the workers with the limits.
+    pub fn eval_with_limits<S, T>(&self, source: S, limits: Limits) -> JobHandle<Option<T>>
+    where
+        S: Into<String>,
+        T: ExtractValue + Send + 'static,
+    {
+        let source = source.into();
+
+        self.submit_with_limits(limits, move |ctxt| ctxt.eval(source.as_str(), Eval::GLOBAL))
+    }
+
+    /// Call a global function, which was defined in the `init` function of the workers.
+    pub fn call<A, T>(&self, name: &str, args: A) -> JobHandle<T>
+    where
+        A: Args + Send + 'static,
+        T: ExtractValue + Send + 'static,
+    {
+        let name = name.to_owned();
+
+        self.submit(move |ctxt| {
+            let func = ctxt
+                .get_property(&ctxt.global_object(), name.as_str())
+                .ok_or_else(|| format_err!("function `{}` not found", name))?;
+
+            ctxt.call(&func, None, args)?.extract()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    use crate::ErrorKind;
+
+    use super::*;
+
+    struct Unpark(Thread, AtomicBool);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.1.store(true, Ordering::SeqCst);
+            self.0.unpark()
+        }
+    }
+
+    fn block_on<F: Future>(mut fut: F) -> F::Output {
+        let unpark = Arc::new(Unpark(thread::current(), AtomicBool::new(false)));
+        let waker = Waker::from(unpark.clone());
+        let mut cx = TaskContext::from_waker(&waker);
+        let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+
+        loop {
+            if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+                return output;
+            }
+
+            while !unpark.1.swap(false, Ordering::SeqCst) {
+                thread::park()
+            }
+        }
+    }
+
+    #[test]
+    fn farm() {
+        let _ = pretty_env_logger::try_init();
+
+        let farm = RuntimeFarm::builder()
+            .with_workers(3)
+            .with_limits(Limits::default().with_time_limit(Duration::from_secs(5)))
+            .with_init(|ctxt| {
+                ctxt.eval_script(
+                    "var calls = 0; function square(x) { calls++; return x * x }",
+                    "<init>",
+                    Eval::GLOBAL,
+                )?;
+                Ok(())
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(farm.workers(), 3);
+
+        let jobs = (0..300)
+            .map(|i| farm.call::<_, i64>("square", i))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            jobs.into_iter().map(|job| job.wait().unwrap()).sum::<i64>(),
+            (0..300).map(|i| i * i).sum::<i64>()
+        );
+
+        let calls = (0..farm.workers())
+            .map(|_| farm.eval::<_, i32>("calls"))
+            .collect::<Vec<_>>();
+
+        assert!(calls
+            .into_iter()
+            .all(|job| job.wait().unwrap().unwrap() <= 300));
+
+        assert_eq!(
+            block_on(farm.eval::<_, String>("'hello ' + 'world'")).unwrap(),
+            Some("hello world".to_owned())
+        );
+
+        let err = farm
+            .eval_with_limits::<_, ()>(
+                "while (true) {}",
+                Limits::default().with_time_limit(Duration::from_millis(50)),
+            )
+            .wait()
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast::<ErrorKind>().unwrap().message(),
+            "interrupted"
+        );
+
+        assert!(farm
+            .eval_with_limits::<_, ()>(
+                "new Array(10000000).fill(0)",
+                Limits::default().with_memory_limit(1024 * 1024),
+            )
+            .wait()
+            .is_err());
+        assert!(farm.call::<_, i32>("missing", ()).wait().is_err());
+        assert!(farm.submit::<_, ()>(|_| panic!("boom")).wait().is_err());
+        assert_eq!(farm.eval::<_, i32>("1 + 2").wait().unwrap(), Some(3));
+
+        assert!(RuntimeFarm::builder()
+            .with_workers(2)
+            .with_init(|_| Err(err_msg("init failed")))
+            .build()
+            .is_err());
+
+        // a panicked initialization doesn't block the builder
+        assert!(RuntimeFarm::builder()
+            .with_workers(2)
+            .with_init(|_| panic!("boom"))
+            .build()
+            .unwrap_err()
+            .to_string()
+            .ends_with("panicked when initializing"));
+    }
+}
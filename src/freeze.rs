@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use failure::Error;
+
+use crate::{prop::Names, ContextRef, Local, Prop, Value};
+
+impl Local<'_, Value> {
+    /// Freezes an object, the existing properties can't be changed and new properties can't be added.
+    pub fn freeze(&self) -> Result<(), Error> {
+        self.ctxt.freeze(self)
+    }
+
+    /// Recursively freezes an object and all the objects reachable from its own properties.
+    pub fn deep_freeze(&self) -> Result<(), Error> {
+        self.ctxt.deep_freeze(self)
+    }
+
+    /// Check if an object is frozen.
+    pub fn is_frozen(&self) -> Result<bool, Error> {
+        self.ctxt.is_frozen(self)
+    }
+}
+
+impl ContextRef {
+    /// Freezes an object, the existing properties can't be changed and new properties can't be added.
+    ///
+    /// It works like `Object.freeze`, but doesn't depend on the global `Object`
+    /// which may have been tampered by the scripts.
+    pub fn freeze(&self, obj: &Value) -> Result<(), Error> {
+        self.freeze_object(obj).map(|_| ())
+    }
+
+    /// Recursively freezes an object and all the objects reachable from its own properties,
+    /// including the values, getters and setters of the properties.
+    ///
+    /// The prototypes are not frozen, and the cyclic references are visited only once.
+    pub fn deep_freeze(&self, obj: &Value) -> Result<(), Error> {
+        self.freeze_reachable(vec![self.clone_value(obj)], &[], false)
+    }
+
+    /// Freezes the objects reachable from the `roots`, and their prototypes if `prototypes` is `true`.
+    ///
+    /// The `excluded` objects are not frozen, and the objects only reachable through them are not visited.
+    pub(crate) fn freeze_reachable(
+        &self,
+        roots: Vec<Local<Value>>,
+        excluded: &[&Value],
+        prototypes: bool,
+    ) -> Result<(), Error> {
+        let mut visited = excluded
+            .iter()
+            .flat_map(|obj| obj.as_object())
+            .collect::<HashSet<_>>();
+        let mut pending = roots;
+
+        while let Some(obj) = pending.pop() {
+            if let Some(ptr) = obj.as_object() {
+                if visited.insert(ptr) {
+                    if prototypes {
+                        pending.push(self.get_prototype(&obj));
+                    }
+
+                    pending.extend(self.freeze_object(&obj)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if an object is frozen.
+    pub fn is_frozen(&self, obj: &Value) -> Result<bool, Error> {
+        if !obj.is_object() {
+            return Ok(true);
+        }
+        if self.is_extensible(obj)? {
+            return Ok(false);
+        }
+
+        for name in self
+            .get_own_property_names(obj, Names::STRING | Names::SYMBOL)?
+            .unwrap_or_default()
+        {
+            if let Some(desc) = self.get_own_property_descriptor(obj, &name)? {
+                if desc.configurable || desc.writable {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Freezes an object, returns the objects referenced by its own properties.
+    fn freeze_object(&self, obj: &Value) -> Result<Vec<Local<Value>>, Error> {
+        let mut children = vec![];
+
+        if !obj.is_object() {
+            return Ok(children);
+        }
+
+        self.prevent_extensions(obj)?;
+
+        for name in self
+            .get_own_property_names(obj, Names::STRING | Names::SYMBOL)?
+            .unwrap_or_default()
+        {
+            let desc = match self.get_own_property_descriptor(obj, &name)? {
+                Some(desc) => desc,
+                None => continue,
+            };
+
+            let mut flags = Prop::HAS_CONFIGURABLE | Prop::THROW;
+
+            if desc.getter.is_none() && desc.setter.is_none() {
+                flags |= Prop::HAS_WRITABLE;
+            }
+
+            self.define_property(obj, &name, None, None, None, flags)?;
+
+            children.extend(
+                desc.value
+                    .into_iter()
+                    .chain(desc.getter)
+                    .chain(desc.setter)
+                    .filter(|v| v.is_object()),
+            );
+        }
+
+        Ok(children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    #[test]
+    fn deep_freeze() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let config = ctxt
+            .eval_script(
+                "var config = { name: 'foo', limits: { cpu: 1, tags: ['a'] }, get size() { return 1; } }; \
+                 config.self = config; config",
+                "<evalScript>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+        config.deep_freeze().unwrap();
+
+        assert!(config.is_frozen().unwrap());
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "Object.isFrozen(config) && Object.isFrozen(config.limits) && \
+                 Object.isFrozen(config.limits.tags) && \
+                 Object.isFrozen(Object.getOwnPropertyDescriptor(config, 'size').get) && \
+                 !Object.isFrozen(Object.prototype)",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            ctxt.eval::<_, i32>(
+                "config.limits.cpu = 100; config.limits.tags.push('b'); config.limits.cpu",
+                Eval::GLOBAL
+            )
+            .ok(),
+            None
+        );
+        assert_eq!(
+            ctxt.eval::<_, i32>(
+                "'use strict'; try { config.limits.cpu = 100 } catch (e) {}; config.limits.cpu",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(1)
+        );
+    }
+}
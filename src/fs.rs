@@ -0,0 +1,536 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+
+use crate::{Capabilities, ContextRef, ErrorKind, Eval, Local, NewValue, Value, UNDEFINED};
+
+/// The name of the file system module.
+pub const FS_MODULE: &str = "host:fs";
+
+/// The functions exported by the `host:fs` module.
+const FS_EXPORTS: &[&str] = &[
+    "readFile",
+    "readFileSync",
+    "writeFile",
+    "writeFileSync",
+    "readDir",
+    "readDirSync",
+    "stat",
+    "statSync",
+];
+
+/// The factory of the module, the promise-based functions are built on the native sync functions.
+const FS_FACTORY: &str = r#"
+(function (native) {
+    const toBuffer = (data) => ArrayBuffer.isView(data)
+        ? data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength)
+        : data;
+    const fs = {
+        readFileSync: (path, encoding) => native.readFile(path, encoding),
+        writeFileSync: (path, data) => native.writeFile(path, toBuffer(data)),
+        readDirSync: (path) => native.readDir(path),
+        statSync: (path) => native.stat(path),
+    };
+    fs.readFile = async (path, encoding) => fs.readFileSync(path, encoding);
+    fs.writeFile = async (path, data) => fs.writeFileSync(path, data);
+    fs.readDir = async (path) => fs.readDirSync(path);
+    fs.stat = async (path) => fs.statSync(path);
+    return fs;
+})
+"#;
+
+/// The metadata of a file or directory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FileStat {
+    /// the entry is a regular file
+    pub is_file: bool,
+    /// the entry is a directory
+    pub is_dir: bool,
+    /// the size of the file in bytes
+    pub size: u64,
+    /// the last modification time
+    pub modified: Option<SystemTime>,
+}
+
+/// The virtual file system accessed by the `host:fs` module.
+///
+/// The paths are passed as the scripts given, the implementation resolves and scopes them.
+pub trait Vfs: Send {
+    /// Read the whole content of a file.
+    fn read_file(&self, path: &str) -> io::Result<Vec<u8>>;
+
+    /// Write the content to a file, creates it if not exists.
+    fn write_file(&self, path: &str, data: &[u8]) -> io::Result<()>;
+
+    /// Returns the names of the entries in a directory.
+    fn read_dir(&self, path: &str) -> io::Result<Vec<String>>;
+
+    /// Returns the metadata of a file or directory.
+    fn stat(&self, path: &str) -> io::Result<FileStat>;
+
+    /// Returns the path on the host file system, e.g. the working directory of the child processes.
+    fn host_path(&self, path: &str) -> io::Result<PathBuf> {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("`{}` is not on the host file system", path),
+        ))
+    }
+}
+
+/// The file system of the host, scoped to a root directory.
+///
+/// The relative paths are resolved from the root, and the paths outside of the root,
+/// including the symbolic links pointing outside, are denied.
+#[derive(Clone, Debug)]
+pub struct OsFs {
+    root: PathBuf,
+}
+
+impl OsFs {
+    pub fn new<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        Ok(OsFs {
+            root: root.as_ref().canonicalize()?,
+        })
+    }
+
+    /// The root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve the path in the root directory.
+    pub fn resolve(&self, path: &str) -> io::Result<PathBuf> {
+        let denied = || {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("`{}` is outside of the root directory", path),
+            )
+        };
+
+        let path = Path::new(path);
+        let mut resolved = if path.is_absolute() {
+            PathBuf::new()
+        } else {
+            self.root.clone()
+        };
+
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => resolved.push(component),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return Err(denied());
+                    }
+                }
+                Component::Normal(name) => resolved.push(name),
+            }
+        }
+
+        if !resolved.starts_with(&self.root) {
+            return Err(denied());
+        }
+
+        // the symbolic links are checked component by component before any access,
+        // a dangling link is denied, since writing through it creates the file wherever it points to.
+        let mut checked = self.root.clone();
+
+        for name in resolved.strip_prefix(&self.root).map_err(|_| denied())? {
+            checked.push(name);
+
+            match fs::symlink_metadata(&checked) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    checked = checked.canonicalize().map_err(|_| denied())?;
+
+                    if !checked.starts_with(&self.root) {
+                        return Err(denied());
+                    }
+                }
+                Ok(_) => {}
+                Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(checked)
+    }
+}
+
+impl Vfs for OsFs {
+    fn read_file(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.resolve(path)?)
+    }
+
+    fn write_file(&self, path: &str, data: &[u8]) -> io::Result<()> {
+        fs::write(self.resolve(path)?, data)
+    }
+
+    fn read_dir(&self, path: &str) -> io::Result<Vec<String>> {
+        let mut names = fs::read_dir(self.resolve(path)?)?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    fn stat(&self, path: &str) -> io::Result<FileStat> {
+        let metadata = fs::metadata(self.resolve(path)?)?;
+
+        Ok(FileStat {
+            is_file: metadata.is_file(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn host_path(&self, path: &str) -> io::Result<PathBuf> {
+        self.resolve(path)
+    }
+}
+
+/// The virtual file system of the `host:fs` module in a context.
+#[derive(Default)]
+struct FsState(RefCell<Option<Box<dyn Vfs>>>);
+
+/// The builder of the `host:fs` module, which gives the scripts a controlled file access.
+///
+/// The module exports `readFile(path, encoding)`, `writeFile(path, data)`, `readDir(path)` and `stat(path)`
+/// returning the promises, and their sync variants with the `Sync` suffix, and a default export with all of them.
+/// The file is read as an `ArrayBuffer`, or a string if the encoding is `utf8`.
+/// The files are accessed through the `Vfs`, the reading requires the `FS_READ` capability,
+/// and the writing requires the `FS_WRITE` capability.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, HostFs, OsFs, Runtime};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// HostFs::new(OsFs::new(dir.path()).unwrap()).init(&ctxt).unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     r#"
+/// import { writeFileSync, readFileSync } from 'host:fs';
+///
+/// writeFileSync('hello.txt', 'hello world');
+///
+/// globalThis.content = readFileSync('hello.txt', 'utf8');
+/// "#,
+///     Eval::MODULE,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(ctxt.eval::<_, String>("content", Eval::GLOBAL).unwrap().unwrap(), "hello world");
+/// assert!(ctxt.eval::<_, ()>("import { readFileSync } from 'host:fs'; readFileSync('../secret')", Eval::MODULE).is_err());
+/// ```
+pub struct HostFs {
+    vfs: Box<dyn Vfs>,
+}
+
+impl HostFs {
+    pub fn new<V: Vfs + 'static>(vfs: V) -> Self {
+        HostFs { vfs: Box::new(vfs) }
+    }
+
+    /// Register the `host:fs` module in the context.
+    pub fn init(self, ctxt: &ContextRef) -> Result<(), Error> {
+        *ctxt.state::<FsState>().0.borrow_mut() = Some(self.vfs);
+
+        let native = ctxt.bind(ctxt.new_object());
+
+        native.set_property(
+            "readFile",
+            ctxt.new_c_function_with_capabilities(
+                read_file,
+                Some("readFile"),
+                2,
+                Capabilities::FS_READ,
+            )?,
+        )?;
+        native.set_property(
+            "writeFile",
+            ctxt.new_c_function_with_capabilities(
+                write_file,
+                Some("writeFile"),
+                2,
+                Capabilities::FS_WRITE,
+            )?,
+        )?;
+        native.set_property(
+            "readDir",
+            ctxt.new_c_function_with_capabilities(
+                read_dir,
+                Some("readDir"),
+                1,
+                Capabilities::FS_READ,
+            )?,
+        )?;
+        native.set_property(
+            "stat",
+            ctxt.new_c_function_with_capabilities(stat, Some("stat"), 1, Capabilities::FS_READ)?,
+        )?;
+
+        let factory = ctxt.eval_script(FS_FACTORY, "<fs>", Eval::GLOBAL | Eval::STRICT)?;
+        let fs = ctxt.call(&factory, None, [&native])?;
+        let exports = FS_EXPORTS
+            .iter()
+            .map(|&name| {
+                fs.get_property(name)
+                    .ok_or_else(|| format_err!("`{}` not defined", name))
+                    .map(|func| (name, func))
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .into_iter()
+            .chain(Some(("default", ctxt.clone_value(&fs))));
+
+        ctxt.new_native_module(FS_MODULE, exports)?;
+
+        Ok(())
+    }
+}
+
+impl ContextRef {
+    fn with_vfs<T, F: FnOnce(&dyn Vfs) -> io::Result<T>>(&self, f: F) -> io::Result<T> {
+        match self.state::<FsState>().0.borrow().as_ref() {
+            Some(vfs) => f(vfs.as_ref()),
+            None => Err(io::Error::other("file system not initialized")),
+        }
+    }
+
+    /// Resolve the path on the host file system through the `Vfs` of the `host:fs` module.
+    pub(crate) fn host_path(&self, path: &str) -> io::Result<PathBuf> {
+        self.with_vfs(|vfs| vfs.host_path(path))
+    }
+
+    fn throw_io_error(&self, path: &str, err: io::Error) -> Value {
+        ErrorKind::Error(format!("{}: {}", path, err), None)
+            .new_value(self)
+            .into()
+    }
+}
+
+fn read_file(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let path = ctxt.clone_value(&args[0]).to_string();
+    let encoding = ctxt.clone_value(&args[1]);
+
+    match ctxt.with_vfs(|vfs| vfs.read_file(&path)) {
+        Ok(data) if encoding.is_undefined() => {
+            let mut data = data;
+
+            ctxt.new_value(ctxt.new_array_buffer_copy(&mut data))
+        }
+        Ok(data) => match encoding.to_string().to_lowercase().as_str() {
+            "utf8" | "utf-8" => match String::from_utf8(data) {
+                Ok(s) => ctxt.new_value(s),
+                Err(err) => ctxt.throw_io_error(
+                    &path,
+                    io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()),
+                ),
+            },
+            encoding => ErrorKind::TypeError(format!("unsupported encoding `{}`", encoding), None)
+                .new_value(ctxt)
+                .into(),
+        },
+        Err(err) => ctxt.throw_io_error(&path, err),
+    }
+}
+
+fn write_file(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let path = ctxt.clone_value(&args[0]).to_string();
+    let data = ctxt.clone_value(&args[1]);
+    let res = match ctxt.get_array_buffer(&data) {
+        Some(buf) => ctxt.with_vfs(|vfs| vfs.write_file(&path, buf.as_ref())),
+        None => ctxt.with_vfs(|vfs| vfs.write_file(&path, data.to_string().as_bytes())),
+    };
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => ctxt.throw_io_error(&path, err),
+    }
+}
+
+fn read_dir(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let path = ctxt.clone_value(&args[0]).to_string();
+
+    match ctxt.with_vfs(|vfs| vfs.read_dir(&path)) {
+        Ok(names) => {
+            let entries = ctxt.bind(ctxt.new_array());
+
+            for (idx, name) in names.into_iter().enumerate() {
+                if let Err(err) = entries.set_property(idx as u32, name) {
+                    return ctxt.throw_rust_error(&err).into_inner_untracked();
+                }
+            }
+
+            entries.into_inner_untracked()
+        }
+        Err(err) => ctxt.throw_io_error(&path, err),
+    }
+}
+
+fn stat(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let path = ctxt.clone_value(&args[0]).to_string();
+
+    match ctxt.with_vfs(|vfs| vfs.stat(&path)) {
+        Ok(stat) => {
+            let obj = ctxt.bind(ctxt.new_object());
+            let res = new_stat(&obj, &stat);
+
+            match res {
+                Ok(_) => obj.into_inner_untracked(),
+                Err(err) => ctxt.throw_rust_error(&err).into_inner_untracked(),
+            }
+        }
+        Err(err) => ctxt.throw_io_error(&path, err),
+    }
+}
+
+fn new_stat(obj: &Local<Value>, stat: &FileStat) -> Result<(), Error> {
+    obj.set_property("isFile", stat.is_file)?;
+    obj.set_property("isDirectory", stat.is_dir)?;
+    obj.set_property("size", stat.size as f64)?;
+
+    match stat
+        .modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    {
+        Some(modified) => obj.set_property("mtimeMs", modified.as_millis() as f64)?,
+        None => obj.set_property("mtimeMs", UNDEFINED)?,
+    };
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Permissions, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn host_fs() {
+        let _ = pretty_env_logger::try_init();
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(dir.path().join("secret"), "secret").unwrap();
+        fs::write(root.join("data.bin"), [1u8, 2, 3]).unwrap();
+
+        let osfs = OsFs::new(&root).unwrap();
+
+        assert!(osfs.resolve("sub/../data.bin").is_ok());
+        assert!(osfs.resolve("../secret").is_err());
+        assert!(osfs
+            .resolve(dir.path().join("secret").to_str().unwrap())
+            .is_err());
+        assert!(osfs
+            .resolve(root.join("data.bin").to_str().unwrap())
+            .is_ok());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("secret"), root.join("link")).unwrap();
+
+            assert!(osfs.resolve("link").is_err());
+
+            // the dangling link is denied, the file is not created outside of the root
+            std::os::unix::fs::symlink(dir.path().join("created"), root.join("dangling")).unwrap();
+
+            assert!(osfs.resolve("dangling").is_err());
+            assert!(osfs.write_file("dangling", b"data").is_err());
+            assert!(!dir.path().join("created").exists());
+
+            // the link inside of the root is followed
+            std::os::unix::fs::symlink(root.join("sub"), root.join("alias")).unwrap();
+
+            assert_eq!(
+                osfs.resolve("alias/new.txt").unwrap(),
+                root.canonicalize().unwrap().join("sub/new.txt")
+            );
+        }
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        HostFs::new(osfs).init(&ctxt).unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+import fs, { readFileSync, writeFileSync, readDirSync, statSync } from 'host:fs';
+
+globalThis.fs = fs;
+
+writeFileSync('sub/hello.txt', 'hello');
+writeFileSync('sub/bytes.bin', new Uint8Array([0, 1, 2, 3]).subarray(1));
+
+const stat = statSync('sub/hello.txt');
+
+globalThis.result = [
+    readFileSync('sub/hello.txt', 'utf8'),
+    new Uint8Array(readFileSync('data.bin')).join(':'),
+    new Uint8Array(readFileSync('sub/bytes.bin')).join(':'),
+    readDirSync('sub').join(),
+    stat.isFile, stat.isDirectory, stat.size, typeof stat.mtimeMs,
+    statSync('sub').isDirectory,
+].join('|');
+
+fs.readFile('sub/hello.txt', 'utf8').then((s) => { globalThis.async = s; });
+fs.readFile('missing').catch((e) => { globalThis.missing = e.message; });
+"#,
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        while rt.execute_pending_job().unwrap().is_some() {}
+
+        assert_eq!(
+            ctxt.eval::<_, String>("result", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "hello|1:2:3|1:2:3|bytes.bin,hello.txt|true|false|5|number|true"
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>("async", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "hello"
+        );
+        assert!(ctxt
+            .eval::<_, String>("missing", Eval::GLOBAL)
+            .unwrap()
+            .unwrap()
+            .starts_with("missing: "));
+        assert!(ctxt
+            .eval::<_, ()>("fs.readFileSync('../secret')", Eval::GLOBAL)
+            .is_err());
+        assert!(ctxt
+            .eval::<_, ()>("fs.readFileSync('data.bin', 'latin1')", Eval::GLOBAL)
+            .is_err());
+
+        // the writing is denied without the capability
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::FS_WRITE));
+
+        assert!(ctxt
+            .eval::<_, ()>("fs.writeFileSync('denied.txt', 'x')", Eval::GLOBAL)
+            .is_err());
+        assert!(!root.join("denied.txt").exists());
+        assert_eq!(
+            ctxt.eval::<_, String>("fs.readFileSync('sub/hello.txt', 'utf8')", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "hello"
+        );
+    }
+}
@@ -1,12 +1,91 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, value::ToBool, ContextRef, Local, NewAtom, NewValue, Value};
+use crate::{
+    ffi, value::ToBool, Atom, ContextRef, ErrorKind, ExtractValue, Local, NewAtom, NewValue,
+    RuntimeMismatch, Value,
+};
 
 pub trait Args {
     type Values: AsRef<[ffi::JSValue]>;
 
     fn into_values(self, ctxt: &ContextRef) -> Self::Values;
+
+    /// Check if the arguments could be passed to the context, e.g. they belong to the same runtime.
+    fn check_values(&self, _ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        Ok(())
+    }
+}
+
+/// The number of the arguments stored inline by `ArgBuf`.
+pub(crate) const INLINE_ARGS: usize = 8;
+
+/// A buffer of the arguments, which stores a few arguments inline to avoid the allocation per call.
+///
+/// The buffer doesn't free the values, the caller should free them after the call.
+/// It isn't exported from the crate, and could only be used as the opaque `Args::Values`.
+pub enum ArgBuf {
+    /// the arguments stored inline
+    Inline(usize, [ffi::JSValue; INLINE_ARGS]),
+    /// the arguments stored on the heap
+    Heap(Vec<ffi::JSValue>),
+}
+
+impl Default for ArgBuf {
+    fn default() -> Self {
+        ArgBuf::Inline(0, [ffi::UNDEFINED; INLINE_ARGS])
+    }
+}
+
+impl ArgBuf {
+    /// Create an empty buffer.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an argument to the buffer, spills to the heap when the inline storage is full.
+    pub(crate) fn push(&mut self, value: ffi::JSValue) {
+        match self {
+            ArgBuf::Inline(len, values) if *len < INLINE_ARGS => {
+                values[*len] = value;
+                *len += 1;
+            }
+            ArgBuf::Inline(len, values) => {
+                let mut heap = Vec::with_capacity(*len * 2);
+
+                heap.extend_from_slice(&values[..*len]);
+                heap.push(value);
+
+                *self = ArgBuf::Heap(heap);
+            }
+            ArgBuf::Heap(values) => values.push(value),
+        }
+    }
+}
+
+impl AsRef<[ffi::JSValue]> for ArgBuf {
+    fn as_ref(&self) -> &[ffi::JSValue] {
+        match self {
+            ArgBuf::Inline(len, values) => &values[..*len],
+            ArgBuf::Heap(values) => values.as_slice(),
+        }
+    }
+}
+
+impl FromIterator<ffi::JSValue> for ArgBuf {
+    fn from_iter<I: IntoIterator<Item = ffi::JSValue>>(iter: I) -> Self {
+        let mut buf = ArgBuf::new();
+
+        for value in iter {
+            buf.push(value);
+        }
+
+        buf
+    }
 }
 
 impl<T> Args for T
@@ -18,17 +97,25 @@ where
     fn into_values(self, ctxt: &ContextRef) -> Self::Values {
         [self.new_value(ctxt)]
     }
+
+    fn check_values(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        self.check_value(ctxt)
+    }
 }
 
 impl<T> Args for &[T]
 where
     T: NewValue + Clone,
 {
-    type Values = Vec<ffi::JSValue>;
+    type Values = ArgBuf;
 
     fn into_values(self, ctxt: &ContextRef) -> Self::Values {
         self.iter().map(|v| v.clone().new_value(ctxt)).collect()
     }
+
+    fn check_values(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        self.iter().try_for_each(|v| v.check_value(ctxt))
+    }
 }
 
 macro_rules! array_args {
@@ -38,15 +125,21 @@ macro_rules! array_args {
             where
                 T: NewValue,
             {
-                type Values = Vec<ffi::JSValue>;
+                type Values = [ffi::JSValue; $N];
 
                 fn into_values(self, ctxt: &ContextRef) -> Self::Values {
-                    let len = self.len();
                     let mut data = std::mem::ManuallyDrop::new(self);
+                    let mut values = [ffi::UNDEFINED; $N];
+
+                    for (idx, value) in values.iter_mut().enumerate() {
+                        *value = unsafe { std::ptr::read(data.get_unchecked_mut(idx)).new_value(ctxt) };
+                    }
+
+                    values
+                }
 
-                    (0..len).map(|idx| unsafe {
-                        std::ptr::read(data.get_unchecked_mut(idx)).new_value(ctxt)
-                    }).collect()
+                fn check_values(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+                    self.iter().try_for_each(|v| v.check_value(ctxt))
                 }
             }
         )*
@@ -84,6 +177,15 @@ macro_rules! tuple_args {
 
                 [ $( $name.new_value(ctxt), )* ]
             }
+
+            #[allow(non_snake_case)]
+            fn check_values(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+                let ( $($name,)* ) = self;
+
+                $( $name.check_value(ctxt)?; )*
+
+                Ok(())
+            }
         }
     }
 }
@@ -93,6 +195,29 @@ macro_rules! count {
     ( $x:tt $($xs:tt)* ) => (1usize + count!($($xs)*));
 }
 
+impl<T> Args for Vec<T>
+where
+    T: NewValue,
+{
+    type Values = ArgBuf;
+
+    fn into_values(self, ctxt: &ContextRef) -> Self::Values {
+        self.into_iter().map(|v| v.new_value(ctxt)).collect()
+    }
+
+    fn check_values(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        self.iter().try_for_each(|v| v.check_value(ctxt))
+    }
+}
+
+impl Args for ArgBuf {
+    type Values = ArgBuf;
+
+    fn into_values(self, _ctxt: &ContextRef) -> Self::Values {
+        self
+    }
+}
+
 tuple_args! {}
 tuple_args! { A }
 tuple_args! { A B }
@@ -135,6 +260,46 @@ impl<'a> Local<'a, Value> {
     ) -> Result<Local<Value>, Error> {
         self.ctxt.call_constructor2(self, new_target, args)
     }
+
+    /// Get a constructor property of the object, e.g. a class defined by the script in the global object.
+    pub fn get_constructor<N: NewAtom>(&self, name: N) -> Result<Local<'a, Value>, Error> {
+        self.ctxt.get_constructor(self, name)
+    }
+
+    /// Create a new instance with the constructor, like the `new` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.eval_script(
+    ///     "class Point { constructor(x, y) { this.x = x; this.y = y; } get len() { return Math.hypot(this.x, this.y); } }",
+    ///     "<eval>",
+    ///     Eval::GLOBAL,
+    /// ).unwrap();
+    ///
+    /// let point = ctxt.global_object().get_constructor("Point").unwrap().new_instance((3, 4)).unwrap();
+    ///
+    /// assert_eq!(point.class_name().unwrap(), "Point");
+    /// assert_eq!(point.get_property("len").unwrap().as_int().unwrap(), 5);
+    /// ```
+    pub fn new_instance<T: Args>(&self, args: T) -> Result<Local<'a, Value>, Error> {
+        self.ctxt.new_instance(self, None, args)
+    }
+
+    /// Create a new instance with the constructor and `new.target`,
+    /// like `Reflect.construct`, the prototype of the instance is taken from `new.target`.
+    pub fn new_instance_with_target<T: Args>(
+        &self,
+        new_target: &Value,
+        args: T,
+    ) -> Result<Local<'a, Value>, Error> {
+        self.ctxt.new_instance(self, Some(new_target), args)
+    }
 }
 
 impl ContextRef {
@@ -152,8 +317,13 @@ impl ContextRef {
         this: Option<&Value>,
         args: T,
     ) -> Result<Local<Value>, Error> {
+        self.check_owner(func)?;
+        this.map_or(Ok(()), |this| self.check_owner(this))?;
+        args.check_values(self)?;
+
         let args = args.into_values(self);
         let args = args.as_ref();
+        let _evaluation = self.start_evaluation();
         let ret = {
             unsafe {
                 ffi::JS_Call(
@@ -179,9 +349,13 @@ impl ContextRef {
         atom: N,
         args: T,
     ) -> Result<Local<Value>, Error> {
+        self.check_owner(this)?;
+        args.check_values(self)?;
+
         let atom = atom.new_atom(self);
         let args = args.into_values(self);
         let args = args.as_ref();
+        let _evaluation = self.start_evaluation();
 
         let res = self.bind(unsafe {
             ffi::JS_Invoke(
@@ -201,8 +375,12 @@ impl ContextRef {
     }
 
     pub fn call_constructor<T: Args>(&self, func: &Value, args: T) -> Result<Local<Value>, Error> {
+        self.check_owner(func)?;
+        args.check_values(self)?;
+
         let args = args.into_values(self);
         let args = args.as_ref();
+        let _evaluation = self.start_evaluation();
         let ret = unsafe {
             ffi::JS_CallConstructor(
                 self.as_ptr(),
@@ -219,14 +397,120 @@ impl ContextRef {
         self.bind(ret).ok()
     }
 
+    /// Get a property of the object, or a lexical variable of the global scope if the object is the global object.
+    fn get_property_or_global_var(&self, obj: &Value, atom: &Atom) -> Result<Local<Value>, Error> {
+        self.get_property(obj, atom)
+            .or_else(|| {
+                // the classes and `let` declared by the scripts are the lexical variables of the global scope
+                if self
+                    .strict_eq(obj, &self.global_object())
+                    .unwrap_or_default()
+                {
+                    let vars = self.bind(unsafe { ffi::JS_GetGlobalVarObject(self.as_ptr()) });
+
+                    if vars.is_object() && vars.has_property(atom).unwrap_or_default() {
+                        return self.get_property(&vars, atom);
+                    }
+                }
+
+                None
+            })
+            .ok_or_else(|| ErrorKind::ReferenceError(format!("`{}` is not defined", atom), None))?
+            .ok()
+    }
+
+    /// Get a constructor property of the object, e.g. a class defined by the script in the global object.
+    pub fn get_constructor<N: NewAtom>(&self, obj: &Value, name: N) -> Result<Local<Value>, Error> {
+        self.check_owner(obj)?;
+
+        let atom = self.new_atom(name);
+        let ctor = self.get_property_or_global_var(obj, &atom)?;
+
+        if ctor.is_constructor() {
+            Ok(ctor)
+        } else {
+            Err(ErrorKind::TypeError(format!("`{}` is not a constructor", atom), None).into())
+        }
+    }
+
+    /// Get a global function defined by the script as a typed function.
+    ///
+    /// The function is looked up once, and the arguments and the return value are converted on each call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.eval_script(
+    ///     "function validate(age, name) { return age >= 18 && name.length > 0 }",
+    ///     "<eval>",
+    ///     Eval::GLOBAL,
+    /// ).unwrap();
+    ///
+    /// let validate = ctxt.get_function::<(i32, String), bool>("validate").unwrap();
+    ///
+    /// assert!(validate.call((42, "Alice".to_owned())).unwrap());
+    /// assert!(!validate.call((12, "Bob".to_owned())).unwrap());
+    /// ```
+    pub fn get_function<A: Args, R: ExtractValue>(
+        &self,
+        name: &str,
+    ) -> Result<TypedFunction<A, R>, Error> {
+        let atom = self.new_atom(name);
+        let func = self.get_property_or_global_var(&self.global_object(), &atom)?;
+
+        if func.is_function() {
+            Ok(TypedFunction {
+                func,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(ErrorKind::TypeError(format!("`{}` is not a function", atom), None).into())
+        }
+    }
+
+    /// Create a new instance with the constructor and the optional `new.target`, like the `new` operator.
+    pub fn new_instance<T: Args>(
+        &self,
+        ctor: &Value,
+        new_target: Option<&Value>,
+        args: T,
+    ) -> Result<Local<Value>, Error> {
+        if !self.is_constructor(ctor) {
+            return Err(ErrorKind::TypeError("not a constructor".into(), None).into());
+        }
+        if new_target.map_or(false, |target| !self.is_constructor(target)) {
+            return Err(
+                ErrorKind::TypeError("`new.target` is not a constructor".into(), None).into(),
+            );
+        }
+
+        let obj = self.call_constructor2(ctor, Some(new_target.unwrap_or(ctor)), args)?;
+
+        if obj.is_object() {
+            Ok(obj)
+        } else {
+            Err(ErrorKind::TypeError("constructor returns a non-object".into(), None).into())
+        }
+    }
+
     pub fn call_constructor2<T: Args>(
         &self,
         func: &Value,
         new_target: Option<&Value>,
         args: T,
     ) -> Result<Local<Value>, Error> {
+        self.check_owner(func)?;
+        new_target.map_or(Ok(()), |target| self.check_owner(target))?;
+        args.check_values(self)?;
+
         let args = args.into_values(self);
         let args = args.as_ref();
+        let _evaluation = self.start_evaluation();
         let ret = unsafe {
             ffi::JS_CallConstructor2(
                 self.as_ptr(),
@@ -245,10 +529,38 @@ impl ContextRef {
     }
 }
 
+/// A function defined by the script, with the typed arguments and return value.
+pub struct TypedFunction<'a, A, R> {
+    func: Local<'a, Value>,
+    phantom: PhantomData<fn(A) -> R>,
+}
+
+impl<A, R> fmt::Debug for TypedFunction<'_, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TypedFunction").field(&self.func).finish()
+    }
+}
+
+impl<'a, A, R> TypedFunction<'a, A, R> {
+    /// Returns the underlying function.
+    pub fn as_value(&self) -> &Local<'a, Value> {
+        &self.func
+    }
+}
+
+impl<A: Args, R: ExtractValue> TypedFunction<'_, A, R> {
+    /// Call the function with the arguments, and extract the return value.
+    pub fn call(&self, args: A) -> Result<R, Error> {
+        self.func.call(None, args)?.extract()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Context, Eval, Runtime};
 
+    use super::*;
+
     #[test]
     fn call() {
         let _ = pretty_env_logger::try_init();
@@ -294,5 +606,127 @@ function Product(name, price) {
 
         assert_eq!(product.get_property("name").unwrap().to_string(), "foobar");
         assert_eq!(product.get_property("price").unwrap().as_int().unwrap(), 30);
+
+        let sum = ctxt
+            .eval_script(
+                "(function (...args) { return args.reduce((a, b) => a + b, 0) })",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+        for n in &[0, 3, INLINE_ARGS, INLINE_ARGS + 1, 20] {
+            let args = (1..=*n as i32).collect::<Vec<_>>();
+            let expected = args.iter().sum::<i32>();
+
+            assert_eq!(sum.call(None, args.as_slice()).unwrap(), expected);
+            assert_eq!(sum.call(None, args).unwrap(), expected);
+        }
+
+        let mut args = ArgBuf::new();
+
+        args.push(ctxt.new_value(1).raw());
+        args.push(ctxt.new_value(2).raw());
+
+        assert_eq!(sum.call(None, args).unwrap(), 3);
+        assert_eq!(sum.call(None, [1, 2, 3]).unwrap(), 6);
+    }
+
+    #[test]
+    fn new_instance() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.eval_script(
+            r#"
+class Animal {
+    constructor(name) { this.name = name; this.target = new.target.name; }
+    speak() { return `${this.name} makes a sound`; }
+}
+class Dog extends Animal {
+    speak() { return `${this.name} barks`; }
+}
+var notCtor = () => {};
+"#,
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let global = ctxt.global_object();
+        let animal = global.get_constructor("Animal").unwrap();
+        let dog = global.get_constructor("Dog").unwrap();
+
+        let rex = dog.new_instance(["Rex"]).unwrap();
+
+        assert_eq!(rex.invoke("speak", ()).unwrap().to_string(), "Rex barks");
+        assert_eq!(rex.get_property("target").unwrap().to_string(), "Dog");
+
+        let pet = animal.new_instance_with_target(&dog, ["Pet"]).unwrap();
+
+        assert_eq!(pet.invoke("speak", ()).unwrap().to_string(), "Pet barks");
+        assert_eq!(pet.get_property("target").unwrap().to_string(), "Dog");
+        assert!(pet.instance_of(&dog).unwrap());
+
+        assert!(global.get_constructor("Missing").is_err());
+        assert!(global.get_constructor("notCtor").is_err());
+        assert!(animal.new_instance_with_target(&global, ["x"]).is_err());
+        assert!(animal
+            .call(None, ["x"])
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .is_ok());
+    }
+
+    #[test]
+    fn get_function() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.eval_script(
+            r#"
+function validate(age, name) { return age >= 18 && name.length > 0; }
+const greet = (name) => `hello ${name}`;
+var counter = 0;
+function fail() { throw new TypeError('boom'); }
+"#,
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let validate = ctxt
+            .get_function::<(i32, String), bool>("validate")
+            .unwrap();
+
+        assert!(validate.call((42, "Alice".to_owned())).unwrap());
+        assert!(!validate.call((42, String::new())).unwrap());
+        assert!(!validate.call((12, "Bob".to_owned())).unwrap());
+
+        // the lexical variables of the global scope
+        let greet = ctxt.get_function::<&str, String>("greet").unwrap();
+
+        assert_eq!(greet.call("world").unwrap(), "hello world");
+
+        // the function is cached, the later redefinition is not visible
+        ctxt.eval_script(
+            "function validate() { return true; }",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        assert!(!validate.call((12, "Bob".to_owned())).unwrap());
+
+        assert!(ctxt.get_function::<(), ()>("missing").is_err());
+        assert!(ctxt.get_function::<(), ()>("counter").is_err());
+
+        let fail = ctxt.get_function::<(), ()>("fail").unwrap();
+
+        assert!(fail.call(()).unwrap_err().downcast::<ErrorKind>().is_ok());
     }
 }
@@ -0,0 +1,162 @@
+use std::cell::Cell;
+
+use failure::Error;
+
+use crate::{ContextRef, Eval, Local, RuntimeRef, Value, WriteObj};
+
+/// The gas charged each time the engine polls the interrupt handler.
+///
+/// QuickJS polls the interrupt handler every 10000 function calls or backward jumps,
+/// the counter is kept by the context, so the gas is charged in this granularity.
+pub const GAS_PER_INTERRUPT: u64 = 10_000;
+
+/// The gas charged for each byte of the compiled bytecode.
+pub const GAS_PER_BYTECODE: u64 = 1;
+
+/// The evaluation was interrupted because the gas limit exceeded.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+#[fail(display = "out of gas, used {} of {} gas", used, limit)]
+pub struct OutOfGas {
+    /// the consumed gas
+    pub used: u64,
+    /// the gas limit
+    pub limit: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct GasMeter {
+    limit: Cell<Option<u64>>,
+    used: Cell<u64>,
+}
+
+impl GasMeter {
+    pub fn is_metering(&self) -> bool {
+        self.limit.get().is_some()
+    }
+
+    /// Consume the gas, returns `false` if the gas limit exceeded.
+    pub fn consume(&self, gas: u64) -> bool {
+        self.used.set(self.used.get().saturating_add(gas));
+
+        !self.is_exhausted()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.limit
+            .get()
+            .map_or(false, |limit| self.used.get() > limit)
+    }
+
+    fn out_of_gas(&self) -> OutOfGas {
+        OutOfGas {
+            used: self.used.get(),
+            limit: self.limit.get().unwrap_or_default(),
+        }
+    }
+}
+
+/// Stop the metering when dropped.
+struct Metering<'a>(&'a RuntimeRef);
+
+impl<'a> Metering<'a> {
+    fn start(rt: &'a RuntimeRef, limit: u64) -> Result<Self, Error> {
+        let meter = rt.state::<GasMeter>();
+
+        if meter.is_metering() {
+            bail!("gas metering already in progress")
+        }
+
+        meter.limit.set(Some(limit));
+        meter.used.set(0);
+
+        rt.update_interrupt_handler();
+
+        Ok(Metering(rt))
+    }
+}
+
+impl Drop for Metering<'_> {
+    fn drop(&mut self) {
+        self.0.state::<GasMeter>().limit.set(None);
+        self.0.update_interrupt_handler();
+    }
+}
+
+impl ContextRef {
+    /// Evaluate a script or module source with the gas limit.
+    ///
+    /// The gas is approximately counted with the size of compiled bytecode,
+    /// and the times of the engine polling the interrupt handler during the execution.
+    ///
+    /// It returns the result with the consumed gas, or `OutOfGas` error if the gas limit exceeded.
+    pub fn eval_with_gas<T: Into<Vec<u8>>>(
+        &self,
+        input: T,
+        filename: &str,
+        flags: Eval,
+        limit: u64,
+    ) -> Result<(Local<Value>, u64), Error> {
+        let rt = self.runtime();
+        let _metering = Metering::start(rt, limit)?;
+        let meter = rt.state::<GasMeter>();
+
+        let func = self.eval_script(input, filename, flags | Eval::COMPILE_ONLY)?;
+        let bytecode = self.write_object(&func, WriteObj::BYTECODE)?;
+
+        trace!("compiled to {} bytes bytecode", bytecode.len());
+
+        if !meter.consume(bytecode.len() as u64 * GAS_PER_BYTECODE) {
+            return Err(meter.out_of_gas().into());
+        }
+
+        match self.eval_function(func) {
+            Ok(res) => Ok((res, meter.used.get())),
+            Err(_) if meter.is_exhausted() => Err(meter.out_of_gas().into()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn eval_with_gas() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let (res, used) = ctxt
+            .eval_with_gas("1+2", "<evalScript>", Eval::GLOBAL, 100_000)
+            .unwrap();
+
+        assert_eq!(res.as_int(), Some(3));
+        assert!(used > 0 && used < 2 * GAS_PER_INTERRUPT);
+
+        let (_, used2) = ctxt
+            .eval_with_gas(
+                "for (var i=0; i<1000000; i++) {}",
+                "<evalScript>",
+                Eval::GLOBAL,
+                u64::max_value(),
+            )
+            .unwrap();
+
+        assert!(used2 > 1_000_000);
+
+        let err = ctxt
+            .eval_with_gas("while(true) {}", "<evalScript>", Eval::GLOBAL, 100_000)
+            .unwrap_err()
+            .downcast::<OutOfGas>()
+            .unwrap();
+
+        assert_eq!(err.limit, 100_000);
+        assert!(err.used > err.limit);
+
+        assert_eq!(ctxt.eval::<_, i32>("1+2", Eval::GLOBAL).unwrap(), Some(3));
+    }
+}
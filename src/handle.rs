@@ -1,6 +1,9 @@
 use std::ops::{Deref, DerefMut};
+use std::panic::Location;
 
-use crate::ContextRef;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, RuntimeRef, Value};
 
 pub trait Bindable<'a> {
     type Output: Unbindable;
@@ -10,8 +13,16 @@ pub trait Bindable<'a> {
 
 pub trait Unbindable {
     fn unbind(ctxt: &ContextRef, inner: Self);
+
+    /// Called when the value is detached from the `Local` with `Local::into_inner`.
+    fn detach(_ctxt: &ContextRef, _inner: &Self, _location: &'static Location<'static>) {}
 }
 
+/// The value belongs to another `Runtime`.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+#[fail(display = "value belongs to another runtime")]
+pub struct RuntimeMismatch;
+
 pub struct Local<'a, T>
 where
     T: Unbindable,
@@ -55,7 +66,30 @@ impl<'a, T> Local<'a, T>
 where
     T: Unbindable,
 {
-    pub fn into_inner(mut self) -> T {
+    /// The context which owns the value.
+    pub fn context(&self) -> &'a ContextRef {
+        self.ctxt
+    }
+
+    /// The runtime which owns the value.
+    pub fn runtime(&self) -> &'a RuntimeRef {
+        self.ctxt.runtime()
+    }
+
+    /// Detach the value from the `Local`, which must be freed manually.
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        let ctxt = self.ctxt;
+        let inner = self.into_inner_untracked();
+
+        T::detach(ctxt, &inner, Location::caller());
+
+        inner
+    }
+
+    /// Detach the value from the `Local` without the leak detection,
+    /// the ownership is passed to the engine or a state which frees it.
+    pub(crate) fn into_inner_untracked(mut self) -> T {
         self.inner.take().unwrap()
     }
 
@@ -74,6 +108,33 @@ where
 }
 
 impl ContextRef {
+    /// Check if the value belongs to the same runtime of the context.
+    ///
+    /// The values could be shared between the contexts of the same runtime,
+    /// but passing a value to another runtime will corrupt the heap,
+    /// so the functions which take the values, e.g. `call` and `set_property`, return `RuntimeMismatch`.
+    pub fn check_runtime<T: Unbindable>(&self, local: &Local<T>) -> Result<(), RuntimeMismatch> {
+        if local.ctxt.runtime().as_ptr() == self.runtime().as_ptr() {
+            Ok(())
+        } else {
+            Err(RuntimeMismatch)
+        }
+    }
+
+    /// Check if the raw value belongs to the same runtime of the context.
+    ///
+    /// Only the objects record their owning runtime, the other values are always accepted,
+    /// and nothing could be checked if the linked library is not patched.
+    pub fn check_owner(&self, val: &Value) -> Result<(), RuntimeMismatch> {
+        let rt = unsafe { ffi::JS_GetValueRuntime(val.raw()) };
+
+        if rt.is_null() || rt == self.runtime().as_ptr() {
+            Ok(())
+        } else {
+            Err(RuntimeMismatch)
+        }
+    }
+
     pub fn bind<'a, T: Bindable<'a>>(&'a self, val: T) -> Local<'a, T::Output> {
         Local {
             ctxt: self,
@@ -81,3 +142,114 @@ impl ContextRef {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Prop, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn check_runtime() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let ctxt2 = Context::new(&rt);
+
+        let rt2 = Runtime::new();
+        let ctxt3 = Context::new(&rt2);
+
+        let obj = ctxt.bind(ctxt.new_object());
+
+        assert_eq!(obj.runtime(), &*rt);
+        assert_eq!(ctxt.check_runtime(&obj), Ok(()));
+        assert_eq!(ctxt2.check_runtime(&obj), Ok(()));
+        assert_eq!(ctxt3.check_runtime(&obj), Err(RuntimeMismatch));
+
+        let global = ctxt3.global_object();
+        let f = ctxt3
+            .eval_script("(function (x) { return x })", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        for err in vec![
+            global.set_property("obj", &obj).unwrap_err(),
+            global
+                .define_property_value("obj", &obj, Prop::WRITABLE)
+                .unwrap_err(),
+            global.set_many(&[("obj", &obj)]).unwrap_err(),
+            f.call(None, &obj).unwrap_err(),
+            f.call(None, (1, &obj)).unwrap_err(),
+            f.call(None, vec![&obj]).unwrap_err(),
+            global.invoke("f", [&obj]).unwrap_err(),
+        ] {
+            assert_eq!(err.downcast::<RuntimeMismatch>().unwrap(), RuntimeMismatch);
+        }
+
+        assert!(!global.has_property("obj").unwrap());
+        assert_eq!(
+            ctxt3.eval::<_, i32>("1 + 2", Eval::GLOBAL).unwrap(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn check_owner() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let ctxt2 = Context::new(&rt);
+
+        let rt2 = Runtime::new();
+        let ctxt3 = Context::new(&rt2);
+
+        let obj = ctxt
+            .eval_script("({ x: 1 })", "<eval>", Eval::GLOBAL)
+            .unwrap();
+        let f = ctxt
+            .eval_script("(function () { return this })", "<eval>", Eval::GLOBAL)
+            .unwrap();
+        let global = ctxt3.global_object();
+        let f3 = ctxt3
+            .eval_script("(function () { return this })", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(ctxt.check_owner(&obj), Ok(()));
+        assert_eq!(ctxt2.check_owner(&obj), Ok(()));
+        assert_eq!(ctxt3.check_owner(&obj), Err(RuntimeMismatch));
+        assert_eq!(ctxt3.check_owner(&ctxt.bind(ctxt.new_value(1))), Ok(()));
+        assert_eq!(ctxt3.check_owner(&ctxt.bind(ctxt.new_value("foo"))), Ok(()));
+
+        assert!(ctxt3.get_property(&obj, "x").is_none());
+        assert!(ctxt3.get_prototype(&obj).is_null());
+
+        for err in vec![
+            ctxt3.call(&f, None, ()).unwrap_err(),
+            ctxt3.call(&f3, Some(&obj), ()).unwrap_err(),
+            ctxt3.invoke(&obj, "toString", ()).unwrap_err(),
+            ctxt3.call_constructor(&f, ()).unwrap_err(),
+            ctxt3.new_instance(&f3, Some(&f), ()).unwrap_err(),
+            ctxt3.set_property(&obj, "x", 2).unwrap_err(),
+            ctxt3.set_property(&global, "obj", &*obj).unwrap_err(),
+            ctxt3.has_property(&obj, "x").unwrap_err(),
+            ctxt3.delete_property(&obj, "x").unwrap_err(),
+            ctxt3
+                .define_property_value(&obj, "y", 1, Prop::WRITABLE)
+                .unwrap_err(),
+            ctxt3
+                .define_property_get_set(&global, "y", Some(&f), None, Prop::CONFIGURABLE)
+                .unwrap_err(),
+            ctxt3.get_many(&obj, &["x"]).unwrap_err(),
+            ctxt3.own_keys(&obj).unwrap_err(),
+            ctxt3.prevent_extensions(&obj).unwrap_err(),
+            ctxt3.set_prototype(&global, &obj).unwrap_err(),
+        ] {
+            assert_eq!(err.downcast::<RuntimeMismatch>().unwrap(), RuntimeMismatch);
+        }
+
+        assert_eq!(ctxt.get_property(&obj, "x").unwrap().to_int32(), Some(1));
+        assert!(!global.has_property("obj").unwrap());
+        assert!(!global.has_property("y").unwrap());
+    }
+}
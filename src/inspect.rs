@@ -0,0 +1,839 @@
+use std::collections::HashMap;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Local, PropertyNames as Names, Value};
+
+/// The default max depth of the nested objects to inspect.
+pub const DEFAULT_INSPECT_DEPTH: usize = 2;
+
+/// The default max number of the array, set or map elements to inspect.
+const DEFAULT_MAX_ARRAY_LENGTH: usize = 100;
+
+/// The default max number of the `ArrayBuffer` bytes to inspect.
+const MAX_BUFFER_BYTES: usize = 50;
+
+/// The default length at which the entries are split across multiple lines.
+const DEFAULT_BREAK_LENGTH: usize = 80;
+
+const TYPED_ARRAYS: &[&str] = &[
+    "Uint8ClampedArray",
+    "Int8Array",
+    "Uint8Array",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "BigInt64Array",
+    "BigUint64Array",
+    "Float32Array",
+    "Float64Array",
+];
+
+/// The options of `Value::inspect`.
+#[derive(Clone, Debug)]
+pub struct InspectOptions {
+    depth: Option<usize>,
+    colors: bool,
+    max_array_length: usize,
+    break_length: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        InspectOptions {
+            depth: Some(DEFAULT_INSPECT_DEPTH),
+            colors: false,
+            max_array_length: DEFAULT_MAX_ARRAY_LENGTH,
+            break_length: DEFAULT_BREAK_LENGTH,
+        }
+    }
+}
+
+impl InspectOptions {
+    pub fn new() -> Self {
+        InspectOptions::default()
+    }
+
+    /// Set the max depth of the nested objects, `None` to recurse without limit.
+    pub fn with_depth(mut self, depth: Option<usize>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Style the output with the ANSI color codes.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Set the max number of the array, set or map elements to include.
+    pub fn with_max_array_length(mut self, len: usize) -> Self {
+        self.max_array_length = len;
+        self
+    }
+
+    /// Set the length at which the entries are split across multiple lines.
+    pub fn with_break_length(mut self, len: usize) -> Self {
+        self.break_length = len;
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Number,
+    String,
+    Undefined,
+    Null,
+    Date,
+    RegExp,
+    Special,
+}
+
+impl Style {
+    fn codes(self) -> (u8, u8) {
+        match self {
+            Style::Number => (33, 39),
+            Style::String => (32, 39),
+            Style::Undefined => (90, 39),
+            Style::Null => (1, 22),
+            Style::Date => (35, 39),
+            Style::RegExp => (31, 39),
+            Style::Special => (36, 39),
+        }
+    }
+}
+
+impl<'a> Local<'a, Value> {
+    /// Format the value like `util.inspect` of Node.js.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, InspectOptions, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let v = ctxt.eval_script(
+    ///     "var o = { s: 'hi', a: [1, 2], u8: new Uint8Array(2), m: new Map([[1, true]]) }; o.self = o; o",
+    ///     "<input>",
+    ///     Eval::GLOBAL,
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     v.inspect(&InspectOptions::new().with_break_length(120)),
+    ///     "<ref *1> { s: 'hi', a: [ 1, 2 ], u8: Uint8Array(2) [ 0, 0 ], m: Map(1) { 1 => true }, self: [Circular *1] }"
+    /// );
+    /// ```
+    pub fn inspect(&self, options: &InspectOptions) -> String {
+        self.ctxt.inspect(self, options)
+    }
+}
+
+impl ContextRef {
+    /// Format the value like `util.inspect` of Node.js.
+    pub fn inspect(&self, value: &Value, options: &InspectOptions) -> String {
+        Inspector {
+            ctxt: self,
+            options,
+            seen: vec![],
+            circular: HashMap::new(),
+        }
+        .format(&self.clone_value(value), 0, 0)
+    }
+}
+
+struct Inspector<'a> {
+    ctxt: &'a ContextRef,
+    options: &'a InspectOptions,
+    seen: Vec<usize>,
+    circular: HashMap<usize, usize>,
+}
+
+impl<'a> Inspector<'a> {
+    fn stylize<S: AsRef<str>>(&self, s: S, style: Style) -> String {
+        if self.options.colors {
+            let (open, close) = style.codes();
+
+            format!("\x1b[{}m{}\x1b[{}m", open, s.as_ref(), close)
+        } else {
+            s.as_ref().to_owned()
+        }
+    }
+
+    fn format(&mut self, value: &Local<Value>, depth: usize, indent: usize) -> String {
+        if value.is_object() {
+            self.format_object(value, depth, indent)
+        } else {
+            self.format_primitive(value)
+        }
+    }
+
+    fn format_primitive(&self, value: &Local<Value>) -> String {
+        match value.tag() {
+            ffi::JS_TAG_STRING => self.stylize(quote(&value.to_string()), Style::String),
+            ffi::JS_TAG_SYMBOL => self.stylize(
+                value
+                    .invoke("toString", ())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| "Symbol()".to_owned()),
+                Style::String,
+            ),
+            ffi::JS_TAG_UNDEFINED => self.stylize("undefined", Style::Undefined),
+            ffi::JS_TAG_NULL => self.stylize("null", Style::Null),
+            ffi::JS_TAG_BIG_INT => self.stylize(format!("{}n", value), Style::Number),
+            ffi::JS_TAG_FLOAT64
+                if value
+                    .as_float()
+                    .map_or(false, |n| n == 0.0 && n.is_sign_negative()) =>
+            {
+                self.stylize("-0", Style::Number)
+            }
+            _ => self.stylize(value.to_string(), Style::Number),
+        }
+    }
+
+    fn format_object(&mut self, value: &Local<Value>, depth: usize, indent: usize) -> String {
+        let ptr = value.as_object().unwrap().as_ptr() as usize;
+
+        if self.seen.contains(&ptr) {
+            let next = self.circular.len() + 1;
+            let idx = *self.circular.entry(ptr).or_insert(next);
+
+            return self.stylize(format!("[Circular *{}]", idx), Style::Special);
+        }
+
+        let ctxt = self.ctxt;
+        let tag = self.builtin_tag(value);
+        let constructor = self.constructor_name(value);
+        let is_array = ctxt.is_array(value).unwrap_or_default();
+        let is_typed_array = TYPED_ARRAYS.contains(&tag.as_str());
+        let prefix = match constructor.as_deref() {
+            None => "[Object: null prototype] ".to_owned(),
+            Some("Object") => String::new(),
+            Some("Array") if is_array => String::new(),
+            Some("") => String::new(),
+            Some(name) => format!("{} ", name),
+        };
+
+        if value.is_error() {
+            let mut s = value.to_string();
+
+            if let Some(stack) = value.get_property("stack").filter(|s| s.is_string()) {
+                let stack = stack.to_string();
+
+                if !stack.is_empty() {
+                    s.push('\n');
+                    s.push_str(stack.trim_end());
+                }
+            }
+
+            return s;
+        }
+
+        if self.options.depth.map_or(false, |max| depth > max) {
+            let name = if is_array {
+                "Array".to_owned()
+            } else {
+                constructor.unwrap_or_else(|| "Object".to_owned())
+            };
+
+            return self.stylize(format!("[{}]", name), Style::Special);
+        }
+
+        self.seen.push(ptr);
+
+        let mut entries = vec![];
+        let mut keys = self.own_keys(value);
+        // the primitive-like objects are formatted without the braces if there is no property
+        let mut bare = true;
+        let (start, braces) = if value.is_function() {
+            (self.function_name(value), ("{", "}"))
+        } else if is_array || is_typed_array {
+            let len = value
+                .get_property("length")
+                .and_then(|len| len.to_index())
+                .unwrap_or_default() as usize;
+
+            keys.retain(|key| !is_index(key, len));
+            entries.extend(self.format_elements(value, len, is_array, depth, indent));
+
+            let start = if is_array {
+                prefix
+            } else {
+                format!("{}({}) ", tag, len)
+            };
+
+            (start, ("[", "]"))
+        } else if tag == "Map" || tag == "Set" {
+            entries.extend(self.format_collection(value, tag == "Map", depth, indent));
+
+            let size = value
+                .get_property("size")
+                .and_then(|size| size.to_index())
+                .unwrap_or_default();
+
+            (format!("{}({}) ", tag, size), ("{", "}"))
+        } else if tag == "WeakMap" || tag == "WeakSet" {
+            entries.push(self.stylize("<items unknown>", Style::Special));
+
+            (prefix, ("{", "}"))
+        } else if tag == "ArrayBuffer" || tag == "SharedArrayBuffer" {
+            entries.extend(self.format_array_buffer(value));
+
+            (prefix, ("{", "}"))
+        } else if tag == "Date" {
+            let date = value
+                .invoke("toISOString", ())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|_| {
+                    let _ = ctxt.get_exception();
+
+                    "Invalid Date".to_owned()
+                });
+
+            (self.stylize(date, Style::Date) + " ", ("{", "}"))
+        } else if tag == "RegExp" {
+            let re = value
+                .invoke("toString", ())
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+
+            (self.stylize(re, Style::RegExp) + " ", ("{", "}"))
+        } else if let Some(boxed) = self.format_boxed(value, &tag) {
+            if tag == "String" {
+                let len = value
+                    .get_property("length")
+                    .and_then(|len| len.to_index())
+                    .unwrap_or_default() as usize;
+
+                keys.retain(|key| !is_index(key, len));
+            }
+
+            (boxed + " ", ("{", "}"))
+        } else {
+            bare = false;
+
+            (prefix, ("{", "}"))
+        };
+
+        if is_array || is_typed_array || tag == "Map" || tag == "Set" {
+            bare = false;
+        }
+
+        for key in keys {
+            let entry = self.format_property(value, &key, depth, indent);
+
+            entries.push(entry);
+        }
+
+        self.seen.pop();
+
+        let mut s = if bare && entries.is_empty() {
+            start.trim_end().to_owned()
+        } else {
+            self.reduce(start, entries, braces, indent)
+        };
+
+        if let Some(idx) = self.circular.get(&ptr) {
+            s = format!(
+                "{} {}",
+                self.stylize(format!("<ref *{}>", idx), Style::Special),
+                s
+            );
+        }
+
+        s
+    }
+
+    /// Combine the entries into a single line if it fits, or split them across multiple lines.
+    fn reduce(
+        &self,
+        start: String,
+        entries: Vec<String>,
+        (open, close): (&str, &str),
+        indent: usize,
+    ) -> String {
+        let start = start.trim_end();
+        let head = if start.is_empty() {
+            open.to_owned()
+        } else {
+            format!("{} {}", start, open)
+        };
+
+        if entries.is_empty() {
+            return format!("{}{}", head, close);
+        }
+
+        let total = entries
+            .iter()
+            .map(|entry| visible_len(entry))
+            .sum::<usize>()
+            + entries.len() * 2
+            + visible_len(&head)
+            + close.len()
+            + 1;
+
+        if indent + total <= self.options.break_length
+            && entries.iter().all(|entry| !entry.contains('\n'))
+        {
+            format!("{} {} {}", head, entries.join(", "), close)
+        } else {
+            let padding = " ".repeat(indent + 2);
+
+            format!(
+                "{}\n{}{}\n{}{}",
+                head,
+                padding,
+                entries.join(&format!(",\n{}", padding)),
+                " ".repeat(indent),
+                close
+            )
+        }
+    }
+
+    fn format_elements(
+        &mut self,
+        value: &Local<Value>,
+        len: usize,
+        check_holes: bool,
+        depth: usize,
+        indent: usize,
+    ) -> Vec<String> {
+        let max = self.options.max_array_length;
+        let mut entries = vec![];
+        let mut holes = 0;
+        let mut idx = 0;
+
+        while idx < len && entries.len() < max {
+            if check_holes && !value.has_property(idx as u32).unwrap_or_default() {
+                holes += 1;
+                idx += 1;
+                continue;
+            }
+
+            if holes > 0 {
+                entries.push(self.stylize(empty_items(holes), Style::Undefined));
+                holes = 0;
+            }
+
+            let item = value
+                .get_property(idx as u32)
+                .unwrap_or_else(|| self.ctxt.bind(ffi::UNDEFINED));
+
+            entries.push(self.format(&item, depth + 1, indent + 2));
+            idx += 1;
+        }
+
+        if holes > 0 {
+            entries.push(self.stylize(empty_items(holes), Style::Undefined));
+        }
+        if idx < len {
+            entries.push(more_items(len - idx));
+        }
+
+        entries
+    }
+
+    fn format_collection(
+        &mut self,
+        value: &Local<Value>,
+        is_map: bool,
+        depth: usize,
+        indent: usize,
+    ) -> Vec<String> {
+        let ctxt = self.ctxt;
+        let items = match ctxt
+            .get_property(&ctxt.global_object(), "Array")
+            .and_then(|array| ctxt.invoke(&array, "from", value).ok())
+        {
+            Some(items) => items,
+            None => {
+                let _ = self.ctxt.get_exception();
+
+                return vec![];
+            }
+        };
+        let len = items
+            .get_property("length")
+            .and_then(|len| len.to_index())
+            .unwrap_or_default() as usize;
+        let max = self.options.max_array_length;
+        let mut entries = vec![];
+
+        for idx in 0..len.min(max) {
+            let item = match items.get_property(idx as u32) {
+                Some(item) => item,
+                None => continue,
+            };
+
+            if is_map {
+                let k = item
+                    .get_property(0)
+                    .unwrap_or_else(|| self.ctxt.bind(ffi::UNDEFINED));
+                let v = item
+                    .get_property(1)
+                    .unwrap_or_else(|| self.ctxt.bind(ffi::UNDEFINED));
+                let k = self.format(&k, depth + 1, indent + 2);
+                let v = self.format(&v, depth + 1, indent + 2);
+
+                entries.push(format!("{} => {}", k, v));
+            } else {
+                entries.push(self.format(&item, depth + 1, indent + 2));
+            }
+        }
+
+        if len > max {
+            entries.push(more_items(len - max));
+        }
+
+        entries
+    }
+
+    fn format_array_buffer(&self, value: &Local<Value>) -> Vec<String> {
+        let mut size = 0;
+        let data = unsafe { ffi::JS_GetArrayBuffer(self.ctxt.as_ptr(), &mut size, value.raw()) };
+
+        if data.is_null() {
+            let _ = self.ctxt.get_exception();
+
+            return vec![];
+        }
+
+        let bytes = unsafe { std::slice::from_raw_parts(data, size) };
+        let mut contents = bytes
+            .iter()
+            .take(MAX_BUFFER_BYTES)
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if size > MAX_BUFFER_BYTES {
+            contents.push_str(&format!(
+                " ... {} more byte{}",
+                size - MAX_BUFFER_BYTES,
+                plural(size - MAX_BUFFER_BYTES)
+            ));
+        }
+
+        vec![
+            format!("[Uint8Contents]: <{}>", contents),
+            format!(
+                "byteLength: {}",
+                self.stylize(size.to_string(), Style::Number)
+            ),
+        ]
+    }
+
+    fn format_boxed(&self, value: &Local<Value>, tag: &str) -> Option<String> {
+        if !["Number", "String", "Boolean", "Symbol", "BigInt"].contains(&tag) {
+            return None;
+        }
+
+        match value.invoke("valueOf", ()) {
+            Ok(primitive) if !primitive.is_object() => {
+                Some(format!("[{}: {}]", tag, self.format_primitive(&primitive)))
+            }
+            Ok(_) => None,
+            Err(_) => {
+                let _ = self.ctxt.get_exception();
+
+                None
+            }
+        }
+    }
+
+    fn format_property(
+        &mut self,
+        value: &Local<Value>,
+        key: &Local<ffi::JSAtom>,
+        depth: usize,
+        indent: usize,
+    ) -> String {
+        let name = key.to_value();
+        let name = if name.is_symbol() {
+            format!("[{}]", self.format_primitive(&name))
+        } else {
+            let name = name.to_string();
+
+            if is_identifier(&name) {
+                name
+            } else {
+                self.stylize(quote(&name), Style::String)
+            }
+        };
+
+        let desc = value
+            .get_own_property_descriptor(key)
+            .ok()
+            .and_then(|desc| desc);
+        let accessor = desc.as_ref().map(|desc| {
+            (
+                desc.getter.as_ref().map_or(false, |f| !f.is_undefined()),
+                desc.setter.as_ref().map_or(false, |f| !f.is_undefined()),
+            )
+        });
+
+        let formatted = match accessor {
+            Some((true, true)) => self.stylize("[Getter/Setter]", Style::Special),
+            Some((true, false)) => self.stylize("[Getter]", Style::Special),
+            Some((false, true)) => self.stylize("[Setter]", Style::Special),
+            _ => {
+                let v = value
+                    .get_property(key)
+                    .unwrap_or_else(|| self.ctxt.bind(ffi::UNDEFINED));
+
+                self.format(&v, depth + 1, indent + 2)
+            }
+        };
+
+        format!("{}: {}", name, formatted)
+    }
+
+    fn own_keys(&self, value: &Local<Value>) -> Vec<Local<'a, ffi::JSAtom>> {
+        self.ctxt
+            .get_own_property_names(value, Names::STRING | Names::SYMBOL | Names::ENUM_ONLY)
+            .ok()
+            .and_then(|names| names)
+            .unwrap_or_default()
+    }
+
+    /// The `[[Class]]` tag of the object from `Object.prototype.toString`.
+    fn builtin_tag(&self, value: &Local<Value>) -> String {
+        let ctxt = self.ctxt;
+        let tag = ctxt
+            .get_property(&ctxt.global_object(), "Object")
+            .and_then(|object| ctxt.get_property(&object, "prototype"))
+            .and_then(|proto| ctxt.get_property(&proto, "toString"))
+            .and_then(|to_string| ctxt.call(&to_string, Some(value), ()).ok())
+            .map(|tag| tag.to_string())
+            .unwrap_or_default();
+
+        tag.trim_start_matches("[object ")
+            .trim_end_matches(']')
+            .to_owned()
+    }
+
+    /// The name of the constructor of the object, `None` if the object has no prototype.
+    fn constructor_name(&self, value: &Local<Value>) -> Option<String> {
+        let proto = Value::from(unsafe { ffi::JS_GetPrototype(self.ctxt.as_ptr(), value.raw()) });
+
+        if !proto.is_object() {
+            return None;
+        }
+
+        Some(
+            self.ctxt
+                .get_property(&proto, "constructor")
+                .filter(|f| f.is_function())
+                .and_then(|f| self.ctxt.get_property(&f, "name"))
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn function_name(&self, value: &Local<Value>) -> String {
+        let name = value
+            .get_property("name")
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+        let ctxt = self.ctxt;
+        let is_class = ctxt
+            .get_property(&ctxt.global_object(), "Function")
+            .and_then(|func| ctxt.get_property(&func, "prototype"))
+            .and_then(|proto| ctxt.get_property(&proto, "toString"))
+            .and_then(|to_string| ctxt.call(&to_string, Some(value), ()).ok())
+            .map_or(false, |src| src.to_string().starts_with("class"));
+
+        let s = match (is_class, name.is_empty()) {
+            (true, false) => format!("[class {}]", name),
+            (true, true) => "[class (anonymous)]".to_owned(),
+            (false, false) => format!("[Function: {}]", name),
+            (false, true) => "[Function (anonymous)]".to_owned(),
+        };
+
+        self.stylize(s, Style::Special)
+    }
+}
+
+/// Quote the string like Node.js, prefers the single quotes.
+fn quote(s: &str) -> String {
+    let quote = if !s.contains('\'') {
+        '\''
+    } else if !s.contains('"') {
+        '"'
+    } else if !s.contains('`') && !s.contains("${") {
+        '`'
+    } else {
+        '\''
+    };
+
+    let mut out = String::with_capacity(s.len() + 2);
+
+    out.push(quote);
+
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\u{b}' => out.push_str("\\v"),
+            '\\' => out.push_str("\\\\"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c < ' ' || c == '\u{7f}' => out.push_str(&format!("\\x{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push(quote);
+    out
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    chars
+        .next()
+        .map_or(false, |c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn is_index(key: &Local<ffi::JSAtom>, len: usize) -> bool {
+    key.to_string()
+        .parse::<u32>()
+        .map_or(false, |idx| (idx as usize) < len)
+}
+
+/// The length of the string without the ANSI escape codes.
+fn visible_len(s: &str) -> usize {
+    let mut len = 0;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in &mut chars {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            len += 1;
+        }
+    }
+
+    len
+}
+
+fn plural(n: usize) -> &'static str {
+    if n > 1 {
+        "s"
+    } else {
+        ""
+    }
+}
+
+fn empty_items(n: usize) -> String {
+    format!("<{} empty item{}>", n, plural(n))
+}
+
+fn more_items(n: usize) -> String {
+    format!("... {} more item{}", n, plural(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn inspect() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let opts = InspectOptions::new();
+        let inspect = |code: &str, opts: &InspectOptions| {
+            ctxt.eval_script(code, "<input>", Eval::GLOBAL)
+                .unwrap()
+                .inspect(opts)
+        };
+
+        assert_eq!(inspect("'it\\'s\\n'", &opts), r#""it's\n""#);
+        assert_eq!(inspect("-0", &opts), "-0");
+        assert_eq!(
+            inspect("[Symbol('s'), undefined, null]", &opts),
+            "[ Symbol(s), undefined, null ]"
+        );
+        assert_eq!(inspect("[1, , , 4]", &opts), "[ 1, <2 empty items>, 4 ]");
+        assert_eq!(
+            inspect(
+                "({ 'a-b': 1, [Symbol.iterator]: 2, get x() { return 1 } })",
+                &opts
+            ),
+            "{ 'a-b': 1, x: [Getter], [Symbol(Symbol.iterator)]: 2 }"
+        );
+        assert_eq!(
+            inspect("({ a: { b: { c: { d: 1 } } } })", &opts),
+            "{ a: { b: { c: [Object] } } }"
+        );
+        assert_eq!(
+            inspect(
+                "({ a: { b: { c: { d: 1 } } } })",
+                &InspectOptions::new().with_depth(None)
+            ),
+            "{ a: { b: { c: { d: 1 } } } }"
+        );
+        assert_eq!(
+            inspect(
+                "class Point { constructor() { this.x = 1 } }; [new Point(), Point]",
+                &opts
+            ),
+            "[ Point { x: 1 }, [class Point] ]"
+        );
+        assert_eq!(
+            inspect("Object.assign(Object.create(null), { a: 1 })", &opts),
+            "[Object: null prototype] { a: 1 }"
+        );
+        assert_eq!(
+            inspect(
+                "[new Set(['a']), new Map(), new Float64Array([0.5]), new ArrayBuffer(2)]",
+                &InspectOptions::new().with_break_length(120)
+            ),
+            "[ Set(1) { 'a' }, Map(0) {}, Float64Array(1) [ 0.5 ], ArrayBuffer { [Uint8Contents]: <00 00>, byteLength: 2 } ]"
+        );
+        assert_eq!(
+            inspect("[new Date(0), /a+/g, new Number(3), function () {}]", &opts),
+            "[ 1970-01-01T00:00:00.000Z, /a+/g, [Number: 3], [Function (anonymous)] ]"
+        );
+        assert_eq!(
+            inspect("var a = [1]; a.push(a); a", &opts),
+            "<ref *1> [ 1, [Circular *1] ]"
+        );
+        assert_eq!(
+            inspect(
+                "Array.from({ length: 5 }, (_, i) => i)",
+                &InspectOptions::new().with_max_array_length(2)
+            ),
+            "[ 0, 1, ... 3 more items ]"
+        );
+        assert_eq!(
+            inspect("({ name: 'a'.repeat(40), value: 'b'.repeat(40) })", &opts),
+            format!(
+                "{{\n  name: '{}',\n  value: '{}'\n}}",
+                "a".repeat(40),
+                "b".repeat(40)
+            )
+        );
+        assert_eq!(
+            inspect("[1, 'a', null]", &InspectOptions::new().with_colors(true)),
+            "[ \x1b[33m1\x1b[39m, \x1b[32m'a'\x1b[39m, \x1b[1mnull\x1b[22m ]"
+        );
+    }
+}
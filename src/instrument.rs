@@ -0,0 +1,38 @@
+//! The `tracing` spans around the engine operations.
+//!
+//! The spans are emitted with the `qjs::eval`, `qjs::module`, `qjs::job`, `qjs::gc` and `qjs::native` targets,
+//! and record the `elapsed` time of the operation when exited.
+
+use std::time::Instant;
+
+use tracing::{field, span::EnteredSpan, Span};
+
+/// The entered span of an engine operation.
+pub(crate) struct Instrumented {
+    span: EnteredSpan,
+    started: Option<Instant>,
+}
+
+impl Instrumented {
+    pub fn enter(span: Span) -> Self {
+        let started = if span.is_disabled() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+
+        Instrumented {
+            span: span.entered(),
+            started,
+        }
+    }
+}
+
+impl Drop for Instrumented {
+    fn drop(&mut self) {
+        if let Some(started) = self.started {
+            self.span
+                .record("elapsed", &field::debug(started.elapsed()));
+        }
+    }
+}
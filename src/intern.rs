@@ -0,0 +1,202 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, Atom, ContextRef, Local, NewValue, Value};
+
+/// The statistics of the string interning cache of a context.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// the number of the lookups found in the cache
+    pub hits: usize,
+    /// the number of the lookups created a new string or atom
+    pub misses: usize,
+    /// the number of the cached strings
+    pub strings: usize,
+    /// the number of the cached atoms
+    pub atoms: usize,
+}
+
+/// A string interned in the context when converted to a Javascript value.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Interned, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let headers = ctxt.bind(ctxt.new_object());
+///
+/// for _ in 0..3 {
+///     headers.set_property("content-type", Interned("application/json")).unwrap();
+/// }
+///
+/// assert_eq!(ctxt.intern_stats().strings, 1);
+/// assert_eq!(ctxt.intern_stats().hits, 2);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Interned<'a>(pub &'a str);
+
+impl NewValue for Interned<'_> {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.intern(self.0).into_inner_untracked().raw()
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    ctx: Cell<usize>,
+    strings: RefCell<HashMap<String, Value>>,
+    atoms: RefCell<HashMap<String, ffi::JSAtom>>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+unsafe impl Send for Cache {}
+
+impl Cache {
+    fn clear(&self) {
+        let ctxt = unsafe { ContextRef::from_ptr(self.ctx.get() as *mut _) };
+
+        for (_, value) in self.strings.borrow_mut().drain() {
+            ctxt.free_value(value)
+        }
+        for (_, atom) in self.atoms.borrow_mut().drain() {
+            ctxt.free_atom(atom)
+        }
+
+        self.hits.set(0);
+        self.misses.set(0);
+    }
+
+    fn hit(&self, hit: bool) {
+        if hit {
+            self.hits.set(self.hits.get() + 1);
+        } else {
+            self.misses.set(self.misses.get() + 1);
+        }
+    }
+}
+
+impl Drop for Cache {
+    fn drop(&mut self) {
+        if self.ctx.get() != 0 {
+            self.clear()
+        }
+    }
+}
+
+impl ContextRef {
+    fn intern_cache(&self) -> &Cache {
+        let cache = self.state::<Cache>();
+
+        cache.ctx.set(self.as_ptr() as usize);
+        cache
+    }
+
+    /// Returns a string from the interning cache of the context, creates and caches it if not exists.
+    pub fn intern(&self, s: &str) -> Local<Value> {
+        let cache = self.intern_cache();
+        let mut strings = cache.strings.borrow_mut();
+
+        cache.hit(strings.contains_key(s));
+
+        if let Some(value) = strings.get(s) {
+            return self.clone_value(value);
+        }
+
+        let value = self.new_value(s);
+
+        strings.insert(
+            s.to_owned(),
+            self.clone_value(&value).into_inner_untracked(),
+        );
+
+        self.bind(value)
+    }
+
+    /// Returns an atom from the interning cache of the context, creates and caches it if not exists.
+    pub fn intern_atom(&self, s: &str) -> Atom {
+        let cache = self.intern_cache();
+        let mut atoms = cache.atoms.borrow_mut();
+
+        cache.hit(atoms.contains_key(s));
+
+        let atom = *atoms
+            .entry(s.to_owned())
+            .or_insert_with(|| self.new_atom(s).into_inner());
+
+        self.clone_atom(atom)
+    }
+
+    /// Returns the statistics of the interning cache.
+    pub fn intern_stats(&self) -> InternStats {
+        let cache = self.intern_cache();
+
+        InternStats {
+            hits: cache.hits.get(),
+            misses: cache.misses.get(),
+            strings: cache.strings.borrow().len(),
+            atoms: cache.atoms.borrow().len(),
+        }
+    }
+
+    /// Clear the interning cache and its statistics.
+    pub fn clear_interned(&self) {
+        self.intern_cache().clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let s = ctxt.intern("hello");
+        let t = ctxt.intern("hello");
+
+        assert_eq!(s.as_ptr::<()>(), t.as_ptr::<()>());
+        assert_eq!(s.to_string(), "hello");
+
+        let a = ctxt.intern_atom("key");
+        let b = ctxt.intern_atom("key");
+
+        assert_eq!(*a, *b);
+        assert_eq!(a.to_string(), "key");
+
+        let obj = ctxt.bind(ctxt.new_object());
+
+        obj.set_property(a.clone(), Interned("world")).unwrap();
+        ctxt.global_object().set_property("obj", obj).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("obj.key", Eval::GLOBAL).unwrap(),
+            Some("world".to_owned())
+        );
+        assert_eq!(
+            ctxt.intern_stats(),
+            InternStats {
+                hits: 2,
+                misses: 3,
+                strings: 2,
+                atoms: 1,
+            }
+        );
+
+        ctxt.clear_interned();
+
+        assert_eq!(ctxt.intern_stats(), InternStats::default());
+        assert_eq!(s.to_string(), "hello");
+        assert_eq!(ctxt.intern("hello").to_string(), "hello");
+    }
+}
@@ -0,0 +1,296 @@
+(function (natives) {
+    const kOptions = Symbol('options');
+    const kFormat = Symbol('format');
+    // the internal option to select the default fields of `toLocaleString` and `toLocaleTimeString`
+    const kDefaults = Symbol('defaults');
+
+    const DATE_FIELDS = ['weekday', 'year', 'month', 'day', 'hour', 'minute', 'second'];
+
+    function resolveLocale(locales) {
+        if (locales === undefined) {
+            return natives.resolveLocale();
+        }
+
+        if (typeof locales === 'string') {
+            locales = [locales];
+        }
+
+        for (const locale of Array.from(locales)) {
+            if (typeof locale !== 'string' && (typeof locale !== 'object' || locale === null)) {
+                throw new TypeError('Language ID should be string or object.');
+            }
+
+            const resolved = natives.resolveLocale(String(locale));
+
+            if (resolved !== null) {
+                return resolved;
+            }
+        }
+
+        return natives.resolveLocale();
+    }
+
+    function supportedLocalesOf(locales) {
+        if (locales === undefined) {
+            return [];
+        }
+
+        return Array.from(typeof locales === 'string' ? [locales] : locales, String).filter(
+            (locale) => natives.resolveLocale(locale) !== null
+        );
+    }
+
+    function toOptions(options) {
+        if (options === undefined) {
+            return Object.create(null);
+        }
+
+        if (options === null) {
+            throw new TypeError('Cannot convert undefined or null to object');
+        }
+
+        return Object(options);
+    }
+
+    function getOption(options, name, allowed, fallback) {
+        const value = options[name];
+
+        if (value === undefined) {
+            return fallback;
+        }
+
+        if (typeof fallback === 'boolean') {
+            return Boolean(value);
+        }
+
+        const s = String(value);
+
+        if (allowed !== undefined && !allowed.includes(s)) {
+            throw new RangeError(`Value ${s} out of range for Intl options property ${name}`);
+        }
+
+        return s;
+    }
+
+    function getNumberOption(options, name, min, max, fallback) {
+        const value = options[name];
+
+        if (value === undefined) {
+            return fallback;
+        }
+
+        const n = Number(value);
+
+        if (Number.isNaN(n) || n < min || n > max) {
+            throw new RangeError(`${name} value is out of range.`);
+        }
+
+        return Math.floor(n);
+    }
+
+    function boundFormat(obj, format) {
+        if (!obj[kFormat]) {
+            Object.defineProperty(obj, kFormat, { value: format.bind(obj) });
+        }
+
+        return obj[kFormat];
+    }
+
+    function checkReceiver(obj, ctor, method) {
+        if (!(obj instanceof ctor) || !obj[kOptions]) {
+            throw new TypeError(`Method Intl.${ctor.name}.prototype.${method} called on incompatible receiver`);
+        }
+
+        return obj[kOptions];
+    }
+
+    function NumberFormat(locales, options) {
+        if (new.target === undefined) {
+            return new NumberFormat(locales, options);
+        }
+
+        options = toOptions(options);
+
+        const locale = resolveLocale(locales);
+        const style = getOption(options, 'style', ['decimal', 'percent', 'currency'], 'decimal');
+        let currency = getOption(options, 'currency');
+
+        if (currency !== undefined) {
+            if (!/^[A-Za-z]{3}$/.test(currency)) {
+                throw new RangeError(`Invalid currency code : ${currency}`);
+            }
+
+            currency = currency.toUpperCase();
+        } else if (style === 'currency') {
+            throw new TypeError('Currency code is required with currency style.');
+        }
+
+        const currencyDisplay = getOption(options, 'currencyDisplay', ['symbol', 'code'], 'symbol');
+        const digits = style === 'currency' ? natives.currencyDigits(currency) : 0;
+        const minimumIntegerDigits = getNumberOption(options, 'minimumIntegerDigits', 1, 21, 1);
+        const minimumFractionDigits = getNumberOption(options, 'minimumFractionDigits', 0, 20, digits);
+        const maximumFractionDigits = getNumberOption(
+            options,
+            'maximumFractionDigits',
+            minimumFractionDigits,
+            20,
+            Math.max(minimumFractionDigits, style === 'currency' ? digits : style === 'percent' ? 0 : 3)
+        );
+        const useGrouping = getOption(options, 'useGrouping', undefined, true);
+        const resolved = { locale, numberingSystem: 'latn', style };
+
+        if (style === 'currency') {
+            Object.assign(resolved, { currency, currencyDisplay });
+        }
+
+        Object.assign(resolved, { minimumIntegerDigits, minimumFractionDigits, maximumFractionDigits, useGrouping });
+        Object.defineProperty(this, kOptions, { value: Object.freeze(resolved) });
+    }
+
+    Object.defineProperties(NumberFormat.prototype, {
+        format: {
+            get() {
+                const options = checkReceiver(this, NumberFormat, 'format');
+
+                return boundFormat(this, (value) => natives.formatNumber(options.locale, Number(value), options));
+            },
+            configurable: true,
+        },
+        resolvedOptions: {
+            value() {
+                return Object.assign({}, checkReceiver(this, NumberFormat, 'resolvedOptions'));
+            },
+            writable: true,
+            configurable: true,
+        },
+        [Symbol.toStringTag]: { value: 'Intl.NumberFormat', configurable: true },
+    });
+
+    NumberFormat.supportedLocalesOf = supportedLocalesOf;
+
+    function DateTimeFormat(locales, options) {
+        if (new.target === undefined) {
+            return new DateTimeFormat(locales, options);
+        }
+
+        options = toOptions(options);
+
+        const locale = resolveLocale(locales);
+        const timeZone = getOption(options, 'timeZone');
+
+        if (timeZone !== undefined && timeZone.toUpperCase() !== 'UTC') {
+            throw new RangeError(`Unsupported time zone specified ${timeZone}`);
+        }
+
+        const resolved = { locale, calendar: 'gregory', numberingSystem: 'latn' };
+
+        if (timeZone !== undefined) {
+            resolved.timeZone = 'UTC';
+        }
+
+        const hour12 = getOption(options, 'hour12', undefined, natives.defaultHour12(locale));
+        const fields = {
+            weekday: getOption(options, 'weekday', ['long', 'short']),
+            year: getOption(options, 'year', ['numeric', '2-digit']),
+            month: getOption(options, 'month', ['numeric', '2-digit', 'long', 'short']),
+            day: getOption(options, 'day', ['numeric', '2-digit']),
+            hour: getOption(options, 'hour', ['numeric', '2-digit']),
+            minute: getOption(options, 'minute', ['numeric', '2-digit']),
+            second: getOption(options, 'second', ['numeric', '2-digit']),
+        };
+        const defaults = options[kDefaults] || 'date';
+
+        if (DATE_FIELDS.every((name) => fields[name] === undefined)) {
+            if (defaults !== 'time') {
+                Object.assign(fields, { year: 'numeric', month: 'numeric', day: 'numeric' });
+            }
+
+            if (defaults !== 'date') {
+                Object.assign(fields, { hour: 'numeric', minute: '2-digit', second: '2-digit' });
+            }
+        }
+
+        for (const name of DATE_FIELDS) {
+            if (fields[name] !== undefined) {
+                resolved[name] = fields[name];
+            }
+        }
+
+        if (fields.hour !== undefined) {
+            resolved.hour12 = hour12;
+        }
+
+        Object.defineProperty(this, kOptions, { value: Object.freeze(resolved) });
+    }
+
+    function formatDate(options, date) {
+        const time = date === undefined ? Date.now() : Number(date);
+
+        if (!Number.isFinite(time)) {
+            throw new RangeError('Invalid time value');
+        }
+
+        const d = new Date(time);
+        const parts =
+            options.timeZone === 'UTC'
+                ? [d.getUTCDay(), d.getUTCFullYear(), d.getUTCMonth(), d.getUTCDate(), d.getUTCHours(), d.getUTCMinutes(), d.getUTCSeconds()]
+                : [d.getDay(), d.getFullYear(), d.getMonth(), d.getDate(), d.getHours(), d.getMinutes(), d.getSeconds()];
+
+        return natives.formatDate(options.locale, parts, options);
+    }
+
+    Object.defineProperties(DateTimeFormat.prototype, {
+        format: {
+            get() {
+                const options = checkReceiver(this, DateTimeFormat, 'format');
+
+                return boundFormat(this, (date) => formatDate(options, date));
+            },
+            configurable: true,
+        },
+        resolvedOptions: {
+            value() {
+                return Object.assign({}, checkReceiver(this, DateTimeFormat, 'resolvedOptions'));
+            },
+            writable: true,
+            configurable: true,
+        },
+        [Symbol.toStringTag]: { value: 'Intl.DateTimeFormat', configurable: true },
+    });
+
+    DateTimeFormat.supportedLocalesOf = supportedLocalesOf;
+
+    function localeMethod(defaults) {
+        return {
+            value(locales, options) {
+                if (Number.isNaN(this.getTime())) {
+                    return 'Invalid Date';
+                }
+
+                return new DateTimeFormat(locales, Object.assign({ [kDefaults]: defaults }, options)).format(this);
+            },
+            writable: true,
+            configurable: true,
+        };
+    }
+
+    Object.defineProperties(Date.prototype, {
+        toLocaleString: localeMethod('all'),
+        toLocaleDateString: localeMethod('date'),
+        toLocaleTimeString: localeMethod('time'),
+    });
+
+    Object.defineProperty(Number.prototype, 'toLocaleString', {
+        value(locales, options) {
+            return new NumberFormat(locales, options).format(Number(this));
+        },
+        writable: true,
+        configurable: true,
+    });
+
+    const Intl = { NumberFormat, DateTimeFormat };
+
+    Object.defineProperty(Intl, Symbol.toStringTag, { value: 'Intl', configurable: true });
+
+    return Intl;
+})
@@ -0,0 +1,935 @@
+//! A minimal `Intl.NumberFormat` and `Intl.DateTimeFormat` backed by a lightweight formatter,
+//! which covers the common number and date formatting of a few locales without the ICU data.
+
+use failure::Error;
+
+use crate::{ContextRef, Eval, Local, Prop, Value, NULL};
+
+const INTL: &str = include_str!("intl.js");
+
+/// The default locale if the scripts didn't request a supported one.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// The formatting data of a locale.
+struct Locale {
+    tag: &'static str,
+    decimal: &'static str,
+    group: &'static str,
+    /// the minimum digits of the integer part before grouping, e.g. `1234` is not grouped in Spanish
+    min_grouping: usize,
+    /// the currency symbol is placed after the number
+    currency_suffix: bool,
+    /// the symbol of the local currency if different from the common one
+    local_currency: Option<(&'static str, &'static str)>,
+    percent: &'static str,
+    months: [&'static str; 12],
+    short_months: [&'static str; 12],
+    /// the weekdays start from Sunday
+    weekdays: [&'static str; 7],
+    short_weekdays: [&'static str; 7],
+    /// the date patterns, the fields are written in `{}`, the literals in `[]` are attached to the preceding field,
+    /// and the other literals separate the present fields.
+    numeric_date: &'static str,
+    text_date: &'static str,
+    date_time_separator: &'static str,
+    hour12: bool,
+    /// the hour is padded to 2 digits in the 24-hour clock
+    pad_hour: bool,
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        tag: "en-US",
+        decimal: ".",
+        group: ",",
+        min_grouping: 1,
+        currency_suffix: false,
+        local_currency: None,
+        percent: "%",
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+        weekdays: [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ],
+        short_weekdays: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+        numeric_date: "{E}, {M}/{d}/{y}",
+        text_date: "{E}, {MMMM} {d}, {y}",
+        date_time_separator: ", ",
+        hour12: true,
+        pad_hour: false,
+    },
+    Locale {
+        tag: "en-GB",
+        decimal: ".",
+        group: ",",
+        min_grouping: 1,
+        currency_suffix: false,
+        local_currency: None,
+        percent: "%",
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sept", "Oct", "Nov", "Dec",
+        ],
+        weekdays: [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ],
+        short_weekdays: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+        numeric_date: "{E}, {dd}/{MM}/{y}",
+        text_date: "{E} {d} {MMMM} {y}",
+        date_time_separator: ", ",
+        hour12: false,
+        pad_hour: true,
+    },
+    Locale {
+        tag: "de-DE",
+        decimal: ",",
+        group: ".",
+        min_grouping: 1,
+        currency_suffix: true,
+        local_currency: None,
+        percent: "\u{a0}%",
+        months: [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        short_months: [
+            "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+            "Dez.",
+        ],
+        weekdays: [
+            "Sonntag",
+            "Montag",
+            "Dienstag",
+            "Mittwoch",
+            "Donnerstag",
+            "Freitag",
+            "Samstag",
+        ],
+        short_weekdays: ["So.", "Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa."],
+        numeric_date: "{E}, {d}[.]{M}[.]{y}",
+        text_date: "{E}, {d}[.] {MMMM} {y}",
+        date_time_separator: ", ",
+        hour12: false,
+        pad_hour: true,
+    },
+    Locale {
+        tag: "fr-FR",
+        decimal: ",",
+        group: "\u{202f}",
+        min_grouping: 1,
+        currency_suffix: true,
+        local_currency: None,
+        percent: "\u{a0}%",
+        months: [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        short_months: [
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+        weekdays: [
+            "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+        ],
+        short_weekdays: ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+        numeric_date: "{E} {dd}/{MM}/{y}",
+        text_date: "{E} {d} {MMMM} {y}",
+        date_time_separator: " ",
+        hour12: false,
+        pad_hour: true,
+    },
+    Locale {
+        tag: "es-ES",
+        decimal: ",",
+        group: ".",
+        min_grouping: 2,
+        currency_suffix: true,
+        local_currency: None,
+        percent: "\u{a0}%",
+        months: [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        short_months: [
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+        ],
+        weekdays: [
+            "domingo",
+            "lunes",
+            "martes",
+            "miércoles",
+            "jueves",
+            "viernes",
+            "sábado",
+        ],
+        short_weekdays: ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"],
+        numeric_date: "{E}, {d}/{M}/{y}",
+        text_date: "{E}, {d} de {MMMM} de {y}",
+        date_time_separator: ", ",
+        hour12: false,
+        pad_hour: false,
+    },
+    Locale {
+        tag: "ja-JP",
+        decimal: ".",
+        group: ",",
+        min_grouping: 1,
+        currency_suffix: false,
+        local_currency: Some(("JPY", "￥")),
+        percent: "%",
+        months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        short_months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        weekdays: [
+            "日曜日",
+            "月曜日",
+            "火曜日",
+            "水曜日",
+            "木曜日",
+            "金曜日",
+            "土曜日",
+        ],
+        short_weekdays: ["日", "月", "火", "水", "木", "金", "土"],
+        numeric_date: "{y}/{M}/{d}({E}[)]",
+        text_date: "{y}[年]{MMMM}{d}[日]{E}",
+        date_time_separator: " ",
+        hour12: false,
+        pad_hour: false,
+    },
+    Locale {
+        tag: "zh-CN",
+        decimal: ".",
+        group: ",",
+        min_grouping: 1,
+        currency_suffix: false,
+        local_currency: Some(("CNY", "¥")),
+        percent: "%",
+        months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        short_months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        weekdays: [
+            "星期日",
+            "星期一",
+            "星期二",
+            "星期三",
+            "星期四",
+            "星期五",
+            "星期六",
+        ],
+        short_weekdays: ["周日", "周一", "周二", "周三", "周四", "周五", "周六"],
+        numeric_date: "{y}/{M}/{d}{E}",
+        text_date: "{y}[年]{MMMM}{d}[日]{E}",
+        date_time_separator: " ",
+        hour12: false,
+        pad_hour: true,
+    },
+];
+
+/// Returns the supported locale of the language tag, fallback to the locale of the same language.
+fn find_locale(tag: &str) -> Option<&'static Locale> {
+    let tag = tag.replace('_', "-");
+    let language = tag.split('-').next().unwrap_or_default();
+
+    LOCALES
+        .iter()
+        .find(|locale| locale.tag.eq_ignore_ascii_case(&tag))
+        .or_else(|| {
+            LOCALES.iter().find(|locale| {
+                locale
+                    .tag
+                    .split('-')
+                    .next()
+                    .map_or(false, |lang| lang.eq_ignore_ascii_case(language))
+            })
+        })
+}
+
+fn locale(tag: &str) -> &'static Locale {
+    find_locale(tag).unwrap_or(&LOCALES[0])
+}
+
+fn currency_digits(code: &str) -> usize {
+    match code {
+        "JPY" | "KRW" => 0,
+        _ => 2,
+    }
+}
+
+fn currency_symbol(locale: &Locale, code: &str) -> String {
+    match locale.local_currency {
+        Some((local, symbol)) if local == code => return symbol.to_owned(),
+        _ => {}
+    }
+
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        "CNY" => "CN¥",
+        "INR" => "₹",
+        "KRW" => "₩",
+        _ => code,
+    }
+    .to_owned()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NumberStyle {
+    Decimal,
+    Percent,
+    Currency,
+}
+
+/// The resolved options of `Intl.NumberFormat`.
+#[derive(Clone, Debug)]
+struct NumberOptions {
+    style: NumberStyle,
+    currency: String,
+    currency_code: bool,
+    min_integer_digits: usize,
+    min_fraction_digits: usize,
+    max_fraction_digits: usize,
+    grouping: bool,
+}
+
+fn format_number(locale: &Locale, value: f64, opts: &NumberOptions) -> String {
+    let sign = if value.is_sign_negative() && !value.is_nan() {
+        "-"
+    } else {
+        ""
+    };
+    let value = if opts.style == NumberStyle::Percent {
+        value.abs() * 100.0
+    } else {
+        value.abs()
+    };
+    let number = if value.is_nan() {
+        "NaN".to_owned()
+    } else if value.is_infinite() {
+        "∞".to_owned()
+    } else {
+        let (int, mut frac) = round_half_expand(value, opts.max_fraction_digits);
+
+        while frac.len() > opts.min_fraction_digits && frac.ends_with('0') {
+            frac.pop();
+        }
+
+        while frac.len() < opts.min_fraction_digits {
+            frac.push('0');
+        }
+
+        let int = format!("{:0>width$}", int, width = opts.min_integer_digits);
+        let mut s = String::new();
+
+        if opts.grouping && int.len() >= 3 + locale.min_grouping {
+            for (idx, c) in int.chars().enumerate() {
+                if idx > 0 && (int.len() - idx) % 3 == 0 {
+                    s.push_str(locale.group);
+                }
+
+                s.push(c);
+            }
+        } else {
+            s.push_str(&int);
+        }
+
+        if !frac.is_empty() {
+            s.push_str(locale.decimal);
+            s.push_str(&frac);
+        }
+
+        s
+    };
+
+    match opts.style {
+        NumberStyle::Decimal => format!("{}{}", sign, number),
+        NumberStyle::Percent => format!("{}{}{}", sign, number, locale.percent),
+        NumberStyle::Currency => {
+            let symbol = if opts.currency_code {
+                opts.currency.clone()
+            } else {
+                currency_symbol(locale, &opts.currency)
+            };
+
+            if locale.currency_suffix {
+                format!("{}{}\u{a0}{}", sign, number, symbol)
+            } else if symbol.chars().last().map_or(false, char::is_alphabetic) {
+                format!("{}{}\u{a0}{}", sign, symbol, number)
+            } else {
+                format!("{}{}{}", sign, symbol, number)
+            }
+        }
+    }
+}
+
+/// Round the shortest representation of a finite positive number, the ties are rounded away from zero.
+fn round_half_expand(value: f64, max_fraction_digits: usize) -> (String, String) {
+    let digits = value.to_string();
+    let (int, frac) = match digits.find('.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (&digits[..], ""),
+    };
+
+    if frac.len() <= max_fraction_digits {
+        return (int.to_owned(), frac.to_owned());
+    }
+
+    let mut digits = format!("{}{}", int, &frac[..max_fraction_digits]).into_bytes();
+
+    if frac.as_bytes()[max_fraction_digits] >= b'5' {
+        let mut carry = true;
+
+        for d in digits.iter_mut().rev() {
+            if *d == b'9' {
+                *d = b'0';
+            } else {
+                *d += 1;
+                carry = false;
+                break;
+            }
+        }
+
+        if carry {
+            digits.insert(0, b'1');
+        }
+    }
+
+    let frac = digits.split_off(digits.len() - max_fraction_digits);
+
+    (
+        String::from_utf8_lossy(&digits).into_owned(),
+        String::from_utf8_lossy(&frac).into_owned(),
+    )
+}
+
+/// The fields of a date in its time zone.
+#[derive(Clone, Copy, Debug, Default)]
+struct DateFields {
+    weekday: usize,
+    year: i32,
+    month: usize,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+/// The resolved options of `Intl.DateTimeFormat`, the styles are `None` if the field is omitted.
+#[derive(Clone, Debug, Default)]
+struct DateOptions {
+    weekday: Option<String>,
+    year: Option<String>,
+    month: Option<String>,
+    day: Option<String>,
+    hour: Option<String>,
+    minute: Option<String>,
+    second: Option<String>,
+    hour12: bool,
+}
+
+fn two_digits(n: u32) -> String {
+    format!("{:02}", n % 100)
+}
+
+fn numeric(n: u32, style: &str) -> String {
+    if style == "2-digit" {
+        two_digits(n)
+    } else {
+        n.to_string()
+    }
+}
+
+fn format_date_fields(locale: &Locale, fields: &DateFields, opts: &DateOptions) -> String {
+    let text_month = opts
+        .month
+        .as_ref()
+        .map_or(false, |style| style == "long" || style == "short");
+    let render = |field: &str| -> Option<String> {
+        match field {
+            "E" => opts.weekday.as_ref().map(|style| {
+                if style == "long" {
+                    locale.weekdays[fields.weekday % 7].to_owned()
+                } else {
+                    locale.short_weekdays[fields.weekday % 7].to_owned()
+                }
+            }),
+            "y" => opts.year.as_ref().map(|style| {
+                if style == "2-digit" {
+                    two_digits(fields.year.rem_euclid(100) as u32)
+                } else {
+                    fields.year.to_string()
+                }
+            }),
+            "M" | "MM" => opts.month.as_ref().map(|style| {
+                numeric(
+                    fields.month as u32 + 1,
+                    if field == "MM" { "2-digit" } else { style },
+                )
+            }),
+            "MMMM" => opts.month.as_ref().map(|style| {
+                if style == "long" {
+                    locale.months[fields.month % 12].to_owned()
+                } else {
+                    locale.short_months[fields.month % 12].to_owned()
+                }
+            }),
+            "d" | "dd" => opts
+                .day
+                .as_ref()
+                .map(|style| numeric(fields.day, if field == "dd" { "2-digit" } else { style })),
+            _ => None,
+        }
+    };
+
+    let pattern = if text_month {
+        locale.text_date
+    } else {
+        locale.numeric_date
+    };
+    let mut s = String::new();
+    let mut rest = pattern;
+    let mut separator: Option<&str> = None;
+    let mut last_present = false;
+
+    while !rest.is_empty() {
+        if let Some(field) = rest.strip_prefix('{') {
+            let end = field.find('}').unwrap_or(field.len());
+
+            match render(&field[..end]) {
+                Some(value) => {
+                    if !s.is_empty() {
+                        s.push_str(separator.take().unwrap_or_default());
+                    }
+
+                    s.push_str(&value);
+                    last_present = true;
+                }
+                None => last_present = false,
+            }
+
+            rest = field.get(end + 1..).unwrap_or_default();
+        } else if let Some(attached) = rest.strip_prefix('[') {
+            let end = attached.find(']').unwrap_or(attached.len());
+
+            if last_present {
+                s.push_str(&attached[..end]);
+            }
+
+            rest = attached.get(end + 1..).unwrap_or_default();
+        } else {
+            let end = rest.find(&['{', '['][..]).unwrap_or(rest.len());
+
+            if last_present {
+                separator = Some(&rest[..end]);
+                last_present = false;
+            }
+
+            rest = &rest[end..];
+        }
+    }
+
+    s
+}
+
+fn format_time_fields(locale: &Locale, fields: &DateFields, opts: &DateOptions) -> String {
+    let mut parts = vec![];
+
+    if let Some(ref style) = opts.hour {
+        let hour = if opts.hour12 {
+            match fields.hour % 12 {
+                0 => 12,
+                hour => hour,
+            }
+        } else {
+            fields.hour
+        };
+
+        parts.push(if !opts.hour12 && locale.pad_hour {
+            two_digits(hour)
+        } else {
+            numeric(hour, style)
+        });
+    }
+
+    if let Some(ref style) = opts.minute {
+        parts.push(if parts.is_empty() {
+            numeric(fields.minute, style)
+        } else {
+            two_digits(fields.minute)
+        });
+    }
+
+    if let Some(ref style) = opts.second {
+        parts.push(if parts.is_empty() {
+            numeric(fields.second, style)
+        } else {
+            two_digits(fields.second)
+        });
+    }
+
+    let mut s = parts.join(":");
+
+    if opts.hour.is_some() && opts.hour12 {
+        s.push_str(if fields.hour < 12 { " AM" } else { " PM" });
+    }
+
+    s
+}
+
+fn format_date(locale: &Locale, fields: &DateFields, opts: &DateOptions) -> String {
+    let date = format_date_fields(locale, fields, opts);
+    let time = format_time_fields(locale, fields, opts);
+
+    match (date.is_empty(), time.is_empty()) {
+        (false, false) => format!("{}{}{}", date, locale.date_time_separator, time),
+        (true, _) => time,
+        (_, true) => date,
+    }
+}
+
+fn get_string(obj: &Local<Value>, name: &str) -> Option<String> {
+    obj.get_property(name)
+        .filter(|value| !value.is_undefined())
+        .map(|value| value.to_string())
+}
+
+fn get_usize(obj: &Local<Value>, name: &str) -> usize {
+    obj.get_property(name)
+        .and_then(|value| value.to_int32())
+        .unwrap_or_default()
+        .max(0) as usize
+}
+
+/// Returns the argument, or `undefined` if it is missing.
+fn arg<'a>(ctxt: &'a ContextRef, args: &[Value], idx: usize) -> Local<'a, Value> {
+    args.get(idx)
+        .map_or_else(|| ctxt.undefined(), |arg| ctxt.clone_value(arg))
+}
+
+fn resolve_locale(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let tag = arg(ctxt, args, 0);
+
+    if tag.is_undefined() {
+        return ctxt.new_value(DEFAULT_LOCALE);
+    }
+
+    match find_locale(&tag.to_string()) {
+        Some(locale) => ctxt.new_value(locale.tag),
+        None => NULL,
+    }
+}
+
+fn default_hour12(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> bool {
+    locale(&arg(ctxt, args, 0).to_string()).hour12
+}
+
+fn currency_digits_of(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> i32 {
+    currency_digits(&arg(ctxt, args, 0).to_string()) as i32
+}
+
+fn format_number_with(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> String {
+    let locale = locale(&arg(ctxt, args, 0).to_string());
+    let value = arg(ctxt, args, 1).to_float64().unwrap_or(f64::NAN);
+    let opts = arg(ctxt, args, 2);
+    let opts = NumberOptions {
+        style: match get_string(&opts, "style").as_deref() {
+            Some("percent") => NumberStyle::Percent,
+            Some("currency") => NumberStyle::Currency,
+            _ => NumberStyle::Decimal,
+        },
+        currency: get_string(&opts, "currency").unwrap_or_default(),
+        currency_code: get_string(&opts, "currencyDisplay").as_deref() == Some("code"),
+        min_integer_digits: get_usize(&opts, "minimumIntegerDigits"),
+        min_fraction_digits: get_usize(&opts, "minimumFractionDigits"),
+        max_fraction_digits: get_usize(&opts, "maximumFractionDigits"),
+        grouping: opts
+            .get_property("useGrouping")
+            .and_then(|value| value.to_bool())
+            .unwrap_or(true),
+    };
+
+    format_number(locale, value, &opts)
+}
+
+fn format_date_with(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> String {
+    let locale = locale(&arg(ctxt, args, 0).to_string());
+    let parts = arg(ctxt, args, 1);
+    let part = |idx: u32| {
+        parts
+            .get_property(idx)
+            .and_then(|value| value.to_int32())
+            .unwrap_or_default()
+    };
+    let fields = DateFields {
+        weekday: part(0).max(0) as usize,
+        year: part(1),
+        month: part(2).max(0) as usize,
+        day: part(3).max(0) as u32,
+        hour: part(4).max(0) as u32,
+        minute: part(5).max(0) as u32,
+        second: part(6).max(0) as u32,
+    };
+    let opts = arg(ctxt, args, 2);
+    let opts = DateOptions {
+        weekday: get_string(&opts, "weekday"),
+        year: get_string(&opts, "year"),
+        month: get_string(&opts, "month"),
+        day: get_string(&opts, "day"),
+        hour: get_string(&opts, "hour"),
+        minute: get_string(&opts, "minute"),
+        second: get_string(&opts, "second"),
+        hour12: opts
+            .get_property("hour12")
+            .and_then(|value| value.to_bool())
+            .unwrap_or(locale.hour12),
+    };
+
+    format_date(locale, &fields, &opts)
+}
+
+impl ContextRef {
+    /// Install a minimal `Intl` with `NumberFormat` and `DateTimeFormat`,
+    /// and the `toLocaleString` methods of `Number` and `Date` based on them.
+    ///
+    /// The `en-US`, `en-GB`, `de-DE`, `fr-FR`, `es-ES`, `ja-JP` and `zh-CN` locales are supported,
+    /// the other locales fallback to the locale of the same language, or `en-US`.
+    /// The dates are formatted in the local time zone, or `UTC` with the `timeZone` option.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.init_intl().unwrap();
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>(
+    ///         "new Intl.NumberFormat('de-DE', { style: 'currency', currency: 'EUR' }).format(1234.5)",
+    ///         Eval::GLOBAL
+    ///     )
+    ///     .unwrap(),
+    ///     Some("1.234,50\u{a0}€".to_owned())
+    /// );
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>(
+    ///         "new Date(Date.UTC(2020, 0, 2)).toLocaleDateString('en-US', { timeZone: 'UTC', month: 'long', day: 'numeric', year: 'numeric' })",
+    ///         Eval::GLOBAL
+    ///     )
+    ///     .unwrap(),
+    ///     Some("January 2, 2020".to_owned())
+    /// );
+    /// ```
+    pub fn init_intl(&self) -> Result<(), Error> {
+        let glue = self.eval_script(INTL, "<intl>", Eval::GLOBAL)?;
+        let natives = self.bind(self.new_object());
+
+        natives.set_property(
+            "resolveLocale",
+            self.new_c_function(resolve_locale, Some("resolveLocale"), 1)?,
+        )?;
+        natives.set_property(
+            "defaultHour12",
+            self.new_c_function(default_hour12, Some("defaultHour12"), 1)?,
+        )?;
+        natives.set_property(
+            "currencyDigits",
+            self.new_c_function(currency_digits_of, Some("currencyDigits"), 1)?,
+        )?;
+        natives.set_property(
+            "formatNumber",
+            self.new_c_function(format_number_with, Some("formatNumber"), 3)?,
+        )?;
+        natives.set_property(
+            "formatDate",
+            self.new_c_function(format_date_with, Some("formatDate"), 3)?,
+        )?;
+
+        let intl = self.call(&glue, None, natives)?;
+
+        self.global_object().define_property_value(
+            "Intl",
+            intl,
+            Prop::WRITABLE | Prop::CONFIGURABLE,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn intl() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_intl().unwrap();
+
+        let eval = |script: &str| {
+            ctxt.eval::<_, String>(script, Eval::GLOBAL)
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(
+            eval(
+                r#"
+[
+    new Intl.NumberFormat().format(1234567.8915),
+    (1234.5).toLocaleString('en-US', { style: 'currency', currency: 'USD' }),
+    (-1234.5).toLocaleString('en-US', { style: 'currency', currency: 'USD', currencyDisplay: 'code' }),
+    (1234).toLocaleString('es'),
+    (12345).toLocaleString('es'),
+    (0.256).toLocaleString('fr-FR', { style: 'percent', maximumFractionDigits: 1 }),
+    (1234567.5).toLocaleString('fr', { minimumFractionDigits: 2 }),
+    (7).toLocaleString(undefined, { minimumIntegerDigits: 3 }),
+    (1234.5).toLocaleString('ja-JP', { style: 'currency', currency: 'JPY' }),
+    (1234.5).toLocaleString('xx', { useGrouping: false }),
+    NaN.toLocaleString(),
+    (-Infinity).toLocaleString(),
+].join('|')
+"#
+            ),
+            "1,234,567.892|$1,234.50|-USD\u{a0}1,234.50|1234|12.345|25,6\u{a0}%|\
+             1\u{202f}234\u{202f}567,50|007|￥1,235|1234.5|NaN|-∞"
+        );
+
+        assert_eq!(
+            eval(
+                r#"
+var date = new Date(Date.UTC(2020, 0, 2, 15, 4, 5));
+var utc = { timeZone: 'UTC' };
+var long = { timeZone: 'UTC', weekday: 'long', year: 'numeric', month: 'long', day: 'numeric' };
+
+[
+    date.toLocaleDateString('en-US', utc),
+    date.toLocaleString('en-US', utc),
+    date.toLocaleTimeString('en-GB', utc),
+    date.toLocaleString('de-DE', utc),
+    date.toLocaleDateString('fr-FR', utc),
+    date.toLocaleDateString('en-US', long),
+    date.toLocaleDateString('de', long),
+    date.toLocaleDateString('es', long),
+    date.toLocaleDateString('ja', long),
+    date.toLocaleDateString('zh-CN', { timeZone: 'UTC', year: 'numeric', month: 'long' }),
+    date.toLocaleDateString('en', { timeZone: 'UTC', month: 'short', year: '2-digit' }),
+    new Intl.DateTimeFormat('en-US', { timeZone: 'UTC', hour: '2-digit', minute: '2-digit', hour12: false }).format(date),
+    new Date(NaN).toLocaleString(),
+].join('|')
+"#
+            ),
+            "1/2/2020|1/2/2020, 3:04:05 PM|15:04:05|2.1.2020, 15:04:05|02/01/2020|\
+             Thursday, January 2, 2020|Donnerstag, 2. Januar 2020|jueves, 2 de enero de 2020|\
+             2020年1月2日木曜日|2020年1月|Jan 20|15:04|Invalid Date"
+        );
+
+        assert_eq!(
+            eval(
+                r#"
+var nf = new Intl.NumberFormat('de', { style: 'percent' });
+var errors = [
+    () => new Intl.NumberFormat('en', { style: 'currency' }),
+    () => new Intl.NumberFormat('en', { style: 'unit' }),
+    () => new Intl.DateTimeFormat('en', { timeZone: 'Asia/Shanghai' }),
+].map(f => { try { f() } catch (err) { return err.name } });
+
+[
+    JSON.stringify(nf.resolvedOptions()),
+    [0.5, 1].map(nf.format).join(),
+    Intl.NumberFormat.supportedLocalesOf(['de-AT', 'xx', 'zh']).join(),
+    Object.prototype.toString.call(Intl),
+    errors.join(),
+].join('|')
+"#
+            ),
+            r#"{"locale":"de-DE","numberingSystem":"latn","style":"percent","minimumIntegerDigits":1,"minimumFractionDigits":0,"maximumFractionDigits":0,"useGrouping":true}|50 %,100 %|de-AT,zh|[object Intl]|TypeError,RangeError,RangeError"#
+                .replace(" %", "\u{a0}%")
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::iter::FromIterator;
+
+use failure::Error;
+
+use crate::{ContextRef, ErrorKind, ExtractValue, Local, Value};
+
+/// An iterator over a Javascript iterable, e.g. an array, a generator, a `Map` or a `Set`.
+///
+/// The Javascript iterator is closed with its `return` method when the iterator is dropped before it is done.
+pub struct JsIterator<'a> {
+    ctxt: &'a ContextRef,
+    iter: Local<'a, Value>,
+    next: Local<'a, Value>,
+    done: bool,
+}
+
+impl<'a> Iterator for JsIterator<'a> {
+    type Item = Result<Local<'a, Value>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self
+            .ctxt
+            .call(&self.next, Some(&self.iter), ())
+            .and_then(|res| {
+                if res.is_object() {
+                    Ok(res)
+                } else {
+                    Err(
+                        ErrorKind::TypeError("iterator result is not an object".into(), None)
+                            .into(),
+                    )
+                }
+            })
+            .map(|res| {
+                let done = res
+                    .get_property("done")
+                    .and_then(|done| done.to_bool())
+                    .unwrap_or_default();
+
+                if done {
+                    None
+                } else {
+                    Some(
+                        self.ctxt
+                            .get_property(&res, "value")
+                            .unwrap_or_else(|| self.ctxt.undefined()),
+                    )
+                }
+            });
+
+        match res {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.done = true;
+
+                None
+            }
+            Err(err) => {
+                self.done = true;
+
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl Drop for JsIterator<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            if let Some(f) = self.iter.get_property("return").filter(|f| f.is_function()) {
+                if let Err(err) = self.ctxt.call(&f, Some(&self.iter), ()) {
+                    debug!("close iterator failed, {}", err);
+                }
+            }
+        }
+    }
+}
+
+impl JsIterator<'_> {
+    /// Extract the remaining values as Rust types, and collect them into a collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeSet;
+    ///
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let gen = ctxt.eval_script("(function* () { yield 3; yield 1; yield 2; })()", "<eval>", Eval::GLOBAL).unwrap();
+    /// assert_eq!(gen.try_iter().unwrap().collect_into::<Vec<i32>>().unwrap(), vec![3, 1, 2]);
+    ///
+    /// let set = ctxt.eval_script("new Set(['b', 'a', 'b'])", "<eval>", Eval::GLOBAL).unwrap();
+    /// assert_eq!(
+    ///     set.try_iter().unwrap().collect_into::<BTreeSet<String>>().unwrap(),
+    ///     vec!["a".to_owned(), "b".to_owned()].into_iter().collect()
+    /// );
+    /// ```
+    pub fn collect_into<C>(self) -> Result<C, Error>
+    where
+        C: FromIterator<<C as IntoIterator>::Item> + IntoIterator,
+        C::Item: ExtractValue,
+    {
+        self.map(|value| value.and_then(|value| value.extract()))
+            .collect()
+    }
+}
+
+impl<'a> Local<'a, Value> {
+    /// Get the iterator of an iterable with the `Symbol.iterator` method.
+    pub fn try_iter(&self) -> Result<JsIterator<'a>, Error> {
+        self.ctxt.try_iter(self)
+    }
+}
+
+impl ContextRef {
+    /// Get the iterator of an iterable with the `Symbol.iterator` method.
+    pub fn try_iter(&self, value: &Value) -> Result<JsIterator, Error> {
+        let symbol = self
+            .get_property(&self.global_object(), "Symbol")
+            .and_then(|symbol| self.get_property(&symbol, "iterator"))
+            .ok_or_else(|| failure::err_msg("missing `Symbol.iterator`"))?;
+        let method = self
+            .get_property(value, self.value_to_atom(&symbol))
+            .filter(|f| f.is_function())
+            .ok_or_else(|| ErrorKind::TypeError("value is not iterable".into(), None))?;
+        let iter = self.call(&method, Some(value), ())?;
+
+        if !iter.is_object() {
+            return Err(ErrorKind::TypeError("iterator is not an object".into(), None).into());
+        }
+
+        let next = self
+            .get_property(&iter, "next")
+            .filter(|f| f.is_function())
+            .ok_or_else(|| ErrorKind::TypeError("iterator has no `next` method".into(), None))?;
+
+        Ok(JsIterator {
+            ctxt: self,
+            iter,
+            next,
+            done: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Context, Eval, Runtime};
+
+    #[test]
+    fn try_iter() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let eval = |s: &str| ctxt.eval_script(s, "<eval>", Eval::GLOBAL).unwrap();
+
+        assert_eq!(
+            eval("[1, 2, 3]")
+                .try_iter()
+                .unwrap()
+                .collect_into::<Vec<i32>>()
+                .unwrap(),
+            vec![1, 2, 3]
+        );
+
+        let entries = eval("new Map([['a', 1], ['b', 2]])")
+            .try_iter()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let key = entry.get_property(0).unwrap().to_string();
+                let value = entry.get_property(1).unwrap().as_int().unwrap();
+
+                (key, value)
+            })
+            .collect::<HashMap<_, _>>();
+
+        assert_eq!(entries["a"], 1);
+        assert_eq!(entries["b"], 2);
+
+        // the custom iterable is closed when the Rust iterator is dropped
+        eval(
+            r#"
+var closed = false;
+var iterable = {
+    [Symbol.iterator]() {
+        let i = 0;
+        return {
+            next() { return { value: i++, done: i > 10 }; },
+            return() { closed = true; return {}; },
+        };
+    },
+};"#,
+        );
+
+        assert_eq!(
+            eval("iterable")
+                .try_iter()
+                .unwrap()
+                .take(3)
+                .map(|v| v.unwrap().as_int().unwrap())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(eval("closed").as_bool().unwrap());
+
+        let mut iter = eval("(function* () { yield 1; throw new Error('boom'); })()")
+            .try_iter()
+            .unwrap();
+
+        assert_eq!(iter.next().unwrap().unwrap().as_int(), Some(1));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        assert!(eval("({})").try_iter().is_err());
+        assert!(eval("[null]")
+            .try_iter()
+            .unwrap()
+            .collect_into::<Vec<()>>()
+            .is_err());
+    }
+}
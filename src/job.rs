@@ -1,9 +1,10 @@
 use std::ptr;
+use std::time::Instant;
 
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, value::ToBool, Args, ContextRef, RuntimeRef};
+use crate::{ffi, value::ToBool, Args, ContextRef, ExceptionOrigin, RuntimeRef};
 
 pub use ffi::JSJobFunc as JobFunc;
 
@@ -12,8 +13,16 @@ impl RuntimeRef {
         unsafe { ffi::JS_IsJobPending(self.as_ptr()).to_bool() }
     }
 
+    /// Execute a pending job, returns the context of the job or `None` if no job is pending.
+    ///
+    /// The exception of the job is returned as an error, unless it was handled by `ContextRef::set_exception_handler`,
+    /// so is the unhandled promise rejection with `RejectionPolicy::Abort`.
     pub fn execute_pending_job(&self) -> Result<Option<&ContextRef>, Error> {
+        instrument!("qjs::job", DEBUG, "execute_pending_job");
+
         let mut ctxt = ptr::null_mut();
+        let _deadline = self.start_deadline();
+        let started = Instant::now();
 
         let ret = unsafe { ffi::JS_ExecutePendingJob(self.as_ptr(), &mut ctxt) };
 
@@ -22,24 +31,38 @@ impl RuntimeRef {
         } else {
             let ctxt = unsafe { ContextRef::from_ptr(ctxt) };
 
-            ctxt.check_bool(ret).map(|_| Some(ctxt))
+            ctxt.account_job(started.elapsed());
+
+            match ctxt.check_bool(ret) {
+                Err(err) if ctxt.handle_exception(ExceptionOrigin::Job, &err) => Ok(Some(ctxt)),
+                res => res
+                    .and_then(|_| ctxt.check_rejections())
+                    .map(|_| Some(ctxt)),
+            }
         }
     }
 }
 
 impl ContextRef {
     pub fn enqueue_job<T: Args>(&self, job_func: JobFunc, args: T) -> Result<(), Error> {
+        args.check_values(self)?;
+
         let args = args.into_values(self);
         let args = args.as_ref();
 
-        self.check_error(unsafe {
+        let ret = unsafe {
             ffi::JS_EnqueueJob(
                 self.as_ptr(),
                 job_func,
                 args.len() as i32,
                 args.as_ptr() as *mut _,
             )
-        })
-        .map(|_| ())
+        };
+
+        for arg in args {
+            self.free_value(*arg);
+        }
+
+        self.check_error(ret).map(|_| ())
     }
 }
@@ -0,0 +1,203 @@
+use std::any;
+use std::cell::OnceCell;
+use std::fmt;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ErrorKind, ExtractValue, GetProperty, Local, NewValue, Value};
+
+/// A value which is converted to the Rust type on the first access, instead of being extracted eagerly.
+///
+/// The nested objects and arrays are not touched until they are accessed,
+/// so a handler could read a few fields of a large object without converting the whole object.
+///
+/// The `Lazy` holds a reference to the Javascript value, and must not outlive its context.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, Lazy, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// let v = ctxt
+///     .eval_script("({ id: 42, items: new Array(10000).fill('item') })", "<eval>", Eval::GLOBAL)
+///     .unwrap();
+/// let lazy = v.extract::<Lazy<()>>().unwrap();
+///
+/// assert_eq!(lazy.field::<_, i32>("id").unwrap(), 42);
+///
+/// let id = lazy.lazy_field::<_, i32>("id").unwrap();
+///
+/// assert!(!id.is_extracted());
+/// assert_eq!(*id.get().unwrap(), 42);
+/// assert!(id.is_extracted());
+/// ```
+pub struct Lazy<T> {
+    ctx: usize,
+    value: Value,
+    extracted: OnceCell<Option<T>>,
+}
+
+impl<T> Drop for Lazy<T> {
+    fn drop(&mut self) {
+        self.ctxt().free_value(Value::from(self.value.raw()))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("type", &self.value.type_name())
+            .field("extracted", &self.extracted.get())
+            .finish()
+    }
+}
+
+impl<T> ExtractValue for Lazy<T> {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        Some(Lazy::new(v))
+    }
+}
+
+impl<T> NewValue for Lazy<T> {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.clone_value(&self.value).into_inner_untracked().raw()
+    }
+}
+
+impl<T> NewValue for &Lazy<T> {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.clone_value(&self.value).into_inner_untracked().raw()
+    }
+}
+
+impl<T> Lazy<T> {
+    /// Create a lazy value, which holds a reference to the Javascript value.
+    pub fn new(v: &Local<Value>) -> Self {
+        Lazy {
+            ctx: v.ctxt.as_ptr() as usize,
+            value: v.ctxt.clone_value(v).into_inner_untracked(),
+            extracted: OnceCell::new(),
+        }
+    }
+
+    fn ctxt(&self) -> &ContextRef {
+        unsafe { ContextRef::from_ptr(self.ctx as *mut _) }
+    }
+
+    /// Returns the Javascript value without conversion.
+    pub fn value(&self) -> Local<Value> {
+        self.ctxt().clone_value(&self.value)
+    }
+
+    /// Returns `true` if the value has been converted.
+    pub fn is_extracted(&self) -> bool {
+        self.extracted.get().is_some()
+    }
+
+    /// Extract a property of the value, without converting the value itself.
+    pub fn field<K: GetProperty, V: ExtractValue>(&self, key: K) -> Result<V, Error> {
+        let ctxt = self.ctxt();
+
+        ctxt.get_property(&self.value, key)
+            .unwrap_or_else(|| ctxt.undefined())
+            .ok()?
+            .extract()
+    }
+
+    /// Returns a property of the value, which is converted on the first access.
+    pub fn lazy_field<K: GetProperty, V>(&self, key: K) -> Result<Lazy<V>, Error> {
+        let ctxt = self.ctxt();
+
+        ctxt.get_property(&self.value, key)
+            .unwrap_or_else(|| ctxt.undefined())
+            .ok()
+            .map(|v| Lazy::new(&v))
+    }
+}
+
+impl<T: ExtractValue> Lazy<T> {
+    /// Converts the value on the first access, and returns the cached result later.
+    ///
+    /// Returns `ErrorKind::Conversion` if the value could not be converted.
+    pub fn get(&self) -> Result<&T, Error> {
+        self.extracted
+            .get_or_init(|| T::extract_value(&self.value()))
+            .as_ref()
+            .ok_or_else(|| {
+                ErrorKind::Conversion {
+                    expected: any::type_name::<T>(),
+                    found: self.value.type_name(),
+                }
+                .into()
+            })
+    }
+
+    /// Converts the value if not yet, and returns the result.
+    pub fn into_inner(mut self) -> Result<T, Error> {
+        self.get()?;
+
+        Ok(self
+            .extracted
+            .take()
+            .and_then(|v| v)
+            .expect("extracted value"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn lazy() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let value = ctxt
+            .eval_script(
+                "var touched = 0; ({ name: 'qjs', get expensive() { touched++; return 1 }, get broken() { throw new Error('broken') }, nested: { n: '2' } })",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let lazy = value.extract::<Lazy<String>>().unwrap();
+
+        assert!(!lazy.is_extracted());
+        assert_eq!(lazy.field::<_, String>("name").unwrap(), "qjs");
+        assert_eq!(
+            ctxt.eval::<_, i32>("touched", Eval::GLOBAL).unwrap(),
+            Some(0)
+        );
+
+        let nested = lazy.lazy_field::<_, ()>("nested").unwrap();
+        let n = nested.lazy_field::<_, i32>("n").unwrap();
+
+        assert_eq!(*n.get().unwrap(), 2);
+        assert!(n.is_extracted());
+        assert!(lazy.field::<_, i32>("broken").is_err());
+
+        assert_eq!(lazy.get().unwrap(), "[object Object]");
+        assert_eq!(lazy.into_inner().unwrap(), "[object Object]");
+
+        let values = ctxt
+            .eval_script("[1, 'two']", "<eval>", Eval::GLOBAL)
+            .unwrap();
+        let first = Lazy::<i32>::new(&values.get_property(0u32).unwrap());
+
+        assert_eq!(*first.get().unwrap(), 1);
+        assert_eq!(*first.get().unwrap(), 1);
+
+        ctxt.global_object().set_property("first", &first).unwrap();
+
+        assert_eq!(ctxt.eval::<_, i32>("first", Eval::GLOBAL).unwrap(), Some(1));
+        assert!(Lazy::<()>::new(&ctxt.null()).get().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
+
+use crate::RuntimeRef;
+
+/// The kind of a live handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    /// a `Context` which is not freed
+    Context,
+    /// a value detached from `Local` with `Local::into_inner`, which must be freed manually
+    Value,
+}
+
+/// A live handle tracked by the leak detection, with its creation location.
+#[derive(Clone, Debug)]
+pub struct LiveHandle {
+    /// the kind of the handle
+    pub kind: HandleKind,
+    /// the location where the handle was created
+    pub location: &'static Location<'static>,
+    /// the backtrace where the handle was created, captured if `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` enabled
+    pub backtrace: Arc<Backtrace>,
+}
+
+impl fmt::Display for LiveHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} created at {}", self.kind, self.location)
+    }
+}
+
+/// The live handles of a runtime, keyed by the context or object pointer.
+#[derive(Default)]
+struct Handles(RefCell<Vec<(usize, LiveHandle)>>);
+
+pub(crate) fn track(
+    rt: &RuntimeRef,
+    kind: HandleKind,
+    ptr: usize,
+    location: &'static Location<'static>,
+) {
+    rt.state::<Handles>().0.borrow_mut().push((
+        ptr,
+        LiveHandle {
+            kind,
+            location,
+            backtrace: Arc::new(Backtrace::capture()),
+        },
+    ));
+}
+
+pub(crate) fn untrack(rt: &RuntimeRef, kind: HandleKind, ptr: usize) {
+    let mut handles = rt.state::<Handles>().0.borrow_mut();
+
+    if let Some(idx) = handles
+        .iter()
+        .rposition(|(p, handle)| *p == ptr && handle.kind == kind)
+    {
+        handles.remove(idx);
+    }
+}
+
+/// Log the leaked handles before the runtime freed.
+pub(crate) fn report(rt: &RuntimeRef) {
+    for (_, handle) in rt.state::<Handles>().0.borrow().iter() {
+        error!("{:?} leaked {}\n{}", rt, handle, handle.backtrace);
+    }
+}
+
+impl RuntimeRef {
+    /// Returns the live contexts and the detached values of the runtime, with their creation locations.
+    ///
+    /// A detached object is tracked until it is freed with `free_value` or passed to the engine as `NewValue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, HandleKind, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let value = ctxt.eval_script("({})", "<eval>", Eval::GLOBAL).unwrap().into_inner();
+    ///
+    /// let handles = rt.live_handles();
+    ///
+    /// assert_eq!(handles.len(), 2);
+    /// assert_eq!(handles[0].kind, HandleKind::Context);
+    /// assert_eq!(handles[1].kind, HandleKind::Value);
+    /// assert_eq!(handles[1].location.file(), file!());
+    ///
+    /// ctxt.free_value(value);
+    ///
+    /// assert_eq!(rt.live_handles().len(), 1);
+    /// ```
+    pub fn live_handles(&self) -> Vec<LiveHandle> {
+        self.state::<Handles>()
+            .0
+            .borrow()
+            .iter()
+            .map(|(_, handle)| handle.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn live_handles() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let raw = Context::builder(&rt).build();
+
+        let kinds = || {
+            rt.live_handles()
+                .into_iter()
+                .map(|handle| handle.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kinds(), vec![HandleKind::Context, HandleKind::Context]);
+
+        drop(raw);
+
+        assert_eq!(kinds(), vec![HandleKind::Context]);
+
+        let obj = ctxt
+            .eval_script("({})", "<eval>", Eval::GLOBAL)
+            .unwrap()
+            .into_inner();
+        let num = ctxt
+            .eval_script("1", "<eval>", Eval::GLOBAL)
+            .unwrap()
+            .into_inner();
+
+        // only the objects are tracked
+        assert_eq!(kinds(), vec![HandleKind::Context, HandleKind::Value]);
+        assert_eq!(rt.live_handles()[1].location.file(), file!());
+
+        // dropping another handle of the object keeps the detached one tracked
+        let local = ctxt.clone_value(&obj);
+
+        drop(local);
+
+        assert_eq!(kinds(), vec![HandleKind::Context, HandleKind::Value]);
+
+        ctxt.free_value(obj);
+        ctxt.free_value(num);
+
+        assert_eq!(kinds(), vec![HandleKind::Context]);
+
+        drop(ctxt);
+
+        assert!(rt.live_handles().is_empty());
+    }
+}
@@ -76,48 +76,215 @@ pub use qjs_derive::qjs;
 
 #[macro_use]
 mod macros;
+mod accessor;
+mod arguments;
 mod arraybuf;
 mod atom;
+mod audit;
+mod background;
+mod backtrace;
+mod batch;
+mod bench;
+#[cfg(feature = "bignum")]
+mod bigfloat;
 mod cfunc;
 mod class;
+mod clock;
+mod codegen;
+pub mod compat;
+mod console;
 mod context;
+mod conversion;
+mod ctor;
+#[cfg(feature = "debugger")]
+mod debugger;
+mod determinism;
+mod engine;
+mod equal;
 mod error;
+mod error_class;
 mod eval;
+mod event_loop;
+mod exception;
+mod expr;
+mod extensions;
+#[cfg(not(target_arch = "wasm32"))]
+mod farm;
+mod freeze;
+mod fs;
 mod func;
+mod gas;
 mod handle;
+mod inspect;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod intern;
+#[cfg(feature = "intl")]
+mod intl;
+mod iter;
 mod job;
+mod lazy;
+#[cfg(feature = "leak-detection")]
+mod leak;
 mod module;
+#[cfg(feature = "rmp")]
+mod msgpack;
+mod multi;
+mod namespace;
+#[cfg(feature = "net")]
+mod net;
+mod numeric;
+mod panic;
+mod permissions;
+mod pipe;
 mod precompile;
+mod prelude;
+mod process;
 mod prop;
+mod reentrancy;
+mod rejection;
+#[cfg(feature = "repl")]
+mod repl;
+mod replay;
+mod reset;
 mod runtime;
-#[cfg(feature = "stdlib")]
+mod sandbox;
+mod shutdown;
+mod snapshot;
+mod sourcemap;
+mod state;
+mod stats;
+#[cfg(all(feature = "stdlib", not(target_os = "wasi")))]
 mod stdlib;
+mod syntax;
+mod tagged;
+mod template;
+mod transform;
+mod trycatch;
+mod types;
+mod unicode;
 mod userdata;
 mod value;
+#[cfg(not(target_arch = "wasm32"))]
+mod watchdog;
+#[cfg(feature = "web")]
+mod web;
 
+pub use accessor::Setter;
+pub use arguments::Arguments;
 pub use arraybuf::{ArrayBuffer, SharedArrayBuffer};
 pub use atom::{Atom, NewAtom};
-pub use cfunc::{CFunc, CFunction, UnsafeCFunction, UnsafeCFunctionData, UnsafeCFunctionMagic};
+pub use audit::{ApiEntry, ApiKind, ApiManifest};
+pub use backtrace::{format_backtrace, parse_backtrace, BacktraceFrame, SourcePosition};
+pub use batch::{Batch, DEFAULT_BATCH_CAPACITY};
+pub use bench::{bench, Bench, BenchReport};
+#[cfg(feature = "bignum")]
+pub use bigfloat::{BigDecimal, BigFloat};
+pub use cfunc::{
+    CFunc, CFunction, This, UnsafeCFunction, UnsafeCFunctionData, UnsafeCFunctionMagic,
+};
 pub use class::{ClassDef, ClassId};
+pub use clock::{Clock, ManualClock, SystemClock};
+#[cfg(feature = "tracing")]
+pub use console::TracingSink;
+pub use console::{ConsoleSink, Level as ConsoleLevel, LogSink, StderrSink};
 pub use context::{Builder as ContextBuilder, Context, ContextRef};
-pub use error::ErrorKind;
+pub use conversion::{ConversionLimit, ConversionLimitExceeded, ConversionLimits};
+pub use ctor::NewTarget;
+#[cfg(feature = "debugger")]
+pub use debugger::{
+    AdapterHandler, DebugAdapter, DebugHandler, Debugger, PauseReason, Paused, Ready, Resume,
+    StackFrame,
+};
+#[cfg(feature = "coverage")]
+pub use debugger::{Coverage, FileCoverage, FunctionCoverage};
+#[cfg(feature = "hooks")]
+pub use debugger::{ExecEvent, ExecHook, Execution};
+#[cfg(feature = "profiler")]
+pub use debugger::{Profile, ProfileNode};
+pub use determinism::{Determinism, Random};
+pub use engine::{EngineStats, Shape};
+pub use equal::EqualOptions;
+pub use error::{ErrorKind, Exception, Unsupported};
+pub use error_class::HostError;
 pub use eval::{eval, load_file, Eval, Source};
-pub use func::Args;
-pub use handle::{Bindable, Local, Unbindable};
+pub use event_loop::{EventLoop, DEFAULT_MAX_TIMERS_PER_TICK, TIMER_WHEEL_SLOTS};
+pub use exception::ExceptionOrigin;
+pub use expr::{CompiledExpr, ExpressionContext};
+pub use extensions::{Extensions, ModuleDetection};
+#[cfg(not(target_arch = "wasm32"))]
+pub use farm::{Builder as FarmBuilder, JobHandle, Limits as JobLimits, RuntimeFarm};
+pub use fs::{FileStat, HostFs, OsFs, Vfs, FS_MODULE};
+pub use func::{Args, TypedFunction};
+pub use gas::{OutOfGas, GAS_PER_BYTECODE, GAS_PER_INTERRUPT};
+pub use handle::{Bindable, Local, RuntimeMismatch, Unbindable};
+pub use inspect::{InspectOptions, DEFAULT_INSPECT_DEPTH};
+pub use intern::{InternStats, Interned};
+#[cfg(feature = "intl")]
+pub use intl::DEFAULT_LOCALE;
+pub use iter::JsIterator;
 pub use job::JobFunc;
+pub use lazy::Lazy;
+#[cfg(feature = "leak-detection")]
+pub use leak::{HandleKind, LiveHandle};
 pub use module::{detect_module, ModuleDef, ModuleInitFunc, ModuleLoaderFunc, ModuleNormalizeFunc};
+pub use multi::MultiValue;
+pub use namespace::Namespace;
+#[cfg(feature = "net")]
+pub use net::{HostNet, NET_MODULE};
+pub use numeric::{
+    Fraction, LargeInteger, NumericError, NumericPolicy, Overflow, MAX_SAFE_INTEGER,
+};
+pub use permissions::{AuditEvent, Capabilities, PermissionDenied, Permissions};
 pub use precompile::{ReadObj, WriteObj};
+pub use process::{HostProcess, PROCESS_MODULE};
 pub use prop::{
     DefinePropertyGetSet, DefinePropertyValue, DeleteProperty, Descriptor as PropertyDescriptor,
     GetProperty, HasProperty, Names as PropertyNames, Prop, SetProperty,
 };
-pub use runtime::{Interrupt, InterruptHandler, MallocFunctions, MemoryUsage, Runtime, RuntimeRef};
+pub use rejection::RejectionPolicy;
+#[cfg(feature = "repl")]
+pub use repl::{Outcome, Repl};
+pub use replay::{HostCall, Trace};
+pub use runtime::{
+    Builder as RuntimeBuilder, Interrupt, InterruptHandler, MallocFunctions, MemoryUsage, Runtime,
+    RuntimeRef,
+};
+pub use sandbox::{Intrinsics, Sandbox};
+pub use shutdown::{ShutdownReport, MAX_SHUTDOWN_JOBS};
+pub use sourcemap::{source_mapping_url, SourceMap};
+pub use stats::Stats;
+#[cfg(all(feature = "stdlib", not(target_os = "wasi")))]
+pub use stdlib::StdLib;
+pub use syntax::SyntaxDiagnostic;
+pub use tagged::TaggedTemplate;
+pub use template::{Escape, Template};
+pub use transform::SourceTransform;
+pub use trycatch::TryCatch;
+pub use types::JsType;
+pub use unicode::{JsString, LoneSurrogate, SurrogatePolicy, FULL_UNICODE};
+pub use userdata::DowncastError;
 pub use value::{
     ExtractValue, NewValue, Value, EXCEPTION, FALSE, NAN, NULL, TRUE, UNDEFINED, UNINITIALIZED,
 };
 
+#[cfg(all(feature = "fetch", feature = "reqwest"))]
+pub use web::ReqwestBackend;
+#[cfg(feature = "web")]
+pub use web::{BlobData, CancellationToken};
+#[cfg(feature = "fetch")]
+pub use web::{Fetch, HttpBackend, HttpRequest, HttpResponse};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use watchdog::{
+    Builder as WatchdogBuilder, Watchdog, WatchdogEvent, DEFAULT_WATCHDOG_INTERVAL,
+};
+
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The version of the linked QuickJS library, the bundled or the system one.
+pub use ffi::VERSION as QUICKJS_VERSION;
+
 lazy_static! {
     pub static ref LONG_VERSION: String = format!(
         "{} (quickjs {}{})",
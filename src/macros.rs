@@ -29,3 +29,17 @@ macro_rules! impl_foreign_type {
         }
     };
 }
+
+/// Enter a `tracing` span until the end of the scope when the `tracing` feature enabled.
+macro_rules! instrument {
+    ($target:expr, $level:ident, $name:expr $(, $($fields:tt)+)?) => {
+        #[cfg(feature = "tracing")]
+        let _instrumented = $crate::instrument::Instrumented::enter(tracing::span!(
+            target: $target,
+            tracing::Level::$level,
+            $name,
+            elapsed = tracing::field::Empty
+            $(, $($fields)+)?
+        ));
+    };
+}
@@ -1,10 +1,16 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr::{null_mut, NonNull};
 
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, value::ToBool, Atom, ContextRef, Local, RuntimeRef, Value};
+use crate::{
+    codegen::CODE_GENERATION_DISALLOWED, ffi, value::ToBool, Atom, ContextRef, Local, RuntimeRef,
+    Value,
+};
 
 /// The C module definition.
 pub type ModuleDef = ffi::JSModuleDef;
@@ -20,23 +26,63 @@ pub type ModuleNormalizeFunc = ffi::JSModuleNormalizeFunc;
 
 impl RuntimeRef {
     /// Set the module loader and normalizer functions.
+    ///
+    /// The module loader will not be called from a context which disabled the code generation,
+    /// except the modules are loaded by the host.
     pub fn set_module_loader<T>(
         &self,
         module_normalize: ModuleNormalizeFunc,
         module_loader: ModuleLoaderFunc,
         opaque: Option<NonNull<T>>,
     ) {
+        self.state::<ModuleLoader>().0.set(module_loader);
+
         unsafe {
             ffi::JS_SetModuleLoaderFunc(
                 self.as_ptr(),
                 module_normalize,
-                module_loader,
+                module_loader.and(Some(module_loader_stub)),
                 opaque.map_or_else(null_mut, |p| p.cast().as_ptr()),
             )
         }
     }
 }
 
+#[derive(Default)]
+struct ModuleLoader(Cell<ModuleLoaderFunc>);
+
+unsafe extern "C" fn module_loader_stub(
+    ctx: *mut ffi::JSContext,
+    module_name: *const c_char,
+    opaque: *mut c_void,
+) -> *mut ModuleDef {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind_or(null_mut(), || {
+        instrument!(
+            "qjs::module",
+            DEBUG,
+            "load_module",
+            module = %std::ffi::CStr::from_ptr(module_name).to_string_lossy()
+        );
+
+        if !ctxt.is_module_loading_allowed() {
+            ctxt.throw_custom_error("EvalError", CODE_GENERATION_DISALLOWED, None);
+
+            return null_mut();
+        }
+
+        match ctxt.runtime().state::<ModuleLoader>().0.get() {
+            Some(loader) => loader(ctx, module_name, opaque),
+            None => {
+                ctxt.throw_reference_error("module loader not set");
+
+                null_mut()
+            }
+        }
+    })
+}
+
 /// return true if `input` contains the source of a module (heuristic).
 ///
 /// Heuristic: skip comments and expect 'import' keyword not followed by '(' or '.'
@@ -62,6 +108,69 @@ impl ContextRef {
         })
     }
 
+    /// Create a native module with the exports created by the host.
+    ///
+    /// The module is registered in the context, and could be imported by its name
+    /// without the module loader. The exports are set when the module is initialized,
+    /// and retained by the context for auditing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.new_native_module("host:math", vec![("answer", ctxt.bind(ctxt.new_value(42)))]).unwrap();
+    ///
+    /// ctxt.eval::<_, ()>("import { answer } from 'host:math'; globalThis.answer = answer;", Eval::MODULE).unwrap();
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("answer", Eval::GLOBAL).unwrap(), Some(42));
+    /// ```
+    pub fn new_native_module<'a, I, S>(
+        &'a self,
+        name: &str,
+        exports: I,
+    ) -> Result<NonNull<ModuleDef>, Error>
+    where
+        I: IntoIterator<Item = (S, Local<'a, Value>)>,
+        S: Into<Vec<u8>>,
+    {
+        let m = self.new_c_module(name, Some(native_module_init))?;
+        let exports = exports
+            .into_iter()
+            .map(|(export_name, value)| {
+                let export_name = CString::new(export_name)?;
+
+                self.check_error(unsafe {
+                    ffi::JS_AddModuleExport(self.as_ptr(), m.as_ptr(), export_name.as_ptr())
+                })?;
+
+                Ok((export_name, value.into_inner_untracked()))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        debug!(
+            "{:?} new native module `{}` with {} exports",
+            self,
+            name,
+            exports.len()
+        );
+
+        let modules = self.state::<NativeModules>();
+        let mut modules = modules.0.borrow_mut();
+        let (_, modules) = modules.get_or_insert_with(|| (self.as_ptr() as usize, HashMap::new()));
+
+        if let Some(exports) = modules.insert(name.to_owned(), exports) {
+            for (_, value) in exports {
+                self.free_value(value);
+            }
+        }
+
+        Ok(m)
+    }
+
     /// return the name of a module
     pub fn module_name(&self, module: &ModuleDef) -> Atom {
         self.bind_atom(unsafe {
@@ -97,7 +206,94 @@ impl ContextRef {
     ///
     /// Useful when `read_object()` returns a module.
     pub fn resolve_module(&self, module: &Value) -> Result<(), Error> {
+        let _loading = self.start_module_loading();
+
         self.check_error(unsafe { ffi::JS_ResolveModule(self.as_ptr(), module.raw()) })
             .map(|_| ())
     }
+
+    /// Returns the exports of the native modules, sorted by the module name.
+    pub(crate) fn native_module_exports(&self) -> Vec<(String, ModuleExports)> {
+        let modules = self.state::<NativeModules>().0.borrow();
+        let mut modules = modules
+            .iter()
+            .flat_map(|(_, modules)| modules.iter())
+            .map(|(name, exports)| {
+                (
+                    name.clone(),
+                    exports
+                        .iter()
+                        .map(|(export_name, value)| {
+                            (
+                                export_name.to_string_lossy().into_owned(),
+                                self.clone_value(value),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        modules.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        modules
+    }
+}
+
+type NativeExports = Vec<(CString, Value)>;
+
+/// The exports of a native module bound to the context.
+pub(crate) type ModuleExports<'a> = Vec<(String, Local<'a, Value>)>;
+
+/// The exports of the native modules.
+#[derive(Default)]
+struct NativeModules(RefCell<Option<(usize, HashMap<String, NativeExports>)>>);
+
+unsafe impl Send for NativeModules {}
+
+impl Drop for NativeModules {
+    fn drop(&mut self) {
+        if let Some((ctx, modules)) = self.0.get_mut().take() {
+            let ctxt = unsafe { ContextRef::from_ptr(ctx as *mut _) };
+
+            for (_, value) in modules.into_values().flatten() {
+                ctxt.free_value(value);
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn native_module_init(ctx: *mut ffi::JSContext, m: *mut ModuleDef) -> c_int {
+    let ctxt = ContextRef::from_ptr(ctx);
+    let name = ctxt.module_name(&*m).to_string();
+    let exports = ctxt
+        .state::<NativeModules>()
+        .0
+        .borrow_mut()
+        .as_mut()
+        .and_then(|(_, modules)| modules.get(&name))
+        .map(|exports| {
+            exports
+                .iter()
+                .map(|(export_name, value)| {
+                    (
+                        export_name.clone(),
+                        ctxt.clone_value(value).into_inner_untracked(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    trace!("{:?} init native module `{}`", ctxt, name);
+
+    let mut ret = 0;
+
+    // the exports are consumed even if failed
+    for (export_name, value) in exports {
+        if ffi::JS_SetModuleExport(ctx, m, export_name.as_ptr(), value.raw()) < 0 {
+            ret = -1;
+        }
+    }
+
+    ret
 }
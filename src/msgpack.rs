@@ -0,0 +1,366 @@
+use std::convert::TryInto;
+
+use failure::{bail, Error};
+use rmp::{encode, Marker};
+
+use crate::{conversion::Budget, prop::Names, ContextRef, Local, Value};
+
+/// The maximum depth of the nested objects and arrays, to break the cycles.
+const MAX_DEPTH: usize = 128;
+
+impl Local<'_, Value> {
+    /// Encode the value to MessagePack, a compact alternative to JSON.
+    ///
+    /// The `null` and `undefined` are encoded as `nil`, the `ArrayBuffer` and typed arrays as `bin`,
+    /// and the objects as `map` of their own enumerable string properties.
+    /// The functions and symbols are skipped in the objects, like `JSON.stringify`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let value = ctxt.eval_script("({ compact: true, schema: 0 })", "<eval>", Eval::GLOBAL).unwrap();
+    /// let buf = value.to_msgpack().unwrap();
+    ///
+    /// assert_eq!(buf, b"\x82\xa7compact\xc3\xa6schema\x00");
+    ///
+    /// let value = ctxt.from_msgpack(&buf).unwrap();
+    ///
+    /// assert_eq!(value.get_property("compact").unwrap(), true);
+    /// ```
+    ///
+    /// The encoding fails with `ConversionLimitExceeded` if it exceeded the conversion limits of the context.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![];
+
+        self.ctxt
+            .convert(|budget| write_value(&mut buf, self, budget, 0))?;
+
+        Ok(buf)
+    }
+}
+
+impl ContextRef {
+    /// Decode a value from MessagePack.
+    ///
+    /// The `bin` is decoded as `ArrayBuffer`, and the `map` as object with the stringified keys.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_msgpack(&self, buf: &[u8]) -> Result<Local<Value>, Error> {
+        let mut rd = buf;
+        let value = read_value(self, &mut rd, 0)?;
+
+        if !rd.is_empty() {
+            bail!("unexpected {} trailing bytes", rd.len())
+        }
+
+        Ok(value)
+    }
+}
+
+fn is_skipped(value: &Local<Value>) -> bool {
+    value.is_function() || value.is_symbol()
+}
+
+fn write_value(
+    wr: &mut Vec<u8>,
+    value: &Local<Value>,
+    budget: &Budget,
+    depth: usize,
+) -> Result<(), Error> {
+    if depth > MAX_DEPTH {
+        bail!("too deep or circular value")
+    }
+
+    let ctxt = value.ctxt;
+
+    if value.is_null() || value.is_undefined() {
+        encode::write_nil(wr)?;
+    } else if let Some(b) = value.as_bool() {
+        encode::write_bool(wr, b)?;
+    } else if let Some(n) = value.as_int() {
+        encode::write_sint(wr, i64::from(n))?;
+    } else if let Some(n) = value.as_float() {
+        encode::write_f64(wr, n)?;
+    } else if value.is_string() {
+        budget.check_string(value)?;
+
+        let s = value.to_string();
+
+        budget.charge_string_bytes(s.len())?;
+        encode::write_str(wr, &s)?;
+    } else if is_skipped(value) || !value.is_object() {
+        bail!("{} can't be encoded", value.to_string())
+    } else if let Some(buf) = ctxt.get_array_buffer(value) {
+        encode::write_bin(wr, buf.as_ref())?;
+    } else if let Some(buf) = value
+        .get_property("buffer")
+        .and_then(|buf| ctxt.get_array_buffer(&buf))
+    {
+        let offset = value
+            .get_property("byteOffset")
+            .and_then(|n| n.to_index())
+            .unwrap_or_default() as usize;
+        let len = value
+            .get_property("byteLength")
+            .and_then(|n| n.to_index())
+            .unwrap_or_default() as usize;
+
+        match buf.get(offset..offset + len) {
+            Some(bytes) => encode::write_bin(wr, bytes)?,
+            None => bail!("out of bounds typed array"),
+        }
+    } else if value.is_array()? {
+        let len = value
+            .get_property("length")
+            .and_then(|len| len.to_index())
+            .unwrap_or_default();
+        let _nested = budget.enter()?;
+
+        budget.charge_elements(len as usize)?;
+        encode::write_array_len(wr, len.try_into()?)?;
+
+        for i in 0..len {
+            match value.get_property(i as u32) {
+                Some(item) if !is_skipped(&item) => {
+                    write_value(wr, &item.ok()?, budget, depth + 1)?
+                }
+                _ => encode::write_nil(wr)?,
+            }
+        }
+    } else {
+        let _nested = budget.enter()?;
+        let names = ctxt
+            .get_own_property_names(value, Names::STRING | Names::ENUM_ONLY)?
+            .unwrap_or_default();
+        let props = names
+            .into_iter()
+            .flat_map(|name| {
+                value
+                    .get_property(&name)
+                    .filter(|item| !is_skipped(item))
+                    .map(|item| (name.to_string(), item))
+            })
+            .collect::<Vec<_>>();
+
+        budget.charge_elements(props.len())?;
+        encode::write_map_len(wr, props.len().try_into()?)?;
+
+        for (key, item) in props {
+            budget.charge_string_bytes(key.len())?;
+            encode::write_str(wr, &key)?;
+            write_value(wr, &item.ok()?, budget, depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_bytes<'a>(rd: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if rd.len() < len {
+        bail!("unexpected end of input")
+    }
+
+    let (bytes, rest) = rd.split_at(len);
+
+    *rd = rest;
+
+    Ok(bytes)
+}
+
+macro_rules! read_be {
+    ($rd:expr, $ty:ty) => {{
+        let mut bytes = [0; std::mem::size_of::<$ty>()];
+
+        bytes.copy_from_slice(read_bytes($rd, std::mem::size_of::<$ty>())?);
+
+        <$ty>::from_be_bytes(bytes)
+    }};
+}
+
+fn read_value<'a>(
+    ctxt: &'a ContextRef,
+    rd: &mut &[u8],
+    depth: usize,
+) -> Result<Local<'a, Value>, Error> {
+    if depth > MAX_DEPTH {
+        bail!("too deep value")
+    }
+
+    let marker = Marker::from_u8(read_be!(rd, u8));
+    let value = match marker {
+        Marker::Null => ctxt.null(),
+        Marker::True => ctxt.bind(ctxt.new_value(true)),
+        Marker::False => ctxt.bind(ctxt.new_value(false)),
+        Marker::FixPos(n) => ctxt.bind(ctxt.new_value(i32::from(n))),
+        Marker::FixNeg(n) => ctxt.bind(ctxt.new_value(i32::from(n))),
+        Marker::U8 => ctxt.bind(ctxt.new_value(i32::from(read_be!(rd, u8)))),
+        Marker::U16 => ctxt.bind(ctxt.new_value(i32::from(read_be!(rd, u16)))),
+        Marker::U32 => ctxt.bind(ctxt.new_value(read_be!(rd, u32))),
+        Marker::U64 => ctxt.bind(ctxt.new_value(read_be!(rd, u64))),
+        Marker::I8 => ctxt.bind(ctxt.new_value(i32::from(read_be!(rd, i8)))),
+        Marker::I16 => ctxt.bind(ctxt.new_value(i32::from(read_be!(rd, i16)))),
+        Marker::I32 => ctxt.bind(ctxt.new_value(read_be!(rd, i32))),
+        Marker::I64 => ctxt.bind(ctxt.new_value(read_be!(rd, i64))),
+        Marker::F32 => ctxt.bind(ctxt.new_value(read_be!(rd, f32))),
+        Marker::F64 => ctxt.bind(ctxt.new_value(read_be!(rd, f64))),
+        Marker::FixStr(len) => read_str(ctxt, rd, usize::from(len))?,
+        Marker::Str8 => {
+            let len = read_be!(rd, u8);
+
+            read_str(ctxt, rd, usize::from(len))?
+        }
+        Marker::Str16 => {
+            let len = read_be!(rd, u16);
+
+            read_str(ctxt, rd, usize::from(len))?
+        }
+        Marker::Str32 => {
+            let len = read_be!(rd, u32);
+
+            read_str(ctxt, rd, len as usize)?
+        }
+        Marker::Bin8 => {
+            let len = read_be!(rd, u8);
+
+            read_bin(ctxt, rd, usize::from(len))?
+        }
+        Marker::Bin16 => {
+            let len = read_be!(rd, u16);
+
+            read_bin(ctxt, rd, usize::from(len))?
+        }
+        Marker::Bin32 => {
+            let len = read_be!(rd, u32);
+
+            read_bin(ctxt, rd, len as usize)?
+        }
+        Marker::FixArray(len) => read_array(ctxt, rd, u32::from(len), depth)?,
+        Marker::Array16 => {
+            let len = read_be!(rd, u16);
+
+            read_array(ctxt, rd, u32::from(len), depth)?
+        }
+        Marker::Array32 => {
+            let len = read_be!(rd, u32);
+
+            read_array(ctxt, rd, len, depth)?
+        }
+        Marker::FixMap(len) => read_map(ctxt, rd, u32::from(len), depth)?,
+        Marker::Map16 => {
+            let len = read_be!(rd, u16);
+
+            read_map(ctxt, rd, u32::from(len), depth)?
+        }
+        Marker::Map32 => {
+            let len = read_be!(rd, u32);
+
+            read_map(ctxt, rd, len, depth)?
+        }
+        marker => bail!("unsupported MessagePack marker {:?}", marker),
+    };
+
+    value.ok()
+}
+
+fn read_str<'a>(
+    ctxt: &'a ContextRef,
+    rd: &mut &[u8],
+    len: usize,
+) -> Result<Local<'a, Value>, Error> {
+    let s = std::str::from_utf8(read_bytes(rd, len)?)?;
+
+    Ok(ctxt.bind(ctxt.new_value(s)))
+}
+
+fn read_bin<'a>(
+    ctxt: &'a ContextRef,
+    rd: &mut &[u8],
+    len: usize,
+) -> Result<Local<'a, Value>, Error> {
+    let mut bytes = read_bytes(rd, len)?.to_vec();
+
+    Ok(ctxt.bind(ctxt.new_value(ctxt.new_array_buffer_copy(&mut bytes))))
+}
+
+fn read_array<'a>(
+    ctxt: &'a ContextRef,
+    rd: &mut &[u8],
+    len: u32,
+    depth: usize,
+) -> Result<Local<'a, Value>, Error> {
+    let arr = ctxt.bind(ctxt.new_array());
+
+    for i in 0..len {
+        arr.set_property(i, read_value(ctxt, rd, depth + 1)?)?;
+    }
+
+    Ok(arr)
+}
+
+fn read_map<'a>(
+    ctxt: &'a ContextRef,
+    rd: &mut &[u8],
+    len: u32,
+    depth: usize,
+) -> Result<Local<'a, Value>, Error> {
+    let obj = ctxt.bind(ctxt.new_object());
+
+    for _ in 0..len {
+        let key = read_value(ctxt, rd, depth + 1)?.to_string();
+        let value = read_value(ctxt, rd, depth + 1)?;
+
+        obj.set_property(key.as_str(), value)?;
+    }
+
+    Ok(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn msgpack() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let value = ctxt
+            .eval_script(
+                "({ n: -1, big: 4294967296, pi: 1.5, s: 'hello', arr: [null, true, undefined], bytes: new Uint8Array([1, 2, 3]).subarray(1), f: function () {} })",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let buf = value.to_msgpack().unwrap();
+
+        let restored = ctxt.from_msgpack(&buf).unwrap();
+
+        ctxt.global_object().set_property("v", restored).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "JSON.stringify([v.n, v.big, v.pi, v.s, v.arr, Array.from(new Uint8Array(v.bytes)), 'f' in v])",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(r#"[-1,4294967296,1.5,"hello",[null,true,null],[2,3],false]"#.to_owned())
+        );
+
+        assert!(ctxt.from_msgpack(b"\x92\x01").is_err());
+        assert!(ctxt.from_msgpack(b"\x01\x02").is_err());
+
+        let circular = ctxt
+            .eval_script("var o = {}; o.self = o; o", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert!(circular.to_msgpack().is_err());
+    }
+}
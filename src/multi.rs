@@ -0,0 +1,286 @@
+use failure::Error;
+
+use crate::{ffi, prop::Names, Args, ContextRef, ExtractValue, Local, NewValue, Prop, Value};
+
+/// `MultiValue` returns several values from a function as a single Javascript value,
+/// which could be destructured by the scripts, like `const [a, b] = f()` or `const {a, b} = f()`.
+///
+/// The values of a tuple are converted to an array by default,
+/// or to a plain object when the fields are named with `with_names`.
+/// The fields without name are keyed by their position, which are enumerated before the named ones.
+///
+/// `MultiValue` could be extracted from an array or an object as well,
+/// the values are taken in the order of the elements or the own enumerable properties,
+/// so the Javascript closures of the `qjs!` macro could return several values.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{qjs, Context, Eval, MultiValue, Runtime};
+///
+/// fn div_rem(a: i32, b: i32) -> MultiValue<(i32, i32)> {
+///     MultiValue::new((a / b, a % b)).with_names(&["quot", "rem"])
+/// }
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// ctxt.global_object()
+///     .set_property("div_rem", ctxt.new_function("div_rem", div_rem as fn(i32, i32) -> _).unwrap())
+///     .unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("const {quot, rem} = div_rem(7, 2); `${quot} ${rem}`", Eval::GLOBAL)
+///         .unwrap(),
+///     Some("3 1".to_owned())
+/// );
+///
+/// let f = qjs!{ (s: &str) -> MultiValue<(String, i32)> => { return [s.toUpperCase(), s.length]; } };
+/// let v = f("hello").unwrap().unwrap();
+///
+/// assert_eq!(v.into_inner(), ("HELLO".to_owned(), 5));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiValue<T> {
+    values: T,
+    names: Option<Vec<String>>,
+}
+
+impl<T> From<T> for MultiValue<T> {
+    fn from(values: T) -> Self {
+        MultiValue::new(values)
+    }
+}
+
+impl<T> MultiValue<T> {
+    /// Create a `MultiValue` from a tuple, which is converted to an array.
+    pub fn new(values: T) -> Self {
+        MultiValue {
+            values,
+            names: None,
+        }
+    }
+
+    /// Name the fields in order, the values are converted to an object with the named properties.
+    pub fn with_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.names = Some(
+            names
+                .into_iter()
+                .map(|name| name.as_ref().to_owned())
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns the names of the fields, `None` for an array.
+    pub fn names(&self) -> Option<&[String]> {
+        self.names.as_deref()
+    }
+
+    /// Returns the values.
+    pub fn values(&self) -> &T {
+        &self.values
+    }
+
+    /// Consumes the `MultiValue`, returning the values.
+    pub fn into_inner(self) -> T {
+        self.values
+    }
+}
+
+impl<T: Args> NewValue for MultiValue<T> {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        let names = self.names;
+        let values = self.values.into_values(ctxt);
+        let res = (|| -> Result<Local<Value>, Error> {
+            let obj = ctxt.bind(match names {
+                Some(_) => ctxt.new_object(),
+                None => ctxt.new_array(),
+            });
+
+            for (idx, &value) in values.as_ref().iter().enumerate() {
+                let value = ctxt.bind(Value::from(value));
+
+                match names.as_ref().and_then(|names| names.get(idx)) {
+                    Some(name) => obj.define_property_value(name.as_str(), value, Prop::C_W_E)?,
+                    None => obj.define_property_value(idx as u32, value, Prop::C_W_E)?,
+                };
+            }
+
+            Ok(obj)
+        })();
+
+        res.new_value(ctxt)
+    }
+}
+
+/// The values with the names of the properties, `None` for the elements of an array.
+type Fields<'a> = (Vec<Local<'a, Value>>, Option<Vec<String>>);
+
+impl Local<'_, Value> {
+    /// Returns the elements of an array, or the values of the own enumerable properties with their names.
+    fn multi_values(&self) -> Option<Fields<'_>> {
+        self.ctxt.convert(|budget| {
+            let _nested = budget.enter().ok()?;
+
+            if self.is_array().ok()? {
+                let len = self.get_property("length")?.to_index()?;
+
+                budget.charge_elements(len as usize).ok()?;
+
+                (0..len)
+                    .map(|idx| self.get_property(idx as u32))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| (values, None))
+            } else if self.is_object() {
+                let names = self
+                    .ctxt
+                    .get_own_property_names(self, Names::STRING | Names::ENUM_ONLY)
+                    .ok()?
+                    .unwrap_or_default();
+
+                budget.charge_elements(names.len()).ok()?;
+
+                let values = names
+                    .iter()
+                    .map(|name| self.get_property(name))
+                    .collect::<Option<Vec<_>>>()?;
+
+                Some((
+                    values,
+                    Some(names.iter().map(|name| name.to_string()).collect()),
+                ))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+macro_rules! multi_value {
+    ($($name:ident)+) => {
+        impl<$( $name ),*> ExtractValue for MultiValue<($( $name, )*)>
+        where
+            $( $name: ExtractValue, )*
+        {
+            #[allow(non_snake_case)]
+            fn extract_value(v: &Local<Value>) -> Option<Self> {
+                let (values, names) = v.multi_values()?;
+                let mut values = values.iter();
+                $( let $name = $name::extract_value(values.next()?)?; )*
+
+                Some(MultiValue {
+                    values: ( $( $name, )* ),
+                    names,
+                })
+            }
+        }
+    }
+}
+
+multi_value! { A }
+multi_value! { A B }
+multi_value! { A B C }
+multi_value! { A B C D }
+multi_value! { A B C D E }
+multi_value! { A B C D E F }
+multi_value! { A B C D E F G }
+multi_value! { A B C D E F G H }
+multi_value! { A B C D E F G H I }
+multi_value! { A B C D E F G H I J }
+multi_value! { A B C D E F G H I J K }
+multi_value! { A B C D E F G H I J K L }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn multi_value() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        fn min_max(values: Vec<i32>) -> MultiValue<(i32, i32)> {
+            MultiValue::new((
+                values.iter().cloned().min().unwrap_or_default(),
+                values.iter().cloned().max().unwrap_or_default(),
+            ))
+        }
+
+        ctxt.global_object()
+            .set_property(
+                "min_max",
+                ctxt.new_function("min_max", min_max as fn(Vec<i32>) -> _)
+                    .unwrap(),
+            )
+            .unwrap();
+        ctxt.global_object()
+            .set_property(
+                "parse",
+                ctxt.new_c_function(
+                    |ctxt, _this, args| {
+                        let s = ctxt.clone_value(&args[0]).to_string();
+                        let mut parts = s.splitn(2, '@');
+
+                        MultiValue::new((
+                            parts.next().unwrap_or_default().to_owned(),
+                            parts.next().unwrap_or_default().to_owned(),
+                            s.len() as i32,
+                        ))
+                        .with_names(&["user", "host"])
+                    },
+                    Some("parse"),
+                    1,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+const [min, max] = min_max([3, 1, 4, 1, 5]);
+const {user, host, 2: len} = parse('root@localhost');
+
+[Array.isArray(min_max([])), min, max, user, host, len, Object.keys(parse('guest'))].join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("true,1,5,root,localhost,14,2,user,host".to_owned())
+        );
+
+        // extract the values from an array or an object
+        let v = ctxt
+            .eval::<_, MultiValue<(String, f64)>>("['x', 1.5]", Eval::GLOBAL)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(v.names(), None);
+        assert_eq!(v.into_inner(), ("x".to_owned(), 1.5));
+
+        let v = ctxt
+            .eval::<_, MultiValue<(i32, bool)>>("({ count: 2, done: true })", Eval::GLOBAL)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            v.names(),
+            Some(&["count".to_owned(), "done".to_owned()][..])
+        );
+        assert_eq!(v.values(), &(2, true));
+
+        assert!(ctxt
+            .eval::<_, MultiValue<(i32, i32)>>("[1]", Eval::GLOBAL)
+            .unwrap()
+            .is_none());
+    }
+}
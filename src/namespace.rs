@@ -0,0 +1,219 @@
+use std::fmt;
+
+use failure::Error;
+
+use crate::{ffi, CFunction, ContextRef, Local, NewValue, Prop, Value};
+
+type Member = Box<dyn FnOnce(&ContextRef) -> Result<Local<Value>, Error>>;
+
+/// `Namespace` builds a frozen plain object from the Rust functions and constants,
+/// like `Math` or `JSON`, without the constructor and prototype of a class.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, Namespace, Runtime};
+///
+/// fn clamp(value: f64, min: f64, max: f64) -> f64 {
+///     value.max(min).min(max)
+/// }
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// Namespace::new()
+///     .with_function("clamp", clamp as fn(f64, f64, f64) -> f64)
+///     .with_constant("MAX", 10)
+///     .register(&ctxt, "util")
+///     .unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval("util.clamp(42, 0, util.MAX)", Eval::GLOBAL).unwrap(),
+///     Some(10.0)
+/// );
+/// assert_eq!(
+///     ctxt.eval("util.MAX = 100; util.MAX", Eval::GLOBAL).unwrap(),
+///     Some(10)
+/// );
+/// ```
+#[derive(Default)]
+pub struct Namespace {
+    members: Vec<(String, Member)>,
+}
+
+impl fmt::Debug for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Namespace")
+            .field(
+                "members",
+                &self
+                    .members
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Namespace {
+    /// Create an empty namespace.
+    pub fn new() -> Self {
+        Namespace::default()
+    }
+
+    /// Add a function, e.g. a typed function like `fn(T0, T1) -> Ret`,
+    /// the `name` property of the function is set to the member name.
+    pub fn with_function<F: NewValue + 'static>(mut self, name: &str, func: F) -> Self {
+        let func_name = name.to_owned();
+
+        self.members.push((
+            name.to_owned(),
+            Box::new(move |ctxt| ctxt.new_function(&func_name, func)),
+        ));
+        self
+    }
+
+    /// Add a native function which receives the `this` value and the raw arguments.
+    pub fn with_native<T: NewValue + 'static>(
+        mut self,
+        name: &str,
+        func: CFunction<T>,
+        length: usize,
+    ) -> Self {
+        let func_name = name.to_owned();
+
+        self.members.push((
+            name.to_owned(),
+            Box::new(move |ctxt| ctxt.new_c_function(func, Some(&func_name), length)),
+        ));
+        self
+    }
+
+    /// Add a constant value.
+    pub fn with_constant<V: NewValue + 'static>(mut self, name: &str, value: V) -> Self {
+        self.members.push((
+            name.to_owned(),
+            Box::new(move |ctxt| ctxt.bind(value.new_value(ctxt)).ok()),
+        ));
+        self
+    }
+
+    /// Add a nested namespace, which is frozen as well.
+    pub fn with_namespace(mut self, name: &str, namespace: Namespace) -> Self {
+        self.members
+            .push((name.to_owned(), Box::new(move |ctxt| namespace.build(ctxt))));
+        self
+    }
+
+    /// Build the namespace object in the context.
+    ///
+    /// The namespace is frozen, the members can't be replaced, added or removed by the scripts.
+    pub fn build(self, ctxt: &ContextRef) -> Result<Local<Value>, Error> {
+        let obj = ctxt.bind(ctxt.new_object());
+
+        for (name, member) in self.members {
+            let value = member(ctxt)?;
+
+            obj.define_property_value(name.as_str(), value, Prop::ENUMERABLE)?;
+        }
+
+        obj.freeze()?;
+
+        Ok(obj)
+    }
+
+    /// Build the namespace object and add it to the global object.
+    pub fn register(self, ctxt: &ContextRef, name: &str) -> Result<(), Error> {
+        let obj = self.build(ctxt)?;
+
+        ctxt.global_object().define_property_value(
+            name,
+            obj,
+            Prop::CONFIGURABLE | Prop::WRITABLE,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl NewValue for Namespace {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        self.build(ctxt).new_value(ctxt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn namespace() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        fn parse(s: String) -> i32 {
+            s.trim().parse().unwrap_or_default()
+        }
+
+        let ns = Namespace::new()
+            .with_function("parse", parse as fn(String) -> i32)
+            .with_native(
+                "sum",
+                |ctxt, _this, args| {
+                    args.iter()
+                        .map(|arg| ctxt.to_int32(arg).unwrap_or_default())
+                        .sum::<i32>()
+                },
+                2,
+            )
+            .with_constant("VERSION", "1.0")
+            .with_namespace("limits", Namespace::new().with_constant("MAX", 10));
+
+        assert_eq!(
+            format!("{:?}", ns),
+            r#"Namespace { members: ["parse", "sum", "VERSION", "limits"] }"#
+        );
+
+        ctxt.global_object().set_property("ns", ns).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[ns.parse(' 42 '), ns.sum(1, 2, 3), ns.VERSION, ns.limits.MAX].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("42,6,1.0,10".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "[ns.parse.name, ns.sum.name, ns.sum.length, Object.keys(ns)].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("parse,sum,2,parse,sum,VERSION,limits".to_owned())
+        );
+
+        // the namespace and the nested ones are frozen
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+'use strict';
+[
+    () => { ns.parse = null },
+    () => { ns.extra = 1 },
+    () => { delete ns.VERSION },
+    () => { ns.limits.MAX = 100 },
+].map(f => { try { f() } catch (err) { return err.name } }).join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("TypeError,TypeError,TypeError,TypeError".to_owned())
+        );
+    }
+}
@@ -0,0 +1,553 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use failure::Error;
+use futures::{
+    channel::oneshot,
+    future::{select, Either, FutureExt, Shared},
+    lock::Mutex,
+};
+use tokio::net::{
+    lookup_host,
+    udp::{RecvHalf, SendHalf},
+    TcpListener, TcpStream, UdpSocket,
+};
+use tokio::runtime::{Builder, Runtime as TokioRuntime};
+use tokio_util::compat::{Tokio02AsyncReadCompatExt, Tokio02AsyncWriteCompatExt};
+
+use crate::{ffi, Capabilities, ContextRef, ErrorKind, Eval, Local, NewValue, Value, UNDEFINED};
+
+/// The name of the network module.
+pub const NET_MODULE: &str = "host:net";
+
+/// The maximum size of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 64 * 1024;
+
+/// The functions exported by the `host:net` module.
+const NET_EXPORTS: &[&str] = &["connect", "listen", "bindUdp"];
+
+/// The factory of the module, the native functions are wrapped with the promises.
+const NET_FACTORY: &str = r#"
+(function (native) {
+    const toBuffer = (data) => ArrayBuffer.isView(data)
+        ? data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength)
+        : data;
+    const call = (func, ...args) => new Promise((resolve, reject) => func(...args, (err, res) => {
+        err !== null ? reject(new Error(err)) : resolve(res);
+    }));
+
+    class Listener {
+        constructor(handle) {
+            Object.defineProperty(this, 'handle', { value: handle });
+            this.address = native.listenerAddress(handle);
+        }
+        async accept() { return call(native.accept, this.handle); }
+        close() { native.closeListener(this.handle); }
+    }
+
+    class UdpSocket {
+        constructor(handle) {
+            Object.defineProperty(this, 'handle', { value: handle });
+            this.address = native.udpAddress(handle);
+        }
+        async send(data, host, port) { return call(native.send, this.handle, toBuffer(data), String(host), port); }
+        async recv() { return call(native.recv, this.handle); }
+        close() { native.closeUdp(this.handle); }
+    }
+
+    return {
+        connect: async (host, port) => call(native.connect, String(host), port),
+        listen: async (host, port) => new Listener(await call(native.listen, String(host), port)),
+        bindUdp: async (host, port) => new UdpSocket(await call(native.bindUdp, String(host), port)),
+    };
+})
+"#;
+
+/// The tokio runtime which drives the sockets of a context.
+#[derive(Default)]
+struct NetState(RefCell<Option<TokioRuntime>>);
+
+/// The signal of closing a socket, which is resolved when the socket dropped.
+type Closed = Shared<oneshot::Receiver<()>>;
+
+/// The listening socket holds by a userdata object, which is shared with the pending accepts.
+struct Listener {
+    listener: Arc<Mutex<TcpListener>>,
+    addr: SocketAddr,
+    closed: Closed,
+    _close: oneshot::Sender<()>,
+}
+
+impl Listener {
+    fn new(listener: TcpListener) -> Result<Self, Error> {
+        let (close, closed) = oneshot::channel();
+
+        Ok(Listener {
+            addr: listener.local_addr()?,
+            listener: Arc::new(Mutex::new(listener)),
+            closed: closed.shared(),
+            _close: close,
+        })
+    }
+}
+
+impl NewValue for Listener {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.new_userdata(self).new_value(ctxt)
+    }
+}
+
+/// The UDP socket holds by a userdata object, which is shared with the pending sends and receives.
+struct Udp {
+    recv: Arc<Mutex<RecvHalf>>,
+    send: Arc<Mutex<SendHalf>>,
+    addr: SocketAddr,
+    closed: Closed,
+    _close: oneshot::Sender<()>,
+}
+
+impl Udp {
+    fn new(socket: UdpSocket) -> Result<Self, Error> {
+        let addr = socket.local_addr()?;
+        let (recv, send) = socket.split();
+        let (close, closed) = oneshot::channel();
+
+        Ok(Udp {
+            recv: Arc::new(Mutex::new(recv)),
+            send: Arc::new(Mutex::new(send)),
+            addr,
+            closed: closed.shared(),
+            _close: close,
+        })
+    }
+}
+
+impl NewValue for Udp {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.new_userdata(self).new_value(ctxt)
+    }
+}
+
+/// The TCP connection accepted or connected, which is wrapped with the streams on the runtime thread.
+struct Connection(TcpStream);
+
+impl NewValue for Connection {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        ctxt.new_connection(self.0).new_value(ctxt)
+    }
+}
+
+/// The UDP datagram received from the address.
+struct Datagram(Vec<u8>, SocketAddr);
+
+impl NewValue for Datagram {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        let Datagram(mut buf, addr) = self;
+        let res = (|| -> Result<Local<Value>, Error> {
+            let datagram = ctxt.bind(ctxt.new_object());
+
+            datagram.set_property("data", ctxt.new_array_buffer_copy(&mut buf))?;
+            datagram.set_property("address", addr.to_string())?;
+
+            Ok(datagram)
+        })();
+
+        res.new_value(ctxt)
+    }
+}
+
+/// Run the future until it completes, or the socket closed.
+async fn until_closed<F, T>(closed: Closed, future: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    match select(Box::pin(future), closed).await {
+        Either::Left((res, _)) => res,
+        Either::Right(_) => bail!("socket closed"),
+    }
+}
+
+/// The builder of the `host:net` module, which exposes the TCP and UDP sockets to the scripts.
+///
+/// The module exports `connect(host, port)`, `listen(host, port)` and `bindUdp(host, port)` returning the promises.
+/// A TCP connection is an object with the `readable` and `writable` streams, and the `localAddress`
+/// and `remoteAddress`, the backpressure is applied by the streams which read the socket on demand.
+/// A listener has `address`, `accept()` and `close()`, and a UDP socket has `address`,
+/// `send(data, host, port)`, `recv()` and `close()`.
+///
+/// The sockets are driven by a tokio runtime, the promises are settled when the runtime polls the
+/// completed operations, e.g. in the `EventLoop`, and the functions require the `NET` capability.
+/// Closing a listener or a UDP socket rejects its pending operations.
+/// The web platform must be initialized before the module, for the streams.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, EventLoop, HostNet, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// ctxt.init_web_platform().unwrap();
+/// HostNet::new().init(&ctxt).unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     r#"
+/// import { bindUdp } from 'host:net';
+///
+/// (async () => {
+///     const a = await bindUdp('127.0.0.1', 0);
+///     const b = await bindUdp('127.0.0.1', 0);
+///     const [host, port] = b.address.split(':');
+///
+///     await a.send('ping', host, Number(port));
+///
+///     const { data } = await b.recv();
+///
+///     globalThis.received = new TextDecoder().decode(data);
+/// })();
+/// "#,
+///     Eval::MODULE,
+/// )
+/// .unwrap();
+///
+/// EventLoop::new(&rt).run().unwrap();
+///
+/// assert_eq!(ctxt.eval::<_, String>("received", Eval::GLOBAL).unwrap().unwrap(), "ping");
+/// ```
+#[derive(Debug, Default)]
+pub struct HostNet {
+    worker_threads: Option<usize>,
+}
+
+impl HostNet {
+    pub fn new() -> Self {
+        HostNet::default()
+    }
+
+    /// Set the number of the worker threads of the tokio runtime.
+    pub fn with_worker_threads(mut self, n: usize) -> Self {
+        self.worker_threads = Some(n);
+        self
+    }
+
+    /// Register the `host:net` module in the context.
+    pub fn init(self, ctxt: &ContextRef) -> Result<(), Error> {
+        let mut builder = Builder::new();
+
+        builder.threaded_scheduler().enable_all();
+
+        if let Some(n) = self.worker_threads {
+            builder.core_threads(n);
+        }
+
+        *ctxt.state::<NetState>().0.borrow_mut() = Some(builder.build()?);
+
+        let native = ctxt.bind(ctxt.new_object());
+
+        for &(name, func, length) in &[
+            (
+                "connect",
+                connect as fn(&ContextRef, Option<&Value>, &[Value]) -> Value,
+                3,
+            ),
+            ("listen", listen, 3),
+            ("listenerAddress", listener_address, 1),
+            ("accept", accept, 2),
+            ("closeListener", close_listener, 1),
+            ("bindUdp", bind_udp, 3),
+            ("udpAddress", udp_address, 1),
+            ("send", send, 5),
+            ("recv", recv, 2),
+            ("closeUdp", close_udp, 1),
+        ] {
+            native.set_property(
+                name,
+                ctxt.new_c_function_with_capabilities(func, Some(name), length, Capabilities::NET)?,
+            )?;
+        }
+
+        let factory = ctxt.eval_script(NET_FACTORY, "<net>", Eval::GLOBAL | Eval::STRICT)?;
+        let net = ctxt.call(&factory, None, [&native])?;
+        let exports = NET_EXPORTS
+            .iter()
+            .map(|&name| {
+                net.get_property(name)
+                    .ok_or_else(|| format_err!("`{}` not defined", name))
+                    .map(|func| (name, func))
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .into_iter()
+            .chain(Some(("default", ctxt.clone_value(&net))));
+
+        ctxt.new_native_module(NET_MODULE, exports)?;
+
+        Ok(())
+    }
+}
+
+impl ContextRef {
+    /// Spawn the future on the tokio runtime of the `host:net` module, the callback is called when it completes.
+    fn spawn_net<F, T>(&self, callback: &Value, future: F) -> Result<(), Error>
+    where
+        F: Future<Output = Result<T, Error>> + Send + 'static,
+        T: NewValue + Send + 'static,
+    {
+        let state = self.state::<NetState>().0.borrow();
+        let rt = state
+            .as_ref()
+            .ok_or_else(|| format_err!("network is not initialized"))?;
+        let op = self.start_operation(&[callback]);
+
+        rt.spawn(async move { op.complete(future.await) });
+
+        Ok(())
+    }
+
+    fn new_connection(&self, stream: TcpStream) -> Result<Local<Value>, Error> {
+        let local_addr = stream.local_addr()?;
+        let peer_addr = stream.peer_addr()?;
+        let (reader, writer) = tokio::io::split(stream);
+        let conn = self.bind(self.new_object());
+
+        conn.set_property("readable", self.new_readable_stream(reader.compat())?)?;
+        conn.set_property("writable", self.new_writable_stream(writer.compat_write())?)?;
+        conn.set_property("localAddress", local_addr.to_string())?;
+        conn.set_property("remoteAddress", peer_addr.to_string())?;
+
+        Ok(conn)
+    }
+}
+
+fn to_value(ctxt: &ContextRef, res: Result<Local<Value>, Error>) -> Value {
+    match res {
+        Ok(value) => value.into_inner_untracked(),
+        Err(err) => ErrorKind::Error(err.to_string(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+fn to_undefined(ctxt: &ContextRef, res: Result<(), Error>) -> Value {
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => to_value(ctxt, Err(err)),
+    }
+}
+
+fn host_port(ctxt: &ContextRef, args: &[Value]) -> Result<(String, u16), Error> {
+    let host = ctxt.clone_value(&args[0]).to_string();
+    let port = ctxt
+        .clone_value(&args[1])
+        .as_int()
+        .filter(|&port| 0 <= port && port <= i32::from(u16::max_value()))
+        .ok_or_else(|| ErrorKind::RangeError("invalid port".into(), None))?;
+
+    Ok((host, port as u16))
+}
+
+fn connect(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = host_port(ctxt, args).and_then(|(host, port)| {
+        trace!("connect to {}:{}", host, port);
+
+        ctxt.spawn_net(&args[2], async move {
+            Ok(Connection(TcpStream::connect((host.as_str(), port)).await?))
+        })
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn listen(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = host_port(ctxt, args).and_then(|(host, port)| {
+        trace!("listen on {}:{}", host, port);
+
+        ctxt.spawn_net(&args[2], async move {
+            Listener::new(TcpListener::bind((host.as_str(), port)).await?)
+        })
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn listener_address(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0]
+        .downcast_ref::<Listener>()
+        .map(|listener| ctxt.bind(ctxt.new_value(listener.addr.to_string())));
+
+    to_value(ctxt, res)
+}
+
+fn accept(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0].downcast_ref::<Listener>().and_then(|listener| {
+        let closed = listener.closed.clone();
+        let listener = listener.listener.clone();
+
+        ctxt.spawn_net(
+            &args[1],
+            until_closed(closed, async move {
+                let (stream, _) = listener.lock().await.accept().await?;
+
+                Ok(Connection(stream))
+            }),
+        )
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn close_listener(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    to_undefined(ctxt, args[0].take::<Listener>().map(|_| ()))
+}
+
+fn bind_udp(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = host_port(ctxt, args).and_then(|(host, port)| {
+        trace!("bind UDP socket on {}:{}", host, port);
+
+        ctxt.spawn_net(&args[2], async move {
+            Udp::new(UdpSocket::bind((host.as_str(), port)).await?)
+        })
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn udp_address(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0]
+        .downcast_ref::<Udp>()
+        .map(|socket| ctxt.bind(ctxt.new_value(socket.addr.to_string())));
+
+    to_value(ctxt, res)
+}
+
+fn send(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let data = ctxt.clone_value(&args[1]);
+    let data = match ctxt.get_array_buffer(&data) {
+        Some(buf) => buf.as_ref().to_vec(),
+        None => data.to_string().into_bytes(),
+    };
+    let res = host_port(ctxt, &args[2..]).and_then(|(host, port)| {
+        let socket = args[0].downcast_ref::<Udp>()?;
+        let closed = socket.closed.clone();
+        let socket = socket.send.clone();
+
+        ctxt.spawn_net(
+            &args[4],
+            until_closed(closed, async move {
+                let addr = lookup_host((host.as_str(), port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| format_err!("fail to resolve `{}`", host))?;
+                let sent = socket.lock().await.send_to(&data, &addr).await?;
+
+                Ok(sent as u32)
+            }),
+        )
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn recv(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0].downcast_ref::<Udp>().and_then(|socket| {
+        let closed = socket.closed.clone();
+        let socket = socket.recv.clone();
+
+        ctxt.spawn_net(
+            &args[1],
+            until_closed(closed, async move {
+                let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+                let (len, addr) = socket.lock().await.recv_from(&mut buf).await?;
+
+                buf.truncate(len);
+
+                Ok(Datagram(buf, addr))
+            }),
+        )
+    });
+
+    to_undefined(ctxt, res)
+}
+
+fn close_udp(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    to_undefined(ctxt, args[0].take::<Udp>().map(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, EventLoop, Permissions, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn host_net() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+        HostNet::new().with_worker_threads(1).init(&ctxt).unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+import net from 'host:net';
+
+globalThis.net = net;
+
+(async () => {
+    const listener = await net.listen('127.0.0.1', 0);
+    const [host, port] = listener.address.split(':');
+    const client = await net.connect(host, Number(port));
+    const server = await listener.accept();
+
+    const writer = client.writable.getWriter();
+    await writer.write(new TextEncoder().encode('hello'));
+    await writer.close();
+
+    const reader = server.readable.getReader();
+    const chunks = [];
+
+    for (;;) {
+        const { done, value } = await reader.read();
+        if (done) break;
+        chunks.push(new TextDecoder().decode(value));
+    }
+
+    listener.close();
+
+    globalThis.result = [chunks.join(''), server.remoteAddress === client.localAddress].join('|');
+})().catch((err) => { globalThis.result = String(err); });
+"#,
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("result", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "hello|true"
+        );
+
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::NET));
+        ctxt.eval::<_, ()>(
+            "net.connect('127.0.0.1', 1).catch((err) => { globalThis.denied = err.message; })",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("denied", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "permission denied, `connect` requires NET"
+        );
+    }
+}
@@ -0,0 +1,620 @@
+use std::any;
+use std::cell::Cell;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ErrorKind, Local, NewValue, Value};
+
+/// The largest integer which can be represented exactly as a Javascript number, `Number.MAX_SAFE_INTEGER`.
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// How a number with the fractional part is converted to an integer type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fraction {
+    /// discard the fractional part, like `ToInt32`
+    Truncate,
+    /// round half away from zero
+    Round,
+    /// fail the conversion with `NumericError::Fraction`
+    Error,
+}
+
+/// How a number out of the range of an integer type is converted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// wrap around like `ToInt32` and `ToInt64`, the `u64` fails if it is not a valid index
+    Wrap,
+    /// clamp to the minimum or maximum value of the type
+    Saturate,
+    /// fail the conversion with `NumericError::Overflow`
+    Error,
+}
+
+/// How the `i64` and `u64` values are converted to the Javascript values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LargeInteger {
+    /// always create a `BigInt`
+    BigInt,
+    /// create a number if it is a safe integer, otherwise a `BigInt`
+    Auto,
+    /// create a number, the precision is lost beyond `MAX_SAFE_INTEGER`
+    Lossy,
+    /// create a number if it is a safe integer, otherwise throw a `RangeError`
+    Error,
+}
+
+/// The policy of converting the numbers between the Javascript values and the Rust integer types.
+///
+/// The default policy follows the Javascript semantics, e.g. `2.5` is converted to `2i32`, `NaN` to `0i32`,
+/// and `2 ** 32 + 1` wraps around to `1i32`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumericPolicy {
+    /// how the fractional part is handled when extracting the integers
+    pub fraction: Fraction,
+    /// how the out of range numbers are handled when extracting the integers
+    pub overflow: Overflow,
+    /// whether the `NaN` and infinities fail when extracting the integers, instead of being converted to zero
+    pub reject_non_finite: bool,
+    /// how the `i64` and `u64` are converted to the Javascript values,
+    /// defaults to `LargeInteger::BigInt` with the `bignum` feature, otherwise `LargeInteger::Lossy`
+    pub large_integer: LargeInteger,
+}
+
+impl Default for NumericPolicy {
+    fn default() -> Self {
+        NumericPolicy {
+            fraction: Fraction::Truncate,
+            overflow: Overflow::Wrap,
+            reject_non_finite: false,
+            large_integer: if cfg!(feature = "bignum") {
+                LargeInteger::BigInt
+            } else {
+                LargeInteger::Lossy
+            },
+        }
+    }
+}
+
+impl NumericPolicy {
+    /// The strict policy, which fails the lossy conversions instead of guessing.
+    pub fn strict() -> Self {
+        NumericPolicy {
+            fraction: Fraction::Error,
+            overflow: Overflow::Error,
+            reject_non_finite: true,
+            large_integer: LargeInteger::Error,
+        }
+    }
+
+    /// Set how the fractional part is handled.
+    pub fn with_fraction(mut self, fraction: Fraction) -> Self {
+        self.fraction = fraction;
+        self
+    }
+
+    /// Set how the out of range numbers are handled.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Set whether the `NaN` and infinities fail when extracting the integers.
+    pub fn with_reject_non_finite(mut self, reject: bool) -> Self {
+        self.reject_non_finite = reject;
+        self
+    }
+
+    /// Set how the `i64` and `u64` are converted to the Javascript values.
+    pub fn with_large_integer(mut self, large_integer: LargeInteger) -> Self {
+        self.large_integer = large_integer;
+        self
+    }
+
+    /// Whether the integers are extracted with the Javascript semantics.
+    fn is_lenient(&self) -> bool {
+        self.fraction == Fraction::Truncate
+            && self.overflow == Overflow::Wrap
+            && !self.reject_non_finite
+    }
+}
+
+/// The number can't be converted to an integer type with the `NumericPolicy`.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+pub enum NumericError {
+    #[fail(display = "{} is not an integer", _0)]
+    Fraction(f64),
+    #[fail(display = "{} is out of the range of `{}`", _0, _1)]
+    Overflow(f64, &'static str),
+    #[fail(display = "{} is not a finite number", _0)]
+    NonFinite(f64),
+}
+
+#[derive(Debug, Default)]
+struct Numeric(Cell<Option<NumericPolicy>>);
+
+impl ContextRef {
+    /// Set the policy of converting the numbers with `ExtractValue` and `NewValue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, NumericError, NumericPolicy, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("2.5", Eval::GLOBAL).unwrap(), Some(2));
+    /// assert_eq!(ctxt.eval::<_, i32>("2 ** 32 + 1", Eval::GLOBAL).unwrap(), Some(1));
+    ///
+    /// ctxt.set_numeric_policy(NumericPolicy::strict());
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, i32>("2.5", Eval::GLOBAL)
+    ///         .unwrap_err()
+    ///         .downcast::<NumericError>()
+    ///         .unwrap(),
+    ///     NumericError::Fraction(2.5)
+    /// );
+    /// assert_eq!(
+    ///     ctxt.eval::<_, i32>("2 ** 32 + 1", Eval::GLOBAL)
+    ///         .unwrap_err()
+    ///         .to_string(),
+    ///     "4294967297 is out of the range of `i32`"
+    /// );
+    /// ```
+    pub fn set_numeric_policy(&self, policy: NumericPolicy) {
+        self.state::<Numeric>().0.set(Some(policy))
+    }
+
+    /// Returns the policy of converting the numbers.
+    pub fn numeric_policy(&self) -> NumericPolicy {
+        self.state::<Numeric>().0.get().unwrap_or_default()
+    }
+
+    /// Call the function with the numeric policy, the previous policy is restored after it returned.
+    pub fn with_numeric_policy<T, F: FnOnce() -> T>(&self, policy: NumericPolicy, f: F) -> T {
+        let state = self.state::<Numeric>();
+        let prev = state.0.replace(Some(policy));
+        let res = f();
+
+        state.0.set(prev);
+
+        res
+    }
+
+    /// Create a value of the `i64` with the numeric policy.
+    pub(crate) fn new_int64(&self, n: i64) -> ffi::JSValue {
+        let safe = (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&n);
+
+        match self.numeric_policy().large_integer {
+            LargeInteger::BigInt => unsafe { ffi::JS_NewBigInt64(self.as_ptr(), n) },
+            LargeInteger::Auto if !safe => unsafe { ffi::JS_NewBigInt64(self.as_ptr(), n) },
+            LargeInteger::Error if !safe => {
+                ErrorKind::RangeError(format!("{} is not a safe integer", n), None).new_value(self)
+            }
+            _ => unsafe { ffi::JS_NewInt64(self.as_ptr(), n) },
+        }
+    }
+
+    /// Create a value of the `u64` with the numeric policy.
+    pub(crate) fn new_uint64(&self, n: u64) -> ffi::JSValue {
+        let safe = n <= MAX_SAFE_INTEGER as u64;
+
+        match self.numeric_policy().large_integer {
+            LargeInteger::BigInt => unsafe { ffi::JS_NewBigUint64(self.as_ptr(), n) },
+            LargeInteger::Auto if !safe => unsafe { ffi::JS_NewBigUint64(self.as_ptr(), n) },
+            LargeInteger::Error if !safe => {
+                ErrorKind::RangeError(format!("{} is not a safe integer", n), None).new_value(self)
+            }
+            LargeInteger::Lossy if !safe => Value::from(n as f64).raw(),
+            _ => unsafe { ffi::JS_NewInt64(self.as_ptr(), n as i64) },
+        }
+    }
+}
+
+/// The elements of a typed array, which are borrowed from its backing store.
+enum Elements<'a> {
+    Uint8(&'a [u8]),
+    Int8(&'a [i8]),
+    Int16(&'a [i16]),
+    Uint16(&'a [u16]),
+    Int32(&'a [i32]),
+    Uint32(&'a [u32]),
+    BigInt64(&'a [i64]),
+    BigUint64(&'a [u64]),
+    Float32(&'a [f32]),
+    Float64(&'a [f64]),
+}
+
+impl Local<'_, Value> {
+    /// Extract an array or typed array of numbers as `f64`.
+    ///
+    /// The backing store of `Float64Array` is copied, and the other typed arrays or fast arrays
+    /// are converted without getting the elements one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let v = ctxt.eval_script("new Float64Array([1.5, 2.5])", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert_eq!(v.extract_f64_slice().unwrap(), vec![1.5, 2.5]);
+    ///
+    /// let v = ctxt.eval_script("[1, 2.5, 3]", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert_eq!(v.extract_f64_slice().unwrap(), vec![1.0, 2.5, 3.0]);
+    /// ```
+    pub fn extract_f64_slice(&self) -> Result<Vec<f64>, Error> {
+        if let Some(elements) = self.typed_array_elements() {
+            return Ok(match elements {
+                Elements::Float64(v) => v.to_vec(),
+                Elements::Float32(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Uint8(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Int8(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Int16(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Uint16(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Int32(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::Uint32(v) => v.iter().map(|&n| f64::from(n)).collect(),
+                Elements::BigInt64(v) => v.iter().map(|&n| n as f64).collect(),
+                Elements::BigUint64(v) => v.iter().map(|&n| n as f64).collect(),
+            });
+        }
+
+        self.extract_numbers(|v| match v.tag() {
+            ffi::JS_TAG_INT => v.as_int().map(f64::from),
+            ffi::JS_TAG_FLOAT64 => v.as_float(),
+            _ => None,
+        })
+    }
+
+    /// Extract an array or typed array of integers as `i32`.
+    ///
+    /// The backing store of `Int32Array` is copied, and the other typed arrays or fast arrays
+    /// are converted without getting the elements one by one,
+    /// returns `ErrorKind::Conversion` if an element is not an integer in the `i32` range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let v = ctxt.eval_script("new Int32Array([1, -2, 3])", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert_eq!(v.extract_i32_vec().unwrap(), vec![1, -2, 3]);
+    ///
+    /// let v = ctxt.eval_script("[1, 2.5]", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// assert!(v.extract_i32_vec().is_err());
+    /// ```
+    pub fn extract_i32_vec(&self) -> Result<Vec<i32>, Error> {
+        if let Some(elements) = self.typed_array_elements() {
+            return match elements {
+                Elements::Int32(v) => Ok(v.to_vec()),
+                Elements::Uint8(v) => Ok(v.iter().map(|&n| i32::from(n)).collect()),
+                Elements::Int8(v) => Ok(v.iter().map(|&n| i32::from(n)).collect()),
+                Elements::Int16(v) => Ok(v.iter().map(|&n| i32::from(n)).collect()),
+                Elements::Uint16(v) => Ok(v.iter().map(|&n| i32::from(n)).collect()),
+                Elements::Uint32(v) => collect_i32(v.iter().map(|&n| f64::from(n))),
+                Elements::BigInt64(v) => collect_i32(v.iter().map(|&n| n as f64)),
+                Elements::BigUint64(v) => collect_i32(v.iter().map(|&n| n as f64)),
+                Elements::Float32(v) => collect_i32(v.iter().map(|&n| f64::from(n))),
+                Elements::Float64(v) => collect_i32(v.iter().cloned()),
+            };
+        }
+
+        self.extract_numbers(|v| match v.tag() {
+            ffi::JS_TAG_INT => v.as_int(),
+            ffi::JS_TAG_FLOAT64 => v.as_float().and_then(to_i32),
+            _ => None,
+        })
+    }
+
+    /// Extract the number as an integer type in the range with the numeric policy of the context,
+    /// the lenient policy and the non-number values fall back to the Javascript conversion.
+    pub(crate) fn extract_integer<T, W, F>(&self, min: f64, max: f64, wrap: W, from: F) -> Option<T>
+    where
+        W: Fn(&Local<Value>) -> Option<T>,
+        F: FnOnce(f64) -> T,
+    {
+        let policy = self.ctxt.numeric_policy();
+
+        let n = match self.tag() {
+            _ if policy.is_lenient() => return wrap(self),
+            ffi::JS_TAG_INT => f64::from(self.as_int()?),
+            ffi::JS_TAG_FLOAT64 => self.as_float()?,
+            _ => return wrap(self),
+        };
+
+        self.ctxt.convert(|budget| {
+            let err = if n.is_nan() || n.is_infinite() {
+                if policy.reject_non_finite {
+                    NumericError::NonFinite(n)
+                } else {
+                    return wrap(self);
+                }
+            } else {
+                let n = if n.fract() == 0.0 {
+                    n
+                } else {
+                    match policy.fraction {
+                        Fraction::Truncate => n.trunc(),
+                        Fraction::Round => n.round(),
+                        Fraction::Error => {
+                            budget.fail(NumericError::Fraction(n));
+
+                            return None;
+                        }
+                    }
+                };
+
+                if min <= n && n <= max {
+                    return Some(from(n));
+                }
+
+                match policy.overflow {
+                    Overflow::Wrap => return wrap(&self.ctxt.bind(Value::from(n))),
+                    Overflow::Saturate => return Some(from(n.max(min).min(max))),
+                    Overflow::Error => NumericError::Overflow(n, any::type_name::<T>()),
+                }
+            };
+
+            budget.fail(err);
+
+            None
+        })
+    }
+
+    fn typed_array_elements(&self) -> Option<Elements> {
+        let mut data: *mut c_void = ptr::null_mut();
+        let mut count = 0;
+        let kind = unsafe {
+            ffi::JS_GetTypedArrayElements(self.ctxt.as_ptr(), self.raw(), &mut data, &mut count)
+        };
+
+        if kind < 0 {
+            return None;
+        }
+
+        let len = count as usize;
+
+        macro_rules! elements {
+            ($variant:ident) => {
+                Elements::$variant(if len == 0 {
+                    &[]
+                } else {
+                    unsafe { slice::from_raw_parts(data as *const _, len) }
+                })
+            };
+        }
+
+        Some(match kind {
+            ffi::JS_TYPED_ARRAY_UINT8C | ffi::JS_TYPED_ARRAY_UINT8 => elements!(Uint8),
+            ffi::JS_TYPED_ARRAY_INT8 => elements!(Int8),
+            ffi::JS_TYPED_ARRAY_INT16 => elements!(Int16),
+            ffi::JS_TYPED_ARRAY_UINT16 => elements!(Uint16),
+            ffi::JS_TYPED_ARRAY_INT32 => elements!(Int32),
+            ffi::JS_TYPED_ARRAY_UINT32 => elements!(Uint32),
+            ffi::JS_TYPED_ARRAY_BIG_INT64 => elements!(BigInt64),
+            ffi::JS_TYPED_ARRAY_BIG_UINT64 => elements!(BigUint64),
+            ffi::JS_TYPED_ARRAY_FLOAT32 => elements!(Float32),
+            ffi::JS_TYPED_ARRAY_FLOAT64 => elements!(Float64),
+            _ => return None,
+        })
+    }
+
+    /// Extract the numbers from the values of a fast array, or get the elements one by one.
+    fn extract_numbers<T, F>(&self, f: F) -> Result<Vec<T>, Error>
+    where
+        F: Fn(&Value) -> Option<T>,
+    {
+        let mut values = ptr::null_mut();
+        let mut count = 0;
+
+        if unsafe { ffi::JS_GetFastArray(self.ctxt.as_ptr(), self.raw(), &mut values, &mut count) }
+            != ffi::FALSE_VALUE
+        {
+            let values = if count == 0 {
+                &[]
+            } else {
+                unsafe { slice::from_raw_parts(values as *const Value, count as usize) }
+            };
+
+            return values
+                .iter()
+                .map(|v| f(v).ok_or_else(|| conversion_error::<T>(v)))
+                .collect();
+        }
+
+        if !self.is_array()? {
+            return Err(conversion_error::<Vec<T>>(self));
+        }
+
+        let len = self
+            .get_property("length")
+            .and_then(|len| len.to_index())
+            .unwrap_or_default();
+
+        (0..len)
+            .map(|i| {
+                let item = self
+                    .get_property(i as u32)
+                    .unwrap_or_else(|| self.ctxt.undefined())
+                    .ok()?;
+
+                f(&item).ok_or_else(|| conversion_error::<T>(&item))
+            })
+            .collect()
+    }
+}
+
+fn to_i32(n: f64) -> Option<i32> {
+    if n.trunc() == n && n >= f64::from(i32::min_value()) && n <= f64::from(i32::max_value()) {
+        Some(n as i32)
+    } else {
+        None
+    }
+}
+
+fn collect_i32<I: Iterator<Item = f64>>(iter: I) -> Result<Vec<i32>, Error> {
+    iter.map(|n| {
+        to_i32(n).ok_or_else(|| {
+            ErrorKind::Conversion {
+                expected: any::type_name::<i32>(),
+                found: "number",
+            }
+            .into()
+        })
+    })
+    .collect()
+}
+
+fn conversion_error<T>(value: &Value) -> Error {
+    ErrorKind::Conversion {
+        expected: any::type_name::<T>(),
+        found: value.type_name(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn extract_numbers() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let eval = |s| ctxt.eval_script(s, "<eval>", Eval::GLOBAL).unwrap();
+
+        assert_eq!(
+            eval("new Float64Array([1, 2, 3]).subarray(1)")
+                .extract_f64_slice()
+                .unwrap(),
+            vec![2.0, 3.0]
+        );
+        assert_eq!(
+            eval("new Uint8Array([1, 255])")
+                .extract_f64_slice()
+                .unwrap(),
+            vec![1.0, 255.0]
+        );
+        assert_eq!(
+            eval("Array.from({ length: 1000 }, (_, i) => i / 2)")
+                .extract_f64_slice()
+                .unwrap()[999],
+            499.5
+        );
+        assert_eq!(
+            eval("var a = [1, 2]; a[5] = 3; a")
+                .extract_f64_slice()
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap(),
+            ErrorKind::Conversion {
+                expected: "f64",
+                found: "undefined"
+            }
+        );
+        assert!(eval("[1, '2']").extract_f64_slice().is_err());
+        assert!(eval("({ length: 1, 0: 1 })").extract_f64_slice().is_err());
+        assert_eq!(
+            eval("new Int16Array([-1, 2])").extract_i32_vec().unwrap(),
+            vec![-1, 2]
+        );
+        assert_eq!(
+            eval("new Float64Array([-1, 2])").extract_i32_vec().unwrap(),
+            vec![-1, 2]
+        );
+        assert!(eval("new Uint32Array([4294967295])")
+            .extract_i32_vec()
+            .is_err());
+        assert_eq!(
+            eval("[1, -2, 3.0]").extract_i32_vec().unwrap(),
+            vec![1, -2, 3]
+        );
+        assert!(eval("[2147483648]").extract_i32_vec().is_err());
+    }
+
+    #[test]
+    fn numeric_policy() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let i32 = |s| ctxt.eval::<_, i32>(s, Eval::GLOBAL);
+
+        // the Javascript semantics by default
+        assert_eq!(i32("-2.5").unwrap(), Some(-2));
+        assert_eq!(i32("NaN").unwrap(), Some(0));
+        assert_eq!(i32("2 ** 32 + 1").unwrap(), Some(1));
+
+        ctxt.set_numeric_policy(NumericPolicy::default().with_fraction(Fraction::Round));
+
+        assert_eq!(i32("-2.5").unwrap(), Some(-3));
+        assert_eq!(i32("2.4").unwrap(), Some(2));
+        assert_eq!(i32("2 ** 32 + 1.5").unwrap(), Some(2));
+
+        ctxt.set_numeric_policy(NumericPolicy::default().with_overflow(Overflow::Saturate));
+
+        assert_eq!(i32("2 ** 40").unwrap(), Some(i32::max_value()));
+        assert_eq!(i32("-Infinity").unwrap(), Some(0));
+        assert_eq!(ctxt.eval::<_, u64>("-1", Eval::GLOBAL).unwrap(), Some(0));
+
+        ctxt.set_numeric_policy(NumericPolicy::strict());
+
+        let err = |s| {
+            ctxt.eval::<_, i64>(s, Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<NumericError>()
+                .unwrap()
+        };
+
+        assert_eq!(err("0.5"), NumericError::Fraction(0.5));
+        assert_eq!(err("Infinity"), NumericError::NonFinite(f64::INFINITY));
+        assert_eq!(err("2 ** 64"), NumericError::Overflow(2f64.powi(64), "i64"));
+        assert_eq!(
+            ctxt.eval::<_, i64>("2 ** 53", Eval::GLOBAL).unwrap(),
+            Some(1 << 53)
+        );
+        // the strings are still converted with the Javascript semantics
+        assert_eq!(i32("'42'").unwrap(), Some(42));
+
+        // the large integers are converted to the Javascript values with the policy
+        let typeof_ = |n: i64| ctxt.bind(n.new_value(&ctxt)).ok().map(|v| v.type_name());
+
+        assert_eq!(typeof_(1).unwrap(), "number");
+        assert_eq!(
+            typeof_(MAX_SAFE_INTEGER + 1).unwrap_err().to_string(),
+            "RangeError: 9007199254740992 is not a safe integer"
+        );
+
+        ctxt.set_numeric_policy(NumericPolicy::default().with_large_integer(LargeInteger::Auto));
+
+        assert_eq!(typeof_(MAX_SAFE_INTEGER).unwrap(), "number");
+
+        // the policy of a call is restored after it returned
+        ctxt.with_numeric_policy(NumericPolicy::strict(), || {
+            assert!(i32("0.5").is_err());
+        });
+
+        assert_eq!(ctxt.numeric_policy().fraction, Fraction::Truncate);
+
+        #[cfg(feature = "bignum")]
+        assert_eq!(typeof_(MAX_SAFE_INTEGER + 1).unwrap(), "bigint");
+    }
+}
@@ -0,0 +1,150 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{ffi, ContextRef, NewValue, RuntimeRef};
+
+type PanicHook = Box<dyn Fn(&str) + Send>;
+
+#[derive(Default)]
+struct PanicHandler(RefCell<Option<PanicHook>>);
+
+impl RuntimeRef {
+    /// Set a hook which is called when a Rust callback panicked.
+    ///
+    /// The panics in the native functions, finalizers, module loaders and interrupt handlers
+    /// are caught before unwinding into the engine, the panics in the native functions
+    /// will be thrown as an `InternalError` to the scripts.
+    pub fn set_panic_hook<F: Fn(&str) + Send + 'static>(&self, hook: Option<F>) -> &Self {
+        *self.state::<PanicHandler>().0.borrow_mut() = hook.map(|f| Box::new(f) as PanicHook);
+        self
+    }
+
+    /// Handle a caught panic, returns the panic message.
+    pub(crate) fn handle_panic(&self, payload: Box<dyn Any + Send>) -> String {
+        let msg = panic_message(&*payload);
+
+        warn!("{:?} callback panicked, {}", self, msg);
+
+        if let Some(ref hook) = *self.state::<PanicHandler>().0.borrow() {
+            hook(&msg);
+        }
+
+        msg
+    }
+
+    /// Call the Rust callback from C, returns `default` if panicked.
+    pub(crate) fn catch_unwind<F: FnOnce() -> T, T>(&self, default: T, f: F) -> T {
+        panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            self.handle_panic(payload);
+
+            default
+        })
+    }
+}
+
+impl ContextRef {
+    /// Call the native function from C, throws an `InternalError` if panicked
+    /// or the nested native calls exceed the limit.
+    pub(crate) fn catch_unwind<F: FnOnce() -> ffi::JSValue>(&self, f: F) -> ffi::JSValue {
+        instrument!("qjs::native", TRACE, "native_call");
+
+        let _call = match self.enter_native() {
+            Ok(call) => call,
+            Err(err) => return err.new_value(self),
+        };
+
+        self.catch_unwind_or(ffi::EXCEPTION, f)
+    }
+
+    /// Call the Rust callback from C, throws an `InternalError` and returns `default` if panicked.
+    pub(crate) fn catch_unwind_or<F: FnOnce() -> T, T>(&self, default: T, f: F) -> T {
+        panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            let msg = self.runtime().handle_panic(payload);
+
+            self.throw_internal_error(format!("panicked, {}", msg));
+
+            default
+        })
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Box<Any>".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, ErrorKind, Eval, Runtime};
+
+    #[test]
+    fn panic_in_callback() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let panics = Arc::new(Mutex::new(vec![]));
+        let hook_panics = panics.clone();
+
+        rt.set_panic_hook(Some(move |msg: &str| {
+            hook_panics.lock().unwrap().push(msg.to_owned())
+        }));
+
+        let f = ctxt
+            .new_c_function(
+                |_ctxt, _this, _args| -> i32 { panic!("Whoops!") },
+                Some("f"),
+                0,
+            )
+            .unwrap();
+
+        ctxt.global_object().set_property("f", f).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, i32>("f()", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "panicked, Whoops!"
+        );
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "try { f() } catch (e) { e instanceof InternalError }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+        assert_eq!(*panics.lock().unwrap(), vec!["Whoops!", "Whoops!"]);
+    }
+
+    #[test]
+    fn panic_in_loader() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let module = ctxt.catch_unwind_or(std::ptr::null_mut::<()>(), || panic!("Whoops!"));
+
+        assert!(module.is_null());
+        assert_eq!(
+            ctxt.get_exception()
+                .unwrap()
+                .get_property("message")
+                .unwrap()
+                .to_string(),
+            "panicked, Whoops!"
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::os::raw::c_int;
+use std::ptr;
+use std::slice;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, CFunction, ContextRef, ErrorKind, Local, NewValue, Prop, Value};
+
+bitflags! {
+    /// The capabilities required by the host functions.
+    pub struct Capabilities: u32 {
+        /// read the file system
+        const FS_READ = 1 << 0;
+        /// write the file system
+        const FS_WRITE = 1 << 1;
+        /// access the network
+        const NET = 1 << 2;
+        /// access the environment variables
+        const ENV = 1 << 3;
+        /// access the system time and timers
+        const TIME = 1 << 4;
+        /// spawn, signal or exit the processes
+        const PROCESS = 1 << 5;
+        /// read and write the file system
+        const FS = Self::FS_READ.bits | Self::FS_WRITE.bits;
+    }
+}
+
+/// The permission check of a host function call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuditEvent<'a> {
+    /// the name of the host function
+    pub function: &'a str,
+    /// the capabilities required by the host function
+    pub required: Capabilities,
+    /// the call is allowed or denied
+    pub allowed: bool,
+}
+
+/// The host function call was denied because the missing capabilities.
+#[derive(Debug, Clone, Fail, PartialEq)]
+#[fail(display = "permission denied, `{}` requires {:?}", function, missing)]
+pub struct PermissionDenied {
+    /// the name of the host function
+    pub function: String,
+    /// the missing capabilities
+    pub missing: Capabilities,
+}
+
+type AuditFunc = Box<dyn Fn(&AuditEvent) + Send>;
+
+/// `Permissions` grants the capabilities to the host functions called in a `Context`.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Capabilities, Context, Eval, Permissions, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// ctxt.set_permissions(Permissions::new().allow(Capabilities::TIME));
+///
+/// let now = ctxt
+///     .new_c_function_with_capabilities(|_, _, _| 0.0, Some("now"), 0, Capabilities::TIME)
+///     .unwrap();
+/// let getenv = ctxt
+///     .new_c_function_with_capabilities(|_, _, _| "", Some("getenv"), 1, Capabilities::ENV)
+///     .unwrap();
+///
+/// ctxt.global_object().set_property("now", now).unwrap();
+/// ctxt.global_object().set_property("getenv", getenv).unwrap();
+///
+/// assert_eq!(ctxt.eval::<_, f64>("now()", Eval::GLOBAL).unwrap(), Some(0.0));
+/// assert!(ctxt.eval::<_, String>("getenv('HOME')", Eval::GLOBAL).is_err());
+/// ```
+pub struct Permissions {
+    granted: Capabilities,
+    audit: Option<AuditFunc>,
+}
+
+impl fmt::Debug for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Permissions")
+            .field("granted", &self.granted)
+            .field("audit", &self.audit.is_some())
+            .finish()
+    }
+}
+
+impl Permissions {
+    /// Create a `Permissions` which denies all the capabilities.
+    pub fn new() -> Self {
+        Permissions {
+            granted: Capabilities::empty(),
+            audit: None,
+        }
+    }
+
+    /// Create a `Permissions` which grants all the capabilities.
+    pub fn all() -> Self {
+        Permissions::new().allow(Capabilities::all())
+    }
+
+    /// Grant the capabilities.
+    pub fn allow(mut self, capabilities: Capabilities) -> Self {
+        self.granted |= capabilities;
+        self
+    }
+
+    /// Revoke the capabilities.
+    pub fn deny(mut self, capabilities: Capabilities) -> Self {
+        self.granted -= capabilities;
+        self
+    }
+
+    /// Set the audit callback which is called on each permission check.
+    pub fn with_audit<F: Fn(&AuditEvent) + Send + 'static>(mut self, audit: F) -> Self {
+        self.audit = Some(Box::new(audit));
+        self
+    }
+
+    /// The granted capabilities.
+    pub fn granted(&self) -> Capabilities {
+        self.granted
+    }
+
+    fn check(&self, function: &str, required: Capabilities) -> Result<(), PermissionDenied> {
+        let missing = required - self.granted;
+        let allowed = missing.is_empty();
+
+        if let Some(ref audit) = self.audit {
+            audit(&AuditEvent {
+                function,
+                required,
+                allowed,
+            });
+        }
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(PermissionDenied {
+                function: function.to_owned(),
+                missing,
+            })
+        }
+    }
+}
+
+#[derive(Default)]
+struct ContextPermissions(RefCell<Option<Permissions>>);
+
+/// The host function with the required capabilities.
+struct Guarded<T> {
+    func: CFunction<T>,
+    name: String,
+    required: Capabilities,
+}
+
+impl ContextRef {
+    /// Attach the `Permissions` to the context.
+    ///
+    /// All the capabilities are granted if no `Permissions` was attached.
+    pub fn set_permissions(&self, permissions: Permissions) -> &Self {
+        trace!("{:?} set permissions to {:?}", self, permissions);
+
+        *self.state::<ContextPermissions>().0.borrow_mut() = Some(permissions);
+        self
+    }
+
+    /// The capabilities granted to the context.
+    pub fn granted_capabilities(&self) -> Capabilities {
+        self.state::<ContextPermissions>()
+            .0
+            .borrow()
+            .as_ref()
+            .map_or_else(Capabilities::all, Permissions::granted)
+    }
+
+    /// Check if the host function could be called with the required capabilities.
+    pub fn check_capabilities(
+        &self,
+        function: &str,
+        required: Capabilities,
+    ) -> Result<(), PermissionDenied> {
+        self.state::<ContextPermissions>()
+            .0
+            .borrow()
+            .as_ref()
+            .map_or(Ok(()), |permissions| permissions.check(function, required))
+    }
+
+    /// Create a new C function which requires the capabilities.
+    ///
+    /// The capabilities are checked against the `Permissions` of the context before each call,
+    /// an `Error` will be thrown if the call was denied.
+    pub fn new_c_function_with_capabilities<T: NewValue + 'static>(
+        &self,
+        func: CFunction<T>,
+        name: Option<&str>,
+        length: usize,
+        required: Capabilities,
+    ) -> Result<Local<Value>, Error> {
+        unsafe extern "C" fn stub<T: NewValue + 'static>(
+            ctx: *mut ffi::JSContext,
+            this_val: ffi::JSValue,
+            argc: c_int,
+            argv: *mut ffi::JSValue,
+            _magic: c_int,
+            data: *mut ffi::JSValue,
+        ) -> ffi::JSValue {
+            let ctxt = ContextRef::from_ptr(ctx);
+
+            ctxt.catch_unwind(|| {
+                let data = ptr::NonNull::new_unchecked(data);
+                let guarded = ctxt.get_userdata_unchecked::<Guarded<T>>(data.cast().as_ref());
+                let guarded = guarded.as_ref();
+
+                if let Err(err) = ctxt.check_capabilities(&guarded.name, guarded.required) {
+                    debug!("{}", err);
+
+                    return ErrorKind::Error(err.to_string(), None).new_value(ctxt);
+                }
+
+                let this = Value::from(this_val);
+                let this = this.check_undefined();
+                let args = slice::from_raw_parts(argv, argc as usize);
+                let args = &*(args as *const _ as *const [Value]);
+
+                ctxt.call_host(&guarded.name, args, || {
+                    (guarded.func)(ctxt, this, args).new_value(ctxt)
+                })
+            })
+        }
+
+        let guarded = Guarded {
+            func,
+            name: name.unwrap_or("<anonymous>").to_owned(),
+            required,
+        };
+
+        let func = self.new_c_function_data(stub::<T>, length, 0, self.new_userdata(guarded))?;
+
+        if let Some(name) = name {
+            func.define_property_value("name", name, Prop::CONFIGURABLE)?;
+        }
+
+        Ok(func)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn permissions() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let getenv = ctxt
+            .new_c_function_with_capabilities(|_, _, _| "bar", Some("getenv"), 1, Capabilities::ENV)
+            .unwrap();
+
+        ctxt.global_object().set_property("getenv", getenv).unwrap();
+
+        assert_eq!(ctxt.granted_capabilities(), Capabilities::all());
+        assert_eq!(
+            ctxt.eval::<_, String>("getenv('foo')", Eval::GLOBAL)
+                .unwrap(),
+            Some("bar".to_owned())
+        );
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let audit_events = events.clone();
+
+        ctxt.set_permissions(
+            Permissions::all()
+                .deny(Capabilities::ENV)
+                .with_audit(move |event| {
+                    audit_events
+                        .lock()
+                        .unwrap()
+                        .push((event.function.to_owned(), event.allowed))
+                }),
+        );
+
+        assert_eq!(
+            ctxt.eval::<_, String>("getenv('foo')", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "permission denied, `getenv` requires ENV"
+        );
+        assert_eq!(ctxt.check_capabilities("fetch", Capabilities::NET), Ok(()));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("getenv".to_owned(), false), ("fetch".to_owned(), true)]
+        );
+    }
+}
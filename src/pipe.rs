@@ -0,0 +1,302 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use failure::{err_msg, Error};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Eval, Local, NewValue, RuntimeMismatch, Value, WriteObj};
+
+const PIPE_FACTORY: &str = r#"
+(function (call, pipe, name, length) {
+    const func = function (...args) {
+        return call(pipe, args);
+    };
+
+    Object.defineProperties(func, {
+        name: { value: name },
+        length: { value: length },
+    });
+
+    return func;
+})
+"#;
+
+/// The function of the source context called through the pipes.
+struct PipeFn {
+    /// the source context, or 0 if the source context has been freed
+    ctx: Cell<usize>,
+    func: Cell<ffi::JSValue>,
+}
+
+impl Drop for PipeFn {
+    fn drop(&mut self) {
+        self.close()
+    }
+}
+
+impl PipeFn {
+    fn source(&self) -> Option<&ContextRef> {
+        match self.ctx.get() {
+            0 => None,
+            ctx => Some(unsafe { ContextRef::from_ptr(ctx as *mut _) }),
+        }
+    }
+
+    fn close(&self) {
+        if let Some(source) = self.source() {
+            source.free_value(Value::from(self.func.get()));
+
+            self.ctx.set(0);
+        }
+    }
+
+    /// Clone the arguments to the source context, and clone the result back to the target context.
+    fn call<'a>(&self, target: &'a ContextRef, args: &Value) -> Result<Local<'a, Value>, Error> {
+        let source = self
+            .source()
+            .ok_or_else(|| err_msg("the source context of pipe has been freed"))?;
+        let args = source.structured_clone_from(target, args)?;
+        let len = args
+            .get_property("length")
+            .and_then(|len| len.to_int32())
+            .unwrap_or_default() as u32;
+        let args = (0..len)
+            .map(|idx| args.get_property(idx).unwrap_or_else(|| source.undefined()))
+            .collect::<Vec<_>>();
+        let func = Value::from(self.func.get());
+        let res = source.call(&func, None, args.iter().collect::<Vec<_>>().as_slice())?;
+
+        target.structured_clone_from(source, &res)
+    }
+}
+
+/// The functions of a context which are piped to the other contexts.
+#[derive(Default)]
+struct PipeSources(RefCell<Vec<Weak<PipeFn>>>);
+
+unsafe impl Send for PipeSources {}
+
+impl Drop for PipeSources {
+    fn drop(&mut self) {
+        for pipe in self.0.borrow().iter().flat_map(Weak::upgrade) {
+            pipe.close();
+        }
+    }
+}
+
+impl ContextRef {
+    /// Clone a value of the `source` context into this context, like the structured clone algorithm.
+    ///
+    /// The value is serialized and deserialized, so only the plain objects, arrays and primitives are supported,
+    /// the shared references are copied, and the circular references or the other objects throw a `TypeError`.
+    pub fn structured_clone_from(
+        &self,
+        source: &ContextRef,
+        value: &Value,
+    ) -> Result<Local<Value>, Error> {
+        let buf = source.write_object(value, WriteObj::empty())?;
+
+        self.bind(unsafe { ffi::JS_ReadObject(self.as_ptr(), buf.as_ptr(), buf.len(), 0) })
+            .ok()
+    }
+
+    /// Wrap a function of the `source` context into a function of this context.
+    ///
+    /// The arguments and the result are marshalled between the contexts with `structured_clone_from`,
+    /// so the contexts never share any object, and the errors thrown by the function are thrown again in this context.
+    /// Calling the pipe after the `source` context freed throws an error.
+    ///
+    /// Both contexts must belong to the same runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let plugin = Context::new(&rt);
+    /// let host = Context::new(&rt);
+    ///
+    /// let sum = plugin
+    ///     .eval_script("(function sum({ values }) { return { sum: values.reduce((a, b) => a + b, 0) }; })", "<plugin>", Eval::GLOBAL)
+    ///     .unwrap();
+    ///
+    /// host.global_object()
+    ///     .set_property("sum", host.new_pipe(&plugin, &sum).unwrap())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     host.eval::<_, i32>("sum({ values: [1, 2, 3] }).sum", Eval::GLOBAL).unwrap(),
+    ///     Some(6)
+    /// );
+    /// ```
+    pub fn new_pipe(&self, source: &ContextRef, func: &Value) -> Result<Local<Value>, Error> {
+        if self.runtime().as_ptr() != source.runtime().as_ptr() {
+            return Err(RuntimeMismatch.into());
+        }
+
+        if !source.is_function(func) {
+            return Err(err_msg("pipe requires a function"));
+        }
+
+        let func = source.clone_value(func);
+        let name = func
+            .get_property("name")
+            .map_or_else(String::new, |name| name.to_string());
+        let length = func
+            .get_property("length")
+            .and_then(|len| len.to_int32())
+            .unwrap_or_default();
+        let pipe = Rc::new(PipeFn {
+            ctx: Cell::new(source.as_ptr() as usize),
+            func: Cell::new(func.into_inner_untracked().raw()),
+        });
+
+        source
+            .state::<PipeSources>()
+            .0
+            .borrow_mut()
+            .push(Rc::downgrade(&pipe));
+
+        let factory = self.eval_script(PIPE_FACTORY, "<pipe>", Eval::GLOBAL | Eval::STRICT)?;
+
+        self.call(
+            &factory,
+            None,
+            (
+                self.new_c_function(call_pipe, Some("call"), 2)?,
+                self.new_userdata(pipe),
+                name,
+                length,
+            ),
+        )
+    }
+}
+
+fn call_pipe(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let pipe = ctxt.clone_value(&args[0]);
+    let res = pipe
+        .downcast_ref::<Rc<PipeFn>>()
+        .and_then(|pipe| pipe.call(ctxt, &args[1]));
+
+    res.new_value(ctxt).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn pipe() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let plugin = Context::new(&rt);
+        let host = Context::new(&rt);
+
+        let func = plugin
+            .eval_script(
+                r#"
+var calls = 0;
+
+(function transform(input, scale) {
+    calls++;
+
+    if (scale < 0) {
+        throw new RangeError('negative scale');
+    }
+
+    input.values = input.values.map(v => v * scale);
+    input.leaked = typeof host === 'undefined';
+
+    return input;
+})
+"#,
+                "<plugin>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let pipe = host.new_pipe(&plugin, &func).unwrap();
+
+        host.global_object()
+            .set_property("transform", pipe)
+            .unwrap();
+
+        assert_eq!(
+            host.eval::<_, String>(
+                r#"
+var host = {};
+var input = { values: [1, 2, 3], nested: { big: 1n << 64n } };
+var output = transform(input, 2);
+
+[transform.name, transform.length, output.values, input.values, output.leaked, output.nested.big, output !== input].join('|')
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap()
+            .unwrap(),
+            "transform|2|2,4,6|1,2,3|true|18446744073709551616|true"
+        );
+
+        // the errors are thrown again in the target context
+        assert_eq!(
+            host.eval::<_, String>(
+                "try { transform({ values: [] }, -1) } catch (err) { `${err instanceof RangeError}: ${err.message}` }",
+                Eval::GLOBAL
+            )
+            .unwrap()
+            .unwrap(),
+            "true: negative scale"
+        );
+
+        // the functions and circular references can't be cloned
+        assert_eq!(
+            host.eval::<_, String>(
+                r#"
+var cyclic = { values: [] };
+cyclic.self = cyclic;
+
+[{ values: [], f() {} }, cyclic].map(input => {
+    try { transform(input, 1) } catch (err) { return err.message }
+}).join()
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap()
+            .unwrap(),
+            "unsupported object class,circular reference"
+        );
+
+        assert_eq!(
+            plugin.eval::<_, i32>("calls", Eval::GLOBAL).unwrap(),
+            Some(2)
+        );
+
+        // the pipe is closed when the source context freed
+        drop(func);
+        drop(plugin);
+
+        match host
+            .eval::<_, ()>("transform({ values: [] }, 1)", Eval::GLOBAL)
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap()
+        {
+            ErrorKind::Error(msg, _) => {
+                assert_eq!(msg, "the source context of pipe has been freed")
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        // the pipes are restricted to the contexts of the same runtime
+        let rt2 = Runtime::new();
+        let other = Context::new(&rt2);
+        let func = other
+            .eval_script("(() => 1)", "<other>", Eval::GLOBAL)
+            .unwrap();
+
+        assert!(host.new_pipe(&other, &func).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::slice;
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, ContextRef, Local, Value};
+use crate::{ffi, ContextRef, ExceptionOrigin, Local, Value};
 
 bitflags! {
     pub struct WriteObj: u32 {
@@ -61,7 +61,21 @@ impl ContextRef {
 
     /// Evaluate a script or module source in bytecode.
     pub fn eval_function<T: Into<ffi::JSValue>>(&self, func: T) -> Result<Local<Value>, Error> {
-        self.bind(unsafe { ffi::JS_EvalFunction(self.as_ptr(), func.into()) })
-            .ok()
+        instrument!("qjs::eval", DEBUG, "eval_function");
+
+        let _evaluation = self.start_evaluation();
+        let func = func.into();
+        let is_module = Value::from(func).is_module();
+        let res = self
+            .bind(unsafe { ffi::JS_EvalFunction(self.as_ptr(), func) })
+            .ok();
+
+        if let Err(ref err) = res {
+            if is_module {
+                self.handle_exception(ExceptionOrigin::Module, err);
+            }
+        }
+
+        res
     }
 }
@@ -0,0 +1,245 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use failure::Error;
+use foreign_types::{ForeignType, ForeignTypeRef};
+
+use crate::{ffi, Context, ContextRef, Eval, ReadObj, RuntimeRef};
+
+struct Prelude {
+    name: String,
+    bytecode: Arc<[u8]>,
+}
+
+#[derive(Default)]
+struct Preludes(RefCell<Vec<Prelude>>);
+
+impl RuntimeRef {
+    /// Register a prelude script, which is compiled to bytecode once
+    /// and evaluated in each new `Context` of the runtime before the other scripts.
+    ///
+    /// The preludes are evaluated in the order of registration,
+    /// a prelude with the same name replaces the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    ///
+    /// rt.register_prelude("sdk", "function double(x) { return x * 2 }").unwrap();
+    ///
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("double(21)", Eval::GLOBAL).unwrap(), Some(42));
+    /// ```
+    pub fn register_prelude(&self, name: &str, source: &str) -> Result<(), Error> {
+        // compile the prelude in a context without the preludes
+        let ctxt = unsafe { Context::from_ptr(ffi::JS_NewContext(self.as_ptr())) };
+        let bytecode = ctxt
+            .eval_script(source, name, Eval::GLOBAL | Eval::COMPILE_ONLY)?
+            .write_bytecode()?;
+
+        self.register_prelude_bytecode(name, bytecode);
+
+        Ok(())
+    }
+
+    /// Register a prelude script which was compiled to bytecode.
+    pub fn register_prelude_bytecode<T: Into<Vec<u8>>>(&self, name: &str, bytecode: T) {
+        let prelude = Prelude {
+            name: name.to_owned(),
+            bytecode: bytecode.into().into(),
+        };
+        let mut preludes = self.state::<Preludes>().0.borrow_mut();
+
+        trace!(
+            "{:?} register prelude `{}` with {} bytes bytecode",
+            self,
+            name,
+            prelude.bytecode.len()
+        );
+
+        match preludes.iter_mut().find(|p| p.name == name) {
+            Some(p) => *p = prelude,
+            None => preludes.push(prelude),
+        }
+    }
+
+    /// Returns the names of the registered preludes.
+    pub fn preludes(&self) -> Vec<String> {
+        self.state::<Preludes>()
+            .0
+            .borrow()
+            .iter()
+            .map(|p| p.name.clone())
+            .collect()
+    }
+
+    /// Unregister all the preludes, the existing contexts are not affected.
+    pub fn clear_preludes(&self) {
+        self.state::<Preludes>().0.borrow_mut().clear()
+    }
+}
+
+impl ContextRef {
+    /// Evaluate the registered preludes of the runtime in the context.
+    ///
+    /// The preludes are evaluated automatically by `Context::new`,
+    /// the contexts created by `Context::builder` should call it after adding the intrinsic objects.
+    pub fn load_preludes(&self) -> Result<(), Error> {
+        // the preludes may register or clear the preludes when evaluated
+        let preludes = self
+            .runtime()
+            .state::<Preludes>()
+            .0
+            .borrow()
+            .iter()
+            .map(|p| Prelude {
+                name: p.name.clone(),
+                bytecode: p.bytecode.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        for prelude in preludes {
+            trace!("{:?} load prelude `{}`", self, prelude.name);
+
+            let func = self.bind(unsafe {
+                ffi::JS_ReadObject(
+                    self.as_ptr(),
+                    prelude.bytecode.as_ptr(),
+                    prelude.bytecode.len(),
+                    ReadObj::BYTECODE.bits() as i32,
+                )
+            });
+
+            self.eval_function(func.ok()?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Runtime, Value};
+
+    use super::*;
+
+    #[test]
+    fn prelude() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+
+        rt.register_prelude(
+            "sdk",
+            "var counter = 0; function next() { return ++counter } const VERSION = '1.0';",
+        )
+        .unwrap();
+        rt.register_prelude("utils", "function version() { return 'v' + VERSION }")
+            .unwrap();
+
+        assert!(rt.register_prelude("broken", "function (").is_err());
+        assert_eq!(rt.preludes(), vec!["sdk", "utils"]);
+
+        let first = Context::new(&rt);
+        let second = Context::new(&rt);
+
+        assert_eq!(
+            first.eval::<_, i32>("next()", Eval::GLOBAL).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            first.eval::<_, i32>("next()", Eval::GLOBAL).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            second.eval::<_, i32>("next()", Eval::GLOBAL).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            second.eval::<_, String>("version()", Eval::GLOBAL).unwrap(),
+            Some("v1.0".to_owned())
+        );
+
+        rt.register_prelude("utils", "function version() { return VERSION }")
+            .unwrap();
+
+        let third = Context::new(&rt);
+
+        assert_eq!(
+            third.eval::<_, String>("version()", Eval::GLOBAL).unwrap(),
+            Some("1.0".to_owned())
+        );
+
+        let raw = Context::builder(&rt)
+            .with_base_objects()
+            .with_eval()
+            .build();
+
+        assert!(raw.eval::<_, ()>("next", Eval::GLOBAL).is_err());
+        raw.load_preludes().unwrap();
+        assert_eq!(raw.eval::<_, i32>("next()", Eval::GLOBAL).unwrap(), Some(1));
+
+        rt.clear_preludes();
+
+        assert!(rt.preludes().is_empty());
+        assert!(Context::new(&rt)
+            .eval::<_, i32>("next()", Eval::GLOBAL)
+            .is_err());
+        assert_eq!(
+            third.eval::<_, i32>("next()", Eval::GLOBAL).unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+
+        rt.register_prelude("broken", "throw new Error('boom')")
+            .unwrap();
+
+        assert!(Context::try_new(&rt)
+            .unwrap_err()
+            .to_string()
+            .contains("boom"));
+        assert!(Context::new(&rt)
+            .eval::<_, i32>("1 + 2", Eval::GLOBAL)
+            .is_ok());
+
+        rt.clear_preludes();
+
+        assert!(Context::try_new(&rt).is_ok());
+    }
+
+    fn register(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Result<bool, Error> {
+        ctxt.runtime()
+            .register_prelude("late", "var late = true")
+            .map(|_| true)
+    }
+
+    #[test]
+    fn register_when_loading() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::builder(&rt).with_base_objects().build();
+
+        ctxt.global_object()
+            .set_property(
+                "register",
+                ctxt.new_c_function(register, Some("register"), 0).unwrap(),
+            )
+            .unwrap();
+        rt.register_prelude("sdk", "register()").unwrap();
+
+        ctxt.load_preludes().unwrap();
+
+        assert_eq!(rt.preludes(), vec!["sdk", "late"]);
+    }
+}
@@ -0,0 +1,727 @@
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use failure::Error;
+
+use crate::{
+    background::Operation, ffi, Capabilities, ContextRef, ErrorKind, Eval, Local, NewValue, Value,
+    NULL, UNDEFINED,
+};
+
+/// The name of the process module.
+pub const PROCESS_MODULE: &str = "host:process";
+
+/// The factory of the `spawn` function, which wraps the native function with a promise.
+const SPAWN_FACTORY: &str = r#"
+(function (native) {
+    return function spawn(cmd, args = [], opts = {}) {
+        return new Promise((resolve, reject) => {
+            native(String(cmd), Array.from(args, String), opts, (err, res) => {
+                if (err === null) {
+                    resolve(res);
+                } else {
+                    reject(err instanceof Error ? err : new Error(err));
+                }
+            });
+        });
+    };
+})
+"#;
+
+/// The state of the `host:process` module in a context.
+#[derive(Default)]
+struct ProcessState {
+    /// the names of the environment variables visible to the scripts
+    env: RefCell<Vec<String>>,
+    /// the exit code set by the scripts
+    exit_code: Cell<Option<i32>>,
+}
+
+/// The builder of the `host:process` module, which exposes the process environment to the scripts.
+///
+/// The module exports `argv`, `getenv(name)`, `env()`, `cwd()`, `exitCode()`, `setExitCode(code)`
+/// and `spawn(cmd, args, opts)`, and a default export with all of them. Only the environment variables
+/// in the allow-list are visible, the functions require the `ENV`, `FS_READ` and `PROCESS` capabilities.
+///
+/// `spawn` returns a promise of `{ status, success, stdout, stderr }`, the child process is executed
+/// as a background operation of the runtime, the promise is settled when the runtime polls the operations,
+/// e.g. in the `EventLoop`. The child is killed when the runtime is interrupted, or the time limit of the runtime
+/// elapsed since it was spawned. The options are `cwd`, `env`, `stdin`, and the streaming callbacks
+/// `onStdout(line)` and `onStderr(line)` called for each line of the output.
+///
+/// The child only inherits the environment variables in the allow-list, and the variables of the `env` option.
+/// The `cwd` option is resolved by the `Vfs` of the `host:fs` module, so it is denied without the module.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, HostProcess, Runtime};
+///
+/// std::env::set_var("APP_MODE", "release");
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// HostProcess::new()
+///     .with_args(vec!["build", "--verbose"])
+///     .with_env(vec!["APP_MODE"])
+///     .init(&ctxt)
+///     .unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     r#"
+/// import { argv, getenv, setExitCode } from 'host:process';
+///
+/// if (argv.includes('--verbose') && getenv('APP_MODE') === 'release' && getenv('HOME') === undefined) {
+///     setExitCode(3);
+/// }
+/// "#,
+///     Eval::MODULE,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(ctxt.exit_code(), Some(3));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HostProcess {
+    args: Vec<String>,
+    env: Vec<String>,
+}
+
+impl HostProcess {
+    /// Create a builder without arguments and environment variables.
+    pub fn new() -> Self {
+        HostProcess::default()
+    }
+
+    /// Set the arguments exported as `argv`.
+    pub fn with_args<I: IntoIterator<Item = S>, S: Into<String>>(mut self, args: I) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allow the environment variables to be read by the scripts.
+    pub fn with_env<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.env.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Register the `host:process` module in the context.
+    pub fn init(self, ctxt: &ContextRef) -> Result<(), Error> {
+        let state = ctxt.state::<ProcessState>();
+
+        *state.env.borrow_mut() = self.env;
+        state.exit_code.set(None);
+
+        let argv = ctxt.bind(ctxt.new_array());
+
+        for (idx, arg) in self.args.into_iter().enumerate() {
+            argv.set_property(idx as u32, arg)?;
+        }
+
+        let exports = vec![
+            ("argv", argv),
+            (
+                "getenv",
+                ctxt.new_c_function_with_capabilities(
+                    getenv,
+                    Some("getenv"),
+                    1,
+                    Capabilities::ENV,
+                )?,
+            ),
+            (
+                "env",
+                ctxt.new_c_function_with_capabilities(env_vars, Some("env"), 0, Capabilities::ENV)?,
+            ),
+            (
+                "cwd",
+                ctxt.new_c_function_with_capabilities(cwd, Some("cwd"), 0, Capabilities::FS_READ)?,
+            ),
+            (
+                "exitCode",
+                ctxt.new_c_function(exit_code, Some("exitCode"), 0)?,
+            ),
+            (
+                "setExitCode",
+                ctxt.new_c_function_with_capabilities(
+                    set_exit_code,
+                    Some("setExitCode"),
+                    1,
+                    Capabilities::PROCESS,
+                )?,
+            ),
+            ("spawn", new_spawn(ctxt)?),
+        ];
+
+        let default = ctxt.bind(ctxt.new_object());
+
+        for (name, value) in &exports {
+            default.set_property(*name, value)?;
+        }
+
+        ctxt.new_native_module(
+            PROCESS_MODULE,
+            exports.into_iter().chain(Some(("default", default))),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl ContextRef {
+    /// Returns the exit code set by the scripts with `host:process`.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.state::<ProcessState>().exit_code.get()
+    }
+
+    fn is_env_allowed(&self, name: &str) -> bool {
+        self.state::<ProcessState>()
+            .env
+            .borrow()
+            .iter()
+            .any(|allowed| allowed == name)
+    }
+}
+
+fn getenv(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let name = ctxt.clone_value(&args[0]).to_string();
+
+    if ctxt.is_env_allowed(&name) {
+        if let Ok(value) = env::var(&name) {
+            return ctxt.new_value(value);
+        }
+    }
+
+    UNDEFINED
+}
+
+fn env_vars(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Value {
+    let vars = ctxt.bind(ctxt.new_object());
+    let names = ctxt.state::<ProcessState>().env.borrow().clone();
+
+    for name in names {
+        if let Ok(value) = env::var(&name) {
+            if let Err(err) = vars.set_property(name.as_str(), value) {
+                return ctxt.throw_rust_error(&err).into_inner_untracked();
+            }
+        }
+    }
+
+    vars.into_inner_untracked()
+}
+
+fn cwd(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Value {
+    match env::current_dir() {
+        Ok(dir) => ctxt.new_value(dir.to_string_lossy().into_owned()),
+        Err(err) => ErrorKind::Error(err.to_string(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+fn exit_code(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Value {
+    ctxt.exit_code()
+        .map_or(UNDEFINED, |code| ctxt.new_value(code))
+}
+
+fn set_exit_code(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    match ctxt.clone_value(&args[0]).as_int() {
+        Some(code) => {
+            ctxt.state::<ProcessState>().exit_code.set(Some(code));
+
+            UNDEFINED
+        }
+        None => ErrorKind::TypeError("exit code must be an integer".into(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+fn new_spawn(ctxt: &ContextRef) -> Result<Local<Value>, Error> {
+    let native =
+        ctxt.new_c_function_with_capabilities(spawn, Some("spawn"), 4, Capabilities::PROCESS)?;
+    let factory = ctxt.eval_script(SPAWN_FACTORY, "<spawn>", Eval::GLOBAL | Eval::STRICT)?;
+
+    ctxt.call(&factory, None, [&native])
+}
+
+/// Spawn the child process on a background thread,
+/// the arguments are `cmd`, `args`, `opts` and `callback(err, res)`.
+fn spawn(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let opts = ctxt.clone_value(&args[2]);
+    let opt = |name: &str| opts.get_property(name).filter(|v| !v.is_undefined());
+    let on_stdout = opt("onStdout").filter(|f| f.is_function());
+    let on_stderr = opt("onStderr").filter(|f| f.is_function());
+    let undefined = ctxt.undefined();
+
+    let res = ChildProcess::new(
+        ctxt,
+        &ctxt.clone_value(&args[0]),
+        &ctxt.clone_value(&args[1]),
+        &opts,
+    )
+    .and_then(|child| {
+        ctxt.spawn_operation(
+            &[
+                &args[3],
+                on_stdout.as_ref().unwrap_or(&undefined),
+                on_stderr.as_ref().unwrap_or(&undefined),
+            ],
+            move |op| child.run(op),
+        )
+    });
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+/// The interval to check the cancellation of the child process.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The child process to spawn, with the options extracted from the scripts.
+struct ChildProcess {
+    program: String,
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    stdin: Option<String>,
+    on_stdout: bool,
+    on_stderr: bool,
+}
+
+/// The output of the child process.
+enum Output {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+/// The result of the child process.
+struct ChildOutput {
+    status: Option<i32>,
+    success: bool,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl NewValue for ChildOutput {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        let res = (|| -> Result<Local<Value>, Error> {
+            let res = ctxt.bind(ctxt.new_object());
+
+            match self.status {
+                Some(code) => res.set_property("status", code)?,
+                None => res.set_property("status", NULL)?,
+            };
+            res.set_property("success", self.success)?;
+            res.set_property("stdout", String::from_utf8_lossy(&self.stdout).into_owned())?;
+            res.set_property("stderr", String::from_utf8_lossy(&self.stderr).into_owned())?;
+
+            Ok(res)
+        })();
+
+        res.new_value(ctxt)
+    }
+}
+
+impl ChildProcess {
+    fn new(
+        ctxt: &ContextRef,
+        cmd: &Local<Value>,
+        args: &Local<Value>,
+        opts: &Local<Value>,
+    ) -> Result<Self, Error> {
+        let len = args
+            .get_property("length")
+            .and_then(|len| len.to_int32())
+            .unwrap_or_default() as u32;
+        let args = (0..len)
+            .map(|idx| {
+                args.get_property(idx)
+                    .map_or_else(String::new, |arg| arg.to_string())
+            })
+            .collect();
+        let opt = |name: &str| opts.get_property(name).filter(|v| !v.is_undefined());
+
+        // the working directory is scoped by the file system of the `host:fs` module
+        let cwd = match opt("cwd") {
+            Some(cwd) => {
+                let cwd = cwd.to_string();
+
+                Some(
+                    ctxt.host_path(&cwd)
+                        .map_err(|err| format_err!("invalid `cwd` {}, {}", cwd, err))?,
+                )
+            }
+            None => None,
+        };
+
+        // the child only inherits the environment variables in the allow-list
+        let mut env = ctxt
+            .state::<ProcessState>()
+            .env
+            .borrow()
+            .iter()
+            .flat_map(|name| env::var(name).ok().map(|value| (name.clone(), value)))
+            .collect::<Vec<_>>();
+
+        if let Some(vars) = opt("env") {
+            for key in vars.keys()?.unwrap_or_default() {
+                if let Some(value) = vars.get_property(&key) {
+                    env.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+
+        Ok(ChildProcess {
+            program: cmd.to_string(),
+            args,
+            cwd,
+            env,
+            stdin: opt("stdin").map(|stdin| stdin.to_string()),
+            on_stdout: opt("onStdout").map_or(false, |f| f.is_function()),
+            on_stderr: opt("onStderr").map_or(false, |f| f.is_function()),
+        })
+    }
+
+    /// Run the child process until it exits, or kill it when the operation cancelled.
+    fn run(self, op: &Operation) -> Result<ChildOutput, Error> {
+        let ChildProcess {
+            program,
+            args,
+            cwd,
+            env,
+            stdin,
+            on_stdout,
+            on_stderr,
+        } = self;
+        let mut command = Command::new(&program);
+
+        command.args(&args).env_clear().envs(env);
+
+        if let Some(ref cwd) = cwd {
+            command.current_dir(cwd);
+        }
+
+        trace!("spawn `{}` with {} args", program, args.len());
+
+        let mut child = command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format_err!("spawn `{}` failed, {}", program, err))?;
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            thread::spawn(move || pipe.write_all(input.as_bytes()));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+
+        fn read_lines<R, F>(pipe: Option<R>, sender: &mpsc::Sender<Output>, output: F)
+        where
+            R: Read + Send + 'static,
+            F: Fn(Vec<u8>) -> Output + Send + 'static,
+        {
+            if let Some(pipe) = pipe {
+                let sender = sender.clone();
+
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(pipe);
+
+                    loop {
+                        let mut line = vec![];
+
+                        match reader.read_until(b'\n', &mut line) {
+                            Ok(n) if n > 0 => {
+                                if sender.send(output(line)).is_err() {
+                                    break;
+                                }
+                            }
+                            _ => break,
+                        }
+                    }
+                });
+            }
+        }
+
+        read_lines(child.stdout.take(), &sender, Output::Stdout);
+        read_lines(child.stderr.take(), &sender, Output::Stderr);
+
+        drop(sender);
+
+        let killed = |mut child: Child| {
+            let _ = child.kill();
+            let _ = child.wait();
+
+            format_err!(
+                "`{}` was killed, the operation was cancelled or exceeded the time limit",
+                program
+            )
+        };
+
+        let mut stdout = vec![];
+        let mut stderr = vec![];
+
+        // the lines are streamed to the callbacks until both pipes closed
+        loop {
+            let (buf, streaming, idx, line) = match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(Output::Stdout(line)) => (&mut stdout, on_stdout, 1, line),
+                Ok(Output::Stderr(line)) => (&mut stderr, on_stderr, 2, line),
+                Err(mpsc::RecvTimeoutError::Timeout) if op.is_cancelled() => {
+                    return Err(killed(child))
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            if streaming {
+                let text = String::from_utf8_lossy(&line).into_owned();
+
+                op.notify(move |ctxt, values| ctxt.call(&values[idx], None, text).map(|_| ()));
+            }
+
+            buf.extend(line);
+        }
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if op.is_cancelled() {
+                return Err(killed(child));
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        Ok(ChildOutput {
+            status: status.code(),
+            success: status.success(),
+            stdout,
+            stderr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, EventLoop, HostFs, OsFs, Permissions, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn host_process() {
+        let _ = pretty_env_logger::try_init();
+
+        env::set_var("QJS_PROCESS_TEST", "yes");
+        env::set_var("QJS_PROCESS_SECRET", "no");
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        HostProcess::new()
+            .with_args(vec!["a", "b"])
+            .with_env(vec!["QJS_PROCESS_TEST", "QJS_PROCESS_MISSING"])
+            .init(&ctxt)
+            .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+import process, { argv, getenv, env, cwd, exitCode } from 'host:process';
+
+globalThis.process = process;
+globalThis.result = [
+    argv.join(),
+    getenv('QJS_PROCESS_TEST'),
+    getenv('QJS_PROCESS_SECRET'),
+    getenv('QJS_PROCESS_MISSING'),
+    JSON.stringify(env()),
+    cwd(),
+    exitCode(),
+].join('|');
+"#,
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("result", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            format!(
+                "a,b|yes|||{{\"QJS_PROCESS_TEST\":\"yes\"}}|{}|",
+                env::current_dir().unwrap().display()
+            )
+        );
+        assert_eq!(ctxt.exit_code(), None);
+
+        ctxt.eval::<_, ()>("process.setExitCode(2)", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(ctxt.exit_code(), Some(2));
+        assert_eq!(
+            ctxt.eval::<_, i32>("process.exitCode()", Eval::GLOBAL)
+                .unwrap(),
+            Some(2)
+        );
+        assert!(ctxt
+            .eval::<_, ()>("process.setExitCode('x')", Eval::GLOBAL)
+            .is_err());
+
+        ctxt.eval::<_, ()>(
+            r#"
+process.spawn('sh', ['-c', 'echo "$GREETING"; read name; echo "hello $name"; echo oops >&2; exit 3'], {
+    env: { GREETING: 'hi' },
+    stdin: 'world\n',
+    onStdout: (line) => (globalThis.lines = (globalThis.lines || []).concat(line.trim())),
+}).then((res) => { globalThis.spawned = [res.status, res.success, res.stdout, res.stderr].join('|'); });
+
+process.spawn('sh', ['-c', 'echo "[$QJS_PROCESS_TEST] [$QJS_PROCESS_SECRET]"'])
+    .then((res) => { globalThis.inherited = res.stdout.trim(); });
+
+process.spawn('qjs-missing-command').catch((err) => { globalThis.missing = err.message; });
+
+// the working directory requires the `host:fs` module
+process.spawn('pwd', [], { cwd: '.' }).catch((err) => { globalThis.no_fs = err.message; });
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        // the child processes are spawned in the background
+        assert!(rt.pending_operations() > 0);
+
+        EventLoop::new(&rt).run().unwrap();
+
+        if cfg!(unix) {
+            assert_eq!(
+                ctxt.eval::<_, String>("spawned", Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                "3|false|hi\nhello world\n|oops\n"
+            );
+            assert_eq!(
+                ctxt.eval::<_, String>("lines.join()", Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                "hi,hello world"
+            );
+
+            // only the environment variables in the allow-list are inherited
+            assert_eq!(
+                ctxt.eval::<_, String>("inherited", Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                "[yes] []"
+            );
+        }
+        assert!(ctxt
+            .eval::<_, String>("missing", Eval::GLOBAL)
+            .unwrap()
+            .unwrap()
+            .starts_with("spawn `qjs-missing-command` failed"));
+        assert!(ctxt
+            .eval::<_, String>("no_fs", Eval::GLOBAL)
+            .unwrap()
+            .unwrap()
+            .starts_with("invalid `cwd` ."));
+
+        // the working directory is scoped by the root of the file system
+        let dir = tempfile::tempdir().unwrap();
+
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+
+        HostFs::new(OsFs::new(dir.path()).unwrap())
+            .init(&ctxt)
+            .unwrap();
+
+        if cfg!(unix) {
+            ctxt.eval::<_, ()>(
+                r#"
+process.spawn('pwd', [], { cwd: 'sub' }).then((res) => { globalThis.pwd = res.stdout.trim(); });
+process.spawn('pwd', [], { cwd: '..' }).catch((err) => { globalThis.outside = err.message; });
+"#,
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+            EventLoop::new(&rt).run().unwrap();
+
+            assert_eq!(
+                ctxt.eval::<_, String>("pwd", Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                dir.path()
+                    .canonicalize()
+                    .unwrap()
+                    .join("sub")
+                    .display()
+                    .to_string()
+            );
+            assert!(ctxt
+                .eval::<_, String>("outside", Eval::GLOBAL)
+                .unwrap()
+                .unwrap()
+                .contains("outside of the root directory"));
+
+            // the child is killed when the time limit of the runtime elapsed
+            rt.set_time_limit(Some(Duration::from_millis(100)));
+
+            ctxt.eval::<_, ()>(
+                "process.spawn('sleep', ['1000']).catch((err) => { globalThis.killed = err.message; })",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+            EventLoop::new(&rt).run().unwrap();
+
+            assert_eq!(
+                ctxt.eval::<_, String>("killed", Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                "`sleep` was killed, the operation was cancelled or exceeded the time limit"
+            );
+
+            rt.set_time_limit(None);
+        }
+
+        // the functions are guarded by the capabilities
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::ENV));
+
+        assert!(ctxt
+            .eval::<_, String>("process.getenv('QJS_PROCESS_TEST')", Eval::GLOBAL)
+            .is_err());
+        assert!(ctxt
+            .eval::<_, String>("process.cwd()", Eval::GLOBAL)
+            .is_ok());
+
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::PROCESS));
+        ctxt.eval::<_, ()>(
+            "process.spawn('true').catch((err) => { globalThis.denied = err.message; })",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        while rt.execute_pending_job().unwrap().is_some() {}
+
+        assert_eq!(
+            ctxt.eval::<_, String>("denied", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "permission denied, `spawn` requires PROCESS"
+        );
+    }
+}
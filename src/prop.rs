@@ -95,6 +95,12 @@ impl GetProperty for Local<'_, ffi::JSAtom> {
     }
 }
 
+impl GetProperty for &Local<'_, ffi::JSAtom> {
+    fn get_property<'a>(&self, ctxt: &'a ContextRef, this: &Value) -> Option<Local<'a, Value>> {
+        (*self).get_property(ctxt, this)
+    }
+}
+
 /// Set a property value on an object.
 pub trait SetProperty {
     /// Set a property value on an object.
@@ -440,6 +446,53 @@ impl<'a> Local<'a, Value> {
         self.ctxt.set_property(self, prop, val)
     }
 
+    /// Get the property values on an object in a batch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let obj = ctxt.eval_script("({ a: 1, b: 'two' })", "<eval>", Eval::GLOBAL).unwrap();
+    /// let values = obj.get_many(&["a", "b", "c"]).unwrap();
+    ///
+    /// assert_eq!(values[0].as_ref().unwrap().to_int32(), Some(1));
+    /// assert_eq!(values[1].as_ref().unwrap().to_string(), "two");
+    /// assert!(values[2].is_none());
+    /// ```
+    pub fn get_many<K: NewAtom + Copy>(
+        &self,
+        keys: &[K],
+    ) -> Result<Vec<Option<Local<'a, Value>>>, Error> {
+        self.ctxt.get_many(self, keys)
+    }
+
+    /// Set the property values on an object in a batch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let obj = ctxt.bind(ctxt.new_object());
+    ///
+    /// obj.set_many(&[("a", 1), ("b", 2)]).unwrap();
+    ///
+    /// assert_eq!(obj.get_property("b").unwrap(), 2);
+    /// ```
+    pub fn set_many<K: NewAtom + Copy, V: NewValue + Clone>(
+        &self,
+        props: &[(K, V)],
+    ) -> Result<(), Error> {
+        self.ctxt.set_many(self, props)
+    }
+
     /// Check if a property on an object.
     pub fn has_property<T: HasProperty>(&self, prop: T) -> Result<bool, Error> {
         self.ctxt.has_property(self, prop)
@@ -498,6 +551,41 @@ impl<'a> Local<'a, Value> {
     pub fn prevent_extensions(&self) -> Result<bool, Error> {
         self.ctxt.prevent_extensions(self)
     }
+
+    /// Returns an array of all own property keys of an object, including the non-enumerable properties and symbols,
+    /// like `Reflect.ownKeys`.
+    pub fn own_keys(&self) -> Result<Option<Vec<Atom>>, Error> {
+        self.ctxt.own_keys(self)
+    }
+
+    /// Returns the prototype of an object, or `null` if it has no prototype.
+    pub fn get_prototype(&self) -> Local<Value> {
+        self.ctxt.get_prototype(self)
+    }
+
+    /// Sets the prototype of an object to another object or `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let base = ctxt.eval_script("({ greet() { return 'hi' } })", "<eval>", Eval::GLOBAL).unwrap();
+    /// let obj = ctxt.bind(ctxt.new_object());
+    ///
+    /// assert!(!obj.has_property("greet").unwrap());
+    ///
+    /// obj.set_prototype(&base).unwrap();
+    ///
+    /// assert!(obj.get_prototype().strict_eq(&base).unwrap());
+    /// assert_eq!(obj.invoke("greet", ()).unwrap().to_string(), "hi");
+    /// ```
+    pub fn set_prototype(&self, proto: &Value) -> Result<bool, Error> {
+        self.ctxt.set_prototype(self, proto)
+    }
 }
 
 impl ContextRef {
@@ -508,6 +596,8 @@ impl ContextRef {
         value: &Value,
         flags: Names,
     ) -> Result<Option<Vec<Atom>>, Error> {
+        self.check_owner(value)?;
+
         let mut ptab = ptr::null_mut();
         let mut count = 0;
 
@@ -540,6 +630,8 @@ impl ContextRef {
         value: &Value,
         prop: T,
     ) -> Result<Option<Descriptor>, Error> {
+        self.check_owner(value)?;
+
         let atom = prop.new_atom(self);
         let mut desc = MaybeUninit::<ffi::JSPropertyDescriptor>::uninit();
         let res =
@@ -567,6 +659,12 @@ impl ContextRef {
 
     /// Get a property value on an object.
     pub fn get_property<T: GetProperty>(&self, this: &Value, prop: T) -> Option<Local<Value>> {
+        if let Err(err) = self.check_owner(this) {
+            warn!("{:?} get property, {}", self, err);
+
+            return None;
+        }
+
         prop.get_property(self, this)
     }
 
@@ -577,11 +675,79 @@ impl ContextRef {
         prop: T,
         val: V,
     ) -> Result<bool, Error> {
+        self.check_owner(this)?;
+        val.check_value(self)?;
+
         prop.set_property(self, this, val)
     }
 
+    /// Get the property values on an object in a batch, returns `None` for the undefined values.
+    ///
+    /// The keys are converted to atoms without going through `CString`,
+    /// returns the first exception thrown by a getter.
+    pub fn get_many<K: NewAtom + Copy>(
+        &self,
+        this: &Value,
+        keys: &[K],
+    ) -> Result<Vec<Option<Local<Value>>>, Error> {
+        self.check_owner(this)?;
+
+        let ctx = self.as_ptr();
+
+        keys.iter()
+            .map(|key| {
+                let atom = key.new_atom(self);
+                let value = unsafe {
+                    ffi::JS_GetPropertyInternal(ctx, this.raw(), atom, this.raw(), ffi::FALSE_VALUE)
+                };
+
+                self.free_atom(atom);
+
+                self.bind(value).ok().map(Local::check_undefined)
+            })
+            .collect()
+    }
+
+    /// Set the property values on an object in a batch.
+    ///
+    /// The keys are converted to atoms without going through `CString`,
+    /// stops at the first exception thrown by a setter.
+    pub fn set_many<K: NewAtom + Copy, V: NewValue + Clone>(
+        &self,
+        this: &Value,
+        props: &[(K, V)],
+    ) -> Result<(), Error> {
+        let ctx = self.as_ptr();
+
+        self.check_owner(this)?;
+
+        props
+            .iter()
+            .try_for_each(|(_, value)| value.check_value(self))?;
+
+        for (key, value) in props {
+            let atom = key.new_atom(self);
+            let ret = unsafe {
+                ffi::JS_SetPropertyInternal(
+                    ctx,
+                    this.raw(),
+                    atom,
+                    value.clone().new_value(self),
+                    ffi::JS_PROP_THROW as i32,
+                )
+            };
+
+            self.free_atom(atom);
+            self.check_error(ret)?;
+        }
+
+        Ok(())
+    }
+
     /// Check if a property on an object.
     pub fn has_property<T: HasProperty>(&self, this: &Value, prop: T) -> Result<bool, Error> {
+        self.check_owner(this)?;
+
         prop.has_property(self, this)
     }
 
@@ -589,6 +755,8 @@ impl ContextRef {
     ///
     /// It returns a `bool` indicating whether or not the property was successfully deleted.
     pub fn delete_property<T: DeleteProperty>(&self, this: &Value, prop: T) -> Result<bool, Error> {
+        self.check_owner(this)?;
+
         prop.delete_property(self, this)
     }
 
@@ -602,6 +770,11 @@ impl ContextRef {
         setter: Option<&Value>,
         flags: Prop,
     ) -> Result<bool, Error> {
+        self.check_owner(this)?;
+        val.as_ref().map_or(Ok(()), |val| self.check_owner(val))?;
+        getter.map_or(Ok(()), |getter| self.check_owner(getter))?;
+        setter.map_or(Ok(()), |setter| self.check_owner(setter))?;
+
         prop.define_property(self, this, val, getter, setter, flags)
     }
 
@@ -614,6 +787,9 @@ impl ContextRef {
         val: V,
         flags: Prop,
     ) -> Result<bool, Error> {
+        self.check_owner(this)?;
+        val.check_value(self)?;
+
         prop.define_property(self, this, val, flags)
     }
 
@@ -627,18 +803,54 @@ impl ContextRef {
         setter: Option<&Value>,
         flags: Prop,
     ) -> Result<bool, Error> {
+        self.check_owner(this)?;
+        getter.map_or(Ok(()), |getter| self.check_owner(getter))?;
+        setter.map_or(Ok(()), |setter| self.check_owner(setter))?;
+
         prop.define_property(self, this, getter, setter, flags)
     }
 
     /// Check if an object is extensible (whether it can have new properties added to it).
     pub fn is_extensible(&self, obj: &Value) -> Result<bool, Error> {
+        self.check_owner(obj)?;
+
         self.check_bool(unsafe { ffi::JS_IsExtensible(self.as_ptr(), obj.raw()) })
     }
 
     /// Prevents new properties from ever being added to an object (i.e. prevents future extensions to the object).
     pub fn prevent_extensions(&self, obj: &Value) -> Result<bool, Error> {
+        self.check_owner(obj)?;
+
         self.check_bool(unsafe { ffi::JS_PreventExtensions(self.as_ptr(), obj.raw()) })
     }
+
+    /// Returns an array of all own property keys of an object, including the non-enumerable properties and symbols,
+    /// like `Reflect.ownKeys`.
+    pub fn own_keys(&self, obj: &Value) -> Result<Option<Vec<Atom>>, Error> {
+        self.get_own_property_names(obj, Names::STRING | Names::SYMBOL)
+    }
+
+    /// Returns the prototype of an object, or `null` if it has no prototype.
+    pub fn get_prototype(&self, obj: &Value) -> Local<Value> {
+        if let Err(err) = self.check_owner(obj) {
+            warn!("{:?} get prototype, {}", self, err);
+
+            return self.null();
+        }
+
+        // the prototype is not duplicated by `JS_GetPrototype`
+        self.clone_value(&Value::from(unsafe {
+            ffi::JS_GetPrototype(self.as_ptr(), obj.raw())
+        }))
+    }
+
+    /// Sets the prototype of an object to another object or `null`.
+    pub fn set_prototype(&self, obj: &Value, proto: &Value) -> Result<bool, Error> {
+        self.check_owner(obj)?;
+        self.check_owner(proto)?;
+
+        self.check_bool(unsafe { ffi::JS_SetPrototype(self.as_ptr(), obj.raw(), proto.raw()) })
+    }
 }
 
 #[cfg(test)]
@@ -685,6 +897,55 @@ mod tests {
         assert!(!obj.has_property("foo").unwrap());
     }
 
+    #[test]
+    fn get_set_many() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let obj = ctxt
+            .eval_script(
+                "({ a: 1, b: 'two', 0: true, get err() { throw new Error('getter') } })",
+                "<evalScript>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+        let values = obj.get_many(&["a", "b", "c"]).unwrap();
+
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0].as_ref().unwrap().to_int32().unwrap(), 1);
+        assert_eq!(values[1].as_ref().unwrap().to_string(), "two");
+        assert!(values[2].is_none());
+
+        assert!(obj.get_many(&[0u32]).unwrap()[0]
+            .as_ref()
+            .unwrap()
+            .to_bool()
+            .unwrap());
+
+        match obj
+            .get_many(&["a", "err"])
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap()
+        {
+            ErrorKind::Error(msg, _) => assert_eq!(msg, "getter"),
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        obj.set_many(&[("a", "one"), ("c", "three")]).unwrap();
+
+        assert_eq!(obj.get_property("a").unwrap().to_string(), "one");
+        assert_eq!(obj.get_property("c").unwrap().to_string(), "three");
+
+        assert!(obj.prevent_extensions().unwrap());
+        assert!(obj.set_many(&[("a", 1), ("d", 4)]).is_err());
+        assert_eq!(obj.get_property("a").unwrap().to_int32().unwrap(), 1);
+        assert!(obj.get_property("d").is_none());
+    }
+
     #[test]
     fn extensible() {
         let _ = pretty_env_logger::try_init();
@@ -708,4 +969,46 @@ mod tests {
             ErrorKind::TypeError("object is not extensible".into(), None)
         );
     }
+
+    #[test]
+    fn prototype() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let obj = ctxt
+            .eval_script(
+                "var obj = Object.defineProperty({ a: 1 }, 'hidden', { value: 2 }); obj[Symbol('s')] = 3; obj",
+                "<evalScript>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let object_proto = ctxt
+            .eval_script("Object.prototype", "<evalScript>", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(
+            obj.own_keys()
+                .unwrap()
+                .unwrap()
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a", "hidden", "s"]
+        );
+        assert_eq!(obj.keys().unwrap().unwrap().len(), 1);
+
+        assert!(obj.get_prototype().strict_eq(&object_proto).unwrap());
+        assert!(obj.has_property("toString").unwrap());
+
+        assert!(obj.set_prototype(&ctxt.null()).unwrap());
+        assert!(obj.get_prototype().is_null());
+        assert!(!obj.has_property("toString").unwrap());
+
+        assert!(obj.set_prototype(&object_proto).unwrap());
+        assert!(obj.prevent_extensions().unwrap());
+        assert!(obj.set_prototype(&ctxt.null()).is_err());
+        assert!(obj.set_prototype(&ctxt.new_value(1)).is_err());
+    }
 }
@@ -0,0 +1,222 @@
+use std::cell::Cell;
+
+use crate::{
+    conversion::{Budget, Suspended},
+    ContextRef, ErrorKind,
+};
+
+#[derive(Default)]
+struct Reentrancy {
+    depth: Cell<usize>,
+    max_depth: Cell<Option<usize>>,
+}
+
+/// Leave the native call when dropped.
+pub(crate) struct NativeCall<'a> {
+    state: &'a Reentrancy,
+    _budget: Suspended<'a>,
+}
+
+impl Drop for NativeCall<'_> {
+    fn drop(&mut self) {
+        self.state.depth.set(self.state.depth.get() - 1);
+    }
+}
+
+impl ContextRef {
+    /// Set the limit of the nested native calls, `None` for unlimited, which is the default.
+    ///
+    /// The native functions could call back into the scripts with `eval` or `call`, e.g. a sort comparator,
+    /// which may call the native functions again. Entering a native function beyond the limit
+    /// throws an `InternalError` to the scripts, before the native stack overflows.
+    /// Without a limit, the recursion is only bounded by the stack size of the context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    /// let nested = ctxt
+    ///     .new_c_function(
+    ///         |ctxt, _this, args| {
+    ///             let n = ctxt.to_int32(&args[0]).unwrap_or_default();
+    ///
+    ///             if n > 0 {
+    ///                 ctxt.eval::<_, i32>(format!("nested({})", n - 1).as_str(), Eval::GLOBAL)
+    ///                     .map(|depth| depth.unwrap_or_default() + 1)
+    ///             } else {
+    ///                 Ok(0)
+    ///             }
+    ///         },
+    ///         Some("nested"),
+    ///         1,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// ctxt.global_object().set_property("nested", nested).unwrap();
+    /// ctxt.set_max_native_depth(Some(10));
+    ///
+    /// assert_eq!(ctxt.eval("nested(9)", Eval::GLOBAL).unwrap(), Some(9));
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("try { nested(10) } catch (err) { err.name }", Eval::GLOBAL).unwrap(),
+    ///     Some("InternalError".to_owned())
+    /// );
+    /// ```
+    pub fn set_max_native_depth(&self, max_depth: Option<usize>) {
+        self.state::<Reentrancy>().max_depth.set(max_depth)
+    }
+
+    /// Returns the limit of the nested native calls.
+    pub fn max_native_depth(&self) -> Option<usize> {
+        self.state::<Reentrancy>().max_depth.get()
+    }
+
+    /// Returns the number of the native calls in progress.
+    pub fn native_depth(&self) -> usize {
+        self.state::<Reentrancy>().depth.get()
+    }
+
+    /// Enter a native call, the conversion in progress is suspended until the call returns.
+    pub(crate) fn enter_native(&self) -> Result<NativeCall, ErrorKind> {
+        let state = self.state::<Reentrancy>();
+        let depth = state.depth.get();
+
+        if let Some(max_depth) = state.max_depth.get() {
+            if depth >= max_depth {
+                return Err(ErrorKind::InternalError(
+                    format!(
+                        "too much recursion, the native calls exceed the limit of {}",
+                        max_depth
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        state.depth.set(depth + 1);
+
+        Ok(NativeCall {
+            state,
+            _budget: self.state::<Budget>().suspend(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Context, ConversionLimits, Eval, Runtime};
+
+    #[test]
+    fn reentrancy() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(ctxt.max_native_depth(), None);
+
+        // the comparator calls back into the scripts
+        let compare = ctxt
+            .new_c_function(
+                |ctxt, _this, args| {
+                    let key = ctxt.eval_script("key", "<compare>", Eval::GLOBAL)?;
+                    let a = ctxt.call(&key, None, ctxt.clone_value(&args[0]))?;
+                    let b = ctxt.call(&key, None, ctxt.clone_value(&args[1]))?;
+
+                    Ok(a.to_int32().unwrap_or_default() - b.to_int32().unwrap_or_default())
+                },
+                Some("compare"),
+                2,
+            )
+            .unwrap();
+        let depth = ctxt
+            .new_c_function(
+                |ctxt, _this, _args| ctxt.native_depth() as i32,
+                Some("depth"),
+                0,
+            )
+            .unwrap();
+        let count = ctxt
+            .new_c_function(
+                |ctxt, _this, args| {
+                    ctxt.clone_value(&args[0])
+                        .extract::<Vec<i32>>()
+                        .map(|v| v.len() as i32)
+                },
+                Some("count"),
+                1,
+            )
+            .unwrap();
+
+        ctxt.global_object()
+            .set_property("compare", compare)
+            .unwrap();
+        ctxt.global_object().set_property("depth", depth).unwrap();
+        ctxt.global_object().set_property("count", count).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "var key = (s) => s.length; ['ccc', 'a', 'bb'].sort(compare).join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("a,bb,ccc".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "key = (s) => depth(); [depth(), ['a', 'b'].sort(compare).length].join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("1,2".to_owned())
+        );
+        assert_eq!(ctxt.native_depth(), 0);
+
+        // the nested conversions have their own budget
+        ctxt.set_conversion_limits(ConversionLimits::default().with_max_elements(4));
+
+        assert_eq!(
+            ctxt.eval::<_, HashMap<String, i32>>(
+                "({ get a() { return count([1, 2, 3]) }, b: count([4]) })",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(
+                vec![("a".to_owned(), 3), ("b".to_owned(), 1)]
+                    .into_iter()
+                    .collect()
+            )
+        );
+        assert!(ctxt
+            .eval::<_, i32>("count([1, 2, 3, 4, 5])", Eval::GLOBAL)
+            .is_err());
+
+        // the recursion is limited
+        ctxt.set_max_native_depth(Some(3));
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "key = (s) => ['x', 'y'].sort(compare).length; try { ['a', 'b'].sort(compare) } catch (err) { err.message }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("too much recursion, the native calls exceed the limit of 3".to_owned())
+        );
+        assert_eq!(ctxt.native_depth(), 0);
+
+        ctxt.set_max_native_depth(None);
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "key = (s) => s.length; ['bb', 'a'].sort(compare).join()",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("a,bb".to_owned())
+        );
+    }
+}
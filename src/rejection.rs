@@ -0,0 +1,278 @@
+use std::cell::{Cell, RefCell};
+use std::convert::TryFrom;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, value::ToBool, ContextRef, ErrorKind, Value};
+
+/// How the unhandled promise rejections of a context are treated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RejectionPolicy {
+    /// ignore the unhandled rejections
+    Ignore,
+    /// log the unhandled rejections after the evaluation or the pending job
+    Log,
+    /// fail the evaluation or the pending job which left an unhandled rejection
+    Abort,
+    /// collect the unhandled rejections, see `ContextRef::take_unhandled_rejections`
+    Collect,
+}
+
+impl Default for RejectionPolicy {
+    fn default() -> Self {
+        RejectionPolicy::Ignore
+    }
+}
+
+/// The promises rejected without any handler, with their reasons.
+#[derive(Default)]
+struct Rejections {
+    policy: Cell<RejectionPolicy>,
+    ctx: Cell<usize>,
+    pending: RefCell<Vec<(Value, Value)>>,
+}
+
+unsafe impl Send for Rejections {}
+
+impl Drop for Rejections {
+    fn drop(&mut self) {
+        let ctx = self.ctx.get();
+
+        if ctx != 0 {
+            let ctxt = unsafe { ContextRef::from_ptr(ctx as *mut _) };
+
+            for (promise, reason) in self.pending.get_mut().drain(..) {
+                ctxt.free_value(promise);
+                ctxt.free_value(reason);
+            }
+        }
+    }
+}
+
+impl ContextRef {
+    /// Set the policy of the unhandled promise rejections, the rejections are ignored by default.
+    ///
+    /// A rejection is unhandled if the promise has no handler when the evaluation or the pending job finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, RejectionPolicy, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.set_rejection_policy(RejectionPolicy::Collect);
+    ///
+    /// ctxt.eval_script(
+    ///     "Promise.reject(1).catch(() => {}); Promise.reject(new Error('oops'))",
+    ///     "<eval>",
+    ///     Eval::GLOBAL,
+    /// ).unwrap();
+    ///
+    /// while rt.execute_pending_job().unwrap().is_some() {}
+    ///
+    /// assert_eq!(ctxt.take_unhandled_rejections()[0].message(), "oops");
+    /// assert!(ctxt.take_unhandled_rejections().is_empty());
+    /// ```
+    pub fn set_rejection_policy(&self, policy: RejectionPolicy) {
+        let rejections = self.state::<Rejections>();
+
+        rejections.policy.set(policy);
+        rejections.ctx.set(self.as_ptr() as usize);
+
+        if policy != RejectionPolicy::Ignore {
+            unsafe {
+                ffi::JS_SetHostPromiseRejectionTracker(
+                    self.runtime().as_ptr(),
+                    Some(track_rejection),
+                    ptr::null_mut(),
+                )
+            }
+        }
+    }
+
+    /// Returns the policy of the unhandled promise rejections.
+    pub fn rejection_policy(&self) -> RejectionPolicy {
+        self.state::<Rejections>().policy.get()
+    }
+
+    /// Take the reasons of the unhandled promise rejections, which are collected with `RejectionPolicy::Collect`.
+    pub fn take_unhandled_rejections(&self) -> Vec<ErrorKind> {
+        let pending = self.state::<Rejections>().pending.replace(vec![]);
+
+        pending
+            .into_iter()
+            .map(|(promise, reason)| {
+                self.free_value(promise);
+
+                let reason = self.bind(reason);
+                let desc = reason.to_string();
+
+                ErrorKind::try_from(reason).unwrap_or(ErrorKind::Throw(desc))
+            })
+            .collect()
+    }
+
+    /// Apply the policy to the unhandled rejections, after the evaluation or the pending job.
+    pub(crate) fn check_rejections(&self) -> Result<(), Error> {
+        match self.rejection_policy() {
+            RejectionPolicy::Ignore | RejectionPolicy::Collect => Ok(()),
+            RejectionPolicy::Log => {
+                for err in self.take_unhandled_rejections() {
+                    warn!("unhandled promise rejection, {}", err);
+                }
+
+                Ok(())
+            }
+            RejectionPolicy::Abort => match self.take_unhandled_rejections().into_iter().next() {
+                Some(err) => Err(err.into()),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn track_rejection(&self, promise: &Value, reason: &Value, is_handled: bool) {
+        let rejections = self.state::<Rejections>();
+
+        if rejections.policy.get() == RejectionPolicy::Ignore {
+            return;
+        }
+
+        let mut pending = rejections.pending.borrow_mut();
+
+        if is_handled {
+            if let Some(idx) = pending
+                .iter()
+                .position(|(p, _)| p.as_object() == promise.as_object())
+            {
+                let (promise, reason) = pending.remove(idx);
+
+                self.free_value(promise);
+                self.free_value(reason);
+            }
+        } else {
+            trace!("{:?} promise rejected without handler", self);
+
+            pending.push((
+                self.clone_value(promise).into_inner_untracked(),
+                self.clone_value(reason).into_inner_untracked(),
+            ));
+        }
+    }
+}
+
+unsafe extern "C" fn track_rejection(
+    ctx: *mut ffi::JSContext,
+    promise: ffi::JSValue,
+    reason: ffi::JSValue,
+    is_handled: c_int,
+    _opaque: *mut c_void,
+) {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.runtime().catch_unwind((), || {
+        ctxt.track_rejection(
+            &Value::from(promise),
+            &Value::from(reason),
+            is_handled.to_bool(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn rejection_policy() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let run_jobs = || -> Result<(), Error> {
+            while rt.execute_pending_job()?.is_some() {}
+
+            Ok(())
+        };
+
+        assert_eq!(ctxt.rejection_policy(), RejectionPolicy::Ignore);
+
+        ctxt.eval_script("Promise.reject(1)", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        ctxt.set_rejection_policy(RejectionPolicy::Abort);
+
+        // the rejection is handled before the evaluation finished
+        ctxt.eval_script(
+            "Promise.reject(new Error('handled')).catch(() => {})",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+        run_jobs().unwrap();
+
+        let err = ctxt
+            .eval_script(
+                "Promise.reject(new RangeError('sync'))",
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.message(), "sync");
+
+        ctxt.eval_script(
+            "(async () => { await null; throw new TypeError('async') })()",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_jobs()
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "async"
+        );
+
+        ctxt.set_rejection_policy(RejectionPolicy::Collect);
+
+        ctxt.eval_script(
+            r#"
+var p = Promise.reject('later');
+Promise.reject('first');
+(async () => { throw 'second' })();
+Promise.resolve().then(() => p.catch(() => {}));
+"#,
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+        run_jobs().unwrap();
+
+        assert_eq!(
+            ctxt.take_unhandled_rejections(),
+            vec![
+                ErrorKind::Throw("first".into()),
+                ErrorKind::Throw("second".into())
+            ]
+        );
+
+        ctxt.set_rejection_policy(RejectionPolicy::Log);
+
+        ctxt.eval_script("Promise.reject('logged')", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert!(ctxt.take_unhandled_rejections().is_empty());
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    ffi, ContextRef, ErrorKind, Eval, InspectOptions, Local, PropertyNames as Names, Value,
+};
+
+/// The filename of the code evaluated in the REPL.
+const REPL_FILENAME: &str = "<repl>";
+
+/// The result of feeding a line to the REPL.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// the input is syntactically incomplete, waiting for more lines
+    Incomplete,
+    /// the input was evaluated to the pretty-printed value
+    Value(String),
+    /// the input threw an exception
+    Exception(String),
+}
+
+/// The read-eval-print loop of a context.
+///
+/// The `Repl` evaluates the lines fed to it and keeps the input until it's syntactically complete,
+/// the line editing is left to the front end, which could use `Repl::complete` for the tab completion.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Outcome, Repl, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let mut repl = Repl::new(&ctxt);
+///
+/// assert_eq!(repl.feed("function add(a, b) {"), Outcome::Incomplete);
+/// assert_eq!(repl.feed("  return a + b;"), Outcome::Incomplete);
+/// assert_eq!(repl.feed("}"), Outcome::Value("undefined".to_owned()));
+/// assert_eq!(repl.feed("add(1, 2)"), Outcome::Value("3".to_owned()));
+/// assert_eq!(repl.complete("ad", 2), (0, vec!["add".to_owned()]));
+/// ```
+pub struct Repl<'a> {
+    ctxt: &'a ContextRef,
+    input: String,
+    options: InspectOptions,
+}
+
+impl<'a> Repl<'a> {
+    /// Create a REPL evaluates the input in the context.
+    pub fn new(ctxt: &'a ContextRef) -> Self {
+        Repl {
+            ctxt,
+            input: String::new(),
+            options: InspectOptions::new(),
+        }
+    }
+
+    /// Style the results with the ANSI color codes.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.options = self.options.with_colors(colors);
+        self
+    }
+
+    /// The prompt of the next line.
+    pub fn prompt(&self) -> &'static str {
+        if self.is_continuing() {
+            "... "
+        } else {
+            "> "
+        }
+    }
+
+    /// The previous lines are waiting for more input.
+    pub fn is_continuing(&self) -> bool {
+        !self.input.is_empty()
+    }
+
+    /// Discard the incomplete input.
+    pub fn reset(&mut self) {
+        self.input.clear();
+    }
+
+    /// Feed a line to the REPL, evaluate the input if it's syntactically complete.
+    pub fn feed(&mut self, line: &str) -> Outcome {
+        if !self.input.is_empty() {
+            self.input.push('\n');
+        }
+        self.input.push_str(line);
+
+        if self.input.trim().is_empty() {
+            self.input.clear();
+
+            return Outcome::Value(String::new());
+        }
+
+        if let Err(err) = self.ctxt.eval_script(
+            &*self.input,
+            REPL_FILENAME,
+            Eval::GLOBAL | Eval::COMPILE_ONLY,
+        ) {
+            if is_incomplete(&self.input, &err) {
+                return Outcome::Incomplete;
+            }
+        }
+
+        let input = self.input.split_off(0);
+
+        match self.eval(&input) {
+            Ok(value) => Outcome::Value(value),
+            Err(err) => Outcome::Exception(describe(&err)),
+        }
+    }
+
+    fn eval(&self, input: &str) -> Result<String, Error> {
+        let value = self.ctxt.eval_script(input, REPL_FILENAME, Eval::GLOBAL)?;
+        let rt = self.ctxt.runtime();
+
+        while rt.execute_pending_job()?.is_some() {}
+
+        Ok(value.inspect(&self.options))
+    }
+
+    /// Complete the global or property name before the cursor at `pos`,
+    /// returns the start position of the completed name and the candidates.
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let line = &line[..pos.min(line.len())];
+        let expr_start = line
+            .rfind(|c: char| !(is_ident_char(c) || c == '.'))
+            .map_or(0, |idx| idx + 1);
+        let expr = &line[expr_start..];
+        let (base, prefix) = match expr.rfind('.') {
+            Some(idx) => (Some(&expr[..idx]), &expr[idx + 1..]),
+            None => (None, expr),
+        };
+        let start = line.len() - prefix.len();
+
+        if prefix.starts_with(|c: char| c.is_ascii_digit()) {
+            return (start, vec![]);
+        }
+
+        let obj = match base {
+            Some(base) => match self.resolve(base) {
+                Some(obj) => obj,
+                None => return (start, vec![]),
+            },
+            None => self.ctxt.global_object(),
+        };
+
+        let candidates = self
+            .property_names(&obj)
+            .into_iter()
+            .filter(|name| name.starts_with(prefix) && name.chars().all(is_ident_char))
+            .collect();
+
+        (start, candidates)
+    }
+
+    /// Resolve the value of a dotted path of identifiers without side effects of the calls.
+    fn resolve(&self, path: &str) -> Option<Local<'a, Value>> {
+        let mut obj = self.ctxt.global_object();
+
+        for name in path.split('.') {
+            if name.is_empty() || !name.chars().all(is_ident_char) {
+                return None;
+            }
+
+            obj = self.ctxt.get_property(&obj, name)?;
+        }
+
+        Some(obj)
+    }
+
+    /// The names of the properties of the value and its prototypes.
+    fn property_names(&self, value: &Value) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        let mut obj = self.ctxt.clone_value(value);
+
+        for _ in 0..32 {
+            if obj.is_object() {
+                if let Ok(Some(atoms)) = self.ctxt.get_own_property_names(&obj, Names::STRING) {
+                    names.extend(atoms.iter().map(|atom| atom.to_string()));
+                }
+            }
+
+            let proto = unsafe { ffi::JS_GetPrototype(self.ctxt.as_ptr(), obj.raw()) };
+
+            if !Value::from(proto).is_object() {
+                break;
+            }
+
+            obj = self.ctxt.clone_value(&Value::from(proto));
+        }
+
+        names
+    }
+
+    /// Run the REPL with the lines from `input`, writes the prompts and results to `output`.
+    pub fn run<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<()> {
+        loop {
+            write!(output, "{}", self.prompt())?;
+            output.flush()?;
+
+            let mut line = String::new();
+
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            match self.feed(line.trim_end_matches(&['\r', '\n'][..])) {
+                Outcome::Incomplete => {}
+                Outcome::Value(value) => {
+                    if !value.is_empty() {
+                        writeln!(output, "{}", value)?
+                    }
+                }
+                Outcome::Exception(err) => writeln!(output, "Uncaught {}", err)?,
+            }
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn describe(err: &Error) -> String {
+    match err.downcast_ref::<ErrorKind>() {
+        Some(kind) => match kind.stack() {
+            Some(stack) if !stack.is_empty() => format!("{}\n{}", kind, stack.trim_end()),
+            _ => kind.to_string(),
+        },
+        None => err.to_string(),
+    }
+}
+
+/// The syntax error is caused by the end of input, which means the input is not completed.
+fn is_incomplete(input: &str, err: &Error) -> bool {
+    match err.downcast_ref::<ErrorKind>() {
+        Some(ErrorKind::SyntaxError(msg, _)) => {
+            msg.ends_with(": ''") || msg == "unexpected end of comment" || is_unclosed(input)
+        }
+        _ => false,
+    }
+}
+
+/// The brackets, template literals or comments of the input are not closed.
+fn is_unclosed(input: &str) -> bool {
+    let mut stack = vec![];
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (stack.last(), c) {
+            (Some('`'), '\\') | (Some('\''), '\\') | (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some('`'), '`') => {
+                stack.pop();
+            }
+            (Some('`'), '$') if chars.peek() == Some(&'{') => {
+                chars.next();
+                stack.push('{');
+            }
+            (Some('`'), _) => {}
+            (Some('\''), '\'') | (Some('"'), '"') => {
+                stack.pop();
+            }
+            (Some('\''), '\n') | (Some('"'), '\n') => return false,
+            (Some('\''), _) | (Some('"'), _) => {}
+            (_, '/') if chars.peek() == Some(&'/') => {
+                while chars.peek().map_or(false, |&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            (_, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                let mut closed = false;
+
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.peek() == Some(&'/') {
+                        chars.next();
+                        closed = true;
+                        break;
+                    }
+                }
+
+                if !closed {
+                    return true;
+                }
+            }
+            (_, '(') | (_, '[') | (_, '{') | (_, '`') | (_, '\'') | (_, '"') => stack.push(c),
+            (Some('('), ')') | (Some('['), ']') | (Some('{'), '}') => {
+                stack.pop();
+            }
+            (_, ')') | (_, ']') | (_, '}') => return false,
+            _ => {}
+        }
+    }
+
+    stack
+        .iter()
+        .any(|&c| c == '(' || c == '[' || c == '{' || c == '`')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn repl() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let mut repl = Repl::new(&ctxt);
+
+        assert_eq!(repl.prompt(), "> ");
+        assert_eq!(repl.feed("var point = {"), Outcome::Incomplete);
+        assert_eq!(repl.prompt(), "... ");
+        assert_eq!(repl.feed("  x: 1, y: `a"), Outcome::Incomplete);
+        assert_eq!(repl.feed("b` };"), Outcome::Value("undefined".to_owned()));
+        assert_eq!(
+            repl.feed("point"),
+            Outcome::Value("{ x: 1, y: 'a\\nb' }".to_owned())
+        );
+        assert_eq!(
+            repl.feed("'abc"),
+            Outcome::Exception("SyntaxError: unexpected end of string\n    at <repl>:1".to_owned())
+        );
+        assert_eq!(repl.feed(""), Outcome::Value(String::new()));
+        assert_eq!(repl.feed("/* comment"), Outcome::Incomplete);
+        assert_eq!(
+            repl.feed("*/ [1, 2].map(x => x * 2)"),
+            Outcome::Value("[ 2, 4 ]".to_owned())
+        );
+
+        match repl.feed("null.x") {
+            Outcome::Exception(err) => assert!(err.starts_with("TypeError"), "{}", err),
+            outcome => panic!("unexpected {:?}", outcome),
+        }
+
+        assert_eq!(
+            repl.feed("}"),
+            Outcome::Exception(
+                "SyntaxError: unexpected token in expression: '}'\n    at <repl>:1".to_owned()
+            )
+        );
+
+        assert_eq!(repl.complete("poi", 3), (0, vec!["point".to_owned()]));
+        assert_eq!(repl.complete("1 + point.", 10).0, 10);
+        assert_eq!(
+            repl.complete("1 + point.", 10).1[..2],
+            ["__defineGetter__", "__defineSetter__"]
+        );
+        assert_eq!(
+            repl.complete("point.x + point.", 16)
+                .1
+                .last()
+                .map(String::as_str),
+            Some("y")
+        );
+        assert!(repl
+            .complete("point.y.toUpp", 13)
+            .1
+            .contains(&"toUpperCase".to_owned()));
+        assert_eq!(repl.complete("Math.PI.", 8).0, 8);
+        assert_eq!(repl.complete("foo.bar.", 8), (8, vec![]));
+
+        let mut output = vec![];
+
+        repl.run(&b"1 +\n2\nthrow new Error('boom')\n"[..], &mut output)
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(
+            output.starts_with("> ... 3\n> Uncaught Error: boom\n"),
+            "{}",
+            output
+        );
+        assert!(output.ends_with("> "));
+    }
+}
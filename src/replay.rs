@@ -0,0 +1,315 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ErrorKind, NewValue, Value, WriteObj};
+
+/// A recorded call of the host function.
+///
+/// The values are serialized with `JS_WriteObject`, so only the plain objects, arrays and primitives are recorded,
+/// the other values are recorded as `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostCall {
+    /// the name of the host function
+    pub name: String,
+    /// the arguments array
+    pub args: Option<Vec<u8>>,
+    /// the returned value, or the thrown exception
+    pub result: Result<Option<Vec<u8>>, ErrorKind>,
+    /// the time elapsed in the host function
+    pub elapsed: Duration,
+}
+
+/// The replayable trace of the host function calls, in the order they were called.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trace {
+    pub calls: Vec<HostCall>,
+}
+
+enum Mode {
+    Off,
+    Recording(Vec<HostCall>),
+    Replaying(VecDeque<HostCall>),
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Off
+    }
+}
+
+#[derive(Default)]
+struct Replay(RefCell<Mode>);
+
+unsafe impl Send for Replay {}
+
+impl ContextRef {
+    /// Start recording the calls of the host functions created by `new_c_function`
+    /// or `new_c_function_with_capabilities`, the previous recording or replaying is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ContextRef, Eval, Runtime, Value};
+    ///
+    /// fn roll(_ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> i32 {
+    ///     4 // chosen by fair dice roll
+    /// }
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.global_object()
+    ///     .set_property("roll", ctxt.new_c_function(roll, Some("roll"), 0).unwrap())
+    ///     .unwrap();
+    ///
+    /// ctxt.start_recording();
+    /// assert_eq!(ctxt.eval::<_, i32>("roll() + roll()", Eval::GLOBAL).unwrap(), Some(8));
+    /// let trace = ctxt.stop_recording();
+    ///
+    /// assert_eq!(trace.calls.len(), 2);
+    ///
+    /// // the recorded results are served without calling the host functions
+    /// ctxt.global_object()
+    ///     .set_property("roll", ctxt.new_c_function(|_, _, _| 6, Some("roll"), 0).unwrap())
+    ///     .unwrap();
+    ///
+    /// ctxt.start_replay(trace);
+    /// assert_eq!(ctxt.eval::<_, i32>("roll() + roll()", Eval::GLOBAL).unwrap(), Some(8));
+    /// assert!(ctxt.stop_replay().calls.is_empty());
+    /// ```
+    pub fn start_recording(&self) {
+        *self.state::<Replay>().0.borrow_mut() = Mode::Recording(vec![]);
+    }
+
+    /// Stop recording, returns the recorded trace.
+    pub fn stop_recording(&self) -> Trace {
+        match mem::take(&mut *self.state::<Replay>().0.borrow_mut()) {
+            Mode::Recording(calls) => Trace { calls },
+            _ => Trace::default(),
+        }
+    }
+
+    /// Start replaying the trace, the host functions are not called but return the recorded results.
+    ///
+    /// An `InternalError` is thrown if the calls diverged from the trace.
+    pub fn start_replay(&self, trace: Trace) {
+        *self.state::<Replay>().0.borrow_mut() = Mode::Replaying(trace.calls.into());
+    }
+
+    /// Stop replaying, returns the calls which were not replayed.
+    pub fn stop_replay(&self) -> Trace {
+        match mem::take(&mut *self.state::<Replay>().0.borrow_mut()) {
+            Mode::Replaying(calls) => Trace {
+                calls: calls.into(),
+            },
+            _ => Trace::default(),
+        }
+    }
+
+    fn write_args(&self, args: &[Value]) -> Option<Vec<u8>> {
+        let arr = self.bind(self.new_array());
+
+        for (idx, arg) in args.iter().enumerate() {
+            arr.set_property(idx as u32, self.clone_value(arg)).ok()?;
+        }
+
+        self.write_object(&arr, WriteObj::empty()).ok()
+    }
+
+    fn read_value(&self, buf: &[u8]) -> ffi::JSValue {
+        unsafe { ffi::JS_ReadObject(self.as_ptr(), buf.as_ptr(), buf.len(), 0) }
+    }
+
+    /// Call the host function, which is recorded or replayed.
+    pub(crate) fn call_host<F: FnOnce() -> ffi::JSValue>(
+        &self,
+        name: &str,
+        args: &[Value],
+        f: F,
+    ) -> ffi::JSValue {
+        let state = self.state::<Replay>();
+        let idx = match *state.0.borrow_mut() {
+            Mode::Off => None,
+            Mode::Recording(ref mut calls) => {
+                // reserve the slot before calling, the nested calls are recorded after it
+                calls.push(HostCall {
+                    name: name.to_owned(),
+                    args: self.write_args(args),
+                    result: Ok(None),
+                    elapsed: Duration::default(),
+                });
+
+                Some(calls.len() - 1)
+            }
+            Mode::Replaying(ref mut calls) => {
+                let args = self.write_args(args);
+                let err = match calls.pop_front() {
+                    None => format!("replay exhausted, unexpected call to `{}`", name),
+                    Some(ref call) if call.name != name => {
+                        format!(
+                            "replay diverged, expected `{}`, called `{}`",
+                            call.name, name
+                        )
+                    }
+                    Some(ref call) if call.args != args => {
+                        format!(
+                            "replay diverged, `{}` called with different arguments",
+                            name
+                        )
+                    }
+                    Some(HostCall {
+                        result: Ok(Some(ref buf)),
+                        ..
+                    }) => return self.read_value(buf),
+                    Some(HostCall {
+                        result: Ok(None), ..
+                    }) => {
+                        format!("the result of `{}` can't be replayed", name)
+                    }
+                    Some(HostCall {
+                        result: Err(err), ..
+                    }) => return err.new_value(self),
+                };
+
+                debug!("{:?} {}", self, err);
+
+                return ErrorKind::InternalError(err, None).new_value(self);
+            }
+        };
+
+        let idx = match idx {
+            Some(idx) => idx,
+            None => return f(),
+        };
+
+        let start = Instant::now();
+        let ret = f();
+        let elapsed = start.elapsed();
+        let (ret, result) = if Value::from(ret).is_exception() {
+            let exc = self.get_exception().unwrap_or_else(|| self.undefined());
+            let err = ErrorKind::try_from(self.clone_value(&exc))
+                .unwrap_or_else(|err| ErrorKind::Throw(err.to_string()));
+
+            (self.throw(exc).into_inner_untracked().raw(), Err(err))
+        } else {
+            (
+                ret,
+                Ok(self.write_object(&Value::from(ret), WriteObj::empty()).ok()),
+            )
+        };
+
+        if let Mode::Recording(ref mut calls) = *state.0.borrow_mut() {
+            if let Some(call) = calls.get_mut(idx) {
+                call.result = result;
+                call.elapsed = elapsed;
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    thread_local! {
+        static CALLS: Cell<usize> = Cell::new(0);
+    }
+
+    fn lookup(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+        CALLS.with(|calls| calls.set(calls.get() + 1));
+
+        let key = ctxt.clone_value(&args[0]).to_string();
+
+        if key == "missing" {
+            ErrorKind::RangeError(format!("no such key: {}", key), None)
+                .new_value(ctxt)
+                .into()
+        } else {
+            let obj = ctxt.bind(ctxt.new_object());
+
+            obj.set_property("key", key).unwrap();
+            obj.set_property("hits", CALLS.with(Cell::get) as i32)
+                .unwrap();
+            obj.into_inner_untracked()
+        }
+    }
+
+    #[test]
+    fn record_replay() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.global_object()
+            .set_property(
+                "lookup",
+                ctxt.new_c_function(lookup, Some("lookup"), 1).unwrap(),
+            )
+            .unwrap();
+
+        let script = r#"
+var res = lookup('a');
+var err;
+
+try { lookup('missing') } catch (e) { err = e }
+
+[res.key, res.hits, err instanceof RangeError, err.message].join()
+"#;
+
+        ctxt.start_recording();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(script, Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "a,1,true,no such key: missing"
+        );
+
+        let trace = ctxt.stop_recording();
+
+        assert_eq!(
+            trace
+                .calls
+                .iter()
+                .map(|call| (call.name.as_str(), call.result.is_ok()))
+                .collect::<Vec<_>>(),
+            vec![("lookup", true), ("lookup", false)]
+        );
+        assert_eq!(CALLS.with(Cell::get), 2);
+
+        // the recorded results are replayed without calling the host function
+        ctxt.start_replay(trace.clone());
+
+        assert_eq!(
+            ctxt.eval::<_, String>(script, Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "a,1,true,no such key: missing"
+        );
+        assert_eq!(CALLS.with(Cell::get), 2);
+        assert_eq!(ctxt.stop_replay(), Trace::default());
+
+        // the diverged calls are detected
+        ctxt.start_replay(trace);
+
+        assert_eq!(
+            ctxt.eval::<_, ()>("lookup('b')", Eval::GLOBAL)
+                .unwrap_err()
+                .to_string(),
+            "InternalError: replay diverged, `lookup` called with different arguments"
+        );
+        assert_eq!(ctxt.stop_replay().calls.len(), 1);
+    }
+}
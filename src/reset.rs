@@ -0,0 +1,261 @@
+use std::cell::RefCell;
+
+use failure::{err_msg, Error};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Local, Unsupported, Value};
+
+/// The baseline of the global object, which is restored by `ContextRef::reset`.
+///
+/// It holds the property descriptors of the global object and the intrinsic `Object.defineProperty`,
+/// which may be replaced by the scripts.
+#[derive(Default)]
+struct Baseline(RefCell<Option<(usize, Value, Value)>>);
+
+unsafe impl Send for Baseline {}
+
+impl Drop for Baseline {
+    fn drop(&mut self) {
+        if let Some((ctx, descriptors, define_property)) = self.0.get_mut().take() {
+            let ctxt = unsafe { ContextRef::from_ptr(ctx as *mut _) };
+
+            ctxt.free_value(descriptors);
+            ctxt.free_value(define_property);
+        }
+    }
+}
+
+impl ContextRef {
+    /// Record the current global object as the baseline, which is restored by `ContextRef::reset`.
+    ///
+    /// The baseline is recorded when the context is created,
+    /// it should be recorded again after the host objects are added to the global object.
+    pub fn record_baseline(&self) -> Result<(), Error> {
+        let global = self.global_object();
+        let object = self
+            .get_property(&global, "Object")
+            .ok_or_else(|| err_msg("missing `Object`"))?;
+        let get_descriptors = self
+            .get_property(&object, "getOwnPropertyDescriptors")
+            .ok_or_else(|| err_msg("missing `Object.getOwnPropertyDescriptors`"))?;
+        let define_property = self
+            .get_property(&object, "defineProperty")
+            .ok_or_else(|| err_msg("missing `Object.defineProperty`"))?;
+        let descriptors = self.call(&get_descriptors, None, &global)?;
+
+        // only the own properties are looked up in the descriptors
+        descriptors.set_prototype(&self.null())?;
+
+        trace!("{:?} record baseline of the global object", self);
+
+        if let Some((_, descriptors, define_property)) = self.state::<Baseline>().0.replace(Some((
+            self.as_ptr() as usize,
+            descriptors.into_inner_untracked(),
+            define_property.into_inner_untracked(),
+        ))) {
+            self.free_value(descriptors);
+            self.free_value(define_property);
+        }
+
+        Ok(())
+    }
+
+    /// Reset the context to the recorded baseline, for reusing it between the untrusted executions.
+    ///
+    /// The global variables and properties added after the baseline are removed,
+    /// the replaced or removed ones are restored, and the loaded modules are forgotten.
+    /// The forgotten modules are freed unless a handle of them is still held.
+    ///
+    /// # Warning
+    ///
+    /// The objects which are modified in place, e.g. `Array.prototype.map = ...`, are **not** restored,
+    /// so the changes leak into the next execution. The reset is only safe to isolate the untrusted executions
+    /// when the intrinsics are frozen, e.g. `Sandbox::with_frozen_intrinsics` or `ContextRef::deep_freeze`.
+    ///
+    /// Returns an `Unsupported` error if the linked library is not patched,
+    /// which can't remove the global variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.eval_script("var secret = 42; let token = 'abc'; JSON = null;", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// ctxt.reset().unwrap();
+    ///
+    /// assert_eq!(ctxt.eval::<_, String>("typeof secret", Eval::GLOBAL).unwrap().unwrap(), "undefined");
+    /// assert_eq!(ctxt.eval::<_, String>("typeof JSON", Eval::GLOBAL).unwrap().unwrap(), "object");
+    /// assert_eq!(ctxt.eval::<_, String>("let token = 'def'; token", Eval::GLOBAL).unwrap().unwrap(), "def");
+    /// ```
+    pub fn reset(&self) -> Result<(), Error> {
+        Unsupported::check("reset")?;
+
+        debug!("{:?} reset to the baseline", self);
+
+        let (descriptors, define_property) = self
+            .state::<Baseline>()
+            .0
+            .borrow()
+            .as_ref()
+            .map(|(_, descriptors, define_property)| {
+                (
+                    self.clone_value(descriptors),
+                    self.clone_value(define_property),
+                )
+            })
+            .ok_or_else(|| err_msg("missing baseline"))?;
+        let global = self.global_object();
+
+        for key in global.own_keys()?.unwrap_or_default() {
+            if !descriptors.has_property(&key)? {
+                self.scrub_property(&global, &key)?;
+            }
+        }
+
+        for key in descriptors.own_keys()?.unwrap_or_default() {
+            let desc = self
+                .get_property(&descriptors, &key)
+                .ok_or_else(|| err_msg("missing descriptor"))?;
+            let args = (&global, key.to_value(), &desc);
+
+            // the non-configurable properties can't be redefined, e.g. the global `var`
+            if self.call(&define_property, None, args).is_err() {
+                self.scrub_property(&global, &key)?;
+                self.call(&define_property, None, (&global, key.to_value(), &desc))?;
+            }
+        }
+
+        let vars = self.bind(unsafe { ffi::JS_GetGlobalVarObject(self.as_ptr()) });
+
+        if vars.is_object() {
+            for key in vars.own_keys()?.unwrap_or_default() {
+                self.scrub_property(&vars, &key)?;
+            }
+        }
+
+        unsafe { ffi::JS_ResetModules(self.as_ptr()) };
+
+        Ok(())
+    }
+
+    /// Delete a property even if it is not configurable.
+    fn scrub_property(&self, obj: &Value, key: &Local<ffi::JSAtom>) -> Result<bool, Error> {
+        self.check_bool(unsafe { ffi::JS_ScrubProperty(self.as_ptr(), obj.raw(), **key) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    #[test]
+    fn reset() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let type_of = |name: &str| {
+            ctxt.eval::<_, String>(format!("typeof {}", name).as_str(), Eval::GLOBAL)
+                .unwrap()
+                .unwrap()
+        };
+
+        ctxt.global_object().set_property("host", 1).unwrap();
+        ctxt.record_baseline().unwrap();
+
+        ctxt.eval_script(
+            r#"
+var v = 1;
+let l = 2;
+const c = 3;
+class K {}
+function f() {}
+globalThis.p = 4;
+globalThis[Symbol.for('s')] = 5;
+Object.defineProperty(globalThis, 'fixed', { value: 6 });
+Math = null;
+delete globalThis.Reflect;
+host = 'changed';
+"#,
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+        ctxt.eval_script("export const n = 1;", "counter", Eval::MODULE)
+            .unwrap();
+        ctxt.eval_script(
+            "import { n } from 'counter'; globalThis.imported = n;",
+            "main",
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        assert_eq!(type_of("imported"), "number");
+
+        ctxt.reset().unwrap();
+
+        for name in &["v", "l", "c", "K", "f", "p", "fixed", "imported"] {
+            assert_eq!(type_of(name), "undefined", "typeof {}", name);
+        }
+
+        assert_eq!(type_of("Math"), "object");
+        assert_eq!(type_of("Reflect"), "object");
+        assert_eq!(
+            ctxt.eval::<_, i32>("host", Eval::GLOBAL).unwrap().unwrap(),
+            1
+        );
+        assert!(!ctxt
+            .eval::<_, bool>("Symbol.for('s') in globalThis", Eval::GLOBAL)
+            .unwrap()
+            .unwrap());
+
+        // the declarations can be declared again after the reset
+        ctxt.eval_script(
+            "var v = 'v'; let l = 'l'; const c = 'c'; class K {}",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        // the module cache is cleared
+        assert!(ctxt
+            .eval_script("import { n } from 'counter';", "main", Eval::MODULE)
+            .is_err());
+    }
+
+    #[test]
+    fn reset_frees_modules() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.record_baseline().unwrap();
+
+        let run = || {
+            ctxt.eval_script("export const n = 1;", "counter", Eval::MODULE)
+                .unwrap();
+            ctxt.eval_script("import { n } from 'counter';", "main", Eval::MODULE)
+                .unwrap();
+            ctxt.reset().unwrap();
+            rt.run_gc();
+        };
+
+        for _ in 0..4 {
+            run();
+        }
+
+        let usage = rt.memory_usage();
+
+        for _ in 0..16 {
+            run();
+        }
+
+        // the forgotten modules are freed, instead of growing with each reset
+        assert!(rt.memory_usage().memory_used_count <= usage.memory_used_count);
+    }
+}
@@ -1,11 +1,22 @@
+use std::any::Any;
+use std::cell::Cell;
 use std::mem::MaybeUninit;
 use std::os::raw::{c_int, c_void};
-use std::panic;
 use std::ptr::{null_mut, NonNull};
+use std::time::{Duration, Instant};
 
+use failure::Error;
 use foreign_types::{ForeignType, ForeignTypeRef};
 
-use crate::{ffi, value::ToBool, Value};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::watchdog;
+use crate::{
+    ffi,
+    gas::{GasMeter, GAS_PER_INTERRUPT},
+    state,
+    value::ToBool,
+    Extensions, ModuleDetection, Value,
+};
 
 pub use crate::ffi::{JSMallocFunctions as MallocFunctions, JSMemoryUsage as MemoryUsage};
 
@@ -19,10 +30,27 @@ foreign_type! {
     pub type Runtime : Send {
         type CType = ffi::JSRuntime;
 
-        fn drop = ffi::JS_FreeRuntime;
+        fn drop = free_runtime;
     }
 }
 
+unsafe fn free_runtime(rt: *mut ffi::JSRuntime) {
+    #[cfg(feature = "leak-detection")]
+    crate::leak::report(RuntimeRef::from_ptr(rt));
+
+    // the finalizers may access the states when freeing the runtime, so they are freed after it.
+    let states = state::RUNTIME.get_or_attach(rt) as *const state::States as *mut state::States;
+
+    ffi::JS_FreeRuntime(rt);
+
+    // the unpatched library keeps the opaque pointer of the runtime in a global map.
+    if !ffi::PATCHED {
+        ffi::JS_SetRuntimeOpaque(rt, null_mut());
+    }
+
+    drop(Box::from_raw(states));
+}
+
 impl_foreign_type!(Runtime, RuntimeRef);
 
 impl Default for Runtime {
@@ -31,7 +59,65 @@ impl Default for Runtime {
     }
 }
 
+/// The builder of `Runtime`, which configures the engine before any context is created.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, Extensions, ModuleDetection, Runtime};
+///
+/// let rt = Runtime::builder()
+///     .with_extensions(Extensions::all() - Extensions::ANNEX_B)
+///     .with_module_detection(ModuleDetection::Extension)
+///     .build()
+///     .unwrap();
+/// let ctxt = Context::new(&rt);
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("typeof escape", Eval::GLOBAL).unwrap(),
+///     Some("undefined".to_owned())
+/// );
+/// assert!(!rt.is_module("import * as std from 'std'", "main.js"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    extensions: Extensions,
+    module_detection: ModuleDetection,
+}
+
+impl Builder {
+    /// Set the enabled language extensions, all the extensions of the build are enabled by default.
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Set how to tell the module sources from the scripts.
+    pub fn with_module_detection(mut self, detection: ModuleDetection) -> Self {
+        self.module_detection = detection;
+        self
+    }
+
+    /// Construct a new `Runtime` with the configuration.
+    ///
+    /// Returns an `Unsupported` error if the extensions can't be switched by the linked library.
+    pub fn build(self) -> Result<Runtime, Error> {
+        let runtime = Runtime::new();
+
+        runtime
+            .set_extensions(self.extensions)?
+            .set_module_detection(self.module_detection);
+
+        Ok(runtime)
+    }
+}
+
 impl Runtime {
+    /// Returns a builder to configure the engine of a new `Runtime`.
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
     /// Construct a new `Runtime`.
     pub fn new() -> Self {
         let runtime = unsafe { Runtime::from_ptr(ffi::JS_NewRuntime()) };
@@ -80,6 +166,8 @@ impl RuntimeRef {
     pub fn run_gc(&self) {
         trace!("{:?} run GC", self);
 
+        instrument!("qjs::gc", DEBUG, "run_gc");
+
         unsafe { ffi::JS_RunGC(self.as_ptr()) }
     }
 
@@ -106,28 +194,152 @@ impl RuntimeRef {
     ///
     /// This callback can be used to implement an execution timeout.
     pub fn set_interrupt_handler(&self, handler: InterruptHandler) {
-        unsafe {
-            if let Some(func) = handler {
-                unsafe extern "C" fn stub(rt: *mut ffi::JSRuntime, opaque: *mut c_void) -> c_int {
-                    panic::catch_unwind(|| {
-                        let rt = RuntimeRef::from_ptr(rt);
-                        let func: fn(rt: &RuntimeRef) -> Interrupt = *(opaque as *mut _);
-
-                        match func(rt) {
-                            Interrupt::Break => true,
-                            Interrupt::Continue => false,
+        self.state::<Interrupts>().handler.set(handler);
+        self.update_interrupt_handler();
+    }
+
+    /// Set the maximum execution time of each evaluation.
+    ///
+    /// The execution will be interrupted with an `InternalError` when the time limit exceeded.
+    pub fn set_time_limit(&self, limit: Option<Duration>) -> &Self {
+        trace!("{:?} set time limit to {:?}", self, limit);
+
+        self.state::<Interrupts>().time_limit.set(limit);
+        self.update_interrupt_handler();
+        self
+    }
+
+    /// Get the maximum execution time of each evaluation.
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.state::<Interrupts>().time_limit.get()
+    }
+
+    /// Start the deadline of the outermost evaluation if time limit was set.
+    pub(crate) fn start_deadline(&self) -> Deadline {
+        let interrupts = self.state::<Interrupts>();
+        let outermost = interrupts.depth.replace(interrupts.depth.get() + 1) == 0;
+
+        if outermost {
+            if let Some(limit) = interrupts.time_limit.get() {
+                interrupts.deadline.set(Some(Instant::now() + limit));
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            watchdog::start_evaluation(self);
+
+            #[cfg(feature = "profiler")]
+            crate::debugger::profiler::start_evaluation(self);
+        }
+
+        Deadline {
+            rt: self,
+            outermost,
+        }
+    }
+
+    pub(crate) fn update_interrupt_handler(&self) {
+        unsafe extern "C" fn stub(rt: *mut ffi::JSRuntime, _opaque: *mut c_void) -> c_int {
+            let rt = RuntimeRef::from_ptr(rt);
+            let interrupted = rt
+                .catch_unwind(true, || {
+                    let interrupts = rt.state::<Interrupts>();
+
+                    match interrupts.deadline.get() {
+                        Some(deadline) if deadline <= Instant::now() => {
+                            debug!("{:?} interrupted, deadline exceeded", rt);
+
+                            return true;
                         }
-                    })
-                    .unwrap_or(true)
-                    .to_bool()
-                }
+                        _ => {}
+                    }
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        if watchdog::is_tripped(rt) {
+                            debug!("{:?} interrupted by watchdog", rt);
+
+                            return true;
+                        }
+                    }
+
+                    let meter = rt.state::<GasMeter>();
+
+                    if meter.is_metering() && !meter.consume(GAS_PER_INTERRUPT) {
+                        debug!("{:?} interrupted, out of gas", rt);
+
+                        return true;
+                    }
+
+                    match interrupts.handler.get().map(|func| func(rt)) {
+                        Some(Interrupt::Break) => true,
+                        _ => false,
+                    }
+                })
+                .to_bool();
+
+            // the background operations started by the interrupted scripts are cancelled as well
+            if interrupted != 0 {
+                rt.cancel_operations();
+            }
 
-                ffi::JS_SetInterruptHandler(self.as_ptr(), Some(stub), func as *mut _)
+            interrupted
+        }
+
+        let interrupts = self.state::<Interrupts>();
+        #[cfg(not(target_arch = "wasm32"))]
+        let watched = watchdog::is_watched(self);
+        #[cfg(target_arch = "wasm32")]
+        let watched = false;
+
+        unsafe {
+            if interrupts.handler.get().is_some()
+                || interrupts.time_limit.get().is_some()
+                || self.state::<GasMeter>().is_metering()
+                || watched
+            {
+                ffi::JS_SetInterruptHandler(self.as_ptr(), Some(stub), null_mut())
             } else {
                 ffi::JS_SetInterruptHandler(self.as_ptr(), None, null_mut())
             }
         }
     }
+
+    /// Get the Rust state of type `T` attached to the `Runtime`.
+    pub(crate) fn state<T: Any + Send + Default>(&self) -> &T {
+        unsafe { state::RUNTIME.get_or_attach(self.as_ptr()) }.get_or_default()
+    }
+}
+
+#[derive(Default)]
+struct Interrupts {
+    handler: Cell<InterruptHandler>,
+    time_limit: Cell<Option<Duration>>,
+    deadline: Cell<Option<Instant>>,
+    depth: Cell<usize>,
+}
+
+/// The deadline of the outermost evaluation, clear it when dropped.
+pub(crate) struct Deadline<'a> {
+    rt: &'a RuntimeRef,
+    outermost: bool,
+}
+
+impl Drop for Deadline<'_> {
+    fn drop(&mut self) {
+        let interrupts = self.rt.state::<Interrupts>();
+
+        interrupts.depth.set(interrupts.depth.get() - 1);
+
+        if self.outermost {
+            interrupts.deadline.set(None);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            watchdog::end_evaluation(self.rt);
+
+            #[cfg(feature = "profiler")]
+            crate::debugger::profiler::end_evaluation(self.rt);
+        }
+    }
 }
 
 /// Interrupt the execution code.
@@ -0,0 +1,441 @@
+use std::fmt;
+use std::time::Duration;
+
+use failure::Error;
+
+use crate::{CFunction, Context, ContextRef, Eval, Local, NewValue, RuntimeRef, Value};
+
+bitflags! {
+    /// The intrinsic objects to be added to the sandbox context.
+    pub struct Intrinsics: u32 {
+        /// `Object`, `Function`, `Array`, `Error`, `Math` etc.
+        const BASE_OBJECTS = 1 << 0;
+        /// `Date`
+        const DATE = 1 << 1;
+        /// the internal evaluator which is required to evaluate scripts from source code.
+        const EVAL = 1 << 2;
+        /// `String.prototype.normalize`
+        const STRING_NORMALIZE = 1 << 3;
+        /// the regular expression compiler
+        const REGEXP_COMPILER = 1 << 4;
+        /// `RegExp`
+        const REGEXP = 1 << 5;
+        /// `JSON`
+        const JSON = 1 << 6;
+        /// `Proxy` (`Reflect` belongs to the base objects)
+        const PROXY = 1 << 7;
+        /// `Map`, `Set`, `WeakMap` and `WeakSet`
+        const MAP_SET = 1 << 8;
+        /// `ArrayBuffer`, `DataView` and typed arrays
+        const TYPED_ARRAYS = 1 << 9;
+        /// `Promise`
+        const PROMISE = 1 << 10;
+
+        /// all the intrinsic objects
+        const ALL = Self::BASE_OBJECTS.bits | Self::DATE.bits | Self::EVAL.bits |
+            Self::STRING_NORMALIZE.bits | Self::REGEXP_COMPILER.bits | Self::REGEXP.bits |
+            Self::JSON.bits | Self::PROXY.bits | Self::MAP_SET.bits |
+            Self::TYPED_ARRAYS.bits | Self::PROMISE.bits;
+    }
+}
+
+impl Default for Intrinsics {
+    fn default() -> Self {
+        Intrinsics::ALL
+    }
+}
+
+type HostFunction = Box<dyn Fn(&ContextRef) -> Result<Local<Value>, Error>>;
+
+/// `Sandbox` assembles a locked-down `Context` for the untrusted code.
+///
+/// The sandbox context only contains the whitelisted intrinsic objects and host functions,
+/// the standard helpers (e.g. `print`) and the `std`/`os` modules are never added.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use qjs::{Eval, Runtime, Sandbox};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Sandbox::pure_compute()
+///     .with_memory_limit(16 * 1024 * 1024)
+///     .with_time_limit(Duration::from_secs(1))
+///     .build(&rt)
+///     .unwrap();
+///
+/// assert_eq!(ctxt.eval::<_, i32>("1+2", Eval::GLOBAL).unwrap(), Some(3));
+/// assert_eq!(ctxt.eval::<_, String>("typeof Date", Eval::GLOBAL).unwrap(), Some("undefined".to_owned()));
+/// ```
+pub struct Sandbox {
+    intrinsics: Intrinsics,
+    allow_eval: bool,
+    freeze_intrinsics: bool,
+    memory_limit: Option<usize>,
+    stack_size: Option<usize>,
+    time_limit: Option<Duration>,
+    functions: Vec<(String, HostFunction)>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Sandbox {
+            intrinsics: Intrinsics::default(),
+            allow_eval: true,
+            freeze_intrinsics: false,
+            memory_limit: None,
+            stack_size: None,
+            time_limit: None,
+            functions: vec![],
+        }
+    }
+}
+
+impl fmt::Debug for Sandbox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sandbox")
+            .field("intrinsics", &self.intrinsics)
+            .field("allow_eval", &self.allow_eval)
+            .field("freeze_intrinsics", &self.freeze_intrinsics)
+            .field("memory_limit", &self.memory_limit)
+            .field("stack_size", &self.stack_size)
+            .field("time_limit", &self.time_limit)
+            .field(
+                "functions",
+                &self
+                    .functions
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Sandbox {
+    /// Create a sandbox with all the intrinsic objects.
+    pub fn new() -> Self {
+        Sandbox::default()
+    }
+
+    /// The pure computation preset.
+    ///
+    /// The non-deterministic or dynamic objects (`Date`, `Proxy` and `Promise`) are stripped,
+    /// the code generation from strings is disabled, and all the intrinsic objects are frozen.
+    pub fn pure_compute() -> Self {
+        Sandbox::new()
+            .with_intrinsics(
+                Intrinsics::ALL - Intrinsics::DATE - Intrinsics::PROXY - Intrinsics::PROMISE,
+            )
+            .without_eval()
+            .with_frozen_intrinsics()
+    }
+
+    /// The preset which forbids `eval`, `new Function` and dynamic import.
+    pub fn no_eval() -> Self {
+        Sandbox::new().without_eval()
+    }
+
+    /// The preset which strips the `Proxy` object, `Reflect` is kept as one of the base objects.
+    pub fn no_proxy() -> Self {
+        Sandbox::new().with_intrinsics(Intrinsics::ALL - Intrinsics::PROXY)
+    }
+
+    /// The preset which freezes all the intrinsic objects and their prototypes, including the hidden ones.
+    pub fn frozen_intrinsics() -> Self {
+        Sandbox::new().with_frozen_intrinsics()
+    }
+
+    /// Set the intrinsic objects to be added to the sandbox context.
+    pub fn with_intrinsics(mut self, intrinsics: Intrinsics) -> Self {
+        self.intrinsics = intrinsics | Intrinsics::BASE_OBJECTS;
+        self
+    }
+
+    /// Disable the code generation from strings in the sandbox context.
+    ///
+    /// see `ContextRef::disable_code_generation`
+    pub fn without_eval(mut self) -> Self {
+        self.allow_eval = false;
+        self
+    }
+
+    /// Freeze all the intrinsic objects reachable from the global object and their prototypes,
+    /// including the hidden ones, e.g. `%GeneratorFunction%` and `%ArrayIteratorPrototype%`.
+    ///
+    /// The global object itself is not frozen, so the scripts could still define the global variables.
+    pub fn with_frozen_intrinsics(mut self) -> Self {
+        self.freeze_intrinsics = true;
+        self
+    }
+
+    /// Set the memory limit of the runtime.
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Set the maximum system stack size of the context.
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = Some(stack_size);
+        self
+    }
+
+    /// Set the maximum execution time of each evaluation.
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Whitelist a host function which will be added to the global object.
+    pub fn with_function<T: NewValue + 'static>(
+        mut self,
+        name: &str,
+        func: CFunction<T>,
+        length: usize,
+    ) -> Self {
+        let func_name = name.to_owned();
+
+        self.functions.push((
+            name.to_owned(),
+            Box::new(move |ctxt| ctxt.new_c_function(func, Some(&func_name), length)),
+        ));
+        self
+    }
+
+    /// Build a sandbox context in the runtime.
+    ///
+    /// The memory and time limits are applied to the whole runtime.
+    pub fn build(&self, rt: &RuntimeRef) -> Result<Context, Error> {
+        debug!("build sandbox: {:?}", self);
+
+        if let Some(limit) = self.memory_limit {
+            rt.set_memory_limit(Some(limit));
+        }
+        if let Some(limit) = self.time_limit {
+            rt.set_time_limit(Some(limit));
+        }
+
+        let mut builder = Context::builder(rt).with_base_objects();
+
+        if self.intrinsics.contains(Intrinsics::DATE) {
+            builder = builder.with_date();
+        }
+        if self.intrinsics.contains(Intrinsics::EVAL) {
+            builder = builder.with_eval();
+        }
+        if self.intrinsics.contains(Intrinsics::STRING_NORMALIZE) {
+            builder = builder.with_string_normalize();
+        }
+        if self.intrinsics.contains(Intrinsics::REGEXP_COMPILER) {
+            builder = builder.with_regexp_compiler();
+        }
+        if self.intrinsics.contains(Intrinsics::REGEXP) {
+            builder = builder.with_regexp();
+        }
+        if self.intrinsics.contains(Intrinsics::JSON) {
+            builder = builder.with_json();
+        }
+        if self.intrinsics.contains(Intrinsics::PROXY) {
+            builder = builder.with_proxy();
+        }
+        if self.intrinsics.contains(Intrinsics::MAP_SET) {
+            builder = builder.with_map();
+        }
+        if self.intrinsics.contains(Intrinsics::TYPED_ARRAYS) {
+            builder = builder.with_typedarray();
+        }
+        if self.intrinsics.contains(Intrinsics::PROMISE) {
+            builder = builder.with_promise();
+        }
+
+        let ctxt = builder.build();
+
+        if let Some(stack_size) = self.stack_size {
+            ctxt.set_max_stack_size(stack_size);
+        }
+
+        {
+            let global = ctxt.global_object();
+
+            if !self.allow_eval {
+                ctxt.disable_code_generation()?;
+            }
+
+            if self.freeze_intrinsics {
+                freeze_intrinsics(&ctxt, &global, self.intrinsics)?;
+            }
+
+            for (name, func) in &self.functions {
+                global.set_property(name.as_str(), func(&ctxt)?)?;
+            }
+        }
+
+        ctxt.record_baseline()?;
+
+        Ok(ctxt)
+    }
+}
+
+/// The scripts to reach the hidden intrinsics, which are not reachable from the global object,
+/// e.g. `%GeneratorFunction%`, `%ArrayIteratorPrototype%` and `%AsyncFunction%`.
+const HIDDEN_INTRINSICS: &[(Intrinsics, &str)] = &[
+    (Intrinsics::BASE_OBJECTS, "(function* () {})"),
+    (Intrinsics::BASE_OBJECTS, "[][Symbol.iterator]()"),
+    (Intrinsics::BASE_OBJECTS, "''[Symbol.iterator]()"),
+    (Intrinsics::REGEXP, "/(?:)/[Symbol.matchAll]('')"),
+    (Intrinsics::MAP_SET, "new Map().entries()"),
+    (Intrinsics::MAP_SET, "new Set().values()"),
+    (Intrinsics::PROMISE, "(async function () {})"),
+    (Intrinsics::PROMISE, "(async function* () {})"),
+];
+
+/// Freeze all the intrinsic objects reachable from the global object and the hidden intrinsics,
+/// including their prototypes, the global object itself is not frozen.
+fn freeze_intrinsics(
+    ctxt: &ContextRef,
+    global: &Local<Value>,
+    intrinsics: Intrinsics,
+) -> Result<(), Error> {
+    let mut roots = vec![];
+
+    for name in global.get_own_property_names()?.unwrap_or_default() {
+        roots.extend(ctxt.get_property(global, name));
+    }
+
+    // the hidden intrinsics are only reachable from the instances, which are created by the scripts.
+    if intrinsics.contains(Intrinsics::EVAL) {
+        for &(required, script) in HIDDEN_INTRINSICS {
+            if intrinsics.contains(required) {
+                roots.push(ctxt.eval_script(script, "<sandbox>", Eval::GLOBAL)?);
+            }
+        }
+    }
+
+    ctxt.freeze_reachable(roots, &[global], true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{ErrorKind, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn sandbox() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Sandbox::pure_compute()
+            .with_function(
+                "hello",
+                |ctxt, _this, args| {
+                    format!(
+                        "hello {}",
+                        ctxt.to_cstring(&args[0]).unwrap().to_string_lossy()
+                    )
+                },
+                1,
+            )
+            .build(&rt)
+            .unwrap();
+
+        for name in &["Date", "Proxy", "Promise", "print", "std"] {
+            assert_eq!(
+                ctxt.eval::<_, String>(format!("typeof {}", name).as_str(), Eval::GLOBAL)
+                    .unwrap(),
+                Some("undefined".to_owned()),
+                "{}",
+                name
+            );
+        }
+
+        assert_eq!(
+            ctxt.eval::<_, String>("typeof Reflect", Eval::GLOBAL)
+                .unwrap(),
+            Some("object".to_owned())
+        );
+
+        assert!(ctxt.is_code_generation_disabled());
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "Object.isFrozen(Array) && Object.isFrozen(Array.prototype)",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+        // the hidden intrinsics are frozen as well
+        for intrinsic in &[
+            "Object.getPrototypeOf(function* () {})",
+            "Object.getPrototypeOf(function* () {}).constructor",
+            "Object.getPrototypeOf(Object.getPrototypeOf((function* () {})()))",
+            "Object.getPrototypeOf([][Symbol.iterator]())",
+            "Object.getPrototypeOf(Object.getPrototypeOf([][Symbol.iterator]()))",
+            "Object.getPrototypeOf(Int8Array)",
+            "Object.getPrototypeOf(Int8Array.prototype)",
+            "Object.getPrototypeOf(new Map().entries())",
+            "Object.prototype",
+            "Function.prototype",
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, bool>(
+                    format!("Object.isFrozen({})", intrinsic).as_str(),
+                    Eval::GLOBAL
+                )
+                .unwrap(),
+                Some(true),
+                "{}",
+                intrinsic
+            );
+        }
+        assert_eq!(
+            ctxt.eval::<_, bool>(
+                "Object.getPrototypeOf([][Symbol.iterator]()).next = null; \
+                 typeof [][Symbol.iterator]().next === 'function'",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(true)
+        );
+
+        // the global object is still extensible
+        assert_eq!(
+            ctxt.eval::<_, i32>("var answer = 42; answer", Eval::GLOBAL)
+                .unwrap(),
+            Some(42)
+        );
+
+        assert_eq!(
+            ctxt.eval::<_, String>("hello('world')", Eval::GLOBAL)
+                .unwrap(),
+            Some("hello world".to_owned())
+        );
+    }
+
+    #[test]
+    fn time_limit() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Sandbox::new()
+            .with_time_limit(Duration::from_millis(50))
+            .build(&rt)
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, ()>("while(true) {}", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "interrupted"
+        );
+
+        assert_eq!(ctxt.eval::<_, i32>("1+2", Eval::GLOBAL).unwrap(), Some(3));
+    }
+}
@@ -0,0 +1,333 @@
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::{c_char, c_int, c_void};
+use std::time::Instant;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+#[cfg(feature = "leak-detection")]
+use crate::LiveHandle;
+use crate::{ffi, Context, Runtime, RuntimeRef};
+
+/// The maximum number of the pending jobs executed in each draining on shutdown,
+/// so a promise which requeues itself can't hang the shutdown.
+pub const MAX_SHUTDOWN_JOBS: usize = 10_000;
+
+type ShutdownHook = Box<dyn FnOnce(&RuntimeRef) + Send>;
+
+/// The hooks called by `Runtime::shutdown`.
+#[derive(Default)]
+struct ShutdownHooks(RefCell<Vec<ShutdownHook>>);
+
+/// The report of `Runtime::shutdown`.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// the number of the pending jobs executed
+    pub executed_jobs: usize,
+    /// the errors of the pending jobs
+    pub job_errors: Vec<Error>,
+    /// the pending jobs were abandoned, after `MAX_SHUTDOWN_JOBS` jobs executed or the time limit exceeded
+    pub abandoned_jobs: bool,
+    /// the number of the shutdown hooks called
+    pub called_hooks: usize,
+    /// the number of the contexts which are not freed
+    pub live_contexts: usize,
+    /// the class names and the reference counts of the objects alive after GC
+    pub live_objects: Vec<(String, usize)>,
+    /// the leaked handles with their creation locations and backtraces
+    #[cfg(feature = "leak-detection")]
+    pub live_handles: Vec<LiveHandle>,
+}
+
+impl ShutdownReport {
+    /// Returns `true` if all the contexts and objects were freed.
+    pub fn is_clean(&self) -> bool {
+        self.live_contexts == 0 && self.live_objects.is_empty()
+    }
+}
+
+impl RuntimeRef {
+    /// Register a hook called by `Runtime::shutdown`, the hooks are called in the reverse order of registration.
+    pub fn on_shutdown<F: FnOnce(&RuntimeRef) + Send + 'static>(&self, hook: F) {
+        self.state::<ShutdownHooks>()
+            .0
+            .borrow_mut()
+            .push(Box::new(hook));
+    }
+
+    /// Returns the number of the contexts which are not freed.
+    pub fn live_contexts(&self) -> usize {
+        unsafe { ffi::JS_CountLiveContexts(self.as_ptr()) as usize }
+    }
+
+    /// Returns the class names and the reference counts of the live objects.
+    pub fn live_objects(&self) -> Vec<(String, usize)> {
+        unsafe extern "C" fn collect(
+            opaque: *mut c_void,
+            class_name: *const c_char,
+            ref_count: c_int,
+        ) {
+            let objects = &mut *(opaque as *mut Vec<(String, usize)>);
+
+            objects.push((
+                CStr::from_ptr(class_name).to_string_lossy().into_owned(),
+                ref_count as usize,
+            ));
+        }
+
+        let mut objects = vec![];
+
+        unsafe {
+            ffi::JS_ForEachLiveObject(
+                self.as_ptr(),
+                Some(collect),
+                &mut objects as *mut Vec<_> as *mut _,
+            )
+        };
+
+        objects
+    }
+
+    fn drain_pending_jobs(&self, report: &mut ShutdownReport) {
+        let deadline = self.time_limit().map(|limit| Instant::now() + limit);
+        let mut executed = 0;
+
+        while self.is_job_pending() {
+            if executed >= MAX_SHUTDOWN_JOBS
+                || deadline.map_or(false, |deadline| deadline <= Instant::now())
+            {
+                warn!(
+                    "{:?} abandon the pending jobs on shutdown, {} jobs executed",
+                    self, executed
+                );
+
+                report.abandoned_jobs = true;
+                break;
+            }
+
+            executed += 1;
+
+            match self.execute_pending_job() {
+                Ok(Some(_)) => report.executed_jobs += 1,
+                Ok(None) => break,
+                Err(err) => {
+                    warn!("{:?} pending job failed on shutdown, {}", self, err);
+
+                    report.executed_jobs += 1;
+                    report.job_errors.push(err);
+                }
+            }
+        }
+    }
+}
+
+impl Runtime {
+    /// Shutdown the runtime and its contexts in order, instead of aborting on the leaked objects when it is dropped.
+    ///
+    /// The pending jobs are executed, the shutdown hooks are called, then the contexts are freed
+    /// in the given order and the garbage are collected. The runtime is freed if all the contexts and objects
+    /// were freed, otherwise the leaks are reported and the runtime is leaked.
+    ///
+    /// The pending jobs are abandoned after `MAX_SHUTDOWN_JOBS` jobs executed,
+    /// or the time limit of the runtime exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.eval_script("Promise.resolve().then(() => {})", "<eval>", Eval::GLOBAL).unwrap();
+    ///
+    /// rt.on_shutdown(|_| println!("bye"));
+    ///
+    /// let report = rt.shutdown(vec![ctxt]);
+    ///
+    /// assert_eq!(report.executed_jobs, 1);
+    /// assert_eq!(report.called_hooks, 1);
+    /// assert!(report.is_clean());
+    /// ```
+    pub fn shutdown<I: IntoIterator<Item = Context>>(self, contexts: I) -> ShutdownReport {
+        debug!("{:?} shutdown", self);
+
+        let mut report = ShutdownReport::default();
+
+        self.drain_pending_jobs(&mut report);
+
+        loop {
+            let hook = self.state::<ShutdownHooks>().0.borrow_mut().pop();
+
+            match hook {
+                Some(hook) => {
+                    hook(&self);
+
+                    report.called_hooks += 1;
+                }
+                None => break,
+            }
+        }
+
+        self.drain_pending_jobs(&mut report);
+
+        for ctxt in contexts {
+            drop(ctxt);
+        }
+
+        self.run_gc();
+
+        report.live_contexts = self.live_contexts();
+
+        if report.live_contexts == 0 {
+            report.live_objects = self.live_objects();
+        }
+
+        if report.is_clean() {
+            drop(self);
+        } else {
+            #[cfg(feature = "leak-detection")]
+            {
+                report.live_handles = self.live_handles();
+
+                for handle in &report.live_handles {
+                    error!("{:?} leaked {}\n{}", self, handle, handle.backtrace);
+                }
+            }
+
+            error!(
+                "{:?} leaked {} contexts and {} objects on shutdown: {:?}",
+                self,
+                report.live_contexts,
+                report.live_objects.len(),
+                report.live_objects
+            );
+
+            mem::forget(self);
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::{Context, Eval, RejectionPolicy};
+
+    use super::*;
+
+    #[test]
+    fn shutdown() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        ctxt.set_rejection_policy(RejectionPolicy::Abort);
+        ctxt.eval_script(
+            "Promise.resolve().then(() => { throw new Error('boom') }); Promise.resolve().then(() => {})",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        for name in &["first", "second"] {
+            let calls = calls.clone();
+
+            rt.on_shutdown(move |_| calls.lock().unwrap().push(*name));
+        }
+
+        assert_eq!(rt.live_contexts(), 1);
+        assert!(!rt.live_objects().is_empty());
+
+        let report = rt.shutdown(vec![ctxt]);
+
+        assert_eq!(report.executed_jobs, 2);
+        assert_eq!(report.job_errors.len(), 1);
+        assert_eq!(report.called_hooks, 2);
+        assert_eq!(*calls.lock().unwrap(), vec!["second", "first"]);
+        assert!(report.is_clean(), "{:?}", report);
+
+        // the leaked value is reported instead of aborting
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let leaked = ctxt
+            .eval_script("({ leaked: true })", "<eval>", Eval::GLOBAL)
+            .unwrap()
+            .into_inner();
+
+        let report = rt.shutdown(vec![ctxt]);
+
+        assert!(!report.is_clean());
+        assert_eq!(report.live_contexts, 0);
+        // the prototype chain of the leaked object is alive too
+        assert!(report.live_objects.contains(&("Object".to_owned(), 1)));
+
+        let _ = leaked;
+    }
+
+    #[test]
+    fn abandon_jobs() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.eval_script(
+            "(function requeue() { Promise.resolve().then(requeue) })()",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let report = rt.shutdown(vec![ctxt]);
+
+        assert!(report.abandoned_jobs);
+        assert_eq!(report.executed_jobs, MAX_SHUTDOWN_JOBS * 2);
+        assert!(!report.is_clean());
+
+        // the time limit of the runtime is honored
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        rt.set_time_limit(Some(Duration::from_millis(10)));
+        ctxt.eval_script(
+            "(function requeue() { for (let i = 0; i < 1000; i++); Promise.resolve().then(requeue) })()",
+            "<eval>",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        let report = rt.shutdown(vec![ctxt]);
+
+        assert!(report.abandoned_jobs);
+        assert!(report.executed_jobs < MAX_SHUTDOWN_JOBS * 2);
+    }
+
+    #[cfg(feature = "leak-detection")]
+    #[test]
+    fn leaked_handles() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let leaked = ctxt
+            .eval_script("({ leaked: true })", "<eval>", Eval::GLOBAL)
+            .unwrap()
+            .into_inner();
+
+        let report = rt.shutdown(vec![ctxt]);
+
+        assert_eq!(report.live_handles.len(), 1);
+        assert_eq!(report.live_handles[0].kind, crate::HandleKind::Value);
+        assert_eq!(report.live_handles[0].location.file(), file!());
+
+        let _ = leaked;
+    }
+}
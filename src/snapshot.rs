@@ -0,0 +1,367 @@
+use failure::Error;
+
+use crate::{ContextRef, Eval, Local, ReadObj, Value, WriteObj};
+
+const SNAPSHOT_CODEC: &str = r#"(function () {
+    const TAG = '\u0000qjs';
+    const TYPED_ARRAYS = [
+        Int8Array, Uint8Array, Uint8ClampedArray, Int16Array, Uint16Array,
+        Int32Array, Uint32Array, Float32Array, Float64Array,
+    ];
+
+    if (typeof BigInt64Array === 'function') {
+        TYPED_ARRAYS.push(BigInt64Array, BigUint64Array);
+    }
+
+    const ERRORS = [Error, EvalError, RangeError, ReferenceError, SyntaxError, TypeError, URIError];
+    const hasOwn = (obj, key) => Object.prototype.hasOwnProperty.call(obj, key);
+    const isShared = (buf) => typeof SharedArrayBuffer === 'function' && buf instanceof SharedArrayBuffer;
+    const isSkipped = (value) => typeof value === 'function' || typeof value === 'symbol' || isShared(value);
+
+    function put(obj, key, value) {
+        Object.defineProperty(obj, key, { value, writable: true, enumerable: true, configurable: true });
+    }
+
+    function toBinary(buffer, offset, length) {
+        const bytes = new Uint8Array(buffer, offset, length);
+        let s = '';
+
+        for (let i = 0; i < bytes.length; i += 4096) {
+            s += String.fromCharCode.apply(null, bytes.subarray(i, i + 4096));
+        }
+
+        return s;
+    }
+
+    function fromBinary(s) {
+        const bytes = new Uint8Array(s.length);
+
+        for (let i = 0; i < s.length; i++) {
+            bytes[i] = s.charCodeAt(i);
+        }
+
+        return bytes.buffer;
+    }
+
+    function encode(value) {
+        const stack = new Set();
+
+        function encodeObject(value) {
+            if (Array.isArray(value)) {
+                const arr = new Array(value.length);
+
+                for (let i = 0; i < value.length; i++) {
+                    arr[i] = encodeValue(value[i]);
+                }
+
+                return arr;
+            }
+            if (value instanceof Date) {
+                return { [TAG]: 'Date', value: value.getTime() };
+            }
+            if (value instanceof RegExp) {
+                return { [TAG]: 'RegExp', source: value.source, flags: value.flags };
+            }
+            if (value instanceof Boolean || value instanceof Number || value instanceof String) {
+                return { [TAG]: 'Boxed', value: value.valueOf() };
+            }
+            if (value instanceof Map) {
+                const entries = [];
+
+                value.forEach((v, k) => {
+                    if (!isSkipped(k) && !isSkipped(v)) {
+                        entries.push([encodeValue(k), encodeValue(v)]);
+                    }
+                });
+
+                return { [TAG]: 'Map', entries };
+            }
+            if (value instanceof Set) {
+                const values = [];
+
+                value.forEach((v) => {
+                    if (!isSkipped(v)) {
+                        values.push(encodeValue(v));
+                    }
+                });
+
+                return { [TAG]: 'Set', values };
+            }
+            if (value instanceof ArrayBuffer) {
+                return { [TAG]: 'ArrayBuffer', bytes: toBinary(value, 0, value.byteLength) };
+            }
+            if (ArrayBuffer.isView(value)) {
+                const ctor = value instanceof DataView
+                    ? DataView
+                    : TYPED_ARRAYS.find((ctor) => value instanceof ctor);
+
+                if (!ctor || isShared(value.buffer)) {
+                    return undefined;
+                }
+
+                return { [TAG]: ctor.name, bytes: toBinary(value.buffer, value.byteOffset, value.byteLength) };
+            }
+            if (value instanceof Error) {
+                return { [TAG]: 'Error', name: String(value.name), message: String(value.message), stack: value.stack };
+            }
+
+            const obj = {};
+            let escaped = false;
+
+            for (const key of Object.keys(value)) {
+                const v = value[key];
+
+                if (!isSkipped(v)) {
+                    escaped = escaped || key === TAG;
+
+                    put(obj, key, encodeValue(v));
+                }
+            }
+
+            return escaped ? { [TAG]: 'Object', entries: Object.keys(obj).map((key) => [key, obj[key]]) } : obj;
+        }
+
+        function encodeValue(value) {
+            if (typeof value === 'bigint') {
+                return { [TAG]: 'BigInt', value: value.toString() };
+            }
+            if (isSkipped(value)) {
+                return undefined;
+            }
+            if (value === null || typeof value !== 'object') {
+                return value;
+            }
+            if (stack.has(value)) {
+                throw new TypeError('circular reference');
+            }
+
+            stack.add(value);
+
+            try {
+                return encodeObject(value);
+            } finally {
+                stack.delete(value);
+            }
+        }
+
+        return encodeValue(value);
+    }
+
+    function decode(value) {
+        if (value === null || typeof value !== 'object') {
+            return value;
+        }
+        if (Array.isArray(value)) {
+            for (let i = 0; i < value.length; i++) {
+                value[i] = decode(value[i]);
+            }
+
+            return value;
+        }
+        if (!hasOwn(value, TAG)) {
+            for (const key of Object.keys(value)) {
+                value[key] = decode(value[key]);
+            }
+
+            return value;
+        }
+
+        const tag = value[TAG];
+
+        switch (tag) {
+            case 'Object': {
+                const obj = {};
+
+                value.entries.forEach(([key, v]) => put(obj, key, decode(v)));
+
+                return obj;
+            }
+            case 'BigInt':
+                return BigInt(value.value);
+            case 'Date':
+                return new Date(value.value);
+            case 'RegExp':
+                return new RegExp(value.source, value.flags);
+            case 'Boxed':
+                return Object(value.value);
+            case 'Map':
+                return new Map(value.entries.map(([k, v]) => [decode(k), decode(v)]));
+            case 'Set':
+                return new Set(value.values.map(decode));
+            case 'ArrayBuffer':
+                return fromBinary(value.bytes);
+            case 'DataView':
+                return new DataView(fromBinary(value.bytes));
+            case 'Error': {
+                const ctor = ERRORS.find((ctor) => ctor.name === value.name) || Error;
+                const err = new ctor(value.message);
+
+                if (err.name !== value.name) {
+                    err.name = value.name;
+                }
+                if (value.stack !== undefined) {
+                    Object.defineProperty(err, 'stack', { value: value.stack, writable: true, configurable: true });
+                }
+
+                return err;
+            }
+            default: {
+                const ctor = TYPED_ARRAYS.find((ctor) => ctor.name === tag);
+
+                if (!ctor) {
+                    throw new TypeError(`unknown snapshot tag ${tag}`);
+                }
+
+                return new ctor(fromBinary(value.bytes));
+            }
+        }
+    }
+
+    return { encode, decode };
+})()"#;
+
+impl Local<'_, Value> {
+    /// Serialize the value to a binary snapshot, which could be restored by `ContextRef::deserialize_binary`.
+    ///
+    /// Besides the primitives, plain objects and arrays, the `Date`, `RegExp`, `Map`, `Set`, `Error`,
+    /// `ArrayBuffer`, `DataView`, typed arrays and boxed primitives are preserved.
+    ///
+    /// The functions, symbols and `SharedArrayBuffer` are skipped like `JSON.stringify`,
+    /// the getters are evaluated, and the shared references are duplicated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let value = ctxt
+    ///     .eval_script("({ date: new Date(0), map: new Map([[1, 'one']]), bytes: new Uint8Array([1, 2]) })", "<eval>", Eval::GLOBAL)
+    ///     .unwrap();
+    /// let snapshot = value.serialize_binary().unwrap();
+    ///
+    /// let other = Context::new(&rt);
+    /// let restored = other.deserialize_binary(&snapshot).unwrap();
+    ///
+    /// other.global_object().set_property("restored", restored).unwrap();
+    ///
+    /// assert_eq!(
+    ///     other.eval::<_, String>("restored.date.toISOString() + restored.map.get(1) + restored.bytes[1]", Eval::GLOBAL).unwrap(),
+    ///     Some("1970-01-01T00:00:00.000Zone2".to_owned())
+    /// );
+    /// ```
+    pub fn serialize_binary(&self) -> Result<Vec<u8>, Error> {
+        let codec = self.ctxt.snapshot_codec()?;
+        let encoded = self.ctxt.invoke(&codec, "encode", self)?;
+
+        self.ctxt.write_object(&encoded, WriteObj::empty())
+    }
+}
+
+impl ContextRef {
+    /// Deserialize a value from the binary snapshot of `Local::serialize_binary`.
+    pub fn deserialize_binary(&self, buf: &[u8]) -> Result<Local<Value>, Error> {
+        let encoded = self.read_object(buf, ReadObj::empty())?;
+
+        let codec = self.snapshot_codec()?;
+
+        self.invoke(&codec, "decode", encoded)
+    }
+
+    fn snapshot_codec(&self) -> Result<Local<Value>, Error> {
+        self.eval_script(SNAPSHOT_CODEC, "<snapshot>", Eval::GLOBAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn snapshot() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let value = ctxt
+            .eval_script(
+                r#"({
+                    n: -0,
+                    nan: NaN,
+                    u: undefined,
+                    s: 'hello 世界',
+                    arr: [1, , 'two', null],
+                    date: new Date(1500000000000),
+                    re: /a+b/gi,
+                    map: new Map([['k', { v: 1 }], [2, [3]]]),
+                    set: new Set([1, 'a']),
+                    buf: new Uint8Array([0, 128, 255]).buffer,
+                    f64: new Float64Array([1.5, -2]),
+                    view: new DataView(new Int16Array([-1, 2]).buffer),
+                    err: new RangeError('out of range'),
+                    boxed: new String('boxed'),
+                    func: function () {},
+                    sym: Symbol('sym'),
+                    get getter() { return 'got'; },
+                    '\u0000qjs': 'escaped',
+                    ['__proto__']: 'proto',
+                })"#,
+                "<eval>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+        let snapshot = value.serialize_binary().unwrap();
+
+        let other = Context::new(&rt);
+        let restored = other.deserialize_binary(&snapshot).unwrap();
+
+        other.global_object().set_property("v", restored).unwrap();
+
+        for check in &[
+            "Object.is(v.n, -0)",
+            "Number.isNaN(v.nan)",
+            "'u' in v && v.u === undefined",
+            "v.s === 'hello 世界'",
+            "v.arr.length === 4 && v.arr[0] === 1 && v.arr[1] === undefined && v.arr[2] === 'two' && v.arr[3] === null",
+            "v.date instanceof Date && v.date.getTime() === 1500000000000",
+            "v.re instanceof RegExp && v.re.source === 'a+b' && v.re.flags === 'gi'",
+            "v.map instanceof Map && v.map.get('k').v === 1 && v.map.get(2)[0] === 3",
+            "v.set instanceof Set && v.set.has(1) && v.set.has('a')",
+            "v.buf instanceof ArrayBuffer && new Uint8Array(v.buf).join() === '0,128,255'",
+            "v.f64 instanceof Float64Array && v.f64.join() === '1.5,-2'",
+            "v.view instanceof DataView && v.view.getInt16(0, true) === -1",
+            "v.err instanceof RangeError && v.err.message === 'out of range'",
+            "v.boxed instanceof String && v.boxed.valueOf() === 'boxed'",
+            "!('func' in v) && !('sym' in v)",
+            "v.getter === 'got'",
+            "v['\\u0000qjs'] === 'escaped'",
+            "Object.prototype.hasOwnProperty.call(v, '__proto__') && v.__proto__ === 'proto'",
+        ] {
+            assert_eq!(
+                other.eval::<_, bool>(*check, Eval::GLOBAL).unwrap(),
+                Some(true),
+                "{}",
+                check
+            );
+        }
+
+        let circular = ctxt
+            .eval_script("var o = {}; o.self = o; o", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        match circular
+            .serialize_binary()
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap()
+        {
+            ErrorKind::TypeError(msg, _) => assert_eq!(msg, "circular reference"),
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
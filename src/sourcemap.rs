@@ -0,0 +1,424 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use failure::{bail, format_err, Error};
+
+use crate::{BacktraceFrame, ContextRef, Local, RuntimeRef, SourcePosition, Value};
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const SOURCE_MAPPING_URL: &str = "sourceMappingURL=";
+const DATA_URL_BASE64: &str = ";base64,";
+
+fn base64_value(c: u8) -> Option<u32> {
+    BASE64_CHARS.iter().position(|&b| b == c).map(|n| n as u32)
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::with_capacity(s.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut n = 0;
+
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        bits = (bits << 6)
+            | base64_value(c).ok_or_else(|| format_err!("invalid base64 `{}`", c as char))?;
+        n += 6;
+
+        if n >= 8 {
+            n -= 8;
+            buf.push((bits >> n) as u8);
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Decode the Base64 VLQ values of a segment.
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, Error> {
+    let mut values = vec![];
+    let mut value = 0i64;
+    let mut shift = 0;
+
+    for c in segment.bytes() {
+        let digit =
+            base64_value(c).ok_or_else(|| format_err!("invalid VLQ `{}`", c as char))? as i64;
+
+        value += (digit & 0x1f) << shift;
+
+        if digit & 0x20 != 0 {
+            shift += 5;
+
+            if shift > 60 {
+                bail!("VLQ overflow");
+            }
+        } else {
+            values.push(if value & 1 != 0 {
+                -(value >> 1)
+            } else {
+                value >> 1
+            });
+            value = 0;
+            shift = 0;
+        }
+    }
+
+    if shift != 0 {
+        bail!("incomplete VLQ `{}`", segment)
+    }
+
+    Ok(values)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Mapping {
+    column: u32,
+    source: Option<(u32, u32, u32)>,
+}
+
+/// A source map (revision 3) which maps the positions of the generated code back to the original sources.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    names: Vec<String>,
+    lines: Vec<Vec<Mapping>>,
+}
+
+impl SourceMap {
+    /// Create a source map from the `sources`, `names` and the encoded `mappings`.
+    pub fn new(sources: Vec<String>, names: Vec<String>, mappings: &str) -> Result<Self, Error> {
+        let mut lines = vec![];
+        let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+
+        for mappings in mappings.split(';') {
+            let mut generated_column = 0i64;
+            let mut segments = vec![];
+
+            for segment in mappings.split(',').filter(|s| !s.is_empty()) {
+                let values = decode_vlq(segment)?;
+
+                generated_column += values[0];
+
+                let mapping = match values.len() {
+                    1 => None,
+                    4 | 5 => {
+                        source += values[1];
+                        line += values[2];
+                        column += values[3];
+
+                        if source < 0 || source as usize >= sources.len() || line < 0 || column < 0
+                        {
+                            bail!("invalid mapping `{}`", segment)
+                        }
+
+                        Some((source as u32, line as u32, column as u32))
+                    }
+                    _ => bail!("invalid mapping `{}`", segment),
+                };
+
+                if generated_column < 0 {
+                    bail!("invalid mapping `{}`", segment)
+                }
+
+                segments.push(Mapping {
+                    column: generated_column as u32,
+                    source: mapping,
+                });
+            }
+
+            segments.sort_by_key(|m| m.column);
+            lines.push(segments);
+        }
+
+        Ok(SourceMap {
+            sources,
+            names,
+            lines,
+        })
+    }
+
+    /// The original sources of the source map.
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// The symbol names of the source map.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the original position of the 1-based line and column in the generated code.
+    ///
+    /// The first mapping of the line is used if the column is unknown.
+    pub fn lookup(&self, line: u32, column: Option<u32>) -> Option<SourcePosition> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let mapping = match column {
+            Some(column) => {
+                let idx = segments.partition_point(|m| m.column < column);
+
+                segments.get(idx.checked_sub(1)?)?
+            }
+            None => segments.first()?,
+        };
+        let (source, line, column) = mapping.source?;
+
+        Some(SourcePosition {
+            file: self.sources[source as usize].clone(),
+            line: Some(line + 1),
+            column: Some(column + 1),
+        })
+    }
+}
+
+/// Returns the URL of the `//# sourceMappingURL=` comment in the source.
+pub fn source_mapping_url(source: &str) -> Option<&str> {
+    source.lines().rev().find_map(|line| {
+        let line = line.trim();
+        let url = line
+            .strip_prefix("//#")
+            .or_else(|| line.strip_prefix("//@"))?
+            .trim_start()
+            .strip_prefix(SOURCE_MAPPING_URL)?
+            .trim();
+
+        if url.is_empty() {
+            None
+        } else {
+            Some(url)
+        }
+    })
+}
+
+#[derive(Default)]
+struct SourceMaps(RefCell<HashMap<String, SourceMap>>);
+
+impl ContextRef {
+    /// Parse a source map from the JSON text.
+    pub fn parse_source_map(&self, json: &str) -> Result<SourceMap, Error> {
+        let map = self.parse_json(json, "<sourcemap>")?;
+
+        match map.get_property("version").and_then(|v| v.as_int()) {
+            Some(3) => {}
+            version => bail!("unsupported source map version {:?}", version),
+        }
+
+        let strings = |key: &str| -> Vec<String> {
+            map.get_property(key)
+                .map(|arr| array_of_strings(&arr))
+                .unwrap_or_default()
+        };
+        let root = map
+            .get_property("sourceRoot")
+            .filter(|v| v.is_string())
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let sources = strings("sources")
+            .into_iter()
+            .map(|s| {
+                if root.is_empty() || root.ends_with('/') {
+                    format!("{}{}", root, s)
+                } else {
+                    format!("{}/{}", root, s)
+                }
+            })
+            .collect();
+        let mappings = map
+            .get_property("mappings")
+            .filter(|v| v.is_string())
+            .ok_or_else(|| format_err!("missing `mappings`"))?
+            .to_string();
+
+        SourceMap::new(sources, strings("names"), &mappings)
+    }
+
+    /// Parse the inline source map of the `//# sourceMappingURL=data:...` comment in the source.
+    ///
+    /// Returns `None` if the source has no source map or the source map is not inline.
+    pub fn inline_source_map(&self, source: &str) -> Result<Option<SourceMap>, Error> {
+        match source_mapping_url(source) {
+            Some(url) if url.starts_with("data:") => {
+                let off = url
+                    .find(DATA_URL_BASE64)
+                    .ok_or_else(|| format_err!("inline source map should be base64 encoded"))?;
+                let json = String::from_utf8(base64_decode(&url[off + DATA_URL_BASE64.len()..])?)?;
+
+                self.parse_source_map(&json).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Register the inline source map of the source for the file, returns `true` if found.
+    pub fn register_inline_source_map(&self, filename: &str, source: &str) -> Result<bool, Error> {
+        Ok(match self.inline_source_map(source)? {
+            Some(map) => {
+                self.runtime().register_source_map(filename, map);
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+fn array_of_strings(arr: &Local<Value>) -> Vec<String> {
+    let len = arr
+        .get_property("length")
+        .and_then(|v| v.to_index())
+        .unwrap_or_default();
+
+    (0..len as u32)
+        .map(|i| {
+            arr.get_property(i)
+                .filter(|v| v.is_string())
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+impl RuntimeRef {
+    /// Associate a source map with the file of the evaluated script or module,
+    /// the frames of the backtraces in the file are mapped to the original sources.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// // function greet(name: string) {
+    /// //   throw new Error(`hello ${name}`);
+    /// // }
+    /// let map = ctxt
+    ///     .parse_source_map(r#"{"version":3,"sources":["greet.ts"],"names":[],"mappings":"AAAA;EACE"}"#)
+    ///     .unwrap();
+    ///
+    /// rt.register_source_map("greet.js", map);
+    ///
+    /// let err = ctxt
+    ///     .eval_script("function greet(name) {\n  throw new Error('hello ' + name);\n}\ngreet('qjs')", "greet.js", Eval::GLOBAL)
+    ///     .unwrap_err()
+    ///     .downcast::<ErrorKind>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(err.frames()[0].to_string(), "greet (greet.ts:2:3)");
+    /// ```
+    pub fn register_source_map(&self, filename: &str, map: SourceMap) {
+        trace!(
+            "{:?} register source map of `{}` with {} sources",
+            self,
+            filename,
+            map.sources.len()
+        );
+
+        self.state::<SourceMaps>()
+            .0
+            .borrow_mut()
+            .insert(filename.to_owned(), map);
+
+        self.update_stack_trace_hook();
+    }
+
+    /// Remove the source map of the file.
+    pub fn unregister_source_map(&self, filename: &str) -> Option<SourceMap> {
+        let map = self.state::<SourceMaps>().0.borrow_mut().remove(filename);
+
+        self.update_stack_trace_hook();
+
+        map
+    }
+
+    pub(crate) fn has_source_maps(&self) -> bool {
+        !self.state::<SourceMaps>().0.borrow().is_empty()
+    }
+
+    /// Map a position of the generated code to the original source, or `None` if not mapped.
+    pub fn map_position(&self, pos: &SourcePosition) -> Option<SourcePosition> {
+        self.state::<SourceMaps>()
+            .0
+            .try_borrow()
+            .ok()?
+            .get(&pos.file)?
+            .lookup(pos.line?, pos.column)
+    }
+
+    pub(crate) fn map_frames(&self, frames: Vec<BacktraceFrame>) -> Vec<BacktraceFrame> {
+        frames
+            .into_iter()
+            .map(|mut frame| {
+                if let Some(pos) = frame
+                    .position
+                    .as_ref()
+                    .and_then(|pos| self.map_position(pos))
+                {
+                    frame.position = Some(pos);
+                }
+                frame
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn source_map() {
+        let _ = pretty_env_logger::try_init();
+
+        assert_eq!(decode_vlq("AAgBC").unwrap(), vec![0, 0, 16, 1]);
+        assert_eq!(decode_vlq("D").unwrap(), vec![-1]);
+        assert!(decode_vlq("g").is_err());
+        assert_eq!(base64_decode("cWpz").unwrap(), b"qjs");
+
+        let map = SourceMap::new(
+            vec!["a.ts".to_owned(), "b.ts".to_owned()],
+            vec![],
+            "AAAA,IAAI;;ACCA,GAAG",
+        )
+        .unwrap();
+        let pos = |file: &str, line, column| {
+            Some(SourcePosition {
+                file: file.to_owned(),
+                line: Some(line),
+                column: Some(column),
+            })
+        };
+
+        assert_eq!(map.lookup(1, Some(1)), pos("a.ts", 1, 1));
+        assert_eq!(map.lookup(1, Some(6)), pos("a.ts", 1, 5));
+        assert_eq!(map.lookup(1, None), pos("a.ts", 1, 1));
+        assert_eq!(map.lookup(2, Some(1)), None);
+        assert_eq!(map.lookup(3, Some(5)), pos("b.ts", 2, 8));
+        assert_eq!(map.lookup(4, Some(1)), None);
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        // {"version":3,"sourceRoot":"src","sources":["app.ts"],"names":[],"mappings":";AAEA;EACE"}
+        let source = "\nfunction run() {\n  null.x;\n}\nrun()\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,eyJ2ZXJzaW9uIjozLCJzb3VyY2VSb290Ijoic3JjIiwic291cmNlcyI6WyJhcHAudHMiXSwibmFtZXMiOltdLCJtYXBwaW5ncyI6IjtBQUVBO0VBQ0UifQ==\n";
+
+        assert_eq!(
+            source_mapping_url("x\n//# sourceMappingURL=app.js.map\n"),
+            Some("app.js.map")
+        );
+        assert_eq!(ctxt.inline_source_map("1 + 2").unwrap(), None);
+        assert!(ctxt.register_inline_source_map("app.js", source).unwrap());
+
+        let err = ctxt
+            .eval_script(source, "app.js", Eval::GLOBAL)
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.position(), pos("src/app.ts", 4, 3));
+        assert_eq!(err.frames()[1].position, pos("app.js", 5, 5));
+
+        assert!(rt.unregister_source_map("app.js").is_some());
+        assert!(ctxt.parse_source_map(r#"{"version":2}"#).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr::null_mut;
+
+use crate::ffi;
+
+/// The Rust states attached to a runtime or context, keyed by the type of the state.
+///
+/// The states are owned by the opaque pointer of the runtime or context,
+/// so the lookup doesn't contend with the other runtimes.
+#[derive(Default)]
+pub struct States {
+    states: RefCell<HashMap<TypeId, Box<dyn Any + Send>>>,
+    /// The userdata of the context, since the states take its opaque pointer.
+    pub userdata: Cell<*mut c_void>,
+}
+
+impl States {
+    /// Get the state of type `T`, create a default one if not exists.
+    pub fn get_or_default<T: Any + Send + Default>(&self) -> &T {
+        let found = self
+            .states
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .and_then(|state| state.downcast_ref::<T>())
+            .map(|state| state as *const T);
+
+        let state = match found {
+            Some(state) => state,
+            None => {
+                // create the state without the borrow, since it may access other states.
+                let state: Box<dyn Any + Send> = Box::new(T::default());
+
+                self.states
+                    .borrow_mut()
+                    .entry(TypeId::of::<T>())
+                    .or_insert(state)
+                    .downcast_ref::<T>()
+                    .unwrap() as *const T
+            }
+        };
+
+        // the state is boxed and never moved until the states are freed.
+        unsafe { &*state }
+    }
+
+    /// Drop all the states without the borrow, the state may access other states when drop.
+    pub fn clear(&self) {
+        loop {
+            let states = mem::take(&mut *self.states.borrow_mut());
+
+            if states.is_empty() {
+                break;
+            }
+
+            mem::drop(states);
+        }
+    }
+}
+
+impl Drop for States {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+/// The accessors of the opaque pointer of a runtime or context.
+pub struct Opaque<T> {
+    get: unsafe extern "C" fn(*mut T) -> *mut c_void,
+    set: unsafe extern "C" fn(*mut T, *mut c_void),
+}
+
+pub const RUNTIME: Opaque<ffi::JSRuntime> = Opaque {
+    get: ffi::JS_GetRuntimeOpaque,
+    set: ffi::JS_SetRuntimeOpaque,
+};
+
+pub const CONTEXT: Opaque<ffi::JSContext> = Opaque {
+    get: ffi::JS_GetContextOpaque,
+    set: ffi::JS_SetContextOpaque,
+};
+
+impl<T> Opaque<T> {
+    /// Get the states attached to the `owner`.
+    ///
+    /// # Safety
+    ///
+    /// The `owner` must be alive, the caller must ensure the returned reference doesn't outlive it.
+    pub unsafe fn get<'a>(&self, owner: *mut T) -> Option<&'a States> {
+        ((self.get)(owner) as *const States).as_ref()
+    }
+
+    /// Get the states attached to the `owner`, attach the new states if not exists.
+    ///
+    /// # Safety
+    ///
+    /// see `get`.
+    pub unsafe fn get_or_attach<'a>(&self, owner: *mut T) -> &'a States {
+        match self.get(owner) {
+            Some(states) => states,
+            None => {
+                let states = Box::into_raw(Box::new(States::default()));
+
+                (self.set)(owner, states as *mut _);
+
+                &*states
+            }
+        }
+    }
+
+    /// Free the states attached to the `owner`, they are dropped before detached.
+    ///
+    /// # Safety
+    ///
+    /// The `owner` must be alive, and the states must not be accessed after freed.
+    pub unsafe fn free(&self, owner: *mut T) {
+        if let Some(states) = self.get(owner) {
+            states.clear();
+
+            (self.set)(owner, null_mut());
+
+            mem::drop(Box::from_raw(states as *const States as *mut States));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::{runtime::Deadline, ContextRef};
+
+/// The resource accounting of a `Context`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// the cumulative time spent in the evaluations, function calls and jobs.
+    pub eval_time: Duration,
+    /// the peak memory allocated by the runtime, sampled after each evaluation.
+    pub peak_memory: usize,
+    /// the number of jobs executed.
+    pub jobs_executed: usize,
+    /// the number of the uncaught exceptions thrown to the host.
+    pub exceptions: usize,
+}
+
+#[derive(Debug, Default)]
+struct Accounting {
+    enabled: Cell<bool>,
+    depth: Cell<usize>,
+    stats: Cell<Stats>,
+}
+
+impl Accounting {
+    fn update<F: FnOnce(&mut Stats)>(&self, f: F) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+}
+
+/// Account the outermost evaluation of the context until dropped.
+pub(crate) struct Evaluation<'a> {
+    ctxt: &'a ContextRef,
+    started: Option<Instant>,
+    _deadline: Deadline<'a>,
+}
+
+impl Drop for Evaluation<'_> {
+    fn drop(&mut self) {
+        let accounting = self.ctxt.state::<Accounting>();
+
+        accounting.depth.set(accounting.depth.get() - 1);
+
+        if let Some(started) = self.started {
+            let elapsed = started.elapsed();
+            let memory = self.ctxt.runtime().memory_usage().malloc_size as usize;
+
+            accounting.update(|stats| {
+                stats.eval_time += elapsed;
+                stats.peak_memory = stats.peak_memory.max(memory);
+            });
+        }
+    }
+}
+
+impl ContextRef {
+    /// Enable or disable the resource accounting of the context.
+    ///
+    /// The peak memory is sampled with `RuntimeRef::memory_usage` after each evaluation,
+    /// which walks through the whole heap, so the accounting is disabled by default.
+    pub fn enable_stats(&self, enabled: bool) -> &Self {
+        self.state::<Accounting>().enabled.set(enabled);
+        self
+    }
+
+    /// Returns the resource accounting of the context.
+    pub fn stats(&self) -> Stats {
+        self.state::<Accounting>().stats.get()
+    }
+
+    /// Reset the resource accounting of the context, returns the previous one.
+    pub fn reset_stats(&self) -> Stats {
+        self.state::<Accounting>().stats.replace(Stats::default())
+    }
+
+    /// Start an evaluation, arms the deadline of the runtime and accounts the evaluation.
+    pub(crate) fn start_evaluation(&self) -> Evaluation {
+        let accounting = self.state::<Accounting>();
+        let depth = accounting.depth.replace(accounting.depth.get() + 1);
+        let started = if accounting.enabled.get() && depth == 0 {
+            Some(Instant::now())
+        } else {
+            None
+        };
+
+        Evaluation {
+            ctxt: self,
+            started,
+            _deadline: self.runtime().start_deadline(),
+        }
+    }
+
+    pub(crate) fn account_job(&self, elapsed: Duration) {
+        let accounting = self.state::<Accounting>();
+
+        if accounting.enabled.get() {
+            accounting.update(|stats| {
+                stats.eval_time += elapsed;
+                stats.jobs_executed += 1;
+            })
+        }
+    }
+
+    pub(crate) fn account_exception(&self) {
+        let accounting = self.state::<Accounting>();
+
+        if accounting.enabled.get() {
+            accounting.update(|stats| stats.exceptions += 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.eval::<_, ()>("var x = 1", Eval::GLOBAL).unwrap();
+
+        assert_eq!(ctxt.stats(), Stats::default());
+
+        ctxt.enable_stats(true);
+
+        ctxt.eval::<_, ()>(
+            "var a = []; for (var i=0; i<10000; i++) { a.push({i}) }; Promise.resolve(1).then(v => v)",
+            Eval::GLOBAL,
+        )
+        .unwrap();
+        ctxt.eval::<_, ()>("throw new Error('Whoops!')", Eval::GLOBAL)
+            .unwrap_err();
+
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+
+        let stats = ctxt.reset_stats();
+
+        assert!(stats.eval_time > Duration::from_secs(0));
+        assert!(stats.peak_memory > 0);
+        assert_eq!(stats.jobs_executed, 1);
+        assert_eq!(stats.exceptions, 1);
+
+        assert_eq!(ctxt.stats(), Stats::default());
+    }
+}
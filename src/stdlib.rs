@@ -1,10 +1,231 @@
 use std::ffi::CString;
+use std::os::raw::c_int;
 use std::ptr::NonNull;
+use std::slice;
 
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, ContextRef, ModuleDef, RuntimeRef};
+use crate::{
+    codegen::CODE_GENERATION_DISALLOWED, ffi, prop::Names, Capabilities, ContextRef, ErrorKind,
+    Eval, Local, ModuleDef, NewValue, PermissionDenied, Prop, RuntimeRef, Value,
+};
+
+/// The capabilities required by the functions of the `std` module.
+const STD_CAPABILITIES: &[(&str, Capabilities)] = &[
+    ("exit", Capabilities::PROCESS),
+    ("evalScript", Capabilities::empty()),
+    ("loadScript", Capabilities::FS_READ),
+    ("getenv", Capabilities::ENV),
+    ("urlGet", Capabilities::NET),
+    ("open", Capabilities::FS),
+    ("popen", Capabilities::PROCESS),
+    ("fdopen", Capabilities::FS),
+    ("tmpfile", Capabilities::FS_WRITE),
+    ("puts", Capabilities::FS_WRITE),
+    ("printf", Capabilities::FS_WRITE),
+];
+
+/// The capabilities required by the `FILE` objects of the standard streams exported by the `std` module.
+const STD_FILES: &[(&str, Capabilities)] = &[
+    ("in", Capabilities::FS_READ),
+    ("out", Capabilities::FS_WRITE),
+    ("err", Capabilities::FS_WRITE),
+];
+
+/// The functions which evaluate the scripts, they are denied if the code generation was disabled.
+const CODE_GENERATORS: &[&str] = &["std.evalScript", "std.loadScript", "__loadScript"];
+
+/// The global helper which loads and evaluates a script file.
+const LOAD_SCRIPT: &str = "__loadScript";
+
+/// The capabilities required by the functions of the `os` module.
+const OS_CAPABILITIES: &[(&str, Capabilities)] = &[
+    ("open", Capabilities::FS),
+    ("close", Capabilities::FS),
+    ("seek", Capabilities::FS),
+    ("read", Capabilities::FS_READ),
+    ("write", Capabilities::FS_WRITE),
+    ("isatty", Capabilities::FS_READ),
+    ("ttyGetWinSize", Capabilities::FS_READ),
+    ("ttySetRaw", Capabilities::FS_WRITE),
+    ("setReadHandler", Capabilities::FS_READ),
+    ("setWriteHandler", Capabilities::FS_WRITE),
+    ("remove", Capabilities::FS_WRITE),
+    ("rename", Capabilities::FS_WRITE),
+    ("signal", Capabilities::PROCESS),
+    ("setTimeout", Capabilities::TIME),
+    ("clearTimeout", Capabilities::TIME),
+    ("getcwd", Capabilities::FS_READ),
+    ("realpath", Capabilities::FS_READ),
+    ("mkdir", Capabilities::FS_WRITE),
+    ("stat", Capabilities::FS_READ),
+    ("lstat", Capabilities::FS_READ),
+    ("symlink", Capabilities::FS_WRITE),
+    ("readlink", Capabilities::FS_READ),
+    ("readdir", Capabilities::FS_READ),
+    ("utimes", Capabilities::FS_WRITE),
+    ("exec", Capabilities::PROCESS),
+    ("waitpid", Capabilities::PROCESS),
+    ("pipe", Capabilities::PROCESS),
+    ("kill", Capabilities::PROCESS),
+    ("sleep", Capabilities::TIME),
+    ("dup", Capabilities::FS),
+    ("dup2", Capabilities::FS),
+];
+
+/// The builder to initialize the `std` and `os` modules of quickjs-libc.
+///
+/// The functions of the modules which require the capabilities are guarded,
+/// the call will throw an `Error` if the capabilities were not enabled in the builder,
+/// or denied by the `Permissions` of the context. The raw file descriptors of the `os` module,
+/// and the methods of the standard streams `std.in`, `std.out` and `std.err`, require the `FS` capabilities
+/// as well, since they could reach the files opened by the host. `std.evalScript` and `std.loadScript`
+/// throw an `EvalError` if the code generation was disabled in the context, so does the global
+/// `__loadScript` added with the helpers.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Capabilities, Context, Eval, Runtime, StdLib};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// StdLib::new()
+///     .with_std()
+///     .with_os()
+///     .with_capabilities(Capabilities::FS_READ)
+///     .init(&ctxt)
+///     .unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     r#"
+/// import * as os from 'os';
+///
+/// os.getcwd();
+/// os.exec(['ls']);
+/// "#,
+///     Eval::MODULE,
+/// )
+/// .unwrap_err();
+/// ```
+#[derive(Clone, Debug)]
+pub struct StdLib {
+    std: bool,
+    os: bool,
+    helpers: Option<Vec<String>>,
+    capabilities: Capabilities,
+}
+
+impl Default for StdLib {
+    fn default() -> Self {
+        StdLib::new()
+    }
+}
+
+impl StdLib {
+    /// Create a builder without any module, all the capabilities are enabled.
+    pub fn new() -> Self {
+        StdLib {
+            std: false,
+            os: false,
+            helpers: None,
+            capabilities: Capabilities::all(),
+        }
+    }
+
+    /// Initialize the `std` module.
+    pub fn with_std(mut self) -> Self {
+        self.std = true;
+        self
+    }
+
+    /// Initialize the `os` module.
+    pub fn with_os(mut self) -> Self {
+        self.os = true;
+        self
+    }
+
+    /// Add the global helpers, including `print`, `console.log` and `scriptArgs`.
+    pub fn with_helpers<I: IntoIterator<Item = S>, S: Into<String>>(mut self, args: I) -> Self {
+        self.helpers = Some(args.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the capabilities of the guarded functions.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Initialize the modules in the context.
+    pub fn init(self, ctxt: &ContextRef) -> Result<(), Error> {
+        if let Some(args) = self.helpers {
+            ctxt.std_add_helpers(args)?;
+            ctxt.guard_load_script(self.capabilities)?;
+        }
+
+        if self.std {
+            let m = ctxt.init_module_std()?;
+
+            ctxt.guard_module_exports(m, "std", STD_CAPABILITIES, STD_FILES, self.capabilities)?;
+        }
+
+        if self.os {
+            let m = ctxt.init_module_os()?;
+
+            ctxt.guard_module_exports(m, "os", OS_CAPABILITIES, &[], self.capabilities)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Call the guarded function of the module, the `data` contains the original function,
+/// the qualified name and the capabilities missing from the builder.
+unsafe extern "C" fn guarded_function(
+    ctx: *mut ffi::JSContext,
+    this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let data = slice::from_raw_parts(data, 3);
+        let name = ctxt.clone_value(&Value::from(data[1])).to_string();
+        let required = Capabilities::from_bits_truncate(magic as u32);
+        let missing = Capabilities::from_bits_truncate(
+            Value::from(data[2]).as_int().unwrap_or_default() as u32,
+        );
+
+        if CODE_GENERATORS.contains(&name.as_str()) && ctxt.is_code_generation_disabled() {
+            return ErrorKind::EvalError(CODE_GENERATION_DISALLOWED.to_owned(), None)
+                .new_value(ctxt);
+        }
+
+        let res = if missing.is_empty() {
+            ctxt.check_capabilities(&name, required)
+        } else {
+            Err(PermissionDenied {
+                function: name,
+                missing,
+            })
+        };
+
+        match res {
+            Ok(_) => ffi::JS_Call(ctx, data[0], this_val, argc, argv),
+            Err(err) => {
+                debug!("{}", err);
+
+                ErrorKind::Error(err.to_string(), None).new_value(ctxt)
+            }
+        }
+    })
+}
 
 impl ContextRef {
     pub fn init_module_std(&self) -> Result<NonNull<ModuleDef>, Error> {
@@ -19,6 +240,176 @@ impl ContextRef {
         self.check_null(unsafe { ffi::js_init_module_os(self.as_ptr(), cstr!(os).as_ptr()) })
     }
 
+    /// Replace the exported functions of the module with the guarded functions,
+    /// and the exported objects with the objects of the guarded methods.
+    fn guard_module_exports(
+        &self,
+        m: NonNull<ModuleDef>,
+        module_name: &str,
+        exports: &[(&str, Capabilities)],
+        objects: &[(&str, Capabilities)],
+        capabilities: Capabilities,
+    ) -> Result<(), Error> {
+        const NAMESPACE: &str = "__qjs_stdlib_namespace__";
+
+        // evaluate the module to initialize the exports, and take its namespace.
+        self.eval_script(
+            format!(
+                "import * as ns from '{}'; globalThis.{} = ns;",
+                module_name, NAMESPACE
+            ),
+            "<stdlib>",
+            Eval::MODULE,
+        )?;
+
+        let global = self.global_object();
+        let ns = global
+            .get_property(NAMESPACE)
+            .ok_or_else(|| format_err!("`{}` module not loaded", module_name))?;
+
+        global.delete_property(NAMESPACE)?;
+
+        for &(name, required) in exports {
+            let func = match ns.get_property(name).filter(|func| func.is_function()) {
+                Some(func) => func,
+                None => continue,
+            };
+            let guarded = self.guard_function(
+                func,
+                format!("{}.{}", module_name, name),
+                name,
+                required,
+                capabilities,
+            )?;
+
+            self.set_module_export(m, name, guarded)?;
+        }
+
+        for &(name, required) in objects {
+            let obj = match ns.get_property(name).filter(|obj| obj.is_object()) {
+                Some(obj) => obj,
+                None => continue,
+            };
+            let proto = obj.get_prototype();
+            let guarded = self.bind(self.new_object());
+
+            // the methods are bound to the original object, e.g. a `FILE` of the standard streams
+            for method in self
+                .get_own_property_names(&proto, Names::STRING)?
+                .unwrap_or_default()
+            {
+                let method = method.to_string();
+                let func = match proto
+                    .get_property(method.as_str())
+                    .filter(|func| func.is_function() && method != "constructor")
+                {
+                    Some(func) => func,
+                    None => continue,
+                };
+                let bind = self
+                    .get_property(&func, "bind")
+                    .ok_or_else(|| format_err!("`bind` not found"))?;
+                let bound = self.call(&bind, Some(&func), [&obj])?;
+
+                guarded.set_property(
+                    method.as_str(),
+                    self.guard_function(
+                        bound,
+                        format!("{}.{}.{}", module_name, name, method),
+                        &method,
+                        required,
+                        capabilities,
+                    )?,
+                )?;
+            }
+
+            self.set_module_export(m, name, guarded)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a guarded function which calls the original function if the capabilities are allowed.
+    fn guard_function(
+        &self,
+        func: Local<Value>,
+        qualified_name: String,
+        name: &str,
+        required: Capabilities,
+        capabilities: Capabilities,
+    ) -> Result<Local<Value>, Error> {
+        let missing = required - capabilities;
+
+        trace!(
+            "guard `{}` with {:?}, missing {:?}",
+            qualified_name,
+            required,
+            missing
+        );
+
+        let guarded = self.new_c_function_data(
+            guarded_function,
+            self.get_property(&func, "length")
+                .and_then(|len| len.as_int())
+                .unwrap_or_default() as usize,
+            required.bits() as i32,
+            [
+                func.into_inner_untracked(),
+                self.new_value(qualified_name),
+                self.new_value(missing.bits() as i32),
+            ],
+        )?;
+
+        guarded.define_property_value("name", name, Prop::CONFIGURABLE)?;
+
+        Ok(guarded)
+    }
+
+    /// Replace the global `__loadScript` added by the helpers with the guarded function,
+    /// it reads the host files and evaluates them, as `std.loadScript` does.
+    fn guard_load_script(&self, capabilities: Capabilities) -> Result<(), Error> {
+        let global = self.global_object();
+        let func = match global
+            .get_property(LOAD_SCRIPT)
+            .filter(|func| func.is_function())
+        {
+            Some(func) => func,
+            None => return Ok(()),
+        };
+        let guarded = self.guard_function(
+            func,
+            LOAD_SCRIPT.to_owned(),
+            LOAD_SCRIPT,
+            Capabilities::FS_READ,
+            capabilities,
+        )?;
+
+        global.set_property(LOAD_SCRIPT, guarded)?;
+
+        Ok(())
+    }
+
+    fn set_module_export(
+        &self,
+        m: NonNull<ModuleDef>,
+        name: &str,
+        value: Local<Value>,
+    ) -> Result<(), Error> {
+        let export_name = CString::new(name)?;
+
+        self.check_error(unsafe {
+            ffi::JS_SetModuleExport(
+                self.as_ptr(),
+                m.as_ptr(),
+                export_name.as_ptr(),
+                value.into_inner_untracked().raw(),
+            )
+        })
+        .map(|_| ())
+    }
+
+    /// Add the global helpers of quickjs-libc, the `__loadScript` helper is not guarded,
+    /// use `StdLib::with_helpers` to add them for the untrusted code.
     pub fn std_add_helpers<I: IntoIterator<Item = S>, S: Into<Vec<u8>>>(
         &self,
         args: I,
@@ -53,3 +444,187 @@ impl RuntimeRef {
         unsafe { ffi::js_std_free_handlers(self.as_ptr()) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Permissions, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn stdlib() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        StdLib::new()
+            .with_std()
+            .with_os()
+            .with_capabilities(Capabilities::FS_READ | Capabilities::ENV)
+            .init(&ctxt)
+            .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+import * as std from 'std';
+import * as os from 'os';
+
+globalThis.cwd = os.getcwd()[0];
+globalThis.platform = os.platform;
+globalThis.getenv = std.getenv;
+globalThis.exec = os.exec;
+"#,
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("cwd", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            std::env::current_dir().unwrap().to_str().unwrap()
+        );
+        assert!(ctxt
+            .eval::<_, String>("platform", Eval::GLOBAL)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            ctxt.eval::<_, String>("getenv.name", Eval::GLOBAL).unwrap(),
+            Some("getenv".to_owned())
+        );
+        assert_eq!(
+            ctxt.eval::<_, i32>("exec.length", Eval::GLOBAL).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            ctxt.eval::<_, ()>("exec(['true'])", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "permission denied, `os.exec` requires PROCESS"
+        );
+
+        // the raw file descriptors and the standard streams are guarded
+        for &(script, msg) in &[
+            (
+                "os.write(1, new ArrayBuffer(1), 0, 1)",
+                "permission denied, `os.write` requires FS_WRITE",
+            ),
+            ("os.dup(1)", "permission denied, `os.dup` requires FS_WRITE"),
+            (
+                "std.out.puts('leaked')",
+                "permission denied, `std.out.puts` requires FS_WRITE",
+            ),
+            (
+                "std.err.printf('%s', 'leaked')",
+                "permission denied, `std.err.printf` requires FS_WRITE",
+            ),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, ()>(
+                    format!(
+                        "import * as std from 'std'; import * as os from 'os'; {}",
+                        script
+                    )
+                    .as_str(),
+                    Eval::MODULE
+                )
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+                msg
+            );
+        }
+        ctxt.eval::<_, ()>("import * as os from 'os'; os.isatty(0)", Eval::MODULE)
+            .unwrap();
+
+        // `std.evalScript` follows the code generation of the context
+        ctxt.eval::<_, ()>(
+            "import * as std from 'std'; globalThis.evalScript = std.evalScript",
+            Eval::MODULE,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, i32>("evalScript('1 + 2')", Eval::GLOBAL)
+                .unwrap(),
+            Some(3)
+        );
+
+        ctxt.disable_code_generation().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, ()>("evalScript('1 + 2')", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            CODE_GENERATION_DISALLOWED
+        );
+
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::ENV));
+
+        assert_eq!(
+            ctxt.eval::<_, ()>("getenv('HOME')", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "permission denied, `std.getenv` requires ENV"
+        );
+    }
+
+    #[test]
+    fn load_script() {
+        let _ = pretty_env_logger::try_init();
+
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+
+        std::io::Write::write_all(&mut script, b"globalThis.loaded = true").unwrap();
+
+        let path = script.path().to_str().unwrap().to_owned();
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        StdLib::new()
+            .with_helpers(Vec::<String>::new())
+            .with_capabilities(Capabilities::empty())
+            .init(&ctxt)
+            .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, ()>(format!("__loadScript({:?})", path).as_str(), Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "permission denied, `__loadScript` requires FS_READ"
+        );
+
+        let ctxt = Context::new(&rt);
+
+        StdLib::new()
+            .with_helpers(Vec::<String>::new())
+            .init(&ctxt)
+            .unwrap();
+
+        ctxt.disable_code_generation().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, ()>(format!("__loadScript({:?})", path).as_str(), Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            CODE_GENERATION_DISALLOWED
+        );
+        assert_eq!(
+            ctxt.eval::<_, bool>("typeof loaded === 'undefined'", Eval::GLOBAL)
+                .unwrap(),
+            Some(true)
+        );
+    }
+}
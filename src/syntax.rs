@@ -0,0 +1,159 @@
+use std::ffi::CString;
+use std::fmt;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef};
+
+/// A syntax error found in the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxDiagnostic {
+    /// the message of the error
+    pub message: String,
+    /// the filename of the source
+    pub file: String,
+    /// the 1-based line number of the error
+    pub line: u32,
+    /// the 1-based column number of the error, in characters
+    pub column: u32,
+}
+
+impl fmt::Display for SyntaxDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.line, self.column, self.message
+        )
+    }
+}
+
+impl ContextRef {
+    /// Check the syntax of a script or module source without running it.
+    ///
+    /// The source is parsed as a module if it's detected as a module by the `ModuleDetection` of the runtime,
+    /// the imported modules are not resolved.
+    ///
+    /// The parser stops at the first error, so at most one diagnostic is returned for now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// assert!(ctxt.check_syntax("1 + 2", "<input>").is_ok());
+    ///
+    /// let errs = ctxt.check_syntax("let x = 1;\nlet y = );", "<input>").unwrap_err();
+    ///
+    /// assert_eq!(errs.len(), 1);
+    /// assert_eq!((errs[0].line, errs[0].column), (2, 9));
+    /// ```
+    pub fn check_syntax(&self, source: &str, filename: &str) -> Result<(), Vec<SyntaxDiagnostic>> {
+        let flags = if self.runtime().is_module(source, filename) {
+            ffi::JS_EVAL_TYPE_MODULE
+        } else {
+            ffi::JS_EVAL_TYPE_GLOBAL
+        } | ffi::JS_EVAL_FLAG_PARSE_ONLY;
+
+        let diagnostic = |message: String| SyntaxDiagnostic {
+            message,
+            file: filename.to_owned(),
+            line: 0,
+            column: 0,
+        };
+
+        let input = CString::new(source).map_err(|err| vec![diagnostic(err.to_string())])?;
+        let input = input.to_bytes_with_nul();
+        let c_filename = CString::new(filename).map_err(|err| vec![diagnostic(err.to_string())])?;
+
+        trace!("check syntax of `{}`", filename);
+
+        let res = self.bind(unsafe {
+            ffi::JS_Eval(
+                self.as_ptr(),
+                input.as_ptr() as *const _,
+                input.len() - 1,
+                c_filename.as_ptr(),
+                flags as i32,
+            )
+        });
+
+        if !res.is_exception() {
+            return Ok(());
+        }
+
+        let exc = match self.get_exception() {
+            Some(exc) => exc,
+            None => return Err(vec![diagnostic("unknown error".to_owned())]),
+        };
+
+        let position = |name: &str| {
+            exc.get_property(name)
+                .and_then(|v| v.to_int32())
+                .map_or(0, |n| n.max(0) as u32)
+        };
+
+        let message = if exc.is_error() {
+            exc.get_property("message")
+                .map(|msg| msg.to_string())
+                .unwrap_or_default()
+        } else {
+            exc.to_string()
+        };
+
+        Err(vec![SyntaxDiagnostic {
+            message,
+            file: filename.to_owned(),
+            line: position("lineNumber"),
+            column: position("columnNumber"),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn check_syntax() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(ctxt.check_syntax("var x = 1; x += 2;", "test.js"), Ok(()));
+        assert_eq!(
+            ctxt.check_syntax(
+                "import { a } from './missing.mjs';\nexport default a;",
+                "test.mjs"
+            ),
+            Ok(())
+        );
+        assert!(ctxt.global_object().get_property("x").is_none());
+
+        assert_eq!(
+            ctxt.check_syntax("function f() {\n  return 'π' + ;\n}", "test.js"),
+            Err(vec![SyntaxDiagnostic {
+                message: "unexpected token in expression: ';'".to_owned(),
+                file: "test.js".to_owned(),
+                line: 2,
+                column: 16,
+            }])
+        );
+        assert_eq!(
+            ctxt.check_syntax("let s = 'abc", "test.js").unwrap_err()[0].to_string(),
+            "test.js:1:9: unexpected end of string"
+        );
+        assert_eq!(
+            ctxt.check_syntax("export let x = 1;\nexport let x = 2;", "test.mjs")
+                .unwrap_err()[0]
+                .line,
+            2
+        );
+    }
+}
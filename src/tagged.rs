@@ -0,0 +1,277 @@
+use std::os::raw::c_int;
+use std::slice;
+
+use failure::{bail, Error};
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ErrorKind, Local, NewValue, Value};
+
+type TagHandler =
+    Box<dyn for<'a> Fn(&'a ContextRef, TaggedTemplate<'a>) -> Result<Local<'a, Value>, Error>>;
+
+/// The static strings and interpolated values of a tagged template literal.
+///
+/// The template `` sql`select * from t where id = ${id}` `` has the strings `["select * from t where id = ", ""]`
+/// and the values `[id]`, the strings always have one more element than the values.
+#[derive(Debug)]
+pub struct TaggedTemplate<'a> {
+    /// the cooked strings, `None` if the string contains an invalid escape sequence
+    pub strings: Vec<Option<String>>,
+    /// the raw strings, as written in the source
+    pub raw: Vec<String>,
+    /// the interpolated values
+    pub values: Vec<Local<'a, Value>>,
+}
+
+impl TaggedTemplate<'_> {
+    /// Join the cooked strings with the placeholders generated by their index,
+    /// the raw string is used if the cooked one is invalid.
+    pub fn join_with<F: FnMut(usize) -> String>(&self, mut placeholder: F) -> String {
+        let mut s = String::new();
+
+        for (idx, (cooked, raw)) in self.strings.iter().zip(&self.raw).enumerate() {
+            if idx > 0 {
+                s.push_str(&placeholder(idx - 1));
+            }
+
+            s.push_str(cooked.as_ref().unwrap_or(raw));
+        }
+
+        s
+    }
+}
+
+struct Tag {
+    name: String,
+    handler: TagHandler,
+}
+
+impl ContextRef {
+    /// Create a tag function for the tagged template literals, which calls the handler
+    /// with the static strings and interpolated values separately.
+    ///
+    /// Calling the tag as a plain function throws a `TypeError`.
+    pub fn new_template_tag<F>(&self, name: &str, handler: F) -> Result<Local<Value>, Error>
+    where
+        F: for<'a> Fn(&'a ContextRef, TaggedTemplate<'a>) -> Result<Local<'a, Value>, Error>
+            + 'static,
+    {
+        let tag = Tag {
+            name: name.to_owned(),
+            handler: Box::new(handler),
+        };
+        let func = self.new_c_function_data(tag_stub, 1, 0, self.new_userdata(tag))?;
+
+        func.set_function_name(name)?;
+
+        Ok(func)
+    }
+
+    /// Register a tag function for the tagged template literals to the global object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.register_template_tag("sql", |ctxt, tpl| {
+    ///     let query = ctxt.bind(ctxt.new_object());
+    ///
+    ///     query.set_property("text", tpl.join_with(|idx| format!("${}", idx + 1)))?;
+    ///     query.set_property("values", tpl.values.len() as i32)?;
+    ///
+    ///     Ok(query)
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>(
+    ///         "var id = \"1'; drop table t; --\"; var q = sql`select * from t where id = ${id}`; q.text + ' ' + q.values",
+    ///         Eval::GLOBAL
+    ///     )
+    ///     .unwrap(),
+    ///     Some("select * from t where id = $1 1".to_owned())
+    /// );
+    /// ```
+    pub fn register_template_tag<F>(&self, name: &str, handler: F) -> Result<(), Error>
+    where
+        F: for<'a> Fn(&'a ContextRef, TaggedTemplate<'a>) -> Result<Local<'a, Value>, Error>
+            + 'static,
+    {
+        let tag = self.new_template_tag(name, handler)?;
+
+        self.global_object().set_property(name, tag)?;
+
+        Ok(())
+    }
+}
+
+unsafe extern "C" fn tag_stub(
+    ctx: *mut ffi::JSContext,
+    _this_val: ffi::JSValue,
+    argc: c_int,
+    argv: *mut ffi::JSValue,
+    _magic: c_int,
+    data: *mut ffi::JSValue,
+) -> ffi::JSValue {
+    let ctxt = ContextRef::from_ptr(ctx);
+
+    ctxt.catch_unwind(|| {
+        let args = slice::from_raw_parts(argv, argc as usize);
+        let tag = ctxt.clone_value(&Value::from(*data));
+        let tag = match tag.downcast_ref::<Tag>() {
+            Ok(tag) => tag,
+            Err(err) => return Err::<Value, _>(err).new_value(ctxt),
+        };
+
+        match template(ctxt, args) {
+            Ok(tpl) => (tag.handler)(ctxt, tpl).new_value(ctxt),
+            Err(_) => ErrorKind::TypeError(
+                format!("`{}` must be used as a tagged template", tag.name),
+                None,
+            )
+            .new_value(ctxt),
+        }
+    })
+}
+
+fn strings<'a>(ctxt: &'a ContextRef, arr: &Value) -> Result<Vec<Local<'a, Value>>, Error> {
+    if !ctxt.is_array(arr)? {
+        bail!("expected an array")
+    }
+
+    let len = ctxt
+        .get_property(arr, "length")
+        .and_then(|len| len.to_index())
+        .unwrap_or_default() as u32;
+
+    Ok((0..len)
+        .map(|idx| {
+            ctxt.get_property(arr, idx)
+                .unwrap_or_else(|| ctxt.undefined())
+        })
+        .collect())
+}
+
+fn template<'a>(ctxt: &'a ContextRef, args: &[ffi::JSValue]) -> Result<TaggedTemplate<'a>, Error> {
+    let (strs, values) = match args.split_first() {
+        Some((strs, values)) => (ctxt.clone_value(&Value::from(*strs)), values),
+        None => bail!("missing the template strings"),
+    };
+    let raw = match strs.get_property("raw") {
+        Some(raw) => strings(ctxt, &raw)?,
+        None => bail!("missing the raw strings"),
+    };
+    let strs = strings(ctxt, &strs)?;
+
+    if strs.len() != raw.len() || strs.len() != values.len() + 1 {
+        bail!("mismatched template strings and values")
+    }
+
+    Ok(TaggedTemplate {
+        strings: strs
+            .iter()
+            .map(|s| {
+                if s.is_string() {
+                    Some(s.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        raw: raw.iter().map(|s| s.to_string()).collect(),
+        values: values
+            .iter()
+            .map(|v| ctxt.clone_value(&Value::from(*v)))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn template_tag() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.register_template_tag("sql", |ctxt, tpl| {
+            let query = ctxt.bind(ctxt.new_object());
+            let params = ctxt.bind(ctxt.new_array());
+
+            for (idx, value) in tpl.values.iter().enumerate() {
+                params.set_property(idx as u32, value)?;
+            }
+
+            query.set_property("text", tpl.join_with(|idx| format!("${}", idx + 1)))?;
+            query.set_property("params", params)?;
+            query.set_property("raw", tpl.raw.join("|"))?;
+            query.set_property(
+                "invalid",
+                tpl.strings.iter().filter(|s| s.is_none()).count() as i32,
+            )?;
+
+            Ok(query)
+        })
+        .unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                r#"
+var name = "Robert'); DROP TABLE students;--";
+var q = sql`select * from students where name = ${name} and age > ${18}\n`;
+
+[q.text, q.params.join(), q.raw, q.invalid].join('#')
+"#,
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some(
+                "select * from students where name = $1 and age > $2\n#\
+                 Robert'); DROP TABLE students;--,18#\
+                 select * from students where name = | and age > |\\n#0"
+                    .to_owned()
+            )
+        );
+
+        // the invalid escape sequences are allowed in the tagged templates
+        assert_eq!(
+            ctxt.eval::<_, String>(r#"var q = sql`\unicode`; q.text + q.invalid"#, Eval::GLOBAL)
+                .unwrap(),
+            Some("\\unicode1".to_owned())
+        );
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { sql('select 1') } catch (err) { `${err.name}: ${err.message}` }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("TypeError: `sql` must be used as a tagged template".to_owned())
+        );
+
+        // the errors of handler are thrown to the scripts
+        let tag = ctxt
+            .new_template_tag("fail", |_ctxt, _tpl| Err(failure::err_msg("rejected")))
+            .unwrap();
+
+        ctxt.global_object().set_property("fail", tag).unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "try { fail`x` } catch (err) { fail.name + ': ' + err.message }",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            Some("fail: rejected".to_owned())
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use failure::Error;
+
+use crate::{ContextRef, Eval, Local, NewValue, Value, NULL};
+
+/// The escaping of the interpolated values of a `Template`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Escape {
+    /// insert the values as is
+    None,
+    /// escape the `&`, `<`, `>`, `"` and `'` of the values, the static text is not escaped
+    Html,
+}
+
+/// The tag function which escapes the interpolated values.
+const HTML_ESCAPE: &str = r#"
+(function () {
+    const entities = { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' };
+    const escape = (c) => entities[c];
+
+    return function (strings, ...values) {
+        let s = strings[0];
+
+        for (let i = 0; i < values.length; i++) {
+            s += String(values[i]).replace(/[&<>"']/g, escape) + strings[i + 1];
+        }
+
+        return s;
+    };
+})()
+"#;
+
+/// A template compiled once and rendered many times, with the syntax of the template literals.
+///
+/// The template is evaluated as Javascript code, the placeholders like `${name}` or `${items.length}`
+/// could be any expression with the fields of the rendered data as the variables,
+/// so the untrusted templates should not be compiled.
+pub struct Template<'a> {
+    func: Local<'a, Value>,
+}
+
+impl ContextRef {
+    /// Compile the template with the syntax of the template literals, without the enclosing backticks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ContextRef, Escape, NewValue, Runtime, ffi};
+    ///
+    /// struct Order {
+    ///     customer: String,
+    ///     items: Vec<(&'static str, u32)>,
+    /// }
+    ///
+    /// impl NewValue for &Order {
+    ///     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+    ///         let order = ctxt.bind(ctxt.new_object());
+    ///         let items = ctxt.bind(ctxt.new_array());
+    ///
+    ///         for (idx, &(name, qty)) in self.items.iter().enumerate() {
+    ///             let item = ctxt.bind(ctxt.new_object());
+    ///
+    ///             item.set_property("name", name).unwrap();
+    ///             item.set_property("qty", qty as i32).unwrap();
+    ///             items.set_property(idx as u32, item).unwrap();
+    ///         }
+    ///
+    ///         order.set_property("customer", self.customer.as_str()).unwrap();
+    ///         order.set_property("items", items).unwrap();
+    ///         order.new_value(ctxt)
+    ///     }
+    /// }
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let tpl = ctxt
+    ///     .compile_template("<p>Dear ${customer},</p><ul>${items.map(i => `<li>${i.qty} x ${i.name}</li>`).join('')}</ul>", Escape::None)
+    ///     .unwrap();
+    ///
+    /// let order = Order {
+    ///     customer: "Bob".to_owned(),
+    ///     items: vec![("apple", 3), ("pear", 1)],
+    /// };
+    ///
+    /// assert_eq!(
+    ///     tpl.render(&order).unwrap(),
+    ///     "<p>Dear Bob,</p><ul><li>3 x apple</li><li>1 x pear</li></ul>"
+    /// );
+    ///
+    /// let tpl = ctxt.compile_template("<p>Dear ${customer},</p>", Escape::Html).unwrap();
+    /// let order = Order {
+    ///     customer: "<script>alert('x')</script>".to_owned(),
+    ///     items: vec![],
+    /// };
+    ///
+    /// assert_eq!(
+    ///     tpl.render(&order).unwrap(),
+    ///     "<p>Dear &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;,</p>"
+    /// );
+    /// ```
+    pub fn compile_template(&self, template: &str, escape: Escape) -> Result<Template, Error> {
+        let tag = match escape {
+            Escape::None => "",
+            Escape::Html => "__tag__",
+        };
+        let factory = self.eval_script(
+            format!(
+                "(function (__tag__) {{ return function (__data__) {{ with (__data__) {{ return {}`{}`; }} }}; }})",
+                tag, template
+            ),
+            "<template>",
+            Eval::GLOBAL,
+        )?;
+        let func = match escape {
+            Escape::None => self.call(&factory, None, ())?,
+            Escape::Html => self.call(
+                &factory,
+                None,
+                self.eval_script(HTML_ESCAPE, "<template>", Eval::GLOBAL)?,
+            )?,
+        };
+
+        Ok(Template { func })
+    }
+}
+
+impl Template<'_> {
+    /// Render the template with the fields of the data as the variables.
+    pub fn render<T: NewValue>(&self, data: T) -> Result<String, Error> {
+        self.func.call(None, data)?.extract()
+    }
+
+    /// Render the template with the fields as the variables, the global variables are visible unless shadowed.
+    pub fn render_fields<I, K, V>(&self, fields: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: NewValue,
+    {
+        let ctxt = self.func.ctxt;
+        let scope = ctxt.bind(ctxt.new_object_proto(&NULL));
+
+        for (key, value) in fields {
+            scope.set_property(key.as_ref(), value)?;
+        }
+
+        self.func.call(None, scope)?.extract()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn template() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.global_object()
+            .set_property("company", "ACME")
+            .unwrap();
+
+        let tpl = ctxt
+            .compile_template(
+                "Hi ${name},\\nyour total is ${total.toFixed(2)} (${company})",
+                Escape::Html,
+            )
+            .unwrap();
+
+        for &(name, total) in &[("Alice", 12.5), ("Tom & Jerry", 3.0)] {
+            let fields = vec![
+                ("name", ctxt.bind(name.new_value(&ctxt))),
+                ("total", ctxt.bind(total.new_value(&ctxt))),
+            ];
+
+            assert_eq!(
+                tpl.render_fields(fields).unwrap(),
+                format!(
+                    "Hi {},\nyour total is {:.2} (ACME)",
+                    name.replace('&', "&amp;"),
+                    total
+                )
+            );
+        }
+
+        // the missing fields are reported
+        assert_eq!(
+            tpl.render_fields(vec![("name", "Bob")])
+                .unwrap_err()
+                .to_string(),
+            "ReferenceError: total is not defined"
+        );
+
+        let tpl = ctxt
+            .compile_template("<b>${html}</b>", Escape::None)
+            .unwrap();
+
+        assert_eq!(
+            tpl.render_fields(vec![("html", "<i>x</i>")]).unwrap(),
+            "<b><i>x</i></b>"
+        );
+
+        // the syntax errors are reported when compiled
+        assert!(ctxt.compile_template("${name", Escape::Html).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use failure::{Error, ResultExt};
+
+use crate::ContextRef;
+
+/// A transform applied to the script or module source before compilation,
+/// e.g. the coverage instrumentation, the macro expansion or the banned API rewriting.
+pub trait SourceTransform {
+    /// Transform the source of the script or module with its filename.
+    ///
+    /// The evaluation fails with the error returned by the transform.
+    fn transform(&self, source: String, filename: &str) -> Result<String, Error>;
+}
+
+impl<F> SourceTransform for F
+where
+    F: Fn(String, &str) -> Result<String, Error>,
+{
+    fn transform(&self, source: String, filename: &str) -> Result<String, Error> {
+        self(source, filename)
+    }
+}
+
+#[derive(Default)]
+struct SourceTransforms(RefCell<Vec<Rc<dyn SourceTransform>>>);
+
+unsafe impl Send for SourceTransforms {}
+
+impl ContextRef {
+    /// Add a transform applied to every script or module source compiled by `eval_script`, including the modules
+    /// compiled by the module loaders with it, the transforms are applied in the order they were added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.add_source_transform(|source: String, _filename: &str| Ok(source.replace("@answer", "42")));
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("@answer + 1", Eval::GLOBAL).unwrap(), Some(43));
+    /// ```
+    pub fn add_source_transform<T: SourceTransform + 'static>(&self, transform: T) -> &Self {
+        self.state::<SourceTransforms>()
+            .0
+            .borrow_mut()
+            .push(Rc::new(transform));
+        self
+    }
+
+    /// Remove all the source transforms.
+    pub fn clear_source_transforms(&self) {
+        self.state::<SourceTransforms>().0.borrow_mut().clear();
+    }
+
+    /// Apply the source transforms to the source.
+    pub(crate) fn transform_source(
+        &self,
+        source: Vec<u8>,
+        filename: &str,
+    ) -> Result<Vec<u8>, Error> {
+        // the transforms may evaluate the scripts
+        let transforms = self.state::<SourceTransforms>().0.borrow().clone();
+
+        if transforms.is_empty() {
+            return Ok(source);
+        }
+
+        let source = String::from_utf8(source).context("source")?;
+
+        transforms
+            .iter()
+            .try_fold(source, |source, transform| {
+                transform.transform(source, filename)
+            })
+            .map(String::into_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use failure::err_msg;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn source_transform() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let seen = Rc::new(RefCell::new(vec![]));
+
+        ctxt.add_source_transform({
+            let seen = seen.clone();
+
+            move |source: String, filename: &str| {
+                seen.borrow_mut().push(filename.to_owned());
+
+                if source.contains("eval(") {
+                    Err(err_msg("`eval` is banned"))
+                } else {
+                    Ok(source.replace("__FILE__", &format!("{:?}", filename)))
+                }
+            }
+        })
+        .add_source_transform(|source: String, _filename: &str| {
+            Ok(format!("var hits = (globalThis.hits || 0) + 1; {}", source))
+        });
+
+        assert_eq!(
+            ctxt.eval_script("__FILE__", "main.js", Eval::GLOBAL)
+                .unwrap()
+                .to_string(),
+            "main.js"
+        );
+
+        ctxt.eval_script(
+            "export default __FILE__",
+            "main.mjs",
+            Eval::MODULE | Eval::COMPILE_ONLY,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ctxt.eval_script("eval('1')", "banned.js", Eval::GLOBAL)
+                .unwrap_err()
+                .to_string(),
+            "`eval` is banned"
+        );
+        assert_eq!(ctxt.eval::<_, i32>("hits", Eval::GLOBAL).unwrap(), Some(2));
+        assert_eq!(
+            *seen.borrow(),
+            vec!["main.js", "main.mjs", "banned.js", "<evalScript>"]
+        );
+
+        ctxt.clear_source_transforms();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("typeof __FILE__", Eval::GLOBAL)
+                .unwrap(),
+            Some("undefined".to_owned())
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::ops::Deref;
+
+use failure::Error;
+
+use crate::{value::ERR, ContextRef, Local, Value};
+
+/// The scope of `ContextRef::try_catch`, which dereferences to the context.
+pub struct TryCatch<'a> {
+    ctxt: &'a ContextRef,
+}
+
+impl Deref for TryCatch<'_> {
+    type Target = ContextRef;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctxt
+    }
+}
+
+impl TryCatch<'_> {
+    /// Returns `true` if an exception is pending in the scope.
+    pub fn has_caught(&self) -> bool {
+        match self.ctxt.take_pending_exception() {
+            Some(exc) => {
+                self.ctxt.throw(exc);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the pending exception as an error, to exit the scope early with `?`.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.has_caught() {
+            self.ctxt.check_error(ERR).map(|_| ())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl ContextRef {
+    fn take_pending_exception(&self) -> Option<Local<Value>> {
+        self.get_exception().filter(|exc| !exc.is_null())
+    }
+
+    /// Run a group of operations, and returns the exception raised by any of them as a single error.
+    ///
+    /// The exceptions left pending by the operations, e.g. the raw FFI calls or the unchecked values,
+    /// are captured when the closure returns, the pending exception takes precedence over the returned error.
+    /// The exception pending before the scope is restored after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, ErrorKind, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let err = ctxt
+    ///     .try_catch(|scope| {
+    ///         let obj = scope.bind(scope.new_object());
+    ///
+    ///         obj.set_property("x", 1)?;
+    ///         scope.throw_type_error("invalid x");
+    ///         obj.set_property("y", 2)?;
+    ///
+    ///         Ok(obj.get_property("y").and_then(|v| v.as_int()))
+    ///     })
+    ///     .unwrap_err()
+    ///     .downcast::<ErrorKind>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(err.message(), "invalid x");
+    /// assert_eq!(ctxt.try_catch(|scope| scope.eval::<_, i32>("1 + 2", Eval::GLOBAL)).unwrap(), Some(3));
+    /// ```
+    pub fn try_catch<F, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&TryCatch) -> Result<T, Error>,
+    {
+        let outer = self.take_pending_exception().map(Local::into_inner);
+
+        let res = f(&TryCatch { ctxt: self });
+
+        let res = match self.take_pending_exception() {
+            Some(exc) => {
+                self.throw(exc);
+                self.check_error(ERR).and(res)
+            }
+            None => res,
+        };
+
+        if let Some(exc) = outer {
+            self.throw(exc);
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, ErrorKind, Eval, Runtime};
+
+    #[test]
+    fn try_catch() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let res = ctxt.try_catch(|scope| {
+            let a = scope.eval_script("1", "<eval>", Eval::GLOBAL)?;
+            let _ = scope.bind(scope.new_object()).get_property("x");
+            let b = scope.eval_script("2", "<eval>", Eval::GLOBAL)?;
+
+            Ok(a.as_int().unwrap() + b.as_int().unwrap())
+        });
+
+        assert_eq!(res.unwrap(), 3);
+
+        let err = ctxt
+            .try_catch(|scope| {
+                scope.throw_range_error("first");
+
+                assert!(scope.has_caught());
+
+                scope.check()?;
+
+                Ok(())
+            })
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.message(), "first");
+
+        let err = ctxt
+            .try_catch(|scope| {
+                scope.throw_range_error("pending");
+
+                Err::<(), _>(failure::err_msg("returned"))
+            })
+            .unwrap_err()
+            .downcast::<ErrorKind>()
+            .unwrap();
+
+        assert_eq!(err.message(), "pending");
+
+        ctxt.throw_type_error("outer");
+
+        assert!(!ctxt.try_catch(|scope| Ok(scope.has_caught())).unwrap());
+        assert_eq!(
+            ctxt.get_exception().unwrap().to_string(),
+            "TypeError: outer"
+        );
+        assert!(ctxt.get_exception().unwrap().is_null());
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, Local, Value};
+
+/// The type of a value, as the `typeof` operator returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JsType {
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    BigInt,
+    BigFloat,
+    String,
+    Symbol,
+    Object,
+    Function,
+}
+
+impl JsType {
+    /// Returns the result of the `typeof` operator, which is `object` for `null`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JsType::Undefined => "undefined",
+            JsType::Null | JsType::Object => "object",
+            JsType::Boolean => "boolean",
+            JsType::Number => "number",
+            JsType::BigInt => "bigint",
+            JsType::BigFloat => "bigfloat",
+            JsType::String => "string",
+            JsType::Symbol => "symbol",
+            JsType::Function => "function",
+        }
+    }
+}
+
+impl fmt::Display for JsType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Local<'_, Value> {
+    /// Returns the type of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, JsType, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let v = ctxt.eval_script("[1, 'a', null, () => {}, new Date()]", "<eval>", Eval::GLOBAL).unwrap();
+    /// let date = v.get_property(4).unwrap();
+    ///
+    /// assert_eq!(v.get_property(0).unwrap().type_of(), JsType::Number);
+    /// assert_eq!(v.get_property(1).unwrap().type_of(), JsType::String);
+    /// assert_eq!(v.get_property(2).unwrap().type_of().as_str(), "object");
+    /// assert_eq!(v.get_property(3).unwrap().type_of(), JsType::Function);
+    /// assert_eq!(date.type_of(), JsType::Object);
+    /// assert_eq!(date.class_name().unwrap(), "Date");
+    /// assert!(date.instance_of(&ctxt.global_object().get_property("Date").unwrap()).unwrap());
+    /// ```
+    pub fn type_of(&self) -> JsType {
+        self.ctxt.type_of(self)
+    }
+
+    /// Returns the class name of an object, or `None` if the value is not an object.
+    ///
+    /// The class name is the name of the constructor, or the builtin tag of the object without a named constructor.
+    pub fn class_name(&self) -> Option<String> {
+        self.ctxt.class_name(self)
+    }
+}
+
+impl ContextRef {
+    /// Returns the type of the value.
+    pub fn type_of(&self, value: &Value) -> JsType {
+        match value.tag() {
+            ffi::JS_TAG_UNDEFINED | ffi::JS_TAG_UNINITIALIZED => JsType::Undefined,
+            ffi::JS_TAG_NULL => JsType::Null,
+            ffi::JS_TAG_BOOL => JsType::Boolean,
+            ffi::JS_TAG_INT | ffi::JS_TAG_FLOAT64 => JsType::Number,
+            ffi::JS_TAG_BIG_INT => JsType::BigInt,
+            ffi::JS_TAG_BIG_FLOAT => JsType::BigFloat,
+            ffi::JS_TAG_STRING => JsType::String,
+            ffi::JS_TAG_SYMBOL => JsType::Symbol,
+            _ if self.is_function(value) => JsType::Function,
+            _ => JsType::Object,
+        }
+    }
+
+    /// Returns the class name of an object, or `None` if the value is not an object.
+    pub fn class_name(&self, value: &Value) -> Option<String> {
+        if !value.is_object() {
+            return None;
+        }
+
+        let proto = Value::from(unsafe { ffi::JS_GetPrototype(self.as_ptr(), value.raw()) });
+        let name = if proto.is_object() {
+            self.get_property(&proto, "constructor")
+                .filter(|f| f.is_function())
+                .and_then(|f| self.get_property(&f, "name"))
+                .map(|name| name.to_string())
+                .filter(|name| !name.is_empty())
+        } else {
+            None
+        };
+
+        name.or_else(|| {
+            self.get_property(&self.global_object(), "Object")
+                .and_then(|object| self.get_property(&object, "prototype"))
+                .and_then(|proto| self.get_property(&proto, "toString"))
+                .and_then(|to_string| self.call(&to_string, Some(value), ()).ok())
+                .map(|tag| {
+                    tag.to_string()
+                        .trim_start_matches("[object ")
+                        .trim_end_matches(']')
+                        .to_owned()
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn type_of() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let eval = |s: &str| ctxt.eval_script(s, "<eval>", Eval::GLOBAL).unwrap();
+        let type_of = eval("(v) => typeof v");
+
+        for (source, ty, class_name) in vec![
+            ("undefined", JsType::Undefined, None),
+            ("null", JsType::Null, None),
+            ("true", JsType::Boolean, None),
+            ("1", JsType::Number, None),
+            ("1.5", JsType::Number, None),
+            ("'s'", JsType::String, None),
+            ("Symbol('s')", JsType::Symbol, None),
+            ("({})", JsType::Object, Some("Object")),
+            ("[]", JsType::Object, Some("Array")),
+            ("new Map()", JsType::Object, Some("Map")),
+            ("Math", JsType::Object, Some("Object")),
+            ("Object.create(null)", JsType::Object, Some("Object")),
+            ("class Foo {}; new Foo()", JsType::Object, Some("Foo")),
+            ("function Bar() {}; Bar", JsType::Function, Some("Function")),
+            ("class Baz {}; Baz", JsType::Function, Some("Function")),
+        ] {
+            let v = eval(source);
+
+            assert_eq!(v.type_of(), ty, "typeof {}", source);
+            assert_eq!(v.class_name().as_deref(), class_name, "class of {}", source);
+            assert_eq!(
+                ty.as_str(),
+                type_of.call(None, [&v]).unwrap().to_string(),
+                "{}",
+                source
+            );
+        }
+
+        let foo = eval("Foo");
+
+        assert!(eval("new Foo()").instance_of(&foo).unwrap());
+        assert!(!eval("({})").instance_of(&foo).unwrap());
+        assert!(eval("1").instance_of(&eval("({})")).is_err());
+    }
+}
@@ -0,0 +1,470 @@
+use std::cell::Cell;
+use std::char;
+use std::ptr;
+
+use failure::Error;
+use foreign_types::ForeignTypeRef;
+
+use crate::{ffi, ContextRef, ExtractValue, Local, NewValue, Value};
+
+/// Whether the engine was built with the full Unicode tables.
+///
+/// Without them (the `lite-unicode` feature), `String.prototype.normalize` is not available,
+/// and the regular expressions don't support the Unicode property escapes like `\p{Script=Greek}`.
+pub const FULL_UNICODE: bool = cfg!(not(feature = "lite-unicode"));
+
+/// How the lone surrogates of a Javascript string are converted to a Rust string.
+///
+/// The Javascript strings are sequences of UTF-16 code units, which may contain the unpaired surrogates,
+/// but a Rust `String` must be valid UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SurrogatePolicy {
+    /// replace the lone surrogates with `U+FFFD REPLACEMENT CHARACTER`
+    Replace,
+    /// fail the conversion with `LoneSurrogate`
+    Error,
+    /// preserve the code units as `JsString::Utf16`, the conversion to `String` fails with `LoneSurrogate`
+    Preserve,
+}
+
+impl Default for SurrogatePolicy {
+    fn default() -> Self {
+        SurrogatePolicy::Replace
+    }
+}
+
+/// The string can't be converted because it contains a lone surrogate.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+#[fail(display = "lone surrogate U+{:04X} at index {}", code_unit, index)]
+pub struct LoneSurrogate {
+    /// the index of the code unit in UTF-16
+    pub index: usize,
+    /// the unpaired surrogate
+    pub code_unit: u16,
+}
+
+impl LoneSurrogate {
+    /// Find the first lone surrogate of the UTF-16 code units.
+    pub(crate) fn find(units: &[u16]) -> Option<Self> {
+        let mut index = 0;
+
+        for c in char::decode_utf16(units.iter().cloned()) {
+            match c {
+                Ok(c) => index += c.len_utf16(),
+                Err(err) => {
+                    return Some(LoneSurrogate {
+                        index,
+                        code_unit: err.unpaired_surrogate(),
+                    })
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A Javascript string, which is kept as UTF-16 code units if it contains the lone surrogates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsString {
+    /// the well-formed string
+    Utf8(String),
+    /// the code units with the lone surrogates, only returned with `SurrogatePolicy::Preserve`
+    Utf16(Vec<u16>),
+}
+
+impl JsString {
+    /// Convert to a Rust string, the lone surrogates are replaced with `U+FFFD`.
+    pub fn to_string_lossy(&self) -> String {
+        match self {
+            JsString::Utf8(s) => s.clone(),
+            JsString::Utf16(units) => String::from_utf16_lossy(units),
+        }
+    }
+
+    /// Returns the string as UTF-16 code units.
+    pub fn to_utf16(&self) -> Vec<u16> {
+        match self {
+            JsString::Utf8(s) => s.encode_utf16().collect(),
+            JsString::Utf16(units) => units.clone(),
+        }
+    }
+
+    /// The length of the string in WTF-8, which encodes each lone surrogate in 3 bytes.
+    fn wtf8_len(&self) -> usize {
+        match self {
+            JsString::Utf8(s) => s.len(),
+            JsString::Utf16(units) => char::decode_utf16(units.iter().cloned())
+                .map(|c| c.map_or(3, char::len_utf8))
+                .sum(),
+        }
+    }
+}
+
+impl ExtractValue for JsString {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        v.ctxt.convert(|budget| {
+            budget.check_string(v).ok()?;
+
+            let s = match v.ctxt.to_js_string(v)? {
+                Ok(s) => s,
+                Err(err) => {
+                    budget.fail(err);
+
+                    return None;
+                }
+            };
+
+            budget.charge_string_bytes(s.wtf8_len()).ok()?;
+
+            Some(s)
+        })
+    }
+}
+
+impl NewValue for JsString {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        match self {
+            JsString::Utf8(s) => s.new_value(ctxt),
+            JsString::Utf16(units) => unsafe {
+                ffi::JS_NewStringUTF16(ctxt.as_ptr(), units.as_ptr(), units.len())
+            },
+        }
+    }
+}
+
+/// Decode the string returned by `JS_ToCStringLen2`, which encodes the lone surrogates like WTF-8.
+///
+/// Returns the UTF-16 code units if the string contains any lone surrogate.
+fn decode_wtf8(bytes: Vec<u8>) -> Result<String, Vec<u16>> {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(s) => return Ok(s),
+        Err(err) => err.into_bytes(),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = &bytes[..];
+
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(s) => (s, &[][..]),
+            Err(err) => {
+                let (valid, invalid) = rest.split_at(err.valid_up_to());
+
+                (unsafe { std::str::from_utf8_unchecked(valid) }, invalid)
+            }
+        };
+
+        units.extend(valid.encode_utf16());
+
+        rest = match invalid {
+            [0xED, b1 @ 0xA0..=0xBF, b2 @ 0x80..=0xBF, rest @ ..] => {
+                units.push(0xD000 | (u16::from(b1 & 0x3F) << 6) | u16::from(b2 & 0x3F));
+                rest
+            }
+            [] => invalid,
+            [_, rest @ ..] => {
+                units.push(0xFFFD);
+                rest
+            }
+        };
+    }
+
+    Err(units)
+}
+
+#[derive(Debug, Default)]
+struct Unicode {
+    policy: Cell<SurrogatePolicy>,
+}
+
+impl Local<'_, Value> {
+    /// Convert the value to a string, the lone surrogates are replaced with `U+FFFD`
+    /// regardless of the `SurrogatePolicy` of the context.
+    ///
+    /// Returns `None` if the value can't be converted to a string, e.g. a `Symbol`.
+    pub fn to_string_lossy(&self) -> Option<String> {
+        Some(
+            decode_wtf8(self.to_cstring()?.into_bytes())
+                .unwrap_or_else(|units| String::from_utf16_lossy(&units)),
+        )
+    }
+
+    /// Returns the UTF-16 code units of the value, which is converted to a string first.
+    ///
+    /// Returns `None` if the value can't be converted to a string, e.g. a `Symbol`.
+    pub fn as_utf16(&self) -> Option<Vec<u16>> {
+        if self.is_string() {
+            self.ctxt.to_utf16(self)
+        } else {
+            self.ctxt.to_utf16(&self.to_str())
+        }
+    }
+}
+
+impl ContextRef {
+    /// Set how the lone surrogates are handled when converting the strings with `ExtractValue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, JsString, LoneSurrogate, Runtime, SurrogatePolicy};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("'a\\uD800b'", Eval::GLOBAL).unwrap(),
+    ///     Some("a\u{FFFD}b".to_owned())
+    /// );
+    ///
+    /// ctxt.set_surrogate_policy(SurrogatePolicy::Error);
+    ///
+    /// let err = ctxt.eval::<_, String>("'a\\uD800b'", Eval::GLOBAL).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     err.downcast::<LoneSurrogate>().unwrap(),
+    ///     LoneSurrogate { index: 1, code_unit: 0xD800 }
+    /// );
+    ///
+    /// ctxt.set_surrogate_policy(SurrogatePolicy::Preserve);
+    ///
+    /// assert_eq!(
+    ///     ctxt.eval::<_, JsString>("'a\\uD800b'", Eval::GLOBAL).unwrap(),
+    ///     Some(JsString::Utf16(vec![0x61, 0xD800, 0x62]))
+    /// );
+    /// ```
+    pub fn set_surrogate_policy(&self, policy: SurrogatePolicy) {
+        self.state::<Unicode>().policy.set(policy)
+    }
+
+    /// Returns how the lone surrogates are handled when converting the strings.
+    pub fn surrogate_policy(&self) -> SurrogatePolicy {
+        self.state::<Unicode>().policy.get()
+    }
+
+    /// Convert the value to a string with the surrogate policy of the context.
+    ///
+    /// Returns `None` if the value can't be converted to a string.
+    pub(crate) fn to_js_string(&self, v: &Value) -> Option<Result<JsString, LoneSurrogate>> {
+        let units = match decode_wtf8(self.to_cstring(v)?.into_bytes()) {
+            Ok(s) => return Some(Ok(JsString::Utf8(s))),
+            Err(units) => units,
+        };
+
+        Some(match self.surrogate_policy() {
+            SurrogatePolicy::Replace => Ok(JsString::Utf8(String::from_utf16_lossy(&units))),
+            SurrogatePolicy::Error => match LoneSurrogate::find(&units) {
+                Some(err) => Err(err),
+                None => Ok(JsString::Utf8(String::from_utf16_lossy(&units))),
+            },
+            SurrogatePolicy::Preserve => Ok(JsString::Utf16(units)),
+        })
+    }
+
+    /// Create a string from the UTF-16 code units, the lone surrogates are kept as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// let units = "h\u{e9}llo, \u{4e16}\u{754c}".encode_utf16().collect::<Vec<_>>();
+    /// let s = ctxt.new_string_utf16(&units).unwrap();
+    ///
+    /// assert_eq!(s.to_string(), "h\u{e9}llo, \u{4e16}\u{754c}");
+    /// assert_eq!(s.as_utf16().unwrap(), units);
+    /// ```
+    pub fn new_string_utf16(&self, units: &[u16]) -> Result<Local<Value>, Error> {
+        self.bind(unsafe { ffi::JS_NewStringUTF16(self.as_ptr(), units.as_ptr(), units.len()) })
+            .ok()
+    }
+
+    /// Copy the UTF-16 code units of a string.
+    fn to_utf16(&self, s: &Value) -> Option<Vec<u16>> {
+        let len = unsafe { ffi::JS_GetStringUTF16(s.raw(), ptr::null_mut(), 0) };
+
+        if len < 0 {
+            // not a string, or the unpatched library
+            return self
+                .to_cstring(s)
+                .map(|s| match decode_wtf8(s.into_bytes()) {
+                    Ok(s) => s.encode_utf16().collect(),
+                    Err(units) => units,
+                });
+        }
+
+        let mut units = Vec::with_capacity(len as usize);
+
+        unsafe {
+            ffi::JS_GetStringUTF16(s.raw(), units.as_mut_ptr(), len as u32);
+            units.set_len(len as usize);
+        }
+
+        Some(units)
+    }
+
+    /// Returns whether `String.prototype.normalize` is available.
+    pub fn has_string_normalize(&self) -> Result<bool, Error> {
+        let proto = self.get_prototype(&self.new_value(""));
+
+        proto.has_property("normalize")
+    }
+
+    /// Add or remove `String.prototype.normalize`, returns whether it is available.
+    ///
+    /// It can't be added if the engine was built without the full Unicode tables, see `FULL_UNICODE`.
+    pub fn set_string_normalize(&self, enabled: bool) -> Result<bool, Error> {
+        if enabled {
+            unsafe { ffi::JS_AddIntrinsicStringNormalize(self.as_ptr()) }
+        } else {
+            let proto = self.get_prototype(&self.new_value(""));
+
+            proto.delete_property("normalize")?;
+        }
+
+        self.has_string_normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn lone_surrogates() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(ctxt.surrogate_policy(), SurrogatePolicy::Replace);
+
+        let s = ctxt
+            .eval_script("'\\uDC00a\\uD83D\\uDE00b\\uD800'", "<eval>", Eval::GLOBAL)
+            .unwrap();
+
+        assert_eq!(s.to_string_lossy().unwrap(), "\u{FFFD}a\u{1F600}b\u{FFFD}");
+        assert_eq!(s.to_string(), "\u{FFFD}a\u{1F600}b\u{FFFD}");
+        assert_eq!(
+            s.extract::<String>().unwrap(),
+            "\u{FFFD}a\u{1F600}b\u{FFFD}"
+        );
+
+        ctxt.set_surrogate_policy(SurrogatePolicy::Error);
+
+        assert_eq!(
+            s.extract::<String>()
+                .unwrap_err()
+                .downcast::<LoneSurrogate>()
+                .unwrap(),
+            LoneSurrogate {
+                index: 0,
+                code_unit: 0xDC00
+            }
+        );
+        // the well-formed strings are not affected
+        assert_eq!(
+            ctxt.eval::<_, String>("'\\uD83D\\uDE00'", Eval::GLOBAL)
+                .unwrap(),
+            Some("\u{1F600}".to_owned())
+        );
+
+        ctxt.set_surrogate_policy(SurrogatePolicy::Preserve);
+
+        assert_eq!(
+            s.extract::<JsString>().unwrap(),
+            JsString::Utf16(vec![0xDC00, 0x61, 0xD83D, 0xDE00, 0x62, 0xD800])
+        );
+        assert_eq!(
+            ctxt.eval::<_, JsString>("'\\u00e9t\\u00e9'", Eval::GLOBAL)
+                .unwrap(),
+            Some(JsString::Utf8("été".to_owned()))
+        );
+        assert!(s
+            .extract::<String>()
+            .unwrap_err()
+            .downcast_ref::<LoneSurrogate>()
+            .is_some());
+    }
+
+    #[test]
+    fn utf16() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        // the narrow, wide and ill-formed strings
+        for units in &[
+            vec![],
+            vec![0x61, 0xE9],
+            vec![0x4E16, 0xD83D, 0xDE00],
+            vec![0xDC00, 0x61, 0xD800],
+        ] {
+            let s = ctxt.new_string_utf16(units).unwrap();
+
+            assert!(s.is_string());
+            assert_eq!(&s.as_utf16().unwrap(), units);
+            assert_eq!(
+                s.get_property("length").unwrap().as_int(),
+                Some(units.len() as i32)
+            );
+        }
+
+        let s = ctxt.new_string_utf16(&[0xD83D, 0xDE00]).unwrap();
+
+        assert_eq!(s.to_string(), "\u{1F600}");
+        assert_eq!(
+            ctxt.eval_script("[1, 'a']", "<eval>", Eval::GLOBAL)
+                .unwrap()
+                .as_utf16()
+                .unwrap(),
+            vec![0x31, 0x2C, 0x61]
+        );
+
+        let s = ctxt.bind(JsString::Utf16(vec![0xD800, 0x62]).new_value(&ctxt));
+
+        ctxt.set_surrogate_policy(SurrogatePolicy::Preserve);
+
+        assert_eq!(
+            s.extract::<JsString>().unwrap(),
+            JsString::Utf16(vec![0xD800, 0x62])
+        );
+    }
+
+    #[test]
+    fn string_normalize() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        assert_eq!(ctxt.has_string_normalize().unwrap(), FULL_UNICODE);
+        assert_eq!(ctxt.set_string_normalize(false).unwrap(), false);
+        assert_eq!(
+            ctxt.eval::<_, String>("typeof ''.normalize", Eval::GLOBAL)
+                .unwrap(),
+            Some("undefined".to_owned())
+        );
+        assert_eq!(ctxt.set_string_normalize(true).unwrap(), FULL_UNICODE);
+
+        if FULL_UNICODE {
+            assert_eq!(
+                ctxt.eval::<_, i32>("'\\u00e9'.normalize('NFD').length", Eval::GLOBAL)
+                    .unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                ctxt.eval::<_, bool>("/\\p{Script=Greek}/u.test('\\u03b1')", Eval::GLOBAL)
+                    .unwrap(),
+                Some(true)
+            );
+        }
+    }
+}
@@ -1,26 +1,78 @@
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
 use std::mem;
 use std::ptr::{null_mut, NonNull};
 
+use failure::Error;
 use foreign_types::ForeignTypeRef;
 
-use crate::{ffi, ClassId, ContextRef, Local, Runtime, Value};
+use crate::{ffi, ClassId, ContextRef, Local, Runtime, RuntimeRef, Value};
 
 lazy_static! {
     static ref RUNTIME_USERDATA_CLASS_ID: ClassId = Runtime::new_class_id();
 }
 
+/// The error when downcast a userdata object to the Rust type.
+#[derive(Debug, Clone, Copy, Fail, PartialEq)]
+pub enum DowncastError {
+    /// the value is not a userdata object.
+    #[fail(display = "not a userdata object")]
+    NotUserdata,
+
+    /// the userdata object holds a value of other type.
+    #[fail(display = "userdata type mismatch")]
+    TypeMismatch,
+
+    /// the userdata is borrowed, can't be mutably borrowed or taken.
+    #[fail(display = "userdata already borrowed")]
+    Borrowed,
+
+    /// the userdata is mutably borrowed, can't be borrowed or taken.
+    #[fail(display = "userdata already mutably borrowed")]
+    BorrowedMut,
+
+    /// the userdata has been taken.
+    #[fail(display = "userdata has been taken")]
+    Taken,
+}
+
+/// The boxed value holds by a userdata object.
+struct Userdata {
+    type_id: TypeId,
+    value: RefCell<Option<Box<dyn Any>>>,
+}
+
+impl Userdata {
+    fn new<T: 'static>(v: T) -> Self {
+        Userdata {
+            type_id: TypeId::of::<T>(),
+            value: RefCell::new(Some(Box::new(v))),
+        }
+    }
+
+    fn check_type<T: 'static>(&self) -> Result<(), DowncastError> {
+        if self.type_id == TypeId::of::<T>() {
+            Ok(())
+        } else {
+            Err(DowncastError::TypeMismatch)
+        }
+    }
+}
+
 impl Runtime {
     pub fn userdata_class_id() -> ClassId {
         *RUNTIME_USERDATA_CLASS_ID
     }
 
     pub(crate) fn register_userdata_class(&self) -> bool {
-        unsafe extern "C" fn userdata_finalizer(_rt: *mut ffi::JSRuntime, obj: ffi::JSValue) {
-            let ptr = ffi::JS_GetOpaque(obj, Runtime::userdata_class_id());
+        unsafe extern "C" fn userdata_finalizer(rt: *mut ffi::JSRuntime, obj: ffi::JSValue) {
+            let ptr = ffi::JS_GetOpaque(obj, Runtime::userdata_class_id()) as *mut Userdata;
 
             trace!("free userdata {:p} @ {:?}", ptr, obj.u.ptr);
 
-            mem::drop(Box::from_raw(ptr));
+            if !ptr.is_null() {
+                RuntimeRef::from_ptr(rt).catch_unwind((), || mem::drop(Box::from_raw(ptr)));
+            }
         }
 
         self.new_class(
@@ -37,9 +89,9 @@ impl Runtime {
 }
 
 impl ContextRef {
-    pub fn new_userdata<T>(&self, v: T) -> Local<'_, Value> {
+    pub fn new_userdata<T: 'static>(&self, v: T) -> Local<'_, Value> {
         let obj = self.new_object_class(Runtime::userdata_class_id());
-        let ptr = Box::into_raw(Box::new(v));
+        let ptr = Box::into_raw(Box::new(Userdata::new(v)));
 
         trace!("new userdata {:p} @ {:?}", ptr, obj.as_ptr::<()>());
 
@@ -48,16 +100,99 @@ impl ContextRef {
         self.bind(obj)
     }
 
-    pub fn get_userdata_unchecked<T>(&self, obj: &Value) -> NonNull<T> {
-        let ptr = self.get_opaque(obj, Runtime::userdata_class_id());
+    pub fn get_userdata_unchecked<T: 'static>(&self, obj: &Value) -> NonNull<T> {
+        let ptr = self.get_opaque::<Userdata>(obj, Runtime::userdata_class_id());
 
         trace!("got userdata {:p} @ {:?}", ptr, obj.as_ptr::<()>());
 
-        unsafe { NonNull::new_unchecked(ptr) }
+        unsafe {
+            let value = (*ptr).value.as_ptr();
+            let value = (*value).as_mut().expect("userdata has been taken");
+
+            NonNull::new_unchecked(value.as_mut() as *mut dyn Any as *mut T)
+        }
     }
 }
 
 impl Value {
+    fn userdata(&self) -> Result<&Userdata, DowncastError> {
+        unsafe {
+            self.get_opaque::<Userdata>(Runtime::userdata_class_id())
+                .as_ref()
+        }
+        .ok_or(DowncastError::NotUserdata)
+    }
+
+    /// Check if the value is a userdata object which holds a value of type `T`.
+    pub fn is_userdata<T: 'static>(&self) -> bool {
+        self.userdata().and_then(Userdata::check_type::<T>).is_ok()
+    }
+
+    /// Immutably borrows the Rust value holds by a userdata object.
+    ///
+    /// The borrow lasts until the returned `Ref` exits scope,
+    /// multiple immutable borrows can be taken out at the same time.
+    pub fn downcast_ref<T: 'static>(&self) -> Result<Ref<T>, Error> {
+        let userdata = self.userdata()?;
+
+        userdata.check_type::<T>()?;
+
+        let value = userdata
+            .value
+            .try_borrow()
+            .map_err(|_| DowncastError::BorrowedMut)?;
+
+        if value.is_none() {
+            Err(DowncastError::Taken.into())
+        } else {
+            Ok(Ref::map(value, |v| {
+                v.as_ref().and_then(|v| v.downcast_ref()).unwrap()
+            }))
+        }
+    }
+
+    /// Mutably borrows the Rust value holds by a userdata object.
+    ///
+    /// The borrow lasts until the returned `RefMut` exits scope,
+    /// the value cannot be borrowed while this borrow is active.
+    pub fn downcast_mut<T: 'static>(&self) -> Result<RefMut<T>, Error> {
+        let userdata = self.userdata()?;
+
+        userdata.check_type::<T>()?;
+
+        let value = userdata
+            .value
+            .try_borrow_mut()
+            .map_err(|_| DowncastError::Borrowed)?;
+
+        if value.is_none() {
+            Err(DowncastError::Taken.into())
+        } else {
+            Ok(RefMut::map(value, |v| {
+                v.as_mut().and_then(|v| v.downcast_mut()).unwrap()
+            }))
+        }
+    }
+
+    /// Takes the ownership of the Rust value holds by a userdata object.
+    ///
+    /// The userdata object is left empty, any further downcast will fail.
+    pub fn take<T: 'static>(&self) -> Result<T, Error> {
+        let userdata = self.userdata()?;
+
+        userdata.check_type::<T>()?;
+
+        let mut value = userdata
+            .value
+            .try_borrow_mut()
+            .map_err(|_| DowncastError::Borrowed)?;
+
+        value
+            .take()
+            .ok_or_else(|| DowncastError::Taken.into())
+            .map(|v| *v.downcast().unwrap())
+    }
+
     pub fn set_opaque<T>(&self, opaque: *mut T) {
         unsafe { ffi::JS_SetOpaque(self.raw(), opaque as *mut _) }
     }
@@ -78,3 +213,84 @@ impl ContextRef {
         unsafe { ffi::JS_GetOpaque2(self.as_ptr(), obj.raw(), class_id) as *mut _ }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Runtime};
+
+    use super::DowncastError;
+
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn downcast() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        let obj = ctxt.new_userdata(Point { x: 1, y: 2 });
+
+        assert!(obj.is_userdata::<Point>());
+        assert!(!obj.is_userdata::<String>());
+        assert!(!ctxt.bind(ctxt.new_object()).is_userdata::<Point>());
+
+        assert_eq!(*obj.downcast_ref::<Point>().unwrap(), Point { x: 1, y: 2 });
+        assert_eq!(
+            obj.downcast_ref::<String>()
+                .unwrap_err()
+                .downcast::<DowncastError>()
+                .unwrap(),
+            DowncastError::TypeMismatch
+        );
+        assert_eq!(
+            ctxt.null()
+                .downcast_ref::<Point>()
+                .unwrap_err()
+                .downcast::<DowncastError>()
+                .unwrap(),
+            DowncastError::NotUserdata
+        );
+
+        {
+            let p = obj.downcast_ref::<Point>().unwrap();
+            let p2 = obj.downcast_ref::<Point>().unwrap();
+
+            assert_eq!(p.x, p2.x);
+            assert_eq!(
+                obj.downcast_mut::<Point>()
+                    .unwrap_err()
+                    .downcast::<DowncastError>()
+                    .unwrap(),
+                DowncastError::Borrowed
+            );
+        }
+
+        {
+            let mut p = obj.downcast_mut::<Point>().unwrap();
+
+            p.x = 3;
+
+            assert_eq!(
+                obj.downcast_ref::<Point>()
+                    .unwrap_err()
+                    .downcast::<DowncastError>()
+                    .unwrap(),
+                DowncastError::BorrowedMut
+            );
+        }
+
+        assert_eq!(obj.take::<Point>().unwrap(), Point { x: 3, y: 2 });
+        assert_eq!(
+            obj.downcast_ref::<Point>()
+                .unwrap_err()
+                .downcast::<DowncastError>()
+                .unwrap(),
+            DowncastError::Taken
+        );
+    }
+}
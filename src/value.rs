@@ -1,20 +1,29 @@
 #![allow(clippy::cast_lossless)]
 
+use std::any;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_char;
+#[cfg(feature = "leak-detection")]
+use std::os::raw::c_void;
+#[cfg(feature = "leak-detection")]
+use std::panic::Location;
 use std::ptr::NonNull;
 use std::slice;
 
 use failure::Error;
 use foreign_types::ForeignTypeRef;
 
+#[cfg(feature = "leak-detection")]
+use crate::leak::{self, HandleKind};
 use crate::{
     ffi,
     handle::{Bindable, Unbindable},
-    ClassId, ContextRef, Local, RuntimeRef,
+    prop::Names,
+    ClassId, ContextRef, ErrorKind, JsString, Local, LoneSurrogate, RuntimeMismatch, RuntimeRef,
 };
 
 pub const ERR: i32 = -1;
@@ -61,9 +70,33 @@ impl Default for &Value {
     }
 }
 
+/// Remove a detached value from the leak detection, when it is freed or passed to the engine.
+#[cfg(feature = "leak-detection")]
+fn untrack_value(rt: &RuntimeRef, v: &Value) {
+    if v.is_object() {
+        leak::untrack(
+            rt,
+            HandleKind::Value,
+            v.as_ptr::<c_void>().as_ptr() as usize,
+        );
+    }
+}
+
 impl Unbindable for Value {
     fn unbind(ctxt: &ContextRef, inner: Self) {
-        ctxt.free_value(inner)
+        ctxt.release_value(inner)
+    }
+
+    #[cfg(feature = "leak-detection")]
+    fn detach(ctxt: &ContextRef, inner: &Self, location: &'static Location<'static>) {
+        if inner.is_object() {
+            leak::track(
+                ctxt.runtime(),
+                HandleKind::Value,
+                inner.as_ptr::<c_void>().as_ptr() as usize,
+                location,
+            );
+        }
     }
 }
 
@@ -127,18 +160,21 @@ impl Into<ffi::JSValue> for Value {
 
 impl<'a> Into<Value> for Local<'a, Value> {
     fn into(self) -> Value {
-        self.into_inner()
+        self.into_inner_untracked()
     }
 }
 
 impl<'a> Into<ffi::JSValue> for Local<'a, Value> {
     fn into(self) -> ffi::JSValue {
-        self.into_inner().raw()
+        self.into_inner_untracked().raw()
     }
 }
 
 impl RuntimeRef {
     pub fn free_value(&self, v: Value) {
+        #[cfg(feature = "leak-detection")]
+        untrack_value(self, &v);
+
         if v.has_ref_cnt() {
             unsafe {
                 let mut ref_cnt = v.as_ptr::<ffi::JSRefCountHeader>();
@@ -155,7 +191,7 @@ impl RuntimeRef {
 
 impl fmt::Display for Local<'_, Value> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.to_cstring().unwrap().to_string_lossy().to_string())
+        f.write_str(&self.to_string_lossy().unwrap())
     }
 }
 
@@ -192,6 +228,10 @@ impl<'a> Local<'a, Value> {
         self.ctxt.is_constructor(self)
     }
 
+    pub fn is_array(&self) -> Result<bool, Error> {
+        self.ctxt.is_array(self)
+    }
+
     pub fn to_bool(&self) -> Option<bool> {
         self.ctxt.to_bool(self)
     }
@@ -229,9 +269,23 @@ impl<'a> Local<'a, Value> {
         self.ctxt.to_cstring(self)
     }
 
+    /// Returns the result of `this instanceof obj`, throws a `TypeError` if `obj` is not callable.
     pub fn instance_of(&self, obj: &Value) -> Result<bool, Error> {
         self.ctxt.is_instance_of(self, obj)
     }
+
+    /// Extract the value as a Rust type, returns `ErrorKind::Conversion` if failed.
+    ///
+    /// Returns `ConversionLimitExceeded` if the conversion exceeded the limits of the context.
+    pub fn extract<T: ExtractValue>(&self) -> Result<T, Error> {
+        self.ctxt.extract_within_limits(self)?.ok_or_else(|| {
+            ErrorKind::Conversion {
+                expected: any::type_name::<T>(),
+                found: self.type_name(),
+            }
+            .into()
+        })
+    }
 }
 
 impl ContextRef {
@@ -248,6 +302,14 @@ impl ContextRef {
     pub fn free_value<T: Into<Value>>(&self, v: T) {
         let v = v.into();
 
+        #[cfg(feature = "leak-detection")]
+        untrack_value(self.runtime(), &v);
+
+        self.release_value(v)
+    }
+
+    /// Free the value without the leak detection.
+    pub(crate) fn release_value(&self, v: Value) {
         if v.has_ref_cnt() {
             unsafe {
                 let mut ref_cnt = v.as_ptr::<ffi::JSRefCountHeader>();
@@ -405,6 +467,10 @@ impl ContextRef {
         }
     }
 
+    pub fn is_array(&self, val: &Value) -> Result<bool, Error> {
+        self.check_bool(unsafe { ffi::JS_IsArray(self.as_ptr(), val.raw()) })
+    }
+
     pub fn is_instance_of(&self, val: &Value, obj: &Value) -> Result<bool, Error> {
         self.check_bool(unsafe { ffi::JS_IsInstanceOf(self.as_ptr(), val.raw(), obj.raw()) })
     }
@@ -445,6 +511,11 @@ impl From<f64> for Value {
 /// Create new `Value` from primitive.
 pub trait NewValue {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue;
+
+    /// Check if the value could be passed to the context, e.g. it belongs to the same runtime.
+    fn check_value(&self, _ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        Ok(())
+    }
 }
 
 impl NewValue for bool {
@@ -473,11 +544,7 @@ impl NewValue for u32 {
 
 impl NewValue for u64 {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
-        if cfg!(feature = "bignum") {
-            unsafe { ffi::JS_NewBigUint64(ctxt.as_ptr(), self) }
-        } else {
-            (self as i64).new_value(ctxt)
-        }
+        ctxt.new_uint64(self)
     }
 }
 
@@ -501,11 +568,7 @@ impl NewValue for i32 {
 
 impl NewValue for i64 {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
-        if cfg!(feature = "bignum") {
-            unsafe { ffi::JS_NewBigInt64(ctxt.as_ptr(), self) }
-        } else {
-            unsafe { ffi::JS_NewInt64(ctxt.as_ptr(), self) }
-        }
+        ctxt.new_int64(self)
     }
 }
 
@@ -547,26 +610,53 @@ impl NewValue for ffi::JSValue {
 
 impl NewValue for Value {
     fn new_value(self, _ctxt: &ContextRef) -> ffi::JSValue {
+        #[cfg(feature = "leak-detection")]
+        untrack_value(_ctxt.runtime(), &self);
+
         self.raw()
     }
+
+    fn check_value(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        ctxt.check_owner(self)
+    }
 }
 
 impl<'a> NewValue for &'a Value {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
         ctxt.clone_value(self).into()
     }
+
+    fn check_value(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        ctxt.check_owner(self)
+    }
 }
 
 impl<'a> NewValue for Local<'a, Value> {
-    fn new_value(self, _ctxt: &ContextRef) -> ffi::JSValue {
-        self.into_inner().into()
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        if let Err(err) = ctxt.check_runtime(&self) {
+            return Err::<Value, _>(err.into()).new_value(ctxt);
+        }
+
+        self.into_inner_untracked().into()
+    }
+
+    fn check_value(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        ctxt.check_runtime(self)
     }
 }
 
 impl<'a> NewValue for &'a Local<'a, Value> {
     fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        if let Err(err) = ctxt.check_runtime(self) {
+            return Err::<Value, _>(err.into()).new_value(ctxt);
+        }
+
         ctxt.clone_value(self).into()
     }
+
+    fn check_value(&self, ctxt: &ContextRef) -> Result<(), RuntimeMismatch> {
+        ctxt.check_runtime(self)
+    }
 }
 
 /// Extract primitive from `Local<Value>`.
@@ -593,19 +683,33 @@ impl ExtractValue for bool {
 
 impl ExtractValue for i32 {
     fn extract_value(v: &Local<Value>) -> Option<Self> {
-        v.as_int().or_else(|| v.to_int32())
+        v.as_int().or_else(|| {
+            v.extract_integer(
+                f64::from(i32::min_value()),
+                f64::from(i32::max_value()),
+                |v| v.as_int().or_else(|| v.to_int32()),
+                |n| n as i32,
+            )
+        })
     }
 }
 
 impl ExtractValue for i64 {
     fn extract_value(v: &Local<Value>) -> Option<Self> {
-        v.as_int().map(i64::from).or_else(|| v.to_int64())
+        v.as_int().map(i64::from).or_else(|| {
+            v.extract_integer(
+                i64::min_value() as f64,
+                i64::max_value() as f64,
+                |v| v.to_int64(),
+                |n| n as i64,
+            )
+        })
     }
 }
 
 impl ExtractValue for u64 {
     fn extract_value(v: &Local<Value>) -> Option<Self> {
-        v.to_index()
+        v.extract_integer(0.0, u64::max_value() as f64, |v| v.to_index(), |n| n as u64)
     }
 }
 
@@ -617,7 +721,76 @@ impl ExtractValue for f64 {
 
 impl ExtractValue for String {
     fn extract_value(v: &Local<Value>) -> Option<Self> {
-        v.to_cstring().map(|s| s.to_string_lossy().to_string())
+        v.ctxt.convert(|budget| {
+            budget.check_string(v).ok()?;
+
+            let s = match v.ctxt.to_js_string(v)? {
+                Ok(JsString::Utf8(s)) => s,
+                Ok(JsString::Utf16(units)) => {
+                    budget.fail(LoneSurrogate::find(&units)?);
+
+                    return None;
+                }
+                Err(err) => {
+                    budget.fail(err);
+
+                    return None;
+                }
+            };
+
+            budget.charge_string_bytes(s.len()).ok()?;
+
+            Some(s)
+        })
+    }
+}
+
+impl<T: ExtractValue> ExtractValue for Vec<T> {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        if !v.is_array().ok()? {
+            return None;
+        }
+
+        v.ctxt.convert(|budget| {
+            let _nested = budget.enter().ok()?;
+            let len = v.get_property("length")?.to_index()?;
+
+            budget.charge_elements(len as usize).ok()?;
+
+            (0..len)
+                .map(|idx| T::extract_value(&v.get_property(idx as u32)?))
+                .collect()
+        })
+    }
+}
+
+impl<T: ExtractValue> ExtractValue for HashMap<String, T> {
+    fn extract_value(v: &Local<Value>) -> Option<Self> {
+        if !v.is_object() {
+            return None;
+        }
+
+        v.ctxt.convert(|budget| {
+            let _nested = budget.enter().ok()?;
+            let names = v
+                .ctxt
+                .get_own_property_names(v, Names::STRING | Names::ENUM_ONLY)
+                .ok()?
+                .unwrap_or_default();
+
+            budget.charge_elements(names.len()).ok()?;
+
+            names
+                .into_iter()
+                .map(|name| {
+                    let key = name.to_string();
+
+                    budget.charge_string_bytes(key.len()).ok()?;
+
+                    Some((key, T::extract_value(&v.get_property(&name)?)?))
+                })
+                .collect()
+        })
     }
 }
 
@@ -660,6 +833,23 @@ impl Value {
         self.tag as i32
     }
 
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self.tag() {
+            ffi::JS_TAG_INT | ffi::JS_TAG_FLOAT64 => "number",
+            ffi::JS_TAG_BIG_INT => "bigint",
+            ffi::JS_TAG_BIG_FLOAT => "bigfloat",
+            ffi::JS_TAG_BOOL => "boolean",
+            ffi::JS_TAG_NULL => "null",
+            ffi::JS_TAG_UNDEFINED => "undefined",
+            ffi::JS_TAG_SYMBOL => "symbol",
+            ffi::JS_TAG_STRING => "string",
+            ffi::JS_TAG_OBJECT => "object",
+            ffi::JS_TAG_MODULE => "module",
+            ffi::JS_TAG_FUNCTION_BYTECODE => "function bytecode",
+            _ => "unknown",
+        }
+    }
+
     pub fn is_number(&self) -> bool {
         unsafe { ffi::JS_IsNumber(self.raw()).to_bool() }
     }
@@ -0,0 +1,360 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use failure::Error;
+
+use crate::RuntimeRef;
+
+/// The default interval of the watchdog thread checking the limits.
+pub const DEFAULT_WATCHDOG_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The event emitted when a limit of the watchdog trips.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WatchdogEvent {
+    /// the evaluation exceeded the wall-clock time limit.
+    TimeLimitExceeded {
+        /// the elapsed time of the evaluation
+        elapsed: Duration,
+        /// the time limit
+        limit: Duration,
+    },
+    /// the runtime exceeded the memory limit.
+    MemoryLimitExceeded {
+        /// the allocated memory of the runtime
+        used: usize,
+        /// the memory limit
+        limit: usize,
+    },
+}
+
+type EventHandler = Box<dyn Fn(WatchdogEvent) + Send + Sync>;
+
+struct Shared {
+    time_limit: Option<Duration>,
+    memory_limit: Option<usize>,
+    handler: Option<EventHandler>,
+    started: Mutex<Option<Instant>>,
+    tripped: AtomicBool,
+    check_memory: AtomicBool,
+    stopped: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl Shared {
+    fn trip(&self, event: WatchdogEvent) {
+        if !self.tripped.swap(true, Ordering::SeqCst) {
+            debug!("watchdog tripped, {:?}", event);
+
+            if let Some(ref handler) = self.handler {
+                handler(event)
+            }
+        }
+    }
+
+    fn run(&self, interval: Duration) {
+        let mut stopped = self.stopped.lock().unwrap();
+
+        while !*stopped {
+            stopped = self.cond.wait_timeout(stopped, interval).unwrap().0;
+
+            if *stopped {
+                break;
+            }
+
+            let started = *self.started.lock().unwrap();
+
+            if let Some(started) = started {
+                if self.memory_limit.is_some() {
+                    self.check_memory.store(true, Ordering::SeqCst);
+                }
+
+                if let Some(limit) = self.time_limit {
+                    let elapsed = started.elapsed();
+
+                    if elapsed > limit {
+                        self.trip(WatchdogEvent::TimeLimitExceeded { elapsed, limit });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Watched(RefCell<Option<Arc<Shared>>>);
+
+impl Watched {
+    fn get(rt: &RuntimeRef) -> Option<Arc<Shared>> {
+        rt.state::<Watched>().0.borrow().clone()
+    }
+}
+
+pub(crate) fn is_watched(rt: &RuntimeRef) -> bool {
+    rt.state::<Watched>().0.borrow().is_some()
+}
+
+pub(crate) fn start_evaluation(rt: &RuntimeRef) {
+    if let Some(shared) = Watched::get(rt) {
+        *shared.started.lock().unwrap() = Some(Instant::now());
+        shared.tripped.store(false, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn end_evaluation(rt: &RuntimeRef) {
+    if let Some(shared) = Watched::get(rt) {
+        *shared.started.lock().unwrap() = None;
+    }
+}
+
+/// Check the limits in the interrupt handler, returns `true` if the watchdog tripped.
+pub(crate) fn is_tripped(rt: &RuntimeRef) -> bool {
+    if let Some(shared) = Watched::get(rt) {
+        if let Some(limit) = shared.memory_limit {
+            if shared.check_memory.swap(false, Ordering::SeqCst) {
+                let used = rt.memory_usage().malloc_size as usize;
+
+                if used > limit {
+                    shared.trip(WatchdogEvent::MemoryLimitExceeded { used, limit });
+                }
+            }
+        }
+
+        shared.tripped.load(Ordering::SeqCst)
+    } else {
+        false
+    }
+}
+
+/// The builder of `Watchdog`.
+pub struct Builder {
+    time_limit: Option<Duration>,
+    memory_limit: Option<usize>,
+    interval: Duration,
+    handler: Option<EventHandler>,
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("time_limit", &self.time_limit)
+            .field("memory_limit", &self.memory_limit)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl Builder {
+    /// Set the wall-clock time limit of each evaluation.
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Set the memory limit of the runtime.
+    ///
+    /// The memory usage is sampled in the interrupt handler after each interval.
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Set the interval of the watchdog thread checking the limits.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the handler which is called when a limit trips.
+    ///
+    /// The handler may be called from the watchdog thread or the runtime thread.
+    pub fn on_event<F: Fn(WatchdogEvent) + Send + Sync + 'static>(mut self, handler: F) -> Self {
+        self.handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Spawn the watchdog thread to watch the runtime.
+    pub fn watch(self, rt: &RuntimeRef) -> Result<Watchdog<'_>, Error> {
+        if is_watched(rt) {
+            bail!("runtime already watched")
+        }
+
+        let shared = Arc::new(Shared {
+            time_limit: self.time_limit,
+            memory_limit: self.memory_limit,
+            handler: self.handler,
+            started: Mutex::new(None),
+            tripped: AtomicBool::new(false),
+            check_memory: AtomicBool::new(false),
+            stopped: Mutex::new(false),
+            cond: Condvar::new(),
+        });
+
+        let thread = {
+            let shared = shared.clone();
+            let interval = self.interval;
+
+            thread::Builder::new()
+                .name("qjs-watchdog".to_owned())
+                .spawn(move || shared.run(interval))?
+        };
+
+        *rt.state::<Watched>().0.borrow_mut() = Some(shared.clone());
+        rt.update_interrupt_handler();
+
+        Ok(Watchdog {
+            rt,
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// `Watchdog` spawns a monitoring thread which enforces the wall-clock and memory limits
+/// for the evaluations on the watched `Runtime`.
+///
+/// The evaluation will be interrupted with an `InternalError` when a limit trips,
+/// the watchdog thread is stopped when dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use qjs::{Context, Eval, Runtime, Watchdog};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+/// let _watchdog = Watchdog::builder()
+///     .with_time_limit(Duration::from_millis(50))
+///     .on_event(|event| println!("{:?}", event))
+///     .watch(&rt)
+///     .unwrap();
+///
+/// assert!(ctxt.eval::<_, ()>("while(true) {}", Eval::GLOBAL).is_err());
+/// ```
+pub struct Watchdog<'a> {
+    rt: &'a RuntimeRef,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for Watchdog<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("time_limit", &self.shared.time_limit)
+            .field("memory_limit", &self.shared.memory_limit)
+            .finish()
+    }
+}
+
+impl Watchdog<'_> {
+    /// Create a builder of `Watchdog`.
+    pub fn builder() -> Builder {
+        Builder {
+            time_limit: None,
+            memory_limit: None,
+            interval: DEFAULT_WATCHDOG_INTERVAL,
+            handler: None,
+        }
+    }
+
+    /// Returns `true` if a limit tripped in the current or last evaluation.
+    pub fn is_tripped(&self) -> bool {
+        self.shared.tripped.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Watchdog<'_> {
+    fn drop(&mut self) {
+        *self.shared.stopped.lock().unwrap() = true;
+        self.shared.cond.notify_all();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        self.rt.state::<Watched>().0.borrow_mut().take();
+        self.rt.update_interrupt_handler();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    use std::sync::mpsc;
+
+    use crate::{Context, ErrorKind, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn watchdog() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+
+        let handler = move |event| tx.lock().unwrap().send(event).unwrap();
+        let handler = Arc::new(handler);
+        let memory_handler = handler.clone();
+
+        let watchdog = Watchdog::builder()
+            .with_time_limit(Duration::from_millis(50))
+            .with_interval(Duration::from_millis(5))
+            .on_event(move |event| handler(event))
+            .watch(&rt)
+            .unwrap();
+
+        assert!(Watchdog::builder().watch(&rt).is_err());
+
+        assert_eq!(
+            ctxt.eval::<_, ()>("while(true) {}", Eval::GLOBAL)
+                .unwrap_err()
+                .downcast::<ErrorKind>()
+                .unwrap()
+                .message(),
+            "interrupted"
+        );
+        assert!(watchdog.is_tripped());
+
+        match rx.recv().unwrap() {
+            WatchdogEvent::TimeLimitExceeded { elapsed, limit } => {
+                assert!(elapsed > limit);
+                assert_eq!(limit, Duration::from_millis(50));
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        assert_eq!(ctxt.eval::<_, i32>("1+2", Eval::GLOBAL).unwrap(), Some(3));
+        assert!(!watchdog.is_tripped());
+
+        mem::drop(watchdog);
+
+        let _watchdog = Watchdog::builder()
+            .with_memory_limit(16 * 1024 * 1024)
+            .with_interval(Duration::from_millis(5))
+            .on_event(move |event| memory_handler(event))
+            .watch(&rt)
+            .unwrap();
+
+        assert!(ctxt
+            .eval::<_, ()>(
+                "var a = []; while(true) { a.push('x'.repeat(1024)) }",
+                Eval::GLOBAL
+            )
+            .is_err());
+
+        match rx.recv().unwrap() {
+            WatchdogEvent::MemoryLimitExceeded { used, limit } => assert!(used > limit),
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        ctxt.eval::<_, ()>("a = null", Eval::GLOBAL).unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+(function (natives, { DOMException, Event, EventTarget }) {
+    const signals = new WeakMap();
+    const controllers = new WeakMap();
+
+    function newSignal() {
+        const signal = Reflect.construct(EventTarget, [], AbortSignal);
+
+        signals.set(signal, {
+            aborted: false,
+            reason: undefined,
+            token: natives.newToken(),
+            onabort: null,
+        });
+
+        return signal;
+    }
+
+    function abort(signal, reason) {
+        const state = signals.get(signal);
+
+        if (state.aborted) {
+            return;
+        }
+
+        state.aborted = true;
+        state.reason = reason !== undefined ? reason : new DOMException('signal is aborted without reason', 'AbortError');
+
+        natives.cancel(state.token);
+
+        signal.dispatchEvent(new Event('abort'));
+    }
+
+    class AbortSignal extends EventTarget {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        static abort(reason) {
+            const signal = newSignal();
+
+            abort(signal, reason);
+
+            return signal;
+        }
+
+        static any(signals) {
+            const signal = newSignal();
+
+            for (const source of signals) {
+                if (source.aborted) {
+                    abort(signal, source.reason);
+                    break;
+                }
+
+                source.addEventListener('abort', () => abort(signal, source.reason), { once: true });
+            }
+
+            return signal;
+        }
+
+        get aborted() {
+            return signals.get(this).aborted;
+        }
+
+        get reason() {
+            return signals.get(this).reason;
+        }
+
+        get onabort() {
+            return signals.get(this).onabort;
+        }
+
+        set onabort(listener) {
+            const state = signals.get(this);
+
+            // the handler is registered as a listener when first set
+            if (state.onabort === null && typeof listener === 'function') {
+                this.addEventListener('abort', function (event) {
+                    if (state.onabort !== null) {
+                        state.onabort.call(this, event);
+                    }
+                });
+            }
+
+            state.onabort = typeof listener === 'function' ? listener : null;
+        }
+
+        throwIfAborted() {
+            const state = signals.get(this);
+
+            if (state.aborted) {
+                throw state.reason;
+            }
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'AbortSignal';
+        }
+    }
+
+    class AbortController {
+        constructor() {
+            controllers.set(this, newSignal());
+        }
+
+        get signal() {
+            return controllers.get(this);
+        }
+
+        abort(reason) {
+            abort(controllers.get(this), reason);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'AbortController';
+        }
+    }
+
+    // returns the cancellation token of the signal for the host
+    function $tokenOf(signal) {
+        const state = signals.get(signal);
+
+        return state !== undefined ? state.token : undefined;
+    }
+
+    return { AbortController, AbortSignal, $tokenOf };
+})
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{ContextRef, Value, UNDEFINED};
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    callbacks: Mutex<Vec<Callback>>,
+}
+
+/// The token to observe the cancellation of an `AbortSignal` from the host.
+///
+/// The clones of `CancellationToken` share the same state, and could be sent to other threads.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<Inner>);
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CancellationToken")
+            .field(&self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    /// Create a token which is not cancelled.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancel the token and call the registered callbacks, only the first call takes effect.
+    pub fn cancel(&self) {
+        if !self.0.cancelled.swap(true, Ordering::SeqCst) {
+            let callbacks = mem::replace(&mut *self.0.callbacks.lock().unwrap(), vec![]);
+
+            for callback in callbacks {
+                callback()
+            }
+        }
+    }
+
+    /// Register a callback which is called when the token cancelled.
+    ///
+    /// The callback is called immediately if the token was cancelled.
+    pub fn on_cancel<F: FnOnce() + Send + 'static>(&self, callback: F) {
+        {
+            let mut callbacks = self.0.callbacks.lock().unwrap();
+
+            if !self.is_cancelled() {
+                callbacks.push(Box::new(callback));
+                return;
+            }
+        }
+
+        callback()
+    }
+}
+
+impl ContextRef {
+    /// Returns the `CancellationToken` of an `AbortSignal`.
+    ///
+    /// The token is cancelled when the signal aborted, so the native functions could cancel the host IO.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.init_web_platform().unwrap();
+    ///
+    /// let controller = ctxt.eval_script("new AbortController()", "<evalScript>", Eval::GLOBAL).unwrap();
+    /// let signal = controller.get_property("signal").unwrap();
+    /// let token = ctxt.cancellation_token(&signal).unwrap();
+    ///
+    /// assert!(!token.is_cancelled());
+    ///
+    /// controller.invoke("abort", ()).unwrap();
+    ///
+    /// assert!(token.is_cancelled());
+    /// ```
+    pub fn cancellation_token(&self, signal: &Value) -> Option<CancellationToken> {
+        let token_of = self.web_host_binding("$tokenOf")?;
+        let token = self.call(&token_of, None, signal).ok()?;
+        let token = token.downcast_ref::<CancellationToken>().ok()?;
+
+        Some(token.clone())
+    }
+}
+
+pub fn new_token(ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Value {
+    ctxt.new_userdata(CancellationToken::new())
+        .into_inner_untracked()
+}
+
+pub fn cancel(_ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    if let Ok(token) = args[0].downcast_ref::<CancellationToken>() {
+        token.cancel()
+    }
+
+    UNDEFINED
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn cancellation_token() {
+        let token = CancellationToken::new();
+        let called = Arc::new(AtomicUsize::new(0));
+
+        let counter = called.clone();
+        token.on_cancel(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        token.clone().cancel();
+        token.cancel();
+
+        assert!(token.is_cancelled());
+        assert_eq!(called.load(Ordering::SeqCst), 1);
+
+        let counter = called.clone();
+        token.on_cancel(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(called.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn abort_signal() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        for (script, expected) in &[
+            ("typeof $tokenOf", "undefined"),
+            (
+                r#"
+var c = new AbortController(), events = [];
+c.signal.onabort = e => events.push('on' + e.type);
+c.signal.addEventListener('abort', function (e) { events.push(this === c.signal) }, { once: true });
+c.abort('why');
+c.abort('again');
+events.join() + ' ' + c.signal.aborted + ' ' + c.signal.reason
+"#,
+                "onabort,true true why",
+            ),
+            ("AbortSignal.abort().reason.name", "AbortError"),
+            ("try { AbortSignal.abort(1).throwIfAborted() } catch (e) { String(e) }", "1"),
+            (
+                "var a = new AbortController(), b = new AbortController(), s = AbortSignal.any([a.signal, b.signal]); b.abort('b'); s.reason",
+                "b",
+            ),
+            ("try { new AbortSignal() } catch (e) { e.name }", "TypeError"),
+            ("Object.prototype.toString.call(new AbortController().signal)", "[object AbortSignal]"),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(*script, Eval::GLOBAL).unwrap().unwrap(),
+                *expected,
+                "{}",
+                script
+            );
+        }
+
+        let controller = ctxt
+            .eval_script("new AbortController()", "<evalScript>", Eval::GLOBAL)
+            .unwrap();
+        let signal = controller.get_property("signal").unwrap();
+        let token = ctxt.cancellation_token(&signal).unwrap();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+
+        token.on_cancel(move || flag.store(true, Ordering::SeqCst));
+
+        assert!(ctxt.cancellation_token(&controller).is_none());
+        assert!(!token.is_cancelled());
+
+        controller.invoke("abort", ()).unwrap();
+
+        assert!(token.is_cancelled());
+        assert!(cancelled.load(Ordering::SeqCst));
+    }
+}
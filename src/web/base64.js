@@ -0,0 +1,34 @@
+(function (natives, { DOMException }) {
+    function btoa(data) {
+        if (arguments.length < 1) {
+            throw new TypeError("Failed to execute 'btoa': 1 argument required, but only 0 present.");
+        }
+
+        const encoded = natives.btoa(String(data));
+
+        if (encoded === null) {
+            throw new DOMException(
+                'The string to be encoded contains characters outside of the Latin1 range.',
+                'InvalidCharacterError'
+            );
+        }
+
+        return encoded;
+    }
+
+    function atob(data) {
+        if (arguments.length < 1) {
+            throw new TypeError("Failed to execute 'atob': 1 argument required, but only 0 present.");
+        }
+
+        const decoded = natives.atob(String(data));
+
+        if (decoded === null) {
+            throw new DOMException('The string to be decoded is not correctly encoded.', 'InvalidCharacterError');
+        }
+
+        return decoded;
+    }
+
+    return { atob, btoa };
+})
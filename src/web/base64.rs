@@ -0,0 +1,108 @@
+use crate::{ContextRef, Value, NULL};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode the bytes with the standard base64 alphabet and padding.
+pub fn encode(input: &[u8]) -> String {
+    let mut s = String::with_capacity((input.len() + 2) / 3 * 4);
+
+    for chunk in input.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - i * 8));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3F] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+
+    s
+}
+
+/// Decode the forgiving-base64 string, returns `None` if the input is invalid.
+pub fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut input = input
+        .bytes()
+        .filter(|b| !b" \t\n\x0C\r".contains(b))
+        .collect::<Vec<_>>();
+
+    if input.len() % 4 == 0 {
+        for _ in 0..2 {
+            if input.last() == Some(&b'=') {
+                input.pop();
+            }
+        }
+    }
+
+    if input.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut buf = Vec::with_capacity(input.len() * 3 / 4);
+
+    for chunk in input.chunks(4) {
+        let mut n = 0u32;
+
+        for (i, &b) in chunk.iter().enumerate() {
+            let v = ALPHABET.iter().position(|&c| c == b)? as u32;
+
+            n |= v << (18 - i * 6);
+        }
+
+        for i in 0..chunk.len() - 1 {
+            buf.push((n >> (16 - i * 8)) as u8);
+        }
+    }
+
+    Some(buf)
+}
+
+pub fn btoa(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let data = ctxt.clone_value(&args[0]).to_string();
+
+    if data.chars().any(|c| c as u32 > 0xFF) {
+        NULL
+    } else {
+        ctxt.new_value(encode(&data.chars().map(|c| c as u8).collect::<Vec<_>>()))
+    }
+}
+
+pub fn atob(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let data = ctxt.clone_value(&args[0]).to_string();
+
+    match decode(&data) {
+        Some(buf) => ctxt.new_value(buf.into_iter().map(char::from).collect::<String>()),
+        None => NULL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64() {
+        for (plain, encoded) in &[
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(plain.as_bytes()), *encoded);
+            assert_eq!(decode(encoded).unwrap(), plain.as_bytes());
+        }
+
+        assert_eq!(decode(" Zm9v\nYg ").unwrap(), b"foob");
+        assert_eq!(decode("Zm9vYg").unwrap(), b"foob");
+        assert!(decode("Zm9vY").is_none());
+        assert!(decode("Zm9v!").is_none());
+    }
+}
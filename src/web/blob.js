@@ -0,0 +1,193 @@
+(function (natives) {
+    const blobs = new WeakMap();
+    const files = new WeakMap();
+
+    const CHUNK_SIZE = 65536;
+
+    function normalizeType(type) {
+        type = type === undefined ? '' : String(type);
+
+        return /^[\x20-\x7E]*$/.test(type) ? type.toLowerCase() : '';
+    }
+
+    function relativeIndex(index, size, defaultValue) {
+        if (index === undefined) {
+            return defaultValue;
+        }
+
+        index = Math.trunc(Number(index)) || 0;
+
+        return index < 0 ? Math.max(size + index, 0) : Math.min(index, size);
+    }
+
+    // read the bytes of the blob as an `ArrayBuffer`
+    function readAll(blob) {
+        const { source, start, end } = blobs.get(blob);
+
+        return natives.read(source, start, end);
+    }
+
+    function toSource(parts) {
+        if (parts === undefined) {
+            return natives.fromBytes(new ArrayBuffer(0));
+        }
+
+        if (typeof parts !== 'object' || parts === null || typeof parts[Symbol.iterator] !== 'function') {
+            throw new TypeError("Failed to construct 'Blob': The provided value cannot be converted to a sequence.");
+        }
+
+        const buffers = Array.from(parts, (part) => {
+            if (part instanceof Blob) {
+                return readAll(part);
+            }
+
+            if (part instanceof ArrayBuffer) {
+                return part;
+            }
+
+            if (ArrayBuffer.isView(part)) {
+                return part.buffer.slice(part.byteOffset, part.byteOffset + part.byteLength);
+            }
+
+            return natives.encode(String(part));
+        });
+        const bytes = new Uint8Array(buffers.reduce((size, buf) => size + buf.byteLength, 0));
+        let offset = 0;
+
+        for (const buf of buffers) {
+            bytes.set(new Uint8Array(buf), offset);
+            offset += buf.byteLength;
+        }
+
+        return natives.fromBytes(bytes.buffer);
+    }
+
+    function newBlob(proto, source, start, end, type) {
+        const blob = Object.create(proto);
+
+        blobs.set(blob, { source, start, end, type });
+
+        return blob;
+    }
+
+    class Blob {
+        constructor(parts, options = {}) {
+            const source = toSource(parts);
+
+            blobs.set(this, { source, start: 0, end: natives.size(source), type: normalizeType(options.type) });
+        }
+
+        get size() {
+            const { start, end } = blobs.get(this);
+
+            return end - start;
+        }
+
+        get type() {
+            return blobs.get(this).type;
+        }
+
+        slice(start, end, contentType) {
+            const state = blobs.get(this);
+            const size = state.end - state.start;
+            const from = relativeIndex(start, size, 0);
+            const to = relativeIndex(end, size, size);
+
+            return newBlob(
+                Blob.prototype,
+                state.source,
+                state.start + from,
+                state.start + Math.max(to, from),
+                normalizeType(contentType)
+            );
+        }
+
+        arrayBuffer() {
+            // the source is read when the pending jobs executed
+            return Promise.resolve().then(() => readAll(this));
+        }
+
+        text() {
+            return this.arrayBuffer().then((buf) => natives.decode(buf, 'utf-8', false, false, false)[0]);
+        }
+
+        stream() {
+            if (typeof ReadableStream !== 'function') {
+                throw new TypeError('ReadableStream is not supported');
+            }
+
+            const { source, start, end } = blobs.get(this);
+            let offset = start;
+
+            return new ReadableStream({
+                type: 'bytes',
+                pull(controller) {
+                    if (offset >= end) {
+                        controller.close();
+                        return;
+                    }
+
+                    const next = Math.min(offset + CHUNK_SIZE, end);
+
+                    controller.enqueue(new Uint8Array(natives.read(source, offset, next)));
+                    offset = next;
+                },
+            });
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Blob';
+        }
+    }
+
+    class File extends Blob {
+        constructor(parts, name, options = {}) {
+            if (arguments.length < 2) {
+                throw new TypeError(`Failed to construct 'File': 2 arguments required, but only ${arguments.length} present.`);
+            }
+
+            super(parts, options);
+
+            files.set(this, {
+                name: String(name),
+                lastModified: options.lastModified !== undefined ? Number(options.lastModified) : Date.now(),
+            });
+        }
+
+        get name() {
+            return files.get(this).name;
+        }
+
+        get lastModified() {
+            return files.get(this).lastModified;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'File';
+        }
+    }
+
+    // create a `Blob` or `File` of the host source
+    function $newBlob(source, type, name, lastModified) {
+        const size = natives.size(source);
+
+        if (name === undefined) {
+            return newBlob(Blob.prototype, source, 0, size, normalizeType(type));
+        }
+
+        const file = newBlob(File.prototype, source, 0, size, normalizeType(type));
+
+        files.set(file, { name: String(name), lastModified });
+
+        return file;
+    }
+
+    // returns the source and range of the blob
+    function $sourceOf(blob) {
+        const state = blobs.get(blob);
+
+        return state !== undefined ? [state.source, state.start, state.end] : undefined;
+    }
+
+    return { Blob, File, $newBlob, $sourceOf };
+})
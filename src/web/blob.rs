@@ -0,0 +1,385 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Read};
+use std::mem;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+
+use crate::{ContextRef, ErrorKind, Local, NewValue, Value};
+
+/// The contents of a `Blob` supplied by the host.
+pub enum BlobData {
+    /// the bytes in memory
+    Bytes(Vec<u8>),
+    /// the memory-mapped file
+    #[cfg(feature = "mmap")]
+    Mmap(memmap::Mmap),
+    /// the lazy source of the given size, which is read on the first access
+    Reader(Box<dyn Read + Send>, u64),
+}
+
+impl fmt::Debug for BlobData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlobData::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            #[cfg(feature = "mmap")]
+            BlobData::Mmap(mmap) => f.debug_tuple("Mmap").field(&mmap.len()).finish(),
+            BlobData::Reader(_, size) => f.debug_tuple("Reader").field(size).finish(),
+        }
+    }
+}
+
+impl From<Vec<u8>> for BlobData {
+    fn from(bytes: Vec<u8>) -> Self {
+        BlobData::Bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a [u8]> for BlobData {
+    fn from(bytes: &[u8]) -> Self {
+        BlobData::Bytes(bytes.to_vec())
+    }
+}
+
+impl BlobData {
+    /// Create a lazy source which reads `size` bytes from the reader on the first access.
+    pub fn from_reader<R: Read + Send + 'static>(reader: R, size: u64) -> Self {
+        BlobData::Reader(Box::new(reader), size)
+    }
+
+    /// Map the file into memory.
+    #[cfg(feature = "mmap")]
+    pub fn map_file(file: &std::fs::File) -> io::Result<Self> {
+        unsafe { memmap::Mmap::map(file) }.map(BlobData::Mmap)
+    }
+
+    /// The size of the contents.
+    pub fn len(&self) -> u64 {
+        match self {
+            BlobData::Bytes(bytes) => bytes.len() as u64,
+            #[cfg(feature = "mmap")]
+            BlobData::Mmap(mmap) => mmap.len() as u64,
+            BlobData::Reader(_, size) => *size,
+        }
+    }
+
+    /// Returns `true` if the contents is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The maximum bytes preallocated for reading a lazy source.
+const MAX_PREALLOCATED: u64 = 64 * 1024;
+
+/// The source of `Blob` holds by a userdata object, shared by the sliced blobs.
+struct Source(RefCell<BlobData>);
+
+impl Source {
+    fn size(&self) -> u64 {
+        self.0.borrow().len()
+    }
+
+    fn read(&self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        let mut data = self.0.borrow_mut();
+
+        if let BlobData::Reader(..) = *data {
+            if let BlobData::Reader(mut reader, size) =
+                mem::replace(&mut *data, BlobData::Bytes(vec![]))
+            {
+                // the size is supplied by the host, the buffer grows as the contents read
+                let mut bytes = Vec::with_capacity(size.min(MAX_PREALLOCATED) as usize);
+                let res = match (&mut reader).take(size).read_to_end(&mut bytes) {
+                    Ok(_) if (bytes.len() as u64) < size => Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "blob source is shorter than its size",
+                    )),
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(err),
+                };
+
+                if let Err(err) = res {
+                    // restore the source with the bytes already read, so the next access retries the reader
+                    *data = BlobData::Reader(Box::new(io::Cursor::new(bytes).chain(reader)), size);
+
+                    return Err(err);
+                }
+
+                *data = BlobData::Bytes(bytes);
+            }
+        }
+
+        let bytes: &[u8] = match *data {
+            BlobData::Bytes(ref bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            BlobData::Mmap(ref mmap) => mmap,
+            BlobData::Reader(..) => unreachable!(),
+        };
+
+        bytes
+            .get(start as usize..end as usize)
+            .map(|bytes| bytes.to_vec())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "out of range"))
+    }
+}
+
+impl ContextRef {
+    /// Create a `Blob` backed by the host contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{BlobData, Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.init_web_platform().unwrap();
+    ///
+    /// let blob = ctxt
+    ///     .new_blob(BlobData::from_reader(&b"hello world"[..], 11), "text/plain")
+    ///     .unwrap();
+    ///
+    /// ctxt.global_object().set_property("blob", blob).unwrap();
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("blob.slice(6).size", Eval::GLOBAL).unwrap(), Some(5));
+    /// ```
+    pub fn new_blob<T: Into<BlobData>>(
+        &self,
+        data: T,
+        content_type: &str,
+    ) -> Result<Local<Value>, Error> {
+        let new_blob = self.blob_binding("$newBlob")?;
+
+        self.call(
+            &new_blob,
+            None,
+            (
+                self.new_userdata(Source(RefCell::new(data.into()))),
+                content_type,
+            ),
+        )
+    }
+
+    /// Create a `File` backed by the host contents.
+    pub fn new_file<T: Into<BlobData>>(
+        &self,
+        data: T,
+        name: &str,
+        content_type: &str,
+        last_modified: SystemTime,
+    ) -> Result<Local<Value>, Error> {
+        let new_blob = self.blob_binding("$newBlob")?;
+        let last_modified = match last_modified.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as f64,
+            Err(err) => -(err.duration().as_millis() as f64),
+        };
+
+        self.call(
+            &new_blob,
+            None,
+            (
+                self.new_userdata(Source(RefCell::new(data.into()))),
+                content_type,
+                name,
+                last_modified,
+            ),
+        )
+    }
+
+    /// Read the contents of a `Blob` or `File`.
+    pub fn read_blob(&self, blob: &Value) -> Result<Vec<u8>, Error> {
+        let source_of = self.blob_binding("$sourceOf")?;
+        let res = self.call(&source_of, None, blob)?;
+
+        if res.is_undefined() {
+            bail!("not a Blob object")
+        }
+
+        let source = res
+            .get_property(0u32)
+            .ok_or_else(|| format_err!("missing blob source"))?;
+        let start = res
+            .get_property(1u32)
+            .and_then(|v| v.to_index())
+            .unwrap_or_default();
+        let end = res
+            .get_property(2u32)
+            .and_then(|v| v.to_index())
+            .unwrap_or_default();
+        let source = source.downcast_ref::<Source>()?;
+
+        Ok(source.read(start, end)?)
+    }
+
+    fn blob_binding(&self, name: &str) -> Result<Local<Value>, Error> {
+        self.web_host_binding(name)
+            .ok_or_else(|| format_err!("web platform not initialized"))
+    }
+}
+
+pub fn from_bytes(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    match ctxt.get_array_buffer(&args[0]) {
+        Some(buf) => ctxt
+            .new_userdata(Source(RefCell::new(BlobData::from(buf.as_ref()))))
+            .into_inner_untracked(),
+        None => ErrorKind::TypeError("expected ArrayBuffer".into(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+pub fn size(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    match args[0].downcast_ref::<Source>() {
+        Ok(source) => ctxt.new_value(source.size() as f64),
+        Err(err) => ErrorKind::TypeError(err.to_string(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+pub fn read(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let start = ctxt.to_index(&args[1]).unwrap_or_default();
+    let end = ctxt.to_index(&args[2]).unwrap_or_default();
+    let res = args[0]
+        .downcast_ref::<Source>()
+        .and_then(|source| source.read(start, end).map_err(Error::from));
+
+    match res {
+        Ok(mut bytes) => ctxt.new_value(ctxt.new_array_buffer_copy(&mut bytes)),
+        Err(err) => ErrorKind::Error(format!("fail to read blob, {}", err), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{Context, Eval, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn blob() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        for (script, expected) in &[
+            (
+                "var b = new Blob(['ab', new Uint8Array([99]), new Blob(['d€'])], { type: 'Text/Plain' }); b.size + ' ' + b.type",
+                "7 text/plain",
+            ),
+            ("String(new Blob().size)", "0"),
+            ("String(new Blob(['abcdef']).slice(1, -1).slice(1).size)", "3"),
+            ("new Blob(['abc']).slice(2, 1, 'x/y').size + new Blob(['abc']).slice(0, 1, 'x/y').type", "0x/y"),
+            (
+                "var f = new File(['x'], 'a.txt', { lastModified: 42 }); [f.name, f.lastModified, f.size, f instanceof Blob].join()",
+                "a.txt,42,1,true",
+            ),
+            ("try { new File(['x']) } catch (e) { e.name }", "TypeError"),
+            ("try { new Blob('abc') } catch (e) { e.name }", "TypeError"),
+            ("Object.prototype.toString.call(new File([], 'f'))", "[object File]"),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(*script, Eval::GLOBAL).unwrap().unwrap(),
+                *expected,
+                "{}",
+                script
+            );
+        }
+
+        let lazy = ctxt
+            .new_blob(
+                BlobData::from_reader(Cursor::new(b"hello world".to_vec()), 11),
+                "text/plain",
+            )
+            .unwrap();
+        let file = ctxt
+            .new_file(b"data".to_vec(), "data.bin", "", UNIX_EPOCH)
+            .unwrap();
+        let short = ctxt
+            .new_blob(BlobData::from_reader(Cursor::new(b"abc".to_vec()), 5), "")
+            .unwrap();
+
+        assert_eq!(ctxt.read_blob(&lazy).unwrap(), b"hello world");
+        assert!(ctxt.read_blob(&ctxt.bind(ctxt.new_object())).is_err());
+
+        ctxt.global_object().set_property("lazy", lazy).unwrap();
+        ctxt.global_object().set_property("file", file).unwrap();
+        ctxt.global_object().set_property("short", short).unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var results = [];
+lazy.slice(6).text().then(text => results.push(text));
+new Blob([lazy.slice(0, 5), ' ', file]).arrayBuffer().then(buf => results.push(new TextDecoder().decode(buf)));
+short.text().catch(e => results.push(e.message));
+results.push([file.name, file.lastModified, file.type, short.size].join());
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+
+        assert_eq!(
+            ctxt.eval::<_, String>("results.join('|')", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "data.bin,0,,5|hello data|world|fail to read blob, blob source is shorter than its size"
+        );
+    }
+
+    #[test]
+    fn restore_reader() {
+        let _ = pretty_env_logger::try_init();
+
+        /// The reader fails once after reading the first chunk.
+        struct Flaky(Cursor<Vec<u8>>, bool);
+
+        impl Read for Flaky {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.position() == 5 && !self.1 {
+                    self.1 = true;
+
+                    return Err(io::Error::new(io::ErrorKind::Other, "flaky"));
+                }
+
+                let len = buf.len().min(5);
+
+                self.0.read(&mut buf[..len])
+            }
+        }
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        let reader = Flaky(Cursor::new(b"hello world".to_vec()), false);
+        let blob = ctxt
+            .new_blob(BlobData::from_reader(reader, u64::max_value()), "")
+            .unwrap();
+
+        assert_eq!(ctxt.read_blob(&blob).unwrap_err().to_string(), "flaky");
+
+        let blob = ctxt
+            .new_blob(
+                BlobData::from_reader(Flaky(Cursor::new(b"hello world".to_vec()), false), 11),
+                "",
+            )
+            .unwrap();
+
+        assert_eq!(ctxt.read_blob(&blob).unwrap_err().to_string(), "flaky");
+        assert_eq!(ctxt.read_blob(&blob).unwrap(), b"hello world");
+    }
+}
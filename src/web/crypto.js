@@ -0,0 +1,92 @@
+(function (natives, { DOMException }) {
+    const INTEGER_ARRAYS = [Int8Array, Uint8Array, Uint8ClampedArray, Int16Array, Uint16Array, Int32Array, Uint32Array]
+        .concat(typeof BigInt64Array === 'function' ? [BigInt64Array, BigUint64Array] : []);
+    const MAX_RANDOM_BYTES = 65536;
+    const DIGEST_ALGORITHMS = ['SHA-1', 'SHA-256', 'SHA-384', 'SHA-512'];
+
+    function toArrayBuffer(data) {
+        if (data instanceof ArrayBuffer) {
+            return data.slice(0);
+        }
+
+        if (ArrayBuffer.isView(data)) {
+            return data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength);
+        }
+
+        throw new TypeError('The provided value is not of type (ArrayBuffer or ArrayBufferView)');
+    }
+
+    class SubtleCrypto {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        digest(algorithm, data) {
+            return new Promise((resolve) => {
+                const name = typeof algorithm === 'object' && algorithm !== null ? algorithm.name : algorithm;
+                const normalized = String(name).toUpperCase();
+
+                if (!DIGEST_ALGORITHMS.includes(normalized)) {
+                    throw new DOMException('Unrecognized algorithm name', 'NotSupportedError');
+                }
+
+                resolve(natives.digest(normalized, toArrayBuffer(data)));
+            });
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'SubtleCrypto';
+        }
+    }
+
+    const subtle = Object.create(SubtleCrypto.prototype);
+
+    class Crypto {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        get subtle() {
+            return subtle;
+        }
+
+        getRandomValues(array) {
+            if (!INTEGER_ARRAYS.some((type) => array instanceof type)) {
+                throw new DOMException(
+                    "Failed to execute 'getRandomValues' on 'Crypto': The provided ArrayBufferView is not an integer array type.",
+                    'TypeMismatchError'
+                );
+            }
+
+            if (array.byteLength > MAX_RANDOM_BYTES) {
+                throw new DOMException(
+                    `Failed to execute 'getRandomValues' on 'Crypto': The ArrayBufferView's byte length (${array.byteLength}) exceeds the number of bytes of entropy available via this API (${MAX_RANDOM_BYTES}).`,
+                    'QuotaExceededError'
+                );
+            }
+
+            natives.fill(array.buffer, array.byteOffset, array.byteLength);
+
+            return array;
+        }
+
+        randomUUID() {
+            const bytes = this.getRandomValues(new Uint8Array(16));
+
+            bytes[6] = (bytes[6] & 0x0f) | 0x40;
+            bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+            const hex = Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
+
+            return `${hex.slice(0, 8)}-${hex.slice(8, 12)}-${hex.slice(12, 16)}-${hex.slice(16, 20)}-${hex.slice(20)}`;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Crypto';
+        }
+    }
+
+    const crypto = Object.create(Crypto.prototype);
+
+    return { Crypto, SubtleCrypto, crypto };
+})
@@ -0,0 +1,58 @@
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{ContextRef, ErrorKind, NewValue, Value, UNDEFINED};
+
+/// Fill the bytes of the `ArrayBuffer` in the range with the random values.
+pub fn fill(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let mut buf = match ctxt.get_array_buffer(&args[0]) {
+        Some(buf) => buf,
+        None => {
+            return ErrorKind::TypeError("expected ArrayBuffer".into(), None)
+                .new_value(ctxt)
+                .into()
+        }
+    };
+    let offset = ctxt.to_index(&args[1]).unwrap_or_default() as usize;
+    let len = ctxt.to_index(&args[2]).unwrap_or_default() as usize;
+
+    let res = match buf.get_mut(offset..offset + len) {
+        Some(bytes) => getrandom::getrandom(bytes).map_err(|err| err.to_string()),
+        None => Err("out of bounds".to_owned()),
+    };
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => ErrorKind::Error(format!("fail to generate random values, {}", err), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+/// Digest the `ArrayBuffer` with the normalized algorithm name, returns the digest as an `ArrayBuffer`.
+pub fn digest(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let algorithm = ctxt.clone_value(&args[0]).to_string();
+    let buf = match ctxt.get_array_buffer(&args[1]) {
+        Some(buf) => buf,
+        None => {
+            return ErrorKind::TypeError("expected ArrayBuffer".into(), None)
+                .new_value(ctxt)
+                .into()
+        }
+    };
+    let data = buf.as_ref();
+
+    let mut digest = match algorithm.as_str() {
+        "SHA-1" => Sha1::digest(data).to_vec(),
+        "SHA-256" => Sha256::digest(data).to_vec(),
+        "SHA-384" => Sha384::digest(data).to_vec(),
+        "SHA-512" => Sha512::digest(data).to_vec(),
+        _ => {
+            return ErrorKind::TypeError(format!("unsupported algorithm `{}`", algorithm), None)
+                .new_value(ctxt)
+                .into()
+        }
+    };
+
+    ctxt.new_value(ctxt.new_array_buffer_copy(&mut digest))
+}
@@ -0,0 +1,114 @@
+(function (natives) {
+    const UTF8_LABELS = ['unicode-1-1-utf-8', 'unicode11utf8', 'unicode20utf8', 'utf-8', 'utf8', 'x-unicode20utf8'];
+
+    function toBytes(input) {
+        if (input === undefined) {
+            return new Uint8Array(0);
+        }
+        if (input instanceof ArrayBuffer || input instanceof SharedArrayBuffer) {
+            return new Uint8Array(input);
+        }
+        if (ArrayBuffer.isView(input)) {
+            return new Uint8Array(input.buffer, input.byteOffset, input.byteLength);
+        }
+
+        throw new TypeError("The provided value is not of type '(ArrayBuffer or ArrayBufferView)'");
+    }
+
+    class TextEncoder {
+        get encoding() {
+            return 'utf-8';
+        }
+
+        encode(input = '') {
+            return new Uint8Array(natives.encode(String(input)));
+        }
+
+        encodeInto(source, destination) {
+            if (!(destination instanceof Uint8Array)) {
+                throw new TypeError("The provided value is not of type 'Uint8Array'");
+            }
+
+            const s = String(source);
+            let read = 0;
+            let written = 0;
+
+            for (const ch of s) {
+                const bytes = natives.encode(ch);
+
+                if (written + bytes.byteLength > destination.length) {
+                    break;
+                }
+
+                destination.set(new Uint8Array(bytes), written);
+                read += ch.length;
+                written += bytes.byteLength;
+            }
+
+            return { read, written };
+        }
+    }
+
+    class TextDecoder {
+        constructor(label = 'utf-8', options = {}) {
+            label = String(label).trim().toLowerCase();
+
+            const encoding = UTF8_LABELS.includes(label) ? 'utf-8' : natives.encodingForLabel(label);
+
+            if (encoding === null) {
+                throw new RangeError(`The encoding label provided ('${label}') is invalid.`);
+            }
+
+            this._encoding = encoding;
+            this._fatal = Boolean(options && options.fatal);
+            this._ignoreBOM = Boolean(options && options.ignoreBOM);
+            this._pending = null;
+            this._started = false;
+        }
+
+        get encoding() {
+            return this._encoding;
+        }
+
+        get fatal() {
+            return this._fatal;
+        }
+
+        get ignoreBOM() {
+            return this._ignoreBOM;
+        }
+
+        decode(input, options = {}) {
+            let bytes = toBytes(input);
+            const stream = Boolean(options && options.stream);
+
+            if (this._pending) {
+                const merged = new Uint8Array(this._pending.length + bytes.length);
+
+                merged.set(this._pending);
+                merged.set(bytes, this._pending.length);
+                bytes = merged;
+                this._pending = null;
+            }
+
+            const copy = bytes.slice().buffer;
+            const [text, pending] = natives.decode(
+                copy,
+                this._encoding,
+                this._fatal,
+                this._ignoreBOM || this._started,
+                stream
+            );
+
+            if (pending > 0) {
+                this._pending = bytes.slice(bytes.length - pending);
+            }
+
+            this._started = stream && (this._started || bytes.length > pending);
+
+            return text;
+        }
+    }
+
+    return { TextEncoder, TextDecoder };
+})
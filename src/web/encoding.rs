@@ -0,0 +1,143 @@
+use std::str;
+
+use crate::{ContextRef, ErrorKind, NewValue, Value, NULL};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub fn encode(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let mut buf = ctxt.clone_value(&args[0]).to_string().into_bytes();
+
+    ctxt.new_value(ctxt.new_array_buffer_copy(&mut buf))
+}
+
+#[cfg(not(feature = "encoding"))]
+pub fn encoding_for_label(_ctxt: &ContextRef, _this: Option<&Value>, _args: &[Value]) -> Value {
+    NULL
+}
+
+#[cfg(feature = "encoding")]
+pub fn encoding_for_label(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let label = ctxt.clone_value(&args[0]).to_string();
+
+    encoding_rs::Encoding::for_label(label.as_bytes())
+        .filter(|encoding| *encoding != encoding_rs::REPLACEMENT)
+        .map_or(NULL, |encoding| {
+            ctxt.new_value(encoding.name().to_lowercase())
+        })
+}
+
+/// Decode the `ArrayBuffer` with the encoding,
+/// returns the decoded string and the length of the incomplete sequence at the end for streaming.
+pub fn decode(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let buf = match ctxt.get_array_buffer(&args[0]) {
+        Some(buf) => buf,
+        None => {
+            return ErrorKind::TypeError("expected ArrayBuffer".into(), None)
+                .new_value(ctxt)
+                .into()
+        }
+    };
+    let encoding = ctxt.clone_value(&args[1]).to_string();
+    let fatal = ctxt.to_bool(&args[2]).unwrap_or_default();
+    let ignore_bom = ctxt.to_bool(&args[3]).unwrap_or_default();
+    let stream = ctxt.to_bool(&args[4]).unwrap_or_default();
+
+    let res = if encoding == "utf-8" {
+        decode_utf8(buf.as_ref(), fatal, ignore_bom, stream)
+    } else {
+        decode_with(&encoding, buf.as_ref(), fatal, ignore_bom)
+    };
+
+    match res {
+        Some((text, pending)) => {
+            let arr = ctxt.bind(ctxt.new_array());
+
+            arr.set_property(0u32, text).expect("text");
+            arr.set_property(1u32, pending as i32).expect("pending");
+
+            arr.into_inner_untracked()
+        }
+        None => ErrorKind::TypeError("The encoded data was not valid.".into(), None)
+            .new_value(ctxt)
+            .into(),
+    }
+}
+
+fn decode_utf8(
+    mut buf: &[u8],
+    fatal: bool,
+    ignore_bom: bool,
+    stream: bool,
+) -> Option<(String, usize)> {
+    if !ignore_bom && buf.starts_with(UTF8_BOM) {
+        buf = &buf[UTF8_BOM.len()..];
+    }
+
+    let pending = if stream {
+        incomplete_utf8_suffix(buf)
+    } else {
+        0
+    };
+    let buf = &buf[..buf.len() - pending];
+
+    if fatal {
+        str::from_utf8(buf).ok().map(|s| (s.to_owned(), pending))
+    } else {
+        Some((String::from_utf8_lossy(buf).into_owned(), pending))
+    }
+}
+
+/// Returns the length of the incomplete UTF-8 sequence at the end of buffer.
+fn incomplete_utf8_suffix(buf: &[u8]) -> usize {
+    for n in 1..=buf.len().min(3) {
+        let b = buf[buf.len() - n];
+
+        if b & 0xC0 == 0x80 {
+            continue;
+        }
+
+        let len = match b {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 1,
+        };
+
+        return if len > n { n } else { 0 };
+    }
+
+    0
+}
+
+#[cfg(not(feature = "encoding"))]
+fn decode_with(
+    _encoding: &str,
+    _buf: &[u8],
+    _fatal: bool,
+    _ignore_bom: bool,
+) -> Option<(String, usize)> {
+    None
+}
+
+#[cfg(feature = "encoding")]
+fn decode_with(
+    encoding: &str,
+    buf: &[u8],
+    fatal: bool,
+    ignore_bom: bool,
+) -> Option<(String, usize)> {
+    let encoding = encoding_rs::Encoding::for_label(encoding.as_bytes())?;
+
+    let buf = match encoding_rs::Encoding::for_bom(buf) {
+        Some((bom, len)) if !ignore_bom && bom == encoding => &buf[len..],
+        _ => buf,
+    };
+
+    let text = if fatal {
+        encoding.decode_without_bom_handling_and_without_replacement(buf)?
+    } else {
+        encoding.decode_without_bom_handling(buf).0
+    };
+
+    Some((text.into_owned(), 0))
+}
@@ -0,0 +1,306 @@
+(function (natives, { DOMException }) {
+    const events = new WeakMap();
+    const details = new WeakMap();
+    const listenerMaps = new WeakMap();
+
+    const NONE = 0;
+    const AT_TARGET = 2;
+
+    function flatten(options) {
+        if (typeof options === 'boolean') {
+            return { capture: options, once: false, passive: false, signal: null };
+        }
+
+        if (typeof options !== 'object' || options === null) {
+            return { capture: false, once: false, passive: false, signal: null };
+        }
+
+        return {
+            capture: Boolean(options.capture),
+            once: Boolean(options.once),
+            passive: Boolean(options.passive),
+            signal: options.signal !== undefined ? options.signal : null,
+        };
+    }
+
+    function listenersOf(target, type) {
+        const map = listenerMaps.get(target);
+
+        if (map === undefined) {
+            throw new TypeError('Illegal invocation');
+        }
+
+        if (!map.has(type)) {
+            map.set(type, []);
+        }
+
+        return map.get(type);
+    }
+
+    class Event {
+        constructor(type, init = {}) {
+            if (arguments.length < 1) {
+                throw new TypeError("Failed to construct 'Event': 1 argument required, but only 0 present.");
+            }
+
+            events.set(this, {
+                type: String(type),
+                bubbles: Boolean(init.bubbles),
+                cancelable: Boolean(init.cancelable),
+                composed: Boolean(init.composed),
+                timeStamp: typeof performance === 'object' ? performance.now() : Date.now(),
+                isTrusted: false,
+                target: null,
+                currentTarget: null,
+                eventPhase: NONE,
+                defaultPrevented: false,
+                stopPropagation: false,
+                stopImmediatePropagation: false,
+                inPassiveListener: false,
+                dispatching: false,
+            });
+        }
+
+        get type() {
+            return events.get(this).type;
+        }
+
+        get bubbles() {
+            return events.get(this).bubbles;
+        }
+
+        get cancelable() {
+            return events.get(this).cancelable;
+        }
+
+        get composed() {
+            return events.get(this).composed;
+        }
+
+        get timeStamp() {
+            return events.get(this).timeStamp;
+        }
+
+        get isTrusted() {
+            return events.get(this).isTrusted;
+        }
+
+        get target() {
+            return events.get(this).target;
+        }
+
+        get srcElement() {
+            return this.target;
+        }
+
+        get currentTarget() {
+            return events.get(this).currentTarget;
+        }
+
+        get eventPhase() {
+            return events.get(this).eventPhase;
+        }
+
+        get defaultPrevented() {
+            return events.get(this).defaultPrevented;
+        }
+
+        get returnValue() {
+            return !this.defaultPrevented;
+        }
+
+        set returnValue(value) {
+            if (!value) {
+                this.preventDefault();
+            }
+        }
+
+        get cancelBubble() {
+            return events.get(this).stopPropagation;
+        }
+
+        set cancelBubble(value) {
+            if (value) {
+                this.stopPropagation();
+            }
+        }
+
+        composedPath() {
+            const target = this.currentTarget;
+
+            return target !== null ? [target] : [];
+        }
+
+        preventDefault() {
+            const state = events.get(this);
+
+            if (state.cancelable && !state.inPassiveListener) {
+                state.defaultPrevented = true;
+            }
+        }
+
+        stopPropagation() {
+            events.get(this).stopPropagation = true;
+        }
+
+        stopImmediatePropagation() {
+            const state = events.get(this);
+
+            state.stopPropagation = true;
+            state.stopImmediatePropagation = true;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Event';
+        }
+    }
+
+    for (const [name, value] of [['NONE', 0], ['CAPTURING_PHASE', 1], ['AT_TARGET', 2], ['BUBBLING_PHASE', 3]]) {
+        Object.defineProperty(Event, name, { value, enumerable: true });
+        Object.defineProperty(Event.prototype, name, { value, enumerable: true });
+    }
+
+    class CustomEvent extends Event {
+        constructor(type, init = {}) {
+            super(type, init);
+
+            details.set(this, init.detail !== undefined ? init.detail : null);
+        }
+
+        get detail() {
+            return details.get(this);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'CustomEvent';
+        }
+    }
+
+    class EventTarget {
+        constructor() {
+            listenerMaps.set(this, new Map());
+        }
+
+        addEventListener(type, listener, options) {
+            const { capture, once, passive, signal } = flatten(options);
+
+            if (listener === null || listener === undefined || (signal !== null && signal.aborted)) {
+                return;
+            }
+
+            type = String(type);
+
+            const listeners = listenersOf(this, type);
+
+            if (listeners.some((entry) => entry.listener === listener && entry.capture === capture)) {
+                return;
+            }
+
+            listeners.push({ listener, capture, once, passive, removed: false });
+
+            if (signal !== null) {
+                signal.addEventListener('abort', () => this.removeEventListener(type, listener, { capture }));
+            }
+        }
+
+        removeEventListener(type, listener, options) {
+            const { capture } = flatten(options);
+
+            type = String(type);
+
+            const listeners = listenersOf(this, type);
+            const index = listeners.findIndex((entry) => entry.listener === listener && entry.capture === capture);
+
+            if (index >= 0) {
+                listeners[index].removed = true;
+                listeners.splice(index, 1);
+            }
+        }
+
+        dispatchEvent(event) {
+            if (!(event instanceof Event)) {
+                throw new TypeError("Failed to execute 'dispatchEvent' on 'EventTarget': parameter 1 is not of type 'Event'.");
+            }
+
+            const state = events.get(event);
+
+            if (state.dispatching) {
+                throw new DOMException('The event is already being dispatched.', 'InvalidStateError');
+            }
+
+            const listeners = listenersOf(this, state.type).slice();
+
+            state.dispatching = true;
+            state.target = this;
+            state.currentTarget = this;
+            state.eventPhase = AT_TARGET;
+
+            for (const entry of listeners) {
+                if (entry.removed) {
+                    continue;
+                }
+
+                if (entry.once) {
+                    this.removeEventListener(state.type, entry.listener, { capture: entry.capture });
+                }
+
+                state.inPassiveListener = entry.passive;
+
+                // the exceptions of the listeners are reported instead of propagated
+                try {
+                    if (typeof entry.listener === 'function') {
+                        entry.listener.call(this, event);
+                    } else if (typeof entry.listener.handleEvent === 'function') {
+                        entry.listener.handleEvent(event);
+                    }
+                } catch (err) {
+                    natives.report(err);
+                }
+
+                state.inPassiveListener = false;
+
+                if (state.stopImmediatePropagation) {
+                    break;
+                }
+            }
+
+            state.dispatching = false;
+            state.currentTarget = null;
+            state.eventPhase = NONE;
+            state.stopPropagation = false;
+            state.stopImmediatePropagation = false;
+
+            return !state.defaultPrevented;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'EventTarget';
+        }
+    }
+
+    // the event target of the global object
+    const globalTarget = new EventTarget();
+
+    function addEventListener(type, listener, options) {
+        return globalTarget.addEventListener(type, listener, options);
+    }
+
+    function removeEventListener(type, listener, options) {
+        return globalTarget.removeEventListener(type, listener, options);
+    }
+
+    function dispatchEvent(event) {
+        return globalTarget.dispatchEvent(event);
+    }
+
+    // dispatch a trusted `CustomEvent` from the host
+    function $dispatch(target, type, detail) {
+        const event = new CustomEvent(type, { detail, cancelable: true });
+
+        events.get(event).isTrusted = true;
+
+        return (target === globalThis ? globalTarget : target).dispatchEvent(event);
+    }
+
+    return { Event, CustomEvent, EventTarget, addEventListener, removeEventListener, dispatchEvent, $dispatch };
+})
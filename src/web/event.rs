@@ -0,0 +1,179 @@
+use std::convert::TryFrom;
+
+use failure::Error;
+
+use crate::{ContextRef, ErrorKind, NewValue, Value, UNDEFINED};
+
+impl ContextRef {
+    /// Dispatch a trusted and cancelable `CustomEvent` with the detail to the `EventTarget`,
+    /// returns `false` if the event was cancelled by a listener.
+    ///
+    /// The global object is also an `EventTarget`, the exceptions thrown by the listeners
+    /// are logged instead of returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qjs::{Context, Eval, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.init_web_platform().unwrap();
+    /// ctxt.eval::<_, ()>(
+    ///     "var received; addEventListener('message', e => { received = e.detail })",
+    ///     Eval::GLOBAL,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(ctxt.dispatch_event(&ctxt.global_object(), "message", "hello").unwrap());
+    /// assert_eq!(
+    ///     ctxt.eval::<_, String>("received", Eval::GLOBAL).unwrap(),
+    ///     Some("hello".to_owned())
+    /// );
+    /// ```
+    pub fn dispatch_event<T: NewValue>(
+        &self,
+        target: &Value,
+        name: &str,
+        detail: T,
+    ) -> Result<bool, Error> {
+        trace!("dispatch event `{}`", name);
+
+        let dispatch = self
+            .web_host_binding("$dispatch")
+            .ok_or_else(|| format_err!("web platform not initialized"))?;
+
+        self.call(&dispatch, None, (target, name, detail))
+            .and_then(|res| res.extract())
+    }
+}
+
+/// Report the uncaught exception of an event listener.
+pub fn report(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let err = ctxt.clone_value(&args[0]);
+
+    match ErrorKind::try_from(err) {
+        Ok(err) => warn!("uncaught exception in event listener, {}", err),
+        Err(err) => warn!("uncaught exception in event listener, {}", err),
+    }
+
+    UNDEFINED
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, Runtime, UNDEFINED};
+
+    #[test]
+    fn event_target() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        for (script, expected) in &[
+            (
+                r#"
+var t = new EventTarget(), calls = [];
+var f = e => calls.push('f:' + e.type + ':' + (e.target === t) + ':' + e.eventPhase);
+t.addEventListener('x', f);
+t.addEventListener('x', f);
+t.addEventListener('x', { handleEvent(e) { calls.push('h') } }, { once: true });
+t.dispatchEvent(new Event('x'));
+t.dispatchEvent(new Event('x'));
+t.removeEventListener('x', f);
+t.dispatchEvent(new Event('x'));
+calls.join()
+"#,
+                "f:x:true:2,h,f:x:true:2",
+            ),
+            (
+                r#"
+var t = new EventTarget(), e = new Event('x', { cancelable: true });
+t.addEventListener('x', e => e.preventDefault());
+[t.dispatchEvent(e), e.defaultPrevented, e.eventPhase, e.currentTarget].join()
+"#,
+                "false,true,0,",
+            ),
+            (
+                r#"
+var t = new EventTarget(), e = new Event('x', { cancelable: true }), calls = [];
+t.addEventListener('x', e => { e.stopImmediatePropagation(); calls.push(1) });
+t.addEventListener('x', e => calls.push(2));
+t.addEventListener('y', e => e.preventDefault(), { passive: true });
+calls.push(t.dispatchEvent(e), t.dispatchEvent(new Event('y', { cancelable: true })));
+calls.join()
+"#,
+                "1,true,true",
+            ),
+            (
+                r#"
+var t = new EventTarget(), c = new AbortController(), calls = [];
+t.addEventListener('x', () => { throw new Error('boom') });
+t.addEventListener('x', () => calls.push('a'), { signal: c.signal });
+t.dispatchEvent(new Event('x'));
+c.abort();
+t.dispatchEvent(new Event('x'));
+calls.join()
+"#,
+                "a",
+            ),
+            (
+                "var e = new CustomEvent('c', { detail: { a: 1 } }); e.detail.a + ' ' + e.isTrusted + ' ' + (e instanceof Event)",
+                "1 false true",
+            ),
+            ("String(new CustomEvent('c').detail)", "null"),
+            ("try { new EventTarget().dispatchEvent({ type: 'x' }) } catch (e) { e.name }", "TypeError"),
+            ("String(new AbortController().signal instanceof EventTarget)", "true"),
+            ("String(Event.AT_TARGET + new Event('x').BUBBLING_PHASE)", "5"),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(*script, Eval::GLOBAL).unwrap().unwrap(),
+                *expected,
+                "{}",
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn dispatch_event() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        let target = ctxt
+            .eval_script(
+                r#"
+var target = new EventTarget(), received = [];
+target.addEventListener('changed', e => { received.push(e.type, e.detail.path, e.isTrusted); e.preventDefault() });
+target
+"#,
+                "<evalScript>",
+                Eval::GLOBAL,
+            )
+            .unwrap();
+
+        let detail = ctxt.bind(ctxt.new_object());
+
+        detail.set_property("path", "/tmp/foo").unwrap();
+
+        assert!(!ctxt.dispatch_event(&target, "changed", detail).unwrap());
+        assert!(ctxt
+            .dispatch_event(&ctxt.global_object(), "changed", UNDEFINED)
+            .unwrap());
+        assert!(ctxt.dispatch_event(&ctxt.global_object(), "x", 1).is_ok());
+        assert_eq!(
+            ctxt.eval::<_, String>("received.join()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "changed,/tmp/foo,true"
+        );
+    }
+}
@@ -0,0 +1,515 @@
+(function (natives) {
+    const headerLists = new WeakMap();
+    const states = new WeakMap();
+
+    const TOKEN = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+    const METHODS = ['DELETE', 'GET', 'HEAD', 'OPTIONS', 'PATCH', 'POST', 'PUT'];
+
+    function normalizeName(name) {
+        name = String(name);
+
+        if (!TOKEN.test(name)) {
+            throw new TypeError(`Invalid header name: ${name}`);
+        }
+
+        return name.toLowerCase();
+    }
+
+    function normalizeValue(value) {
+        return String(value).replace(/^[\t\n\r ]+|[\t\n\r ]+$/g, '');
+    }
+
+    function normalizeMethod(method) {
+        method = String(method);
+
+        if (!TOKEN.test(method)) {
+            throw new TypeError(`Invalid HTTP method: ${method}`);
+        }
+
+        const upper = method.toUpperCase();
+
+        return METHODS.includes(upper) ? upper : method;
+    }
+
+    function resolveUrl(input) {
+        return typeof URL === 'function' ? new URL(input).href : String(input);
+    }
+
+    function decode(buf) {
+        return natives.decode(buf, 'utf-8', false, false, false)[0];
+    }
+
+    class Headers {
+        constructor(init) {
+            headerLists.set(this, []);
+
+            if (init === undefined || init === null) {
+                return;
+            }
+
+            if (typeof init !== 'object') {
+                throw new TypeError("Failed to construct 'Headers': The provided value is not of type 'HeadersInit'.");
+            }
+
+            if (typeof init[Symbol.iterator] === 'function') {
+                for (const pair of init) {
+                    const p = Array.from(pair);
+
+                    if (p.length !== 2) {
+                        throw new TypeError('Each header pair must be an iterable [name, value] tuple');
+                    }
+
+                    this.append(p[0], p[1]);
+                }
+            } else {
+                for (const name of Object.keys(init)) {
+                    this.append(name, init[name]);
+                }
+            }
+        }
+
+        append(name, value) {
+            headerLists.get(this).push([normalizeName(name), normalizeValue(value)]);
+        }
+
+        delete(name) {
+            name = normalizeName(name);
+
+            headerLists.set(this, headerLists.get(this).filter(([n]) => n !== name));
+        }
+
+        get(name) {
+            name = normalizeName(name);
+
+            const values = headerLists.get(this).filter(([n]) => n === name).map(([, v]) => v);
+
+            return values.length === 0 ? null : values.join(', ');
+        }
+
+        has(name) {
+            name = normalizeName(name);
+
+            return headerLists.get(this).some(([n]) => n === name);
+        }
+
+        set(name, value) {
+            name = normalizeName(name);
+            value = normalizeValue(value);
+
+            const list = headerLists.get(this);
+            const index = list.findIndex(([n]) => n === name);
+
+            if (index < 0) {
+                list.push([name, value]);
+            } else {
+                list[index] = [name, value];
+
+                headerLists.set(this, list.filter(([n], i) => n !== name || i === index));
+            }
+        }
+
+        forEach(callback, thisArg) {
+            for (const [name, value] of this) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries() {
+            const names = Array.from(new Set(headerLists.get(this).map(([n]) => n))).sort();
+
+            for (const name of names) {
+                yield [name, this.get(name)];
+            }
+        }
+
+        *keys() {
+            for (const [name] of this.entries()) {
+                yield name;
+            }
+        }
+
+        *values() {
+            for (const [, value] of this.entries()) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Headers';
+        }
+    }
+
+    function isStream(body) {
+        return typeof ReadableStream === 'function' && body instanceof ReadableStream;
+    }
+
+    // read all chunks of the stream as an `ArrayBuffer`
+    function readStream(stream) {
+        const reader = stream.getReader();
+        const chunks = [];
+
+        function next() {
+            return reader.read().then(({ value, done }) => {
+                if (!done) {
+                    if (value instanceof ArrayBuffer) {
+                        chunks.push(new Uint8Array(value));
+                    } else if (ArrayBuffer.isView(value)) {
+                        chunks.push(new Uint8Array(value.buffer, value.byteOffset, value.byteLength));
+                    } else {
+                        throw new TypeError('The body stream must only contain ArrayBuffer or ArrayBufferView chunks');
+                    }
+
+                    return next();
+                }
+
+                const bytes = new Uint8Array(chunks.reduce((size, chunk) => size + chunk.byteLength, 0));
+                let offset = 0;
+
+                for (const chunk of chunks) {
+                    bytes.set(chunk, offset);
+                    offset += chunk.byteLength;
+                }
+
+                return bytes.buffer;
+            });
+        }
+
+        return next();
+    }
+
+    // split the stream body of the state for the cloned object
+    function cloneBody(state) {
+        if (!isStream(state.body)) {
+            return state.body;
+        }
+
+        const [body, cloned] = state.body.tee();
+
+        state.body = body;
+
+        return cloned;
+    }
+
+    // extract the body as an `ArrayBuffer` or `ReadableStream`, and set the default content type
+    function extractBody(body, headers) {
+        if (body === undefined || body === null) {
+            return null;
+        }
+
+        let data;
+        let type = null;
+
+        if (isStream(body)) {
+            if (body.locked) {
+                throw new TypeError('The body stream is locked');
+            }
+
+            data = body;
+        } else if (body instanceof ArrayBuffer) {
+            data = body.slice(0);
+        } else if (ArrayBuffer.isView(body)) {
+            data = body.buffer.slice(body.byteOffset, body.byteOffset + body.byteLength);
+        } else if (typeof URLSearchParams === 'function' && body instanceof URLSearchParams) {
+            data = natives.encode(body.toString());
+            type = 'application/x-www-form-urlencoded;charset=UTF-8';
+        } else {
+            data = natives.encode(String(body));
+            type = 'text/plain;charset=UTF-8';
+        }
+
+        if (type !== null && !headers.has('content-type')) {
+            headers.set('content-type', type);
+        }
+
+        return data;
+    }
+
+    class Body {
+        get bodyUsed() {
+            return states.get(this).used;
+        }
+
+        arrayBuffer() {
+            const state = states.get(this);
+
+            if (state.used) {
+                return Promise.reject(new TypeError('Body has already been consumed.'));
+            }
+
+            state.used = true;
+
+            if (isStream(state.body)) {
+                return readStream(state.body);
+            }
+
+            return Promise.resolve(state.body === null ? new ArrayBuffer(0) : state.body);
+        }
+
+        get body() {
+            const state = states.get(this);
+
+            if (state.body === null || isStream(state.body) || typeof ReadableStream !== 'function') {
+                return state.body;
+            }
+
+            // the buffered body is exposed as a stream of single chunk
+            const buf = state.body;
+
+            state.body = new ReadableStream({
+                pull(controller) {
+                    state.used = true;
+
+                    controller.enqueue(new Uint8Array(buf));
+                    controller.close();
+                },
+            }, { highWaterMark: 0 });
+
+            return state.body;
+        }
+
+        text() {
+            return this.arrayBuffer().then(decode);
+        }
+
+        json() {
+            return this.text().then(JSON.parse);
+        }
+    }
+
+    class Request extends Body {
+        constructor(input, init = {}) {
+            super();
+
+            const source = input instanceof Request ? states.get(input) : null;
+            const method = init.method !== undefined ? normalizeMethod(init.method) : source ? source.method : 'GET';
+            const headers = new Headers(init.headers !== undefined ? init.headers : source ? source.headers : undefined);
+            let body = null;
+
+            if (init.body !== undefined && init.body !== null) {
+                body = extractBody(init.body, headers);
+            } else if (source && source.body !== null) {
+                if (source.used) {
+                    throw new TypeError('Cannot construct a Request with a Request object that has already been used.');
+                }
+
+                body = source.body;
+                source.used = true;
+            }
+
+            if (body !== null && (method === 'GET' || method === 'HEAD')) {
+                throw new TypeError('Request with GET/HEAD method cannot have body.');
+            }
+
+            states.set(this, {
+                url: source ? source.url : resolveUrl(input),
+                method,
+                headers,
+                redirect: init.redirect !== undefined ? String(init.redirect) : source ? source.redirect : 'follow',
+                signal: init.signal !== undefined ? init.signal : source ? source.signal : null,
+                body,
+                used: false,
+            });
+        }
+
+        get url() {
+            return states.get(this).url;
+        }
+
+        get method() {
+            return states.get(this).method;
+        }
+
+        get headers() {
+            return states.get(this).headers;
+        }
+
+        get redirect() {
+            return states.get(this).redirect;
+        }
+
+        get signal() {
+            return states.get(this).signal;
+        }
+
+        clone() {
+            if (this.bodyUsed) {
+                throw new TypeError('Request body is already used');
+            }
+
+            return new Request(this.url, {
+                method: this.method,
+                headers: this.headers,
+                redirect: this.redirect,
+                signal: this.signal,
+                body: cloneBody(states.get(this)),
+            });
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Request';
+        }
+    }
+
+    function newResponse(state) {
+        const response = Object.create(Response.prototype);
+
+        states.set(response, Object.assign({ url: '', type: 'default', redirected: false, used: false }, state));
+
+        return response;
+    }
+
+    class Response extends Body {
+        constructor(body = null, init = {}) {
+            super();
+
+            const status = init.status !== undefined ? Number(init.status) : 200;
+
+            if (!Number.isInteger(status) || status < 200 || status > 599) {
+                throw new RangeError(`Failed to construct 'Response': The status provided (${init.status}) is outside the range [200, 599].`);
+            }
+
+            const headers = new Headers(init.headers);
+
+            states.set(this, {
+                status,
+                statusText: init.statusText !== undefined ? String(init.statusText) : '',
+                headers,
+                body: extractBody(body, headers),
+                url: '',
+                type: 'default',
+                redirected: false,
+                used: false,
+            });
+        }
+
+        static error() {
+            return newResponse({ status: 0, statusText: '', headers: new Headers(), body: null, type: 'error' });
+        }
+
+        static json(data, init = {}) {
+            const headers = new Headers(init.headers);
+
+            if (!headers.has('content-type')) {
+                headers.set('content-type', 'application/json');
+            }
+
+            return new Response(JSON.stringify(data), Object.assign({}, init, { headers }));
+        }
+
+        static redirect(url, status = 302) {
+            if (![301, 302, 303, 307, 308].includes(status)) {
+                throw new RangeError(`Invalid status code: ${status}`);
+            }
+
+            return newResponse({ status, statusText: '', headers: new Headers({ location: resolveUrl(url) }), body: null });
+        }
+
+        get status() {
+            return states.get(this).status;
+        }
+
+        get statusText() {
+            return states.get(this).statusText;
+        }
+
+        get ok() {
+            const status = this.status;
+
+            return status >= 200 && status <= 299;
+        }
+
+        get headers() {
+            return states.get(this).headers;
+        }
+
+        get url() {
+            return states.get(this).url;
+        }
+
+        get type() {
+            return states.get(this).type;
+        }
+
+        get redirected() {
+            return states.get(this).redirected;
+        }
+
+        clone() {
+            if (this.bodyUsed) {
+                throw new TypeError('Response body is already used');
+            }
+
+            const state = states.get(this);
+
+            return newResponse(Object.assign({}, state, { headers: new Headers(state.headers), body: cloneBody(state) }));
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'Response';
+        }
+    }
+
+    function fetch(input, init) {
+        return new Promise((resolve, reject) => {
+            const request = new Request(input, init);
+            const state = states.get(request);
+            const signal = state.signal;
+            const headers = [];
+
+            for (const [name, value] of state.headers) {
+                headers.push(name, value);
+            }
+
+            if (signal !== null) {
+                if (signal.aborted) {
+                    reject(signal.reason);
+                    return;
+                }
+
+                signal.addEventListener('abort', () => reject(signal.reason), { once: true });
+            }
+
+            // the request is sent in the background, the redirects are followed by the natives
+            const send = (body) => natives.send(state.method, state.url, headers, body === null ? undefined : body, (err, res) => {
+                if (signal !== null && signal.aborted) {
+                    return;
+                }
+
+                if (err !== null) {
+                    reject(new TypeError(`Failed to fetch: ${err}`));
+                    return;
+                }
+
+                const headers = new Headers();
+
+                for (let i = 0; i < res.headers.length; i += 2) {
+                    headers.append(res.headers[i], res.headers[i + 1]);
+                }
+
+                resolve(newResponse({
+                    status: res.status,
+                    statusText: res.statusText,
+                    headers,
+                    body: res.body,
+                    url: res.url,
+                    type: 'basic',
+                    redirected: res.url !== state.url,
+                }));
+            }, signal, state.redirect);
+
+            if (isStream(state.body)) {
+                state.used = true;
+
+                readStream(state.body).then(send, reject);
+            } else {
+                send(state.body);
+            }
+        });
+    }
+
+    return { Headers, Request, Response, fetch };
+})
@@ -0,0 +1,777 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+
+use failure::Error;
+use url::Url;
+
+use crate::{ffi, Capabilities, ContextRef, ErrorKind, Local, NewValue, Value, UNDEFINED};
+
+use super::{encoding, FETCH};
+
+/// The HTTP request sent by the `fetch` function.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpRequest {
+    /// the request method
+    pub method: String,
+    /// the absolute URL of the request
+    pub url: String,
+    /// the request headers with the lowercase names
+    pub headers: Vec<(String, String)>,
+    /// the request body
+    pub body: Option<Vec<u8>>,
+}
+
+/// The HTTP response returned by the `HttpBackend`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpResponse {
+    /// the status code
+    pub status: u16,
+    /// the status message
+    pub status_text: String,
+    /// the response headers
+    pub headers: Vec<(String, String)>,
+    /// the response body
+    pub body: Vec<u8>,
+    /// the final URL after the redirects, or the request URL if `None`
+    pub url: Option<String>,
+}
+
+/// The HTTP client which sends the requests of the `fetch` function.
+///
+/// The request is sent as a background operation of the runtime, and the returned `Promise` is settled
+/// when the runtime polls the operations, e.g. in the `EventLoop`. The `Promise` will be rejected
+/// with a `TypeError` if the backend failed.
+pub trait HttpBackend: Send + Sync {
+    /// Send the request and wait for the response, the redirects should not be followed by the backend.
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error>;
+}
+
+impl<F> HttpBackend for F
+where
+    F: Fn(HttpRequest) -> Result<HttpResponse, Error> + Send + Sync,
+{
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        self(request)
+    }
+}
+
+/// The `HttpBackend` based on the blocking client of `reqwest`.
+///
+/// The default client doesn't follow the redirects, which are followed by `fetch`
+/// after checking the location against the allow-list.
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug)]
+pub struct ReqwestBackend(reqwest::blocking::Client);
+
+#[cfg(feature = "reqwest")]
+impl Default for ReqwestBackend {
+    fn default() -> Self {
+        ReqwestBackend(
+            reqwest::blocking::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .expect("client"),
+        )
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestBackend {
+    /// Create a backend with the client.
+    ///
+    /// The client should not follow the redirects, otherwise an allowed URL could redirect to any URL.
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        ReqwestBackend(client)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpBackend for ReqwestBackend {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())?;
+        let mut builder = self.0.request(method, &request.url);
+
+        for (name, value) in request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let res = builder.send()?;
+        let status = res.status();
+        let url = res.url().to_string();
+        let headers = res
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_owned(),
+            headers,
+            body: res.bytes()?.to_vec(),
+            url: Some(url),
+        })
+    }
+}
+
+/// The builder to install the `fetch` function with a `HttpBackend`.
+///
+/// The `Headers`, `Request`, `Response` and `fetch` are installed into the global object,
+/// the `fetch` function requires the `NET` capability, and only the URLs in the allow-list could be fetched.
+/// The redirects are followed by `fetch` up to 20 times, and each location is checked against the allow-list.
+///
+/// # Examples
+///
+/// ```
+/// use qjs::{Context, Eval, EventLoop, Fetch, HttpRequest, HttpResponse, Runtime};
+///
+/// let rt = Runtime::new();
+/// let ctxt = Context::new(&rt);
+///
+/// Fetch::new(|req: HttpRequest| {
+///     Ok(HttpResponse {
+///         status: 200,
+///         body: format!("{} {}", req.method, req.url).into_bytes(),
+///         ..Default::default()
+///     })
+/// })
+/// .allow_url("https://example.com/")
+/// .init(&ctxt)
+/// .unwrap();
+///
+/// ctxt.eval::<_, ()>(
+///     "fetch('https://example.com/hello').then(res => res.text()).then(text => { globalThis.text = text })",
+///     Eval::GLOBAL,
+/// )
+/// .unwrap();
+///
+/// EventLoop::new(&rt).run().unwrap();
+///
+/// assert_eq!(
+///     ctxt.eval::<_, String>("text", Eval::GLOBAL).unwrap(),
+///     Some("GET https://example.com/hello".to_owned())
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Fetch {
+    backend: Arc<dyn HttpBackend>,
+    allowed: Option<Vec<String>>,
+}
+
+impl fmt::Debug for Fetch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Fetch")
+            .field("allowed", &self.allowed)
+            .finish()
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for Fetch {
+    fn default() -> Self {
+        Fetch::new(ReqwestBackend::default())
+    }
+}
+
+impl Fetch {
+    /// Create a builder with the backend, all the URLs are denied.
+    pub fn new<B: HttpBackend + 'static>(backend: B) -> Self {
+        Fetch {
+            backend: Arc::new(backend),
+            allowed: Some(vec![]),
+        }
+    }
+
+    /// Allow the URLs of the same origin as the prefix, and under its path.
+    ///
+    /// The URLs are parsed and normalized before checked, e.g. `https://example.com/api`
+    /// allows `https://EXAMPLE.com/api/v1`, but denies `https://example.com.evil.org/api`
+    /// and `https://example.com/api/../admin`.
+    pub fn allow_url<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.allowed
+            .get_or_insert_with(Vec::new)
+            .push(prefix.into());
+        self
+    }
+
+    /// Allow all the URLs.
+    pub fn allow_any_url(mut self) -> Self {
+        self.allowed = None;
+        self
+    }
+
+    /// Returns `true` if the URL is allowed to fetch.
+    pub fn is_allowed(&self, url: &str) -> bool {
+        let allowed = match self.allowed {
+            Some(ref allowed) => allowed,
+            None => return true,
+        };
+        let url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(_) => return false,
+        };
+
+        allowed
+            .iter()
+            .flat_map(|prefix| Url::parse(prefix))
+            .any(|prefix| {
+                let dir = prefix.path().trim_end_matches('/');
+
+                url.origin() == prefix.origin()
+                    && (url.path() == prefix.path()
+                        || url.path().starts_with(dir) && url.path()[dir.len()..].starts_with('/'))
+            })
+    }
+
+    /// Install the `fetch` function and the related classes into the context.
+    pub fn init(self, ctxt: &ContextRef) -> Result<(), Error> {
+        *ctxt.state::<FetchState>().0.borrow_mut() = Some(self);
+
+        ctxt.install_web_api(
+            "fetch",
+            FETCH,
+            &[
+                (
+                    "send",
+                    ctxt.new_c_function_with_capabilities(
+                        send,
+                        Some("fetch"),
+                        7,
+                        Capabilities::NET,
+                    )?,
+                ),
+                (
+                    "encode",
+                    ctxt.new_c_function(encoding::encode, Some("encode"), 1)?,
+                ),
+                (
+                    "decode",
+                    ctxt.new_c_function(encoding::decode, Some("decode"), 5)?,
+                ),
+            ],
+            &[],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FetchState(RefCell<Option<Fetch>>);
+
+/// The maximum number of the redirects followed by `fetch`.
+const MAX_REDIRECTS: usize = 20;
+
+/// Check the URL against the allow-list and send the request in the background,
+/// the arguments are `method`, `url`, `headers`, `body`, `callback(err, res)`, `signal` and `redirect`.
+fn send(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let url = ctxt.clone_value(&args[1]).to_string();
+    let fetch = ctxt.state::<FetchState>().0.borrow().clone();
+    let fetch = match fetch {
+        Some(ref fetch) if fetch.is_allowed(&url) => fetch.clone(),
+        _ => {
+            debug!("fetch `{}` is not allowed", url);
+
+            return ErrorKind::TypeError(format!("fetch `{}` is not allowed", url), None)
+                .new_value(ctxt)
+                .into();
+        }
+    };
+    let headers = ctxt.clone_value(&args[2]);
+    let len = headers
+        .get_property("length")
+        .and_then(|len| len.to_int32())
+        .unwrap_or_default() as u32;
+    let headers = (0..len / 2)
+        .map(|i| {
+            let name = headers.get_property(i * 2).map(|v| v.to_string());
+            let value = headers.get_property(i * 2 + 1).map(|v| v.to_string());
+
+            (name.unwrap_or_default(), value.unwrap_or_default())
+        })
+        .collect();
+    let request = HttpRequest {
+        method: ctxt.clone_value(&args[0]).to_string(),
+        url,
+        headers,
+        body: ctxt
+            .get_array_buffer(&args[3])
+            .map(|buf| buf.as_ref().to_vec()),
+    };
+    let token = ctxt.cancellation_token(&args[5]);
+    let redirect = ctxt.clone_value(&args[6]).to_string();
+
+    let res = ctxt.spawn_operation(&[&args[4]], move |op| {
+        fetch.follow(request, &redirect, || {
+            op.is_cancelled() || token.as_ref().map_or(false, |token| token.is_cancelled())
+        })
+    });
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+impl Fetch {
+    /// Send the request, and follow the redirects to the allowed URLs if the mode is `follow`.
+    fn follow<F: Fn() -> bool>(
+        &self,
+        mut request: HttpRequest,
+        redirect: &str,
+        is_cancelled: F,
+    ) -> Result<FetchResponse, Error> {
+        for _ in 0..=MAX_REDIRECTS {
+            if is_cancelled() {
+                bail!("the request was aborted");
+            }
+
+            trace!("fetch {} {}", request.method, request.url);
+
+            let url = request.url.clone();
+            let res = self.backend.send(request.clone())?;
+            let location = res
+                .headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("location"))
+                .map(|(_, location)| location.clone());
+
+            let location = match (res.status, location) {
+                (301 | 302 | 303 | 307 | 308, Some(location)) if redirect != "manual" => location,
+                _ => return Ok(FetchResponse { url, res }),
+            };
+
+            if redirect == "error" {
+                bail!("unexpected redirect to `{}`", location);
+            }
+
+            let location = Url::parse(&url)?.join(&location)?.to_string();
+
+            if !self.is_allowed(&location) {
+                bail!("redirect to `{}` is not allowed", location);
+            }
+
+            debug!("fetch {} redirected to {}", url, location);
+
+            // the body is dropped unless the method and body should be kept
+            if res.status == 303 && request.method != "HEAD"
+                || (res.status == 301 || res.status == 302) && request.method == "POST"
+            {
+                request.method = "GET".to_owned();
+                request.body = None;
+                request
+                    .headers
+                    .retain(|(name, _)| !name.starts_with("content-"));
+            }
+
+            request.url = location;
+        }
+
+        bail!("too many redirects")
+    }
+}
+
+/// The response of the `HttpBackend` for the request URL.
+struct FetchResponse {
+    url: String,
+    res: HttpResponse,
+}
+
+impl NewValue for FetchResponse {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        let FetchResponse { url, mut res } = self;
+        let res = (|| -> Result<Local<Value>, Error> {
+            let obj = ctxt.bind(ctxt.new_object());
+            let headers = ctxt.bind(ctxt.new_array());
+
+            for (i, (name, value)) in res.headers.iter().enumerate() {
+                headers.set_property(i as u32 * 2, name.as_str())?;
+                headers.set_property(i as u32 * 2 + 1, value.as_str())?;
+            }
+
+            obj.set_property("status", res.status)?;
+            obj.set_property("statusText", res.status_text.as_str())?;
+            obj.set_property("headers", headers)?;
+            obj.set_property("body", ctxt.new_array_buffer_copy(&mut res.body))?;
+            obj.set_property("url", res.url.unwrap_or(url))?;
+
+            Ok(obj)
+        })();
+
+        res.new_value(ctxt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{Capabilities, Context, Eval, EventLoop, Permissions, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn fetch() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let requests = Arc::new(Mutex::new(vec![]));
+        let sent = requests.clone();
+
+        ctxt.init_web_platform().unwrap();
+
+        Fetch::new(move |req: HttpRequest| {
+            sent.lock().unwrap().push(req.clone());
+
+            if req.url.ends_with("/fail") {
+                bail!("connection refused")
+            }
+
+            Ok(HttpResponse {
+                status: 201,
+                status_text: "Created".to_owned(),
+                headers: vec![
+                    ("content-type".to_owned(), "application/json".to_owned()),
+                    ("x-a".to_owned(), "1".to_owned()),
+                    ("x-a".to_owned(), "2".to_owned()),
+                ],
+                body: br#"{"hello":"world"}"#.to_vec(),
+                url: None,
+            })
+        })
+        .allow_url("https://example.com/")
+        .init(&ctxt)
+        .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var results = [];
+fetch('https://example.com/api', { method: 'post', headers: { 'X-Foo': ' bar ' }, body: 'ping' })
+    .then(res => { results.push([res.status, res.statusText, res.ok, res.headers.get('x-a'), res.url, res.bodyUsed].join()); return res.json() })
+    .then(obj => results.push(obj.hello));
+fetch('https://example.com/fail').catch(e => results.push(e.name + ': ' + e.message));
+fetch('https://example.org/').catch(e => results.push(e.name + ': ' + e.message));
+fetch('https://example.com/', { body: 'x' }).catch(e => results.push(e.message));
+var c = new AbortController();
+fetch('https://example.com/abort', { signal: c.signal }).catch(e => results.push(e.name));
+c.abort();
+fetch('https://example.com/abort', { signal: c.signal }).catch(e => results.push(e.name));
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        // the requests are sent in the background, and completed in any order
+        assert_eq!(
+            ctxt.eval::<_, String>("results.slice(0, 4).join('\\n')", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            [
+                "TypeError: fetch `https://example.org/` is not allowed",
+                "Request with GET/HEAD method cannot have body.",
+                "AbortError",
+                "AbortError",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            ctxt.eval::<_, String>("results.slice(4).sort().join('\\n')", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            [
+                "201,Created,true,1, 2,https://example.com/api,false",
+                "TypeError: Failed to fetch: connection refused",
+                "world",
+            ]
+            .join("\n")
+        );
+
+        let mut requests = requests.lock().unwrap();
+
+        // the aborted request may be sent before the signal aborted
+        requests.retain(|req| !req.url.ends_with("/abort"));
+        requests.sort_by(|a, b| a.url.cmp(&b.url));
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0],
+            HttpRequest {
+                method: "POST".to_owned(),
+                url: "https://example.com/api".to_owned(),
+                headers: vec![
+                    (
+                        "content-type".to_owned(),
+                        "text/plain;charset=UTF-8".to_owned()
+                    ),
+                    ("x-foo".to_owned(), "bar".to_owned()),
+                ],
+                body: Some(b"ping".to_vec()),
+            }
+        );
+
+        ctxt.set_permissions(Permissions::all().deny(Capabilities::NET));
+
+        assert_eq!(
+            ctxt.eval::<_, String>(
+                "var err; fetch('https://example.com/').catch(e => { err = e.message }); err",
+                Eval::GLOBAL
+            )
+            .unwrap(),
+            None
+        );
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert!(ctxt
+            .eval::<_, String>("err", Eval::GLOBAL)
+            .unwrap()
+            .unwrap()
+            .starts_with("permission denied"));
+    }
+
+    #[test]
+    fn allowed_urls() {
+        let fetch = Fetch::new(|_| bail!("offline"))
+            .allow_url("https://example.com/api")
+            .allow_url("http://example.org:8080/");
+
+        for (url, allowed) in &[
+            ("https://example.com/api", true),
+            ("https://EXAMPLE.com:443/api/v1", true),
+            ("https://example.com/api/../admin", false),
+            ("https://example.com/apis", false),
+            ("https://example.com.evil.org/api", false),
+            ("https://example.com@evil.org/api", false),
+            ("http://example.com/api", false),
+            ("http://example.org:8080/any", true),
+            ("http://example.org/any", false),
+            ("api", false),
+        ] {
+            assert_eq!(fetch.is_allowed(url), *allowed, "{}", url);
+        }
+
+        assert!(Fetch::new(|_| bail!("offline"))
+            .allow_any_url()
+            .is_allowed("api"));
+    }
+
+    #[test]
+    fn redirect() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let requests = Arc::new(Mutex::new(vec![]));
+        let sent = requests.clone();
+
+        ctxt.init_web_platform().unwrap();
+
+        Fetch::new(move |req: HttpRequest| {
+            sent.lock()
+                .unwrap()
+                .push(format!("{} {}", req.method, req.url));
+
+            let location = match req.url.as_str() {
+                "https://example.com/a" => "/b",
+                "https://example.com/b" => "c?x=1",
+                "https://example.com/evil" => "https://evil.org/",
+                "https://example.com/loop" => "loop",
+                _ => {
+                    return Ok(HttpResponse {
+                        status: 200,
+                        body: req.url.into_bytes(),
+                        ..Default::default()
+                    })
+                }
+            };
+
+            Ok(HttpResponse {
+                status: if req.url.ends_with("/b") { 303 } else { 302 },
+                headers: vec![("Location".to_owned(), location.to_owned())],
+                ..Default::default()
+            })
+        })
+        .allow_url("https://example.com/")
+        .init(&ctxt)
+        .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var results = {};
+var save = (key) => (res) => res.text().then(text => { results[key] = [res.status, res.redirected, res.url, text].join() });
+var fail = (key) => (err) => { results[key] = err.message };
+fetch('https://example.com/a', { method: 'PUT', body: 'x' }).then(save('follow'), fail('follow'));
+fetch('https://example.com/a', { redirect: 'manual' }).then(save('manual'), fail('manual'));
+fetch('https://example.com/a', { redirect: 'error' }).then(save('error'), fail('error'));
+fetch('https://example.com/evil').then(save('evil'), fail('evil'));
+fetch('https://example.com/loop').then(save('loop'), fail('loop'));
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        for (key, expected) in &[
+            (
+                "follow",
+                "200,true,https://example.com/c?x=1,https://example.com/c?x=1",
+            ),
+            ("manual", "302,false,https://example.com/a,"),
+            ("error", "Failed to fetch: unexpected redirect to `/b`"),
+            (
+                "evil",
+                "Failed to fetch: redirect to `https://evil.org/` is not allowed",
+            ),
+            ("loop", "Failed to fetch: too many redirects"),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(format!("results.{}", key).as_str(), Eval::GLOBAL)
+                    .unwrap()
+                    .unwrap(),
+                *expected,
+                "{}",
+                key
+            );
+        }
+
+        let requests = requests.lock().unwrap();
+
+        // the method is changed to GET by the 303 redirect
+        assert!(requests.contains(&"PUT https://example.com/b".to_owned()));
+        assert!(requests.contains(&"GET https://example.com/c?x=1".to_owned()));
+        assert!(!requests.iter().any(|req| req.contains("evil.org")));
+        assert_eq!(
+            requests.iter().filter(|req| req.ends_with("/loop")).count(),
+            MAX_REDIRECTS + 1
+        );
+    }
+
+    #[test]
+    fn body_stream() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+        let bodies = Arc::new(Mutex::new(vec![]));
+        let sent = bodies.clone();
+
+        ctxt.init_web_platform().unwrap();
+
+        Fetch::new(move |req: HttpRequest| {
+            sent.lock().unwrap().push(req.body);
+
+            Ok(HttpResponse {
+                status: 200,
+                status_text: "OK".to_owned(),
+                headers: vec![],
+                body: b"pong".to_vec(),
+                url: None,
+            })
+        })
+        .allow_any_url()
+        .init(&ctxt)
+        .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var results = [];
+var body = new ReadableStream({ start(c) { c.enqueue(new TextEncoder().encode('pi')); c.enqueue(new Uint8Array([110, 103])); c.close() } });
+fetch('https://example.com/', { method: 'POST', body }).then(res => {
+    var reader = res.body.getReader();
+    return reader.read().then(({ value }) => results.push(value.length, res.bodyUsed));
+});
+var res = new Response(new Blob(['xyz']).stream()), copy = res.clone();
+res.text().then(text => results.push(text));
+copy.arrayBuffer().then(buf => results.push(buf.byteLength));
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("results.join()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "3,xyz,4,true"
+        );
+        assert_eq!(*bodies.lock().unwrap(), vec![Some(b"ping".to_vec())]);
+    }
+
+    #[test]
+    fn classes() {
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        Fetch::new(|_| bail!("offline")).init(&ctxt).unwrap();
+
+        for (script, expected) in &[
+            (
+                "var h = new Headers([['B', '1'], ['a', '2']]); h.append('b', '3'); [...h].join(';')",
+                "a,2;b,1, 3",
+            ),
+            (
+                "var h = new Headers({ a: 1 }); h.set('A', 2); h.has('a') + ' ' + h.get('a')",
+                "true 2",
+            ),
+            ("try { new Headers({ 'a b': 1 }) } catch (e) { e.name }", "TypeError"),
+            (
+                "var r = new Request('http://a/', { method: 'put', body: new Uint8Array([104, 105]) }); r.method + ' ' + r.bodyUsed",
+                "PUT false",
+            ),
+            (
+                "new Request(new Request('http://a/', { method: 'DELETE' })).method",
+                "DELETE",
+            ),
+            ("new Response('x').headers.get('content-type')", "text/plain;charset=UTF-8"),
+            ("new Response().status + ' ' + new Response(null, { status: 404 }).ok", "200 false"),
+            ("try { new Response('', { status: 100 }) } catch (e) { e.name }", "RangeError"),
+            ("Response.error().type + Response.error().status", "error0"),
+            ("Response.json({ a: 1 }).headers.get('content-type')", "application/json"),
+            ("Object.prototype.toString.call(new Headers())", "[object Headers]"),
+        ] {
+            assert_eq!(
+                ctxt.eval::<_, String>(*script, Eval::GLOBAL).unwrap().unwrap(),
+                *expected,
+                "{}",
+                script
+            );
+        }
+
+        ctxt.eval::<_, ()>(
+            r#"
+var res = new Response('hi'), copy = res.clone(), out = [];
+res.text().then(t => out.push(t)).then(() => res.text()).catch(e => out.push(e.message));
+copy.arrayBuffer().then(b => out.push(b.byteLength));
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, String>("out.join()", Eval::GLOBAL)
+                .unwrap()
+                .unwrap(),
+            "2,hi,Body has already been consumed."
+        );
+    }
+}
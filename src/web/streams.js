@@ -0,0 +1,1013 @@
+(function (natives) {
+    const readables = new WeakMap();
+    const readers = new WeakMap();
+    const readableControllers = new WeakMap();
+    const writables = new WeakMap();
+    const writers = new WeakMap();
+    const writableControllers = new WeakMap();
+
+    function deferred() {
+        const d = {};
+
+        d.promise = new Promise((resolve, reject) => {
+            d.resolve = resolve;
+            d.reject = reject;
+        });
+
+        return d;
+    }
+
+    // the rejected promise which never reports the unhandled rejection
+    function handled(promise) {
+        promise.catch(() => {});
+
+        return promise;
+    }
+
+    function invoke(target, name, args) {
+        try {
+            const method = target[name];
+
+            return Promise.resolve(method === undefined ? undefined : method.apply(target, args));
+        } catch (err) {
+            return Promise.reject(err);
+        }
+    }
+
+    function extractStrategy(strategy, defaultHighWaterMark) {
+        const highWaterMark = strategy.highWaterMark === undefined ? defaultHighWaterMark : Number(strategy.highWaterMark);
+
+        if (Number.isNaN(highWaterMark) || highWaterMark < 0) {
+            throw new RangeError('Invalid highWaterMark');
+        }
+
+        const size = strategy.size;
+
+        return { highWaterMark, size: size === undefined ? () => 1 : (chunk) => Number(size(chunk)) };
+    }
+
+    function readResult(value, done) {
+        return { value, done };
+    }
+
+    function readableDesiredSize(s) {
+        if (s.state === 'errored') {
+            return null;
+        }
+
+        return s.state === 'closed' ? 0 : s.highWaterMark - s.queueSize;
+    }
+
+    function finalizeClose(s) {
+        s.state = 'closed';
+
+        if (s.reader !== null) {
+            const reader = readers.get(s.reader);
+
+            reader.requests.splice(0).forEach((request) => request.resolve(readResult(undefined, true)));
+            reader.closed.resolve();
+        }
+    }
+
+    function errorReadable(s, err) {
+        if (s.state !== 'readable') {
+            return;
+        }
+
+        s.state = 'errored';
+        s.storedError = err;
+        s.queue = [];
+        s.queueSize = 0;
+
+        if (s.reader !== null) {
+            const reader = readers.get(s.reader);
+
+            reader.requests.splice(0).forEach((request) => request.reject(err));
+            reader.closed.reject(err);
+        }
+    }
+
+    function pullIfNeeded(s) {
+        if (!s.started || s.closeRequested || s.state !== 'readable') {
+            return;
+        }
+
+        const pending = s.reader !== null && readers.get(s.reader).requests.length > 0;
+
+        if (!pending && readableDesiredSize(s) <= 0) {
+            return;
+        }
+
+        if (s.pulling) {
+            s.pullAgain = true;
+            return;
+        }
+
+        s.pulling = true;
+
+        invoke(s.source, 'pull', [s.controller]).then(
+            () => {
+                s.pulling = false;
+
+                if (s.pullAgain) {
+                    s.pullAgain = false;
+                    pullIfNeeded(s);
+                }
+            },
+            (err) => errorReadable(s, err)
+        );
+    }
+
+    function cancelReadable(s, reason) {
+        s.disturbed = true;
+
+        if (s.state === 'closed') {
+            return Promise.resolve();
+        }
+
+        if (s.state === 'errored') {
+            return Promise.reject(s.storedError);
+        }
+
+        s.queue = [];
+        s.queueSize = 0;
+
+        finalizeClose(s);
+
+        return invoke(s.source, 'cancel', [reason]).then(() => undefined);
+    }
+
+    class ReadableStreamDefaultController {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        get desiredSize() {
+            return readableDesiredSize(readableControllers.get(this));
+        }
+
+        enqueue(chunk) {
+            const s = readableControllers.get(this);
+
+            if (s.closeRequested || s.state !== 'readable') {
+                throw new TypeError('The stream is not in a state that permits enqueue.');
+            }
+
+            const requests = s.reader !== null ? readers.get(s.reader).requests : [];
+
+            if (requests.length > 0) {
+                requests.shift().resolve(readResult(chunk, false));
+            } else {
+                let size;
+
+                try {
+                    size = s.size(chunk);
+                } catch (err) {
+                    errorReadable(s, err);
+                    throw err;
+                }
+
+                s.queue.push({ chunk, size });
+                s.queueSize += size;
+            }
+
+            pullIfNeeded(s);
+        }
+
+        close() {
+            const s = readableControllers.get(this);
+
+            if (s.closeRequested || s.state !== 'readable') {
+                throw new TypeError('The stream is not in a state that permits close.');
+            }
+
+            s.closeRequested = true;
+
+            if (s.queue.length === 0) {
+                finalizeClose(s);
+            }
+        }
+
+        error(err) {
+            errorReadable(readableControllers.get(this), err);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'ReadableStreamDefaultController';
+        }
+    }
+
+    class ReadableStream {
+        constructor(source = {}, strategy = {}) {
+            const { highWaterMark, size } = extractStrategy(strategy, source.type === 'bytes' ? 0 : 1);
+            const controller = Object.create(ReadableStreamDefaultController.prototype);
+            const s = {
+                state: 'readable',
+                source,
+                controller,
+                reader: null,
+                queue: [],
+                queueSize: 0,
+                highWaterMark,
+                size,
+                started: false,
+                pulling: false,
+                pullAgain: false,
+                closeRequested: false,
+                disturbed: false,
+                storedError: undefined,
+            };
+
+            readables.set(this, s);
+            readableControllers.set(controller, s);
+
+            invoke(source, 'start', [controller]).then(
+                () => {
+                    s.started = true;
+                    pullIfNeeded(s);
+                },
+                (err) => errorReadable(s, err)
+            );
+        }
+
+        get locked() {
+            return readables.get(this).reader !== null;
+        }
+
+        cancel(reason) {
+            const s = readables.get(this);
+
+            if (s.reader !== null) {
+                return Promise.reject(new TypeError('Cannot cancel a stream that already has a reader'));
+            }
+
+            return cancelReadable(s, reason);
+        }
+
+        getReader(options = {}) {
+            if (options.mode !== undefined) {
+                throw new TypeError(`Unsupported reader mode '${options.mode}'`);
+            }
+
+            return new ReadableStreamDefaultReader(this);
+        }
+
+        pipeTo(dest, options = {}) {
+            if (this.locked || dest.locked) {
+                return Promise.reject(new TypeError('Cannot pipe a locked stream'));
+            }
+
+            const { preventClose, preventAbort, preventCancel, signal } = options;
+            const reader = this.getReader();
+            const writer = dest.getWriter();
+
+            return new Promise((resolve, reject) => {
+                let finished = false;
+                let lastWrite = Promise.resolve();
+
+                function finish(err, failed) {
+                    if (finished) {
+                        return;
+                    }
+
+                    finished = true;
+
+                    reader.releaseLock();
+                    writer.releaseLock();
+
+                    if (failed) {
+                        reject(err);
+                    } else {
+                        resolve();
+                    }
+                }
+
+                if (signal !== undefined) {
+                    const onAbort = () => {
+                        if (!preventAbort) {
+                            handled(writer.abort(signal.reason));
+                        }
+
+                        if (!preventCancel) {
+                            handled(reader.cancel(signal.reason));
+                        }
+
+                        finish(signal.reason, true);
+                    };
+
+                    if (signal.aborted) {
+                        onAbort();
+                        return;
+                    }
+
+                    signal.addEventListener('abort', onAbort, { once: true });
+                }
+
+                reader.closed.catch((err) => {
+                    if (finished) {
+                        return;
+                    }
+
+                    if (!preventAbort) {
+                        handled(writer.abort(err));
+                    }
+
+                    finish(err, true);
+                });
+                writer.closed.catch((err) => {
+                    if (finished) {
+                        return;
+                    }
+
+                    if (!preventCancel) {
+                        handled(reader.cancel(err));
+                    }
+
+                    finish(err, true);
+                });
+
+                function step() {
+                    writer.ready
+                        .then(() => (finished ? readResult(undefined, true) : reader.read()))
+                        .then(({ value, done }) => {
+                            if (finished) {
+                                return;
+                            }
+
+                            if (done) {
+                                const closed = preventClose ? lastWrite : writer.close();
+
+                                closed.then(
+                                    () => finish(),
+                                    (err) => finish(err, true)
+                                );
+                            } else {
+                                lastWrite = handled(writer.write(value));
+                                step();
+                            }
+                        })
+                        .catch(() => {});
+                }
+
+                step();
+            });
+        }
+
+        pipeThrough({ writable, readable }, options) {
+            handled(this.pipeTo(writable, options));
+
+            return readable;
+        }
+
+        tee() {
+            const reader = this.getReader();
+            const branches = [];
+            const controllers = [];
+            let reading = false;
+
+            function pull() {
+                if (reading) {
+                    return Promise.resolve();
+                }
+
+                reading = true;
+
+                return reader.read().then(
+                    ({ value, done }) => {
+                        reading = false;
+
+                        for (const controller of controllers) {
+                            if (readables.get(controller.stream).state === 'readable') {
+                                if (done) {
+                                    controller.close();
+                                } else {
+                                    controller.enqueue(value);
+                                }
+                            }
+                        }
+                    },
+                    (err) => controllers.forEach((controller) => controller.error(err))
+                );
+            }
+
+            for (let i = 0; i < 2; i++) {
+                const branch = {};
+
+                branch.stream = new ReadableStream({
+                    start(controller) {
+                        branch.close = () => controller.close();
+                        branch.enqueue = (chunk) => controller.enqueue(chunk);
+                        branch.error = (err) => controller.error(err);
+                    },
+                    pull,
+                    cancel(reason) {
+                        branch.cancelled = true;
+
+                        if (controllers.every((controller) => controller.cancelled)) {
+                            return reader.cancel(reason);
+                        }
+                    },
+                });
+
+                controllers.push(branch);
+                branches.push(branch.stream);
+            }
+
+            return branches;
+        }
+
+        values(options = {}) {
+            const reader = this.getReader();
+            const preventCancel = Boolean(options.preventCancel);
+
+            return {
+                next() {
+                    return reader.read().then(
+                        (result) => {
+                            if (result.done) {
+                                reader.releaseLock();
+                            }
+
+                            return result;
+                        },
+                        (err) => {
+                            reader.releaseLock();
+                            throw err;
+                        }
+                    );
+                },
+                return(value) {
+                    const cancelled = preventCancel ? Promise.resolve() : reader.cancel(value);
+
+                    reader.releaseLock();
+
+                    return cancelled.then(() => readResult(value, true));
+                },
+                [Symbol.asyncIterator]() {
+                    return this;
+                },
+            };
+        }
+
+        [Symbol.asyncIterator](options) {
+            return this.values(options);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'ReadableStream';
+        }
+    }
+
+    class ReadableStreamDefaultReader {
+        constructor(stream) {
+            if (!(stream instanceof ReadableStream)) {
+                throw new TypeError("Failed to construct 'ReadableStreamDefaultReader': parameter 1 is not of type 'ReadableStream'.");
+            }
+
+            const s = readables.get(stream);
+
+            if (s.reader !== null) {
+                throw new TypeError('ReadableStream is locked');
+            }
+
+            const closed = deferred();
+
+            handled(closed.promise);
+
+            if (s.state === 'closed') {
+                closed.resolve();
+            } else if (s.state === 'errored') {
+                closed.reject(s.storedError);
+            }
+
+            s.reader = this;
+            readers.set(this, { stream, requests: [], closed });
+        }
+
+        get closed() {
+            return readers.get(this).closed.promise;
+        }
+
+        read() {
+            const reader = readers.get(this);
+
+            if (reader.stream === null) {
+                return Promise.reject(new TypeError('The reader has been released'));
+            }
+
+            const s = readables.get(reader.stream);
+
+            s.disturbed = true;
+
+            if (s.state === 'closed') {
+                return Promise.resolve(readResult(undefined, true));
+            }
+
+            if (s.state === 'errored') {
+                return Promise.reject(s.storedError);
+            }
+
+            if (s.queue.length > 0) {
+                const { chunk, size } = s.queue.shift();
+
+                s.queueSize -= size;
+
+                if (s.closeRequested && s.queue.length === 0) {
+                    finalizeClose(s);
+                } else {
+                    pullIfNeeded(s);
+                }
+
+                return Promise.resolve(readResult(chunk, false));
+            }
+
+            const request = deferred();
+
+            reader.requests.push(request);
+            pullIfNeeded(s);
+
+            return request.promise;
+        }
+
+        cancel(reason) {
+            const reader = readers.get(this);
+
+            if (reader.stream === null) {
+                return Promise.reject(new TypeError('The reader has been released'));
+            }
+
+            return cancelReadable(readables.get(reader.stream), reason);
+        }
+
+        releaseLock() {
+            const reader = readers.get(this);
+
+            if (reader.stream === null) {
+                return;
+            }
+
+            const err = new TypeError('The reader has been released');
+
+            reader.requests.splice(0).forEach((request) => request.reject(err));
+
+            if (readables.get(reader.stream).state === 'readable') {
+                reader.closed.reject(err);
+            } else {
+                reader.closed = deferred();
+                reader.closed.reject(err);
+                handled(reader.closed.promise);
+            }
+
+            readables.get(reader.stream).reader = null;
+            reader.stream = null;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'ReadableStreamDefaultReader';
+        }
+    }
+
+    function writableDesiredSize(s) {
+        if (s.state === 'errored') {
+            return null;
+        }
+
+        return s.state === 'closed' ? 0 : s.highWaterMark - s.queueSize;
+    }
+
+    function updateBackpressure(s) {
+        const backpressure = s.state === 'writable' && !s.closeRequest && writableDesiredSize(s) <= 0;
+
+        if (backpressure !== s.backpressure && s.writer !== null) {
+            const writer = writers.get(s.writer);
+
+            if (backpressure) {
+                writer.ready = deferred();
+                handled(writer.ready.promise);
+            } else {
+                writer.ready.resolve();
+            }
+        }
+
+        s.backpressure = backpressure;
+    }
+
+    function errorWritable(s, err) {
+        if (s.state !== 'writable') {
+            return;
+        }
+
+        s.state = 'errored';
+        s.storedError = err;
+
+        const pending = s.inFlight ? s.queue.splice(1) : s.queue.splice(0);
+
+        pending.forEach((request) => request.reject(err));
+
+        if (s.closeRequest !== null && !s.inFlight) {
+            s.closeRequest.reject(err);
+        }
+
+        if (s.writer !== null) {
+            const writer = writers.get(s.writer);
+
+            if (!s.backpressure) {
+                writer.ready = deferred();
+                handled(writer.ready.promise);
+            }
+
+            writer.ready.reject(err);
+            writer.closed.reject(err);
+        }
+    }
+
+    function advanceWritable(s) {
+        if (!s.started || s.inFlight || s.state !== 'writable') {
+            return;
+        }
+
+        if (s.queue.length > 0) {
+            const request = s.queue[0];
+
+            s.inFlight = true;
+
+            invoke(s.sink, 'write', [request.chunk, s.controller]).then(
+                () => {
+                    s.inFlight = false;
+                    s.queue.shift();
+                    s.queueSize -= request.size;
+                    request.resolve();
+
+                    updateBackpressure(s);
+                    advanceWritable(s);
+                },
+                (err) => {
+                    s.inFlight = false;
+                    s.queue.shift();
+                    request.reject(err);
+
+                    errorWritable(s, err);
+                }
+            );
+        } else if (s.closeRequest !== null) {
+            s.inFlight = true;
+
+            invoke(s.sink, 'close', []).then(
+                () => {
+                    s.inFlight = false;
+                    s.state = 'closed';
+                    s.closeRequest.resolve();
+
+                    if (s.writer !== null) {
+                        writers.get(s.writer).closed.resolve();
+                    }
+                },
+                (err) => {
+                    s.inFlight = false;
+                    s.closeRequest.reject(err);
+
+                    errorWritable(s, err);
+                }
+            );
+        }
+    }
+
+    function abortWritable(s, reason) {
+        if (s.state !== 'writable') {
+            return Promise.resolve();
+        }
+
+        errorWritable(s, reason);
+
+        return invoke(s.sink, 'abort', [reason]).then(() => undefined);
+    }
+
+    function closeWritable(s) {
+        if (s.state === 'errored') {
+            return Promise.reject(s.storedError);
+        }
+
+        if (s.state === 'closed' || s.closeRequest !== null) {
+            return Promise.reject(new TypeError('The stream is closing or closed'));
+        }
+
+        const request = deferred();
+
+        s.closeRequest = request;
+
+        if (s.backpressure && s.writer !== null) {
+            writers.get(s.writer).ready.resolve();
+        }
+
+        advanceWritable(s);
+
+        return request.promise;
+    }
+
+    class WritableStreamDefaultController {
+        constructor() {
+            throw new TypeError('Illegal constructor');
+        }
+
+        error(err) {
+            errorWritable(writableControllers.get(this), err);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'WritableStreamDefaultController';
+        }
+    }
+
+    class WritableStream {
+        constructor(sink = {}, strategy = {}) {
+            const { highWaterMark, size } = extractStrategy(strategy, 1);
+            const controller = Object.create(WritableStreamDefaultController.prototype);
+            const s = {
+                state: 'writable',
+                sink,
+                controller,
+                writer: null,
+                queue: [],
+                queueSize: 0,
+                highWaterMark,
+                size,
+                started: false,
+                inFlight: false,
+                closeRequest: null,
+                backpressure: false,
+                storedError: undefined,
+            };
+
+            writables.set(this, s);
+            writableControllers.set(controller, s);
+
+            updateBackpressure(s);
+
+            invoke(sink, 'start', [controller]).then(
+                () => {
+                    s.started = true;
+                    advanceWritable(s);
+                },
+                (err) => errorWritable(s, err)
+            );
+        }
+
+        get locked() {
+            return writables.get(this).writer !== null;
+        }
+
+        abort(reason) {
+            const s = writables.get(this);
+
+            if (s.writer !== null) {
+                return Promise.reject(new TypeError('Cannot abort a stream that already has a writer'));
+            }
+
+            return abortWritable(s, reason);
+        }
+
+        close() {
+            const s = writables.get(this);
+
+            if (s.writer !== null) {
+                return Promise.reject(new TypeError('Cannot close a stream that already has a writer'));
+            }
+
+            return closeWritable(s);
+        }
+
+        getWriter() {
+            return new WritableStreamDefaultWriter(this);
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'WritableStream';
+        }
+    }
+
+    class WritableStreamDefaultWriter {
+        constructor(stream) {
+            if (!(stream instanceof WritableStream)) {
+                throw new TypeError("Failed to construct 'WritableStreamDefaultWriter': parameter 1 is not of type 'WritableStream'.");
+            }
+
+            const s = writables.get(stream);
+
+            if (s.writer !== null) {
+                throw new TypeError('WritableStream is locked');
+            }
+
+            const ready = deferred();
+            const closed = deferred();
+
+            handled(ready.promise);
+            handled(closed.promise);
+
+            if (s.state === 'errored') {
+                ready.reject(s.storedError);
+                closed.reject(s.storedError);
+            } else {
+                if (!s.backpressure || s.closeRequest !== null) {
+                    ready.resolve();
+                }
+
+                if (s.state === 'closed') {
+                    closed.resolve();
+                }
+            }
+
+            s.writer = this;
+            writers.set(this, { stream, ready, closed });
+        }
+
+        get closed() {
+            return writers.get(this).closed.promise;
+        }
+
+        get ready() {
+            return writers.get(this).ready.promise;
+        }
+
+        get desiredSize() {
+            const writer = writers.get(this);
+
+            if (writer.stream === null) {
+                throw new TypeError('The writer has been released');
+            }
+
+            return writableDesiredSize(writables.get(writer.stream));
+        }
+
+        write(chunk) {
+            const writer = writers.get(this);
+
+            if (writer.stream === null) {
+                return Promise.reject(new TypeError('The writer has been released'));
+            }
+
+            const s = writables.get(writer.stream);
+
+            if (s.state === 'errored') {
+                return Promise.reject(s.storedError);
+            }
+
+            if (s.state === 'closed' || s.closeRequest !== null) {
+                return Promise.reject(new TypeError('The stream is closing or closed'));
+            }
+
+            let size;
+
+            try {
+                size = s.size(chunk);
+            } catch (err) {
+                errorWritable(s, err);
+
+                return Promise.reject(err);
+            }
+
+            const request = deferred();
+
+            request.chunk = chunk;
+            request.size = size;
+
+            s.queue.push(request);
+            s.queueSize += size;
+
+            updateBackpressure(s);
+            advanceWritable(s);
+
+            return request.promise;
+        }
+
+        close() {
+            const writer = writers.get(this);
+
+            if (writer.stream === null) {
+                return Promise.reject(new TypeError('The writer has been released'));
+            }
+
+            return closeWritable(writables.get(writer.stream));
+        }
+
+        abort(reason) {
+            const writer = writers.get(this);
+
+            if (writer.stream === null) {
+                return Promise.reject(new TypeError('The writer has been released'));
+            }
+
+            return abortWritable(writables.get(writer.stream), reason);
+        }
+
+        releaseLock() {
+            const writer = writers.get(this);
+
+            if (writer.stream === null) {
+                return;
+            }
+
+            const err = new TypeError('The writer has been released');
+
+            for (const name of ['ready', 'closed']) {
+                writer[name] = deferred();
+                writer[name].reject(err);
+                handled(writer[name].promise);
+            }
+
+            writables.get(writer.stream).writer = null;
+            writer.stream = null;
+        }
+
+        get [Symbol.toStringTag]() {
+            return 'WritableStreamDefaultWriter';
+        }
+    }
+
+    class CountQueuingStrategy {
+        constructor({ highWaterMark }) {
+            Object.defineProperty(this, 'highWaterMark', { value: Number(highWaterMark), enumerable: true });
+        }
+
+        size() {
+            return 1;
+        }
+    }
+
+    class ByteLengthQueuingStrategy {
+        constructor({ highWaterMark }) {
+            Object.defineProperty(this, 'highWaterMark', { value: Number(highWaterMark), enumerable: true });
+        }
+
+        size(chunk) {
+            return chunk.byteLength;
+        }
+    }
+
+    function toBuffer(chunk) {
+        if (chunk instanceof ArrayBuffer) {
+            return chunk;
+        }
+
+        if (ArrayBuffer.isView(chunk)) {
+            return chunk.buffer.slice(chunk.byteOffset, chunk.byteOffset + chunk.byteLength);
+        }
+
+        if (typeof chunk === 'string') {
+            return natives.encode(chunk);
+        }
+
+        throw new TypeError('The chunk must be a string, ArrayBuffer or ArrayBufferView');
+    }
+
+    // create a `ReadableStream` of the host reader, which is read in the background when the chunks requested
+    function $newReadable(reader) {
+        return new ReadableStream({
+            type: 'bytes',
+            pull(controller) {
+                return new Promise((resolve, reject) => natives.read(reader, (err, buf) => {
+                    if (err !== null) {
+                        reject(new Error(err));
+                        return;
+                    }
+
+                    if (buf === null) {
+                        controller.close();
+                    } else {
+                        controller.enqueue(new Uint8Array(buf));
+                    }
+
+                    resolve();
+                }));
+            },
+        });
+    }
+
+    // create a `WritableStream` of the host writer
+    function $newWritable(writer) {
+        return new WritableStream({
+            write(chunk) {
+                return new Promise((resolve, reject) => natives.write(writer, toBuffer(chunk), (err) => {
+                    err !== null ? reject(new Error(err)) : resolve();
+                }));
+            },
+            close() {
+                return new Promise((resolve, reject) => natives.close(writer, (err) => {
+                    err !== null ? reject(new Error(err)) : resolve();
+                }));
+            },
+        });
+    }
+
+    return {
+        ReadableStream,
+        ReadableStreamDefaultReader,
+        ReadableStreamDefaultController,
+        WritableStream,
+        WritableStreamDefaultWriter,
+        WritableStreamDefaultController,
+        CountQueuingStrategy,
+        ByteLengthQueuingStrategy,
+        $newReadable,
+        $newWritable,
+    };
+})
@@ -0,0 +1,253 @@
+use std::sync::{Arc, Mutex};
+
+use failure::Error;
+use futures::{
+    executor::block_on,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
+
+use crate::{ffi, ContextRef, ErrorKind, Local, NewValue, Value, NULL, UNDEFINED};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The host reader holds by a userdata object, which is shared with the background operations.
+#[derive(Clone)]
+struct Reader(Arc<Mutex<Box<dyn AsyncRead + Unpin + Send>>>);
+
+/// The host writer holds by a userdata object, which is shared with the background operations.
+#[derive(Clone)]
+struct Writer(Arc<Mutex<Box<dyn AsyncWrite + Unpin + Send>>>);
+
+/// The chunk read from the host reader, an empty chunk means the end of stream.
+struct Chunk(Vec<u8>);
+
+impl NewValue for Chunk {
+    fn new_value(self, ctxt: &ContextRef) -> ffi::JSValue {
+        let Chunk(mut buf) = self;
+
+        if buf.is_empty() {
+            NULL.new_value(ctxt)
+        } else {
+            ctxt.new_array_buffer_copy(&mut buf).new_value(ctxt)
+        }
+    }
+}
+
+impl ContextRef {
+    /// Create a `ReadableStream` which reads the chunks from the `AsyncRead`.
+    ///
+    /// The reader is polled on a background operation when the script requests a chunk,
+    /// the chunk is enqueued when the runtime polls the completed operations, e.g. in the `EventLoop`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::io::Cursor;
+    /// use qjs::{Context, Eval, EventLoop, Runtime};
+    ///
+    /// let rt = Runtime::new();
+    /// let ctxt = Context::new(&rt);
+    ///
+    /// ctxt.init_web_platform().unwrap();
+    ///
+    /// let stream = ctxt.new_readable_stream(Cursor::new(b"hello".to_vec())).unwrap();
+    ///
+    /// ctxt.global_object().set_property("stream", stream).unwrap();
+    /// ctxt.eval::<_, ()>(
+    ///     "var len; stream.getReader().read().then(({ value }) => { len = value.length })",
+    ///     Eval::GLOBAL,
+    /// )
+    /// .unwrap();
+    ///
+    /// EventLoop::new(&rt).run().unwrap();
+    ///
+    /// assert_eq!(ctxt.eval::<_, i32>("len", Eval::GLOBAL).unwrap(), Some(5));
+    /// ```
+    pub fn new_readable_stream<R: AsyncRead + Unpin + Send + 'static>(
+        &self,
+        reader: R,
+    ) -> Result<Local<Value>, Error> {
+        let new_readable = self.streams_binding("$newReadable")?;
+
+        self.call(
+            &new_readable,
+            None,
+            self.new_userdata(Reader(Arc::new(Mutex::new(Box::new(reader))))),
+        )
+    }
+
+    /// Create a `WritableStream` which writes the chunks to the `AsyncWrite`.
+    ///
+    /// The chunks are written on the background operations, and the writer is closed when the stream closed.
+    pub fn new_writable_stream<W: AsyncWrite + Unpin + Send + 'static>(
+        &self,
+        writer: W,
+    ) -> Result<Local<Value>, Error> {
+        let new_writable = self.streams_binding("$newWritable")?;
+
+        self.call(
+            &new_writable,
+            None,
+            self.new_userdata(Writer(Arc::new(Mutex::new(Box::new(writer))))),
+        )
+    }
+
+    fn streams_binding(&self, name: &str) -> Result<Local<Value>, Error> {
+        self.web_host_binding(name)
+            .ok_or_else(|| format_err!("web platform not initialized"))
+    }
+}
+
+pub fn read(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0]
+        .downcast_mut::<Reader>()
+        .map(|reader| reader.clone())
+        .and_then(|reader| {
+            ctxt.spawn_operation(&[&args[1]], move |_op| {
+                let mut reader = reader.0.lock().unwrap();
+                let mut buf = vec![0; CHUNK_SIZE];
+                let len = block_on(reader.read(&mut buf))
+                    .map_err(|err| format_err!("fail to read stream, {}", err))?;
+
+                buf.truncate(len);
+
+                Ok(Chunk(buf))
+            })
+        });
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+pub fn write(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let buf = match ctxt.get_array_buffer(&args[1]) {
+        Some(buf) => buf.as_ref().to_vec(),
+        None => {
+            return ErrorKind::TypeError("expected ArrayBuffer".into(), None)
+                .new_value(ctxt)
+                .into()
+        }
+    };
+    let res = args[0]
+        .downcast_mut::<Writer>()
+        .map(|writer| writer.clone())
+        .and_then(|writer| {
+            ctxt.spawn_operation(&[&args[2]], move |_op| {
+                let mut writer = writer.0.lock().unwrap();
+
+                block_on(writer.write_all(&buf))
+                    .map_err(|err| format_err!("fail to write stream, {}", err))?;
+
+                Ok(true)
+            })
+        });
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+pub fn close(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let res = args[0]
+        .downcast_mut::<Writer>()
+        .map(|writer| writer.clone())
+        .and_then(|writer| {
+            ctxt.spawn_operation(&[&args[1]], move |_op| {
+                let mut writer = writer.0.lock().unwrap();
+
+                block_on(writer.close())
+                    .map_err(|err| format_err!("fail to close stream, {}", err))?;
+
+                Ok(true)
+            })
+        });
+
+    match res {
+        Ok(_) => UNDEFINED,
+        Err(err) => Err::<Value, _>(err).new_value(ctxt).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Eval, EventLoop, Runtime};
+
+    #[test]
+    fn async_io() {
+        use std::io;
+        use std::pin::Pin;
+        use std::sync::{Arc, Mutex};
+        use std::task::{Context as TaskContext, Poll};
+
+        use futures::io::{AsyncWrite, Cursor};
+
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<(Vec<u8>, bool)>>);
+
+        impl AsyncWrite for Shared {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _cx: &mut TaskContext,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.0.lock().unwrap().0.extend_from_slice(buf);
+
+                Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut TaskContext) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _cx: &mut TaskContext) -> Poll<io::Result<()>> {
+                self.0.lock().unwrap().1 = true;
+
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let _ = pretty_env_logger::try_init();
+
+        let rt = Runtime::new();
+        let ctxt = Context::new(&rt);
+
+        ctxt.init_web_platform().unwrap();
+
+        let sink = Shared::default();
+        let readable = ctxt
+            .new_readable_stream(Cursor::new(b"hello world".to_vec()))
+            .unwrap();
+        let writable = ctxt.new_writable_stream(sink.clone()).unwrap();
+
+        ctxt.global_object()
+            .set_property("readable", readable)
+            .unwrap();
+        ctxt.global_object()
+            .set_property("writable", writable)
+            .unwrap();
+
+        ctxt.eval::<_, ()>(
+            r#"
+var done = false;
+readable.pipeTo(writable).then(() => { done = true });
+"#,
+            Eval::GLOBAL,
+        )
+        .unwrap();
+
+        EventLoop::new(&rt).run().unwrap();
+
+        assert_eq!(
+            ctxt.eval::<_, bool>("done", Eval::GLOBAL).unwrap(),
+            Some(true)
+        );
+
+        let (buf, closed) = &*sink.0.lock().unwrap();
+
+        assert_eq!(buf.as_slice(), b"hello world");
+        assert!(*closed);
+    }
+}
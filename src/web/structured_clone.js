@@ -0,0 +1,114 @@
+(function (natives, { DOMException }) {
+    const TYPED_ARRAYS = [
+        Int8Array, Uint8Array, Uint8ClampedArray, Int16Array, Uint16Array,
+        Int32Array, Uint32Array, Float32Array, Float64Array,
+    ];
+
+    if (typeof BigInt64Array === 'function') {
+        TYPED_ARRAYS.push(BigInt64Array, BigUint64Array);
+    }
+
+    const ERRORS = [Error, EvalError, RangeError, ReferenceError, SyntaxError, TypeError, URIError];
+
+    function fail(value) {
+        throw new DOMException(`${String(value)} could not be cloned.`, 'DataCloneError');
+    }
+
+    function structuredClone(value, options) {
+        if (options && options.transfer && options.transfer.length > 0) {
+            throw new DOMException('Transferring objects is not supported.', 'DataCloneError');
+        }
+
+        const memory = new Map();
+
+        function clone(value) {
+            const type = typeof value;
+
+            if (type === 'symbol' || type === 'function') {
+                fail(type === 'symbol' ? 'Symbol()' : value);
+            }
+            if (value === null || type !== 'object') {
+                return value;
+            }
+            if (memory.has(value)) {
+                return memory.get(value);
+            }
+
+            let copy;
+            let leaf = true;
+
+            if (value instanceof Boolean || value instanceof Number || value instanceof String) {
+                copy = Object(value.valueOf());
+            } else if (typeof BigInt === 'function' && value instanceof BigInt) {
+                copy = Object(value.valueOf());
+            } else if (value instanceof Date) {
+                copy = new Date(value.getTime());
+            } else if (value instanceof RegExp) {
+                copy = new RegExp(value.source, value.flags);
+            } else if (value instanceof ArrayBuffer) {
+                copy = value.slice(0);
+            } else if (value instanceof DataView) {
+                copy = new DataView(clone(value.buffer), value.byteOffset, value.byteLength);
+            } else if (ArrayBuffer.isView(value)) {
+                const ctor = TYPED_ARRAYS.find((ctor) => value instanceof ctor);
+
+                if (!ctor) {
+                    fail(value);
+                }
+
+                copy = new ctor(clone(value.buffer), value.byteOffset, value.length);
+            } else if (value instanceof Map) {
+                copy = new Map();
+                memory.set(value, copy);
+                value.forEach((v, k) => copy.set(clone(k), clone(v)));
+                return copy;
+            } else if (value instanceof Set) {
+                copy = new Set();
+                memory.set(value, copy);
+                value.forEach((v) => copy.add(clone(v)));
+                return copy;
+            } else if (value instanceof Error) {
+                const ctor = ERRORS.find((ctor) => ctor.name === value.name) || Error;
+
+                copy = new ctor(value.message);
+                memory.set(value, copy);
+
+                if ('stack' in value) {
+                    copy.stack = String(value.stack);
+                }
+
+                return copy;
+            } else if (value instanceof Promise || value instanceof WeakMap || value instanceof WeakSet ||
+                       (typeof WeakRef === 'function' && value instanceof WeakRef)) {
+                fail(value);
+            } else if (Array.isArray(value)) {
+                copy = new Array(value.length);
+                leaf = false;
+            } else {
+                copy = {};
+                leaf = false;
+            }
+
+            memory.set(value, copy);
+
+            if (leaf) {
+                return copy;
+            }
+
+            for (const key of Object.keys(value)) {
+                Object.defineProperty(copy, key, {
+                    value: clone(value[key]),
+                    writable: true,
+                    enumerable: true,
+                    configurable: true,
+                });
+            }
+
+            return copy;
+        }
+
+        return clone(value);
+    }
+
+    return { structuredClone };
+})
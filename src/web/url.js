@@ -0,0 +1,203 @@
+(function (natives) {
+    const urls = new WeakMap();
+    const params = new WeakMap();
+
+    function toPairs(init) {
+        if (typeof init === 'object' && init !== null) {
+            if (typeof init[Symbol.iterator] === 'function') {
+                return Array.from(init, (pair) => {
+                    const p = Array.from(pair);
+
+                    if (p.length !== 2) {
+                        throw new TypeError('Each query pair must be an iterable [name, value] tuple');
+                    }
+
+                    return [String(p[0]), String(p[1])];
+                });
+            }
+
+            return Object.keys(init).map((key) => [key, String(init[key])]);
+        }
+
+        init = String(init);
+
+        return natives.parseQuery(init.startsWith('?') ? init.slice(1) : init);
+    }
+
+    // update the query of the associated URL
+    function update(searchParams) {
+        const state = params.get(searchParams);
+
+        if (state.url) {
+            const query = searchParams.toString();
+
+            urls.get(state.url).components = natives.set(state.url.href, 'search', query === '' ? '' : '?' + query);
+        }
+    }
+
+    // replace the components of URL and update the associated search params
+    function replace(url, components) {
+        const state = urls.get(url);
+
+        state.components = components;
+        params.get(state.searchParams).list = natives.parseQuery(components.search.slice(1));
+    }
+
+    class URLSearchParams {
+        constructor(init = '') {
+            params.set(this, { list: toPairs(init), url: null });
+        }
+
+        get size() {
+            return params.get(this).list.length;
+        }
+
+        append(name, value) {
+            params.get(this).list.push([String(name), String(value)]);
+            update(this);
+        }
+
+        delete(name, value) {
+            const state = params.get(this);
+
+            name = String(name);
+            value = value === undefined ? undefined : String(value);
+            state.list = state.list.filter(([k, v]) => k !== name || (value !== undefined && v !== value));
+            update(this);
+        }
+
+        get(name) {
+            name = String(name);
+
+            const pair = params.get(this).list.find(([k]) => k === name);
+
+            return pair ? pair[1] : null;
+        }
+
+        getAll(name) {
+            name = String(name);
+
+            return params.get(this).list.filter(([k]) => k === name).map(([, v]) => v);
+        }
+
+        has(name, value) {
+            name = String(name);
+            value = value === undefined ? undefined : String(value);
+
+            return params.get(this).list.some(([k, v]) => k === name && (value === undefined || v === value));
+        }
+
+        set(name, value) {
+            const state = params.get(this);
+            const idx = state.list.findIndex(([k]) => k === String(name));
+
+            if (idx < 0) {
+                state.list.push([String(name), String(value)]);
+            } else {
+                state.list[idx][1] = String(value);
+                state.list = state.list.filter(([k], i) => i <= idx || k !== String(name));
+            }
+
+            update(this);
+        }
+
+        sort() {
+            const state = params.get(this);
+
+            state.list = state.list
+                .map((pair, i) => [pair, i])
+                .sort(([[a], i], [[b], j]) => (a < b ? -1 : a > b ? 1 : i - j))
+                .map(([pair]) => pair);
+            update(this);
+        }
+
+        forEach(callback, thisArg) {
+            for (const [k, v] of params.get(this).list.slice()) {
+                callback.call(thisArg, v, k, this);
+            }
+        }
+
+        *entries() {
+            yield* params.get(this).list.map(([k, v]) => [k, v]);
+        }
+
+        *keys() {
+            yield* params.get(this).list.map(([k]) => k);
+        }
+
+        *values() {
+            yield* params.get(this).list.map(([, v]) => v);
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+
+        toString() {
+            return natives.serializeQuery(params.get(this).list);
+        }
+    }
+
+    class URL {
+        constructor(url, base) {
+            const components = natives.parse(String(url), base === undefined ? undefined : String(base));
+
+            if (components === null) {
+                throw new TypeError(`Invalid URL: ${url}`);
+            }
+
+            const searchParams = new URLSearchParams(components.search);
+
+            params.get(searchParams).url = this;
+            urls.set(this, { components, searchParams });
+        }
+
+        static canParse(url, base) {
+            return natives.parse(String(url), base === undefined ? undefined : String(base)) !== null;
+        }
+
+        get href() {
+            return urls.get(this).components.href;
+        }
+
+        set href(value) {
+            const components = natives.set(this.href, 'href', String(value));
+
+            if (components === null) {
+                throw new TypeError(`Invalid URL: ${value}`);
+            }
+
+            replace(this, components);
+        }
+
+        get origin() {
+            return urls.get(this).components.origin;
+        }
+
+        get searchParams() {
+            return urls.get(this).searchParams;
+        }
+
+        toString() {
+            return this.href;
+        }
+
+        toJSON() {
+            return this.href;
+        }
+    }
+
+    for (const name of ['protocol', 'username', 'password', 'host', 'hostname', 'port', 'pathname', 'search', 'hash']) {
+        Object.defineProperty(URL.prototype, name, {
+            get() {
+                return urls.get(this).components[name];
+            },
+            set(value) {
+                replace(this, natives.set(this.href, name, String(value)));
+            },
+            configurable: true,
+        });
+    }
+
+    return { URL, URLSearchParams };
+})
@@ -0,0 +1,132 @@
+use url::{form_urlencoded, quirks, Url};
+
+use crate::{ContextRef, Value, NULL};
+
+/// Returns an object contains the components of URL.
+fn components(ctxt: &ContextRef, url: &Url) -> Value {
+    let obj = ctxt.bind(ctxt.new_object());
+
+    for &(name, value) in &[
+        ("href", quirks::href(url)),
+        ("protocol", quirks::protocol(url)),
+        ("username", quirks::username(url)),
+        ("password", quirks::password(url)),
+        ("host", quirks::host(url)),
+        ("hostname", quirks::hostname(url)),
+        ("port", quirks::port(url)),
+        ("pathname", quirks::pathname(url)),
+        ("search", quirks::search(url)),
+        ("hash", quirks::hash(url)),
+    ] {
+        obj.set_property(name, value).expect(name);
+    }
+
+    obj.set_property("origin", quirks::origin(url))
+        .expect("origin");
+
+    obj.into_inner_untracked()
+}
+
+/// Parse the URL with an optional base URL, returns `null` if failed.
+pub fn parse(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let input = ctxt.clone_value(&args[0]).to_string();
+    let base = args
+        .get(1)
+        .filter(|base| !base.is_undefined())
+        .map(|base| ctxt.clone_value(base).to_string());
+
+    let res = match base {
+        Some(base) => Url::parse(&base).and_then(|base| base.join(&input)),
+        None => Url::parse(&input),
+    };
+
+    res.map_or(NULL, |url| components(ctxt, &url))
+}
+
+/// Set a component of the URL, returns `null` if the `href` is invalid.
+pub fn set(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let href = ctxt.clone_value(&args[0]).to_string();
+    let name = ctxt.clone_value(&args[1]).to_string();
+    let value = ctxt.clone_value(&args[2]).to_string();
+
+    let mut url = match Url::parse(&href) {
+        Ok(url) => url,
+        Err(_) => return NULL,
+    };
+
+    // the invalid values are ignored, like the setters of WHATWG URL
+    match name.as_str() {
+        "href" => {
+            if quirks::set_href(&mut url, &value).is_err() {
+                return NULL;
+            }
+        }
+        "protocol" => {
+            let _ = quirks::set_protocol(&mut url, &value);
+        }
+        "username" => {
+            let _ = quirks::set_username(&mut url, &value);
+        }
+        "password" => {
+            let _ = quirks::set_password(&mut url, &value);
+        }
+        "host" => {
+            let _ = quirks::set_host(&mut url, &value);
+        }
+        "hostname" => {
+            let _ = quirks::set_hostname(&mut url, &value);
+        }
+        "port" => {
+            let _ = quirks::set_port(&mut url, &value);
+        }
+        "pathname" => quirks::set_pathname(&mut url, &value),
+        "search" => quirks::set_search(&mut url, &value),
+        "hash" => quirks::set_hash(&mut url, &value),
+        _ => {}
+    }
+
+    components(ctxt, &url)
+}
+
+/// Parse the `application/x-www-form-urlencoded` query to the name-value pairs.
+pub fn parse_query(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let query = ctxt.clone_value(&args[0]).to_string();
+    let pairs = ctxt.bind(ctxt.new_array());
+
+    for (i, (name, value)) in form_urlencoded::parse(query.as_bytes()).enumerate() {
+        let pair = ctxt.bind(ctxt.new_array());
+
+        pair.set_property(0u32, &*name).expect("name");
+        pair.set_property(1u32, &*value).expect("value");
+        pairs.set_property(i as u32, pair).expect("pair");
+    }
+
+    pairs.into_inner_untracked()
+}
+
+/// Serialize the name-value pairs to the `application/x-www-form-urlencoded` query.
+pub fn serialize_query(ctxt: &ContextRef, _this: Option<&Value>, args: &[Value]) -> Value {
+    let pairs = ctxt.clone_value(&args[0]);
+    let len = pairs
+        .get_property("length")
+        .and_then(|len| len.to_int32())
+        .unwrap_or_default();
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+    for i in 0..len as u32 {
+        if let Some(pair) = pairs.get_property(i) {
+            let name = pair
+                .get_property(0u32)
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            let value = pair
+                .get_property(1u32)
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+
+            serializer.append_pair(&name, &value);
+        }
+    }
+
+    ctxt.new_value(serializer.finish())
+}